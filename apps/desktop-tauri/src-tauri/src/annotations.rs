@@ -0,0 +1,228 @@
+//! Annotation wire protocol
+//!
+//! Viewers draw on the shared screen and send their strokes to the host as
+//! JSON text messages on the annotation data channel. Coordinates are
+//! fractions of the shared screen (0.0 to 1.0 from the top left) so they map
+//! onto the host's display whatever the viewer's video size. The host keeps
+//! every viewer's strokes on a board and renders it in an overlay window.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Points kept per stroke; longer strokes are refused
+pub const MAX_STROKE_POINTS: usize = 2000;
+
+/// Strokes kept per user; the oldest go first
+pub const MAX_STROKES_PER_USER: usize = 200;
+
+/// Stroke width bounds, as a fraction of the screen width
+const MIN_STROKE_WIDTH: f32 = 0.001;
+const MAX_STROKE_WIDTH: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationTool {
+    /// Freehand line
+    Pen,
+    /// Wide translucent freehand line
+    Highlight,
+    /// Straight arrow from the first point to the second
+    Arrow,
+}
+
+/// Message on the annotation channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnnotationMessage {
+    /// Finished stroke, arrow or highlight (viewer to host)
+    Stroke {
+        id: String,
+        tool: AnnotationTool,
+        points: Vec<[f32; 2]>,
+        width: f32,
+    },
+    /// Laser pointer position, shown briefly and not kept (viewer to host)
+    Laser { x: f32, y: f32 },
+    /// Undo one of the sender's strokes (viewer to host)
+    Remove { id: String },
+    /// Clear the sender's strokes; from the host, clear every drawing
+    Clear,
+}
+
+/// Stroke as kept on the board
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stroke {
+    pub id: String,
+    pub tool: AnnotationTool,
+    pub points: Vec<[f32; 2]>,
+    pub width: f32,
+}
+
+impl Stroke {
+    /// Validate a stroke from a viewer, bringing points and width into range
+    pub fn new(
+        id: String,
+        tool: AnnotationTool,
+        points: Vec<[f32; 2]>,
+        width: f32,
+    ) -> Result<Self> {
+        if id.is_empty() {
+            return Err(Error::Parse("Stroke has no ID".to_string()));
+        }
+        let valid_count = match tool {
+            AnnotationTool::Arrow => points.len() == 2,
+            AnnotationTool::Pen | AnnotationTool::Highlight => {
+                (1..=MAX_STROKE_POINTS).contains(&points.len())
+            }
+        };
+        if !valid_count {
+            return Err(Error::Parse(format!(
+                "Invalid point count for {:?} stroke: {}",
+                tool,
+                points.len()
+            )));
+        }
+        if points.iter().flatten().any(|c| !c.is_finite()) || !width.is_finite() {
+            return Err(Error::Parse("Stroke has invalid coordinates".to_string()));
+        }
+
+        Ok(Self {
+            id,
+            tool,
+            points: points
+                .into_iter()
+                .map(|[x, y]| [x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)])
+                .collect(),
+            width: width.clamp(MIN_STROKE_WIDTH, MAX_STROKE_WIDTH),
+        })
+    }
+}
+
+/// Everyone's strokes, oldest first per user
+#[derive(Debug, Default)]
+pub struct AnnotationBoard {
+    strokes: HashMap<String, Vec<Stroke>>,
+}
+
+impl AnnotationBoard {
+    /// Add or replace a user's stroke
+    pub fn add(&mut self, user_id: &str, stroke: Stroke) {
+        let strokes = self.strokes.entry(user_id.to_string()).or_default();
+        strokes.retain(|s| s.id != stroke.id);
+        strokes.push(stroke);
+        if strokes.len() > MAX_STROKES_PER_USER {
+            strokes.remove(0);
+        }
+    }
+
+    /// Remove one of a user's strokes, returning whether it was there
+    pub fn remove(&mut self, user_id: &str, stroke_id: &str) -> bool {
+        let Some(strokes) = self.strokes.get_mut(user_id) else {
+            return false;
+        };
+        let before = strokes.len();
+        strokes.retain(|s| s.id != stroke_id);
+        strokes.len() != before
+    }
+
+    /// Remove a user's strokes, or everyone's with `None`
+    pub fn clear(&mut self, user_id: Option<&str>) {
+        match user_id {
+            Some(user_id) => {
+                self.strokes.remove(user_id);
+            }
+            None => self.strokes.clear(),
+        }
+    }
+
+    /// Strokes by user
+    pub fn strokes(&self) -> &HashMap<String, Vec<Stroke>> {
+        &self.strokes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_wire_format() {
+        let msg: AnnotationMessage = serde_json::from_str(
+            r#"{"type":"stroke","id":"s1","tool":"arrow","points":[[0.1,0.2],[0.3,0.4]],"width":0.004}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            msg,
+            AnnotationMessage::Stroke {
+                tool: AnnotationTool::Arrow,
+                ..
+            }
+        ));
+        assert_eq!(
+            serde_json::to_string(&AnnotationMessage::Clear).unwrap(),
+            r#"{"type":"clear"}"#
+        );
+    }
+
+    #[test]
+    fn test_stroke_validation() {
+        let stroke = Stroke::new(
+            "s1".to_string(),
+            AnnotationTool::Pen,
+            vec![[-0.5, 0.5], [0.5, 1.5]],
+            1.0,
+        )
+        .unwrap();
+        assert_eq!(stroke.points, vec![[0.0, 0.5], [0.5, 1.0]]);
+        assert_eq!(stroke.width, MAX_STROKE_WIDTH);
+
+        let arrow = |points| Stroke::new("a".to_string(), AnnotationTool::Arrow, points, 0.01);
+        assert!(arrow(vec![[0.1, 0.1], [0.2, 0.2]]).is_ok());
+        assert!(arrow(vec![[0.1, 0.1]]).is_err());
+        assert!(Stroke::new("p".to_string(), AnnotationTool::Pen, vec![], 0.01).is_err());
+        assert!(Stroke::new(
+            "p".to_string(),
+            AnnotationTool::Pen,
+            vec![[f32::NAN, 0.0]],
+            0.01
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_board_per_user() {
+        let stroke = |id: &str| {
+            Stroke::new(id.to_string(), AnnotationTool::Pen, vec![[0.5, 0.5]], 0.01).unwrap()
+        };
+        let mut board = AnnotationBoard::default();
+        board.add("alice", stroke("1"));
+        board.add("alice", stroke("2"));
+        board.add("bob", stroke("1"));
+
+        assert!(!board.remove("bob", "2"));
+        assert!(board.remove("alice", "1"));
+        assert_eq!(board.strokes()["alice"].len(), 1);
+
+        board.clear(Some("alice"));
+        assert!(!board.strokes().contains_key("alice"));
+        assert!(board.strokes().contains_key("bob"));
+        board.clear(None);
+        assert!(board.strokes().is_empty());
+    }
+
+    #[test]
+    fn test_board_drops_oldest() {
+        let mut board = AnnotationBoard::default();
+        for i in 0..=MAX_STROKES_PER_USER {
+            let stroke =
+                Stroke::new(i.to_string(), AnnotationTool::Pen, vec![[0.5, 0.5]], 0.01).unwrap();
+            board.add("alice", stroke);
+        }
+        let strokes = &board.strokes()["alice"];
+        assert_eq!(strokes.len(), MAX_STROKES_PER_USER);
+        assert_eq!(strokes[0].id, "1");
+    }
+}
@@ -0,0 +1,162 @@
+//! App announcements
+//!
+//! Maintenance windows and "your version is too old" notices come from the
+//! `app_announcements` table. Each client keeps the ones meant for its
+//! version and platform. Dismissals are saved in the config directory so a
+//! dismissed notice stays gone across launches.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::supabase::AnnouncementRow;
+use crate::Result;
+
+/// File in the app config directory holding dismissed announcement IDs
+pub const DISMISSED_FILE: &str = "dismissed_announcements.json";
+
+/// Whether an announcement targets this app version and platform
+pub fn applies_to(row: &AnnouncementRow, app_version: &semver::Version, platform: &str) -> bool {
+    let platform_matches = row.platforms.as_ref().is_none_or(|platforms| {
+        platforms.is_empty() || platforms.iter().any(|p| p.eq_ignore_ascii_case(platform))
+    });
+
+    let version_matches = match row.below_version.as_deref() {
+        None => true,
+        Some(below) => match semver::Version::parse(below.trim().trim_start_matches('v')) {
+            Ok(below) => *app_version < below,
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring announcement {} with invalid version {}: {}",
+                    row.id,
+                    below,
+                    e
+                );
+                false
+            }
+        },
+    };
+
+    platform_matches && version_matches
+}
+
+/// Announcements the user dismissed
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DismissedAnnouncements {
+    ids: BTreeSet<String>,
+}
+
+impl DismissedAnnouncements {
+    /// Dismissals saved by a previous launch; none if the file is missing or
+    /// unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(json) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring dismissed announcements file: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Returns `false` if it was already dismissed
+    pub fn insert(&mut self, id: &str) -> bool {
+        self.ids.insert(id.to_string())
+    }
+
+    /// Forget dismissals of announcements no longer served, returning whether
+    /// any were dropped
+    pub fn retain_active(&mut self, active: &[AnnouncementRow]) -> bool {
+        let before = self.ids.len();
+        self.ids.retain(|id| active.iter().any(|a| &a.id == id));
+        self.ids.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(below_version: Option<&str>, platforms: Option<Vec<&str>>) -> AnnouncementRow {
+        AnnouncementRow {
+            id: "a1".to_string(),
+            kind: "deprecation".to_string(),
+            severity: "warning".to_string(),
+            title: "Update required".to_string(),
+            message: "This version will stop working soon".to_string(),
+            url: None,
+            starts_at: "2026-03-01T00:00:00+00:00".to_string(),
+            ends_at: None,
+            maintenance_starts_at: None,
+            maintenance_ends_at: None,
+            below_version: below_version.map(str::to_string),
+            platforms: platforms.map(|p| p.into_iter().map(str::to_string).collect()),
+            dismissible: true,
+        }
+    }
+
+    #[test]
+    fn test_version_targeting() {
+        let version = semver::Version::new(1, 3, 2);
+        assert!(applies_to(&announcement(None, None), &version, "macos"));
+        assert!(applies_to(
+            &announcement(Some("1.4.0"), None),
+            &version,
+            "macos"
+        ));
+        assert!(applies_to(
+            &announcement(Some("v1.3.3"), None),
+            &version,
+            "macos"
+        ));
+        assert!(!applies_to(
+            &announcement(Some("1.3.2"), None),
+            &version,
+            "macos"
+        ));
+        assert!(!applies_to(
+            &announcement(Some("not a version"), None),
+            &version,
+            "macos"
+        ));
+    }
+
+    #[test]
+    fn test_platform_targeting() {
+        let version = semver::Version::new(1, 0, 0);
+        let windows_only = announcement(None, Some(vec!["windows"]));
+        assert!(applies_to(&windows_only, &version, "windows"));
+        assert!(!applies_to(&windows_only, &version, "linux"));
+        assert!(applies_to(
+            &announcement(None, Some(vec![])),
+            &version,
+            "linux"
+        ));
+    }
+
+    #[test]
+    fn test_dismissals_pruned_to_active() {
+        let mut dismissed = DismissedAnnouncements::default();
+        assert!(dismissed.insert("a1"));
+        assert!(!dismissed.insert("a1"));
+        assert!(dismissed.insert("gone"));
+
+        assert!(dismissed.retain_active(&[announcement(None, None)]));
+        assert!(dismissed.contains("a1"));
+        assert!(!dismissed.contains("gone"));
+        assert!(!dismissed.retain_active(&[announcement(None, None)]));
+    }
+}
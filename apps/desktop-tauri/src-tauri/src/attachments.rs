@@ -0,0 +1,186 @@
+//! Chat and meeting attachments
+//!
+//! Files sent in chat are uploaded to the private `chat-attachments` bucket
+//! under `<conversation_id>/<sender_id>/`, which the bucket policies require,
+//! and described on the message row. Files attached to meetings follow the
+//! same layout in the `meeting-attachments` bucket, under
+//! `<meeting_id>/<uploader_id>/`. Downloads are cached by object path in the
+//! app cache directory, so opening an attachment twice reads it from disk.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use crate::file_transfer::sanitize_file_name;
+use crate::supabase::SupabaseClient;
+use crate::{Error, Result};
+
+/// Storage bucket holding chat attachments
+pub const ATTACHMENT_BUCKET: &str = "chat-attachments";
+
+/// Storage bucket holding meeting attachments
+pub const MEETING_ATTACHMENT_BUCKET: &str = "meeting-attachments";
+
+/// Largest file that can be attached
+pub const MAX_ATTACHMENT_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Files per message
+pub const MAX_ATTACHMENTS: usize = 10;
+
+/// Directory under the app cache directory holding downloaded attachments
+pub const CACHE_DIR: &str = "attachments";
+
+/// Download progress is reported each time this many bytes arrived
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+/// MIME type of a file from its extension
+pub fn content_type(file_name: &str) -> &'static str {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "json" => "application/json",
+        "txt" | "log" | "md" => "text/plain",
+        "csv" => "text/csv",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" | "opus" => "audio/ogg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Whether chat can show an attachment inline
+pub fn is_image(content_type: &str) -> bool {
+    // SVG can carry scripts, so it is offered as a download only
+    content_type.starts_with("image/") && content_type != "image/svg+xml"
+}
+
+/// Object path of a new attachment
+pub fn storage_path(conversation_id: &str, sender_id: &str, file_name: &str) -> String {
+    format!(
+        "{}/{}/{}-{}",
+        conversation_id,
+        sender_id,
+        uuid::Uuid::new_v4(),
+        sanitize_file_name(file_name).replace(['/', '\\', '#', '%'], "_")
+    )
+}
+
+/// Where a downloaded attachment is cached, keyed by its object path so
+/// files with the same name don't collide
+pub fn cache_path(cache_dir: &Path, storage_path: &str, file_name: &str) -> PathBuf {
+    let key: String = Sha256::digest(storage_path.as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    cache_dir
+        .join(CACHE_DIR)
+        .join(key)
+        .join(sanitize_file_name(file_name))
+}
+
+/// Download an attachment into the cache, or find it there already
+///
+/// `on_progress(received, total)` is called as the file arrives. Returns
+/// the local path of the file.
+pub async fn download_to_cache(
+    supabase: &SupabaseClient,
+    bucket: &str,
+    storage_path: &str,
+    file_name: &str,
+    size: u64,
+    cache_dir: &Path,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<PathBuf> {
+    let path = cache_path(cache_dir, storage_path, file_name);
+    if let Ok(metadata) = tokio::fs::metadata(&path).await {
+        if metadata.len() == size {
+            tracing::debug!("Cache hit for attachment {}", storage_path);
+            return Ok(path);
+        }
+    }
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    let mut response = supabase
+        .download_storage_object(bucket, storage_path)
+        .await?;
+    let total = response.content_length().unwrap_or(size);
+
+    // Written aside and renamed once complete, so a dropped download never
+    // passes for a cached file
+    let partial = path.with_extension("part");
+    let mut file = tokio::fs::File::create(&partial).await?;
+    let mut received = 0u64;
+    let mut last_reported = 0u64;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::Network(e.to_string()))?
+    {
+        file.write_all(&chunk).await?;
+        received += chunk.len() as u64;
+        if received - last_reported >= PROGRESS_STEP || received == total {
+            last_reported = received;
+            on_progress(received, total);
+        }
+    }
+    file.flush().await?;
+    drop(file);
+    tokio::fs::rename(&partial, &path).await?;
+
+    tracing::info!("Attachment {} downloaded", storage_path);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_by_extension() {
+        assert_eq!(content_type("Screenshot.PNG"), "image/png");
+        assert_eq!(content_type("notes.md"), "text/plain");
+        assert_eq!(content_type("voice.ogg"), "audio/ogg");
+        assert_eq!(content_type("archive"), "application/octet-stream");
+        assert!(is_image(content_type("photo.jpeg")));
+        assert!(!is_image(content_type("logo.svg")));
+        assert!(!is_image(content_type("report.pdf")));
+    }
+
+    #[test]
+    fn test_storage_path_under_sender_folder() {
+        let path = storage_path("conv-1", "user-1", "../my report#1.pdf");
+        let parts: Vec<&str> = path.split('/').collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], "conv-1");
+        assert_eq!(parts[1], "user-1");
+        assert!(parts[2].ends_with("-my report_1.pdf"));
+    }
+
+    #[test]
+    fn test_cache_path_per_object() {
+        let dir = Path::new("/cache");
+        let a = cache_path(dir, "conv/user/1-a.txt", "a.txt");
+        let b = cache_path(dir, "conv/user/2-a.txt", "a.txt");
+        assert_ne!(a, b);
+        assert!(a.starts_with("/cache/attachments"));
+        assert!(a.ends_with("a.txt"));
+        assert_eq!(a, cache_path(dir, "conv/user/1-a.txt", "a.txt"));
+    }
+}
@@ -0,0 +1,511 @@
+//! Voice chat audio
+//!
+//! The host's microphone is captured, encoded to Opus in 20 ms frames and
+//! written to an audio track shared by every viewer's connection, like the
+//! video. Each connection also receives the viewer's microphone; those tracks
+//! are decoded and mixed into the default output device. Speaking activity
+//! is detected from the level of each frame, on both sides.
+//!
+//! Audio devices are driven from their own threads: their streams cannot be
+//! moved across threads.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use webrtc::api::media_engine::MIME_TYPE_OPUS;
+use webrtc::media::Sample;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+
+use crate::{Error, Result};
+
+/// Opus always runs at 48 kHz; voice is sent mono
+pub const SAMPLE_RATE: u32 = 48_000;
+
+/// Samples in a 20 ms frame
+pub const FRAME_SAMPLES: usize = 960;
+
+pub const FRAME_DURATION: Duration = Duration::from_millis(20);
+
+/// Largest Opus frame (120 ms) a decoder may produce
+const MAX_DECODED_SAMPLES: usize = 5760;
+
+/// Level (RMS, 0-1) above which a frame counts as speech
+const SPEAKING_THRESHOLD: f32 = 0.02;
+
+/// Quiet frames before someone stops counting as speaking (300 ms)
+const SPEAKING_HANGOVER_FRAMES: u32 = 15;
+
+/// Audio buffered per participant before the oldest is dropped (500 ms at
+/// 48 kHz), so a stalled output doesn't build up latency
+const MAX_BUFFERED_SAMPLES: usize = 24_000;
+
+/// Interval at which device threads check whether to stop
+pub const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Speaking activity from the level of successive frames
+#[derive(Debug, Default)]
+pub struct SpeakingDetector {
+    speaking: bool,
+    quiet_frames: u32,
+}
+
+impl SpeakingDetector {
+    /// Feed a frame; returns the new state when it changes
+    pub fn update(&mut self, frame: &[f32]) -> Option<bool> {
+        if frame_level(frame) >= SPEAKING_THRESHOLD {
+            self.quiet_frames = 0;
+            if !self.speaking {
+                self.speaking = true;
+                return Some(true);
+            }
+        } else if self.speaking {
+            self.quiet_frames += 1;
+            if self.quiet_frames >= SPEAKING_HANGOVER_FRAMES {
+                self.speaking = false;
+                return Some(false);
+            }
+        }
+        None
+    }
+
+    /// Stop counting as speaking right away, e.g. when muted
+    pub fn reset(&mut self) -> Option<bool> {
+        self.quiet_frames = 0;
+        std::mem::take(&mut self.speaking).then_some(false)
+    }
+}
+
+/// Root mean square of a frame of samples in -1..1
+pub fn frame_level(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+/// Linear resampling of mono samples
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let len = (samples.len() as u64 * to_rate as u64 / from_rate as u64) as usize;
+    let step = from_rate as f64 / to_rate as f64;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = samples[index.min(samples.len() - 1)];
+            let b = samples[(index + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Microphone capture/encode loop feeding the shared Opus audio track
+pub struct AudioPipeline {
+    audio_track: Arc<TrackLocalStaticSample>,
+    muted: Arc<AtomicBool>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl Default for AudioPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioPipeline {
+    pub fn new() -> Self {
+        let audio_track = Arc::new(TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_OPUS.to_string(),
+                clock_rate: SAMPLE_RATE,
+                channels: 1,
+                ..Default::default()
+            },
+            "audio".to_string(),
+            "squadx-voice".to_string(),
+        ));
+
+        Self {
+            audio_track,
+            muted: Arc::new(AtomicBool::new(false)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Track every viewer's connection sends
+    pub fn track(&self) -> Arc<TrackLocalStaticSample> {
+        self.audio_track.clone()
+    }
+
+    /// Start capturing the default microphone; `on_speaking` is called as
+    /// the local user starts and stops speaking
+    pub fn start<F>(&self, on_speaking: F)
+    where
+        F: Fn(bool) + Send + 'static,
+    {
+        let track = self.audio_track.clone();
+        let muted = self.muted.clone();
+        let stop_flag = self.stop_flag.clone();
+        let runtime = tokio::runtime::Handle::current();
+
+        std::thread::spawn(move || {
+            if let Err(e) = run_capture(&track, &muted, &stop_flag, &runtime, on_speaking) {
+                tracing::error!("Microphone capture failed: {}", e);
+            }
+            tracing::info!("Microphone capture stopped");
+        });
+    }
+
+    /// Stop sending the microphone, or send it again
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+    }
+
+    /// Stop the capture loop
+    pub fn stop(&self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+    }
+}
+
+fn run_capture<F: Fn(bool)>(
+    track: &TrackLocalStaticSample,
+    muted: &AtomicBool,
+    stop_flag: &AtomicBool,
+    runtime: &tokio::runtime::Handle,
+    on_speaking: F,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let (_stream, input_rate) = open_microphone(tx)?;
+
+    let mut encoder =
+        opus::Encoder::new(SAMPLE_RATE, opus::Channels::Mono, opus::Application::Voip)
+            .map_err(|e| Error::Capture(format!("Failed to create Opus encoder: {}", e)))?;
+    let mut detector = SpeakingDetector::default();
+    let mut pending: Vec<f32> = Vec::new();
+    let mut packet = vec![0u8; 4000];
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        let samples = match rx.recv_timeout(STOP_POLL_INTERVAL) {
+            Ok(samples) => samples,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        pending.extend(resample(&samples, input_rate, SAMPLE_RATE));
+
+        while pending.len() >= FRAME_SAMPLES {
+            let frame: Vec<f32> = pending.drain(..FRAME_SAMPLES).collect();
+            if muted.load(Ordering::Relaxed) {
+                if let Some(speaking) = detector.reset() {
+                    on_speaking(speaking);
+                }
+                continue;
+            }
+            if let Some(speaking) = detector.update(&frame) {
+                on_speaking(speaking);
+            }
+
+            let len = match encoder.encode_float(&frame, &mut packet) {
+                Ok(len) => len,
+                Err(e) => {
+                    tracing::warn!("Failed to encode audio frame: {}", e);
+                    continue;
+                }
+            };
+            let sample = Sample {
+                data: bytes::Bytes::copy_from_slice(&packet[..len]),
+                duration: FRAME_DURATION,
+                ..Default::default()
+            };
+            if let Err(e) = runtime.block_on(track.write_sample(&sample)) {
+                tracing::warn!("Failed to write audio sample: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start the default microphone, sending each buffer, downmixed to mono, to
+/// `tx`; returns the stream, which must be kept alive, and its sample rate
+pub fn open_microphone(tx: mpsc::Sender<Vec<f32>>) -> Result<(cpal::Stream, u32)> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| Error::Capture("No microphone found".to_string()))?;
+    let config = device
+        .default_input_config()
+        .map_err(|e| Error::Capture(e.to_string()))?;
+    let input_rate = config.sample_rate().0;
+
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => build_input::<f32>(&device, &config.into(), tx)?,
+        SampleFormat::I16 => build_input::<i16>(&device, &config.into(), tx)?,
+        SampleFormat::U16 => build_input::<u16>(&device, &config.into(), tx)?,
+        format => {
+            return Err(Error::Capture(format!(
+                "Unsupported microphone sample format: {:?}",
+                format
+            )))
+        }
+    };
+    stream.play().map_err(|e| Error::Capture(e.to_string()))?;
+    Ok((stream, input_rate))
+}
+
+/// Input stream sending each buffer, downmixed to mono, to `tx`
+fn build_input<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    tx: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame.iter().map(|s| f32::from_sample(*s)).sum::<f32>() / channels as f32
+                    })
+                    .collect();
+                let _ = tx.send(mono);
+            },
+            |e| tracing::warn!("Microphone stream error: {}", e),
+            None,
+        )
+        .map_err(|e| Error::Capture(e.to_string()))
+}
+
+/// Decoder of one viewer's voice track, with their speaking activity
+pub struct VoiceDecoder {
+    decoder: opus::Decoder,
+    detector: SpeakingDetector,
+    buffer: Vec<f32>,
+}
+
+impl VoiceDecoder {
+    pub fn new() -> Result<Self> {
+        let decoder = opus::Decoder::new(SAMPLE_RATE, opus::Channels::Mono)
+            .map_err(|e| Error::WebRtc(format!("Failed to create Opus decoder: {}", e)))?;
+        Ok(Self {
+            decoder,
+            detector: SpeakingDetector::default(),
+            buffer: vec![0.0; MAX_DECODED_SAMPLES],
+        })
+    }
+
+    /// Decode an Opus packet; returns the samples and the speaking state
+    /// when it changes
+    pub fn decode(&mut self, packet: &[u8]) -> Result<(&[f32], Option<bool>)> {
+        let len = self
+            .decoder
+            .decode_float(packet, &mut self.buffer, false)
+            .map_err(|e| Error::WebRtc(format!("Failed to decode audio: {}", e)))?;
+        let samples = &self.buffer[..len];
+        let speaking = self.detector.update(samples);
+        Ok((samples, speaking))
+    }
+
+    /// Stop counting as speaking, e.g. when the track ends
+    pub fn reset(&mut self) -> Option<bool> {
+        self.detector.reset()
+    }
+}
+
+/// Mixer of the viewers' voices into the default output device
+#[derive(Clone)]
+pub struct AudioPlayback {
+    buffers: Arc<Mutex<HashMap<String, VecDeque<f32>>>>,
+    /// Participants the host muted, whose audio is dropped
+    muted: Arc<Mutex<HashSet<String>>>,
+    output_rate: Arc<AtomicU32>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl AudioPlayback {
+    pub fn new(muted: HashSet<String>) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(HashMap::new())),
+            muted: Arc::new(Mutex::new(muted)),
+            output_rate: Arc::new(AtomicU32::new(SAMPLE_RATE)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Start playing on the default output device
+    pub fn start(&self) {
+        let playback = self.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = playback.run_output() {
+                tracing::error!("Voice playback failed: {}", e);
+            }
+            tracing::info!("Voice playback stopped");
+        });
+    }
+
+    fn run_output(&self) -> Result<()> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| Error::Capture("No audio output device found".to_string()))?;
+        let config = device
+            .default_output_config()
+            .map_err(|e| Error::Capture(e.to_string()))?;
+        self.output_rate
+            .store(config.sample_rate().0, Ordering::Relaxed);
+
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => self.build_output::<f32>(&device, &config.into())?,
+            SampleFormat::I16 => self.build_output::<i16>(&device, &config.into())?,
+            SampleFormat::U16 => self.build_output::<u16>(&device, &config.into())?,
+            format => {
+                return Err(Error::Capture(format!(
+                    "Unsupported output sample format: {:?}",
+                    format
+                )))
+            }
+        };
+        stream.play().map_err(|e| Error::Capture(e.to_string()))?;
+
+        while !self.stop_flag.load(Ordering::Relaxed) {
+            std::thread::sleep(STOP_POLL_INTERVAL);
+        }
+        Ok(())
+    }
+
+    fn build_output<T>(
+        &self,
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+    ) -> Result<cpal::Stream>
+    where
+        T: SizedSample + FromSample<f32>,
+    {
+        let channels = config.channels.max(1) as usize;
+        let buffers = self.buffers.clone();
+        device
+            .build_output_stream(
+                config,
+                move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                    let mut buffers = buffers.lock().unwrap_or_else(|e| e.into_inner());
+                    for frame in data.chunks_mut(channels) {
+                        let mixed: f32 = buffers
+                            .values_mut()
+                            .filter_map(|buffer| buffer.pop_front())
+                            .sum();
+                        let sample = T::from_sample(mixed.clamp(-1.0, 1.0));
+                        frame.fill(sample);
+                    }
+                },
+                |e| tracing::warn!("Audio output stream error: {}", e),
+                None,
+            )
+            .map_err(|e| Error::Capture(e.to_string()))
+    }
+
+    /// Queue decoded 48 kHz samples of a participant for playback
+    pub fn push(&self, user_id: &str, samples: &[f32]) {
+        if self
+            .muted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(user_id)
+        {
+            return;
+        }
+
+        let samples = resample(
+            samples,
+            SAMPLE_RATE,
+            self.output_rate.load(Ordering::Relaxed),
+        );
+        let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+        let buffer = buffers.entry(user_id.to_string()).or_default();
+        buffer.extend(samples);
+        let excess = buffer.len().saturating_sub(MAX_BUFFERED_SAMPLES);
+        buffer.drain(..excess);
+    }
+
+    /// Drop a participant's audio, or play it again
+    pub fn set_muted(&self, user_id: &str, muted: bool) {
+        let mut muted_users = self.muted.lock().unwrap_or_else(|e| e.into_inner());
+        if muted {
+            muted_users.insert(user_id.to_string());
+            self.forget(user_id);
+        } else {
+            muted_users.remove(user_id);
+        }
+    }
+
+    /// Drop whatever is left of a participant's audio
+    pub fn forget(&self, user_id: &str) {
+        self.buffers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(user_id);
+    }
+
+    /// Stop playing
+    pub fn stop(&self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude: f32) -> Vec<f32> {
+        (0..FRAME_SAMPLES)
+            .map(|i| amplitude * (i as f32 * 0.1).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_speaking_detection() {
+        let mut detector = SpeakingDetector::default();
+        assert_eq!(detector.update(&tone(0.0)), None);
+        assert_eq!(detector.update(&tone(0.5)), Some(true));
+        assert_eq!(detector.update(&tone(0.5)), None);
+
+        // Short pauses don't end speech
+        for _ in 0..SPEAKING_HANGOVER_FRAMES - 1 {
+            assert_eq!(detector.update(&tone(0.0)), None);
+        }
+        assert_eq!(detector.update(&tone(0.0)), Some(false));
+    }
+
+    #[test]
+    fn test_speaking_reset() {
+        let mut detector = SpeakingDetector::default();
+        assert_eq!(detector.reset(), None);
+        detector.update(&tone(0.5));
+        assert_eq!(detector.reset(), Some(false));
+    }
+
+    #[test]
+    fn test_frame_level() {
+        assert_eq!(frame_level(&[]), 0.0);
+        assert_eq!(frame_level(&[0.5, -0.5]), 0.5);
+    }
+
+    #[test]
+    fn test_resample() {
+        let samples = vec![0.0, 1.0, 0.0, -1.0];
+        assert_eq!(resample(&samples, 48_000, 48_000), samples);
+        assert_eq!(resample(&samples, 48_000, 24_000), vec![0.0, 0.0]);
+        assert_eq!(resample(&samples, 24_000, 48_000).len(), 8);
+        assert_eq!(resample(&tone(0.5), 48_000, 44_100).len(), 882);
+    }
+}
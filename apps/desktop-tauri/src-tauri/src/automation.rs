@@ -0,0 +1,335 @@
+//! Local automation API
+//!
+//! A small HTTP API on the loopback interface, so scripts and Stream Deck
+//! style buttons can drive the app:
+//!
+//! | Request              | Action                  | Body                      |
+//! |----------------------|-------------------------|---------------------------|
+//! | `POST /v1/sessions`  | start a session         | `create_session` options  |
+//! | `PUT /v1/presence`   | set the presence status | `{"status": "away"}`      |
+//! | `POST /v1/meetings`  | create a meeting        | `create_meeting` params   |
+//!
+//! Every request carries the token kept in the keychain as a bearer token.
+//! On top of that, each action needs the user's permission, asked on every
+//! request or remembered per action.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Loopback port the API listens on
+pub const PORT: u16 = 47615;
+
+/// File in the app config directory holding the settings
+pub const SETTINGS_FILE: &str = "automation.json";
+
+/// Largest request accepted, head and body together
+pub const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// Random bytes of a token
+const TOKEN_BYTES: usize = 32;
+
+/// Something a script can ask the app to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationAction {
+    StartSession,
+    SetPresence,
+    CreateMeeting,
+}
+
+impl AutomationAction {
+    /// Action of a request, `None` for unknown routes
+    pub fn route(method: &str, path: &str) -> Option<Self> {
+        match (method, path.split('?').next().unwrap_or(path)) {
+            ("POST", "/v1/sessions") => Some(Self::StartSession),
+            ("PUT", "/v1/presence") => Some(Self::SetPresence),
+            ("POST", "/v1/meetings") => Some(Self::CreateMeeting),
+            _ => None,
+        }
+    }
+
+    /// What the action does, for the permission prompt
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::StartSession => "Start a session",
+            Self::SetPresence => "Change your presence status",
+            Self::CreateMeeting => "Create a meeting",
+        }
+    }
+}
+
+/// Whether an action may run without asking
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationPermission {
+    #[default]
+    Ask,
+    Allow,
+    Deny,
+}
+
+/// Settings kept across restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AutomationSettings {
+    pub enabled: bool,
+    /// Remembered answers, actions left out are asked every time
+    #[serde(default)]
+    pub permissions: HashMap<AutomationAction, AutomationPermission>,
+}
+
+impl AutomationSettings {
+    pub fn permission(&self, action: AutomationAction) -> AutomationPermission {
+        self.permissions.get(&action).copied().unwrap_or_default()
+    }
+
+    /// Remember an answer, or forget it with `Ask`
+    pub fn set_permission(&mut self, action: AutomationAction, permission: AutomationPermission) {
+        if permission == AutomationPermission::Ask {
+            self.permissions.remove(&action);
+        } else {
+            self.permissions.insert(action, permission);
+        }
+    }
+
+    /// Read the settings saved by `save`, disabled when there are none
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// New random API token
+pub fn generate_token() -> String {
+    (0..TOKEN_BYTES)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect()
+}
+
+/// Whether a presented token is the API token, in constant time
+pub fn token_matches(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Request line and headers of an API request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestHead {
+    pub method: String,
+    pub path: String,
+    /// Bearer token of the `Authorization` header
+    pub token: Option<String>,
+    pub content_length: usize,
+}
+
+/// Parse the head of an HTTP/1.1 request, up to the blank line
+pub fn parse_head(head: &str) -> Result<RequestHead> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(path), Some(version)) = (
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+    ) else {
+        return Err(Error::Parse("Malformed request line".to_string()));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(Error::Parse(format!(
+            "Unsupported HTTP version: {}",
+            version
+        )));
+    }
+
+    let mut token = None;
+    let mut content_length = 0;
+    for line in lines.filter(|line| !line.is_empty()) {
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Error::Parse(format!("Malformed header: {}", line)));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            token = value
+                .strip_prefix("Bearer ")
+                .map(|token| token.trim().to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .parse()
+                .map_err(|_| Error::Parse(format!("Invalid Content-Length: {}", value)))?;
+        }
+    }
+
+    Ok(RequestHead {
+        method: method.to_string(),
+        path: path.to_string(),
+        token,
+        content_length,
+    })
+}
+
+/// End of a request whose body starts at `body_start`, or `None` when the
+/// client-supplied `content_length` would take it past `MAX_REQUEST_BYTES`
+pub fn request_end(body_start: usize, content_length: usize) -> Option<usize> {
+    if content_length > MAX_REQUEST_BYTES {
+        return None;
+    }
+    body_start
+        .checked_add(content_length)
+        .filter(|end| *end <= MAX_REQUEST_BYTES)
+}
+
+/// HTTP status answering a failed action
+pub fn error_status(error: &Error) -> u16 {
+    match error {
+        Error::Input(_) | Error::Parse(_) | Error::Serde(_) => 400,
+        Error::Auth(_) => 401,
+        Error::Banned => 403,
+        Error::SessionLocked => 423,
+        Error::NotFound(_) => 404,
+        Error::TooManyAttempts { .. } => 429,
+        _ => 500,
+    }
+}
+
+/// Full HTTP/1.1 response with a JSON body
+pub fn response(status: u16, body: &serde_json::Value) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        423 => "Locked",
+        429 => "Too Many Requests",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(
+            AutomationAction::route("POST", "/v1/sessions"),
+            Some(AutomationAction::StartSession)
+        );
+        assert_eq!(
+            AutomationAction::route("PUT", "/v1/presence?source=deck"),
+            Some(AutomationAction::SetPresence)
+        );
+        assert_eq!(AutomationAction::route("GET", "/v1/presence"), None);
+        assert_eq!(AutomationAction::route("POST", "/v1/unknown"), None);
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let head = parse_head(
+            "PUT /v1/presence HTTP/1.1\r\nHost: 127.0.0.1\r\nauthorization: Bearer abc\r\nContent-Length: 19\r\n",
+        )
+        .unwrap();
+        assert_eq!(head.method, "PUT");
+        assert_eq!(head.path, "/v1/presence");
+        assert_eq!(head.token.as_deref(), Some("abc"));
+        assert_eq!(head.content_length, 19);
+
+        let head = parse_head("POST /v1/sessions HTTP/1.0\r\n").unwrap();
+        assert_eq!(head.token, None);
+        assert_eq!(head.content_length, 0);
+
+        assert!(parse_head("garbage").is_err());
+        assert!(parse_head("GET / SPDY/3\r\n").is_err());
+        assert!(parse_head("GET / HTTP/1.1\r\nContent-Length: lots\r\n").is_err());
+    }
+
+    #[test]
+    fn test_request_end() {
+        assert_eq!(request_end(100, 19), Some(119));
+        assert_eq!(
+            request_end(100, MAX_REQUEST_BYTES - 100),
+            Some(MAX_REQUEST_BYTES)
+        );
+        assert_eq!(request_end(100, MAX_REQUEST_BYTES), None);
+        assert_eq!(request_end(100, usize::MAX), None);
+        assert_eq!(request_end(usize::MAX, 1), None);
+    }
+
+    #[test]
+    fn test_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert_ne!(token, generate_token());
+        assert!(token_matches(&token, &token.clone()));
+        assert!(!token_matches(&token, &token[1..]));
+        assert!(!token_matches(&token, &generate_token()));
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("squadx-automation-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SETTINGS_FILE);
+        assert_eq!(
+            AutomationSettings::load(&path),
+            AutomationSettings::default()
+        );
+
+        let mut settings = AutomationSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        settings.set_permission(AutomationAction::SetPresence, AutomationPermission::Allow);
+        settings.set_permission(AutomationAction::CreateMeeting, AutomationPermission::Deny);
+        settings.set_permission(AutomationAction::CreateMeeting, AutomationPermission::Ask);
+        settings.save(&path).unwrap();
+
+        let loaded = AutomationSettings::load(&path);
+        assert_eq!(loaded, settings);
+        assert_eq!(
+            loaded.permission(AutomationAction::SetPresence),
+            AutomationPermission::Allow
+        );
+        assert_eq!(
+            loaded.permission(AutomationAction::CreateMeeting),
+            AutomationPermission::Ask
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_response() {
+        let response = response(403, &serde_json::json!({ "error": "Denied" }));
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(response.contains("Content-Length: 18\r\n"));
+        assert!(response.ends_with("\r\n\r\n{\"error\":\"Denied\"}"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Avatar thumbnail pipeline
+//!
+//! Chat and calendar lists render the same handful of avatars over and over.
+//! Each avatar is downloaded once, downscaled to a small square WebP thumbnail
+//! and stored in the app cache directory under a hash of its URL, so the
+//! frontend can load it from disk (through the asset protocol) instead of
+//! fetching the remote image on every render.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::{Error, Result};
+
+/// Thumbnail edge in pixels, 2x the largest avatar rendered in lists
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 96;
+
+/// Largest thumbnail the frontend may request
+pub const MAX_THUMBNAIL_SIZE: u32 = 512;
+
+/// Remote avatars larger than this are rejected
+const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// File name of a thumbnail: URL hash plus size, so sizes don't collide
+pub fn thumbnail_file_name(url: &str, size: u32) -> String {
+    let digest = Sha256::digest(url.as_bytes());
+    let hash: String = digest
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}-{}.webp", hash, size)
+}
+
+/// Decode an image and encode a square WebP thumbnail of it
+pub fn make_thumbnail(bytes: &[u8], size: u32) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| Error::Parse(format!("Failed to decode avatar: {}", e)))?;
+
+    // The WebP encoder only takes 8-bit RGB(A)
+    let thumbnail = image.resize_to_fill(size, size, FilterType::Lanczos3);
+    let thumbnail = DynamicImage::ImageRgba8(thumbnail.to_rgba8());
+
+    let mut encoded = Cursor::new(Vec::new());
+    thumbnail
+        .write_to(&mut encoded, ImageFormat::WebP)
+        .map_err(|e| Error::Parse(format!("Failed to encode avatar thumbnail: {}", e)))?;
+    Ok(encoded.into_inner())
+}
+
+/// Downloads avatars and keeps their thumbnails on disk
+pub struct AvatarCache {
+    dir: PathBuf,
+    http: reqwest::Client,
+    /// One lock per thumbnail file, so concurrent requests download it once
+    in_flight: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl AvatarCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            http: reqwest::Client::builder()
+                .timeout(DOWNLOAD_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Local path of the thumbnail for `url`, if it was generated already
+    pub async fn cached_thumbnail(&self, url: &str, size: u32) -> Option<PathBuf> {
+        let path = self.dir.join(thumbnail_file_name(url, size));
+        tokio::fs::try_exists(&path)
+            .await
+            .unwrap_or(false)
+            .then_some(path)
+    }
+
+    /// Local path of the thumbnail for `url`, generating it on first use
+    pub async fn thumbnail(&self, url: &str, size: u32) -> Result<PathBuf> {
+        let file_name = thumbnail_file_name(url, size);
+        let path = self.dir.join(&file_name);
+
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(path);
+        }
+
+        let lock = self
+            .in_flight
+            .lock()
+            .await
+            .entry(file_name.clone())
+            .or_default()
+            .clone();
+        let _guard = lock.lock().await;
+
+        // Another request may have produced it while we waited
+        if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            let result = self.generate(url, size, &path).await;
+            self.in_flight.lock().await.remove(&file_name);
+            result?;
+        }
+
+        Ok(path)
+    }
+
+    async fn generate(&self, url: &str, size: u32, path: &Path) -> Result<()> {
+        tracing::debug!("Downloading avatar {}", url);
+        let bytes = self.download(url).await?;
+
+        let thumbnail = tokio::task::spawn_blocking(move || make_thumbnail(&bytes, size))
+            .await
+            .map_err(|e| Error::Parse(format!("Thumbnail task failed: {}", e)))??;
+
+        // Write then rename so readers never see a partial file
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let tmp_path = path.with_extension("webp.tmp");
+        tokio::fs::write(&tmp_path, &thumbnail).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to download avatar: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::Network(format!(
+                "Failed to download avatar: {}",
+                response.status()
+            )));
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len as usize > MAX_AVATAR_BYTES)
+        {
+            return Err(Error::Parse("Avatar image is too large".to_string()));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to download avatar: {}", e)))?;
+        if bytes.len() > MAX_AVATAR_BYTES {
+            return Err(Error::Parse("Avatar image is too large".to_string()));
+        }
+        Ok(bytes.to_vec())
+    }
+
+    /// Delete every cached thumbnail, returning how many were removed
+    pub async fn clear(&self) -> Result<usize> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut removed = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.path().extension().is_some_and(|ext| ext == "webp") {
+                tokio::fs::remove_file(entry.path()).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_file_name_is_stable_per_url_and_size() {
+        let a = thumbnail_file_name("https://example.com/a.png", 96);
+        assert_eq!(a, thumbnail_file_name("https://example.com/a.png", 96));
+        assert_ne!(a, thumbnail_file_name("https://example.com/a.png", 48));
+        assert_ne!(a, thumbnail_file_name("https://example.com/b.png", 96));
+        assert!(a.ends_with("-96.webp"));
+    }
+
+    #[test]
+    fn test_make_thumbnail_produces_square_webp() {
+        let source = DynamicImage::new_rgb8(300, 200);
+        let mut png = Cursor::new(Vec::new());
+        source.write_to(&mut png, ImageFormat::Png).unwrap();
+
+        let thumbnail = make_thumbnail(png.get_ref(), 64).unwrap();
+        let decoded = image::load_from_memory_with_format(&thumbnail, ImageFormat::WebP).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 64));
+
+        assert!(make_thumbnail(b"not an image", 64).is_err());
+    }
+}
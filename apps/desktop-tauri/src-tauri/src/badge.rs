@@ -0,0 +1,83 @@
+//! Dock and taskbar badge
+//!
+//! A single "attention needed" count: unread messages, including replies in
+//! followed threads, meeting invitations not answered yet, and people in the
+//! waiting room of a session the user hosts or co-hosts. macOS and Linux
+//! show the count on the app icon; Windows has no badge count, so a dot is
+//! overlaid on the taskbar button instead.
+
+use serde::Serialize;
+
+/// Width and height of the Windows taskbar overlay
+#[cfg(any(windows, test))]
+pub const OVERLAY_ICON_SIZE: u32 = 16;
+
+/// What needs the user's attention
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Badge {
+    pub unread_messages: u32,
+    pub pending_meeting_responses: u32,
+    pub waiting_room_requests: u32,
+    pub total: u32,
+}
+
+impl Badge {
+    pub fn new(
+        unread_messages: u32,
+        pending_meeting_responses: u32,
+        waiting_room_requests: u32,
+    ) -> Self {
+        Self {
+            unread_messages,
+            pending_meeting_responses,
+            waiting_room_requests,
+            total: unread_messages
+                .saturating_add(pending_meeting_responses)
+                .saturating_add(waiting_room_requests),
+        }
+    }
+}
+
+/// RGBA pixels of the taskbar overlay: a red dot on transparency
+#[cfg(any(windows, test))]
+pub fn overlay_icon_rgba() -> Vec<u8> {
+    let size = OVERLAY_ICON_SIZE as f32;
+    let center = size / 2.0;
+    let radius = center - 1.0;
+
+    let mut rgba = Vec::with_capacity((OVERLAY_ICON_SIZE * OVERLAY_ICON_SIZE * 4) as usize);
+    for y in 0..OVERLAY_ICON_SIZE {
+        for x in 0..OVERLAY_ICON_SIZE {
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
+            // Coverage of the pixel, for a smooth edge
+            let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            rgba.extend_from_slice(&[0xE5, 0x39, 0x35, (coverage * 255.0).round() as u8]);
+        }
+    }
+    rgba
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total() {
+        let badge = Badge::new(3, 1, 2);
+        assert_eq!(badge.total, 6);
+        assert_eq!(Badge::new(u32::MAX, 1, 0).total, u32::MAX);
+        assert_eq!(Badge::new(0, 0, 0), Badge::default());
+    }
+
+    #[test]
+    fn test_overlay_icon() {
+        let rgba = overlay_icon_rgba();
+        let size = OVERLAY_ICON_SIZE as usize;
+        assert_eq!(rgba.len(), size * size * 4);
+
+        let alpha = |x: usize, y: usize| rgba[(y * size + x) * 4 + 3];
+        assert_eq!(alpha(size / 2, size / 2), 255);
+        assert_eq!(alpha(0, 0), 0);
+    }
+}
@@ -3,13 +3,16 @@
 //! Provides in-memory caching with TTL support to reduce API calls
 //! and improve application performance.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 use crate::commands::calendar::Meeting;
-use crate::supabase::MessageRow;
+use crate::low_bandwidth;
+use crate::pagination::{MessageCursor, MessageKey, PageDirection};
+use crate::perf;
+use crate::supabase::{CustomEmojiRow, MessageRow};
 
 // ==========================================
 // Generic Cache Entry
@@ -31,17 +34,18 @@ impl<T> CacheEntry<T> {
         }
     }
 
+    /// Lifetime of the entry, longer in low-bandwidth mode
+    fn effective_ttl(&self) -> Duration {
+        low_bandwidth::cache_ttl(self.ttl)
+    }
+
     pub fn is_expired(&self) -> bool {
-        self.created_at.elapsed() > self.ttl
+        self.created_at.elapsed() > self.effective_ttl()
     }
 
     pub fn remaining_ttl(&self) -> Duration {
-        let elapsed = self.created_at.elapsed();
-        if elapsed > self.ttl {
-            Duration::ZERO
-        } else {
-            self.ttl - elapsed
-        }
+        self.effective_ttl()
+            .saturating_sub(self.created_at.elapsed())
     }
 }
 
@@ -60,6 +64,8 @@ pub struct MeetingCache {
     by_id: HashMap<String, CacheEntry<Meeting>>,
     /// Upcoming meetings for a user
     upcoming: Option<CacheEntry<Vec<Meeting>>>,
+    /// Recurring meetings, whose occurrences fall in any month
+    recurring: Option<CacheEntry<Vec<Meeting>>>,
     /// Default TTL for cached data
     default_ttl: Duration,
 }
@@ -70,6 +76,7 @@ impl MeetingCache {
             by_month: HashMap::new(),
             by_id: HashMap::new(),
             upcoming: None,
+            recurring: None,
             default_ttl: Duration::from_secs(300), // 5 minutes
         }
     }
@@ -86,6 +93,7 @@ impl MeetingCache {
             .get(&key)
             .filter(|entry| !entry.is_expired())
             .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
     }
 
     /// Store meetings for a specific month
@@ -101,6 +109,7 @@ impl MeetingCache {
             .get(id)
             .filter(|entry| !entry.is_expired())
             .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
     }
 
     /// Store a single meeting
@@ -116,6 +125,7 @@ impl MeetingCache {
             .as_ref()
             .filter(|entry| !entry.is_expired())
             .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
     }
 
     /// Store upcoming meetings
@@ -123,17 +133,36 @@ impl MeetingCache {
         self.upcoming = Some(CacheEntry::new(meetings, Duration::from_secs(60))); // 1 minute TTL
     }
 
+    /// Get recurring meetings
+    pub fn get_recurring(&self) -> Option<&Vec<Meeting>> {
+        self.recurring
+            .as_ref()
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
+    }
+
+    /// Store recurring meetings
+    pub fn set_recurring(&mut self, meetings: Vec<Meeting>) {
+        self.recurring = Some(CacheEntry::new(meetings, self.default_ttl));
+    }
+
     /// Invalidate a specific month
     pub fn invalidate_month(&mut self, year: i32, month: u32) {
         let key = format!("{}-{:02}", year, month);
         self.by_month.remove(&key);
+        // A meeting of any month may recur into this one
+        self.recurring = None;
     }
 
     /// Invalidate a specific meeting (and related month caches)
     pub fn invalidate_meeting(&mut self, meeting_id: &str) {
         self.by_id.remove(meeting_id);
+        self.by_month
+            .retain(|_, entry| !entry.data.iter().any(|m| m.id == meeting_id));
         // Also invalidate upcoming since it might contain this meeting
         self.upcoming = None;
+        self.recurring = None;
     }
 
     /// Invalidate all cached data
@@ -141,6 +170,7 @@ impl MeetingCache {
         self.by_month.clear();
         self.by_id.clear();
         self.upcoming = None;
+        self.recurring = None;
     }
 
     /// Clean up expired entries
@@ -152,6 +182,11 @@ impl MeetingCache {
                 self.upcoming = None;
             }
         }
+        if let Some(ref entry) = self.recurring {
+            if entry.is_expired() {
+                self.recurring = None;
+            }
+        }
     }
 
     /// Get cache statistics
@@ -168,10 +203,53 @@ impl MeetingCache {
 // Message Cache
 // ==========================================
 
+/// Newest messages of a conversation, oldest first and without gaps, so
+/// older pages can be served from it as they are scrolled back to
+#[derive(Debug, Clone, Default)]
+pub struct CachedMessages {
+    pub messages: Vec<MessageRow>,
+    /// Whether `messages` reaches back to the first message
+    pub complete: bool,
+}
+
+impl CachedMessages {
+    fn key_range(&self) -> Option<(MessageKey, MessageKey)> {
+        let mut keys = self
+            .messages
+            .iter()
+            .filter_map(|m| MessageKey::of(&m.created_at, &m.id));
+        let oldest = keys.next()?;
+        let newest = keys.last().unwrap_or_else(|| oldest.clone());
+        Some((oldest, newest))
+    }
+
+    /// Whether a position lies within the cached messages, so that what
+    /// lies right past it is cached as well
+    fn covers(&self, position: &MessageKey) -> bool {
+        self.key_range()
+            .is_some_and(|(oldest, newest)| oldest <= *position && *position <= newest)
+    }
+
+    fn merge(&mut self, messages: Vec<MessageRow>) {
+        for msg in messages {
+            match self.messages.iter_mut().find(|m| m.id == msg.id) {
+                Some(existing) => *existing = msg,
+                None => self.messages.push(msg),
+            }
+        }
+        sort_messages(&mut self.messages);
+    }
+}
+
+/// Sort messages oldest first, in the order they are paged in
+fn sort_messages(messages: &mut [MessageRow]) {
+    messages.sort_by_cached_key(|m| MessageKey::of(&m.created_at, &m.id));
+}
+
 #[derive(Debug, Default)]
 pub struct MessageCache {
     /// Messages by conversation ID
-    by_conversation: HashMap<String, CacheEntry<Vec<MessageRow>>>,
+    by_conversation: HashMap<String, CacheEntry<CachedMessages>>,
     /// Last message timestamp per conversation (for incremental fetching)
     last_timestamp: HashMap<String, String>,
     /// Default TTL
@@ -187,18 +265,63 @@ impl MessageCache {
         }
     }
 
-    /// Get messages for a conversation
-    pub fn get_messages(&self, conversation_id: &str) -> Option<&Vec<MessageRow>> {
-        self.by_conversation
+    /// Get a page of messages for a conversation, newest first, along with
+    /// whether there are older ones
+    ///
+    /// Only the newest page and older pages are served, and only when the
+    /// cache holds all of the page or reaches back to the first message.
+    pub fn get_page(
+        &self,
+        conversation_id: &str,
+        cursor: Option<&MessageCursor>,
+        limit: usize,
+    ) -> Option<(Vec<MessageRow>, bool)> {
+        let cached = &self
+            .by_conversation
             .get(conversation_id)
-            .filter(|entry| !entry.is_expired())
-            .map(|entry| &entry.data)
+            .filter(|entry| !entry.is_expired())?
+            .data;
+
+        let older: Vec<&MessageRow> = match cursor {
+            None => cached.messages.iter().collect(),
+            Some(cursor)
+                if cursor.direction == PageDirection::Older && cached.covers(&cursor.position) =>
+            {
+                cached
+                    .messages
+                    .iter()
+                    .filter(|m| {
+                        MessageKey::of(&m.created_at, &m.id)
+                            .is_some_and(|key| cursor.includes(&key))
+                    })
+                    .collect()
+            }
+            Some(_) => return None,
+        };
+        if older.len() <= limit && !cached.complete {
+            return None;
+        }
+
+        perf::record_cache_hit();
+        let has_more = older.len() > limit;
+        let page = older.into_iter().rev().take(limit).cloned().collect();
+        Some((page, has_more))
     }
 
-    /// Store messages for a conversation
-    pub fn set_messages(&mut self, conversation_id: &str, messages: Vec<MessageRow>) {
+    /// Store the newest messages of a conversation, replacing what was cached
+    ///
+    /// `complete` tells whether they go back to the first message.
+    pub fn set_messages(
+        &mut self,
+        conversation_id: &str,
+        messages: Vec<MessageRow>,
+        complete: bool,
+    ) {
+        let mut cached = CachedMessages { messages, complete };
+        sort_messages(&mut cached.messages);
+
         // Update last timestamp
-        if let Some(last_msg) = messages.last() {
+        if let Some(last_msg) = cached.messages.last() {
             if let Some(ref ts) = last_msg.created_at {
                 self.last_timestamp
                     .insert(conversation_id.to_string(), ts.clone());
@@ -207,10 +330,37 @@ impl MessageCache {
 
         self.by_conversation.insert(
             conversation_id.to_string(),
-            CacheEntry::new(messages, self.default_ttl),
+            CacheEntry::new(cached, self.default_ttl),
         );
     }
 
+    /// Add a page fetched while scrolling back to the cached messages
+    ///
+    /// The page is only kept when its cursor points into the cached
+    /// messages, so they stay without gaps. `reached_start` tells whether
+    /// nothing is older than the page.
+    pub fn add_older_page(
+        &mut self,
+        conversation_id: &str,
+        cursor: &MessageCursor,
+        messages: Vec<MessageRow>,
+        reached_start: bool,
+    ) {
+        let Some(entry) = self
+            .by_conversation
+            .get_mut(conversation_id)
+            .filter(|entry| !entry.is_expired())
+        else {
+            return;
+        };
+        if cursor.direction != PageDirection::Older || !entry.data.covers(&cursor.position) {
+            return;
+        }
+
+        entry.data.merge(messages);
+        entry.data.complete |= reached_start;
+    }
+
     /// Append new messages to existing cache
     pub fn append_messages(&mut self, conversation_id: &str, new_messages: Vec<MessageRow>) {
         if new_messages.is_empty() {
@@ -226,25 +376,10 @@ impl MessageCache {
         }
 
         if let Some(entry) = self.by_conversation.get_mut(conversation_id) {
-            // Collect existing IDs as owned strings to avoid borrow conflict
-            let existing_ids: std::collections::HashSet<String> =
-                entry.data.iter().map(|m| m.id.clone()).collect();
-
-            for msg in new_messages {
-                if !existing_ids.contains(&msg.id) {
-                    entry.data.push(msg);
-                }
-            }
-
-            // Re-sort by timestamp
-            entry.data.sort_by(|a, b| {
-                a.created_at
-                    .as_ref()
-                    .cmp(&b.created_at.as_ref())
-            });
+            entry.data.merge(new_messages);
         } else {
             // No existing cache, just set
-            self.set_messages(conversation_id, new_messages);
+            self.set_messages(conversation_id, new_messages, false);
         }
     }
 
@@ -253,6 +388,21 @@ impl MessageCache {
         self.last_timestamp.get(conversation_id).map(|s| s.as_str())
     }
 
+    /// Replace or add messages of a cached conversation; conversations not
+    /// cached are left to be fetched when opened
+    pub fn upsert_messages(&mut self, conversation_id: &str, messages: Vec<MessageRow>) {
+        if let Some(entry) = self.by_conversation.get_mut(conversation_id) {
+            entry.data.merge(messages);
+        }
+    }
+
+    /// Drop a deleted message from whichever conversation holds it
+    pub fn remove_message(&mut self, message_id: &str) {
+        for entry in self.by_conversation.values_mut() {
+            entry.data.messages.retain(|m| m.id != message_id);
+        }
+    }
+
     /// Invalidate a conversation's messages
     pub fn invalidate_conversation(&mut self, conversation_id: &str) {
         self.by_conversation.remove(conversation_id);
@@ -286,6 +436,12 @@ pub struct PresenceInfo {
     pub is_online: bool,
     pub last_seen: Option<String>,
     pub status: Option<String>,
+    #[serde(default)]
+    pub status_message: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -313,6 +469,7 @@ impl PresenceCache {
             .get(user_id)
             .filter(|entry| !entry.is_expired())
             .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
     }
 
     /// Update presence for a user
@@ -328,6 +485,7 @@ impl PresenceCache {
             .as_ref()
             .filter(|entry| !entry.is_expired())
             .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
     }
 
     /// Set team members
@@ -383,6 +541,110 @@ impl PresenceCache {
     }
 }
 
+// ==========================================
+// Custom Emoji Cache
+// ==========================================
+
+#[derive(Debug, Default)]
+pub struct EmojiCache {
+    /// The team's custom emoji, sorted by shortcode
+    emojis: Option<CacheEntry<Vec<CustomEmojiRow>>>,
+    default_ttl: Duration,
+}
+
+impl EmojiCache {
+    pub fn new() -> Self {
+        Self {
+            emojis: None,
+            default_ttl: Duration::from_secs(10 * 60), // 10 minutes
+        }
+    }
+
+    /// Get the team's custom emoji
+    pub fn get(&self) -> Option<&Vec<CustomEmojiRow>> {
+        self.emojis
+            .as_ref()
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
+    }
+
+    /// Set the team's custom emoji
+    pub fn set(&mut self, emojis: Vec<CustomEmojiRow>) {
+        self.emojis = Some(CacheEntry::new(emojis, self.default_ttl));
+    }
+
+    /// Invalidate all
+    pub fn invalidate_all(&mut self) {
+        self.emojis = None;
+    }
+
+    /// Cleanup expired
+    pub fn cleanup(&mut self) {
+        if self.emojis.as_ref().is_some_and(|entry| entry.is_expired()) {
+            self.emojis = None;
+        }
+    }
+}
+
+// ==========================================
+// Block Cache
+// ==========================================
+
+#[derive(Debug, Default)]
+pub struct BlockCache {
+    /// IDs of the users the user blocked
+    blocked: Option<CacheEntry<HashSet<String>>>,
+    default_ttl: Duration,
+}
+
+impl BlockCache {
+    pub fn new() -> Self {
+        Self {
+            blocked: None,
+            default_ttl: Duration::from_secs(10 * 60), // 10 minutes
+        }
+    }
+
+    /// Get the users the user blocked
+    pub fn get(&self) -> Option<&HashSet<String>> {
+        self.blocked
+            .as_ref()
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| &entry.data)
+            .inspect(|_| perf::record_cache_hit())
+    }
+
+    /// Set the users the user blocked
+    pub fn set(&mut self, blocked: HashSet<String>) {
+        self.blocked = Some(CacheEntry::new(blocked, self.default_ttl));
+    }
+
+    /// Whether a user is blocked, going by the last known list even once it
+    /// expired, so realtime events can be checked without a fetch
+    pub fn is_blocked(&self, user_id: &str) -> bool {
+        self.blocked
+            .as_ref()
+            .is_some_and(|entry| entry.data.contains(user_id))
+    }
+
+    /// Add or remove a user from a loaded list
+    pub fn update(&mut self, user_id: &str, blocked: bool) {
+        if let Some(entry) = self.blocked.as_mut() {
+            if blocked {
+                entry.data.insert(user_id.to_string());
+            } else {
+                entry.data.remove(user_id);
+            }
+        }
+    }
+
+    /// Invalidate all
+    pub fn invalidate_all(&mut self) {
+        self.blocked = None;
+    }
+}
+
 // ==========================================
 // Cache Statistics
 // ==========================================
@@ -410,6 +672,8 @@ pub struct AppCache {
     pub meetings: RwLock<MeetingCache>,
     pub messages: RwLock<MessageCache>,
     pub presence: RwLock<PresenceCache>,
+    pub emojis: RwLock<EmojiCache>,
+    pub blocks: RwLock<BlockCache>,
 }
 
 impl AppCache {
@@ -418,6 +682,8 @@ impl AppCache {
             meetings: RwLock::new(MeetingCache::new()),
             messages: RwLock::new(MessageCache::new()),
             presence: RwLock::new(PresenceCache::new()),
+            emojis: RwLock::new(EmojiCache::new()),
+            blocks: RwLock::new(BlockCache::new()),
         }
     }
 
@@ -439,6 +705,7 @@ impl AppCache {
         self.meetings.write().await.cleanup();
         self.messages.write().await.cleanup();
         self.presence.write().await.cleanup();
+        self.emojis.write().await.cleanup();
     }
 
     /// Invalidate all caches
@@ -446,6 +713,8 @@ impl AppCache {
         self.meetings.write().await.invalidate_all();
         self.messages.write().await.invalidate_all();
         self.presence.write().await.invalidate_all();
+        self.emojis.write().await.invalidate_all();
+        self.blocks.write().await.invalidate_all();
     }
 }
 
@@ -0,0 +1,557 @@
+//! Two-way calendar sync planning
+//!
+//! A sync pass gathers what changed on each side since the last pass: the
+//! meetings and tombstones from the change feed, and the events from the
+//! calendar's incremental sync. Each synced meeting has a link recording the
+//! event's etag and the meeting's `updated_at` as of the last pass, so the
+//! pass's own writes are recognised when they come back as changes. When both
+//! sides changed the same meeting, the conflict policy picks the winner.
+//!
+//! Planning is pure; carrying out the actions is left to the caller.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Which side wins when a meeting and its event both changed since the last
+/// sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// The most recently modified side
+    #[default]
+    LatestWins,
+    /// The meeting in the app
+    PreferLocal,
+    /// The calendar event
+    PreferRemote,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::LatestWins => "latest_wins",
+            ConflictPolicy::PreferLocal => "prefer_local",
+            ConflictPolicy::PreferRemote => "prefer_remote",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "latest_wins" => Ok(ConflictPolicy::LatestWins),
+            "prefer_local" => Ok(ConflictPolicy::PreferLocal),
+            "prefer_remote" => Ok(ConflictPolicy::PreferRemote),
+            _ => Err(Error::Input(format!("Unknown conflict policy: {}", value))),
+        }
+    }
+
+    fn local_wins(&self, local_updated: DateTime<Utc>, remote_updated: DateTime<Utc>) -> bool {
+        match self {
+            ConflictPolicy::LatestWins => local_updated >= remote_updated,
+            ConflictPolicy::PreferLocal => true,
+            ConflictPolicy::PreferRemote => false,
+        }
+    }
+}
+
+/// Meeting changed or deleted in the app since the last sync
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalChange {
+    pub meeting_id: String,
+    /// Event the meeting was synced to outside the sync engine
+    pub event_id: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    pub cancelled: bool,
+    pub deleted: bool,
+}
+
+/// Event changed or deleted in the calendar since the last sync
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteChange {
+    pub event_id: String,
+    pub etag: String,
+    pub updated: DateTime<Utc>,
+    pub cancelled: bool,
+    /// Meeting the event was created for, when the app created it
+    pub meeting_id: Option<String>,
+}
+
+/// Meeting and event kept in sync, as of the last sync
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncLink {
+    pub meeting_id: String,
+    pub event_id: String,
+    pub etag: Option<String>,
+    pub meeting_updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncAction {
+    CreateRemote {
+        meeting_id: String,
+    },
+    UpdateRemote {
+        meeting_id: String,
+        event_id: String,
+    },
+    DeleteRemote {
+        meeting_id: String,
+        event_id: String,
+    },
+    CreateLocal {
+        event_id: String,
+    },
+    UpdateLocal {
+        meeting_id: String,
+        event_id: String,
+    },
+    /// Cancel the meeting; deleted events don't delete meetings outright
+    CancelLocal {
+        meeting_id: String,
+        event_id: String,
+    },
+    /// Forget a link whose meeting and event are both gone
+    Unlink {
+        meeting_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    pub actions: Vec<SyncAction>,
+    /// Meetings changed on both sides, resolved by the policy
+    pub conflicts: usize,
+}
+
+impl LocalChange {
+    /// Whether the change wasn't made by the last sync itself
+    fn is_new(&self, link: &SyncLink) -> bool {
+        self.deleted
+            || link
+                .meeting_updated_at
+                .is_none_or(|synced| self.updated_at > synced)
+    }
+
+    fn push_action(&self, event_id: &str) -> SyncAction {
+        if self.deleted || self.cancelled {
+            SyncAction::DeleteRemote {
+                meeting_id: self.meeting_id.clone(),
+                event_id: event_id.to_string(),
+            }
+        } else {
+            SyncAction::UpdateRemote {
+                meeting_id: self.meeting_id.clone(),
+                event_id: event_id.to_string(),
+            }
+        }
+    }
+}
+
+impl RemoteChange {
+    /// Whether the change wasn't made by the last sync itself
+    fn is_new(&self, link: &SyncLink) -> bool {
+        link.etag.as_deref() != Some(self.etag.as_str())
+    }
+
+    fn pull_action(&self, meeting_id: &str) -> SyncAction {
+        if self.cancelled {
+            SyncAction::CancelLocal {
+                meeting_id: meeting_id.to_string(),
+                event_id: self.event_id.clone(),
+            }
+        } else {
+            SyncAction::UpdateLocal {
+                meeting_id: meeting_id.to_string(),
+                event_id: self.event_id.clone(),
+            }
+        }
+    }
+}
+
+/// Work out what to copy in which direction
+///
+/// Meetings without a link are created in the calendar unless cancelled;
+/// events without a link are created as meetings unless cancelled or created
+/// by the app for a meeting this sync doesn't own.
+pub fn plan(
+    local: &[LocalChange],
+    remote: &[RemoteChange],
+    links: &[SyncLink],
+    policy: ConflictPolicy,
+) -> SyncPlan {
+    let by_meeting: HashMap<&str, &SyncLink> =
+        links.iter().map(|l| (l.meeting_id.as_str(), l)).collect();
+    let by_event: HashMap<&str, &SyncLink> =
+        links.iter().map(|l| (l.event_id.as_str(), l)).collect();
+    let local_by_meeting: HashMap<&str, &LocalChange> =
+        local.iter().map(|c| (c.meeting_id.as_str(), c)).collect();
+
+    let mut plan = SyncPlan::default();
+    let mut handled: HashSet<&str> = HashSet::new();
+
+    for change in remote {
+        let Some(link) = by_event.get(change.event_id.as_str()) else {
+            if !change.cancelled && change.meeting_id.is_none() {
+                plan.actions.push(SyncAction::CreateLocal {
+                    event_id: change.event_id.clone(),
+                });
+            }
+            continue;
+        };
+        handled.insert(link.meeting_id.as_str());
+        if !change.is_new(link) {
+            if let Some(local) = local_by_meeting.get(link.meeting_id.as_str()) {
+                if local.is_new(link) {
+                    plan.actions.push(local.push_action(&link.event_id));
+                }
+            }
+            continue;
+        }
+
+        let local = local_by_meeting
+            .get(link.meeting_id.as_str())
+            .filter(|local| local.is_new(link));
+        let action = match local {
+            Some(local) if local.deleted && change.cancelled => SyncAction::Unlink {
+                meeting_id: link.meeting_id.clone(),
+            },
+            Some(local) => {
+                plan.conflicts += 1;
+                if policy.local_wins(local.updated_at, change.updated) {
+                    local.push_action(&link.event_id)
+                } else if local.deleted {
+                    // A deleted meeting can't take the event's changes
+                    SyncAction::Unlink {
+                        meeting_id: link.meeting_id.clone(),
+                    }
+                } else {
+                    change.pull_action(&link.meeting_id)
+                }
+            }
+            None => change.pull_action(&link.meeting_id),
+        };
+        plan.actions.push(action);
+    }
+
+    for change in local {
+        if handled.contains(change.meeting_id.as_str()) {
+            continue;
+        }
+        match by_meeting.get(change.meeting_id.as_str()) {
+            Some(link) => {
+                if change.is_new(link) {
+                    plan.actions.push(change.push_action(&link.event_id));
+                }
+            }
+            None if change.deleted || change.cancelled => {}
+            None => plan.actions.push(match &change.event_id {
+                Some(event_id) => SyncAction::UpdateRemote {
+                    meeting_id: change.meeting_id.clone(),
+                    event_id: event_id.clone(),
+                },
+                None => SyncAction::CreateRemote {
+                    meeting_id: change.meeting_id.clone(),
+                },
+            }),
+        }
+    }
+
+    plan
+}
+
+/// Start and length in minutes of an event, from its start and end as
+/// RFC 3339 date-times or, for all-day events, dates
+pub fn event_span(
+    start_date_time: Option<&str>,
+    start_date: Option<&str>,
+    end_date_time: Option<&str>,
+    end_date: Option<&str>,
+) -> Option<(DateTime<Utc>, i32)> {
+    let parse = |date_time: Option<&str>, date: Option<&str>| -> Option<DateTime<Utc>> {
+        if let Some(date_time) = date_time {
+            return DateTime::parse_from_rfc3339(date_time)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc));
+        }
+        NaiveDate::parse_from_str(date?, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+            .map(|dt| dt.and_utc())
+    };
+
+    let start = parse(start_date_time, start_date)?;
+    let end = parse(end_date_time, end_date)?;
+    let minutes = i32::try_from((end - start).num_minutes()).ok()?;
+    (minutes > 0).then_some((start, minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2026-03-10T{:02}:00:00Z", hour))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn link(meeting_id: &str, event_id: &str) -> SyncLink {
+        SyncLink {
+            meeting_id: meeting_id.to_string(),
+            event_id: event_id.to_string(),
+            etag: Some("\"1\"".to_string()),
+            meeting_updated_at: Some(at(8)),
+        }
+    }
+
+    fn local(meeting_id: &str, updated_at: DateTime<Utc>) -> LocalChange {
+        LocalChange {
+            meeting_id: meeting_id.to_string(),
+            event_id: None,
+            updated_at,
+            cancelled: false,
+            deleted: false,
+        }
+    }
+
+    fn remote(event_id: &str, etag: &str, updated: DateTime<Utc>) -> RemoteChange {
+        RemoteChange {
+            event_id: event_id.to_string(),
+            etag: etag.to_string(),
+            updated,
+            cancelled: false,
+            meeting_id: None,
+        }
+    }
+
+    #[test]
+    fn test_conflict_policy_roundtrip() {
+        for policy in [
+            ConflictPolicy::LatestWins,
+            ConflictPolicy::PreferLocal,
+            ConflictPolicy::PreferRemote,
+        ] {
+            assert_eq!(ConflictPolicy::parse(policy.as_str()).unwrap(), policy);
+        }
+        assert!(ConflictPolicy::parse("newest").is_err());
+    }
+
+    #[test]
+    fn test_new_meeting_is_created_remotely() {
+        let plan = plan(&[local("m1", at(9))], &[], &[], ConflictPolicy::default());
+        assert_eq!(
+            plan.actions,
+            vec![SyncAction::CreateRemote {
+                meeting_id: "m1".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_manually_synced_meeting_is_adopted() {
+        let mut change = local("m1", at(9));
+        change.event_id = Some("e1".to_string());
+        let plan = plan(&[change], &[], &[], ConflictPolicy::default());
+        assert_eq!(
+            plan.actions,
+            vec![SyncAction::UpdateRemote {
+                meeting_id: "m1".to_string(),
+                event_id: "e1".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cancelled_unlinked_meeting_is_ignored() {
+        let mut change = local("m1", at(9));
+        change.cancelled = true;
+        assert!(plan(&[change], &[], &[], ConflictPolicy::default())
+            .actions
+            .is_empty());
+    }
+
+    #[test]
+    fn test_own_writes_are_not_synced_back() {
+        let links = [link("m1", "e1")];
+        let plan = plan(
+            &[local("m1", at(8))],
+            &[remote("e1", "\"1\"", at(9))],
+            &links,
+            ConflictPolicy::default(),
+        );
+        assert!(plan.actions.is_empty());
+    }
+
+    #[test]
+    fn test_local_update_and_delete_are_pushed() {
+        let links = [link("m1", "e1"), link("m2", "e2")];
+        let mut deleted = local("m2", at(9));
+        deleted.deleted = true;
+        let plan = plan(
+            &[local("m1", at(9)), deleted],
+            &[],
+            &links,
+            ConflictPolicy::default(),
+        );
+        assert_eq!(
+            plan.actions,
+            vec![
+                SyncAction::UpdateRemote {
+                    meeting_id: "m1".to_string(),
+                    event_id: "e1".to_string()
+                },
+                SyncAction::DeleteRemote {
+                    meeting_id: "m2".to_string(),
+                    event_id: "e2".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remote_update_and_delete_are_pulled() {
+        let links = [link("m1", "e1"), link("m2", "e2")];
+        let mut deleted = remote("e2", "\"2\"", at(9));
+        deleted.cancelled = true;
+        let plan = plan(
+            &[],
+            &[remote("e1", "\"2\"", at(9)), deleted],
+            &links,
+            ConflictPolicy::default(),
+        );
+        assert_eq!(
+            plan.actions,
+            vec![
+                SyncAction::UpdateLocal {
+                    meeting_id: "m1".to_string(),
+                    event_id: "e1".to_string()
+                },
+                SyncAction::CancelLocal {
+                    meeting_id: "m2".to_string(),
+                    event_id: "e2".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_event_is_created_locally() {
+        let mut ours = remote("e2", "\"1\"", at(9));
+        ours.meeting_id = Some("m9".to_string());
+        let mut cancelled = remote("e3", "\"1\"", at(9));
+        cancelled.cancelled = true;
+        let plan = plan(
+            &[],
+            &[remote("e1", "\"1\"", at(9)), ours, cancelled],
+            &[],
+            ConflictPolicy::default(),
+        );
+        assert_eq!(
+            plan.actions,
+            vec![SyncAction::CreateLocal {
+                event_id: "e1".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_conflict_latest_wins() {
+        let links = [link("m1", "e1")];
+        let newer_local = plan(
+            &[local("m1", at(11))],
+            &[remote("e1", "\"2\"", at(10))],
+            &links,
+            ConflictPolicy::LatestWins,
+        );
+        assert_eq!(newer_local.conflicts, 1);
+        assert!(matches!(
+            newer_local.actions[..],
+            [SyncAction::UpdateRemote { .. }]
+        ));
+
+        let newer_remote = plan(
+            &[local("m1", at(10))],
+            &[remote("e1", "\"2\"", at(11))],
+            &links,
+            ConflictPolicy::LatestWins,
+        );
+        assert!(matches!(
+            newer_remote.actions[..],
+            [SyncAction::UpdateLocal { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_conflict_preferred_side_wins() {
+        let links = [link("m1", "e1")];
+        let local_changes = [local("m1", at(10))];
+        let remote_changes = [remote("e1", "\"2\"", at(11))];
+
+        let prefer_local = plan(
+            &local_changes,
+            &remote_changes,
+            &links,
+            ConflictPolicy::PreferLocal,
+        );
+        assert!(matches!(
+            prefer_local.actions[..],
+            [SyncAction::UpdateRemote { .. }]
+        ));
+
+        let prefer_remote = plan(
+            &local_changes,
+            &remote_changes,
+            &links,
+            ConflictPolicy::PreferRemote,
+        );
+        assert!(matches!(
+            prefer_remote.actions[..],
+            [SyncAction::UpdateLocal { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_deleted_on_both_sides_unlinks() {
+        let links = [link("m1", "e1")];
+        let mut deleted = local("m1", at(9));
+        deleted.deleted = true;
+        let mut cancelled = remote("e1", "\"2\"", at(9));
+        cancelled.cancelled = true;
+        let plan = plan(&[deleted], &[cancelled], &links, ConflictPolicy::default());
+        assert_eq!(
+            plan.actions,
+            vec![SyncAction::Unlink {
+                meeting_id: "m1".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_event_span() {
+        assert_eq!(
+            event_span(
+                Some("2026-03-10T09:00:00-03:00"),
+                None,
+                Some("2026-03-10T10:30:00-03:00"),
+                None
+            ),
+            Some((at(12), 90))
+        );
+        let (start, minutes) =
+            event_span(None, Some("2026-03-10"), None, Some("2026-03-11")).unwrap();
+        assert_eq!(start, at(0));
+        assert_eq!(minutes, 24 * 60);
+        assert_eq!(
+            event_span(
+                Some("2026-03-10T10:00:00Z"),
+                None,
+                Some("2026-03-10T10:00:00Z"),
+                None
+            ),
+            None
+        );
+    }
+}
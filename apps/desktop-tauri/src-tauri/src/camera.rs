@@ -0,0 +1,88 @@
+//! Webcam access
+//!
+//! Lists the cameras and reads frames from one, converted to RGBA like
+//! screen captures so the same H.264 path encodes both.
+
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{ApiBackend, CameraIndex, RequestedFormat, RequestedFormatType};
+use nokhwa::Camera;
+use serde::Serialize;
+
+use crate::{Error, Result};
+
+/// Camera that can be opened with its `id`
+#[derive(Debug, Clone, Serialize)]
+pub struct CameraDevice {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// List the cameras attached to the machine
+pub fn list_cameras() -> Result<Vec<CameraDevice>> {
+    let cameras = nokhwa::query(ApiBackend::Auto)
+        .map_err(|e| Error::Capture(format!("Failed to list cameras: {}", e)))?;
+
+    Ok(cameras
+        .into_iter()
+        .map(|info| CameraDevice {
+            id: info.index().to_string(),
+            name: info.human_name(),
+            description: info.description().to_string(),
+        })
+        .collect())
+}
+
+/// Index of a camera from its ID: numeric on most backends, a device path
+/// or name on others
+fn camera_index(id: &str) -> CameraIndex {
+    id.parse::<u32>()
+        .map(CameraIndex::Index)
+        .unwrap_or_else(|_| CameraIndex::String(id.to_string()))
+}
+
+/// Open camera stream, closed when dropped
+pub struct CameraCapture {
+    camera: Camera,
+}
+
+impl CameraCapture {
+    /// Open a camera at its highest frame rate
+    pub fn open(id: &str) -> Result<Self> {
+        let format =
+            RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+        let mut camera = Camera::new(camera_index(id), format)
+            .map_err(|e| Error::Capture(format!("Failed to open camera {}: {}", id, e)))?;
+        camera
+            .open_stream()
+            .map_err(|e| Error::Capture(format!("Failed to start camera {}: {}", id, e)))?;
+
+        tracing::info!("Camera {} opened at {}", id, camera.camera_format());
+        Ok(Self { camera })
+    }
+
+    /// Wait for the next frame
+    pub fn frame(&mut self) -> Result<image::RgbaImage> {
+        let buffer = self
+            .camera
+            .frame()
+            .map_err(|e| Error::Capture(format!("Failed to read camera frame: {}", e)))?;
+        let decoded = buffer
+            .decode_image::<RgbFormat>()
+            .map_err(|e| Error::Capture(format!("Failed to decode camera frame: {}", e)))?;
+
+        // nokhwa uses its own version of the image crate
+        let (width, height) = (decoded.width(), decoded.height());
+        let rgb = image::RgbImage::from_raw(width, height, decoded.into_raw())
+            .ok_or_else(|| Error::Capture("Camera frame has an invalid size".to_string()))?;
+        Ok(image::DynamicImage::ImageRgb8(rgb).to_rgba8())
+    }
+}
+
+impl Drop for CameraCapture {
+    fn drop(&mut self) {
+        if let Err(e) = self.camera.stop_stream() {
+            tracing::debug!("Failed to stop camera stream: {}", e);
+        }
+    }
+}
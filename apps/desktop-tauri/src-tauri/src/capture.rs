@@ -1,5 +1,7 @@
+use std::sync::{Arc, RwLock};
+
 use serde::{Deserialize, Serialize};
-use xcap::Monitor;
+use xcap::{Monitor, Window};
 
 use crate::{Error, Result};
 
@@ -20,6 +22,43 @@ pub enum SourceType {
     Window,
 }
 
+/// Session pinned to one application: only its windows are captured and
+/// remote input is only injected while it has focus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppLock {
+    pub app_name: String,
+}
+
+impl AppLock {
+    /// Whether a window's application is the locked one
+    pub fn matches(&self, app_name: &str) -> bool {
+        let locked = self.app_name.trim();
+        !locked.is_empty() && app_name.trim().eq_ignore_ascii_case(locked)
+    }
+}
+
+/// App lock shared with the frame loop, which reads it on every frame
+pub type SharedAppLock = Arc<RwLock<Option<AppLock>>>;
+
+/// Current lock, if any
+pub fn current_app_lock(lock: &SharedAppLock) -> Option<AppLock> {
+    lock.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Names of applications that have at least one visible window
+pub fn running_apps() -> Result<Vec<String>> {
+    let windows = Window::all().map_err(|e| Error::Capture(e.to_string()))?;
+    let mut apps: Vec<String> = windows
+        .iter()
+        .filter(|w| !w.is_minimized().unwrap_or(false))
+        .filter_map(|w| w.app_name().ok())
+        .filter(|name| !name.trim().is_empty())
+        .collect();
+    apps.sort_by_key(|name| name.to_lowercase());
+    apps.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    Ok(apps)
+}
+
 /// Get all available capture sources (screens and windows)
 pub fn get_available_sources() -> Result<Vec<CaptureSource>> {
     let mut sources = Vec::new();
@@ -52,6 +91,99 @@ pub fn get_available_sources() -> Result<Vec<CaptureSource>> {
 /// Capture a frame from the specified source
 #[allow(dead_code)]
 pub fn capture_frame(source_id: &str) -> Result<Vec<u8>> {
+    let image = capture_rgba(source_id)?;
+
+    // Convert to PNG bytes
+    let mut buffer = Vec::new();
+    let mut cursor = std::io::Cursor::new(&mut buffer);
+    image
+        .write_to(&mut cursor, image::ImageFormat::Png)
+        .map_err(|e| Error::Capture(e.to_string()))?;
+
+    Ok(buffer)
+}
+
+/// Capture a raw RGBA frame from the specified source, restricted to the
+/// locked application's windows when there is an app lock
+pub fn capture_source(source_id: &str, lock: Option<&AppLock>) -> Result<image::RgbaImage> {
+    let Some(lock) = lock else {
+        return capture_rgba(source_id);
+    };
+
+    let index = source_id
+        .strip_prefix("screen:")
+        .and_then(|i| i.parse::<usize>().ok())
+        .ok_or_else(|| Error::Capture("App lock only supports screen sources".to_string()))?;
+    let monitors = Monitor::all().map_err(|e| Error::Capture(e.to_string()))?;
+    let monitor = monitors
+        .get(index)
+        .ok_or_else(|| Error::Capture("Monitor not found".to_string()))?;
+
+    capture_app_windows(monitor, lock)
+}
+
+/// Black frame of the monitor with only the locked application's windows
+/// drawn on it, in their on-screen positions so input coordinates still map
+fn capture_app_windows(monitor: &Monitor, lock: &AppLock) -> Result<image::RgbaImage> {
+    let monitor_x = monitor.x().map_err(|e| Error::Capture(e.to_string()))?;
+    let monitor_y = monitor.y().map_err(|e| Error::Capture(e.to_string()))?;
+    let width = monitor.width().map_err(|e| Error::Capture(e.to_string()))?;
+    let height = monitor
+        .height()
+        .map_err(|e| Error::Capture(e.to_string()))?;
+    let scale = monitor.scale_factor().unwrap_or(1.0);
+
+    let mut frame = image::RgbaImage::from_pixel(
+        (width as f32 * scale) as u32,
+        (height as f32 * scale) as u32,
+        image::Rgba([0, 0, 0, 255]),
+    );
+
+    // Windows come front to back, paint back to front so stacking holds
+    let windows = Window::all().map_err(|e| Error::Capture(e.to_string()))?;
+    for window in windows.iter().rev() {
+        if !window.app_name().is_ok_and(|name| lock.matches(&name))
+            || window.is_minimized().unwrap_or(false)
+        {
+            continue;
+        }
+        let (Ok(x), Ok(y)) = (window.x(), window.y()) else {
+            continue;
+        };
+        match window.capture_image() {
+            Ok(image) => image::imageops::overlay(
+                &mut frame,
+                &image,
+                ((x - monitor_x) as f32 * scale) as i64,
+                ((y - monitor_y) as f32 * scale) as i64,
+            ),
+            Err(e) => tracing::debug!("Skipping window of {}: {}", lock.app_name, e),
+        }
+    }
+
+    Ok(frame)
+}
+
+/// Position and size of a screen source's monitor
+pub fn monitor_bounds(source_id: &str) -> Result<(i32, i32, u32, u32)> {
+    let index = source_id
+        .strip_prefix("screen:")
+        .and_then(|i| i.parse::<usize>().ok())
+        .ok_or_else(|| Error::Capture("Not a screen source".to_string()))?;
+    let monitors = Monitor::all().map_err(|e| Error::Capture(e.to_string()))?;
+    let monitor = monitors
+        .get(index)
+        .ok_or_else(|| Error::Capture("Monitor not found".to_string()))?;
+
+    let bounds = (monitor.x(), monitor.y(), monitor.width(), monitor.height());
+    match bounds {
+        (Ok(x), Ok(y), Ok(width), Ok(height)) => Ok((x, y, width, height)),
+        _ => Err(Error::Capture("Failed to read monitor bounds".to_string())),
+    }
+}
+
+/// Capture a raw RGBA frame from the specified source
+pub fn capture_rgba(source_id: &str) -> Result<image::RgbaImage> {
     let parts: Vec<&str> = source_id.split(':').collect();
     if parts.len() != 2 {
         return Err(Error::Capture("Invalid source ID format".to_string()));
@@ -69,18 +201,9 @@ pub fn capture_frame(source_id: &str) -> Result<Vec<u8>> {
                 .get(index)
                 .ok_or_else(|| Error::Capture("Monitor not found".to_string()))?;
 
-            let image = monitor
+            monitor
                 .capture_image()
-                .map_err(|e| Error::Capture(e.to_string()))?;
-
-            // Convert to PNG bytes
-            let mut buffer = Vec::new();
-            let mut cursor = std::io::Cursor::new(&mut buffer);
-            image
-                .write_to(&mut cursor, image::ImageFormat::Png)
-                .map_err(|e| Error::Capture(e.to_string()))?;
-
-            Ok(buffer)
+                .map_err(|e| Error::Capture(e.to_string()))
         }
         "window" => {
             // TODO: Implement window capture
@@ -89,3 +212,28 @@ pub fn capture_frame(source_id: &str) -> Result<Vec<u8>> {
         _ => Err(Error::Capture("Unknown source type".to_string())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_lock_matches_case_insensitively() {
+        let lock = AppLock {
+            app_name: "Google Chrome".to_string(),
+        };
+        assert!(lock.matches("google chrome"));
+        assert!(lock.matches(" Google Chrome "));
+        assert!(!lock.matches("Google Chrome Helper"));
+        assert!(!lock.matches("Slack"));
+    }
+
+    #[test]
+    fn test_empty_app_lock_matches_nothing() {
+        let lock = AppLock {
+            app_name: "  ".to_string(),
+        };
+        assert!(!lock.matches(""));
+        assert!(!lock.matches("Finder"));
+    }
+}
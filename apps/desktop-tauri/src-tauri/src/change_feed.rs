@@ -0,0 +1,112 @@
+//! Change feed cursors
+//!
+//! Sync clients fetch what changed since their last sync, table by table,
+//! ordered by `(updated_at, id)`. The cursor records the last row seen in
+//! each table so the next page starts right after it, even when several rows
+//! share a timestamp. It is handed out opaque (base64 JSON) so its shape can
+//! change without breaking clients.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Last row returned from a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedPosition {
+    pub updated_at: String,
+    pub id: String,
+}
+
+/// Position reached in every table of the feed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeCursor {
+    #[serde(default)]
+    pub meetings: Option<FeedPosition>,
+    #[serde(default)]
+    pub conversations: Option<FeedPosition>,
+    #[serde(default)]
+    pub messages: Option<FeedPosition>,
+    #[serde(default)]
+    pub deleted: Option<FeedPosition>,
+}
+
+impl ChangeCursor {
+    /// Cursor handed out by `encode`; no cursor starts from the beginning
+    pub fn decode(cursor: Option<&str>) -> Result<Self> {
+        let Some(cursor) = cursor.filter(|c| !c.is_empty()) else {
+            return Ok(Self::default());
+        };
+        let json = BASE64
+            .decode(cursor)
+            .map_err(|_| Error::Parse("Invalid change cursor".to_string()))?;
+        serde_json::from_slice(&json).map_err(|_| Error::Parse("Invalid change cursor".to_string()))
+    }
+
+    pub fn encode(&self) -> Result<String> {
+        Ok(BASE64.encode(serde_json::to_vec(self)?))
+    }
+}
+
+/// Move a table's position to the last of a page of rows
+///
+/// Rows without `updated_at` cannot be paged past and are left out of the
+/// feed by the query, so they never reach here.
+pub fn advance<'a>(
+    position: &mut Option<FeedPosition>,
+    rows: impl IntoIterator<Item = (&'a Option<String>, &'a str)>,
+) {
+    if let Some((Some(updated_at), id)) = rows.into_iter().last() {
+        *position = Some(FeedPosition {
+            updated_at: updated_at.clone(),
+            id: id.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = ChangeCursor {
+            meetings: Some(FeedPosition {
+                updated_at: "2026-02-07T14:30:00.123+00:00".to_string(),
+                id: "m1".to_string(),
+            }),
+            ..Default::default()
+        };
+        let encoded = cursor.encode().unwrap();
+        assert_eq!(ChangeCursor::decode(Some(&encoded)).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_missing_or_invalid_cursor() {
+        assert_eq!(ChangeCursor::decode(None).unwrap(), ChangeCursor::default());
+        assert_eq!(
+            ChangeCursor::decode(Some("")).unwrap(),
+            ChangeCursor::default()
+        );
+        assert!(ChangeCursor::decode(Some("not a cursor")).is_err());
+    }
+
+    #[test]
+    fn test_advance_to_last_row() {
+        let rows = [
+            (Some("2026-02-07T10:00:00+00:00".to_string()), "a"),
+            (Some("2026-02-07T11:00:00+00:00".to_string()), "b"),
+        ];
+        let mut position = None;
+        advance(&mut position, rows.iter().map(|(at, id)| (at, *id)));
+        assert_eq!(position.unwrap().id, "b");
+
+        let mut position = Some(FeedPosition {
+            updated_at: "2026-02-07T09:00:00+00:00".to_string(),
+            id: "z".to_string(),
+        });
+        advance(&mut position, std::iter::empty());
+        assert_eq!(position.unwrap().id, "z");
+    }
+}
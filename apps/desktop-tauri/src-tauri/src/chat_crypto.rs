@@ -0,0 +1,335 @@
+//! End-to-end encryption of chat messages
+//!
+//! Encrypted conversations store only ciphertext. Every device holds an
+//! X25519 key pair; the secret stays in the OS keychain and the public key
+//! is published in `device_keys`. A message is sealed with a random content
+//! key using XChaCha20-Poly1305, and the content key is wrapped for each
+//! device of each participant, the sender's own included:
+//!
+//! - an ephemeral X25519 key pair is drawn per message;
+//! - a device's wrapping key is derived with HKDF-SHA256 from the shared
+//!   secret between the ephemeral key and the device key, salted with the
+//!   ephemeral public key.
+//!
+//! The conversation, sender and device IDs are bound as associated data,
+//! so a sealed message can't be replayed into another conversation or
+//! passed off as someone else's. Devices added after a message was sent
+//! can't read it.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::{Error, Result};
+
+const WRAP_KEY_INFO: &[u8] = b"squadx-live/chat/wrap/v1";
+
+/// Prefix of sealed message content, followed by the base64 JSON envelope
+const SEALED_PREFIX: &str = "e2e.v1:";
+
+/// Shown in place of a message this device has no key for
+pub const UNREADABLE_PLACEHOLDER: &str = "Encrypted message this device can't read";
+
+/// Content key wrapped for one device
+#[derive(Debug, Serialize, Deserialize)]
+struct WrappedKey {
+    nonce: String,
+    key: String,
+}
+
+/// Sealed message as stored in `messages.content`
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedContent {
+    ephemeral_key: String,
+    nonce: String,
+    ciphertext: String,
+    /// Wrapped content keys by device ID
+    keys: HashMap<String, WrappedKey>,
+}
+
+/// A device's public key, as published
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DevicePublicKey {
+    pub device_id: String,
+    pub public_key: String,
+}
+
+/// This device's key pair
+pub struct DeviceKey {
+    device_id: String,
+    secret: StaticSecret,
+}
+
+impl std::fmt::Debug for DeviceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceKey")
+            .field("device_id", &self.device_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeviceKey {
+    /// New key pair for a device
+    pub fn generate(device_id: &str) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            secret: StaticSecret::random_from_rng(rand::rngs::OsRng),
+        }
+    }
+
+    /// Key pair kept by `to_stored`, `None` if it is unreadable or belongs
+    /// to another device ID
+    pub fn from_stored(device_id: &str, stored: &str) -> Option<Self> {
+        let (stored_id, secret) = stored.split_once(':')?;
+        if stored_id != device_id {
+            return None;
+        }
+        let bytes: [u8; 32] = BASE64.decode(secret).ok()?.try_into().ok()?;
+        Some(Self {
+            device_id: device_id.to_string(),
+            secret: StaticSecret::from(bytes),
+        })
+    }
+
+    /// Device ID and secret, for the keychain
+    pub fn to_stored(&self) -> String {
+        format!(
+            "{}:{}",
+            self.device_id,
+            BASE64.encode(self.secret.to_bytes())
+        )
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    pub fn public_key(&self) -> DevicePublicKey {
+        DevicePublicKey {
+            device_id: self.device_id.clone(),
+            public_key: BASE64.encode(PublicKey::from(&self.secret).as_bytes()),
+        }
+    }
+}
+
+fn decode_key(key: &str) -> Result<[u8; 32]> {
+    BASE64
+        .decode(key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| Error::Parse("Invalid encryption key".to_string()))
+}
+
+fn decode_nonce(nonce: &str) -> Result<[u8; 24]> {
+    BASE64
+        .decode(nonce)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| Error::Parse("Invalid nonce".to_string()))
+}
+
+fn random_nonce() -> [u8; 24] {
+    let mut nonce = [0u8; 24];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+fn wrap_key(shared: &[u8], ephemeral_key: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(ephemeral_key), shared)
+        .expand(WRAP_KEY_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn associated_data(conversation_id: &str, sender_id: &str, device_id: Option<&str>) -> Vec<u8> {
+    format!(
+        "{}|{}|{}",
+        conversation_id,
+        sender_id,
+        device_id.unwrap_or_default()
+    )
+    .into_bytes()
+}
+
+fn encrypt(key: &[u8; 32], nonce: &[u8; 24], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(XNonce::from_slice(nonce), Payload { msg, aad })
+        .map_err(|_| Error::External("Failed to encrypt message".to_string()))
+}
+
+fn decrypt(key: &[u8; 32], nonce: &[u8; 24], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(XNonce::from_slice(nonce), Payload { msg, aad })
+        .map_err(|_| Error::External("Failed to decrypt message".to_string()))
+}
+
+/// Whether message content is sealed
+pub fn is_sealed(content: &str) -> bool {
+    content.starts_with(SEALED_PREFIX)
+}
+
+/// Seal message content for the given devices
+pub fn seal(
+    content: &str,
+    conversation_id: &str,
+    sender_id: &str,
+    devices: &[DevicePublicKey],
+) -> Result<String> {
+    if devices.is_empty() {
+        return Err(Error::Input(
+            "No devices to encrypt the message for".to_string(),
+        ));
+    }
+
+    let mut content_key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut content_key);
+    let nonce = random_nonce();
+    let ciphertext = encrypt(
+        &content_key,
+        &nonce,
+        content.as_bytes(),
+        &associated_data(conversation_id, sender_id, None),
+    )?;
+
+    let ephemeral = StaticSecret::random_from_rng(rand::rngs::OsRng);
+    let ephemeral_key = PublicKey::from(&ephemeral);
+    let mut keys = HashMap::new();
+    for device in devices {
+        let shared = ephemeral.diffie_hellman(&PublicKey::from(decode_key(&device.public_key)?));
+        let key_nonce = random_nonce();
+        let wrapped = encrypt(
+            &wrap_key(shared.as_bytes(), ephemeral_key.as_bytes()),
+            &key_nonce,
+            &content_key,
+            &associated_data(conversation_id, sender_id, Some(&device.device_id)),
+        )?;
+        keys.insert(
+            device.device_id.clone(),
+            WrappedKey {
+                nonce: BASE64.encode(key_nonce),
+                key: BASE64.encode(wrapped),
+            },
+        );
+    }
+
+    let sealed = SealedContent {
+        ephemeral_key: BASE64.encode(ephemeral_key.as_bytes()),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+        keys,
+    };
+    Ok(format!(
+        "{}{}",
+        SEALED_PREFIX,
+        BASE64.encode(serde_json::to_vec(&sealed)?)
+    ))
+}
+
+/// Open sealed message content with this device's key
+pub fn open(
+    content: &str,
+    conversation_id: &str,
+    sender_id: &str,
+    device: &DeviceKey,
+) -> Result<String> {
+    let sealed: SealedContent = content
+        .strip_prefix(SEALED_PREFIX)
+        .and_then(|encoded| BASE64.decode(encoded).ok())
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| Error::Parse("Invalid encrypted message".to_string()))?;
+    let wrapped = sealed
+        .keys
+        .get(&device.device_id)
+        .ok_or_else(|| Error::NotFound("Message was not encrypted for this device".to_string()))?;
+
+    let ephemeral_key = decode_key(&sealed.ephemeral_key)?;
+    let shared = device
+        .secret
+        .diffie_hellman(&PublicKey::from(ephemeral_key));
+    let content_key: [u8; 32] = decrypt(
+        &wrap_key(shared.as_bytes(), &ephemeral_key),
+        &decode_nonce(&wrapped.nonce)?,
+        &BASE64
+            .decode(&wrapped.key)
+            .map_err(|e| Error::Parse(format!("Invalid wrapped key: {}", e)))?,
+        &associated_data(conversation_id, sender_id, Some(&device.device_id)),
+    )?
+    .try_into()
+    .map_err(|_| Error::Parse("Invalid content key".to_string()))?;
+
+    let plaintext = decrypt(
+        &content_key,
+        &decode_nonce(&sealed.nonce)?,
+        &BASE64
+            .decode(&sealed.ciphertext)
+            .map_err(|e| Error::Parse(format!("Invalid ciphertext: {}", e)))?,
+        &associated_data(conversation_id, sender_id, None),
+    )?;
+    String::from_utf8(plaintext).map_err(|e| Error::Parse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_for_every_device() {
+        let alice_laptop = DeviceKey::generate("alice-laptop");
+        let alice_desktop = DeviceKey::generate("alice-desktop");
+        let bob = DeviceKey::generate("bob");
+        let devices = [
+            alice_laptop.public_key(),
+            alice_desktop.public_key(),
+            bob.public_key(),
+        ];
+
+        let sealed = seal("meet at 3", "c1", "alice", &devices).unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("meet at 3"));
+
+        for device in [&alice_laptop, &alice_desktop, &bob] {
+            assert_eq!(open(&sealed, "c1", "alice", device).unwrap(), "meet at 3");
+        }
+
+        // A device the message wasn't sealed for can't read it
+        let eve = DeviceKey::generate("eve");
+        assert!(open(&sealed, "c1", "alice", &eve).is_err());
+    }
+
+    #[test]
+    fn test_bound_to_conversation_and_sender() {
+        let bob = DeviceKey::generate("bob");
+        let sealed = seal("hi", "c1", "alice", &[bob.public_key()]).unwrap();
+        assert!(open(&sealed, "c2", "alice", &bob).is_err());
+        assert!(open(&sealed, "c1", "mallory", &bob).is_err());
+    }
+
+    #[test]
+    fn test_stored_key() {
+        let key = DeviceKey::generate("d1");
+        let stored = key.to_stored();
+        let restored = DeviceKey::from_stored("d1", &stored).unwrap();
+        assert_eq!(restored.public_key(), key.public_key());
+
+        // A new device ID means a new key
+        assert!(DeviceKey::from_stored("d2", &stored).is_none());
+        assert!(DeviceKey::from_stored("d1", "d1:not base64").is_none());
+    }
+
+    #[test]
+    fn test_plain_content() {
+        assert!(!is_sealed("hello"));
+        assert!(seal("hi", "c1", "alice", &[]).is_err());
+        let bob = DeviceKey::generate("bob");
+        assert!(open("e2e.v1:garbage", "c1", "alice", &bob).is_err());
+    }
+}
@@ -1,17 +1,27 @@
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::RwLock;
 use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
 
 use crate::cache::SharedCache;
 use crate::commands::chat::Message;
+use crate::commands::encryption;
+use crate::commands::notifications;
+use crate::conversation_channels;
+use crate::state::AppState;
+use crate::supabase::MessageRow;
 use crate::{Error, Result};
 
 const REALTIME_VERSION: &str = "1.0.0";
 const HEARTBEAT_INTERVAL_MS: u64 = 30000;
 
+/// Number of recent message IDs remembered to drop the second copy of a
+/// message that arrives both broadcast and as a database change
+const RECENT_MESSAGE_IDS: usize = 500;
+
 /// Supabase Realtime message format
 #[derive(Debug, Serialize, Deserialize)]
 struct RealtimeMessage {
@@ -22,6 +32,148 @@ struct RealtimeMessage {
     reference: Option<String>,
 }
 
+/// Whether a message comes from a user the user blocked
+async fn is_from_blocked_user(cache: &Option<SharedCache>, message: &Message) -> bool {
+    let (Some(cache), Some(sender_id)) = (cache, &message.sender_id) else {
+        return false;
+    };
+    let blocked = cache.blocks.read().await.is_blocked(sender_id);
+    if blocked {
+        tracing::debug!("Dropping message from blocked user {}", sender_id);
+    }
+    blocked
+}
+
+/// Open the content of a message sealed for an encrypted conversation
+fn open_message(message: &mut Message) {
+    message.content = encryption::open_content(
+        &message.conversation_id,
+        message.sender_id.as_deref(),
+        std::mem::take(&mut message.content),
+    );
+}
+
+/// Payload of `chat:participants-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct ParticipantsChanged {
+    /// Unknown for removals, whose old row only carries its ID
+    pub conversation_id: Option<String>,
+    /// `INSERT`, `UPDATE` or `DELETE`
+    pub change: String,
+}
+
+/// IDs of the messages seen most recently
+#[derive(Debug, Default)]
+struct RecentIds {
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
+
+impl RecentIds {
+    /// Remember an ID, returning whether it is new
+    fn insert(&mut self, id: &str) -> bool {
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > RECENT_MESSAGE_IDS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Message from a database row, with its sender's display name
+async fn message_from_row(app_handle: &AppHandle, row: MessageRow) -> Message {
+    let app_state = app_handle.state::<AppState>();
+    let sender_name = match (&app_state.supabase, &row.sender_id) {
+        (Some(supabase), Some(sender_id)) => supabase
+            .get_user_profiles(std::slice::from_ref(sender_id))
+            .await
+            .ok()
+            .and_then(|profiles| profiles.into_iter().next())
+            .and_then(|profile| profile.display_name),
+        _ => None,
+    };
+
+    Message {
+        id: row.id,
+        conversation_id: row.conversation_id,
+        sender_id: row.sender_id,
+        sender_name: sender_name.unwrap_or_else(|| "Unknown".to_string()),
+        content: row.content,
+        message_type: row.message_type,
+        created_at: row.created_at,
+        attachments: row.attachments,
+        parent_message_id: row.parent_message_id,
+        reply_count: row.reply_count,
+        last_reply_at: row.last_reply_at,
+        session_id: row.session_id,
+    }
+}
+
+/// Handle a database change, which also covers messages and participants
+/// written by clients that don't broadcast (web, mobile)
+async fn handle_postgres_change(
+    app_handle: &AppHandle,
+    inner: &RwLock<ChatRealtimeClientInner>,
+    cache: &Option<SharedCache>,
+    data: &serde_json::Value,
+) {
+    let change = data
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default();
+
+    match data.get("table").and_then(|t| t.as_str()) {
+        Some("messages") if change == "INSERT" => {
+            let Some(row) = data
+                .get("record")
+                .and_then(|record| serde_json::from_value::<MessageRow>(record.clone()).ok())
+            else {
+                return;
+            };
+            if !inner.write().await.recent_messages.insert(&row.id) {
+                return;
+            }
+
+            let mut message = message_from_row(app_handle, row).await;
+            if is_from_blocked_user(cache, &message).await {
+                return;
+            }
+            open_message(&mut message);
+            if let Some(cache) = cache {
+                cache
+                    .messages
+                    .write()
+                    .await
+                    .invalidate_conversation(&message.conversation_id);
+            }
+            let _ = app_handle.emit("chat:new-message", &message);
+            crate::commands::badge::request_refresh(app_handle);
+            tauri::async_runtime::spawn(notifications::notify_message(app_handle.clone(), message));
+        }
+        Some("conversation_participants") => {
+            let conversation_id = data
+                .get("record")
+                .or_else(|| data.get("old_record"))
+                .and_then(|record| record.get("conversation_id"))
+                .and_then(|id| id.as_str())
+                .map(str::to_string);
+            let _ = app_handle.emit(
+                "chat:participants-changed",
+                ParticipantsChanged {
+                    conversation_id,
+                    change: change.to_string(),
+                },
+            );
+        }
+        _ => {}
+    }
+}
+
 /// Chat Realtime Client
 #[derive(Debug)]
 pub struct ChatRealtimeClient {
@@ -35,6 +187,10 @@ struct ChatRealtimeClientInner {
     access_token: Option<String>,
     is_connected: bool,
     message_tx: Option<tokio::sync::mpsc::Sender<RealtimeMessage>>,
+    /// Messages already emitted
+    recent_messages: RecentIds,
+    /// Conversation channels joined on the current socket
+    joined: HashSet<String>,
 }
 
 impl ChatRealtimeClient {
@@ -46,6 +202,8 @@ impl ChatRealtimeClient {
                 access_token: None,
                 is_connected: false,
                 message_tx: None,
+                recent_messages: RecentIds::default(),
+                joined: HashSet::new(),
             })),
         }
     }
@@ -104,6 +262,7 @@ impl ChatRealtimeClient {
             let mut inner = self.inner.write().await;
             inner.is_connected = true;
             inner.message_tx = Some(message_tx);
+            inner.joined.clear();
         }
 
         let inner_clone = self.inner.clone();
@@ -136,12 +295,21 @@ impl ChatRealtimeClient {
                                         if let Some(event_type) = payload.get("type").and_then(|t| t.as_str()) {
                                             match event_type {
                                                 "chat_message" => {
-                                                    if let Ok(message) =
+                                                    if let Ok(mut message) =
                                                         serde_json::from_value::<Message>(
                                                             payload.clone(),
                                                         )
                                                     {
+                                                        if !inner_clone.write().await.recent_messages.insert(&message.id) {
+                                                            continue;
+                                                        }
+                                                        if is_from_blocked_user(&cache_clone, &message).await {
+                                                            continue;
+                                                        }
+                                                        open_message(&mut message);
                                                         let _ = app_handle_clone.emit("chat:new-message", &message);
+                                                        crate::commands::badge::request_refresh(&app_handle_clone);
+                                                        tauri::async_runtime::spawn(notifications::notify_message(app_handle_clone.clone(), message));
                                                     }
                                                 }
                                                 "presence_change" => {
@@ -158,11 +326,31 @@ impl ChatRealtimeClient {
                                                     }
                                                     let _ = app_handle_clone.emit("chat:presence-change", payload);
                                                 }
+                                                "poll_update" => {
+                                                    let _ = app_handle_clone.emit("chat:poll-update", payload);
+                                                }
+                                                "read_receipt" => {
+                                                    if let Some(receipt) = payload.get("receipt") {
+                                                        let _ = app_handle_clone
+                                                            .emit("chat:read-receipt", receipt);
+                                                    }
+                                                }
                                                 _ => {}
                                             }
                                         }
                                     }
                                 }
+                                "postgres_changes" => {
+                                    if let Some(data) = realtime_msg.payload.get("data") {
+                                        handle_postgres_change(
+                                            &app_handle_clone,
+                                            &inner_clone,
+                                            &cache_clone,
+                                            data,
+                                        )
+                                        .await;
+                                    }
+                                }
                                 "presence_diff" | "presence_state" => {
                                     // Handle presence diff to update cache
                                     if let Some(ref cache) = cache_clone {
@@ -237,6 +425,35 @@ impl ChatRealtimeClient {
                 let _ = write.send(WsMessage::Text(json)).await;
             }
 
+            // Database changes, filtered by row level security, for messages
+            // and participants written without a broadcast
+            let changes_msg = RealtimeMessage {
+                topic: format!("realtime:chat:changes:{}", user_id_clone),
+                event: "phx_join".to_string(),
+                payload: serde_json::json!({
+                    "config": {
+                        "postgres_changes": [
+                            {
+                                "event": "INSERT",
+                                "schema": "public",
+                                "table": "messages"
+                            },
+                            {
+                                "event": "*",
+                                "schema": "public",
+                                "table": "conversation_participants"
+                            }
+                        ]
+                    },
+                    "access_token": access_token_clone
+                }),
+                reference: Some("2".to_string()),
+            };
+
+            if let Ok(json) = serde_json::to_string(&changes_msg) {
+                let _ = write.send(WsMessage::Text(json)).await;
+            }
+
             // Handle outgoing messages and heartbeat
             loop {
                 tokio::select! {
@@ -277,10 +494,12 @@ impl ChatRealtimeClient {
 
     /// Broadcast a message to a conversation channel
     pub async fn broadcast_message(&self, conversation_id: &str, message: &Message) -> Result<()> {
-        let inner = self.inner.read().await;
+        let mut inner = self.inner.write().await;
+        // The sender shows it already; its database change is dropped
+        inner.recent_messages.insert(&message.id);
 
         if let Some(ref tx) = inner.message_tx {
-            let channel_topic = format!("realtime:chat:{}", conversation_id);
+            let channel_topic = conversation_channels::topic(conversation_id);
             let broadcast_msg = RealtimeMessage {
                 topic: channel_topic,
                 event: "broadcast".to_string(),
@@ -295,7 +514,8 @@ impl ChatRealtimeClient {
                         "sender_name": message.sender_name,
                         "content": message.content,
                         "message_type": message.message_type,
-                        "created_at": message.created_at
+                        "created_at": message.created_at,
+                        "parent_message_id": message.parent_message_id
                     }
                 }),
                 reference: None,
@@ -309,35 +529,134 @@ impl ChatRealtimeClient {
         Ok(())
     }
 
-    /// Subscribe to a specific conversation channel
-    pub async fn subscribe_to_conversation(&self, conversation_id: &str) -> Result<()> {
+    /// Broadcast an updated scheduling poll to a conversation channel
+    pub async fn broadcast_poll_update<T: Serialize>(
+        &self,
+        conversation_id: &str,
+        poll: &T,
+    ) -> Result<()> {
         let inner = self.inner.read().await;
-        let access_token = inner
-            .access_token
-            .clone()
-            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
         if let Some(ref tx) = inner.message_tx {
-            let channel_topic = format!("realtime:chat:{}", conversation_id);
-            let join_msg = RealtimeMessage {
+            let channel_topic = conversation_channels::topic(conversation_id);
+            let broadcast_msg = RealtimeMessage {
                 topic: channel_topic,
-                event: "phx_join".to_string(),
+                event: "broadcast".to_string(),
                 payload: serde_json::json!({
-                    "config": {
-                        "broadcast": {
-                            "self": false
-                        }
-                    },
-                    "access_token": access_token
+                    "type": "broadcast",
+                    "event": "poll_update",
+                    "payload": {
+                        "type": "poll_update",
+                        "conversation_id": conversation_id,
+                        "poll": poll
+                    }
+                }),
+                reference: None,
+            };
+
+            tx.send(broadcast_msg)
+                .await
+                .map_err(|e| Error::Network(format!("Failed to send poll update: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Broadcast that a participant read a conversation or a thread of it
+    pub async fn broadcast_read_receipt<T: Serialize>(
+        &self,
+        conversation_id: &str,
+        receipt: &T,
+    ) -> Result<()> {
+        let inner = self.inner.read().await;
+
+        if let Some(ref tx) = inner.message_tx {
+            let channel_topic = conversation_channels::topic(conversation_id);
+            let broadcast_msg = RealtimeMessage {
+                topic: channel_topic,
+                event: "broadcast".to_string(),
+                payload: serde_json::json!({
+                    "type": "broadcast",
+                    "event": "read_receipt",
+                    "payload": {
+                        "type": "read_receipt",
+                        "conversation_id": conversation_id,
+                        "receipt": receipt
+                    }
                 }),
-                reference: Some(uuid::Uuid::new_v4().to_string()),
+                reference: None,
             };
 
-            tx.send(join_msg)
+            tx.send(broadcast_msg)
                 .await
-                .map_err(|e| Error::Network(format!("Failed to subscribe: {}", e)))?;
+                .map_err(|e| Error::Network(format!("Failed to send read receipt: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Join a conversation's channel, unless already joined on this socket
+    pub async fn subscribe_to_conversation(&self, conversation_id: &str) -> Result<()> {
+        let mut inner = self.inner.write().await;
+        let access_token = inner
+            .access_token
+            .clone()
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let Some(tx) = inner.message_tx.clone() else {
+            return Ok(());
+        };
+        let channel_topic = conversation_channels::topic(conversation_id);
+        if !inner.joined.insert(channel_topic.clone()) {
+            return Ok(());
+        }
+
+        let join_msg = RealtimeMessage {
+            topic: channel_topic.clone(),
+            event: "phx_join".to_string(),
+            payload: serde_json::json!({
+                "config": {
+                    "broadcast": {
+                        "self": false
+                    }
+                },
+                "access_token": access_token
+            }),
+            reference: Some(uuid::Uuid::new_v4().to_string()),
+        };
+
+        if let Err(e) = tx.send(join_msg).await {
+            inner.joined.remove(&channel_topic);
+            return Err(Error::Network(format!("Failed to subscribe: {}", e)));
         }
 
+        tracing::debug!("Joined {}", channel_topic);
+        Ok(())
+    }
+
+    /// Leave a conversation's channel, if joined
+    pub async fn unsubscribe_from_conversation(&self, conversation_id: &str) -> Result<()> {
+        let mut inner = self.inner.write().await;
+        let channel_topic = conversation_channels::topic(conversation_id);
+        if !inner.joined.remove(&channel_topic) {
+            return Ok(());
+        }
+        let Some(tx) = inner.message_tx.clone() else {
+            return Ok(());
+        };
+
+        let leave_msg = RealtimeMessage {
+            topic: channel_topic.clone(),
+            event: "phx_leave".to_string(),
+            payload: serde_json::json!({}),
+            reference: Some(uuid::Uuid::new_v4().to_string()),
+        };
+
+        tx.send(leave_msg)
+            .await
+            .map_err(|e| Error::Network(format!("Failed to unsubscribe: {}", e)))?;
+
+        tracing::debug!("Left {}", channel_topic);
         Ok(())
     }
 
@@ -346,6 +665,7 @@ impl ChatRealtimeClient {
         let mut inner = self.inner.write().await;
         inner.is_connected = false;
         inner.message_tx = None;
+        inner.joined.clear();
     }
 }
 
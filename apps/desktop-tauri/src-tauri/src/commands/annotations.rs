@@ -0,0 +1,316 @@
+//! Annotation commands
+//!
+//! While the host streams natively, viewers can draw on the shared screen:
+//! pen strokes, arrows, highlights and a laser pointer arrive on each
+//! viewer's annotation channel (see `crate::annotations`). The host shows
+//! them in a transparent, click-through window laid over the shared display,
+//! each viewer in their cursor color. That window listens for
+//! `annotation:changed` (every stroke on the board) and `annotation:laser`.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, State, WebviewUrl,
+    WebviewWindowBuilder,
+};
+use tokio::sync::{mpsc, RwLock};
+
+use crate::annotations::{AnnotationBoard, AnnotationMessage, Stroke};
+use crate::capture;
+use crate::commands::signaling::cursor_color;
+use crate::commands::stream::StreamState;
+use crate::peer::NativePeer;
+use crate::{Error, Result};
+
+/// Label of the overlay window
+pub const OVERLAY_WINDOW_LABEL: &str = "annotation-overlay";
+
+/// Frontend route rendering the overlay
+const OVERLAY_ROUTE: &str = "annotation-overlay";
+
+pub struct AnnotationState {
+    pub inner: Arc<RwLock<AnnotationStateInner>>,
+}
+
+#[derive(Default)]
+pub struct AnnotationStateInner {
+    pub board: AnnotationBoard,
+    /// Whether the overlay is open; viewers' drawings are ignored otherwise
+    pub enabled: bool,
+}
+
+impl Default for AnnotationState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(AnnotationStateInner::default())),
+        }
+    }
+}
+
+// ==========================================
+// Event Types
+// ==========================================
+
+/// One user's strokes, drawn in their color
+#[derive(Debug, Clone, Serialize)]
+pub struct UserAnnotations {
+    pub user_id: String,
+    pub color: String,
+    pub strokes: Vec<Stroke>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LaserPoint {
+    pub user_id: String,
+    pub color: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Board contents by user, in a stable order
+fn snapshot(board: &AnnotationBoard) -> Vec<UserAnnotations> {
+    let mut annotations: Vec<UserAnnotations> = board
+        .strokes()
+        .iter()
+        .filter(|(_, strokes)| !strokes.is_empty())
+        .map(|(user_id, strokes)| UserAnnotations {
+            user_id: user_id.clone(),
+            color: cursor_color(user_id).to_string(),
+            strokes: strokes.clone(),
+        })
+        .collect();
+    annotations.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+    annotations
+}
+
+fn emit_board(app_handle: &AppHandle, board: &AnnotationBoard) {
+    if let Err(e) = app_handle.emit("annotation:changed", snapshot(board)) {
+        tracing::error!("Failed to emit annotation event: {}", e);
+    }
+}
+
+/// Route a viewer's annotation channel to the board, preserving order
+pub(crate) fn attach_peer(app_handle: &AppHandle, peer_id: &str, peer: &NativePeer) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    peer.on_annotation_message(move |text| {
+        let _ = tx.send(text);
+    });
+
+    let app_handle = app_handle.clone();
+    let peer_id = peer_id.to_string();
+    tokio::spawn(async move {
+        while let Some(text) = rx.recv().await {
+            handle_message(&app_handle, &peer_id, &text).await;
+        }
+    });
+}
+
+async fn handle_message(app_handle: &AppHandle, user_id: &str, text: &str) {
+    let message: AnnotationMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            tracing::debug!("Ignoring malformed annotation from {}: {}", user_id, e);
+            return;
+        }
+    };
+
+    let annotation_state = app_handle.state::<AnnotationState>();
+    let mut state = annotation_state.inner.write().await;
+    if !state.enabled {
+        return;
+    }
+
+    match message {
+        AnnotationMessage::Stroke {
+            id,
+            tool,
+            points,
+            width,
+        } => match Stroke::new(id, tool, points, width) {
+            Ok(stroke) => state.board.add(user_id, stroke),
+            Err(e) => {
+                tracing::debug!("Ignoring stroke from {}: {}", user_id, e);
+                return;
+            }
+        },
+        AnnotationMessage::Laser { x, y } => {
+            if !x.is_finite() || !y.is_finite() {
+                return;
+            }
+            let point = LaserPoint {
+                user_id: user_id.to_string(),
+                color: cursor_color(user_id).to_string(),
+                x: x.clamp(0.0, 1.0),
+                y: y.clamp(0.0, 1.0),
+            };
+            if let Err(e) = app_handle.emit("annotation:laser", &point) {
+                tracing::error!("Failed to emit laser event: {}", e);
+            }
+            return;
+        }
+        AnnotationMessage::Remove { id } => {
+            if !state.board.remove(user_id, &id) {
+                return;
+            }
+        }
+        AnnotationMessage::Clear => state.board.clear(Some(user_id)),
+    }
+    emit_board(app_handle, &state.board);
+}
+
+/// Tell viewers to wipe their drawings, all of them or only `user_id`
+async fn send_clear(app_handle: &AppHandle, user_id: Option<&str>) -> Result<()> {
+    let text = serde_json::to_string(&AnnotationMessage::Clear)?;
+    let channels: Vec<_> = {
+        let stream_state = app_handle.state::<StreamState>();
+        let state = stream_state.inner.read().await;
+        state
+            .peers
+            .iter()
+            .filter(|(peer_id, _)| user_id.is_none_or(|user_id| user_id == peer_id.as_str()))
+            .map(|(peer_id, peer)| (peer_id.clone(), peer.annotation_channel()))
+            .collect()
+    };
+
+    for (peer_id, channel) in channels {
+        if let Err(e) = channel.send_text(text.clone()).await {
+            tracing::debug!("Failed to send annotation clear to {}: {}", peer_id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Create the overlay window, hidden
+fn build_overlay(app_handle: &AppHandle) -> Result<tauri::WebviewWindow> {
+    let window = WebviewWindowBuilder::new(
+        app_handle,
+        OVERLAY_WINDOW_LABEL,
+        WebviewUrl::App(OVERLAY_ROUTE.into()),
+    )
+    .title("Annotations")
+    .transparent(true)
+    .decorations(false)
+    .shadow(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .resizable(false)
+    .focused(false)
+    .visible(false)
+    .build()?;
+    Ok(window)
+}
+
+/// Close the overlay and drop every drawing once the native stream stops
+pub(crate) async fn native_stream_stopped(app_handle: &AppHandle) {
+    let annotation_state = app_handle.state::<AnnotationState>();
+    let mut state = annotation_state.inner.write().await;
+    state.enabled = false;
+    state.board.clear(None);
+
+    if let Some(window) = app_handle.get_webview_window(OVERLAY_WINDOW_LABEL) {
+        if let Err(e) = window.close() {
+            tracing::warn!("Failed to close annotation overlay: {}", e);
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Show the overlay over the shared display and accept viewers' drawings
+/// (host only, while streaming natively)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn open_annotation_overlay(
+    stream_state: State<'_, StreamState>,
+    annotation_state: State<'_, AnnotationState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let source_id = stream_state
+        .inner
+        .read()
+        .await
+        .source
+        .as_ref()
+        .map(|(source_id, _)| source_id.clone())
+        .ok_or_else(|| Error::Session("Not streaming".to_string()))?;
+
+    let window = match app_handle.get_webview_window(OVERLAY_WINDOW_LABEL) {
+        Some(window) => window,
+        None => build_overlay(&app_handle)?,
+    };
+
+    // Cover the shared display; the whole screen it is on otherwise
+    match capture::monitor_bounds(&source_id) {
+        Ok((x, y, width, height)) => {
+            window.set_position(PhysicalPosition::new(x, y))?;
+            window.set_size(PhysicalSize::new(width, height))?;
+        }
+        Err(e) => {
+            tracing::debug!("Overlay falls back to the current monitor: {}", e);
+            window.maximize()?;
+        }
+    }
+    // Clicks go through to the apps being annotated
+    window.set_ignore_cursor_events(true)?;
+    window.show()?;
+
+    let mut state = annotation_state.inner.write().await;
+    state.enabled = true;
+    emit_board(&app_handle, &state.board);
+
+    tracing::info!("Annotation overlay opened");
+    Ok(())
+}
+
+/// Hide the overlay and stop accepting drawings; strokes are kept for when
+/// it opens again
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn close_annotation_overlay(
+    annotation_state: State<'_, AnnotationState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    annotation_state.inner.write().await.enabled = false;
+    if let Some(window) = app_handle.get_webview_window(OVERLAY_WINDOW_LABEL) {
+        window.hide()?;
+    }
+
+    tracing::info!("Annotation overlay closed");
+    Ok(())
+}
+
+/// Get every stroke on the board, by user
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_annotations(
+    annotation_state: State<'_, AnnotationState>,
+) -> Result<Vec<UserAnnotations>> {
+    Ok(snapshot(&annotation_state.inner.read().await.board))
+}
+
+/// Clear one viewer's drawings, or everyone's, on the overlay and on the
+/// viewers' screens
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn clear_annotations(
+    user_id: Option<String>,
+    annotation_state: State<'_, AnnotationState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    {
+        let mut state = annotation_state.inner.write().await;
+        state.board.clear(user_id.as_deref());
+        emit_board(&app_handle, &state.board);
+    }
+    send_clear(&app_handle, user_id.as_deref()).await?;
+
+    tracing::info!("Annotations cleared");
+    Ok(())
+}
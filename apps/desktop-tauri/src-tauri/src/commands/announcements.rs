@@ -0,0 +1,173 @@
+//! App announcement commands
+//!
+//! Announcements are polled at startup and then periodically. Each one that
+//! applies to this build and was not dismissed is emitted once per launch as
+//! `app:announcement`; the frontend can also list them on demand.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::RwLock;
+
+use crate::announcements::{self, DismissedAnnouncements, DISMISSED_FILE};
+use crate::state::AppState;
+use crate::supabase::AnnouncementRow;
+use crate::{Error, Result};
+
+/// How often announcements are polled
+const ANNOUNCEMENT_POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+pub struct AnnouncementState {
+    pub inner: Arc<RwLock<AnnouncementStateInner>>,
+}
+
+#[derive(Default)]
+pub struct AnnouncementStateInner {
+    /// Dismissals, loaded from disk on first use
+    pub dismissed: Option<DismissedAnnouncements>,
+    /// Announcements already emitted since launch
+    pub emitted: HashSet<String>,
+}
+
+impl Default for AnnouncementState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(AnnouncementStateInner::default())),
+        }
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn dismissed_path(app_handle: &AppHandle) -> Result<PathBuf> {
+    Ok(app_handle.path().app_config_dir()?.join(DISMISSED_FILE))
+}
+
+/// Active announcements for this version and platform, and every active one
+async fn fetch_applicable(
+    app_handle: &AppHandle,
+) -> Result<(Vec<AnnouncementRow>, Vec<AnnouncementRow>)> {
+    let app_state = app_handle.state::<AppState>();
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let active = supabase.get_active_announcements().await?;
+    let version = &app_handle.package_info().version;
+    let applicable = active
+        .iter()
+        .filter(|a| announcements::applies_to(a, version, std::env::consts::OS))
+        .cloned()
+        .collect();
+    Ok((applicable, active))
+}
+
+/// Load dismissals on first use
+fn dismissed<'a>(
+    app_handle: &AppHandle,
+    state: &'a mut AnnouncementStateInner,
+) -> &'a mut DismissedAnnouncements {
+    state
+        .dismissed
+        .get_or_insert_with(|| match dismissed_path(app_handle) {
+            Ok(path) => DismissedAnnouncements::load(&path),
+            Err(_) => DismissedAnnouncements::default(),
+        })
+}
+
+/// Poll announcements and emit the new ones
+async fn check_announcements(app_handle: &AppHandle) -> Result<()> {
+    let (applicable, active) = fetch_applicable(app_handle).await?;
+
+    let announcement_state = app_handle.state::<AnnouncementState>();
+    let mut state = announcement_state.inner.write().await;
+
+    // Dismissals of announcements that were taken down are no longer needed
+    let dismissed_now = dismissed(app_handle, &mut state);
+    if dismissed_now.retain_active(&active) {
+        dismissed_now.save(&dismissed_path(app_handle)?)?;
+    }
+
+    for announcement in applicable {
+        if dismissed(app_handle, &mut state).contains(&announcement.id)
+            || !state.emitted.insert(announcement.id.clone())
+        {
+            continue;
+        }
+        tracing::info!("Announcement: {}", announcement.title);
+        if let Err(e) = app_handle.emit("app:announcement", &announcement) {
+            tracing::error!("Failed to emit announcement event: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Poll announcements from startup on, emitting each new one once
+pub async fn watch_announcements(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(ANNOUNCEMENT_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = check_announcements(&app_handle).await {
+            tracing::debug!("Failed to check announcements: {}", e);
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the announcements for this version and platform the user has not
+/// dismissed
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_announcements(
+    announcement_state: State<'_, AnnouncementState>,
+    app_handle: AppHandle,
+) -> Result<Vec<AnnouncementRow>> {
+    let (applicable, _) = fetch_applicable(&app_handle).await?;
+
+    let mut state = announcement_state.inner.write().await;
+    let dismissed = dismissed(&app_handle, &mut state);
+    Ok(applicable
+        .into_iter()
+        .filter(|a| !dismissed.contains(&a.id))
+        .collect())
+}
+
+/// Hide an announcement for good
+///
+/// Some notices (a version that no longer works) cannot be dismissed.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn dismiss_announcement(
+    announcement_id: String,
+    announcement_state: State<'_, AnnouncementState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let (applicable, _) = fetch_applicable(&app_handle).await?;
+    let announcement = applicable
+        .iter()
+        .find(|a| a.id == announcement_id)
+        .ok_or_else(|| Error::NotFound(format!("Announcement {} not found", announcement_id)))?;
+    if !announcement.dismissible {
+        return Err(Error::Config(
+            "This announcement cannot be dismissed".to_string(),
+        ));
+    }
+
+    let path = dismissed_path(&app_handle)?;
+    let mut state = announcement_state.inner.write().await;
+    let dismissed = dismissed(&app_handle, &mut state);
+    if dismissed.insert(&announcement_id) {
+        dismissed.save(&path)?;
+        tracing::info!("Announcement {} dismissed", announcement_id);
+    }
+    Ok(())
+}
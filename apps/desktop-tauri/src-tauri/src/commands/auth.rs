@@ -4,8 +4,9 @@
 //! Tokens are stored in the OS keychain and never exposed to the frontend.
 
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::remote_wipe;
 use crate::secure_storage::{
     self, clear_session, is_session_expired,
     store_session, SafeUserInfo, StoredSession,
@@ -110,10 +111,12 @@ async fn clear_app_state(state: &AppState) {
 /// Sign in with email and password
 /// Returns only safe user info (no tokens exposed to frontend)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn login(
     email: String,
     password: String,
     state: State<'_, AppState>,
+    app_handle: AppHandle,
 ) -> Result<SafeUserInfo> {
     let supabase_url = get_supabase_url()?;
     let anon_key = get_supabase_anon_key()?;
@@ -172,6 +175,7 @@ pub async fn login(
 
     store_session(&session)?;
     update_app_state(&state, &session).await;
+    remote_wipe::check_after_sign_in(&app_handle).await?;
 
     tracing::info!("User {} logged in successfully", session.user_id);
 
@@ -184,10 +188,12 @@ pub async fn login(
 
 /// Sign up with email and password
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn signup(
     email: String,
     password: String,
     state: State<'_, AppState>,
+    app_handle: AppHandle,
 ) -> Result<SafeUserInfo> {
     let supabase_url = get_supabase_url()?;
     let anon_key = get_supabase_anon_key()?;
@@ -245,6 +251,7 @@ pub async fn signup(
 
     store_session(&session)?;
     update_app_state(&state, &session).await;
+    remote_wipe::check_after_sign_in(&app_handle).await?;
 
     tracing::info!("User {} signed up successfully", session.user_id);
 
@@ -257,6 +264,7 @@ pub async fn signup(
 
 /// Logout and clear all stored credentials
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn logout(state: State<'_, AppState>) -> Result<()> {
     // Try to invalidate token on server (best effort)
     if let Some(session) = secure_storage::get_session() {
@@ -281,7 +289,11 @@ pub async fn logout(state: State<'_, AppState>) -> Result<()> {
 
 /// Get current session info (safe, no tokens)
 #[tauri::command]
-pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<SafeUserInfo>> {
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_current_user(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<Option<SafeUserInfo>> {
     // Check if already in state
     {
         let inner = state.inner.read().await;
@@ -295,17 +307,26 @@ pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<SafeU
     }
 
     // Try to restore from secure storage
+    let user = restore_session(&state).await?;
+    if user.is_some() {
+        remote_wipe::check_after_sign_in(&app_handle).await?;
+    }
+    Ok(user)
+}
+
+/// Sign back in with the session in secure storage, if it is still valid
+async fn restore_session(state: &AppState) -> Result<Option<SafeUserInfo>> {
     if let Some(session) = secure_storage::get_session() {
         // Check if token is expired
         if is_session_expired() {
             tracing::info!("Stored token is expired, attempting refresh");
             // Try to refresh
-            match refresh_token_internal(&state).await {
+            match refresh_token_internal(state).await {
                 Ok(user_info) => return Ok(Some(user_info)),
                 Err(e) => {
                     tracing::warn!("Token refresh failed: {}", e);
                     clear_session()?;
-                    clear_app_state(&state).await;
+                    clear_app_state(state).await;
                     return Ok(None);
                 }
             }
@@ -326,7 +347,7 @@ pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<SafeU
                         expires_at: session.expires_at,
                     };
 
-                    update_app_state(&state, &updated_session).await;
+                    update_app_state(state, &updated_session).await;
 
                     return Ok(Some(SafeUserInfo {
                         id: updated_session.user_id,
@@ -337,12 +358,12 @@ pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<SafeU
                 Err(e) => {
                     tracing::warn!("Token validation failed: {}, attempting refresh", e);
                     // Try to refresh
-                    match refresh_token_internal(&state).await {
+                    match refresh_token_internal(state).await {
                         Ok(user_info) => return Ok(Some(user_info)),
                         Err(e) => {
                             tracing::warn!("Token refresh failed: {}", e);
                             clear_session()?;
-                            clear_app_state(&state).await;
+                            clear_app_state(state).await;
                             return Ok(None);
                         }
                     }
@@ -351,7 +372,7 @@ pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<SafeU
         }
 
         // No Supabase client (mock mode), trust stored session
-        update_app_state(&state, &session).await;
+        update_app_state(state, &session).await;
         return Ok(Some(SafeUserInfo {
             id: session.user_id,
             email: session.email,
@@ -364,6 +385,7 @@ pub async fn get_current_user(state: State<'_, AppState>) -> Result<Option<SafeU
 
 /// Refresh the access token using the refresh token
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn refresh_token(state: State<'_, AppState>) -> Result<SafeUserInfo> {
     refresh_token_internal(&state).await
 }
@@ -429,6 +451,7 @@ async fn refresh_token_internal(state: &AppState) -> Result<SafeUserInfo> {
 
 /// Check if user is authenticated (without validating token)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn is_authenticated(state: State<'_, AppState>) -> Result<bool> {
     let inner = state.inner.read().await;
     if inner.user.is_some() {
@@ -441,6 +464,7 @@ pub async fn is_authenticated(state: State<'_, AppState>) -> Result<bool> {
 
 /// Validate the current token (triggers refresh if needed)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn validate_token(state: State<'_, AppState>) -> Result<bool> {
     // This now just checks if we have a valid session
     match get_current_user(state).await? {
@@ -0,0 +1,467 @@
+//! Automation API commands
+//!
+//! While the API is enabled the app serves it on the loopback interface (see
+//! `automation`). Actions the user hasn't allowed or denied for good are put
+//! to them as `automation:permission-request`, focusing the main window, and
+//! wait up to `PROMPT_TIMEOUT` for `respond_to_automation_request`. Each
+//! action carried out is emitted as `automation:performed`, so the frontend
+//! can follow up (e.g. open the session that was started).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, RwLock};
+
+use crate::automation::{
+    self, AutomationAction, AutomationPermission, AutomationSettings, RequestHead,
+};
+use crate::commands::calendar::CreateMeetingParams;
+use crate::commands::session::CreateSessionOptions;
+use crate::secure_storage::{self, CredentialKey};
+use crate::{Error, Result};
+
+/// How long an action waits for the user to allow it
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a client has to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct AutomationState {
+    pub inner: Arc<RwLock<AutomationStateInner>>,
+}
+
+#[derive(Default)]
+pub struct AutomationStateInner {
+    pub settings: AutomationSettings,
+    /// Accept loop, running while the API is enabled
+    pub server: Option<tauri::async_runtime::JoinHandle<()>>,
+    /// Permission prompts waiting for an answer, by ID
+    prompts: HashMap<String, oneshot::Sender<PromptAnswer>>,
+}
+
+impl Default for AutomationState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(AutomationStateInner::default())),
+        }
+    }
+}
+
+/// The user's answer to a permission prompt
+struct PromptAnswer {
+    allow: bool,
+    remember: bool,
+}
+
+/// Whether the API is on, where, and what it may do without asking
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationStatus {
+    pub enabled: bool,
+    pub listening: bool,
+    pub port: u16,
+    pub permissions: HashMap<AutomationAction, AutomationPermission>,
+}
+
+/// Payload of `automation:permission-request`
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionRequest {
+    pub id: String,
+    pub action: AutomationAction,
+    pub description: String,
+}
+
+/// Payload of `automation:performed`
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationPerformed {
+    pub action: AutomationAction,
+    pub result: serde_json::Value,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf> {
+    Ok(app_handle
+        .path()
+        .app_config_dir()?
+        .join(automation::SETTINGS_FILE))
+}
+
+fn status(state: &AutomationStateInner) -> AutomationStatus {
+    AutomationStatus {
+        enabled: state.settings.enabled,
+        listening: state.server.is_some(),
+        port: automation::PORT,
+        permissions: state.settings.permissions.clone(),
+    }
+}
+
+/// Token of the API, created on first use
+fn api_token() -> Result<String> {
+    if let Some(token) = secure_storage::get_credential(CredentialKey::AutomationToken) {
+        return Ok(token);
+    }
+    let token = automation::generate_token();
+    secure_storage::store_credential(CredentialKey::AutomationToken, &token)?;
+    Ok(token)
+}
+
+/// Listen on the loopback port and serve requests until aborted
+async fn start_server(app_handle: &AppHandle) -> Result<tauri::async_runtime::JoinHandle<()>> {
+    api_token()?;
+    let listener = TcpListener::bind(("127.0.0.1", automation::PORT))
+        .await
+        .map_err(|e| {
+            Error::Network(format!(
+                "Failed to listen on port {}: {}",
+                automation::PORT,
+                e
+            ))
+        })?;
+    tracing::info!("Automation API listening on port {}", automation::PORT);
+
+    let app_handle = app_handle.clone();
+    Ok(tauri::async_runtime::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve_connection(stream, app_handle.clone()));
+                }
+                Err(e) => {
+                    tracing::error!("Failed to accept automation connection: {}", e);
+                    break;
+                }
+            }
+        }
+    }))
+}
+
+/// Serve the API again if it was enabled when the app last ran
+pub async fn restore(app_handle: AppHandle) {
+    let settings = match settings_path(&app_handle) {
+        Ok(path) => AutomationSettings::load(&path),
+        Err(e) => {
+            tracing::warn!("Failed to locate automation settings: {}", e);
+            return;
+        }
+    };
+    let enabled = settings.enabled;
+
+    let automation_state = app_handle.state::<AutomationState>();
+    let mut state = automation_state.inner.write().await;
+    state.settings = settings;
+    if enabled {
+        match start_server(&app_handle).await {
+            Ok(server) => state.server = Some(server),
+            Err(e) => tracing::warn!("Automation API not started: {}", e),
+        }
+    }
+}
+
+/// Stop serving the API and drop pending prompts
+pub async fn stop(app_handle: &AppHandle) {
+    let automation_state = app_handle.state::<AutomationState>();
+    let mut state = automation_state.inner.write().await;
+    if let Some(server) = state.server.take() {
+        server.abort();
+        tracing::info!("Automation API stopped");
+    }
+    state.prompts.clear();
+}
+
+/// Answer one request; the connection is closed afterwards
+async fn serve_connection(mut stream: TcpStream, app_handle: AppHandle) {
+    let (status, body) = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok((head, body))) => handle_request(&app_handle, head, body).await,
+        Ok(Err((status, message))) => (status, serde_json::json!({ "error": message })),
+        Err(_) => return,
+    };
+    if let Err(e) = stream.write_all(&automation::response(status, &body)).await {
+        tracing::debug!("Failed to answer automation request: {}", e);
+    }
+}
+
+/// Read a request's head and body, or the status to refuse it with
+async fn read_request(
+    stream: &mut TcpStream,
+) -> std::result::Result<(RequestHead, Vec<u8>), (u16, String)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > automation::MAX_REQUEST_BYTES {
+            return Err((413, "Request too large".to_string()));
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return Err((400, "Incomplete request".to_string())),
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+    };
+
+    let head = std::str::from_utf8(&buffer[..head_end])
+        .map_err(|_| (400, "Request head is not UTF-8".to_string()))
+        .and_then(|head| automation::parse_head(head).map_err(|e| (400, e.to_string())))?;
+    let body_start = head_end + 4;
+    if automation::request_end(body_start, head.content_length).is_none() {
+        return Err((413, "Request too large".to_string()));
+    }
+
+    let mut body = buffer.split_off(body_start);
+    while body.len() < head.content_length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return Err((400, "Incomplete request body".to_string())),
+            Ok(n) => body.extend_from_slice(&chunk[..n]),
+        }
+    }
+    body.truncate(head.content_length);
+    Ok((head, body))
+}
+
+/// Authenticate, authorize and carry out a request
+async fn handle_request(
+    app_handle: &AppHandle,
+    head: RequestHead,
+    body: Vec<u8>,
+) -> (u16, serde_json::Value) {
+    let authenticated = match (
+        secure_storage::get_credential(CredentialKey::AutomationToken),
+        &head.token,
+    ) {
+        (Some(expected), Some(presented)) => automation::token_matches(&expected, presented),
+        _ => false,
+    };
+    if !authenticated {
+        return (
+            401,
+            serde_json::json!({ "error": "Missing or invalid token" }),
+        );
+    }
+
+    let Some(action) = AutomationAction::route(&head.method, &head.path) else {
+        return (404, serde_json::json!({ "error": "Unknown endpoint" }));
+    };
+
+    if !authorize(app_handle, action).await {
+        tracing::info!("Automation request to {:?} denied", action);
+        return (
+            403,
+            serde_json::json!({ "error": "Not allowed by the user" }),
+        );
+    }
+
+    match perform(app_handle, action, &body).await {
+        Ok(result) => {
+            tracing::info!("Automation request to {:?} performed", action);
+            let performed = AutomationPerformed {
+                action,
+                result: result.clone(),
+            };
+            if let Err(e) = app_handle.emit("automation:performed", performed) {
+                tracing::error!("Failed to emit automation event: {}", e);
+            }
+            (200, result)
+        }
+        Err(e) => (
+            automation::error_status(&e),
+            serde_json::json!({ "error": e.to_string() }),
+        ),
+    }
+}
+
+/// Whether the user lets an action run, asking them unless they answered
+/// for good
+async fn authorize(app_handle: &AppHandle, action: AutomationAction) -> bool {
+    let automation_state = app_handle.state::<AutomationState>();
+    let (id, answer_rx) = {
+        let mut state = automation_state.inner.write().await;
+        match state.settings.permission(action) {
+            AutomationPermission::Allow => return true,
+            AutomationPermission::Deny => return false,
+            AutomationPermission::Ask => {}
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        let (answer_tx, answer_rx) = oneshot::channel();
+        state.prompts.insert(id.clone(), answer_tx);
+        (id, answer_rx)
+    };
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let request = PermissionRequest {
+        id: id.clone(),
+        action,
+        description: action.description().to_string(),
+    };
+    if let Err(e) = app_handle.emit("automation:permission-request", request) {
+        tracing::error!("Failed to emit automation permission request: {}", e);
+    }
+
+    let answer = tokio::time::timeout(PROMPT_TIMEOUT, answer_rx).await;
+    let mut state = automation_state.inner.write().await;
+    state.prompts.remove(&id);
+    let Ok(Ok(answer)) = answer else {
+        return false;
+    };
+
+    if answer.remember {
+        let permission = if answer.allow {
+            AutomationPermission::Allow
+        } else {
+            AutomationPermission::Deny
+        };
+        state.settings.set_permission(action, permission);
+        if let Err(e) = settings_path(app_handle).and_then(|path| state.settings.save(&path)) {
+            tracing::warn!("Failed to save automation permission: {}", e);
+        }
+    }
+    answer.allow
+}
+
+/// Carry out an action through the matching command
+async fn perform(
+    app_handle: &AppHandle,
+    action: AutomationAction,
+    body: &[u8],
+) -> Result<serde_json::Value> {
+    match action {
+        AutomationAction::StartSession => {
+            let options: Option<CreateSessionOptions> = if body.is_empty() {
+                None
+            } else {
+                serde_json::from_slice(body)?
+            };
+            let session = crate::commands::session::create_session(
+                options,
+                app_handle.state(),
+                app_handle.clone(),
+            )
+            .await?;
+            Ok(serde_json::to_value(session)?)
+        }
+        AutomationAction::SetPresence => {
+            #[derive(serde::Deserialize)]
+            struct PresenceBody {
+                status: String,
+            }
+            let PresenceBody { status } = serde_json::from_slice(body)?;
+            crate::commands::chat::update_presence(
+                status.clone(),
+                app_handle.state(),
+                app_handle.state(),
+            )
+            .await?;
+            Ok(serde_json::json!({ "status": status }))
+        }
+        AutomationAction::CreateMeeting => {
+            let params: CreateMeetingParams = serde_json::from_slice(body)?;
+            let meeting =
+                crate::commands::calendar::create_meeting(params, app_handle.state()).await?;
+            Ok(serde_json::to_value(meeting)?)
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get whether the automation API is on and its remembered permissions
+#[tauri::command]
+pub async fn get_automation_api(
+    automation_state: State<'_, AutomationState>,
+) -> Result<AutomationStatus> {
+    Ok(status(&automation_state.inner.read().await))
+}
+
+/// Turn the automation API on or off, kept across restarts
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_automation_api_enabled(
+    enabled: bool,
+    automation_state: State<'_, AutomationState>,
+    app_handle: AppHandle,
+) -> Result<AutomationStatus> {
+    if !enabled {
+        stop(&app_handle).await;
+    }
+
+    let mut state = automation_state.inner.write().await;
+    if enabled && state.server.is_none() {
+        state.server = Some(start_server(&app_handle).await?);
+    }
+    state.settings.enabled = enabled;
+    state.settings.save(&settings_path(&app_handle)?)?;
+
+    tracing::info!(
+        "Automation API {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(status(&state))
+}
+
+/// Get the token scripts authenticate with, creating it on first use
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_automation_token() -> Result<String> {
+    api_token()
+}
+
+/// Replace the token, locking out every script using the old one
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn regenerate_automation_token() -> Result<String> {
+    let token = automation::generate_token();
+    secure_storage::store_credential(CredentialKey::AutomationToken, &token)?;
+    tracing::info!("Automation API token regenerated");
+    Ok(token)
+}
+
+/// Allow or deny an action for good, or have it asked again with `ask`
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_automation_permission(
+    action: AutomationAction,
+    permission: AutomationPermission,
+    automation_state: State<'_, AutomationState>,
+    app_handle: AppHandle,
+) -> Result<AutomationStatus> {
+    let mut state = automation_state.inner.write().await;
+    state.settings.set_permission(action, permission);
+    state.settings.save(&settings_path(&app_handle)?)?;
+    Ok(status(&state))
+}
+
+/// Answer a permission prompt, remembering the answer for the action if asked
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn respond_to_automation_request(
+    id: String,
+    allow: bool,
+    remember: Option<bool>,
+    automation_state: State<'_, AutomationState>,
+) -> Result<()> {
+    let answer_tx = automation_state
+        .inner
+        .write()
+        .await
+        .prompts
+        .remove(&id)
+        .ok_or_else(|| Error::NotFound("Permission request not found or expired".to_string()))?;
+    let _ = answer_tx.send(PromptAnswer {
+        allow,
+        remember: remember.unwrap_or(false),
+    });
+    Ok(())
+}
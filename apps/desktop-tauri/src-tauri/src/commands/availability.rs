@@ -0,0 +1,343 @@
+//! Availability block commands (office hours)
+//!
+//! Users publish recurring "available for pairing" blocks; teammates can
+//! list the free slots of a block and book them as regular meetings.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::calendar::{
+    extract_year_month, meeting_row_to_meeting, validate_time_zone, Meeting,
+};
+use crate::state::AppState;
+use crate::supabase::AvailabilityBlockRow;
+use crate::utils::availability::{self, AvailabilityBlockSpec};
+use crate::utils::rrule;
+use crate::{Error, Result};
+
+const DEFAULT_SLOT_MINUTES: i32 = 30;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityBlock {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub starts_at: String,
+    pub duration_minutes: i32,
+    pub slot_minutes: i32,
+    pub recurrence_rule: String,
+    /// IANA time zone the block repeats in; UTC when unset
+    pub time_zone: Option<String>,
+}
+
+impl From<AvailabilityBlockRow> for AvailabilityBlock {
+    fn from(row: AvailabilityBlockRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            title: row.title,
+            starts_at: row.starts_at,
+            duration_minutes: row.duration_minutes,
+            slot_minutes: row.slot_minutes,
+            recurrence_rule: row.recurrence_rule,
+            time_zone: row.time_zone,
+        }
+    }
+}
+
+impl AvailabilityBlock {
+    fn spec(&self) -> AvailabilityBlockSpec {
+        AvailabilityBlockSpec {
+            starts_at: self.starts_at.clone(),
+            duration_minutes: self.duration_minutes,
+            slot_minutes: self.slot_minutes,
+            recurrence_rule: self.recurrence_rule.clone(),
+            time_zone: self.time_zone.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookableSlot {
+    pub block_id: String,
+    pub user_id: String,
+    pub title: String,
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAvailabilityBlockParams {
+    pub title: Option<String>,
+    pub starts_at: String,
+    pub duration_minutes: i32,
+    pub slot_minutes: Option<i32>,
+    pub recurrence_rule: String,
+    /// IANA time zone the block repeats in; UTC when unset
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookAvailabilitySlotParams {
+    pub block_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub scheduled_at: String,
+    pub duration_minutes: i32,
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the current user's availability blocks
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_my_availability_blocks(
+    app_state: State<'_, AppState>,
+) -> Result<Vec<AvailabilityBlock>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let rows = supabase.get_availability_blocks(&user_id).await?;
+    Ok(rows.into_iter().map(AvailabilityBlock::from).collect())
+}
+
+/// Get a teammate's availability blocks
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_availability_blocks(
+    user_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<AvailabilityBlock>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let rows = supabase.get_availability_blocks(&user_id).await?;
+    Ok(rows.into_iter().map(AvailabilityBlock::from).collect())
+}
+
+/// Create a recurring availability block
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_availability_block(
+    params: CreateAvailabilityBlockParams,
+    app_state: State<'_, AppState>,
+) -> Result<AvailabilityBlock> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let slot_minutes = params.slot_minutes.unwrap_or(DEFAULT_SLOT_MINUTES);
+    if params.duration_minutes <= 0 || slot_minutes <= 0 || slot_minutes > params.duration_minutes {
+        return Err(Error::Parse("Invalid availability block durations".to_string()));
+    }
+    rrule::validate_rrule(&params.recurrence_rule)?;
+    let time_zone = match params.time_zone.as_deref() {
+        Some(time_zone) => validate_time_zone(time_zone)?,
+        None => None,
+    };
+
+    let row = supabase
+        .create_availability_block(
+            &user_id,
+            params.title.as_deref().unwrap_or("Office hours"),
+            &params.starts_at,
+            params.duration_minutes,
+            slot_minutes,
+            &params.recurrence_rule,
+            time_zone.as_deref(),
+        )
+        .await?;
+
+    tracing::info!("Created availability block {}", row.id);
+    Ok(row.into())
+}
+
+/// Delete one of the current user's availability blocks
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn delete_availability_block(
+    block_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase.delete_availability_block(&block_id).await
+}
+
+/// Get the free slots of a user's availability blocks in a date range
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_available_slots(
+    user_id: String,
+    range_start: String,
+    range_end: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<BookableSlot>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let blocks: Vec<AvailabilityBlock> = supabase
+        .get_availability_blocks(&user_id)
+        .await?
+        .into_iter()
+        .map(AvailabilityBlock::from)
+        .collect();
+
+    let mut slots = Vec::new();
+    for block in blocks {
+        let bookings = supabase
+            .get_availability_bookings(&block.id, &range_start, &range_end)
+            .await?;
+
+        for slot in availability::expand_block_slots(&block.spec(), &range_start, &range_end)? {
+            let mut is_booked = false;
+            for booking in &bookings {
+                if availability::ranges_overlap(
+                    &slot.starts_at,
+                    block.slot_minutes,
+                    &booking.scheduled_at,
+                    booking.duration_minutes,
+                )? {
+                    is_booked = true;
+                    break;
+                }
+            }
+
+            if !is_booked {
+                slots.push(BookableSlot {
+                    block_id: block.id.clone(),
+                    user_id: block.user_id.clone(),
+                    title: block.title.clone(),
+                    starts_at: slot.starts_at,
+                    ends_at: slot.ends_at,
+                });
+            }
+        }
+    }
+
+    slots.sort_by(|a, b| a.starts_at.cmp(&b.starts_at));
+    Ok(slots)
+}
+
+/// Book a slot inside a teammate's availability block
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn book_availability_slot(
+    params: BookAvailabilitySlotParams,
+    app_state: State<'_, AppState>,
+) -> Result<Meeting> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let block: AvailabilityBlock = supabase
+        .get_availability_block(&params.block_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Availability block not found".to_string()))?
+        .into();
+
+    if block.user_id == user_id {
+        return Err(Error::Parse("Cannot book your own availability block".to_string()));
+    }
+
+    // The booking must stay within one occurrence of the block
+    if !availability::fits_in_block(&block.spec(), &params.scheduled_at, params.duration_minutes)? {
+        return Err(Error::Parse(
+            "Requested time is outside the availability block".to_string(),
+        ));
+    }
+
+    // Refused if the slot was booked in the meantime
+    let row = supabase
+        .book_availability_slot(
+            &block.id,
+            &params.title,
+            params.description.as_deref(),
+            &params.scheduled_at,
+            params.duration_minutes,
+        )
+        .await?;
+
+    let meeting = meeting_row_to_meeting(row, &app_state).await?;
+
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        if let Some((year, month)) = extract_year_month(&meeting.scheduled_at) {
+            cache.invalidate_month(year, month);
+        }
+        cache.set_by_id(meeting.clone());
+        cache.set_upcoming(Vec::new());
+    }
+
+    tracing::info!("Booked availability slot {} in block {}", meeting.id, block.id);
+    Ok(meeting)
+}
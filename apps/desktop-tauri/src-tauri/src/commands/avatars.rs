@@ -0,0 +1,114 @@
+//! Avatar thumbnail commands
+//!
+//! Resolve remote avatar URLs to cached local WebP thumbnails; the frontend
+//! loads the returned paths with `convertFileSrc`. In low-bandwidth mode only
+//! thumbnails already on disk are returned.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use futures_util::{stream, StreamExt};
+use tauri::State;
+
+use crate::avatars::{AvatarCache, DEFAULT_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE};
+use crate::low_bandwidth;
+use crate::{Error, Result};
+
+/// Smallest thumbnail the frontend may request
+const MIN_THUMBNAIL_SIZE: u32 = 16;
+
+/// Avatars generated in parallel by `get_avatar_thumbnails`
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn validate_avatar_url(url: &str) -> Result<()> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err(Error::Config(format!("Unsupported avatar URL: {}", url)))
+    }
+}
+
+fn thumbnail_size(size: Option<u32>) -> u32 {
+    size.unwrap_or(DEFAULT_THUMBNAIL_SIZE)
+        .clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE)
+}
+
+/// Thumbnail of an avatar, downloaded on first use unless in low-bandwidth mode
+async fn resolve_thumbnail(avatars: &AvatarCache, url: &str, size: u32) -> Result<PathBuf> {
+    if low_bandwidth::is_enabled() {
+        return avatars.cached_thumbnail(url, size).await.ok_or_else(|| {
+            Error::Network("Avatar downloads are paused in low-bandwidth mode".to_string())
+        });
+    }
+    avatars.thumbnail(url, size).await
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the local thumbnail path of an avatar, downloading it on first use
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_avatar_thumbnail(
+    url: String,
+    size: Option<u32>,
+    avatars: State<'_, AvatarCache>,
+) -> Result<String> {
+    validate_avatar_url(&url)?;
+    let path = resolve_thumbnail(&avatars, &url, thumbnail_size(size)).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Resolve a batch of avatars for a list, keyed by URL
+///
+/// Avatars that fail to download are left out so the list falls back to
+/// its placeholder instead of failing as a whole.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_avatar_thumbnails(
+    urls: Vec<String>,
+    size: Option<u32>,
+    avatars: State<'_, AvatarCache>,
+) -> Result<HashMap<String, String>> {
+    let size = thumbnail_size(size);
+    let mut urls = urls;
+    urls.sort();
+    urls.dedup();
+
+    let avatars = avatars.inner();
+    let thumbnails = stream::iter(urls)
+        .filter(|url| futures_util::future::ready(validate_avatar_url(url).is_ok()))
+        .map(|url| async move {
+            match resolve_thumbnail(avatars, &url, size).await {
+                Ok(path) => Some((url, path.to_string_lossy().to_string())),
+                Err(e) => {
+                    tracing::warn!("Failed to cache avatar {}: {}", url, e);
+                    None
+                }
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_DOWNLOADS)
+        .filter_map(futures_util::future::ready)
+        .collect()
+        .await;
+
+    Ok(thumbnails)
+}
+
+/// Delete all cached avatar thumbnails
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn clear_avatar_cache(avatars: State<'_, AvatarCache>) -> Result<usize> {
+    let removed = avatars.clear().await?;
+    tracing::info!(
+        "Cleared {} avatar thumbnails from {}",
+        removed,
+        avatars.dir().display()
+    );
+    Ok(removed)
+}
@@ -0,0 +1,191 @@
+//! App badge commands
+//!
+//! Unread and meeting counts come from Supabase, refreshed every minute and
+//! whenever something that changes them happens (a message arrives, a
+//! conversation is read, an invitation is answered). The waiting room is
+//! local, so it is checked every couple of seconds. Each change of the
+//! badge updates the dock or taskbar and is emitted as `app:badge-changed`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{Notify, RwLock};
+
+use crate::badge::Badge;
+use crate::commands::signaling::SignalingState;
+use crate::state::AppState;
+use crate::Result;
+
+/// How often unread messages and pending invitations are counted
+const COUNTS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the waiting room is checked
+const WAITING_ROOM_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct BadgeState {
+    pub inner: Arc<RwLock<BadgeStateInner>>,
+    /// Wakes the badge task to count again; requests made while it is
+    /// counting are coalesced
+    refresh_requested: Arc<Notify>,
+}
+
+#[derive(Default)]
+pub struct BadgeStateInner {
+    /// Unread messages, as of the last count
+    pub unread_messages: u32,
+    /// Meeting invitations not answered, as of the last count
+    pub pending_meeting_responses: u32,
+    /// Badge currently shown
+    pub current: Badge,
+}
+
+impl Default for BadgeState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(BadgeStateInner::default())),
+            refresh_requested: Arc::new(Notify::new()),
+        }
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Count again soon, after something that changes the counts
+pub fn request_refresh(app_handle: &AppHandle) {
+    app_handle
+        .state::<BadgeState>()
+        .refresh_requested
+        .notify_one();
+}
+
+/// Count unread messages and pending invitations of the signed-in user
+async fn refresh_counts(app_handle: &AppHandle) -> Result<()> {
+    let app_state = app_handle.state::<AppState>();
+    let user_id = app_state
+        .inner
+        .read()
+        .await
+        .user
+        .as_ref()
+        .map(|u| u.id.clone());
+
+    let (unread_messages, pending_meeting_responses) = match (user_id, &app_state.supabase) {
+        (Some(user_id), Some(supabase)) => {
+            let (unread, pending) = tokio::try_join!(
+                supabase.get_unread_counts(),
+                supabase.count_pending_meeting_responses(&user_id),
+            )?;
+            let unread = unread
+                .iter()
+                .map(|c| c.unread_count.saturating_add(c.unread_thread_replies))
+                .fold(0u32, u32::saturating_add);
+            (unread, pending)
+        }
+        _ => (0, 0),
+    };
+
+    let badge_state = app_handle.state::<BadgeState>();
+    let mut state = badge_state.inner.write().await;
+    state.unread_messages = unread_messages;
+    state.pending_meeting_responses = pending_meeting_responses;
+    Ok(())
+}
+
+/// Recompute the badge from the last counts and the waiting room, showing
+/// and emitting it if it changed
+async fn update_badge(app_handle: &AppHandle) -> Badge {
+    let signed_in = app_handle
+        .state::<AppState>()
+        .inner
+        .read()
+        .await
+        .user
+        .is_some();
+    let waiting_room_requests = app_handle
+        .state::<SignalingState>()
+        .inner
+        .read()
+        .await
+        .join_requests
+        .len() as u32;
+
+    let badge_state = app_handle.state::<BadgeState>();
+    let mut state = badge_state.inner.write().await;
+    let badge = if signed_in {
+        Badge::new(
+            state.unread_messages,
+            state.pending_meeting_responses,
+            waiting_room_requests,
+        )
+    } else {
+        Badge::new(0, 0, waiting_room_requests)
+    };
+    if badge == state.current {
+        return badge;
+    }
+    state.current = badge;
+    drop(state);
+
+    show_badge(app_handle, badge.total);
+    if let Err(e) = app_handle.emit("app:badge-changed", badge) {
+        tracing::error!("Failed to emit badge event: {}", e);
+    }
+    badge
+}
+
+/// Put the count on the dock or taskbar
+fn show_badge(app_handle: &AppHandle, total: u32) {
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return;
+    };
+
+    #[cfg(not(windows))]
+    let result = window.set_badge_count((total > 0).then_some(i64::from(total)));
+    #[cfg(windows)]
+    let result = window.set_overlay_icon((total > 0).then(|| {
+        tauri::image::Image::new_owned(
+            crate::badge::overlay_icon_rgba(),
+            crate::badge::OVERLAY_ICON_SIZE,
+            crate::badge::OVERLAY_ICON_SIZE,
+        )
+    }));
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to update the app badge: {}", e);
+    }
+}
+
+/// Keep the badge up to date from startup on
+pub async fn watch_badge(app_handle: AppHandle) {
+    let refresh_requested = app_handle.state::<BadgeState>().refresh_requested.clone();
+    let mut counts = tokio::time::interval(COUNTS_REFRESH_INTERVAL);
+    let mut waiting_room = tokio::time::interval(WAITING_ROOM_CHECK_INTERVAL);
+    loop {
+        let refresh = tokio::select! {
+            _ = counts.tick() => true,
+            _ = refresh_requested.notified() => true,
+            _ = waiting_room.tick() => false,
+        };
+        if refresh {
+            if let Err(e) = refresh_counts(&app_handle).await {
+                tracing::debug!("Failed to count badge items: {}", e);
+            }
+        }
+        update_badge(&app_handle).await;
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Count everything that needs attention and return the badge
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_badge(app_handle: AppHandle) -> Result<Badge> {
+    refresh_counts(&app_handle).await?;
+    Ok(update_badge(&app_handle).await)
+}
@@ -0,0 +1,148 @@
+//! Blocking and reporting commands
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::blocking;
+use crate::state::AppState;
+use crate::supabase::SupabaseClient;
+use crate::{Error, Result};
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedUser {
+    pub user_id: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub blocked_at: String,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Signed-in user's ID and the Supabase client
+async fn current_user(app_state: &AppState) -> Result<(String, &SupabaseClient)> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    Ok((user_id, supabase))
+}
+
+/// IDs of the users the signed-in user blocked, from the cache when fresh
+pub(crate) async fn blocked_users(app_state: &AppState) -> Result<HashSet<String>> {
+    if let Some(blocked) = app_state.cache.blocks.read().await.get() {
+        return Ok(blocked.clone());
+    }
+
+    let (user_id, supabase) = current_user(app_state).await?;
+    let blocked: HashSet<String> = supabase
+        .get_blocked_users(&user_id)
+        .await?
+        .into_iter()
+        .map(|row| row.blocked_id)
+        .collect();
+    app_state.cache.blocks.write().await.set(blocked.clone());
+    Ok(blocked)
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Block a user, hiding their messages and keeping them from starting
+/// direct conversations with the user
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn block_user(user_id: String, app_state: State<'_, AppState>) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+    blocking::check_target(&current_user_id, &user_id)?;
+
+    supabase.block_user(&current_user_id, &user_id).await?;
+    app_state.cache.blocks.write().await.update(&user_id, true);
+
+    tracing::info!("Blocked user {}", user_id);
+    Ok(())
+}
+
+/// Unblock a user, showing their messages again
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn unblock_user(user_id: String, app_state: State<'_, AppState>) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+
+    supabase.unblock_user(&current_user_id, &user_id).await?;
+    app_state.cache.blocks.write().await.update(&user_id, false);
+
+    tracing::info!("Unblocked user {}", user_id);
+    Ok(())
+}
+
+/// Get the users the user blocked, most recent first
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_blocked_users(app_state: State<'_, AppState>) -> Result<Vec<BlockedUser>> {
+    let (user_id, supabase) = current_user(&app_state).await?;
+
+    let rows = supabase.get_blocked_users(&user_id).await?;
+    let ids: Vec<String> = rows.iter().map(|row| row.blocked_id.clone()).collect();
+    app_state
+        .cache
+        .blocks
+        .write()
+        .await
+        .set(ids.iter().cloned().collect());
+
+    let profiles = if ids.is_empty() {
+        Vec::new()
+    } else {
+        supabase.get_user_profiles(&ids).await?
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let profile = profiles.iter().find(|p| p.user_id == row.blocked_id);
+            BlockedUser {
+                display_name: profile.and_then(|p| p.display_name.clone()),
+                avatar_url: profile.and_then(|p| p.avatar_url.clone()),
+                user_id: row.blocked_id,
+                blocked_at: row.created_at,
+            }
+        })
+        .collect())
+}
+
+/// Report a user for review, with the reason why
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn report_user(
+    user_id: String,
+    reason: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+    blocking::check_target(&current_user_id, &user_id)?;
+    let reason = blocking::report_reason(&reason)?;
+
+    supabase
+        .report_user(&current_user_id, &user_id, &reason)
+        .await?;
+
+    tracing::info!("Reported user {}", user_id);
+    Ok(())
+}
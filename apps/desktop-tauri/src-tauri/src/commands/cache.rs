@@ -26,6 +26,7 @@ pub struct CacheStats {
 
 /// Get cache statistics
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_cache_stats(app_state: State<'_, AppState>) -> Result<CacheStats> {
     let meetings_cache = app_state.cache.meetings.read().await;
     let messages_cache = app_state.cache.messages.read().await;
@@ -45,6 +46,7 @@ pub async fn get_cache_stats(app_state: State<'_, AppState>) -> Result<CacheStat
 
 /// Invalidate all caches
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn invalidate_all_caches(app_state: State<'_, AppState>) -> Result<()> {
     app_state.cache.invalidate_all().await;
     tracing::info!("All caches invalidated");
@@ -53,6 +55,7 @@ pub async fn invalidate_all_caches(app_state: State<'_, AppState>) -> Result<()>
 
 /// Cleanup expired cache entries
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn cleanup_caches(app_state: State<'_, AppState>) -> Result<()> {
     app_state.cache.cleanup().await;
     tracing::info!("Expired cache entries cleaned up");
@@ -61,6 +64,7 @@ pub async fn cleanup_caches(app_state: State<'_, AppState>) -> Result<()> {
 
 /// Invalidate meeting cache for a specific month
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn invalidate_meeting_month(
     year: i32,
     month: u32,
@@ -74,6 +78,7 @@ pub async fn invalidate_meeting_month(
 
 /// Invalidate message cache for a conversation
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn invalidate_conversation_messages(
     conversation_id: String,
     app_state: State<'_, AppState>,
@@ -86,6 +91,7 @@ pub async fn invalidate_conversation_messages(
 
 /// Invalidate all presence cache
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn invalidate_presence_cache(app_state: State<'_, AppState>) -> Result<()> {
     let mut cache = app_state.cache.presence.write().await;
     cache.invalidate_all();
@@ -1,13 +1,37 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{Datelike, NaiveDateTime};
+use futures_util::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::badge;
+use crate::commands::notifications;
+use crate::commands::session;
+use crate::commands::validation::EMAIL_REGEX;
 use crate::state::AppState;
-use crate::supabase::MeetingRow;
+use crate::supabase::{MeetingGuestRow, MeetingLocationUpdate, MeetingRow};
+use crate::utils::agenda::{self, AgendaDay, AgendaRange};
+use crate::utils::datetime;
+use crate::utils::markdown::{self, ChecklistItem};
+use crate::utils::rrule::{self, RecurrenceExceptions};
+use crate::utils::text;
 use crate::{Error, Result};
 
+/// Maximum number of meetings resolved concurrently
+const MAX_CONCURRENT_MEETING_LOOKUPS: usize = 8;
+
+/// Attempts at ticking a checklist item while others edit the description
+const MAX_CHECKLIST_ATTEMPTS: usize = 3;
+
+/// Maximum number of months of a range fetched concurrently
+const MAX_CONCURRENT_MONTH_FETCHES: usize = 4;
+
+/// Ranges spanning more months than this are fetched in a single query
+const MAX_SPLIT_MONTHS: usize = 12;
+
 /// Extract year and month from a datetime string (ISO 8601)
-fn extract_year_month(date_str: &str) -> Option<(i32, u32)> {
+pub(crate) fn extract_year_month(date_str: &str) -> Option<(i32, u32)> {
     // Try to parse ISO 8601 datetime
     let dt = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M:%S%.fZ")
         .or_else(|_| NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M:%S"))
@@ -27,12 +51,54 @@ pub struct Meeting {
     pub organizer_name: String,
     pub title: String,
     pub description: Option<String>,
+    /// Description rendered from Markdown and sanitized
+    #[serde(default)]
+    pub description_html: Option<String>,
+    /// Task list items of the description, in order
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
     pub scheduled_at: String,
     pub duration_minutes: i32,
     pub status: String,
     pub session_id: Option<String>,
     pub recurrence_rule: Option<String>,
+    /// Occurrences removed from and added to the recurrence
+    #[serde(default)]
+    pub recurrence_exceptions: RecurrenceExceptions,
+    /// IANA time zone the meeting is scheduled in; UTC when unset
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Recurring meeting this is a later occurrence of, whose `id` is then
+    /// an occurrence id
+    #[serde(default)]
+    pub recurring_meeting_id: Option<String>,
+    /// Address or place the meeting is held at
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Video call link
+    #[serde(default)]
+    pub meeting_url: Option<String>,
+    #[serde(default)]
+    pub room: Option<String>,
+    /// Session starts on its own at the scheduled time
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Google Calendar creates a Meet link, kept as `meeting_url`, when
+    /// the meeting is synced there
+    #[serde(default)]
+    pub google_meet: bool,
+    /// Join code of the session, once the meeting started
+    #[serde(default)]
+    pub join_code: Option<String>,
+    /// Organizer's tag the meeting is categorized under
+    #[serde(default)]
+    pub tag_id: Option<String>,
+    /// `#rrggbb` color, the tag's when tagged
+    #[serde(default)]
+    pub color: Option<String>,
     pub google_event_id: Option<String>,
+    #[serde(default)]
+    pub outlook_event_id: Option<String>,
     pub attendees: Vec<MeetingAttendee>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
@@ -45,6 +111,44 @@ pub struct MeetingAttendee {
     pub avatar_url: Option<String>,
     pub response_status: String,
     pub responded_at: Option<String>,
+    /// "required" or "optional"
+    pub attendance_type: String,
+    /// When the attendee should have answered by
+    pub rsvp_deadline: Option<String>,
+}
+
+impl MeetingAttendee {
+    /// Whether the attendee still owes an answer: invited, and before the
+    /// RSVP deadline if there is one
+    fn is_pending(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.response_status == "invited"
+            && self.rsvp_deadline.as_deref().map_or(true, |deadline| {
+                datetime::parse_datetime(deadline).map_or(true, |deadline| deadline > now)
+            })
+    }
+}
+
+/// External attendee invited by email, who answers through the RSVP links
+/// of the invitation email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingGuest {
+    pub id: String,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub response_status: String,
+    pub responded_at: Option<String>,
+}
+
+impl From<MeetingGuestRow> for MeetingGuest {
+    fn from(row: MeetingGuestRow) -> Self {
+        Self {
+            id: row.id,
+            email: row.email,
+            display_name: row.display_name,
+            response_status: row.response_status,
+            responded_at: row.responded_at,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,7 +158,28 @@ pub struct CreateMeetingParams {
     pub scheduled_at: String,
     pub duration_minutes: i32,
     pub attendee_ids: Vec<String>,
+    /// Attendees of `attendee_ids` who are optional rather than required
+    #[serde(default)]
+    pub optional_attendee_ids: Vec<String>,
+    /// When attendees should answer by
+    #[serde(default)]
+    pub rsvp_deadline: Option<String>,
     pub recurrence_rule: Option<String>,
+    /// IANA time zone the recurrence is expanded in, e.g. "America/Sao_Paulo"
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub meeting_url: Option<String>,
+    #[serde(default)]
+    pub room: Option<String>,
+    /// Start the session on its own at the scheduled time
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Have Google Calendar create a Meet link when the meeting is synced
+    #[serde(default)]
+    pub google_meet: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,13 +188,91 @@ pub struct UpdateMeetingParams {
     pub description: Option<String>,
     pub scheduled_at: Option<String>,
     pub duration_minutes: Option<i32>,
+    /// New IANA time zone; an empty string resets it to UTC
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// New location, video call link or room; an empty string clears it
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub meeting_url: Option<String>,
+    #[serde(default)]
+    pub room: Option<String>,
+    #[serde(default)]
+    pub auto_start: Option<bool>,
+    #[serde(default)]
+    pub google_meet: Option<bool>,
+}
+
+/// Payload of `meeting:starting`, emitted when a meeting's session started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingStarting {
+    pub meeting_id: String,
+    pub title: String,
+    pub session_id: String,
+    pub join_code: String,
+    /// Video call link, e.g. the Google Meet link, for attendees who can't
+    /// join the session
+    #[serde(default)]
+    pub meeting_url: Option<String>,
 }
 
 // ==========================================
 // Helper Functions
 // ==========================================
 
-async fn meeting_row_to_meeting(
+fn validate_attendance_type(attendance_type: &str) -> Result<()> {
+    match attendance_type {
+        "required" | "optional" => Ok(()),
+        _ => Err(Error::Parse(format!(
+            "Invalid attendance type: {}",
+            attendance_type
+        ))),
+    }
+}
+
+/// Guest emails are stored trimmed and lowercased
+fn normalize_guest_email(email: &str) -> Result<String> {
+    let email = email.trim().to_lowercase();
+    if email.len() > 254 || !EMAIL_REGEX.is_match(&email) {
+        return Err(Error::Parse(format!("Invalid email: {}", email)));
+    }
+    Ok(email)
+}
+
+/// Location changes of a create or update: fields are trimmed, a blank one
+/// clears its field, and the video call link must be a web URL
+fn location_update(
+    location: Option<&str>,
+    meeting_url: Option<&str>,
+    room: Option<&str>,
+) -> Result<MeetingLocationUpdate> {
+    let field = |value: Option<&str>| {
+        value.map(|v| Some(v.trim()).filter(|v| !v.is_empty()).map(str::to_string))
+    };
+    let update = MeetingLocationUpdate {
+        location: field(location),
+        meeting_url: field(meeting_url),
+        room: field(room),
+    };
+    if let Some(Some(ref url)) = update.meeting_url {
+        if !text::is_web_url(url) {
+            return Err(Error::Parse(format!("Invalid meeting URL: {}", url)));
+        }
+    }
+    Ok(update)
+}
+
+/// Canonical name of a meeting time zone; blank means UTC
+pub(crate) fn validate_time_zone(time_zone: &str) -> Result<Option<String>> {
+    if time_zone.trim().is_empty() {
+        return Ok(None);
+    }
+    let tz = datetime::parse_time_zone(time_zone)?;
+    Ok(Some(tz.name().to_string()))
+}
+
+pub(crate) async fn meeting_row_to_meeting(
     row: MeetingRow,
     app_state: &AppState,
 ) -> Result<Meeting> {
@@ -78,8 +281,13 @@ async fn meeting_row_to_meeting(
         .as_ref()
         .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    // Get attendees
-    let attendees_raw = supabase.get_meeting_attendees(&row.id).await?;
+    // Attendees and organizer profile are independent, fetch them together
+    let organizer_ids = [row.organizer_id.clone()];
+    let (attendees_raw, organizer_profiles) = tokio::try_join!(
+        supabase.get_meeting_attendees(&row.id),
+        supabase.get_user_profiles(&organizer_ids),
+    )?;
+
     let attendees: Vec<MeetingAttendee> = attendees_raw
         .into_iter()
         .map(|a| MeetingAttendee {
@@ -88,11 +296,11 @@ async fn meeting_row_to_meeting(
             avatar_url: a.avatar_url,
             response_status: a.response_status,
             responded_at: a.responded_at,
+            attendance_type: a.attendance_type,
+            rsvp_deadline: a.rsvp_deadline,
         })
         .collect();
 
-    // Get organizer name
-    let organizer_profiles = supabase.get_user_profiles(&[row.organizer_id.clone()]).await?;
     let organizer_name = organizer_profiles
         .first()
         .and_then(|p| p.display_name.clone())
@@ -103,25 +311,218 @@ async fn meeting_row_to_meeting(
         organizer_id: row.organizer_id,
         organizer_name,
         title: row.title,
+        description_html: row.description.as_deref().map(markdown::render),
+        checklist: row
+            .description
+            .as_deref()
+            .map(markdown::checklist)
+            .unwrap_or_default(),
         description: row.description,
         scheduled_at: row.scheduled_at,
         duration_minutes: row.duration_minutes,
         status: row.status,
         session_id: row.session_id,
         recurrence_rule: row.recurrence_rule,
+        recurrence_exceptions: RecurrenceExceptions {
+            exdates: row.recurrence_exdates,
+            rdates: row.recurrence_rdates,
+        },
+        time_zone: row.time_zone,
+        recurring_meeting_id: None,
+        location: row.location,
+        meeting_url: row.meeting_url,
+        room: row.room,
+        auto_start: row.auto_start,
+        google_meet: row.google_meet,
+        join_code: row.join_code,
+        tag_id: row.tag_id,
+        color: row.color,
         google_event_id: row.google_event_id,
+        outlook_event_id: row.outlook_event_id,
         attendees,
         created_at: row.created_at,
         updated_at: row.updated_at,
     })
 }
 
+/// Resolve several meeting rows concurrently, keeping their order
+pub(crate) async fn meeting_rows_to_meetings(
+    rows: Vec<MeetingRow>,
+    app_state: &AppState,
+) -> Result<Vec<Meeting>> {
+    futures_util::stream::iter(rows)
+        .map(|row| meeting_row_to_meeting(row, app_state))
+        .buffered(MAX_CONCURRENT_MEETING_LOOKUPS)
+        .try_collect()
+        .await
+}
+
+/// Recurring meetings of the user, whatever month they started in
+async fn recurring_meetings(user_id: &str, app_state: &AppState) -> Result<Vec<Meeting>> {
+    {
+        let cache = app_state.cache.meetings.read().await;
+        if let Some(cached) = cache.get_recurring() {
+            tracing::debug!("Cache hit for recurring meetings");
+            return Ok(cached.clone());
+        }
+    }
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+    let rows = supabase.get_recurring_meetings(user_id, None).await?;
+    let meetings = meeting_rows_to_meetings(rows, app_state).await?;
+
+    let mut cache = app_state.cache.meetings.write().await;
+    cache.set_recurring(meetings.clone());
+    tracing::debug!("Cached {} recurring meetings", meetings.len());
+    Ok(meetings)
+}
+
+/// The meetings with recurring ones replaced by their occurrences starting
+/// within the range. The first occurrence is the meeting itself; later
+/// ones get occurrence ids and point back at it
+fn expand_recurring_meetings(
+    meetings: Vec<Meeting>,
+    recurring: Vec<Meeting>,
+    range_start: chrono::DateTime<chrono::Utc>,
+    range_end: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<Meeting>> {
+    let (mut expanded, in_range): (Vec<Meeting>, Vec<Meeting>) = meetings
+        .into_iter()
+        .partition(|meeting| meeting.recurrence_rule.is_none());
+
+    let mut seen = HashSet::new();
+    for meeting in recurring.into_iter().chain(in_range) {
+        if !seen.insert(meeting.id.clone()) {
+            continue;
+        }
+        let first = datetime::parse_datetime(&meeting.scheduled_at)?;
+        // A second past the end keeps the range end inclusive
+        let starts = agenda::occurrences_in_range(
+            &meeting.scheduled_at,
+            0,
+            meeting.recurrence_rule.as_deref(),
+            &meeting.recurrence_exceptions,
+            meeting.time_zone.as_deref(),
+            (range_start, range_end + chrono::Duration::seconds(1)),
+        )?;
+        for start in starts {
+            if start == first {
+                expanded.push(meeting.clone());
+                continue;
+            }
+            let scheduled_at = start.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let mut occurrence = meeting.clone();
+            occurrence.id = rrule::occurrence_id(&meeting.id, &scheduled_at)?;
+            occurrence.scheduled_at = scheduled_at;
+            occurrence.recurring_meeting_id = Some(meeting.id.clone());
+            expanded.push(occurrence);
+        }
+    }
+
+    expanded.sort_by_cached_key(|meeting| datetime::parse_datetime(&meeting.scheduled_at).ok());
+    Ok(expanded)
+}
+
+/// Meetings of the user in a date range, recurring meetings expanded into
+/// their occurrences
+///
+/// Ranges are served month by month, so each month is cached on its own
+/// and a quarter view only fetches the months it is missing.
+pub(crate) async fn meetings_in_range(
+    app_state: &AppState,
+    user_id: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<Meeting>> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let range_start = datetime::parse_datetime(start_date)?;
+    let range_end = datetime::parse_datetime(end_date)?;
+    let recurring = recurring_meetings(user_id, app_state).await?;
+
+    let months = datetime::get_months_in_range(start_date, end_date).unwrap_or_default();
+    if months.is_empty() || months.len() > MAX_SPLIT_MONTHS {
+        let meeting_rows = supabase
+            .get_meetings_in_range(user_id, start_date, end_date)
+            .await?;
+        let meetings = meeting_rows_to_meetings(meeting_rows, app_state).await?;
+        return expand_recurring_meetings(meetings, recurring, range_start, range_end);
+    }
+
+    let mut by_month: HashMap<(i32, u32), Vec<Meeting>> = HashMap::new();
+    {
+        let cache = app_state.cache.meetings.read().await;
+        for &(year, month) in &months {
+            if let Some(cached) = cache.get_month(year, month) {
+                by_month.insert((year, month), cached.clone());
+            }
+        }
+    }
+    let missing: Vec<(i32, u32)> = months
+        .iter()
+        .filter(|key| !by_month.contains_key(key))
+        .copied()
+        .collect();
+    tracing::debug!(
+        "Meetings {} to {}: {} months cached, {} to fetch",
+        start_date,
+        end_date,
+        by_month.len(),
+        missing.len()
+    );
+
+    let fetched: Vec<((i32, u32), Vec<Meeting>)> = futures_util::stream::iter(missing)
+        .map(|(year, month)| async move {
+            let (month_start, month_end) = datetime::get_utc_month_bounds(year, month)?;
+            let rows = supabase
+                .get_meetings_in_range(user_id, &month_start, &month_end)
+                .await?;
+            let meetings = meeting_rows_to_meetings(rows, app_state).await?;
+            Ok::<_, Error>(((year, month), meetings))
+        })
+        .buffer_unordered(MAX_CONCURRENT_MONTH_FETCHES)
+        .try_collect()
+        .await?;
+
+    if !fetched.is_empty() {
+        let mut cache = app_state.cache.meetings.write().await;
+        for ((year, month), meetings) in &fetched {
+            cache.set_month(*year, *month, meetings.clone());
+            tracing::debug!("Cached meetings for {}-{:02}", year, month);
+        }
+    }
+    by_month.extend(fetched);
+
+    // Months are fetched whole; keep what falls within the requested range
+    let mut seen = HashSet::new();
+    let meetings = months
+        .iter()
+        .filter_map(|key| by_month.remove(key))
+        .flatten()
+        .filter(|meeting| {
+            datetime::parse_datetime(&meeting.scheduled_at)
+                .map_or(true, |at| at >= range_start && at <= range_end)
+        })
+        .filter(|meeting| seen.insert(meeting.id.clone()))
+        .collect();
+
+    expand_recurring_meetings(meetings, recurring, range_start, range_end)
+}
+
 // ==========================================
 // Commands
 // ==========================================
 
-/// Get meetings in a date range
+/// Get meetings in a date range, recurring meetings expanded into their
+/// occurrences
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_meetings(
     start_date: String,
     end_date: String,
@@ -134,57 +535,14 @@ pub async fn get_meetings(
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
     let user_id = user.id.clone();
 
-    let supabase = app_state
-        .supabase
-        .as_ref()
-        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
-
     drop(inner);
 
-    // Check cache first if querying a single month
-    if let (Some((start_year, start_month)), Some((end_year, end_month))) = (
-        extract_year_month(&start_date),
-        extract_year_month(&end_date),
-    ) {
-        // If same month, try cache
-        if start_year == end_year && start_month == end_month {
-            let cache = app_state.cache.meetings.read().await;
-            if let Some(cached) = cache.get_month(start_year, start_month) {
-                tracing::debug!("Cache hit for meetings {}-{:02}", start_year, start_month);
-                return Ok(cached.clone());
-            }
-            drop(cache);
-        }
-    }
-
-    // Cache miss - fetch from API
-    let meeting_rows = supabase
-        .get_meetings_in_range(&user_id, &start_date, &end_date)
-        .await?;
-
-    let mut meetings = Vec::new();
-    for row in meeting_rows {
-        let meeting = meeting_row_to_meeting(row, &app_state).await?;
-        meetings.push(meeting);
-    }
-
-    // Cache the result if single month query
-    if let (Some((start_year, start_month)), Some((end_year, end_month))) = (
-        extract_year_month(&start_date),
-        extract_year_month(&end_date),
-    ) {
-        if start_year == end_year && start_month == end_month {
-            let mut cache = app_state.cache.meetings.write().await;
-            cache.set_month(start_year, start_month, meetings.clone());
-            tracing::debug!("Cached meetings for {}-{:02}", start_year, start_month);
-        }
-    }
-
-    Ok(meetings)
+    meetings_in_range(&app_state, &user_id, &start_date, &end_date).await
 }
 
 /// Get a single meeting by ID
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_meeting(
     meeting_id: String,
     app_state: State<'_, AppState>,
@@ -202,33 +560,56 @@ pub async fn get_meeting(
 
     drop(inner);
 
+    // An occurrence id resolves to its recurring meeting moved to the
+    // occurrence
+    let occurrence = rrule::parse_occurrence_id(&meeting_id);
+    let lookup_id = match &occurrence {
+        Some((recurring_id, _)) => recurring_id.as_str(),
+        None => meeting_id.as_str(),
+    };
+
     // Check cache first
-    {
+    let cached = {
         let cache = app_state.cache.meetings.read().await;
-        if let Some(cached) = cache.get_by_id(&meeting_id) {
-            tracing::debug!("Cache hit for meeting {}", meeting_id);
-            return Ok(Some(cached.clone()));
+        cache.get_by_id(lookup_id).cloned()
+    };
+    let meeting = match cached {
+        Some(meeting) => {
+            tracing::debug!("Cache hit for meeting {}", lookup_id);
+            meeting
         }
-    }
-
-    // Cache miss - fetch from API
-    let row = supabase.get_meeting(&meeting_id).await?;
-
-    match row {
-        Some(r) => {
-            let meeting = meeting_row_to_meeting(r, &app_state).await?;
+        None => {
+            // Cache miss - fetch from API
+            let Some(row) = supabase.get_meeting(lookup_id).await? else {
+                return Ok(None);
+            };
+            let meeting = meeting_row_to_meeting(row, &app_state).await?;
             // Cache the meeting
             let mut cache = app_state.cache.meetings.write().await;
             cache.set_by_id(meeting.clone());
-            tracing::debug!("Cached meeting {}", meeting_id);
-            Ok(Some(meeting))
+            tracing::debug!("Cached meeting {}", lookup_id);
+            meeting
         }
-        None => Ok(None),
-    }
+    };
+
+    let is_first = |scheduled_at: &str| {
+        datetime::parse_datetime(&meeting.scheduled_at).ok()
+            == datetime::parse_datetime(scheduled_at).ok()
+    };
+    Ok(Some(match occurrence {
+        Some((_, scheduled_at)) if !is_first(&scheduled_at) => Meeting {
+            id: meeting_id,
+            scheduled_at,
+            recurring_meeting_id: Some(meeting.id.clone()),
+            ..meeting
+        },
+        _ => meeting,
+    }))
 }
 
 /// Get upcoming meetings
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_upcoming_meetings(
     limit: Option<u32>,
     app_state: State<'_, AppState>,
@@ -263,11 +644,7 @@ pub async fn get_upcoming_meetings(
     let limit = limit.unwrap_or(10);
     let meeting_rows = supabase.get_upcoming_meetings(&user_id, limit).await?;
 
-    let mut meetings = Vec::new();
-    for row in meeting_rows {
-        let meeting = meeting_row_to_meeting(row, &app_state).await?;
-        meetings.push(meeting);
-    }
+    let meetings = meeting_rows_to_meetings(meeting_rows, &app_state).await?;
 
     // Cache the result
     {
@@ -281,6 +658,7 @@ pub async fn get_upcoming_meetings(
 
 /// Create a new meeting
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn create_meeting(
     params: CreateMeetingParams,
     app_state: State<'_, AppState>,
@@ -299,8 +677,21 @@ pub async fn create_meeting(
 
     drop(inner);
 
+    let time_zone = match params.time_zone.as_deref() {
+        Some(time_zone) => validate_time_zone(time_zone)?,
+        None => None,
+    };
+    if let Some(ref deadline) = params.rsvp_deadline {
+        datetime::parse_datetime(deadline)?;
+    }
+    let location = location_update(
+        params.location.as_deref(),
+        params.meeting_url.as_deref(),
+        params.room.as_deref(),
+    )?;
+
     // Create the meeting
-    let row = supabase
+    let mut row = supabase
         .create_meeting(
             &user_id,
             &params.title,
@@ -311,10 +702,40 @@ pub async fn create_meeting(
         )
         .await?;
 
+    if let Some(time_zone) = time_zone {
+        supabase
+            .set_meeting_time_zone(&row.id, Some(&time_zone))
+            .await?;
+        row.time_zone = Some(time_zone);
+    }
+    if !location.is_empty() {
+        supabase.set_meeting_location(&row.id, &location).await?;
+        location.apply(&mut row);
+    }
+    if params.auto_start {
+        supabase.set_meeting_auto_start(&row.id, true).await?;
+        row.auto_start = true;
+    }
+    if params.google_meet {
+        supabase.set_meeting_google_meet(&row.id, true).await?;
+        row.google_meet = true;
+    }
+
     // Add attendees (organizer is auto-added by trigger)
     for attendee_id in &params.attendee_ids {
         if attendee_id != &user_id {
             supabase.add_meeting_attendee(&row.id, attendee_id).await?;
+            let optional = params.optional_attendee_ids.contains(attendee_id);
+            if optional || params.rsvp_deadline.is_some() {
+                supabase
+                    .set_attendee_attendance(
+                        &row.id,
+                        attendee_id,
+                        if optional { "optional" } else { "required" },
+                        params.rsvp_deadline.as_deref(),
+                    )
+                    .await?;
+            }
         }
     }
 
@@ -336,8 +757,109 @@ pub async fn create_meeting(
     Ok(meeting)
 }
 
+/// Copy a meeting to a new time, organized by the current user and with
+/// the same attendees. Recurrence exceptions stay with the original, whose
+/// dates they name
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn duplicate_meeting(
+    meeting_id: String,
+    new_time: String,
+    app_state: State<'_, AppState>,
+) -> Result<Meeting> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    datetime::parse_datetime(&new_time)?;
+
+    // Occurrences duplicate the recurring meeting they belong to
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+    let source = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Meeting {}", meeting_id)))?;
+    let attendees = supabase.get_meeting_attendees(&source.id).await?;
+
+    let mut row = supabase
+        .create_meeting(
+            &user_id,
+            &source.title,
+            source.description.as_deref(),
+            &new_time,
+            source.duration_minutes,
+            source.recurrence_rule.as_deref(),
+        )
+        .await?;
+
+    if let Some(time_zone) = source.time_zone {
+        supabase
+            .set_meeting_time_zone(&row.id, Some(&time_zone))
+            .await?;
+        row.time_zone = Some(time_zone);
+    }
+    let location = MeetingLocationUpdate {
+        location: source.location.map(Some),
+        meeting_url: source.meeting_url.map(Some),
+        room: source.room.map(Some),
+    };
+    if !location.is_empty() {
+        supabase.set_meeting_location(&row.id, &location).await?;
+        location.apply(&mut row);
+    }
+    if source.auto_start {
+        supabase.set_meeting_auto_start(&row.id, true).await?;
+        row.auto_start = true;
+    }
+    if source.color.is_some() {
+        // Tags belong to the organizer, so only their own copies keep it
+        let tag_id = source.tag_id.filter(|_| source.organizer_id == user_id);
+        supabase
+            .set_meeting_tag(&row.id, tag_id.as_deref(), source.color.as_deref())
+            .await?;
+        row.tag_id = tag_id;
+        row.color = source.color;
+    }
+
+    // Add attendees (organizer is auto-added by trigger)
+    for attendee in &attendees {
+        if attendee.user_id != user_id {
+            supabase
+                .add_meeting_attendee(&row.id, &attendee.user_id)
+                .await?;
+        }
+    }
+
+    let meeting = meeting_row_to_meeting(row, &app_state).await?;
+
+    // Invalidate relevant caches
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        if let Some((year, month)) = extract_year_month(&meeting.scheduled_at) {
+            cache.invalidate_month(year, month);
+        }
+        cache.set_by_id(meeting.clone());
+        cache.set_upcoming(Vec::new());
+        tracing::debug!("Cache invalidated after duplicate_meeting");
+    }
+
+    tracing::info!("Duplicated meeting {} as {}", meeting_id, meeting.id);
+    Ok(meeting)
+}
+
 /// Update a meeting
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn update_meeting(
     meeting_id: String,
     params: UpdateMeetingParams,
@@ -356,6 +878,17 @@ pub async fn update_meeting(
 
     drop(inner);
 
+    let time_zone = params
+        .time_zone
+        .as_deref()
+        .map(validate_time_zone)
+        .transpose()?;
+    let location = location_update(
+        params.location.as_deref(),
+        params.meeting_url.as_deref(),
+        params.room.as_deref(),
+    )?;
+
     // Get old meeting to know which month to invalidate
     let old_meeting = supabase.get_meeting(&meeting_id).await?;
 
@@ -369,6 +902,26 @@ pub async fn update_meeting(
             None,
         )
         .await?;
+    if let Some(time_zone) = time_zone {
+        supabase
+            .set_meeting_time_zone(&meeting_id, time_zone.as_deref())
+            .await?;
+    }
+    if !location.is_empty() {
+        supabase
+            .set_meeting_location(&meeting_id, &location)
+            .await?;
+    }
+    if let Some(auto_start) = params.auto_start {
+        supabase
+            .set_meeting_auto_start(&meeting_id, auto_start)
+            .await?;
+    }
+    if let Some(google_meet) = params.google_meet {
+        supabase
+            .set_meeting_google_meet(&meeting_id, google_meet)
+            .await?;
+    }
 
     // Invalidate caches
     {
@@ -395,82 +948,237 @@ pub async fn update_meeting(
     Ok(())
 }
 
-/// Cancel a meeting
+/// Tick or untick a checklist item in a meeting description (organizer or
+/// attendee)
+///
+/// Only that box changes; edits made meanwhile by others are kept.
 #[tauri::command]
-pub async fn cancel_meeting(
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn toggle_description_checkbox(
     meeting_id: String,
+    index: usize,
+    checked: bool,
     app_state: State<'_, AppState>,
-) -> Result<()> {
-    let inner = app_state.inner.read().await;
-    let _user = inner
-        .user
-        .as_ref()
-        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-
+) -> Result<Meeting> {
     let supabase = app_state
         .supabase
         .as_ref()
         .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    drop(inner);
-
-    // Get meeting to know which month to invalidate
-    let meeting = supabase.get_meeting(&meeting_id).await?;
+    let mut attempts = 0;
+    let row = loop {
+        attempts += 1;
+        let current = supabase
+            .get_meeting(&meeting_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Meeting {}", meeting_id)))?;
+        let previous = current
+            .description
+            .clone()
+            .ok_or_else(|| Error::NotFound(format!("Checklist item {}", index)))?;
+        let description = markdown::set_checkbox(&previous, index, checked)?;
+        if description == previous {
+            // Already in that state
+            break current;
+        }
 
-    supabase
-        .update_meeting(&meeting_id, None, None, None, None, Some("cancelled"))
-        .await?;
+        if let Some(row) = supabase
+            .set_meeting_checklist(&meeting_id, &previous, &description)
+            .await?
+        {
+            break row;
+        }
+        if attempts >= MAX_CHECKLIST_ATTEMPTS {
+            return Err(Error::Database(
+                "Meeting description keeps changing, try again".to_string(),
+            ));
+        }
+        tracing::debug!("Description of {} changed meanwhile, retrying", meeting_id);
+    };
 
-    // Invalidate caches
+    let meeting = meeting_row_to_meeting(row, &app_state).await?;
     {
         let mut cache = app_state.cache.meetings.write().await;
         cache.invalidate_meeting(&meeting_id);
-
-        if let Some(m) = meeting {
-            if let Some((year, month)) = extract_year_month(&m.scheduled_at) {
-                cache.invalidate_month(year, month);
-            }
+        cache.set_by_id(meeting.clone());
+        if let Some((year, month)) = extract_year_month(&meeting.scheduled_at) {
+            cache.invalidate_month(year, month);
         }
-
-        tracing::debug!("Cache invalidated after cancel_meeting");
+        tracing::debug!("Cache invalidated after toggle_description_checkbox");
     }
 
-    Ok(())
+    Ok(meeting)
 }
 
-/// Delete a meeting
-#[tauri::command]
-pub async fn delete_meeting(
-    meeting_id: String,
-    app_state: State<'_, AppState>,
-) -> Result<()> {
-    let inner = app_state.inner.read().await;
-    let _user = inner
+/// Recurring meeting organized by the user, whose occurrences they may change
+async fn organized_recurring_meeting(app_state: &AppState, meeting_id: &str) -> Result<MeetingRow> {
+    let user_id = app_state
+        .inner
+        .read()
+        .await
         .user
         .as_ref()
+        .map(|user| user.id.clone())
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-
     let supabase = app_state
         .supabase
         .as_ref()
         .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    drop(inner);
+    let current = supabase
+        .get_meeting(meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Meeting {}", meeting_id)))?;
+    if current.organizer_id != user_id {
+        return Err(Error::Auth(
+            "Only the organizer can change a meeting's occurrences".to_string(),
+        ));
+    }
+    if current.recurrence_rule.is_none() {
+        return Err(Error::Input("Meeting does not recur".to_string()));
+    }
+    Ok(current)
+}
 
-    // Get meeting before deleting to know which month to invalidate
-    let meeting = supabase.get_meeting(&meeting_id).await?;
+/// Save a recurring meeting's exceptions and refresh caches
+async fn save_recurrence_exceptions(
+    app_state: &AppState,
+    meeting_id: &str,
+    exceptions: &RecurrenceExceptions,
+) -> Result<Meeting> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    supabase.delete_meeting(&meeting_id).await?;
+    let exceptions = exceptions.normalized()?;
+    let row = supabase
+        .set_meeting_recurrence_exceptions(meeting_id, &exceptions.exdates, &exceptions.rdates)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Meeting {}", meeting_id)))?;
 
-    // Invalidate caches
+    let meeting = meeting_row_to_meeting(row, app_state).await?;
     {
         let mut cache = app_state.cache.meetings.write().await;
-        cache.invalidate_meeting(&meeting_id);
-
-        if let Some(m) = meeting {
-            if let Some((year, month)) = extract_year_month(&m.scheduled_at) {
-                cache.invalidate_month(year, month);
-            }
+        cache.invalidate_meeting(meeting_id);
+        cache.set_by_id(meeting.clone());
+        if let Some((year, month)) = extract_year_month(&meeting.scheduled_at) {
+            cache.invalidate_month(year, month);
+        }
+    }
+
+    Ok(meeting)
+}
+
+/// Skip one occurrence of a recurring meeting, such as next week's standup
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn skip_meeting_occurrence(
+    meeting_id: String,
+    occurrence_date: String,
+    app_state: State<'_, AppState>,
+) -> Result<Meeting> {
+    let current = organized_recurring_meeting(&app_state, &meeting_id).await?;
+
+    let mut exceptions = RecurrenceExceptions {
+        exdates: current.recurrence_exdates,
+        rdates: current.recurrence_rdates,
+    };
+    exceptions.skip(&occurrence_date)?;
+
+    save_recurrence_exceptions(&app_state, &meeting_id, &exceptions).await
+}
+
+/// Replace the occurrences removed from and added to a recurring meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_meeting_recurrence_exceptions(
+    meeting_id: String,
+    exceptions: RecurrenceExceptions,
+    app_state: State<'_, AppState>,
+) -> Result<Meeting> {
+    organized_recurring_meeting(&app_state, &meeting_id).await?;
+    save_recurrence_exceptions(&app_state, &meeting_id, &exceptions).await
+}
+
+/// Cancel a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn cancel_meeting(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    // Get meeting to know which month to invalidate
+    let meeting = supabase.get_meeting(&meeting_id).await?;
+
+    supabase
+        .update_meeting(&meeting_id, None, None, None, None, Some("cancelled"))
+        .await?;
+
+    // Invalidate caches
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting_id);
+
+        if let Some(m) = meeting {
+            if let Some((year, month)) = extract_year_month(&m.scheduled_at) {
+                cache.invalidate_month(year, month);
+            }
+        }
+
+        tracing::debug!("Cache invalidated after cancel_meeting");
+    }
+
+    Ok(())
+}
+
+/// Delete a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn delete_meeting(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    // Get meeting before deleting to know which month to invalidate
+    let meeting = supabase.get_meeting(&meeting_id).await?;
+
+    supabase.delete_meeting(&meeting_id).await?;
+
+    // Invalidate caches
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting_id);
+
+        if let Some(m) = meeting {
+            if let Some((year, month)) = extract_year_month(&m.scheduled_at) {
+                cache.invalidate_month(year, month);
+            }
         }
 
         tracing::debug!("Cache invalidated after delete_meeting");
@@ -481,10 +1189,12 @@ pub async fn delete_meeting(
 
 /// Respond to a meeting invitation
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn respond_to_meeting(
     meeting_id: String,
     response: String,
     app_state: State<'_, AppState>,
+    app_handle: AppHandle,
 ) -> Result<()> {
     let inner = app_state.inner.read().await;
     let user = inner
@@ -508,12 +1218,14 @@ pub async fn respond_to_meeting(
     supabase
         .update_attendee_response(&meeting_id, &user_id, &response)
         .await?;
+    badge::request_refresh(&app_handle);
 
     Ok(())
 }
 
 /// Add an attendee to a meeting
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn add_meeting_attendee(
     meeting_id: String,
     user_id: String,
@@ -537,8 +1249,54 @@ pub async fn add_meeting_attendee(
     Ok(())
 }
 
+/// Make an attendee required or optional and set when they should answer by
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_attendee_attendance(
+    meeting_id: String,
+    user_id: String,
+    attendance_type: String,
+    rsvp_deadline: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _current_user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    validate_attendance_type(&attendance_type)?;
+    if let Some(ref deadline) = rsvp_deadline {
+        datetime::parse_datetime(deadline)?;
+    }
+
+    supabase
+        .set_attendee_attendance(
+            &meeting_id,
+            &user_id,
+            &attendance_type,
+            rsvp_deadline.as_deref(),
+        )
+        .await?;
+
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting_id);
+    }
+
+    Ok(())
+}
+
 /// Remove an attendee from a meeting
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn remove_meeting_attendee(
     meeting_id: String,
     user_id: String,
@@ -564,8 +1322,141 @@ pub async fn remove_meeting_attendee(
     Ok(())
 }
 
+/// Get the external guests of a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_meeting_guests(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<MeetingGuest>> {
+    let inner = app_state.inner.read().await;
+    let _current_user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let guests = supabase.get_meeting_guests(&meeting_id).await?;
+
+    Ok(guests.into_iter().map(MeetingGuest::from).collect())
+}
+
+/// Invite someone without an account to a meeting by email
+///
+/// They get the invitation, and later updates or the cancellation, by
+/// email with an .ics attachment.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn add_meeting_guest(
+    meeting_id: String,
+    email: String,
+    display_name: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<MeetingGuest> {
+    let inner = app_state.inner.read().await;
+    let _current_user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let email = normalize_guest_email(&email)?;
+    let display_name = display_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    let guest = supabase
+        .add_meeting_guest(&meeting_id, &email, display_name)
+        .await?;
+
+    Ok(guest.into())
+}
+
+/// Remove an external guest from a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn remove_meeting_guest(
+    meeting_id: String,
+    guest_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _current_user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase
+        .remove_meeting_guest(&meeting_id, &guest_id)
+        .await?;
+
+    Ok(())
+}
+
+/// Start a meeting's session: create it, then link it to the meeting with
+/// its join code, so attendees see the meeting start
+pub(crate) async fn start_meeting_session(
+    app_state: &AppState,
+    user_id: &str,
+    meeting: &MeetingRow,
+) -> Result<MeetingStarting> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let join_code = session::generate_join_code();
+    let session = supabase
+        .create_session(
+            user_id,
+            &join_code,
+            session::DEFAULT_IDLE_TIMEOUT_SECS,
+            None,
+        )
+        .await?;
+    supabase
+        .link_meeting_to_session(&meeting.id, &session.id, &session.join_code)
+        .await?;
+
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting.id);
+        cache.set_upcoming(Vec::new());
+    }
+
+    tracing::info!("Meeting {} started as session {}", meeting.id, session.id);
+    Ok(MeetingStarting {
+        meeting_id: meeting.id.clone(),
+        title: meeting.title.clone(),
+        session_id: session.id,
+        join_code: session.join_code,
+        meeting_url: meeting.meeting_url.clone(),
+    })
+}
+
 /// Start a meeting (create session and link)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn start_meeting(
     meeting_id: String,
     app_state: State<'_, AppState>,
@@ -584,27 +1475,70 @@ pub async fn start_meeting(
 
     drop(inner);
 
-    // Generate join code
-    let join_code: String = (0..6)
-        .map(|_| {
-            let idx = rand::random::<usize>() % 36;
-            if idx < 10 {
-                (b'0' + idx as u8) as char
-            } else {
-                (b'A' + (idx - 10) as u8) as char
-            }
-        })
-        .collect();
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+    let meeting = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Meeting {}", meeting_id)))?;
+    let started = start_meeting_session(&app_state, &user_id, &meeting).await?;
 
-    // Create session
-    let session = supabase.create_session(&user_id, &join_code).await?;
+    crate::commands::reminders::record_meeting_join(supabase, &user_id, &meeting_id).await;
 
-    // Link meeting to session
-    supabase
-        .link_meeting_to_session(&meeting_id, &session.id)
-        .await?;
+    Ok(started.session_id)
+}
+
+/// Start a meeting for everyone (organizer only)
+///
+/// Attendees get `meeting:starting` with the join code on their next
+/// reminder check, as when the meeting starts on its own. A meeting
+/// already started returns its running session.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_meeting_everywhere(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<MeetingStarting> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    Ok(session.id)
+    drop(inner);
+
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+    let meeting = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Meeting {}", meeting_id)))?;
+    if meeting.organizer_id != user_id {
+        return Err(Error::Auth(
+            "Only the organizer can start the meeting for everyone".to_string(),
+        ));
+    }
+
+    let started = match (meeting.session_id.clone(), meeting.join_code.clone()) {
+        (Some(session_id), Some(join_code)) if meeting.status == "ongoing" => MeetingStarting {
+            meeting_id: meeting.id,
+            title: meeting.title,
+            session_id,
+            join_code,
+            meeting_url: meeting.meeting_url,
+        },
+        _ => start_meeting_session(&app_state, &user_id, &meeting).await?,
+    };
+
+    crate::commands::reminders::record_meeting_join(supabase, &user_id, &meeting_id).await;
+    notifications::announce_meeting_start(&app_handle, &started);
+
+    Ok(started)
 }
 
 // ==========================================
@@ -622,12 +1556,100 @@ pub struct MeetingFilter {
     pub organizer_id: Option<String>,
     /// Filter by title (case-insensitive search)
     pub title_search: Option<String>,
+    /// Filter by location or room (case-insensitive search)
+    pub location_search: Option<String>,
+    /// Only show meetings with (true) or without (false) a video call link
+    pub online: Option<bool>,
+    /// Filter by meeting tag ID
+    pub tag_id: Option<String>,
     /// Only show meetings where I haven't responded yet
     pub pending_response: Option<bool>,
 }
 
+impl MeetingFilter {
+    /// Whether a meeting matches every criterion set
+    fn matches(&self, meeting: &Meeting, user_id: &str) -> bool {
+        // Status filter
+        if let Some(ref status) = self.status {
+            if &meeting.status != status {
+                return false;
+            }
+        }
+
+        // Organizer filter
+        if let Some(ref organizer_id) = self.organizer_id {
+            if &meeting.organizer_id != organizer_id {
+                return false;
+            }
+        }
+
+        // Participant filter
+        if let Some(ref participant_id) = self.participant_id {
+            if !meeting
+                .attendees
+                .iter()
+                .any(|a| &a.user_id == participant_id)
+            {
+                return false;
+            }
+        }
+
+        // Title search
+        if let Some(ref search) = self.title_search {
+            if !meeting
+                .title
+                .to_lowercase()
+                .contains(&search.to_lowercase())
+            {
+                return false;
+            }
+        }
+
+        // Location search, in the location or the room
+        if let Some(ref search) = self.location_search {
+            let search = search.to_lowercase();
+            if ![&meeting.location, &meeting.room]
+                .into_iter()
+                .flatten()
+                .any(|place| place.to_lowercase().contains(&search))
+            {
+                return false;
+            }
+        }
+
+        // Online filter
+        if let Some(online) = self.online {
+            if meeting.meeting_url.is_some() != online {
+                return false;
+            }
+        }
+
+        // Tag filter
+        if let Some(ref tag_id) = self.tag_id {
+            if meeting.tag_id.as_ref() != Some(tag_id) {
+                return false;
+            }
+        }
+
+        // Pending response filter; a passed RSVP deadline no longer waits
+        if self.pending_response == Some(true) {
+            let my_response = meeting.attendees.iter().find(|a| a.user_id == user_id);
+            if let Some(attendee) = my_response {
+                if !attendee.is_pending(chrono::Utc::now()) {
+                    return false;
+                }
+            } else {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 /// Filter meetings based on criteria
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn filter_meetings(
     start_date: String,
     end_date: String,
@@ -653,58 +1675,12 @@ pub async fn filter_meetings(
         .get_meetings_in_range(&user_id, &start_date, &end_date)
         .await?;
 
-    let mut meetings = Vec::new();
-    for row in meeting_rows {
-        let meeting = meeting_row_to_meeting(row, &app_state).await?;
-        meetings.push(meeting);
-    }
+    let meetings = meeting_rows_to_meetings(meeting_rows, &app_state).await?;
 
     // Apply filters
     let results: Vec<Meeting> = meetings
         .into_iter()
-        .filter(|m| {
-            // Status filter
-            if let Some(ref status) = filter.status {
-                if &m.status != status {
-                    return false;
-                }
-            }
-
-            // Organizer filter
-            if let Some(ref organizer_id) = filter.organizer_id {
-                if &m.organizer_id != organizer_id {
-                    return false;
-                }
-            }
-
-            // Participant filter
-            if let Some(ref participant_id) = filter.participant_id {
-                if !m.attendees.iter().any(|a| &a.user_id == participant_id) {
-                    return false;
-                }
-            }
-
-            // Title search
-            if let Some(ref search) = filter.title_search {
-                if !m.title.to_lowercase().contains(&search.to_lowercase()) {
-                    return false;
-                }
-            }
-
-            // Pending response filter
-            if filter.pending_response == Some(true) {
-                let my_response = m.attendees.iter().find(|a| a.user_id == user_id);
-                if let Some(attendee) = my_response {
-                    if attendee.response_status != "invited" {
-                        return false;
-                    }
-                } else {
-                    return false;
-                }
-            }
-
-            true
-        })
+        .filter(|m| filter.matches(m, &user_id))
         .collect();
 
     tracing::debug!("Filtered meetings: {} results", results.len());
@@ -713,6 +1689,7 @@ pub async fn filter_meetings(
 
 /// Get meetings for a specific date (optimized for single day)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_meetings_for_date(
     date: String, // YYYY-MM-DD format
     app_state: State<'_, AppState>,
@@ -757,13 +1734,11 @@ pub async fn get_meetings_for_date(
         .get_meetings_in_range(&user_id, &start_date, &end_date)
         .await?;
 
-    let mut meetings = Vec::new();
-    for row in meeting_rows {
-        let meeting = meeting_row_to_meeting(row, &app_state).await?;
-        if meeting.status != "cancelled" {
-            meetings.push(meeting);
-        }
-    }
+    let meeting_rows: Vec<MeetingRow> = meeting_rows
+        .into_iter()
+        .filter(|row| row.status != "cancelled")
+        .collect();
+    let meetings = meeting_rows_to_meetings(meeting_rows, &app_state).await?;
 
     tracing::debug!("Fetched {} meetings for date {}", meetings.len(), date);
     Ok(meetings)
@@ -771,6 +1746,7 @@ pub async fn get_meetings_for_date(
 
 /// Search meetings by title
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn search_meetings(
     query: String,
     limit: Option<u32>,
@@ -805,23 +1781,19 @@ pub async fn search_meetings(
         .get_meetings_in_range(&user_id, &start_date, &end_date)
         .await?;
 
-    let mut results = Vec::new();
-    for row in meeting_rows {
-        if row.title.to_lowercase().contains(&query_lower)
-            || row
-                .description
-                .as_ref()
-                .map(|d| d.to_lowercase().contains(&query_lower))
-                .unwrap_or(false)
-        {
-            let meeting = meeting_row_to_meeting(row, &app_state).await?;
-            results.push(meeting);
-
-            if results.len() >= limit as usize {
-                break;
-            }
-        }
-    }
+    let matching_rows: Vec<MeetingRow> = meeting_rows
+        .into_iter()
+        .filter(|row| {
+            row.title.to_lowercase().contains(&query_lower)
+                || row
+                    .description
+                    .as_ref()
+                    .map(|d| d.to_lowercase().contains(&query_lower))
+                    .unwrap_or(false)
+        })
+        .take(limit as usize)
+        .collect();
+    let results = meeting_rows_to_meetings(matching_rows, &app_state).await?;
 
     tracing::debug!("Found {} meetings matching '{}'", results.len(), query);
     Ok(results)
@@ -829,6 +1801,7 @@ pub async fn search_meetings(
 
 /// Get meeting by session ID
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_meeting_by_session(
     session_id: String,
     app_state: State<'_, AppState>,
@@ -846,45 +1819,350 @@ pub async fn get_meeting_by_session(
 
     drop(inner);
 
-    // Query meetings with this session_id
-    let token = supabase
-        .get_access_token()
-        .await
+    match supabase.get_meeting_by_session_id(&session_id).await? {
+        Some(row) => Ok(Some(meeting_row_to_meeting(row, &app_state).await?)),
+        None => Ok(None),
+    }
+}
+
+// ==========================================
+// Agenda Commands
+// ==========================================
+
+/// A meeting occurrence on the agenda
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaMeeting {
+    /// Start of this occurrence
+    pub starts_at: String,
+    pub ends_at: String,
+    #[serde(flatten)]
+    pub meeting: Meeting,
+}
+
+/// Meetings of the range grouped by day, recurrences expanded
+async fn agenda_for_range(
+    range: &AgendaRange,
+    app_state: &AppState,
+) -> Result<Vec<AgendaDay<AgendaMeeting>>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
 
-    let url = format!(
-        "{}/rest/v1/meetings?session_id=eq.{}&limit=1",
-        std::env::var("VITE_SUPABASE_URL")
-            .or_else(|_| std::env::var("NEXT_PUBLIC_SUPABASE_URL"))
-            .map_err(|_| Error::Config("SUPABASE_URL not set".to_string()))?,
-        session_id
-    );
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let (range_start, range_end) = range.bounds()?;
+    // A day back so meetings still running when the range starts are included
+    let fetch_start = format_utc(&(range_start - chrono::Duration::days(1)));
+    let fetch_end = format_utc(&range_end);
+
+    let mut rows = supabase
+        .get_meetings_in_range(&user_id, &fetch_start, &fetch_end)
+        .await?;
+    let mut seen: HashSet<String> = rows.iter().map(|row| row.id.clone()).collect();
+    for row in supabase
+        .get_recurring_meetings(&user_id, Some(&fetch_end))
+        .await?
+    {
+        if seen.insert(row.id.clone()) {
+            rows.push(row);
+        }
+    }
+    let meetings = meeting_rows_to_meetings(rows, app_state).await?;
+
+    let mut items = Vec::new();
+    for meeting in meetings {
+        let starts = agenda::occurrences_in_range(
+            &meeting.scheduled_at,
+            meeting.duration_minutes,
+            meeting.recurrence_rule.as_deref(),
+            &meeting.recurrence_exceptions,
+            meeting.time_zone.as_deref(),
+            (range_start, range_end),
+        )?;
+        let duration = chrono::Duration::minutes(meeting.duration_minutes.max(0) as i64);
+        for start in starts {
+            items.push((
+                start,
+                AgendaMeeting {
+                    starts_at: format_utc(&start),
+                    ends_at: format_utc(&(start + duration)),
+                    meeting: meeting.clone(),
+                },
+            ));
+        }
+    }
+
+    Ok(range.group(items))
+}
+
+fn format_utc(dt: &chrono::DateTime<chrono::Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Get `days` days of meetings from `start_date` (YYYY-MM-DD), grouped by
+/// day in `time_zone` (the system one when unset)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_agenda(
+    start_date: String,
+    days: u32,
+    time_zone: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<AgendaDay<AgendaMeeting>>> {
+    let range = AgendaRange::new(&start_date, days, time_zone.as_deref())?;
+    agenda_for_range(&range, &app_state).await
+}
+
+/// Get the meetings of the week holding `date` (YYYY-MM-DD), grouped by day
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_week_meetings(
+    date: String,
+    start_on_sunday: Option<bool>,
+    time_zone: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<AgendaDay<AgendaMeeting>>> {
+    let range = AgendaRange::week(
+        &date,
+        start_on_sunday.unwrap_or(false),
+        time_zone.as_deref(),
+    )?;
+    agenda_for_range(&range, &app_state).await
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header(
-            "apikey",
-            std::env::var("VITE_SUPABASE_ANON_KEY")
-                .or_else(|_| std::env::var("NEXT_PUBLIC_SUPABASE_ANON_KEY"))
-                .map_err(|_| Error::Config("SUPABASE_ANON_KEY not set".to_string()))?,
+// ==========================================
+// Bulk Commands
+// ==========================================
+
+/// Maximum number of meetings accepted by a bulk operation
+const MAX_BULK_MEETINGS: usize = 100;
+
+/// Changes applied to every meeting of a bulk update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUpdateMeetingsParams {
+    pub meeting_ids: Vec<String>,
+    /// Move each meeting by this many minutes (e.g. 1440 to push a day)
+    pub shift_minutes: Option<i32>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub duration_minutes: Option<i32>,
+}
+
+/// Meetings of a range to move, and by how much
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkShiftMeetingsParams {
+    pub start_date: String,
+    pub end_date: String,
+    pub filter: MeetingFilter,
+    /// Minutes to move each meeting by, negative to move it earlier
+    pub shift_minutes: i32,
+}
+
+/// Outcome of a bulk operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkOperationResult {
+    pub updated_ids: Vec<String>,
+    /// Requested IDs that were not changed (not found or not organized by the user)
+    pub skipped_ids: Vec<String>,
+}
+
+fn validate_bulk_ids(meeting_ids: &[String]) -> Result<()> {
+    if meeting_ids.is_empty() {
+        return Err(Error::Parse("No meetings selected".to_string()));
+    }
+    if meeting_ids.len() > MAX_BULK_MEETINGS {
+        return Err(Error::Parse(format!(
+            "Too many meetings in one bulk operation (max {})",
+            MAX_BULK_MEETINGS
+        )));
+    }
+    Ok(())
+}
+
+/// Year and month of a meeting before it was shifted
+fn shifted_year_month(scheduled_at: &str, shift_minutes: i32) -> Option<(i32, u32)> {
+    let dt = chrono::DateTime::parse_from_rfc3339(scheduled_at).ok()?;
+    let original = dt - chrono::Duration::minutes(shift_minutes as i64);
+    Some((original.year(), original.month()))
+}
+
+/// Drop cached data of bulk-changed meetings, in the months they were
+/// moved from as well as the ones they are in
+async fn invalidate_bulk_rows(
+    app_state: &AppState,
+    rows: &[MeetingRow],
+    shift_minutes: Option<i32>,
+) {
+    let mut cache = app_state.cache.meetings.write().await;
+    for row in rows {
+        cache.invalidate_meeting(&row.id);
+        if let Some((year, month)) = extract_year_month(&row.scheduled_at) {
+            cache.invalidate_month(year, month);
+        }
+        if let Some(shift) = shift_minutes {
+            if let Some((year, month)) = shifted_year_month(&row.scheduled_at, shift) {
+                cache.invalidate_month(year, month);
+            }
+        }
+    }
+}
+
+fn bulk_result(requested: &[String], rows: &[MeetingRow]) -> BulkOperationResult {
+    let updated_ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+    let skipped_ids = requested
+        .iter()
+        .filter(|id| !updated_ids.contains(id))
+        .cloned()
+        .collect();
+    BulkOperationResult {
+        updated_ids,
+        skipped_ids,
+    }
+}
+
+/// Update many meetings at once (single request, single cache invalidation pass)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn bulk_update_meetings(
+    params: BulkUpdateMeetingsParams,
+    app_state: State<'_, AppState>,
+) -> Result<BulkOperationResult> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    validate_bulk_ids(&params.meeting_ids)?;
+    if params.duration_minutes.is_some_and(|d| d <= 0) {
+        return Err(Error::Parse("Duration must be positive".to_string()));
+    }
+
+    let rows = supabase
+        .bulk_update_meetings(
+            &params.meeting_ids,
+            params.shift_minutes,
+            params.title.as_deref(),
+            params.description.as_deref(),
+            params.duration_minutes,
         )
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await
-        .map_err(|e| Error::Network(e.to_string()))?;
+        .await?;
 
-    if !response.status().is_success() {
-        return Ok(None);
+    invalidate_bulk_rows(&app_state, &rows, params.shift_minutes).await;
+    tracing::debug!("Cache invalidated after bulk_update_meetings");
+
+    tracing::info!("Bulk updated {} meetings", rows.len());
+    Ok(bulk_result(&params.meeting_ids, &rows))
+}
+
+/// Move every meeting of a range matching the filter, such as a sprint's
+/// ceremonies pushed by a day. Only meetings the user organizes move; a
+/// recurring meeting moves as a whole
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn bulk_shift_meetings(
+    params: BulkShiftMeetingsParams,
+    app_state: State<'_, AppState>,
+) -> Result<BulkOperationResult> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    if params.shift_minutes == 0 {
+        return Err(Error::Parse("Shift must not be zero".to_string()));
     }
 
-    let meetings: Vec<MeetingRow> = response
-        .json()
-        .await
-        .map_err(|e| Error::Parse(e.to_string()))?;
+    let meeting_rows = supabase
+        .get_meetings_in_range(&user_id, &params.start_date, &params.end_date)
+        .await?;
+    let meetings = meeting_rows_to_meetings(meeting_rows, &app_state).await?;
+    let meeting_ids: Vec<String> = meetings
+        .iter()
+        .filter(|m| m.organizer_id == user_id && params.filter.matches(m, &user_id))
+        .map(|m| m.id.clone())
+        .collect();
+    if meeting_ids.is_empty() {
+        return Ok(bulk_result(&[], &[]));
+    }
+    validate_bulk_ids(&meeting_ids)?;
 
-    match meetings.into_iter().next() {
-        Some(row) => Ok(Some(meeting_row_to_meeting(row, &app_state).await?)),
-        None => Ok(None),
+    let rows = supabase
+        .bulk_update_meetings(&meeting_ids, Some(params.shift_minutes), None, None, None)
+        .await?;
+
+    invalidate_bulk_rows(&app_state, &rows, Some(params.shift_minutes)).await;
+    tracing::debug!("Cache invalidated after bulk_shift_meetings");
+
+    tracing::info!(
+        "Shifted {} meetings by {} minutes",
+        rows.len(),
+        params.shift_minutes
+    );
+    Ok(bulk_result(&meeting_ids, &rows))
+}
+
+/// Cancel many meetings at once (single request, single cache invalidation pass)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn bulk_cancel_meetings(
+    meeting_ids: Vec<String>,
+    app_state: State<'_, AppState>,
+) -> Result<BulkOperationResult> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    validate_bulk_ids(&meeting_ids)?;
+
+    let rows = supabase.bulk_cancel_meetings(&meeting_ids).await?;
+
+    // Invalidate caches
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        for row in &rows {
+            cache.invalidate_meeting(&row.id);
+            if let Some((year, month)) = extract_year_month(&row.scheduled_at) {
+                cache.invalidate_month(year, month);
+            }
+        }
+
+        tracing::debug!("Cache invalidated after bulk_cancel_meetings");
     }
+
+    tracing::info!("Bulk cancelled {} meetings", rows.len());
+    Ok(bulk_result(&meeting_ids, &rows))
 }
@@ -0,0 +1,136 @@
+//! ICS subscription feed commands
+//!
+//! Provisions the private token behind the user's read-only ICS feed, served
+//! by the `calendar-feed` edge function, so meetings can be subscribed to from
+//! Apple Calendar, Outlook and other calendar apps.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::state::AppState;
+use crate::supabase::{CalendarFeedTokenRow, SupabaseClient};
+use crate::{Error, Result};
+
+const FEED_FUNCTION: &str = "calendar-feed";
+
+/// Random bytes per token (hex encoded to twice the length)
+const FEED_TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeed {
+    /// HTTPS URL, for apps that subscribe by URL (Outlook, Google)
+    pub url: String,
+    /// webcal:// URL, opens the subscription dialog on macOS/iOS
+    pub webcal_url: String,
+    pub created_at: Option<String>,
+    pub last_accessed_at: Option<String>,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn generate_feed_token() -> String {
+    (0..FEED_TOKEN_BYTES)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect()
+}
+
+fn row_to_feed(supabase: &SupabaseClient, row: CalendarFeedTokenRow) -> CalendarFeed {
+    let url = format!("{}?token={}", supabase.function_url(FEED_FUNCTION), row.token);
+    let webcal_url = url
+        .replacen("https://", "webcal://", 1)
+        .replacen("http://", "webcal://", 1);
+
+    CalendarFeed {
+        url,
+        webcal_url,
+        created_at: row.created_at,
+        last_accessed_at: row.last_accessed_at,
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the user's ICS feed URL, provisioning a token on first use
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_calendar_feed(app_state: State<'_, AppState>) -> Result<CalendarFeed> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let row = match supabase.get_calendar_feed_token(&user_id).await? {
+        Some(row) => row,
+        None => {
+            tracing::info!("Provisioning calendar feed for user {}", user_id);
+            supabase
+                .save_calendar_feed_token(&user_id, &generate_feed_token())
+                .await?
+        }
+    };
+
+    Ok(row_to_feed(supabase, row))
+}
+
+/// Replace the feed token, invalidating every existing subscription
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn rotate_calendar_feed_token(app_state: State<'_, AppState>) -> Result<CalendarFeed> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let row = supabase
+        .save_calendar_feed_token(&user_id, &generate_feed_token())
+        .await?;
+
+    tracing::info!("Calendar feed token rotated for user {}", user_id);
+    Ok(row_to_feed(supabase, row))
+}
+
+/// Disable the ICS feed by deleting its token
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn disable_calendar_feed(app_state: State<'_, AppState>) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase.delete_calendar_feed_token(&user_id).await?;
+
+    tracing::info!("Calendar feed disabled for user {}", user_id);
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! iCalendar (.ics) export and import commands
+//!
+//! Exports return the file contents for the frontend to save. Other users'
+//! emails aren't visible to the app, so attendees are written with
+//! `urn:uuid:` addresses holding their user ID, and only the signed-in user
+//! gets a `mailto:` address. Imported events become meetings organized by
+//! the signed-in user; attendees are added when they are app users named
+//! by such an address.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::calendar::{
+    extract_year_month, meeting_row_to_meeting, meeting_rows_to_meetings, Meeting,
+};
+use crate::state::AppState;
+use crate::utils::ics::{self, IcsEvent, IcsPerson};
+use crate::utils::rrule;
+use crate::{Error, Result};
+
+/// Largest file accepted for import
+const MAX_ICS_BYTES: usize = 1024 * 1024;
+
+/// Title of imported events without a summary
+const UNTITLED_MEETING: &str = "Untitled meeting";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IcsImportResult {
+    /// Meetings created from the file's events
+    pub meetings: Vec<Meeting>,
+    /// Events already in the calendar, cancelled, or unreadable
+    pub skipped: usize,
+    /// Attendee addresses that don't match an app user
+    pub unmatched_attendees: Vec<String>,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Calendar address of a user, `mailto:` for the signed-in one
+fn user_address(user_id: &str, current_user: (&str, &str)) -> String {
+    if user_id == current_user.0 {
+        format!("mailto:{}", current_user.1)
+    } else {
+        format!("urn:uuid:{}", user_id)
+    }
+}
+
+fn meeting_to_event(meeting: &Meeting, current_user: (&str, &str)) -> Result<IcsEvent> {
+    let start = chrono::DateTime::parse_from_rfc3339(&meeting.scheduled_at)
+        .map_err(|e| Error::Parse(format!("Invalid meeting time: {}", e)))?
+        .with_timezone(&chrono::Utc);
+
+    Ok(IcsEvent {
+        uid: ics::meeting_uid(&meeting.id),
+        summary: meeting.title.clone(),
+        description: meeting.description.clone(),
+        start,
+        duration_minutes: meeting.duration_minutes,
+        recurrence_rule: meeting
+            .recurrence_rule
+            .as_deref()
+            .map(|rule| rule.trim_start_matches("RRULE:").to_string()),
+        cancelled: meeting.status == "cancelled",
+        organizer: Some(IcsPerson {
+            address: user_address(&meeting.organizer_id, current_user),
+            name: Some(meeting.organizer_name.clone()),
+            status: None,
+        }),
+        attendees: meeting
+            .attendees
+            .iter()
+            .filter(|a| a.user_id != meeting.organizer_id)
+            .map(|a| IcsPerson {
+                address: user_address(&a.user_id, current_user),
+                name: Some(a.display_name.clone()),
+                status: Some(ics::partstat(&a.response_status).to_string()),
+            })
+            .collect(),
+        last_modified: meeting
+            .updated_at
+            .as_deref()
+            .and_then(|updated| chrono::DateTime::parse_from_rfc3339(updated).ok())
+            .map(|updated| updated.with_timezone(&chrono::Utc)),
+    })
+}
+
+fn write_meetings(meetings: &[Meeting], current_user: (&str, &str)) -> Result<String> {
+    let events = meetings
+        .iter()
+        .map(|meeting| meeting_to_event(meeting, current_user))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ics::write_calendar(&events, chrono::Utc::now()))
+}
+
+async fn current_user(app_state: &AppState) -> Result<(String, String)> {
+    app_state
+        .inner
+        .read()
+        .await
+        .user
+        .as_ref()
+        .map(|user| (user.id.clone(), user.email.clone()))
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Export a meeting as an .ics file
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn export_meeting_ics(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<String> {
+    let (user_id, email) = current_user(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let row = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Meeting {} not found", meeting_id)))?;
+    let meeting = meeting_row_to_meeting(row, &app_state).await?;
+
+    write_meetings(&[meeting], (&user_id, &email))
+}
+
+/// Export the meetings of a date range as an .ics file
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn export_calendar_ics(
+    start_date: String,
+    end_date: String,
+    app_state: State<'_, AppState>,
+) -> Result<String> {
+    let (user_id, email) = current_user(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let rows = supabase
+        .get_meetings_in_range(&user_id, &start_date, &end_date)
+        .await?;
+    let meetings = meeting_rows_to_meetings(rows, &app_state).await?;
+
+    tracing::info!("Exporting {} meetings as iCalendar", meetings.len());
+    write_meetings(&meetings, (&user_id, &email))
+}
+
+/// Create meetings from the events of an .ics file
+///
+/// Events exported from meetings that still exist, and cancelled events,
+/// are skipped. Recurrence rules the app can't read are dropped, keeping
+/// the first occurrence.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn import_ics(
+    contents: String,
+    app_state: State<'_, AppState>,
+) -> Result<IcsImportResult> {
+    if contents.len() > MAX_ICS_BYTES {
+        return Err(Error::Input(format!(
+            "Calendar file is larger than {} KB",
+            MAX_ICS_BYTES / 1024
+        )));
+    }
+    let (user_id, email) = current_user(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let (events, unreadable) = ics::parse_calendar(&contents)?;
+    let mut result = IcsImportResult {
+        meetings: Vec::new(),
+        skipped: unreadable,
+        unmatched_attendees: Vec::new(),
+    };
+
+    for event in events {
+        if event.cancelled {
+            result.skipped += 1;
+            continue;
+        }
+        if let Some(meeting_id) = event.meeting_id() {
+            if supabase.get_meeting(meeting_id).await?.is_some() {
+                result.skipped += 1;
+                continue;
+            }
+        }
+
+        let recurrence_rule = event
+            .recurrence_rule
+            .filter(|rule| rrule::validate_rrule(rule).is_ok());
+        let title = if event.summary.is_empty() {
+            UNTITLED_MEETING
+        } else {
+            event.summary.as_str()
+        };
+        let row = supabase
+            .create_meeting(
+                &user_id,
+                title,
+                event.description.as_deref(),
+                &event.start.to_rfc3339(),
+                event.duration_minutes,
+                recurrence_rule.as_deref(),
+            )
+            .await?;
+
+        for attendee in &event.attendees {
+            if attendee
+                .email()
+                .is_some_and(|address| address.eq_ignore_ascii_case(&email))
+            {
+                continue;
+            }
+            match attendee.user_id() {
+                Some(attendee_id) if attendee_id != user_id => {
+                    if let Err(e) = supabase.add_meeting_attendee(&row.id, attendee_id).await {
+                        tracing::warn!("Failed to add imported attendee: {}", e);
+                        result.unmatched_attendees.push(attendee.address.clone());
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    let address = attendee.email().unwrap_or(&attendee.address);
+                    result.unmatched_attendees.push(address.to_string());
+                }
+            }
+        }
+
+        result
+            .meetings
+            .push(meeting_row_to_meeting(row, &app_state).await?);
+    }
+
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        for meeting in &result.meetings {
+            if let Some((year, month)) = extract_year_month(&meeting.scheduled_at) {
+                cache.invalidate_month(year, month);
+            }
+            cache.set_by_id(meeting.clone());
+        }
+        cache.set_upcoming(Vec::new());
+    }
+
+    tracing::info!(
+        "Imported {} meetings from iCalendar, skipped {}",
+        result.meetings.len(),
+        result.skipped
+    );
+    Ok(result)
+}
@@ -0,0 +1,255 @@
+//! Camera commands
+//!
+//! Participants can show their webcam next to the shared screen. While the
+//! host streams natively, its camera goes out on its own track (see `peer`);
+//! viewers publish theirs on the frontend's peer connection and announce the
+//! stream ID here. Everyone gets layout hints as `camera:layout-changed`.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::camera::{self, CameraDevice};
+use crate::commands::signaling::{SignalingState, SignalingStateInner};
+use crate::commands::stream::StreamState;
+use crate::peer::{CAMERA_STREAM_ID, SCREEN_STREAM_ID};
+use crate::realtime::SignalingMessage;
+use crate::state::AppState;
+use crate::{Error, Result};
+
+/// Camera frame rate; faces need less than screens
+const DEFAULT_CAMERA_FPS: u32 = 15;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoKind {
+    Screen,
+    Camera,
+}
+
+/// Video to render, matched to a received track by its media stream ID
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoTile {
+    pub user_id: String,
+    pub kind: VideoKind,
+    pub stream_id: String,
+    /// Own camera, rendered from the local preview instead of a track
+    pub is_local: bool,
+}
+
+/// Where each video goes
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoLayout {
+    /// Main area: the host's screen
+    pub stage: Option<VideoTile>,
+    /// Cameras in a filmstrip beside the stage, in roster order
+    pub tiles: Vec<VideoTile>,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Layout of the session as it stands
+pub(crate) fn video_layout(state: &SignalingStateInner, local_user_id: &str) -> VideoLayout {
+    let roster = state.roster();
+    let stage = roster.iter().find(|p| p.is_host).map(|host| VideoTile {
+        user_id: host.user_id.clone(),
+        kind: VideoKind::Screen,
+        stream_id: SCREEN_STREAM_ID.to_string(),
+        is_local: host.user_id == local_user_id,
+    });
+    let tiles = roster
+        .iter()
+        .filter_map(|p| {
+            state.cameras.get(&p.user_id).map(|stream_id| VideoTile {
+                user_id: p.user_id.clone(),
+                kind: VideoKind::Camera,
+                stream_id: stream_id.clone(),
+                is_local: p.user_id == local_user_id,
+            })
+        })
+        .collect();
+
+    VideoLayout { stage, tiles }
+}
+
+/// Notify the frontend of the current layout
+pub(crate) fn emit_layout(
+    app_handle: &AppHandle,
+    state: &SignalingStateInner,
+    local_user_id: &str,
+) {
+    let layout = video_layout(state, local_user_id);
+    if let Err(e) = app_handle.emit("camera:layout-changed", &layout) {
+        tracing::error!("Failed to emit camera layout event: {}", e);
+    }
+}
+
+/// Apply a camera turned on or off by another participant
+pub(crate) async fn handle_camera_changed(
+    app_handle: &AppHandle,
+    from_user_id: &str,
+    enabled: bool,
+    stream_id: Option<String>,
+) {
+    let local_user_id = {
+        let app_state = app_handle.state::<AppState>();
+        let inner = app_state.inner.read().await;
+        inner
+            .user
+            .as_ref()
+            .map(|u| u.id.clone())
+            .unwrap_or_default()
+    };
+
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    if enabled {
+        let stream_id = stream_id.unwrap_or_else(|| CAMERA_STREAM_ID.to_string());
+        state.cameras.insert(from_user_id.to_string(), stream_id);
+    } else {
+        state.cameras.remove(from_user_id);
+    }
+    emit_layout(app_handle, &state, &local_user_id);
+}
+
+/// Record the local camera and tell everyone, `None` when it is off
+async fn set_local_camera(app_handle: &AppHandle, user_id: &str, stream_id: Option<String>) {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    match &stream_id {
+        Some(stream_id) => {
+            state.cameras.insert(user_id.to_string(), stream_id.clone());
+        }
+        None => {
+            state.cameras.remove(user_id);
+        }
+    }
+    emit_layout(app_handle, &state, user_id);
+
+    if let Some(tx) = state.signaling_tx.clone() {
+        let msg = SignalingMessage::CameraChanged {
+            from_user_id: user_id.to_string(),
+            enabled: stream_id.is_some(),
+            stream_id,
+        };
+        drop(state);
+        if let Err(e) = tx.send(msg).await {
+            tracing::warn!("Failed to announce camera change: {}", e);
+        }
+    }
+}
+
+/// Turn the local camera off in the layout once the native stream stops
+pub(crate) async fn native_stream_stopped(app_handle: &AppHandle, user_id: &str) {
+    let was_on = {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let state = signaling_state.inner.read().await;
+        state.cameras.contains_key(user_id)
+    };
+    if was_on {
+        set_local_camera(app_handle, user_id, None).await;
+    }
+}
+
+async fn local_user_id(app_state: &AppState) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    if inner.session.is_none() {
+        return Err(Error::Session("No active session".to_string()));
+    }
+    Ok(user.id.clone())
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// List the cameras attached to the machine
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_cameras() -> Result<Vec<CameraDevice>> {
+    tokio::task::spawn_blocking(camera::list_cameras)
+        .await
+        .map_err(|e| Error::Capture(format!("Camera listing task failed: {}", e)))?
+}
+
+/// Turn the local camera on
+///
+/// While hosting a native stream, the backend captures `camera_id` (the
+/// first camera by default). Otherwise the frontend publishes the camera on
+/// its own connection and passes the `stream_id` it used.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_camera(
+    camera_id: Option<String>,
+    stream_id: Option<String>,
+    app_state: State<'_, AppState>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let user_id = local_user_id(&app_state).await?;
+
+    let state = stream_state.inner.read().await;
+    let stream_id = match state.camera.as_ref() {
+        Some(pipeline) => {
+            let camera_id = match camera_id {
+                Some(camera_id) => camera_id,
+                None => list_cameras()
+                    .await?
+                    .into_iter()
+                    .next()
+                    .map(|c| c.id)
+                    .ok_or_else(|| Error::NotFound("No camera found".to_string()))?,
+            };
+            pipeline.start(camera_id, DEFAULT_CAMERA_FPS).await?;
+            CAMERA_STREAM_ID.to_string()
+        }
+        None => stream_id.ok_or_else(|| {
+            Error::Session("Stream ID of the published camera is required".to_string())
+        })?,
+    };
+    drop(state);
+
+    set_local_camera(&app_handle, &user_id, Some(stream_id)).await;
+    tracing::info!("Camera turned on");
+    Ok(())
+}
+
+/// Turn the local camera off
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn stop_camera(
+    app_state: State<'_, AppState>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let user_id = local_user_id(&app_state).await?;
+
+    if let Some(pipeline) = stream_state.inner.read().await.camera.as_ref() {
+        pipeline.stop();
+    }
+
+    set_local_camera(&app_handle, &user_id, None).await;
+    tracing::info!("Camera turned off");
+    Ok(())
+}
+
+/// Get where each screen and camera video goes
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_video_layout(
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+) -> Result<VideoLayout> {
+    let user_id = local_user_id(&app_state).await?;
+    let state = signaling_state.inner.read().await;
+    Ok(video_layout(&state, &user_id))
+}
@@ -1,15 +1,17 @@
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
-use crate::capture::{self, CaptureSource};
+use crate::capture::{self, AppLock, CaptureSource};
 use crate::state::AppState;
-use crate::Result;
+use crate::{Error, Result};
 
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_sources() -> Result<Vec<CaptureSource>> {
     capture::get_available_sources()
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn start_capture(source_id: String, state: State<'_, AppState>) -> Result<()> {
     let mut inner = state.inner.write().await;
     inner.is_capturing = true;
@@ -18,9 +20,69 @@ pub async fn start_capture(source_id: String, state: State<'_, AppState>) -> Res
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn stop_capture(state: State<'_, AppState>) -> Result<()> {
     let mut inner = state.inner.write().await;
     inner.is_capturing = false;
     tracing::info!("Stopped capture");
     Ok(())
 }
+
+// ==========================================
+// App Lock
+// ==========================================
+
+/// List applications with visible windows the session can be locked to
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_running_apps() -> Result<Vec<String>> {
+    tokio::task::spawn_blocking(capture::running_apps)
+        .await
+        .map_err(|e| Error::Capture(format!("Failed to list applications: {}", e)))?
+}
+
+/// Get the application the session is locked to, if any
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_app_lock(state: State<'_, AppState>) -> Result<Option<AppLock>> {
+    Ok(capture::current_app_lock(&state.app_lock))
+}
+
+/// Lock the session to one application, or unlock it with `None`
+///
+/// While locked, only that application's windows are streamed and remote
+/// input is refused unless it has focus.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_app_lock(
+    app_name: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<Option<AppLock>> {
+    let inner = state.inner.read().await;
+    if inner.session.as_ref().is_some_and(|s| !s.is_host) {
+        return Err(Error::Session(
+            "Only the host can lock the session to an application".to_string(),
+        ));
+    }
+    drop(inner);
+
+    let lock = match app_name.map(|name| name.trim().to_string()) {
+        Some(name) if name.is_empty() => {
+            return Err(Error::Capture("Application name is required".to_string()));
+        }
+        Some(name) => Some(AppLock { app_name: name }),
+        None => None,
+    };
+
+    *state.app_lock.write().unwrap_or_else(|e| e.into_inner()) = lock.clone();
+
+    match &lock {
+        Some(lock) => tracing::info!("Session locked to {}", lock.app_name),
+        None => tracing::info!("Session app lock removed"),
+    }
+    if let Err(e) = app_handle.emit("session:app-lock-changed", &lock) {
+        tracing::error!("Failed to emit app lock event: {}", e);
+    }
+    Ok(lock)
+}
@@ -1,12 +1,35 @@
-use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter, State};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::RwLock;
 
+use crate::attachments;
+use crate::blocking;
 use crate::chat_realtime::ChatRealtimeClient;
+use crate::commands::badge;
+use crate::commands::blocking::blocked_users;
+use crate::commands::encryption;
+use crate::conversation_channels::OpenConversations;
+use crate::group_roles::{self, GroupAction, GroupRoles};
+use crate::link_preview;
+use crate::low_bandwidth;
+use crate::notifications::{ConversationNotificationPrefs, NotificationLevel};
+use crate::pagination::{self, MessageCursor, MessageKey};
+use crate::presence;
 use crate::state::AppState;
+use crate::supabase::{
+    ConversationLinkRow, ConversationRow, MessageAttachment, MessageReadRow, MessageRow,
+    NewConversationLink, ParticipantWithProfile, PresencePeriodRow, SupabaseClient,
+    UserPresenceRow, UserProfileRow,
+};
+use crate::utils::text;
 use crate::{Error, Result};
 
+/// Length of the last message preview shown in conversation lists
+const LAST_MESSAGE_PREVIEW_LENGTH: usize = 120;
+
 // ==========================================
 // Chat State
 // ==========================================
@@ -19,6 +42,15 @@ pub struct ChatState {
 pub struct ChatStateInner {
     pub realtime: Option<ChatRealtimeClient>,
     pub is_connected: bool,
+    /// Status the presence heartbeat keeps sending
+    pub presence_status: Option<String>,
+    /// Whether the user has been away from keyboard and mouse past the idle
+    /// threshold, turning online into away
+    pub idle: bool,
+    /// Presence heartbeat, running while connected
+    pub heartbeat: Option<tauri::async_runtime::JoinHandle<()>>,
+    /// Conversations open in the UI, whose channels are joined
+    pub open_conversations: OpenConversations,
 }
 
 impl Default for ChatState {
@@ -46,6 +78,17 @@ pub struct Conversation {
     pub participants: Vec<Participant>,
     pub last_message: Option<Message>,
     pub unread_count: u32,
+    /// Unread replies in threads the user follows
+    #[serde(default)]
+    pub unread_thread_replies: u32,
+    /// Meeting the conversation was created from
+    pub meeting_id: Option<String>,
+    /// When the current user archived the conversation
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    /// Whether messages are end-to-end encrypted
+    #[serde(default)]
+    pub encrypted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +98,9 @@ pub struct Participant {
     pub avatar_url: Option<String>,
     pub role: String,
     pub is_online: bool,
+    /// Presence status, `online`, `away`, `busy`, `in_session` or `offline`
+    pub status: String,
+    pub status_message: Option<String>,
     pub last_seen_at: Option<String>,
 }
 
@@ -67,6 +113,57 @@ pub struct Message {
     pub content: String,
     pub message_type: String,
     pub created_at: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<MessageAttachment>,
+    /// Top-level message this replies to
+    #[serde(default)]
+    pub parent_message_id: Option<String>,
+    #[serde(default)]
+    pub reply_count: u32,
+    #[serde(default)]
+    pub last_reply_at: Option<String>,
+    /// Session whose chat the message was copied from
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Page of a conversation's messages, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePage {
+    pub messages: Vec<Message>,
+    /// Cursor to the older messages before this page
+    pub next_cursor: Option<String>,
+    /// Cursor to the newer messages after this page
+    pub prev_cursor: Option<String>,
+    /// Whether more messages lie in the direction paged
+    pub has_more: bool,
+}
+
+/// Message with its thread of replies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thread {
+    pub parent: Message,
+    pub replies: Vec<Message>,
+    /// Replies from others the user hasn't read
+    pub unread_count: u32,
+}
+
+/// A participant read a conversation up to `read_at`, or a thread of it
+/// when `parent_message_id` is set; emitted as `chat:read-receipt`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadReceipt {
+    pub conversation_id: String,
+    pub parent_message_id: Option<String>,
+    pub user_id: String,
+    pub read_at: String,
+}
+
+/// Download progress of an attachment, by object path
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentProgress {
+    pub path: String,
+    pub received: u64,
+    pub total: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,17 +172,17 @@ pub struct TeamMember {
     pub display_name: String,
     pub avatar_url: Option<String>,
     pub is_online: bool,
+    /// Presence status, `online`, `away`, `busy`, `in_session` or `offline`
+    pub status: String,
+    pub status_message: Option<String>,
 }
 
 // ==========================================
-// Commands
+// Helper Functions
 // ==========================================
 
-/// Get all conversations for the current user
-#[tauri::command]
-pub async fn get_conversations(
-    app_state: State<'_, AppState>,
-) -> Result<Vec<Conversation>> {
+/// Signed-in user's ID and the Supabase client
+async fn current_user(app_state: &AppState) -> Result<(String, &SupabaseClient)> {
     let inner = app_state.inner.read().await;
     let user = inner
         .user
@@ -98,115 +195,136 @@ pub async fn get_conversations(
         .as_ref()
         .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    drop(inner);
-
-    let conversation_rows = supabase.get_user_conversations(&user_id).await?;
+    Ok((user_id, supabase))
+}
 
-    // Build full conversation objects with participants and last message
-    let mut conversations = Vec::new();
-    for row in conversation_rows {
-        let participants_raw = supabase.get_conversation_participants(&row.id).await?;
-        let participants: Vec<Participant> = participants_raw
-            .into_iter()
-            .map(|p| Participant {
-                user_id: p.user_id,
-                display_name: p.display_name,
-                avatar_url: p.avatar_url,
-                role: p.role,
-                is_online: p.is_online,
-                last_seen_at: p.last_seen_at,
-            })
-            .collect();
+/// Owner and member roles of a group conversation
+async fn load_group_roles(supabase: &SupabaseClient, conversation_id: &str) -> Result<GroupRoles> {
+    let (conversation, participants) = tokio::try_join!(
+        supabase.get_conversation(conversation_id),
+        supabase.get_conversation_participants(conversation_id),
+    )?;
+    let conversation =
+        conversation.ok_or_else(|| Error::NotFound("Conversation not found".to_string()))?;
+    GroupRoles::new(&conversation, &participants)
+}
 
-        // Get last message
-        let messages = supabase.get_messages(&row.id, 1, None).await?;
-        let last_message = messages.into_iter().next().map(|m| Message {
-            id: m.id,
-            conversation_id: m.conversation_id,
-            sender_id: m.sender_id.clone(),
-            sender_name: m.sender_id.unwrap_or_else(|| "Unknown".to_string()),
-            content: m.content,
-            message_type: m.message_type,
-            created_at: m.created_at,
-        });
-
-        conversations.push(Conversation {
-            id: row.id,
-            conversation_type: row.conversation_type,
-            name: row.name,
-            avatar_url: row.avatar_url,
-            created_by: row.created_by,
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-            participants,
-            last_message,
-            unread_count: 0, // TODO: Calculate from last_read_at
-        });
+fn participant_from_row(p: ParticipantWithProfile) -> Participant {
+    Participant {
+        user_id: p.user_id,
+        display_name: p.display_name,
+        avatar_url: p.avatar_url,
+        role: p.role,
+        is_online: p.is_online,
+        status: p.status,
+        status_message: p.status_message,
+        last_seen_at: p.last_seen_at,
     }
-
-    // Sort by updated_at descending
-    conversations.sort_by(|a, b| {
-        b.updated_at
-            .as_ref()
-            .unwrap_or(&String::new())
-            .cmp(a.updated_at.as_ref().unwrap_or(&String::new()))
-    });
-
-    Ok(conversations)
 }
 
-/// Get a single conversation by ID
-#[tauri::command]
-pub async fn get_conversation(
-    conversation_id: String,
-    app_state: State<'_, AppState>,
-) -> Result<Conversation> {
-    let inner = app_state.inner.read().await;
-    let _user = inner
-        .user
-        .as_ref()
-        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-
-    let supabase = app_state
-        .supabase
-        .as_ref()
-        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
-
-    drop(inner);
-
-    // Get conversation details
-    let conversations = supabase.get_user_conversations("").await?; // Will be filtered by RLS
-    let row = conversations
+async fn fetch_participants(
+    supabase: &SupabaseClient,
+    conversation_id: &str,
+) -> Result<Vec<Participant>> {
+    let participants_raw = supabase.get_conversation_participants(conversation_id).await?;
+    Ok(participants_raw
         .into_iter()
-        .find(|c| c.id == conversation_id)
-        .ok_or_else(|| Error::Database("Conversation not found".to_string()))?;
+        .map(participant_from_row)
+        .collect())
+}
 
-    let participants_raw = supabase.get_conversation_participants(&row.id).await?;
-    let participants: Vec<Participant> = participants_raw
+/// Participants of several conversations, by conversation ID, in one batch
+async fn fetch_participants_batch(
+    supabase: &SupabaseClient,
+    conversation_ids: &[String],
+) -> Result<HashMap<String, Vec<Participant>>> {
+    Ok(supabase
+        .get_participants_for_conversations(conversation_ids)
+        .await?
         .into_iter()
-        .map(|p| Participant {
-            user_id: p.user_id,
-            display_name: p.display_name,
-            avatar_url: p.avatar_url,
-            role: p.role,
-            is_online: p.is_online,
-            last_seen_at: p.last_seen_at,
+        .map(|(id, participants)| {
+            (
+                id,
+                participants.into_iter().map(participant_from_row).collect(),
+            )
         })
-        .collect();
+        .collect())
+}
+
+/// Team member with their presence, offline when they have none
+fn team_member(profile: &UserProfileRow, presence: &[UserPresenceRow]) -> TeamMember {
+    let now = chrono::Utc::now();
+    let presence = presence.iter().find(|pr| pr.user_id == profile.user_id);
+    TeamMember {
+        user_id: profile.user_id.clone(),
+        display_name: profile
+            .display_name
+            .clone()
+            .unwrap_or_else(|| "Unknown".to_string()),
+        avatar_url: profile.avatar_url.clone(),
+        is_online: presence.is_some_and(|pr| presence::is_online(pr, now)),
+        status: presence
+            .map(|pr| presence::effective_status(pr, now))
+            .unwrap_or("offline")
+            .to_string(),
+        status_message: presence.and_then(|pr| pr.status_message.clone()),
+    }
+}
 
-    // Get last message
-    let messages = supabase.get_messages(&row.id, 1, None).await?;
-    let last_message = messages.into_iter().next().map(|m| Message {
+/// Message as shown in conversation lists
+fn last_message_from_row(m: MessageRow) -> Message {
+    let content = encryption::open_content(&m.conversation_id, m.sender_id.as_deref(), m.content);
+    Message {
         id: m.id,
         conversation_id: m.conversation_id,
         sender_id: m.sender_id.clone(),
         sender_name: m.sender_id.unwrap_or_else(|| "Unknown".to_string()),
-        content: m.content,
+        // Structured messages (polls) carry JSON that must stay intact
+        content: if m.message_type == "text" {
+            text::message_preview(&content, LAST_MESSAGE_PREVIEW_LENGTH)
+        } else {
+            content
+        },
         message_type: m.message_type,
         created_at: m.created_at,
-    });
+        attachments: m.attachments,
+        parent_message_id: m.parent_message_id,
+        reply_count: m.reply_count,
+        last_reply_at: m.last_reply_at,
+        session_id: m.session_id,
+    }
+}
 
-    Ok(Conversation {
+async fn fetch_last_message(
+    supabase: &SupabaseClient,
+    conversation_id: &str,
+) -> Result<Option<Message>> {
+    let messages = supabase.get_messages(conversation_id, 1, None).await?;
+    Ok(messages.into_iter().next().map(last_message_from_row))
+}
+
+/// Last messages of several conversations, by conversation ID, in one batch
+async fn fetch_last_messages(
+    supabase: &SupabaseClient,
+    conversation_ids: &[String],
+) -> Result<HashMap<String, Message>> {
+    if conversation_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(supabase
+        .get_last_messages(conversation_ids)
+        .await?
+        .into_iter()
+        .map(|m| (m.conversation_id.clone(), last_message_from_row(m)))
+        .collect())
+}
+
+fn conversation_from_row(
+    row: ConversationRow,
+    participants: Vec<Participant>,
+    last_message: Option<Message>,
+) -> Conversation {
+    Conversation {
         id: row.id,
         conversation_type: row.conversation_type,
         name: row.name,
@@ -216,16 +334,21 @@ pub async fn get_conversation(
         updated_at: row.updated_at,
         participants,
         last_message,
+        // Filled in by apply_unread_counts
         unread_count: 0,
-    })
+        unread_thread_replies: 0,
+        meeting_id: row.meeting_id,
+        archived_at: row.archived_at,
+        encrypted: row.encrypted,
+    }
 }
 
-/// Create a direct (1:1) conversation
-#[tauri::command]
-pub async fn create_direct_conversation(
-    other_user_id: String,
-    app_state: State<'_, AppState>,
-) -> Result<Conversation> {
+/// Archive or unarchive a conversation for the current user
+async fn set_conversation_archived(
+    app_state: &AppState,
+    conversation_id: &str,
+    archived: bool,
+) -> Result<()> {
     let inner = app_state.inner.read().await;
     let user = inner
         .user
@@ -240,114 +363,268 @@ pub async fn create_direct_conversation(
 
     drop(inner);
 
-    // Use the database function to find or create
-    let row = supabase
-        .find_or_create_direct_conversation(&user_id, &other_user_id)
+    supabase
+        .set_conversation_archived(conversation_id, &user_id, archived)
         .await?;
 
-    let participants_raw = supabase.get_conversation_participants(&row.id).await?;
-    let participants: Vec<Participant> = participants_raw
+    tracing::info!(
+        "Conversation {} {}",
+        conversation_id,
+        if archived { "archived" } else { "unarchived" }
+    );
+    Ok(())
+}
+
+/// Fill in unread counts; they stay at zero if they can't be fetched
+async fn apply_unread_counts(supabase: &SupabaseClient, conversations: &mut [Conversation]) {
+    let counts = match supabase.get_unread_counts().await {
+        Ok(counts) => counts,
+        Err(e) => {
+            tracing::warn!("Failed to get unread counts: {}", e);
+            return;
+        }
+    };
+    for conversation in conversations {
+        if let Some(count) = counts.iter().find(|c| c.conversation_id == conversation.id) {
+            conversation.unread_count = count.unread_count;
+            conversation.unread_thread_replies = count.unread_thread_replies;
+        }
+    }
+}
+
+/// Build a full conversation, fetching participants and last message together
+async fn build_conversation(supabase: &SupabaseClient, row: ConversationRow) -> Result<Conversation> {
+    let (participants, last_message) = tokio::try_join!(
+        fetch_participants(supabase, &row.id),
+        fetch_last_message(supabase, &row.id),
+    )?;
+    Ok(conversation_from_row(row, participants, last_message))
+}
+
+/// Build full conversations, with participants and last messages fetched
+/// in a batch for all of them rather than per conversation
+async fn build_conversations(
+    supabase: &SupabaseClient,
+    rows: Vec<ConversationRow>,
+) -> Result<Vec<Conversation>> {
+    let ids: Vec<String> = rows.iter().map(|row| row.id.clone()).collect();
+    let (mut participants, mut last_messages) = tokio::try_join!(
+        fetch_participants_batch(supabase, &ids),
+        fetch_last_messages(supabase, &ids),
+    )?;
+    Ok(rows
         .into_iter()
-        .map(|p| Participant {
-            user_id: p.user_id,
-            display_name: p.display_name,
-            avatar_url: p.avatar_url,
-            role: p.role,
-            is_online: p.is_online,
-            last_seen_at: p.last_seen_at,
+        .map(|row| {
+            let participants = participants.remove(&row.id).unwrap_or_default();
+            let last_message = last_messages.remove(&row.id);
+            conversation_from_row(row, participants, last_message)
         })
-        .collect();
-
-    Ok(Conversation {
-        id: row.id,
-        conversation_type: row.conversation_type,
-        name: row.name,
-        avatar_url: row.avatar_url,
-        created_by: row.created_by,
-        created_at: row.created_at,
-        updated_at: row.updated_at,
-        participants,
-        last_message: None,
-        unread_count: 0,
-    })
+        .collect())
 }
 
-/// Create a group conversation
-#[tauri::command]
-pub async fn create_group_conversation(
-    name: String,
-    member_ids: Vec<String>,
-    app_state: State<'_, AppState>,
-) -> Result<Conversation> {
-    let inner = app_state.inner.read().await;
-    let user = inner
-        .user
-        .as_ref()
-        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-    let user_id = user.id.clone();
+/// Upload local files as attachments of a new message
+async fn upload_attachments(
+    supabase: &SupabaseClient,
+    conversation_id: &str,
+    sender_id: &str,
+    files: &[String],
+) -> Result<Vec<MessageAttachment>> {
+    let mut uploaded = Vec::with_capacity(files.len());
+    for file_path in files {
+        match upload_attachment(supabase, conversation_id, sender_id, file_path).await {
+            Ok(attachment) => uploaded.push(attachment),
+            Err(e) => {
+                remove_attachments(supabase, &uploaded).await;
+                return Err(e);
+            }
+        }
+    }
+    Ok(uploaded)
+}
 
-    let supabase = app_state
-        .supabase
-        .as_ref()
-        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+async fn upload_attachment(
+    supabase: &SupabaseClient,
+    conversation_id: &str,
+    sender_id: &str,
+    file_path: &str,
+) -> Result<MessageAttachment> {
+    let path = PathBuf::from(file_path);
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|_| Error::NotFound(format!("File not found: {}", file_path)))?;
+    if metadata.len() > attachments::MAX_ATTACHMENT_BYTES {
+        return Err(Error::Parse(format!(
+            "Attachments are limited to {} MB",
+            attachments::MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
 
-    drop(inner);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    let content_type = attachments::content_type(&name);
+    let bytes = tokio::fs::read(&path).await?;
+
+    // Pixel size lets chat reserve room for the image before it loads
+    let (width, height) = if attachments::is_image(content_type) {
+        match image::ImageReader::new(std::io::Cursor::new(&bytes))
+            .with_guessed_format()
+            .ok()
+            .and_then(|reader| reader.into_dimensions().ok())
+        {
+            Some((width, height)) => (Some(width), Some(height)),
+            None => (None, None),
+        }
+    } else {
+        (None, None)
+    };
 
-    // Create the conversation
-    let row = supabase
-        .create_conversation("group", Some(&name), &user_id)
+    let storage_path = attachments::storage_path(conversation_id, sender_id, &name);
+    supabase
+        .upload_storage_object(
+            attachments::ATTACHMENT_BUCKET,
+            &storage_path,
+            content_type,
+            bytes,
+        )
         .await?;
 
-    // Add creator as admin
-    supabase.add_participant(&row.id, &user_id, "admin").await?;
+    Ok(MessageAttachment {
+        path: storage_path,
+        name,
+        content_type: content_type.to_string(),
+        size: metadata.len(),
+        width,
+        height,
+    })
+}
 
-    // Add other members
-    for member_id in &member_ids {
-        if member_id != &user_id {
-            supabase.add_participant(&row.id, member_id, "member").await?;
+/// Remove uploaded attachments whose message was never saved
+async fn remove_attachments(supabase: &SupabaseClient, uploaded: &[MessageAttachment]) {
+    for attachment in uploaded {
+        if let Err(e) = supabase
+            .delete_storage_object(attachments::ATTACHMENT_BUCKET, &attachment.path)
+            .await
+        {
+            tracing::warn!(
+                "Failed to remove orphaned attachment {}: {}",
+                attachment.path,
+                e
+            );
         }
     }
+}
 
-    let participants_raw = supabase.get_conversation_participants(&row.id).await?;
-    let participants: Vec<Participant> = participants_raw
+/// Index the links in a sent message, with their page titles
+async fn index_links(supabase: SupabaseClient, message: MessageRow) {
+    let Some(sender_id) = message.sender_id else {
+        return;
+    };
+    let urls = link_preview::extract_urls(&message.content);
+    if urls.is_empty() {
+        return;
+    }
+
+    // Titles are left out rather than fetched in low-bandwidth mode
+    let titles = if low_bandwidth::is_enabled() {
+        vec![None; urls.len()]
+    } else {
+        futures_util::future::join_all(urls.iter().map(|url| link_preview::fetch_title(url))).await
+    };
+    let links: Vec<NewConversationLink> = urls
         .into_iter()
-        .map(|p| Participant {
-            user_id: p.user_id,
-            display_name: p.display_name,
-            avatar_url: p.avatar_url,
-            role: p.role,
-            is_online: p.is_online,
-            last_seen_at: p.last_seen_at,
+        .zip(titles)
+        .map(|(url, title)| NewConversationLink {
+            conversation_id: message.conversation_id.clone(),
+            message_id: message.id.clone(),
+            sender_id: sender_id.clone(),
+            url,
+            title,
         })
         .collect();
 
-    Ok(Conversation {
-        id: row.id,
-        conversation_type: row.conversation_type,
-        name: row.name,
-        avatar_url: row.avatar_url,
-        created_by: row.created_by,
-        created_at: row.created_at,
-        updated_at: row.updated_at,
-        participants,
-        last_message: None,
-        unread_count: 0,
-    })
+    if let Err(e) = supabase.add_conversation_links(&links).await {
+        tracing::warn!("Failed to index links of message {}: {}", message.id, e);
+    }
 }
 
-/// Update a group's name or avatar
-#[tauri::command]
-pub async fn update_group(
+/// Messages with their senders' display names
+async fn with_sender_names(
+    supabase: &SupabaseClient,
+    rows: Vec<MessageRow>,
+) -> Result<Vec<Message>> {
+    let sender_ids: Vec<String> = rows.iter().filter_map(|m| m.sender_id.clone()).collect();
+
+    let profiles = if !sender_ids.is_empty() {
+        supabase.get_user_profiles(&sender_ids).await?
+    } else {
+        vec![]
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|m| {
+            let sender_name = m
+                .sender_id
+                .as_ref()
+                .and_then(|sid| {
+                    profiles
+                        .iter()
+                        .find(|p| &p.user_id == sid)
+                        .and_then(|p| p.display_name.clone())
+                })
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            let content =
+                encryption::open_content(&m.conversation_id, m.sender_id.as_deref(), m.content);
+
+            Message {
+                id: m.id,
+                conversation_id: m.conversation_id,
+                sender_id: m.sender_id,
+                sender_name,
+                content,
+                message_type: m.message_type,
+                created_at: m.created_at,
+                attachments: m.attachments,
+                parent_message_id: m.parent_message_id,
+                reply_count: m.reply_count,
+                last_reply_at: m.last_reply_at,
+                session_id: m.session_id,
+            }
+        })
+        .collect())
+}
+
+/// Whether `time` is later than `since`, both RFC 3339; anything is later
+/// than never
+fn is_after(time: Option<&str>, since: Option<&str>) -> bool {
+    let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok();
+    match (time.and_then(parse), since.and_then(parse)) {
+        (Some(time), Some(since)) => time > since,
+        (Some(_), None) => since.is_none(),
+        (None, _) => false,
+    }
+}
+
+/// Save a message, or a reply when `parent_message_id` is set, then publish it
+async fn send_message(
+    app_state: &AppState,
+    chat_state: &ChatState,
+    app_handle: &AppHandle,
     conversation_id: String,
-    name: Option<String>,
-    avatar_url: Option<String>,
-    app_state: State<'_, AppState>,
-) -> Result<()> {
+    content: String,
+    attachments: Option<Vec<String>>,
+    parent_message_id: Option<String>,
+) -> Result<Message> {
     let inner = app_state.inner.read().await;
-    let _user = inner
+    let user = inner
         .user
         .as_ref()
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+    let user_email = user.email.clone();
 
     let supabase = app_state
         .supabase
@@ -356,20 +633,224 @@ pub async fn update_group(
 
     drop(inner);
 
-    supabase
-        .update_group(&conversation_id, name.as_deref(), avatar_url.as_deref())
-        .await?;
+    let files = attachments.unwrap_or_default();
+    if files.len() > attachments::MAX_ATTACHMENTS {
+        return Err(Error::Parse(format!(
+            "At most {} files can be attached to a message",
+            attachments::MAX_ATTACHMENTS
+        )));
+    }
+    if content.trim().is_empty() && files.is_empty() {
+        return Err(Error::Parse("Message is empty".to_string()));
+    }
 
-    Ok(())
-}
+    // Encrypted conversations only ever store sealed content
+    let encrypted = !content.is_empty()
+        && supabase
+            .get_conversation(&conversation_id)
+            .await?
+            .is_some_and(|c| c.encrypted);
+    let stored_content = if encrypted {
+        encryption::seal_content(supabase, &conversation_id, &user_id, &content).await?
+    } else {
+        content.clone()
+    };
 
-/// Add a member to a group
-#[tauri::command]
-pub async fn add_group_member(
-    conversation_id: String,
-    user_id: String,
+    let uploaded = upload_attachments(supabase, &conversation_id, &user_id, &files).await?;
+    let message_type = if content.trim().is_empty() {
+        "file"
+    } else {
+        "text"
+    };
+
+    // Save message to database
+    let message_row = match supabase
+        .create_message_with_attachments(
+            &conversation_id,
+            &user_id,
+            &stored_content,
+            message_type,
+            &uploaded,
+            parent_message_id.as_deref(),
+        )
+        .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            remove_attachments(supabase, &uploaded).await;
+            return Err(e);
+        }
+    };
+
+    let mut message = Message {
+        id: message_row.id.clone(),
+        conversation_id: message_row.conversation_id.clone(),
+        sender_id: message_row.sender_id.clone(),
+        sender_name: user_email,
+        content: message_row.content.clone(),
+        message_type: message_row.message_type.clone(),
+        created_at: message_row.created_at.clone(),
+        attachments: message_row.attachments.clone(),
+        parent_message_id: message_row.parent_message_id.clone(),
+        reply_count: message_row.reply_count,
+        last_reply_at: message_row.last_reply_at.clone(),
+        session_id: message_row.session_id.clone(),
+    };
+
+    // Titles take a moment to fetch, so links are indexed in the background.
+    // Indexing the links of an encrypted message would leak them
+    if !encrypted {
+        tauri::async_runtime::spawn(index_links(supabase.clone(), message_row.clone()));
+    }
+
+    // Update cache with the new message
+    {
+        let mut cache = app_state.cache.messages.write().await;
+        if message_row.parent_message_id.is_some() {
+            // Replies stay out of the main list, but the parent's count changed
+            cache.invalidate_conversation(&conversation_id);
+        } else {
+            cache.append_messages(&conversation_id, vec![message_row]);
+            tracing::debug!("Cache updated with new message in {}", conversation_id);
+        }
+    }
+
+    // Broadcast via realtime if connected, sealed in encrypted conversations
+    let chat_inner = chat_state.inner.read().await;
+    if let Some(ref realtime) = chat_inner.realtime {
+        let _ = realtime
+            .broadcast_message(&conversation_id, &message)
+            .await;
+    }
+    message.content = content;
+
+    // Also emit locally for UI update
+    let _ = app_handle.emit("chat:new-message", &message);
+
+    Ok(message)
+}
+
+/// Tell the other participants what the user has read, when connected
+async fn publish_read_receipt(chat_state: &ChatState, receipt: &ReadReceipt) {
+    let state = chat_state.inner.read().await;
+    if let Some(ref realtime) = state.realtime {
+        if let Err(e) = realtime
+            .broadcast_read_receipt(&receipt.conversation_id, receipt)
+            .await
+        {
+            tracing::warn!("Failed to broadcast read receipt: {}", e);
+        }
+    }
+}
+
+fn emit_attachment_progress(app_handle: &AppHandle, path: &str, received: u64, total: u64) {
+    let progress = AttachmentProgress {
+        path: path.to_string(),
+        received,
+        total,
+    };
+    if let Err(e) = app_handle.emit("chat:attachment-progress", &progress) {
+        tracing::error!("Failed to emit attachment progress event: {}", e);
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Conversations of the current user for which `keep` holds, most recently
+/// updated first
+async fn list_conversations(
+    app_state: &AppState,
+    keep: impl Fn(&ConversationRow) -> bool,
+) -> Result<Vec<Conversation>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let conversation_rows = supabase.get_user_conversations(&user_id).await?;
+
+    // Build full conversation objects with participants and last message
+    let conversation_rows: Vec<ConversationRow> = conversation_rows
+        .into_iter()
+        .filter(|row| keep(row))
+        .collect();
+    let mut conversations = build_conversations(supabase, conversation_rows).await?;
+    apply_unread_counts(supabase, &mut conversations).await;
+
+    // Sort by updated_at descending
+    conversations.sort_by(|a, b| {
+        b.updated_at
+            .as_ref()
+            .unwrap_or(&String::new())
+            .cmp(a.updated_at.as_ref().unwrap_or(&String::new()))
+    });
+
+    Ok(conversations)
+}
+
+/// Get the conversations of the current user, leaving out archived ones
+/// unless `include_archived` is set
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_conversations(
+    include_archived: Option<bool>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<Conversation>> {
+    let include_archived = include_archived.unwrap_or(false);
+    list_conversations(&app_state, |row| {
+        include_archived || row.archived_at.is_none()
+    })
+    .await
+}
+
+/// Get the conversations the current user archived
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_archived_conversations(
+    app_state: State<'_, AppState>,
+) -> Result<Vec<Conversation>> {
+    list_conversations(&app_state, |row| row.archived_at.is_some()).await
+}
+
+/// Archive a conversation, hiding it from the current user's list until
+/// unarchived or until a new message arrives in it
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn archive_conversation(
+    conversation_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    set_conversation_archived(&app_state, &conversation_id, true).await
+}
+
+/// Bring an archived conversation back to the current user's list
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn unarchive_conversation(
+    conversation_id: String,
     app_state: State<'_, AppState>,
 ) -> Result<()> {
+    set_conversation_archived(&app_state, &conversation_id, false).await
+}
+
+/// Get a single conversation by ID
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_conversation(
+    conversation_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Conversation> {
     let inner = app_state.inner.read().await;
     let _current_user = inner
         .user
@@ -383,20 +864,399 @@ pub async fn add_group_member(
 
     drop(inner);
 
+    // Get conversation details
+    let conversations = supabase.get_user_conversations("").await?; // Will be filtered by RLS
+    let row = conversations
+        .into_iter()
+        .find(|c| c.id == conversation_id)
+        .ok_or_else(|| Error::Database("Conversation not found".to_string()))?;
+
+    let mut conversation = build_conversation(supabase, row).await?;
+    apply_unread_counts(supabase, std::slice::from_mut(&mut conversation)).await;
+    Ok(conversation)
+}
+
+/// Create a direct (1:1) conversation
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_direct_conversation(
+    other_user_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Conversation> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    // Use the database function to find or create
+    let row = supabase
+        .find_or_create_direct_conversation(&user_id, &other_user_id)
+        .await?;
+
+    let participants_raw = supabase.get_conversation_participants(&row.id).await?;
+    let participants: Vec<Participant> = participants_raw
+        .into_iter()
+        .map(|p| Participant {
+            user_id: p.user_id,
+            display_name: p.display_name,
+            avatar_url: p.avatar_url,
+            role: p.role,
+            is_online: p.is_online,
+            status: p.status,
+            status_message: p.status_message,
+            last_seen_at: p.last_seen_at,
+        })
+        .collect();
+
+    Ok(Conversation {
+        id: row.id,
+        conversation_type: row.conversation_type,
+        name: row.name,
+        avatar_url: row.avatar_url,
+        created_by: row.created_by,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        participants,
+        last_message: None,
+        unread_count: 0,
+        unread_thread_replies: 0,
+        meeting_id: row.meeting_id,
+        archived_at: None,
+        encrypted: row.encrypted,
+    })
+}
+
+/// Create a group conversation
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_group_conversation(
+    name: String,
+    member_ids: Vec<String>,
+    app_state: State<'_, AppState>,
+) -> Result<Conversation> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    // Create the conversation
+    let row = supabase
+        .create_conversation("group", Some(&name), &user_id, None)
+        .await?;
+
+    // Add creator as admin
+    supabase.add_participant(&row.id, &user_id, "admin").await?;
+
+    // Add other members concurrently
+    futures_util::future::try_join_all(
+        member_ids
+            .iter()
+            .filter(|member_id| *member_id != &user_id)
+            .map(|member_id| supabase.add_participant(&row.id, member_id, "member")),
+    )
+    .await?;
+
+    let participants_raw = supabase.get_conversation_participants(&row.id).await?;
+    let participants: Vec<Participant> = participants_raw
+        .into_iter()
+        .map(|p| Participant {
+            user_id: p.user_id,
+            display_name: p.display_name,
+            avatar_url: p.avatar_url,
+            role: p.role,
+            is_online: p.is_online,
+            status: p.status,
+            status_message: p.status_message,
+            last_seen_at: p.last_seen_at,
+        })
+        .collect();
+
+    Ok(Conversation {
+        id: row.id,
+        conversation_type: row.conversation_type,
+        name: row.name,
+        avatar_url: row.avatar_url,
+        created_by: row.created_by,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        participants,
+        last_message: None,
+        unread_count: 0,
+        unread_thread_replies: 0,
+        meeting_id: row.meeting_id,
+        archived_at: None,
+        encrypted: row.encrypted,
+    })
+}
+
+/// Create a group conversation with a meeting's attendees (organizer only)
+///
+/// The conversation and the meeting are linked both ways; a meeting that
+/// already has a conversation returns it. Attendees who declined are left out.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_conversation_from_meeting(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Conversation> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let (meeting, attendees) = tokio::try_join!(
+        supabase.get_meeting(&meeting_id),
+        supabase.get_meeting_attendees(&meeting_id),
+    )?;
+    let meeting = meeting.ok_or_else(|| Error::NotFound("Meeting not found".to_string()))?;
+    if meeting.organizer_id != user_id {
+        return Err(Error::Auth(
+            "Only the organizer can create the meeting's conversation".to_string(),
+        ));
+    }
+
+    if let Some(conversation_id) = meeting.conversation_id {
+        let conversations = supabase.get_user_conversations(&user_id).await?;
+        if let Some(row) = conversations.into_iter().find(|c| c.id == conversation_id) {
+            return build_conversation(supabase, row).await;
+        }
+    }
+
+    let row = supabase
+        .create_conversation("group", Some(&meeting.title), &user_id, Some(&meeting_id))
+        .await?;
+
+    // The creator must be admin before adding anyone else
+    supabase.add_participant(&row.id, &user_id, "admin").await?;
+
+    let mut member_ids: Vec<String> = attendees
+        .into_iter()
+        .filter(|a| a.response_status != "declined" && a.user_id != user_id)
+        .map(|a| a.user_id)
+        .collect();
+    member_ids.sort();
+    member_ids.dedup();
+
+    let (participants, ()) = tokio::try_join!(
+        async {
+            supabase
+                .add_participants(&row.id, &member_ids, "member")
+                .await?;
+            fetch_participants(supabase, &row.id).await
+        },
+        supabase.set_meeting_conversation(&meeting_id, &row.id),
+    )?;
+
+    tracing::info!(
+        "Conversation {} created for meeting {} with {} members",
+        row.id,
+        meeting_id,
+        participants.len()
+    );
+    Ok(conversation_from_row(row, participants, None))
+}
+
+/// Update a group's name or avatar (admins only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn update_group(
+    conversation_id: String,
+    name: Option<String>,
+    avatar_url: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&user_id, GroupAction::Edit)?;
+
+    supabase
+        .update_group(&conversation_id, name.as_deref(), avatar_url.as_deref())
+        .await?;
+
+    Ok(())
+}
+
+/// Add a member to a group (admins only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn add_group_member(
+    conversation_id: String,
+    user_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&current_user_id, GroupAction::AddMember)?;
+
     supabase
-        .add_participant(&conversation_id, &user_id, "member")
+        .add_participant(&conversation_id, &user_id, group_roles::MEMBER)
         .await?;
 
     Ok(())
 }
 
-/// Remove a member from a group
+/// Remove a member from a group (admins only; the owner can't be removed)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn remove_group_member(
     conversation_id: String,
     user_id: String,
     app_state: State<'_, AppState>,
 ) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&current_user_id, GroupAction::RemoveMember(&user_id))?;
+
+    supabase
+        .remove_participant(&conversation_id, &user_id)
+        .await?;
+
+    Ok(())
+}
+
+/// Make a group member an admin (admins only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn promote_member(
+    conversation_id: String,
+    user_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&current_user_id, GroupAction::Promote(&user_id))?;
+
+    supabase
+        .set_participant_role(&conversation_id, &user_id, group_roles::ADMIN)
+        .await?;
+
+    tracing::info!("Made {} an admin of {}", user_id, conversation_id);
+    Ok(())
+}
+
+/// Make a group admin a plain member again (admins only; the owner stays
+/// an admin)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn demote_member(
+    conversation_id: String,
+    user_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&current_user_id, GroupAction::Demote(&user_id))?;
+
+    supabase
+        .set_participant_role(&conversation_id, &user_id, group_roles::MEMBER)
+        .await?;
+
+    tracing::info!("Made {} a member of {}", user_id, conversation_id);
+    Ok(())
+}
+
+/// Make another member the group owner (owner only)
+///
+/// The new owner becomes an admin if they weren't; the previous owner
+/// stays an admin.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn transfer_group_ownership(
+    conversation_id: String,
+    user_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (current_user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&current_user_id, GroupAction::TransferOwnership(&user_id))?;
+
+    supabase
+        .transfer_group_ownership(&conversation_id, &user_id)
+        .await?;
+
+    tracing::info!(
+        "Transferred ownership of {} to {}",
+        conversation_id,
+        user_id
+    );
+    Ok(())
+}
+
+/// Leave a group; the owner transfers ownership first unless they are the
+/// last member
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn leave_group(
+    conversation_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let (user_id, supabase) = current_user(&app_state).await?;
+
+    load_group_roles(supabase, &conversation_id)
+        .await?
+        .check(&user_id, GroupAction::Leave)?;
+
+    supabase
+        .remove_participant(&conversation_id, &user_id)
+        .await?;
+
+    Ok(())
+}
+
+/// Get a page of messages for a conversation
+///
+/// Without a cursor the newest messages come back; the `next_cursor` and
+/// `prev_cursor` of a page lead to the older and newer pages next to it.
+/// Pages are keyed on `(created_at, id)`, so they stay put while messages
+/// arrive. The newest messages and the older pages scrolled back through
+/// are cached together.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_messages(
+    conversation_id: String,
+    limit: Option<u32>,
+    cursor: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<MessagePage> {
     let inner = app_state.inner.read().await;
     let _current_user = inner
         .user
@@ -410,25 +1270,373 @@ pub async fn remove_group_member(
 
     drop(inner);
 
+    let limit = limit.unwrap_or(50);
+    let cursor = cursor
+        .filter(|c| !c.is_empty())
+        .map(|c| MessageCursor::decode(&c))
+        .transpose()?;
+
+    // Check cache first
+    let cached = app_state.cache.messages.read().await.get_page(
+        &conversation_id,
+        cursor.as_ref(),
+        limit as usize,
+    );
+
+    let (messages, has_more) = match cached {
+        Some((rows, has_more)) => {
+            tracing::debug!("Cache hit for messages in conversation {}", conversation_id);
+            let messages = rows
+                .into_iter()
+                .map(|m| Message {
+                    id: m.id,
+                    conversation_id: m.conversation_id,
+                    sender_id: m.sender_id.clone(),
+                    sender_name: m.sender_id.unwrap_or_else(|| "Unknown".to_string()),
+                    content: m.content,
+                    message_type: m.message_type,
+                    created_at: m.created_at,
+                    attachments: m.attachments,
+                    parent_message_id: m.parent_message_id,
+                    reply_count: m.reply_count,
+                    last_reply_at: m.last_reply_at,
+                    session_id: m.session_id,
+                })
+                .collect();
+            (messages, has_more)
+        }
+        None => {
+            // Cache miss - fetch one past the page to tell whether there is more
+            let rows = supabase
+                .get_messages(&conversation_id, limit + 1, cursor.as_ref())
+                .await?;
+            let (rows, has_more) =
+                pagination::trim_page(rows, limit as usize, cursor.as_ref().map(|c| c.direction));
+
+            {
+                let mut cache = app_state.cache.messages.write().await;
+                match cursor {
+                    None => cache.set_messages(&conversation_id, rows.clone(), !has_more),
+                    Some(ref cursor) => {
+                        cache.add_older_page(&conversation_id, cursor, rows.clone(), !has_more)
+                    }
+                }
+                tracing::debug!("Cached messages for conversation {}", conversation_id);
+            }
+
+            (with_sender_names(supabase, rows).await?, has_more)
+        }
+    };
+
+    let key = |m: Option<&Message>| m.and_then(|m| MessageKey::of(&m.created_at, &m.id));
+    let (next_cursor, prev_cursor) = pagination::page_cursors(
+        cursor.as_ref(),
+        key(messages.first()),
+        key(messages.last()),
+        has_more,
+    );
+
+    // Hidden after paging, so cursors still point past the whole page
+    let blocked = blocked_users(&app_state).await?;
+    let messages = messages
+        .into_iter()
+        .filter(|m| !blocking::is_hidden(m.sender_id.as_deref(), &blocked))
+        .collect();
+
+    Ok(MessagePage {
+        messages,
+        next_cursor: next_cursor.map(|c| c.encode()).transpose()?,
+        prev_cursor: prev_cursor.map(|c| c.encode()).transpose()?,
+        has_more,
+    })
+}
+
+/// Send a message to a conversation, with optional files attached
+///
+/// `attachments` are local file paths; they are uploaded before the message
+/// is saved and removed again if saving fails.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn chat_send_message(
+    conversation_id: String,
+    content: String,
+    attachments: Option<Vec<String>>,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    app_handle: AppHandle,
+) -> Result<Message> {
+    send_message(
+        &app_state,
+        &chat_state,
+        &app_handle,
+        conversation_id,
+        content,
+        attachments,
+        None,
+    )
+    .await
+}
+
+/// Reply to a message in its thread, with optional files attached
+///
+/// Threads are one level deep: replying to a reply joins the same thread.
+/// Replies are emitted as `chat:new-message` with `parent_message_id` set.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn reply_to_message(
+    parent_message_id: String,
+    content: String,
+    attachments: Option<Vec<String>>,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    app_handle: AppHandle,
+) -> Result<Message> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let parent = supabase
+        .get_message(&parent_message_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Message {}", parent_message_id)))?;
+    let thread_id = parent.parent_message_id.unwrap_or(parent.id);
+
+    send_message(
+        &app_state,
+        &chat_state,
+        &app_handle,
+        parent.conversation_id,
+        content,
+        attachments,
+        Some(thread_id),
+    )
+    .await
+}
+
+/// Get a message with its replies, oldest first, and how many of them the
+/// user hasn't read
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_thread(
+    parent_message_id: String,
+    limit: Option<u32>,
+    app_state: State<'_, AppState>,
+) -> Result<Thread> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let parent = supabase
+        .get_message(&parent_message_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Message {}", parent_message_id)))?;
+    let (replies, thread_read) = tokio::try_join!(
+        supabase.get_thread_replies(&parent.id, limit.unwrap_or(200)),
+        supabase.get_thread_read(&user_id, &parent.id),
+    )?;
+
+    // Until the thread is opened, replies since the conversation was read count
+    let read_at = match thread_read {
+        Some(read_at) => Some(read_at),
+        None => {
+            supabase
+                .get_last_read(&parent.conversation_id, &user_id)
+                .await?
+        }
+    };
+    let unread_count = replies
+        .iter()
+        .filter(|r| r.sender_id.as_deref() != Some(user_id.as_str()))
+        .filter(|r| is_after(r.created_at.as_deref(), read_at.as_deref()))
+        .count() as u32;
+
+    let mut messages =
+        with_sender_names(supabase, std::iter::once(parent).chain(replies).collect())
+            .await?
+            .into_iter();
+    let parent = messages
+        .next()
+        .ok_or_else(|| Error::NotFound(format!("Message {}", parent_message_id)))?;
+
+    Ok(Thread {
+        parent,
+        replies: messages.collect(),
+        unread_count,
+    })
+}
+
+/// Mark a thread as read
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn mark_thread_read(
+    parent_message_id: String,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase
+        .mark_thread_read(&user_id, &parent_message_id)
+        .await?;
+    badge::request_refresh(&app_handle);
+
+    // The receipt goes to the thread's conversation channel
+    if chat_state.inner.read().await.realtime.is_some() {
+        if let Some(parent) = supabase.get_message(&parent_message_id).await? {
+            let receipt = ReadReceipt {
+                conversation_id: parent.conversation_id,
+                parent_message_id: Some(parent_message_id),
+                user_id,
+                read_at: chrono::Utc::now().to_rfc3339(),
+            };
+            publish_read_receipt(&chat_state, &receipt).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Participants other than the sender who have seen a message, earliest
+/// first
+///
+/// Updates arrive as `chat:read-receipt` events while connected.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_message_read_by(
+    message_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<MessageReadRow>> {
+    let inner = app_state.inner.read().await;
+    let _current_user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase.get_message_read_by(&message_id).await
+}
+
+/// Get how a conversation notifies the current user
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_conversation_notification_prefs(
+    conversation_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<ConversationNotificationPrefs> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase
+        .get_notification_prefs(&conversation_id, &user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Not a participant of the conversation".to_string()))
+}
+
+/// Set how a conversation notifies the current user: every message, only
+/// mentions, or none, until `muted_until` if given
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_conversation_notification_prefs(
+    conversation_id: String,
+    level: NotificationLevel,
+    muted_until: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<ConversationNotificationPrefs> {
+    let muted_until = muted_until
+        .map(|until| {
+            chrono::DateTime::parse_from_rfc3339(&until)
+                .map(|until| until.with_timezone(&chrono::Utc).to_rfc3339())
+                .map_err(|e| Error::Parse(format!("Invalid mute end time: {}", e)))
+        })
+        .transpose()?;
+
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let prefs = ConversationNotificationPrefs {
+        notification_level: level,
+        muted_until,
+    };
     supabase
-        .remove_participant(&conversation_id, &user_id)
+        .update_notification_prefs(&conversation_id, &user_id, &prefs)
         .await?;
 
-    Ok(())
+    tracing::info!(
+        "Notifications of conversation {} set to {:?}",
+        conversation_id,
+        prefs.notification_level
+    );
+    Ok(prefs)
 }
 
-/// Leave a group
+/// Download a message attachment, or return it from the local cache
+///
+/// Progress is emitted as `chat:attachment-progress`. Returns the local path
+/// of the file.
 #[tauri::command]
-pub async fn leave_group(
-    conversation_id: String,
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn download_attachment(
+    attachment: MessageAttachment,
     app_state: State<'_, AppState>,
-) -> Result<()> {
+    app_handle: AppHandle,
+) -> Result<String> {
     let inner = app_state.inner.read().await;
-    let user = inner
+    let _current_user = inner
         .user
         .as_ref()
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-    let user_id = user.id.clone();
 
     let supabase = app_state
         .supabase
@@ -437,129 +1645,55 @@ pub async fn leave_group(
 
     drop(inner);
 
-    supabase
-        .remove_participant(&conversation_id, &user_id)
-        .await?;
-
-    Ok(())
+    let cache_dir = app_handle.path().app_cache_dir()?;
+    let path = attachments::download_to_cache(
+        supabase,
+        attachments::ATTACHMENT_BUCKET,
+        &attachment.path,
+        &attachment.name,
+        attachment.size,
+        &cache_dir,
+        |received, total| emit_attachment_progress(&app_handle, &attachment.path, received, total),
+    )
+    .await?;
+
+    Ok(path.to_string_lossy().to_string())
 }
 
-/// Get messages for a conversation
+/// Get the links shared in a conversation, newest first
 #[tauri::command]
-pub async fn get_messages(
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_conversation_links(
     conversation_id: String,
     limit: Option<u32>,
     before: Option<String>,
     app_state: State<'_, AppState>,
-) -> Result<Vec<Message>> {
-    let inner = app_state.inner.read().await;
-    let _user = inner
-        .user
-        .as_ref()
-        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-
+) -> Result<Vec<ConversationLinkRow>> {
     let supabase = app_state
         .supabase
         .as_ref()
         .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
 
-    drop(inner);
-
-    let limit = limit.unwrap_or(50);
-
-    // Check cache first (only for initial load without pagination)
-    if before.is_none() {
-        let cache = app_state.cache.messages.read().await;
-        if let Some(cached) = cache.get_messages(&conversation_id) {
-            tracing::debug!("Cache hit for messages in conversation {}", conversation_id);
-            // Return up to limit messages
-            let result: Vec<Message> = cached
-                .iter()
-                .take(limit as usize)
-                .map(|m| Message {
-                    id: m.id.clone(),
-                    conversation_id: m.conversation_id.clone(),
-                    sender_id: m.sender_id.clone(),
-                    sender_name: m.sender_id.clone().unwrap_or_else(|| "Unknown".to_string()),
-                    content: m.content.clone(),
-                    message_type: m.message_type.clone(),
-                    created_at: m.created_at.clone(),
-                })
-                .collect();
-            return Ok(result);
-        }
-        drop(cache);
-    }
-
-    // Cache miss - fetch from API
-    let message_rows = supabase
-        .get_messages(&conversation_id, limit, before.as_deref())
-        .await?;
-
-    // Get sender profiles
-    let sender_ids: Vec<String> = message_rows
-        .iter()
-        .filter_map(|m| m.sender_id.clone())
-        .collect();
-
-    let profiles = if !sender_ids.is_empty() {
-        supabase.get_user_profiles(&sender_ids).await?
-    } else {
-        vec![]
-    };
-
-    let messages: Vec<Message> = message_rows
-        .iter()
-        .map(|m| {
-            let sender_name = m
-                .sender_id
-                .as_ref()
-                .and_then(|sid| {
-                    profiles
-                        .iter()
-                        .find(|p| &p.user_id == sid)
-                        .and_then(|p| p.display_name.clone())
-                })
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            Message {
-                id: m.id.clone(),
-                conversation_id: m.conversation_id.clone(),
-                sender_id: m.sender_id.clone(),
-                sender_name,
-                content: m.content.clone(),
-                message_type: m.message_type.clone(),
-                created_at: m.created_at.clone(),
-            }
-        })
-        .collect();
-
-    // Cache the result (only for initial load)
-    if before.is_none() {
-        let mut cache = app_state.cache.messages.write().await;
-        cache.set_messages(&conversation_id, message_rows);
-        tracing::debug!("Cached messages for conversation {}", conversation_id);
-    }
-
-    Ok(messages)
+    supabase
+        .get_conversation_links(&conversation_id, limit.unwrap_or(50), before.as_deref())
+        .await
 }
 
-/// Send a message to a conversation
+/// Mark a conversation as read
 #[tauri::command]
-pub async fn chat_send_message(
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn mark_as_read(
     conversation_id: String,
-    content: String,
     app_state: State<'_, AppState>,
     chat_state: State<'_, ChatState>,
     app_handle: AppHandle,
-) -> Result<Message> {
+) -> Result<()> {
     let inner = app_state.inner.read().await;
     let user = inner
         .user
         .as_ref()
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
     let user_id = user.id.clone();
-    let user_email = user.email.clone();
 
     let supabase = app_state
         .supabase
@@ -568,54 +1702,41 @@ pub async fn chat_send_message(
 
     drop(inner);
 
-    // Save message to database
-    let message_row = supabase
-        .create_message(&conversation_id, &user_id, &content, "text")
-        .await?;
+    supabase.update_last_read(&conversation_id, &user_id).await?;
+    badge::request_refresh(&app_handle);
 
-    let message = Message {
-        id: message_row.id.clone(),
-        conversation_id: message_row.conversation_id.clone(),
-        sender_id: message_row.sender_id.clone(),
-        sender_name: user_email,
-        content: message_row.content.clone(),
-        message_type: message_row.message_type.clone(),
-        created_at: message_row.created_at.clone(),
+    let receipt = ReadReceipt {
+        conversation_id,
+        parent_message_id: None,
+        user_id,
+        read_at: chrono::Utc::now().to_rfc3339(),
     };
+    publish_read_receipt(&chat_state, &receipt).await;
 
-    // Update cache with the new message
-    {
-        let mut cache = app_state.cache.messages.write().await;
-        cache.append_messages(&conversation_id, vec![message_row]);
-        tracing::debug!("Cache updated with new message in {}", conversation_id);
-    }
-
-    // Broadcast via realtime if connected
-    let chat_inner = chat_state.inner.read().await;
-    if let Some(ref realtime) = chat_inner.realtime {
-        let _ = realtime
-            .broadcast_message(&conversation_id, &message)
-            .await;
-    }
-
-    // Also emit locally for UI update
-    let _ = app_handle.emit("chat:new-message", &message);
-
-    Ok(message)
+    Ok(())
 }
 
-/// Mark a conversation as read
+/// Update presence status: `online`, `away`, `busy` or `offline`
+///
+/// While the user is in a session, online is published as `in_session`.
 #[tauri::command]
-pub async fn mark_as_read(
-    conversation_id: String,
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn update_presence(
+    status: String,
     app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
 ) -> Result<()> {
+    if !presence::SELECTABLE_STATUSES.contains(&status.as_str()) {
+        return Err(Error::Parse(format!("Invalid presence status: {}", status)));
+    }
+
     let inner = app_state.inner.read().await;
     let user = inner
         .user
         .as_ref()
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
     let user_id = user.id.clone();
+    let in_session = inner.session.is_some();
 
     let supabase = app_state
         .supabase
@@ -624,17 +1745,39 @@ pub async fn mark_as_read(
 
     drop(inner);
 
-    supabase.update_last_read(&conversation_id, &user_id).await?;
+    // In low-bandwidth mode a running heartbeat sends the change along with
+    // the next refresh; going offline is never held back
+    let mut chat_inner = chat_state.inner.write().await;
+    if low_bandwidth::is_enabled() && chat_inner.heartbeat.is_some() && status != "offline" {
+        chat_inner.presence_status = Some(status);
+        return Ok(());
+    }
+    let idle = chat_inner.idle;
+    drop(chat_inner);
+
+    supabase
+        .update_presence(
+            &user_id,
+            presence::published_status(&status, in_session, idle),
+        )
+        .await?;
+
+    // The heartbeat keeps sending the chosen status
+    chat_state.inner.write().await.presence_status = Some(status);
 
     Ok(())
 }
 
-/// Update presence status
+/// Set the custom text shown next to the user's status, or clear it with
+/// `None`; kept until changed, whatever the status
 #[tauri::command]
-pub async fn update_presence(
-    status: String,
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_status_message(
+    message: Option<String>,
     app_state: State<'_, AppState>,
-) -> Result<()> {
+) -> Result<Option<String>> {
+    let message = presence::status_message(message.as_deref())?;
+
     let inner = app_state.inner.read().await;
     let user = inner
         .user
@@ -649,13 +1792,72 @@ pub async fn update_presence(
 
     drop(inner);
 
-    supabase.update_presence(&user_id, &status).await?;
+    supabase
+        .update_status_message(&user_id, message.as_deref())
+        .await?;
+    app_state.cache.presence.write().await.invalidate_all();
 
-    Ok(())
+    Ok(message)
+}
+
+/// Publish the user's presence again after they joined or left a session
+/// or went idle, so online turns into `in_session` or `away` and back
+///
+/// Does nothing while chat is disconnected. In low-bandwidth mode the
+/// heartbeat carries the change instead.
+pub(crate) async fn refresh_presence(app_handle: &AppHandle) {
+    if low_bandwidth::is_enabled() {
+        return;
+    }
+    let chat_state = app_handle.state::<ChatState>();
+    let chat_inner = chat_state.inner.read().await;
+    let Some(status) = chat_inner.presence_status.clone() else {
+        return;
+    };
+    let idle = chat_inner.idle;
+    drop(chat_inner);
+
+    let app_state = app_handle.state::<AppState>();
+    let inner = app_state.inner.read().await;
+    let Some(user_id) = inner.user.as_ref().map(|u| u.id.clone()) else {
+        return;
+    };
+    let in_session = inner.session.is_some();
+    drop(inner);
+
+    let Some(ref supabase) = app_state.supabase else {
+        return;
+    };
+    let published = presence::published_status(&status, in_session, idle);
+    if let Err(e) = supabase.update_presence(&user_id, published).await {
+        tracing::warn!("Failed to update presence: {}", e);
+    }
+}
+
+/// Get a user's presence periods over the last `days` days (7 by default),
+/// newest first
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_presence_history(
+    user_id: String,
+    days: Option<u32>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<PresencePeriodRow>> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let days = days.unwrap_or(7).clamp(1, 30);
+    let since = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    supabase
+        .get_presence_history(&user_id, &since.to_rfc3339())
+        .await
 }
 
 /// Get team members (all users)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_team_members(
     app_state: State<'_, AppState>,
 ) -> Result<Vec<TeamMember>> {
@@ -685,9 +1887,14 @@ pub async fn get_team_members(
                 .filter(|p| p.user_id != current_user_id)
                 .map(|p| TeamMember {
                     user_id: p.user_id.clone(),
-                    display_name: p.status.clone().unwrap_or_else(|| "Unknown".to_string()),
-                    avatar_url: None,
+                    display_name: p
+                        .display_name
+                        .clone()
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                    avatar_url: p.avatar_url.clone(),
                     is_online: p.is_online,
+                    status: p.status.clone().unwrap_or_else(|| "offline".to_string()),
+                    status_message: p.status_message.clone(),
                 })
                 .collect();
             return Ok(members);
@@ -699,54 +1906,36 @@ pub async fn get_team_members(
     let user_ids: Vec<String> = profiles.iter().map(|p| p.user_id.clone()).collect();
     let presence = supabase.get_users_presence(&user_ids).await?;
 
-    let members: Vec<TeamMember> = profiles
-        .iter()
-        .filter(|p| p.user_id != current_user_id)
-        .map(|p| {
-            let is_online = presence
-                .iter()
-                .find(|pr| pr.user_id == p.user_id)
-                .map(|pr| pr.status == "online")
-                .unwrap_or(false);
-
-            TeamMember {
-                user_id: p.user_id.clone(),
-                display_name: p.display_name.clone().unwrap_or_else(|| "Unknown".to_string()),
-                avatar_url: p.avatar_url.clone(),
-                is_online,
-            }
-        })
-        .collect();
+    let all_members: Vec<TeamMember> = profiles.iter().map(|p| team_member(p, &presence)).collect();
 
     // Cache the presence information
     {
         let mut cache = app_state.cache.presence.write().await;
-        let presence_infos: Vec<PresenceInfo> = profiles
+        let presence_infos: Vec<PresenceInfo> = all_members
             .iter()
-            .map(|p| {
-                let is_online = presence
-                    .iter()
-                    .find(|pr| pr.user_id == p.user_id)
-                    .map(|pr| pr.status == "online")
-                    .unwrap_or(false);
-
-                PresenceInfo {
-                    user_id: p.user_id.clone(),
-                    is_online,
-                    last_seen: None,
-                    status: p.display_name.clone(),
-                }
+            .map(|m| PresenceInfo {
+                user_id: m.user_id.clone(),
+                is_online: m.is_online,
+                last_seen: None,
+                status: Some(m.status.clone()),
+                status_message: m.status_message.clone(),
+                display_name: Some(m.display_name.clone()),
+                avatar_url: m.avatar_url.clone(),
             })
             .collect();
         cache.set_team_members(presence_infos);
         tracing::debug!("Cached team members presence");
     }
 
-    Ok(members)
+    Ok(all_members
+        .into_iter()
+        .filter(|m| m.user_id != current_user_id)
+        .collect())
 }
 
 /// Connect to chat realtime
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn connect_chat(
     app_state: State<'_, AppState>,
     chat_state: State<'_, ChatState>,
@@ -761,32 +1950,93 @@ pub async fn connect_chat(
     let access_token = user.access_token.clone();
     drop(inner);
 
+    // Realtime events from blocked users are dropped against the cached list
+    if let Err(e) = blocked_users(&app_state).await {
+        tracing::warn!("Failed to load blocked users: {}", e);
+    }
+    if let Err(e) = encryption::publish_device_key(&app_state).await {
+        tracing::warn!("Failed to publish device key: {}", e);
+    }
+
     // Create realtime client
     let realtime = ChatRealtimeClient::from_env()?;
     realtime.set_access_token(Some(access_token)).await;
 
     // Connect and subscribe to conversations, passing the cache for presence updates
     let cache = Some(app_state.cache.clone());
-    realtime.connect(&user_id, app_handle, cache).await?;
+    realtime.connect(&user_id, app_handle.clone(), cache).await?;
 
     // Update state
     {
         let mut state = chat_state.inner.write().await;
         state.realtime = Some(realtime);
         state.is_connected = true;
+        state.presence_status = Some("online".to_string());
+        state.idle = false;
+        if let Some(heartbeat) = state.heartbeat.take() {
+            heartbeat.abort();
+        }
+        state.heartbeat = Some(tauri::async_runtime::spawn(presence_heartbeat(
+            app_handle.clone(),
+            user_id.clone(),
+        )));
+    }
+
+    // Rejoin the channels of conversations that stayed open
+    {
+        let state = chat_state.inner.read().await;
+        if let Some(ref realtime) = state.realtime {
+            for conversation_id in state.open_conversations.ids() {
+                if let Err(e) = realtime.subscribe_to_conversation(conversation_id).await {
+                    tracing::warn!("Failed to rejoin conversation {}: {}", conversation_id, e);
+                }
+            }
+        }
     }
 
     // Update presence to online
     if let Some(ref supabase) = app_state.supabase {
-        let _ = supabase.update_presence(&user_id, "online").await;
+        let in_session = app_state.inner.read().await.session.is_some();
+        let _ = supabase
+            .update_presence(
+                &user_id,
+                presence::published_status("online", in_session, false),
+            )
+            .await;
     }
 
     tracing::info!("Connected to chat realtime");
     Ok(())
 }
 
+/// Refresh the user's presence while chat is connected, so last seen
+/// reflects when the app was last running
+async fn presence_heartbeat(app_handle: AppHandle, user_id: String) {
+    // connect_chat already sent the first update
+    loop {
+        tokio::time::sleep(presence::heartbeat_interval(low_bandwidth::is_enabled())).await;
+        let chat_state = app_handle.state::<ChatState>();
+        let chat_inner = chat_state.inner.read().await;
+        let Some(status) = chat_inner.presence_status.clone() else {
+            return;
+        };
+        let idle = chat_inner.idle;
+        drop(chat_inner);
+        let app_state = app_handle.state::<AppState>();
+        let Some(ref supabase) = app_state.supabase else {
+            return;
+        };
+        let in_session = app_state.inner.read().await.session.is_some();
+        let published = presence::published_status(&status, in_session, idle);
+        if let Err(e) = supabase.update_presence(&user_id, published).await {
+            tracing::debug!("Presence heartbeat failed: {}", e);
+        }
+    }
+}
+
 /// Disconnect from chat realtime
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn disconnect_chat(
     app_state: State<'_, AppState>,
     chat_state: State<'_, ChatState>,
@@ -799,6 +2049,10 @@ pub async fn disconnect_chat(
 
     state.realtime = None;
     state.is_connected = false;
+    state.presence_status = None;
+    if let Some(heartbeat) = state.heartbeat.take() {
+        heartbeat.abort();
+    }
 
     // Update presence to offline
     let inner = app_state.inner.read().await;
@@ -812,8 +2066,47 @@ pub async fn disconnect_chat(
     Ok(())
 }
 
+/// Count a conversation as open, joining its realtime channel so its
+/// broadcasts arrive. Each open is matched by a `close_conversation`
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn open_conversation(
+    conversation_id: String,
+    chat_state: State<'_, ChatState>,
+) -> Result<()> {
+    let mut state = chat_state.inner.write().await;
+    if !state.open_conversations.open(&conversation_id) {
+        return Ok(());
+    }
+    if let Some(ref realtime) = state.realtime {
+        realtime.subscribe_to_conversation(&conversation_id).await?;
+    }
+    Ok(())
+}
+
+/// Count a conversation as closed, leaving its realtime channel once it is
+/// open nowhere
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn close_conversation(
+    conversation_id: String,
+    chat_state: State<'_, ChatState>,
+) -> Result<()> {
+    let mut state = chat_state.inner.write().await;
+    if !state.open_conversations.close(&conversation_id) {
+        return Ok(());
+    }
+    if let Some(ref realtime) = state.realtime {
+        realtime
+            .unsubscribe_from_conversation(&conversation_id)
+            .await?;
+    }
+    Ok(())
+}
+
 /// Get chat connection status
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_chat_status(
     chat_state: State<'_, ChatState>,
 ) -> Result<bool> {
@@ -827,6 +2120,7 @@ pub async fn get_chat_status(
 
 /// Search conversations by name or participant
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn search_conversations(
     query: String,
     app_state: State<'_, AppState>,
@@ -850,60 +2144,42 @@ pub async fn search_conversations(
     // Get all conversations and filter locally
     let conversation_rows = supabase.get_user_conversations(&user_id).await?;
 
-    let mut results = Vec::new();
-
-    for row in conversation_rows {
-        let participants_raw = supabase.get_conversation_participants(&row.id).await?;
-        let participants: Vec<Participant> = participants_raw
-            .into_iter()
-            .map(|p| Participant {
-                user_id: p.user_id,
-                display_name: p.display_name,
-                avatar_url: p.avatar_url,
-                role: p.role,
-                is_online: p.is_online,
-                last_seen_at: p.last_seen_at,
-            })
-            .collect();
+    // Participants are needed to match, last messages only for the matches
+    let ids: Vec<String> = conversation_rows.iter().map(|row| row.id.clone()).collect();
+    let mut participants = fetch_participants_batch(supabase, &ids).await?;
 
-        // Check if query matches conversation name or any participant name
-        let name_matches = row
-            .name
-            .as_ref()
-            .map(|n| n.to_lowercase().contains(&query_lower))
-            .unwrap_or(false);
+    let matching: Vec<(ConversationRow, Vec<Participant>)> = conversation_rows
+        .into_iter()
+        .map(|row| {
+            let row_participants = participants.remove(&row.id).unwrap_or_default();
+            (row, row_participants)
+        })
+        .filter(|(row, participants)| {
+            // Check if query matches conversation name or any participant name
+            let name_matches = row
+                .name
+                .as_ref()
+                .map(|n| n.to_lowercase().contains(&query_lower))
+                .unwrap_or(false);
 
-        let participant_matches = participants
-            .iter()
-            .any(|p| p.display_name.to_lowercase().contains(&query_lower));
+            let participant_matches = participants
+                .iter()
+                .any(|p| p.display_name.to_lowercase().contains(&query_lower));
 
-        if name_matches || participant_matches {
-            // Get last message
-            let messages = supabase.get_messages(&row.id, 1, None).await?;
-            let last_message = messages.into_iter().next().map(|m| Message {
-                id: m.id,
-                conversation_id: m.conversation_id,
-                sender_id: m.sender_id.clone(),
-                sender_name: m.sender_id.unwrap_or_else(|| "Unknown".to_string()),
-                content: m.content,
-                message_type: m.message_type,
-                created_at: m.created_at,
-            });
-
-            results.push(Conversation {
-                id: row.id,
-                conversation_type: row.conversation_type,
-                name: row.name,
-                avatar_url: row.avatar_url,
-                created_by: row.created_by,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-                participants,
-                last_message,
-                unread_count: 0,
-            });
-        }
-    }
+            name_matches || participant_matches
+        })
+        .collect();
+
+    let ids: Vec<String> = matching.iter().map(|(row, _)| row.id.clone()).collect();
+    let mut last_messages = fetch_last_messages(supabase, &ids).await?;
+    let mut results: Vec<Conversation> = matching
+        .into_iter()
+        .map(|(row, participants)| {
+            let last_message = last_messages.remove(&row.id);
+            conversation_from_row(row, participants, last_message)
+        })
+        .collect();
+    apply_unread_counts(supabase, &mut results).await;
 
     // Sort by updated_at descending
     results.sort_by(|a, b| {
@@ -919,6 +2195,7 @@ pub async fn search_conversations(
 
 /// Search messages in a conversation by content
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn search_messages(
     conversation_id: String,
     query: String,
@@ -926,7 +2203,7 @@ pub async fn search_messages(
     app_state: State<'_, AppState>,
 ) -> Result<Vec<Message>> {
     let inner = app_state.inner.read().await;
-    let _user = inner
+    let _current_user = inner
         .user
         .as_ref()
         .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
@@ -947,45 +2224,12 @@ pub async fn search_messages(
         .get_messages(&conversation_id, 500, None) // Fetch more to search
         .await?;
 
-    // Get sender profiles for matching messages
-    let sender_ids: Vec<String> = message_rows
-        .iter()
-        .filter_map(|m| m.sender_id.clone())
-        .collect();
-
-    let profiles = if !sender_ids.is_empty() {
-        supabase.get_user_profiles(&sender_ids).await?
-    } else {
-        vec![]
-    };
-
-    let results: Vec<Message> = message_rows
-        .iter()
+    let matching: Vec<MessageRow> = message_rows
+        .into_iter()
         .filter(|m| m.content.to_lowercase().contains(&query_lower))
         .take(limit as usize)
-        .map(|m| {
-            let sender_name = m
-                .sender_id
-                .as_ref()
-                .and_then(|sid| {
-                    profiles
-                        .iter()
-                        .find(|p| &p.user_id == sid)
-                        .and_then(|p| p.display_name.clone())
-                })
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            Message {
-                id: m.id.clone(),
-                conversation_id: m.conversation_id.clone(),
-                sender_id: m.sender_id.clone(),
-                sender_name,
-                content: m.content.clone(),
-                message_type: m.message_type.clone(),
-                created_at: m.created_at.clone(),
-            }
-        })
         .collect();
+    let results = with_sender_names(supabase, matching).await?;
 
     tracing::debug!(
         "Found {} messages matching '{}' in conversation {}",
@@ -999,6 +2243,7 @@ pub async fn search_messages(
 
 /// Search team members by name
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn search_team_members(
     query: String,
     app_state: State<'_, AppState>,
@@ -1032,20 +2277,7 @@ pub async fn search_team_members(
                 .map(|n| n.to_lowercase().contains(&query_lower))
                 .unwrap_or(false)
         })
-        .map(|p| {
-            let is_online = presence
-                .iter()
-                .find(|pr| pr.user_id == p.user_id)
-                .map(|pr| pr.status == "online")
-                .unwrap_or(false);
-
-            TeamMember {
-                user_id: p.user_id.clone(),
-                display_name: p.display_name.clone().unwrap_or_else(|| "Unknown".to_string()),
-                avatar_url: p.avatar_url.clone(),
-                is_online,
-            }
-        })
+        .map(|p| team_member(p, &presence))
         .collect();
 
     tracing::debug!("Found {} team members matching '{}'", results.len(), query);
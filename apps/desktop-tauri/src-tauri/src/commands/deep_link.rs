@@ -0,0 +1,108 @@
+//! Invite link commands and routing of opened links
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::calendar::Meeting;
+use crate::commands::session::SessionInfo;
+use crate::deep_link::DeepLink;
+use crate::state::{AppState, SessionStatus};
+use crate::{Error, Result};
+
+/// Payload of `deeplink:received`
+///
+/// Session links have already been handed to `join_session` when this is
+/// emitted; `error` says why the link could not be followed.
+#[derive(Serialize)]
+pub struct DeepLinkEvent {
+    pub url: String,
+    pub link: Option<DeepLink>,
+    pub session: Option<SessionInfo>,
+    pub meeting: Option<Meeting>,
+    pub error: Option<String>,
+}
+
+/// Get the invite link of the current session
+#[tauri::command]
+pub async fn create_session_link(state: State<'_, AppState>) -> Result<String> {
+    let inner = state.inner.read().await;
+    let session = inner
+        .session
+        .as_ref()
+        .filter(|s| !matches!(s.status, SessionStatus::Ended))
+        .ok_or_else(|| Error::Session("No active session".to_string()))?;
+
+    let link = DeepLink::Session {
+        join_code: session.join_code.clone(),
+        mode: session.mode,
+    };
+    Ok(link.url())
+}
+
+/// Get the invite link of a meeting
+#[tauri::command]
+pub async fn create_meeting_link(meeting_id: String) -> Result<String> {
+    let link = DeepLink::parse(&DeepLink::Meeting { meeting_id }.url())?;
+    Ok(link.url())
+}
+
+/// Follow links the app was opened with
+pub async fn open_urls(app_handle: AppHandle, urls: Vec<String>) {
+    for url in urls {
+        let event = open_url(&app_handle, url).await;
+        if let Err(e) = app_handle.emit("deeplink:received", &event) {
+            tracing::error!("Failed to emit deep link event: {}", e);
+        }
+    }
+}
+
+async fn open_url(app_handle: &AppHandle, url: String) -> DeepLinkEvent {
+    tracing::info!("Opening link {}", url);
+    let mut event = DeepLinkEvent {
+        url,
+        link: None,
+        session: None,
+        meeting: None,
+        error: None,
+    };
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let link = match DeepLink::parse(&event.url) {
+        Ok(link) => link,
+        Err(e) => {
+            tracing::warn!("Ignoring link: {}", e);
+            event.error = Some(e.to_string());
+            return event;
+        }
+    };
+
+    let result = match &link {
+        DeepLink::Session { join_code, mode } => crate::commands::session::join_session(
+            join_code.clone(),
+            Some(*mode),
+            app_handle.state(),
+            app_handle.clone(),
+        )
+        .await
+        .map(|session| event.session = Some(session)),
+        DeepLink::Meeting { meeting_id } => {
+            crate::commands::calendar::get_meeting(meeting_id.clone(), app_handle.state())
+                .await
+                .and_then(|meeting| {
+                    meeting.ok_or_else(|| Error::NotFound("Meeting not found".to_string()))
+                })
+                .map(|meeting| event.meeting = Some(meeting))
+        }
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to open {}: {}", event.url, e);
+        event.error = Some(e.to_string());
+    }
+    event.link = Some(link);
+    event
+}
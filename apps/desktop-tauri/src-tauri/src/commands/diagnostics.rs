@@ -0,0 +1,131 @@
+//! Diagnostics bundle commands
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::signaling::SignalingState;
+use crate::commands::stream::StreamState;
+use crate::connection_quality::ConnectionStats;
+use crate::diagnostics::{self, DiagnosticsBundle, SystemInfo};
+use crate::logging::{LogFilter, RecentLogs};
+use crate::perf::PerfState;
+use crate::state::AppState;
+use crate::Result;
+
+/// Configuration section of a bundle
+#[derive(Debug, Serialize)]
+struct ConfigReport {
+    environment: std::collections::BTreeMap<String, String>,
+    supabase_configured: bool,
+    trace_filter: String,
+    battery_saver: crate::resources::BatterySaverSettings,
+}
+
+/// Network section of a bundle
+#[derive(Debug, Serialize)]
+struct NetworkReport {
+    connections: Vec<ConnectionStats>,
+    ice_servers: usize,
+    turn_configured: bool,
+}
+
+/// What the background work of a session is doing
+#[derive(Debug, Serialize)]
+struct TaskReport {
+    signaling_connected: bool,
+    lan_signaling: bool,
+    participants: usize,
+    pending_control_requests: usize,
+    control_timer_running: bool,
+    streaming: bool,
+    stats_sampling: bool,
+    viewer_connections: usize,
+    battery_saver_active: bool,
+    is_capturing: bool,
+    is_input_enabled: bool,
+}
+
+/// Zip recent logs, redacted configuration, cache, network and command
+/// stats, background task status and app/OS versions for a bug report
+///
+/// Returns the path of the bundle, written to the app data directory unless
+/// `path` is given.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_diagnostics_bundle(
+    path: Option<String>,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<String> {
+    let perf_state = app_handle.state::<PerfState>();
+    let log_filter = app_handle.state::<LogFilter>();
+    let recent_logs = app_handle.state::<RecentLogs>();
+    let mut bundle = DiagnosticsBundle::default();
+
+    let system = SystemInfo::collect(app_handle.package_info().version.to_string());
+    bundle.add_json("system.json", &system)?;
+    bundle.add_text("logs.txt", recent_logs.snapshot().join("\n"));
+
+    let cache = crate::commands::cache::get_cache_stats(app_handle.state()).await?;
+    bundle.add_json("cache.json", &cache)?;
+    bundle.add_json("perf.json", &perf_state.snapshot())?;
+
+    let (is_capturing, is_input_enabled) = {
+        let inner = app_state.inner.read().await;
+        (inner.is_capturing, inner.is_input_enabled)
+    };
+    let (network, battery_saver, tasks) = {
+        let signaling = signaling_state.inner.read().await;
+        let stream = stream_state.inner.read().await;
+        let network = NetworkReport {
+            connections: stream.quality.latest(),
+            ice_servers: stream.effective_ice_servers().len(),
+            turn_configured: stream.turn_server.is_some(),
+        };
+        let tasks = TaskReport {
+            signaling_connected: signaling.is_connected,
+            lan_signaling: signaling.lan.is_some(),
+            participants: signaling.participants.len(),
+            pending_control_requests: signaling.control_requests.len(),
+            control_timer_running: signaling.control_timer.is_some(),
+            streaming: stream.pipeline.is_some(),
+            stats_sampling: stream
+                .stats_task
+                .as_ref()
+                .is_some_and(|task| !task.is_finished()),
+            viewer_connections: stream.peers.len(),
+            battery_saver_active: stream.battery_saver_active,
+            is_capturing,
+            is_input_enabled,
+        };
+        (network, stream.battery_saver, tasks)
+    };
+    bundle.add_json("network.json", &network)?;
+    bundle.add_json("tasks.json", &tasks)?;
+
+    let config = ConfigReport {
+        environment: diagnostics::config_snapshot(),
+        supabase_configured: app_state.supabase.is_some(),
+        trace_filter: log_filter.current(),
+        battery_saver,
+    };
+    bundle.add_json("config.json", &config)?;
+
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => app_handle
+            .path()
+            .app_data_dir()?
+            .join("diagnostics")
+            .join(format!(
+                "squadx-diagnostics-{}.zip",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            )),
+    };
+    bundle.write_zip(&path)?;
+
+    tracing::info!("Diagnostics bundle written to {}", path.display());
+    Ok(path.to_string_lossy().into_owned())
+}
@@ -0,0 +1,311 @@
+//! Custom emoji commands
+//!
+//! Teammates upload images as custom emoji, used as `:shortcode:` in
+//! messages. Images are normalized to small WebP files before upload and
+//! cached locally through the avatar pipeline; the frontend loads the
+//! returned paths with `convertFileSrc`.
+
+use std::path::PathBuf;
+
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use tauri::State;
+
+use crate::avatars::{self, AvatarCache};
+use crate::emoji::{self, TextPart};
+use crate::state::AppState;
+use crate::supabase::{CustomEmojiRow, SupabaseClient};
+use crate::{Error, Result};
+
+/// Storage bucket holding the emoji images
+const EMOJI_BUCKET: &str = "custom-emoji";
+
+/// Edge of stored and cached emoji images, 2x the largest rendered size
+const EMOJI_IMAGE_SIZE: u32 = 128;
+
+/// Source images larger than this are rejected
+const MAX_EMOJI_SOURCE_BYTES: u64 = 1024 * 1024;
+
+/// Emoji images cached in parallel by `list_custom_emojis`
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomEmoji {
+    pub id: String,
+    pub shortcode: String,
+    pub image_url: String,
+    /// Cached image, unless it failed to download
+    pub local_path: Option<String>,
+    pub created_by: String,
+    pub created_at: Option<String>,
+}
+
+/// Piece of a message as rendered
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text {
+        text: String,
+    },
+    Emoji {
+        shortcode: String,
+        image_url: String,
+        local_path: Option<String>,
+    },
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// The team's emoji, from the cache when fresh
+async fn load_emojis(
+    app_state: &AppState,
+    supabase: &SupabaseClient,
+) -> Result<Vec<CustomEmojiRow>> {
+    if let Some(cached) = app_state.cache.emojis.read().await.get() {
+        return Ok(cached.clone());
+    }
+
+    let emojis = supabase.get_custom_emojis().await?;
+    app_state.cache.emojis.write().await.set(emojis.clone());
+    Ok(emojis)
+}
+
+/// Local path of an emoji image, downloading it on first use
+async fn cached_image(avatars: &AvatarCache, image_url: &str) -> Option<String> {
+    match avatars.thumbnail(image_url, EMOJI_IMAGE_SIZE).await {
+        Ok(path) => Some(path.to_string_lossy().to_string()),
+        Err(e) => {
+            tracing::warn!("Failed to cache emoji image {}: {}", image_url, e);
+            None
+        }
+    }
+}
+
+fn to_custom_emoji(row: CustomEmojiRow, local_path: Option<String>) -> CustomEmoji {
+    CustomEmoji {
+        id: row.id,
+        shortcode: row.shortcode,
+        image_url: row.image_url,
+        local_path,
+        created_by: row.created_by,
+        created_at: row.created_at,
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the team's custom emoji with their cached images
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_custom_emojis(
+    app_state: State<'_, AppState>,
+    avatars: State<'_, AvatarCache>,
+) -> Result<Vec<CustomEmoji>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let rows = load_emojis(&app_state, supabase).await?;
+
+    let avatars = avatars.inner();
+    let emojis = stream::iter(rows)
+        .map(|row| async move {
+            let local_path = cached_image(avatars, &row.image_url).await;
+            to_custom_emoji(row, local_path)
+        })
+        .buffered(MAX_CONCURRENT_DOWNLOADS)
+        .collect()
+        .await;
+
+    Ok(emojis)
+}
+
+/// Add a custom emoji from a local image file
+///
+/// The image is cropped to a square WebP; animated images keep their first
+/// frame.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn add_custom_emoji(
+    shortcode: String,
+    file_path: String,
+    app_state: State<'_, AppState>,
+    avatars: State<'_, AvatarCache>,
+) -> Result<CustomEmoji> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let shortcode = emoji::normalize_shortcode(&shortcode)?;
+    if load_emojis(&app_state, supabase)
+        .await?
+        .iter()
+        .any(|e| e.shortcode == shortcode)
+    {
+        return Err(Error::Parse(format!(
+            "Emoji :{}: already exists",
+            shortcode
+        )));
+    }
+
+    let path = PathBuf::from(&file_path);
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|_| Error::NotFound(format!("File not found: {}", file_path)))?;
+    if metadata.len() > MAX_EMOJI_SOURCE_BYTES {
+        return Err(Error::Parse(format!(
+            "Emoji images are limited to {} KB",
+            MAX_EMOJI_SOURCE_BYTES / 1024
+        )));
+    }
+    let bytes = tokio::fs::read(&path).await?;
+    let image =
+        tokio::task::spawn_blocking(move || avatars::make_thumbnail(&bytes, EMOJI_IMAGE_SIZE))
+            .await
+            .map_err(|e| Error::Parse(format!("Emoji image task failed: {}", e)))??;
+
+    // Under the uploader's folder, as the bucket policies require
+    let storage_path = format!("{}/{}-{}.webp", user_id, shortcode, uuid::Uuid::new_v4());
+    supabase
+        .upload_storage_object(EMOJI_BUCKET, &storage_path, "image/webp", image)
+        .await?;
+    let image_url = supabase.storage_public_url(EMOJI_BUCKET, &storage_path);
+
+    let row = match supabase
+        .create_custom_emoji(&shortcode, &storage_path, &image_url, &user_id)
+        .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            if let Err(cleanup) = supabase
+                .delete_storage_object(EMOJI_BUCKET, &storage_path)
+                .await
+            {
+                tracing::warn!("Failed to remove orphaned emoji image: {}", cleanup);
+            }
+            return Err(e);
+        }
+    };
+    app_state.cache.emojis.write().await.invalidate_all();
+
+    tracing::info!("Custom emoji :{}: added", shortcode);
+    let local_path = cached_image(&avatars, &row.image_url).await;
+    Ok(to_custom_emoji(row, local_path))
+}
+
+/// Remove a custom emoji (uploader only)
+///
+/// Messages that use it keep the `:shortcode:` text.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn delete_custom_emoji(emoji_id: String, app_state: State<'_, AppState>) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let emoji = load_emojis(&app_state, supabase)
+        .await?
+        .into_iter()
+        .find(|e| e.id == emoji_id)
+        .ok_or_else(|| Error::NotFound(format!("Custom emoji {} not found", emoji_id)))?;
+    if emoji.created_by != user_id {
+        return Err(Error::Auth(
+            "Only the uploader can delete a custom emoji".to_string(),
+        ));
+    }
+
+    supabase.delete_custom_emoji(&emoji.id).await?;
+    if let Err(e) = supabase
+        .delete_storage_object(EMOJI_BUCKET, &emoji.storage_path)
+        .await
+    {
+        tracing::warn!("Failed to delete emoji image {}: {}", emoji.storage_path, e);
+    }
+    app_state.cache.emojis.write().await.invalidate_all();
+
+    tracing::info!("Custom emoji :{}: deleted", emoji.shortcode);
+    Ok(())
+}
+
+/// Split message text into text and custom emoji for rendering
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn resolve_emoji_shortcodes(
+    text: String,
+    app_state: State<'_, AppState>,
+    avatars: State<'_, AvatarCache>,
+) -> Result<Vec<MessagePart>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let emojis = load_emojis(&app_state, supabase).await?;
+    let find = |shortcode: &str| emojis.iter().find(|e| e.shortcode == shortcode);
+
+    let mut parts = Vec::new();
+    for part in emoji::split_shortcodes(&text, |shortcode| find(shortcode).is_some()) {
+        parts.push(match part {
+            TextPart::Text(text) => MessagePart::Text {
+                text: text.to_string(),
+            },
+            TextPart::Shortcode(shortcode) => {
+                let image_url = find(shortcode)
+                    .map(|e| e.image_url.clone())
+                    .unwrap_or_default();
+                MessagePart::Emoji {
+                    shortcode: shortcode.to_string(),
+                    local_path: cached_image(&avatars, &image_url).await,
+                    image_url,
+                }
+            }
+        });
+    }
+
+    Ok(parts)
+}
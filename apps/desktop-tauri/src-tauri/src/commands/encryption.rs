@@ -0,0 +1,186 @@
+//! Encrypted conversation commands
+//!
+//! The device key is loaded from the keychain at startup, or generated and
+//! kept there, and its public half is published when chat connects. Message
+//! content is sealed before it is saved or broadcast, and opened wherever
+//! messages are read back, so commands and events only ever carry plaintext
+//! to the frontend. Attachments, links and search stay unencrypted.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use tauri::{AppHandle, State};
+
+use crate::chat_crypto::{self, DeviceKey, DevicePublicKey};
+use crate::commands::remote_wipe::device_id_path;
+use crate::remote_wipe;
+use crate::secure_storage::{self, CredentialKey};
+use crate::state::AppState;
+use crate::supabase::{DeviceKeyRow, SupabaseClient};
+use crate::{Error, Result};
+
+/// This device's key, once loaded
+static DEVICE_KEY: OnceLock<DeviceKey> = OnceLock::new();
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Load this device's key from the keychain, creating it on first run
+pub fn load_device_key(app_handle: &AppHandle) -> Result<()> {
+    let device_id = remote_wipe::load_or_create_device_id(&device_id_path(app_handle)?)?;
+    let key = match secure_storage::get_credential(CredentialKey::DeviceKey)
+        .and_then(|stored| DeviceKey::from_stored(&device_id, &stored))
+    {
+        Some(key) => key,
+        None => {
+            let key = DeviceKey::generate(&device_id);
+            secure_storage::store_credential(CredentialKey::DeviceKey, &key.to_stored())?;
+            tracing::info!("Generated chat encryption key for device {}", device_id);
+            key
+        }
+    };
+    let _ = DEVICE_KEY.set(key);
+    Ok(())
+}
+
+fn device_key() -> Result<&'static DeviceKey> {
+    DEVICE_KEY
+        .get()
+        .ok_or_else(|| Error::Config("Chat encryption key not available".to_string()))
+}
+
+/// Publish this device's public key, so others can encrypt for it
+pub(crate) async fn publish_device_key(app_state: &AppState) -> Result<()> {
+    let user_id = app_state
+        .inner
+        .read()
+        .await
+        .user
+        .as_ref()
+        .map(|user| user.id.clone())
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let key = device_key()?.public_key();
+    supabase
+        .publish_device_key(&DeviceKeyRow {
+            user_id,
+            device_id: key.device_id,
+            public_key: key.public_key,
+        })
+        .await
+}
+
+/// Seal message content for every device of the conversation's participants
+pub(crate) async fn seal_content(
+    supabase: &SupabaseClient,
+    conversation_id: &str,
+    sender_id: &str,
+    content: &str,
+) -> Result<String> {
+    let user_ids: Vec<String> = supabase
+        .get_conversation_participants(conversation_id)
+        .await?
+        .into_iter()
+        .map(|p| p.user_id)
+        .collect();
+
+    // This device can read what it sent even if its key wasn't published
+    let own_key = device_key()?.public_key();
+    let mut seen = HashSet::from([own_key.device_id.clone()]);
+    let mut devices = vec![own_key];
+    for row in supabase.get_device_keys(&user_ids).await? {
+        if seen.insert(row.device_id.clone()) {
+            devices.push(DevicePublicKey {
+                device_id: row.device_id,
+                public_key: row.public_key,
+            });
+        }
+    }
+
+    chat_crypto::seal(content, conversation_id, sender_id, &devices)
+}
+
+/// Message content as shown: opened when sealed, or a placeholder when this
+/// device can't read it
+pub(crate) fn open_content(
+    conversation_id: &str,
+    sender_id: Option<&str>,
+    content: String,
+) -> String {
+    if !chat_crypto::is_sealed(&content) {
+        return content;
+    }
+    let opened = device_key().and_then(|key| {
+        chat_crypto::open(
+            &content,
+            conversation_id,
+            sender_id.unwrap_or_default(),
+            key,
+        )
+    });
+    match opened {
+        Ok(plaintext) => plaintext,
+        Err(e) => {
+            tracing::debug!("Can't open message in {}: {}", conversation_id, e);
+            chat_crypto::UNREADABLE_PLACEHOLDER.to_string()
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Turn on end-to-end encryption for a conversation
+///
+/// Every participant needs a published device key, that is, to have
+/// connected to chat with a version that supports encryption. Encryption
+/// can't be turned off again, and earlier messages stay as they were.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn enable_conversation_encryption(
+    conversation_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    publish_device_key(&app_state).await?;
+
+    let user_ids: Vec<String> = supabase
+        .get_conversation_participants(&conversation_id)
+        .await?
+        .into_iter()
+        .map(|p| p.user_id)
+        .collect();
+    let with_keys: HashSet<String> = supabase
+        .get_device_keys(&user_ids)
+        .await?
+        .into_iter()
+        .map(|row| row.user_id)
+        .collect();
+    let missing = user_ids
+        .iter()
+        .filter(|id| !with_keys.contains(*id))
+        .count();
+    if missing > 0 {
+        return Err(Error::Input(format!(
+            "{} participant(s) need to update the app before encryption can be turned on",
+            missing
+        )));
+    }
+
+    supabase
+        .enable_conversation_encryption(&conversation_id)
+        .await?;
+
+    tracing::info!("Enabled encryption for conversation {}", conversation_id);
+    Ok(())
+}
@@ -0,0 +1,769 @@
+//! File transfer commands
+//!
+//! Exchanges files with a viewer over the file data channel of its native
+//! peer connection (see `crate::file_transfer` for the wire protocol), so
+//! patches and logs can be shared without leaving the app. Downloads land in
+//! the user's download directory; the partial file is kept when the channel
+//! drops, and offering the same file again resumes it.
+
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+use webrtc::data_channel::RTCDataChannel;
+
+use crate::commands::signaling::SignalingState;
+use crate::commands::stream::StreamState;
+use crate::file_transfer::{self, TransferMessage, CHUNK_SIZE};
+use crate::peer::{FileChannelEvent, NativePeer};
+use crate::signaling_protocol::CAP_FILE_TRANSFER;
+use crate::{Error, Result};
+
+/// Progress events are emitted each time this many bytes went through
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+/// Outgoing chunks are paused while the channel buffers more than this
+const MAX_BUFFERED_AMOUNT: usize = 1024 * 1024;
+
+const BUFFER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    Outgoing,
+    Incoming,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Offered,
+    InProgress,
+    /// The channel closed mid-transfer; offering the file again resumes it
+    Interrupted,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl TransferStatus {
+    fn is_finished(self) -> bool {
+        matches!(
+            self,
+            TransferStatus::Completed | TransferStatus::Failed | TransferStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTransfer {
+    pub id: String,
+    /// User on the other end
+    pub peer_id: String,
+    pub direction: TransferDirection,
+    pub file_name: String,
+    /// Source file when sending; partial, then final file when receiving
+    pub path: Option<String>,
+    pub size: u64,
+    pub sha256: String,
+    pub bytes_transferred: u64,
+    pub status: TransferStatus,
+    pub error: Option<String>,
+}
+
+pub struct FileTransferState {
+    pub inner: Arc<RwLock<FileTransferStateInner>>,
+}
+
+#[derive(Default)]
+pub struct FileTransferStateInner {
+    pub transfers: HashMap<String, FileTransfer>,
+    /// Cancellation flags of outgoing transfers being streamed
+    senders: HashMap<String, Arc<AtomicBool>>,
+    /// Open partial files of incoming transfers
+    writers: HashMap<String, tokio::fs::File>,
+}
+
+impl Default for FileTransferState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(FileTransferStateInner::default())),
+        }
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Route a viewer's file channel to the transfer handlers, preserving order
+pub(crate) fn attach_peer(app_handle: &AppHandle, peer_id: &str, peer: &NativePeer) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    peer.on_file_channel_event(move |event| {
+        let _ = tx.send(event);
+    });
+
+    let app_handle = app_handle.clone();
+    let peer_id = peer_id.to_string();
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            handle_channel_event(&app_handle, &peer_id, event).await;
+        }
+    });
+}
+
+async fn file_channel(app_handle: &AppHandle, peer_id: &str) -> Result<Arc<RTCDataChannel>> {
+    let stream_state = app_handle.state::<StreamState>();
+    let state = stream_state.inner.read().await;
+    state
+        .peers
+        .get(peer_id)
+        .map(|peer| peer.file_channel())
+        .ok_or_else(|| Error::Session(format!("No peer connection to {}", peer_id)))
+}
+
+async fn send_message(channel: &RTCDataChannel, message: &TransferMessage) -> Result<()> {
+    channel
+        .send_text(serde_json::to_string(message)?)
+        .await
+        .map_err(|e| Error::WebRtc(format!("Failed to send transfer message: {}", e)))?;
+    Ok(())
+}
+
+fn emit_transfer(app_handle: &AppHandle, event: &str, transfer: &FileTransfer) {
+    if let Err(e) = app_handle.emit(event, transfer) {
+        tracing::error!("Failed to emit {} event: {}", event, e);
+    }
+}
+
+/// Whether a progress event is due after moving from `before` to `after` bytes
+fn crossed_progress_step(before: u64, after: u64, size: u64) -> bool {
+    after == size || before / PROGRESS_STEP != after / PROGRESS_STEP
+}
+
+/// Update a transfer's status and notify the frontend
+async fn set_status(
+    app_handle: &AppHandle,
+    transfer_id: &str,
+    status: TransferStatus,
+    error: Option<String>,
+) {
+    let transfers = app_handle.state::<FileTransferState>();
+    let mut state = transfers.inner.write().await;
+    let Some(transfer) = state.transfers.get_mut(transfer_id) else {
+        return;
+    };
+    transfer.status = status;
+    transfer.error = error;
+    let transfer = transfer.clone();
+    drop(state);
+
+    emit_transfer(app_handle, "file-transfer:updated", &transfer);
+}
+
+async fn handle_channel_event(app_handle: &AppHandle, peer_id: &str, event: FileChannelEvent) {
+    match event {
+        FileChannelEvent::Text(text) => match serde_json::from_str::<TransferMessage>(&text) {
+            Ok(message) => handle_message(app_handle, peer_id, message).await,
+            Err(e) => tracing::warn!("Invalid file transfer message from {}: {}", peer_id, e),
+        },
+        FileChannelEvent::Binary(frame) => match file_transfer::decode_chunk(&frame) {
+            Ok((transfer_id, offset, data)) => {
+                if let Err(e) =
+                    receive_chunk(app_handle, &transfer_id.to_string(), offset, data).await
+                {
+                    tracing::error!("Failed to write file chunk: {}", e);
+                    fail_transfer(app_handle, peer_id, &transfer_id.to_string(), e.to_string())
+                        .await;
+                }
+            }
+            Err(e) => tracing::warn!("Invalid file chunk from {}: {}", peer_id, e),
+        },
+        FileChannelEvent::Closed => interrupt_peer_transfers(app_handle, peer_id).await,
+    }
+}
+
+async fn handle_message(app_handle: &AppHandle, peer_id: &str, message: TransferMessage) {
+    match message {
+        TransferMessage::Offer {
+            transfer_id,
+            file_name,
+            size,
+            sha256,
+        } => {
+            if !file_transfer::is_sha256_hex(&sha256) {
+                tracing::warn!(
+                    "Rejected file offer from {} with an invalid checksum",
+                    peer_id
+                );
+                if let Ok(channel) = file_channel(app_handle, peer_id).await {
+                    let _ = send_message(
+                        &channel,
+                        &TransferMessage::Cancel {
+                            transfer_id,
+                            reason: Some("Invalid file checksum".to_string()),
+                        },
+                    )
+                    .await;
+                }
+                return;
+            }
+
+            let transfer = FileTransfer {
+                id: transfer_id.clone(),
+                peer_id: peer_id.to_string(),
+                direction: TransferDirection::Incoming,
+                file_name: file_transfer::sanitize_file_name(&file_name),
+                path: None,
+                size,
+                sha256,
+                bytes_transferred: 0,
+                status: TransferStatus::Offered,
+                error: None,
+            };
+            let transfers = app_handle.state::<FileTransferState>();
+            transfers
+                .inner
+                .write()
+                .await
+                .transfers
+                .insert(transfer_id, transfer.clone());
+
+            tracing::info!("File offered by {}: {}", peer_id, transfer.file_name);
+            emit_transfer(app_handle, "file-transfer:incoming", &transfer);
+        }
+        TransferMessage::Accept {
+            transfer_id,
+            offset,
+        } => {
+            let app_handle = app_handle.clone();
+            let peer_id = peer_id.to_string();
+            tokio::spawn(async move {
+                if let Err(e) = send_chunks(&app_handle, &transfer_id, offset).await {
+                    tracing::error!("File transfer {} failed: {}", transfer_id, e);
+                    fail_transfer(&app_handle, &peer_id, &transfer_id, e.to_string()).await;
+                }
+            });
+        }
+        TransferMessage::Cancel {
+            transfer_id,
+            reason,
+        } => {
+            stop_transfer(app_handle, &transfer_id).await;
+            set_status(app_handle, &transfer_id, TransferStatus::Cancelled, reason).await;
+        }
+        TransferMessage::Complete { transfer_id } => {
+            tracing::info!("File transfer {} completed", transfer_id);
+            set_status(app_handle, &transfer_id, TransferStatus::Completed, None).await;
+        }
+        TransferMessage::Failed {
+            transfer_id,
+            reason,
+        } => {
+            set_status(
+                app_handle,
+                &transfer_id,
+                TransferStatus::Failed,
+                Some(reason),
+            )
+            .await;
+        }
+    }
+}
+
+/// Stream an accepted outgoing file from `offset`
+async fn send_chunks(app_handle: &AppHandle, transfer_id: &str, offset: u64) -> Result<()> {
+    let transfers = app_handle.state::<FileTransferState>();
+    let (path, peer_id, size, cancelled) = {
+        let mut state = transfers.inner.write().await;
+        let transfer = state
+            .transfers
+            .get_mut(transfer_id)
+            .filter(|t| t.direction == TransferDirection::Outgoing)
+            .ok_or_else(|| Error::NotFound(format!("Transfer {}", transfer_id)))?;
+        if transfer.status.is_finished() {
+            return Ok(());
+        }
+        if offset > transfer.size {
+            return Err(Error::Parse(
+                "Resume offset is past the end of the file".to_string(),
+            ));
+        }
+
+        transfer.status = TransferStatus::InProgress;
+        transfer.bytes_transferred = offset;
+        emit_transfer(app_handle, "file-transfer:updated", transfer);
+        let path = transfer.path.clone().unwrap_or_default();
+        let peer_id = transfer.peer_id.clone();
+        let size = transfer.size;
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        state
+            .senders
+            .insert(transfer_id.to_string(), cancelled.clone());
+        (path, peer_id, size, cancelled)
+    };
+
+    let channel = file_channel(app_handle, &peer_id).await?;
+    let id = Uuid::parse_str(transfer_id)
+        .map_err(|e| Error::Parse(format!("Invalid transfer ID: {}", e)))?;
+
+    let mut file = tokio::fs::File::open(&path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut sent = offset;
+    while sent < size {
+        // Let the channel drain instead of queueing the whole file in memory
+        while channel.buffered_amount().await > MAX_BUFFERED_AMOUNT {
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            tokio::time::sleep(BUFFER_POLL_INTERVAL).await;
+        }
+        if cancelled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let want = (size - sent).min(CHUNK_SIZE as u64) as usize;
+        let n = file.read(&mut buf[..want]).await?;
+        if n == 0 {
+            return Err(Error::Storage("File shrank during transfer".to_string()));
+        }
+
+        let frame = file_transfer::encode_chunk(&id, sent, &buf[..n]);
+        channel
+            .send(&bytes::Bytes::from(frame))
+            .await
+            .map_err(|e| Error::WebRtc(format!("Failed to send file chunk: {}", e)))?;
+
+        let before = sent;
+        sent += n as u64;
+        if crossed_progress_step(before, sent, size) {
+            let mut state = transfers.inner.write().await;
+            if let Some(transfer) = state.transfers.get_mut(transfer_id) {
+                transfer.bytes_transferred = sent;
+                emit_transfer(app_handle, "file-transfer:progress", transfer);
+            }
+        }
+    }
+
+    // Stays in progress until the receiver confirms the checksum
+    transfers.inner.write().await.senders.remove(transfer_id);
+    Ok(())
+}
+
+/// Append a chunk to an incoming transfer, finishing it on the last one
+async fn receive_chunk(
+    app_handle: &AppHandle,
+    transfer_id: &str,
+    offset: u64,
+    data: &[u8],
+) -> Result<()> {
+    let file_transfers = app_handle.state::<FileTransferState>();
+    let mut state = file_transfers.inner.write().await;
+    let FileTransferStateInner {
+        transfers, writers, ..
+    } = &mut *state;
+
+    let (Some(transfer), Some(writer)) =
+        (transfers.get_mut(transfer_id), writers.get_mut(transfer_id))
+    else {
+        return Ok(());
+    };
+    // The channel is ordered, anything else is a stale chunk from before a resume
+    if transfer.status != TransferStatus::InProgress || offset != transfer.bytes_transferred {
+        tracing::debug!(
+            "Ignoring out-of-order chunk at {} for {}",
+            offset,
+            transfer_id
+        );
+        return Ok(());
+    }
+    if offset + data.len() as u64 > transfer.size {
+        return Err(Error::Parse(
+            "File chunk is past the announced size".to_string(),
+        ));
+    }
+
+    writer.write_all(data).await?;
+    let before = transfer.bytes_transferred;
+    transfer.bytes_transferred += data.len() as u64;
+    if crossed_progress_step(before, transfer.bytes_transferred, transfer.size) {
+        emit_transfer(app_handle, "file-transfer:progress", transfer);
+    }
+
+    if transfer.bytes_transferred == transfer.size {
+        if let Some(mut writer) = writers.remove(transfer_id) {
+            writer.flush().await?;
+        }
+        drop(state);
+        finish_incoming(app_handle, transfer_id).await?;
+    }
+    Ok(())
+}
+
+/// Verify a fully received file and move it to its destination
+async fn finish_incoming(app_handle: &AppHandle, transfer_id: &str) -> Result<()> {
+    let transfers = app_handle.state::<FileTransferState>();
+    let transfer = transfers
+        .inner
+        .read()
+        .await
+        .transfers
+        .get(transfer_id)
+        .cloned()
+        .ok_or_else(|| Error::NotFound(format!("Transfer {}", transfer_id)))?;
+    let partial = PathBuf::from(transfer.path.clone().unwrap_or_default());
+
+    let hash_path = partial.clone();
+    let sha256 = tokio::task::spawn_blocking(move || file_transfer::sha256_file(&hash_path))
+        .await
+        .map_err(|e| Error::Storage(format!("Checksum task failed: {}", e)))??;
+
+    let channel = file_channel(app_handle, &transfer.peer_id).await?;
+    if sha256 != transfer.sha256 {
+        tokio::fs::remove_file(&partial).await?;
+        let reason = "Checksum mismatch".to_string();
+        send_message(
+            &channel,
+            &TransferMessage::Failed {
+                transfer_id: transfer_id.to_string(),
+                reason: reason.clone(),
+            },
+        )
+        .await?;
+        set_status(
+            app_handle,
+            transfer_id,
+            TransferStatus::Failed,
+            Some(reason),
+        )
+        .await;
+        return Ok(());
+    }
+
+    let download_dir = app_handle.path().download_dir()?;
+    let destination = file_transfer::unique_destination(&download_dir, &transfer.file_name);
+    tokio::fs::rename(&partial, &destination).await?;
+
+    send_message(
+        &channel,
+        &TransferMessage::Complete {
+            transfer_id: transfer_id.to_string(),
+        },
+    )
+    .await?;
+
+    if let Some(transfer) = transfers.inner.write().await.transfers.get_mut(transfer_id) {
+        transfer.path = Some(destination.to_string_lossy().to_string());
+    }
+    tracing::info!("File received: {}", destination.display());
+    set_status(app_handle, transfer_id, TransferStatus::Completed, None).await;
+    Ok(())
+}
+
+/// Stop streaming an outgoing transfer, or close and delete the partial file
+/// of an incoming one
+async fn stop_transfer(app_handle: &AppHandle, transfer_id: &str) {
+    let transfers = app_handle.state::<FileTransferState>();
+    let mut state = transfers.inner.write().await;
+    state.writers.remove(transfer_id);
+    if let Some(cancelled) = state.senders.remove(transfer_id) {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+    let partial = state
+        .transfers
+        .get(transfer_id)
+        .filter(|t| t.direction == TransferDirection::Incoming && !t.status.is_finished())
+        .and_then(|t| t.path.clone());
+    drop(state);
+
+    if let Some(partial) = partial {
+        if let Err(e) = tokio::fs::remove_file(&partial).await {
+            tracing::debug!("No partial file to remove at {}: {}", partial, e);
+        }
+    }
+}
+
+/// Abort a transfer after a local error, telling the peer why
+async fn fail_transfer(app_handle: &AppHandle, peer_id: &str, transfer_id: &str, reason: String) {
+    stop_transfer(app_handle, transfer_id).await;
+    if let Ok(channel) = file_channel(app_handle, peer_id).await {
+        let _ = send_message(
+            &channel,
+            &TransferMessage::Cancel {
+                transfer_id: transfer_id.to_string(),
+                reason: Some(reason.clone()),
+            },
+        )
+        .await;
+    }
+    set_status(
+        app_handle,
+        transfer_id,
+        TransferStatus::Failed,
+        Some(reason),
+    )
+    .await;
+}
+
+/// Mark the unfinished transfers of a peer whose channel closed as interrupted
+async fn interrupt_peer_transfers(app_handle: &AppHandle, peer_id: &str) {
+    let transfers = app_handle.state::<FileTransferState>();
+    let mut state = transfers.inner.write().await;
+
+    let interrupted: Vec<String> = state
+        .transfers
+        .values()
+        .filter(|t| {
+            t.peer_id == peer_id
+                && matches!(
+                    t.status,
+                    TransferStatus::Offered | TransferStatus::InProgress
+                )
+        })
+        .map(|t| t.id.clone())
+        .collect();
+
+    for transfer_id in &interrupted {
+        if let Some(cancelled) = state.senders.remove(transfer_id) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+        // Dropping the writer keeps the partial file for a later resume
+        if let Some(mut writer) = state.writers.remove(transfer_id) {
+            let _ = writer.flush().await;
+        }
+        if let Some(transfer) = state.transfers.get_mut(transfer_id) {
+            transfer.status = TransferStatus::Interrupted;
+            emit_transfer(app_handle, "file-transfer:updated", transfer);
+        }
+    }
+
+    if !interrupted.is_empty() {
+        tracing::warn!(
+            "File channel to {} closed, {} transfers interrupted",
+            peer_id,
+            interrupted.len()
+        );
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Offer a file to a viewer connected to the native stream
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn send_file(
+    peer_id: String,
+    path: String,
+    file_transfer_state: State<'_, FileTransferState>,
+    app_handle: AppHandle,
+) -> Result<FileTransfer> {
+    let metadata = tokio::fs::metadata(&path).await?;
+    if !metadata.is_file() {
+        return Err(Error::Storage(format!("Not a file: {}", path)));
+    }
+    let file_name = file_transfer::sanitize_file_name(&path);
+
+    let signaling_state = app_handle.state::<SignalingState>();
+    if signaling_state
+        .inner
+        .read()
+        .await
+        .peer_lacks(&peer_id, CAP_FILE_TRANSFER)
+    {
+        return Err(Error::Session(format!(
+            "{} runs a version of SquadX Live without file transfer",
+            peer_id
+        )));
+    }
+
+    let hash_path = PathBuf::from(&path);
+    let sha256 = tokio::task::spawn_blocking(move || file_transfer::sha256_file(&hash_path))
+        .await
+        .map_err(|e| Error::Storage(format!("Checksum task failed: {}", e)))??;
+
+    let transfer = FileTransfer {
+        id: Uuid::new_v4().to_string(),
+        peer_id: peer_id.clone(),
+        direction: TransferDirection::Outgoing,
+        file_name: file_name.clone(),
+        path: Some(path),
+        size: metadata.len(),
+        sha256: sha256.clone(),
+        bytes_transferred: 0,
+        status: TransferStatus::Offered,
+        error: None,
+    };
+
+    let channel = file_channel(&app_handle, &peer_id).await?;
+    file_transfer_state
+        .inner
+        .write()
+        .await
+        .transfers
+        .insert(transfer.id.clone(), transfer.clone());
+
+    send_message(
+        &channel,
+        &TransferMessage::Offer {
+            transfer_id: transfer.id.clone(),
+            file_name,
+            size: transfer.size,
+            sha256,
+        },
+    )
+    .await?;
+
+    tracing::info!("File offered to {}: {}", peer_id, transfer.file_name);
+    emit_transfer(&app_handle, "file-transfer:updated", &transfer);
+    Ok(transfer)
+}
+
+/// Accept an incoming file, resuming a previous partial download of it
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn accept_file(
+    transfer_id: String,
+    file_transfer_state: State<'_, FileTransferState>,
+    app_handle: AppHandle,
+) -> Result<FileTransfer> {
+    let download_dir = app_handle.path().download_dir()?;
+    tokio::fs::create_dir_all(&download_dir).await?;
+
+    let mut state = file_transfer_state.inner.write().await;
+    let transfer = state
+        .transfers
+        .get(&transfer_id)
+        .filter(|t| t.direction == TransferDirection::Incoming)
+        .ok_or_else(|| Error::NotFound(format!("Transfer {}", transfer_id)))?;
+    if transfer.status != TransferStatus::Offered {
+        return Err(Error::Session(
+            "Transfer is not awaiting acceptance".to_string(),
+        ));
+    }
+
+    let partial = file_transfer::partial_path(&download_dir, &transfer.sha256)?;
+    let existing = tokio::fs::metadata(&partial)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let offset = if existing <= transfer.size {
+        existing
+    } else {
+        0
+    };
+    let size = transfer.size;
+    let peer_id = transfer.peer_id.clone();
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&partial)
+        .await?;
+    file.set_len(offset).await?;
+    file.seek(SeekFrom::End(0)).await?;
+
+    if offset > 0 {
+        tracing::info!("Resuming {} from byte {}", transfer_id, offset);
+    }
+
+    state.writers.insert(transfer_id.clone(), file);
+    let transfer = state
+        .transfers
+        .get_mut(&transfer_id)
+        .ok_or_else(|| Error::NotFound(format!("Transfer {}", transfer_id)))?;
+    transfer.status = TransferStatus::InProgress;
+    transfer.bytes_transferred = offset;
+    transfer.path = Some(partial.to_string_lossy().to_string());
+    let transfer = transfer.clone();
+    drop(state);
+
+    let channel = file_channel(&app_handle, &peer_id).await?;
+    send_message(
+        &channel,
+        &TransferMessage::Accept {
+            transfer_id: transfer_id.clone(),
+            offset,
+        },
+    )
+    .await?;
+    emit_transfer(&app_handle, "file-transfer:updated", &transfer);
+
+    // Nothing left to receive (empty file, or fully downloaded before)
+    if offset == size {
+        file_transfer_state
+            .inner
+            .write()
+            .await
+            .writers
+            .remove(&transfer_id);
+        finish_incoming(&app_handle, &transfer_id).await?;
+    }
+
+    Ok(transfer)
+}
+
+/// Cancel a transfer in either direction, or decline an offered file
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn cancel_transfer(
+    transfer_id: String,
+    file_transfer_state: State<'_, FileTransferState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let peer_id = {
+        let state = file_transfer_state.inner.read().await;
+        let transfer = state
+            .transfers
+            .get(&transfer_id)
+            .ok_or_else(|| Error::NotFound(format!("Transfer {}", transfer_id)))?;
+        if transfer.status.is_finished() {
+            return Err(Error::Session("Transfer already finished".to_string()));
+        }
+        transfer.peer_id.clone()
+    };
+
+    stop_transfer(&app_handle, &transfer_id).await;
+
+    // The peer may already be gone, cancel locally regardless
+    match file_channel(&app_handle, &peer_id).await {
+        Ok(channel) => {
+            send_message(
+                &channel,
+                &TransferMessage::Cancel {
+                    transfer_id: transfer_id.clone(),
+                    reason: None,
+                },
+            )
+            .await?
+        }
+        Err(e) => tracing::debug!("Cancelling {} without notifying peer: {}", transfer_id, e),
+    }
+
+    set_status(&app_handle, &transfer_id, TransferStatus::Cancelled, None).await;
+    tracing::info!("File transfer {} cancelled", transfer_id);
+    Ok(())
+}
+
+/// Get all file transfers of this app session
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_file_transfers(
+    file_transfer_state: State<'_, FileTransferState>,
+) -> Result<Vec<FileTransfer>> {
+    let state = file_transfer_state.inner.read().await;
+    Ok(state.transfers.values().cloned().collect())
+}
@@ -1,14 +1,70 @@
+use std::sync::Mutex;
+
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::commands::google_sync::{GoogleSyncState, PendingAuth};
+use crate::oauth_loopback::LoopbackRedirect;
+use crate::secure_storage::{self, CredentialKey};
 use crate::state::AppState;
-use crate::{Error, Result};
+use crate::supabase::{GoogleTokensRow, MeetingLocationUpdate, MeetingRow};
+use crate::utils::colors;
+use crate::{token_crypto, Error, Result};
+
+/// Time zone events of meetings without one are written in, which Google
+/// needs to repeat them
+pub(crate) const EVENT_TIME_ZONE: &str = "America/Sao_Paulo";
+
+/// Path the browser is redirected to on the loopback listener
+const CALLBACK_PATH: &str = "/auth/google/callback";
+
+/// Redirect of codes pasted by hand
+const MANUAL_REDIRECT_URI: &str = "http://localhost:3000/auth/google/callback";
+
+/// Calendar meetings are synced to until the user picks another
+const PRIMARY_CALENDAR: &str = "primary";
+
+/// Key sealing the stored Google tokens, once loaded. Held while loading
+/// or creating it, so concurrent first uses don't each store a key of
+/// their own
+static TOKEN_KEY: Mutex<Option<[u8; 32]>> = Mutex::new(None);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoogleCalendarStatus {
     pub connected: bool,
     pub email: Option<String>,
     pub sync_enabled: bool,
+    /// Calendar meetings are synced to
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Calendars imports read from besides `calendar_id`
+    #[serde(default)]
+    pub selected_calendar_ids: Vec<String>,
+}
+
+/// A calendar of the user's Google account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleCalendarInfo {
+    pub id: String,
+    pub summary: String,
+    pub primary: bool,
+    /// "owner", "writer", "reader" or "freeBusyReader"
+    pub access_role: String,
+    pub background_color: Option<String>,
+    /// Meetings are synced to it
+    pub sync_target: bool,
+    /// Imports and free/busy checks read from it
+    pub selected: bool,
+}
+
+/// Payload of `calendar:google-auth`, once an authorization started with
+/// `start_google_auth` completed, failed or was replaced by a newer one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleAuthOutcome {
+    /// Attempt ID passed to `start_google_auth`
+    pub attempt_id: String,
+    pub connected: bool,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +75,13 @@ pub struct GoogleEvent {
     pub start: GoogleEventDateTime,
     pub end: GoogleEventDateTime,
     pub attendees: Option<Vec<GoogleEventAttendee>>,
+    pub location: Option<String>,
+    /// Google Meet link of the event
+    #[serde(rename = "hangoutLink")]
+    pub hangout_link: Option<String>,
+    /// Calendar the event was imported from
+    #[serde(default)]
+    pub calendar_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +102,140 @@ pub struct GoogleEventAttendee {
     pub response_status: Option<String>,
 }
 
+/// Location written to a meeting's Google event: where it is held, or its
+/// video call link for online meetings. Rooms stay in the app
+pub(crate) fn event_location(meeting: &MeetingRow) -> Option<&str> {
+    meeting
+        .location
+        .as_deref()
+        .or(meeting.meeting_url.as_deref())
+}
+
+/// Conference asking Google to create a Meet link, for meetings that want
+/// one and have no video call link yet. Google only reads it from requests
+/// made with `conferenceDataVersion=1`
+pub(crate) fn meet_request(meeting: &MeetingRow) -> Option<serde_json::Value> {
+    if !meeting.google_meet || meeting.meeting_url.is_some() {
+        return None;
+    }
+    Some(serde_json::json!({
+        "createRequest": {
+            // Retries with the same ID get the conference already created
+            "requestId": format!("squadx-{}", meeting.id),
+            "conferenceSolutionKey": { "type": "hangoutsMeet" }
+        }
+    }))
+}
+
+/// Keep the Meet link Google created for a meeting as its video call link,
+/// returning whether the meeting changed
+pub(crate) async fn save_meet_link(
+    supabase: &crate::supabase::SupabaseClient,
+    meeting: &MeetingRow,
+    meet_link: Option<&str>,
+) -> Result<bool> {
+    let Some(meet_link) = meet_link.filter(|_| meet_request(meeting).is_some()) else {
+        return Ok(false);
+    };
+    let update = MeetingLocationUpdate {
+        meeting_url: Some(Some(meet_link.to_string())),
+        ..Default::default()
+    };
+    supabase.set_meeting_location(&meeting.id, &update).await?;
+    Ok(true)
+}
+
+/// Calendar a user's meetings are synced to
+pub(crate) fn sync_calendar_id(tokens: &GoogleTokensRow) -> &str {
+    tokens
+        .calendar_id
+        .as_deref()
+        .filter(|id| !id.is_empty())
+        .unwrap_or(PRIMARY_CALENDAR)
+}
+
+/// Calendars imports and free/busy checks read from: the sync calendar,
+/// then the selected ones
+pub(crate) fn read_calendar_ids(tokens: &GoogleTokensRow) -> Vec<&str> {
+    let mut ids = vec![sync_calendar_id(tokens)];
+    for id in &tokens.selected_calendar_ids {
+        if !ids.contains(&id.as_str()) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// Events endpoint of a calendar
+pub(crate) fn events_url(calendar_id: &str) -> String {
+    format!(
+        "https://www.googleapis.com/calendar/v3/calendars/{}/events",
+        urlencoding::encode(calendar_id)
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarListEntry {
+    id: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    primary: bool,
+    #[serde(rename = "accessRole")]
+    access_role: String,
+    #[serde(rename = "backgroundColor")]
+    background_color: Option<String>,
+}
+
+/// Calendars of the user's Google account
+async fn fetch_calendar_list(access_token: &str) -> Result<Vec<CalendarListEntry>> {
+    #[derive(Deserialize)]
+    struct CalendarListPage {
+        #[serde(default)]
+        items: Vec<CalendarListEntry>,
+        #[serde(rename = "nextPageToken")]
+        next_page_token: Option<String>,
+    }
+
+    let client = reqwest::Client::new();
+    let mut calendars = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let mut query = vec![("maxResults", "250".to_string())];
+        if let Some(ref page) = page_token {
+            query.push(("pageToken", page.clone()));
+        }
+        let response = client
+            .get("https://www.googleapis.com/calendar/v3/users/me/calendarList")
+            .bearer_auth(access_token)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to list calendars: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error = response.text().await.unwrap_or_default();
+            return Err(Error::External(format!("Google Calendar error: {}", error)));
+        }
+
+        let page: CalendarListPage = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(format!("Failed to parse calendars: {}", e)))?;
+        calendars.extend(page.items);
+        match page.next_page_token {
+            Some(next) => page_token = Some(next),
+            None => return Ok(calendars),
+        }
+    }
+}
+
+/// Whether a calendar of the list is the one an ID refers to, `primary`
+/// standing for the primary calendar
+fn is_calendar(entry: &CalendarListEntry, calendar_id: &str) -> bool {
+    entry.id == calendar_id || (entry.primary && calendar_id == PRIMARY_CALENDAR)
+}
+
 // Helper to get current user ID
 async fn get_current_user_id(app_state: &AppState) -> Result<String> {
     let inner = app_state.inner.read().await;
@@ -50,60 +247,94 @@ async fn get_current_user_id(app_state: &AppState) -> Result<String> {
 }
 
 // Helper to get supabase client
-fn get_supabase(app_state: &AppState) -> Result<&crate::supabase::SupabaseClient> {
+pub(crate) fn get_supabase(app_state: &AppState) -> Result<&crate::supabase::SupabaseClient> {
     app_state
         .supabase
         .as_ref()
         .ok_or_else(|| Error::Config("Supabase not configured".to_string()))
 }
 
-/// Get OAuth URL to start Google Calendar authorization
-#[tauri::command]
-pub async fn start_google_auth(
-    app_state: State<'_, AppState>,
-) -> Result<String> {
-    let user_id = get_current_user_id(&app_state).await?;
-
-    // Google OAuth URL
-    let client_id = std::env::var("GOOGLE_CLIENT_ID")
-        .map_err(|_| Error::Config("GOOGLE_CLIENT_ID not configured".to_string()))?;
+/// Key sealing the stored Google tokens, loaded from the keychain or
+/// created there on first use
+fn token_key() -> Result<[u8; 32]> {
+    let mut cached = TOKEN_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(key) = *cached {
+        return Ok(key);
+    }
+    let stored = match secure_storage::get_credential(CredentialKey::OAuthTokenKey) {
+        Some(stored) => stored,
+        None => {
+            let stored = token_crypto::generate_key();
+            secure_storage::store_credential(CredentialKey::OAuthTokenKey, &stored)?;
+            tracing::info!("Generated Google token key");
+            stored
+        }
+    };
+    let key = token_crypto::decode_key(&stored)?;
+    *cached = Some(key);
+    Ok(key)
+}
 
-    let redirect_uri = "http://localhost:3000/auth/google/callback";
-    let scope = "https://www.googleapis.com/auth/calendar https://www.googleapis.com/auth/userinfo.email";
+/// Drop the token key from memory and the keychain, e.g. on a remote wipe
+pub(crate) fn delete_token_key() -> Result<()> {
+    let mut cached = TOKEN_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    *cached = None;
+    secure_storage::delete_credential(CredentialKey::OAuthTokenKey)
+}
 
-    let auth_url = format!(
-        "https://accounts.google.com/o/oauth2/v2/auth?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent&state={}",
-        urlencoding::encode(&client_id),
-        urlencoding::encode(redirect_uri),
-        urlencoding::encode(scope),
-        user_id
-    );
+/// Seal and save a user's tokens, escrowing the key first so the sync
+/// edge function can always open what it reads
+async fn save_tokens(
+    supabase: &crate::supabase::SupabaseClient,
+    user_id: &str,
+    access_token: &str,
+    refresh_token: &str,
+    expires_at: &str,
+    email: Option<&str>,
+) -> Result<()> {
+    let key = token_key()?;
+    supabase
+        .set_google_token_key(&token_crypto::encode_key(&key))
+        .await?;
+    supabase
+        .save_google_tokens(
+            user_id,
+            &token_crypto::seal(&key, user_id, access_token)?,
+            &token_crypto::seal(&key, user_id, refresh_token)?,
+            expires_at,
+            email,
+        )
+        .await
+}
 
-    Ok(auth_url)
+/// Open a stored token of a user. Tokens are sealed with a key of the
+/// device that connected the account, so another device has to reconnect
+fn open_token(user_id: &str, value: &str) -> Result<String> {
+    token_crypto::open(&token_key()?, user_id, value).map_err(|_| {
+        Error::Auth(
+            "Google Calendar was connected on another device - please reconnect".to_string(),
+        )
+    })
 }
 
-/// Complete OAuth flow with authorization code
-#[tauri::command]
-pub async fn complete_google_auth(
-    code: String,
-    app_state: State<'_, AppState>,
+/// Exchange an authorization code for tokens and keep them for the user
+async fn connect_google(
+    supabase: &crate::supabase::SupabaseClient,
+    user_id: &str,
+    code: &str,
+    redirect_uri: &str,
 ) -> Result<()> {
-    let user_id = get_current_user_id(&app_state).await?;
-    let supabase = get_supabase(&app_state)?;
-
     let client_id = std::env::var("GOOGLE_CLIENT_ID")
         .map_err(|_| Error::Config("GOOGLE_CLIENT_ID not configured".to_string()))?;
     let client_secret = std::env::var("GOOGLE_CLIENT_SECRET")
         .map_err(|_| Error::Config("GOOGLE_CLIENT_SECRET not configured".to_string()))?;
 
-    let redirect_uri = "http://localhost:3000/auth/google/callback";
-
     // Exchange code for tokens
     let client = reqwest::Client::new();
     let token_response = client
         .post("https://oauth2.googleapis.com/token")
         .form(&[
-            ("code", code.as_str()),
+            ("code", code),
             ("client_id", client_id.as_str()),
             ("client_secret", client_secret.as_str()),
             ("redirect_uri", redirect_uri),
@@ -125,11 +356,16 @@ pub async fn complete_google_auth(
         expires_in: i64,
     }
 
-    let tokens: TokenResponse = token_response.json().await
+    let tokens: TokenResponse = token_response
+        .json()
+        .await
         .map_err(|e| Error::Parse(format!("Failed to parse token response: {}", e)))?;
 
-    let refresh_token = tokens.refresh_token
-        .ok_or_else(|| Error::External("No refresh token received - try revoking access and re-authorizing".to_string()))?;
+    let refresh_token = tokens.refresh_token.ok_or_else(|| {
+        Error::External(
+            "No refresh token received - try revoking access and re-authorizing".to_string(),
+        )
+    })?;
 
     // Get user email from Google
     let email = get_google_email(&tokens.access_token).await.ok();
@@ -137,20 +373,119 @@ pub async fn complete_google_auth(
     // Calculate expiration time
     let expires_at = chrono::Utc::now() + chrono::Duration::seconds(tokens.expires_in);
 
-    // Save tokens to database
-    supabase.save_google_tokens(
-        &user_id,
+    // Save tokens to database, sealed
+    save_tokens(
+        supabase,
+        user_id,
         &tokens.access_token,
         &refresh_token,
         &expires_at.to_rfc3339(),
         email.as_deref(),
-    ).await?;
+    )
+    .await?;
 
+    tracing::info!("Google Calendar connected for user {}", user_id);
     Ok(())
 }
 
+/// Wait for the browser to be redirected back, then connect the account
+async fn finish_google_auth(
+    app_handle: &AppHandle,
+    user_id: &str,
+    redirect: LoopbackRedirect,
+) -> Result<()> {
+    let redirect_uri = redirect.redirect_uri.clone();
+    let code = redirect.wait_for_code().await?;
+    let app_state = app_handle.state::<AppState>();
+    let supabase = get_supabase(&app_state)?;
+    connect_google(supabase, user_id, &code, &redirect_uri).await
+}
+
+fn emit_auth_outcome(app_handle: &AppHandle, outcome: &GoogleAuthOutcome) {
+    if let Err(e) = app_handle.emit("calendar:google-auth", outcome) {
+        tracing::debug!("Failed to emit Google authorization outcome: {}", e);
+    }
+}
+
+/// Get OAuth URL to start Google Calendar authorization
+///
+/// The browser is redirected back to a loopback listener, which completes
+/// the authorization on its own and emits `calendar:google-auth` with
+/// `attempt_id`. Starting again drops an authorization still waiting, which
+/// is reported as failed.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_google_auth(
+    attempt_id: String,
+    app_handle: AppHandle,
+    app_state: State<'_, AppState>,
+    sync_state: State<'_, GoogleSyncState>,
+) -> Result<String> {
+    let user_id = get_current_user_id(&app_state).await?;
+
+    // Google OAuth URL
+    let client_id = std::env::var("GOOGLE_CLIENT_ID")
+        .map_err(|_| Error::Config("GOOGLE_CLIENT_ID not configured".to_string()))?;
+
+    let redirect = LoopbackRedirect::bind(CALLBACK_PATH).await?;
+    let scope = "https://www.googleapis.com/auth/calendar https://www.googleapis.com/auth/userinfo.email";
+
+    let auth_url = format!(
+        "https://accounts.google.com/o/oauth2/v2/auth?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent&state={}",
+        urlencoding::encode(&client_id),
+        urlencoding::encode(&redirect.redirect_uri),
+        urlencoding::encode(scope),
+        redirect.state
+    );
+
+    let handle = app_handle.clone();
+    let task_attempt_id = attempt_id.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let outcome = finish_google_auth(&handle, &user_id, redirect).await;
+        if let Err(ref e) = outcome {
+            tracing::warn!("Google authorization failed: {}", e);
+        }
+        emit_auth_outcome(
+            &handle,
+            &GoogleAuthOutcome {
+                attempt_id: task_attempt_id,
+                connected: outcome.is_ok(),
+                error: outcome.err().map(|e| e.to_string()),
+            },
+        );
+    });
+    if let Some(previous) = sync_state.replace_pending_auth(PendingAuth { attempt_id, task }) {
+        previous.task.abort();
+        emit_auth_outcome(
+            &app_handle,
+            &GoogleAuthOutcome {
+                attempt_id: previous.attempt_id,
+                connected: false,
+                error: Some("Authorization was replaced by a newer one".to_string()),
+            },
+        );
+    }
+
+    Ok(auth_url)
+}
+
+/// Complete OAuth flow with an authorization code pasted by the user, for
+/// when the browser can't reach the loopback listener
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn complete_google_auth(
+    code: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    connect_google(supabase, &user_id, code.trim(), MANUAL_REDIRECT_URI).await
+}
+
 /// Disconnect Google Calendar
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn disconnect_google(
     app_state: State<'_, AppState>,
 ) -> Result<()> {
@@ -158,16 +493,22 @@ pub async fn disconnect_google(
     let supabase = get_supabase(&app_state)?;
 
     // Revoke tokens with Google
-    if let Ok(Some(tokens)) = supabase.get_google_tokens(&user_id).await {
+    // (unless they were sealed on another device)
+    let access_token = match supabase.get_google_tokens(&user_id).await {
+        Ok(Some(tokens)) => open_token(&user_id, &tokens.access_token).ok(),
+        _ => None,
+    };
+    if let Some(access_token) = access_token {
         let client = reqwest::Client::new();
         let _ = client
             .post("https://oauth2.googleapis.com/revoke")
-            .form(&[("token", tokens.access_token.as_str())])
+            .form(&[("token", access_token.as_str())])
             .send()
             .await;
     }
 
-    // Delete from database
+    // Delete from database, with the background sync's links
+    supabase.delete_google_event_links(&user_id, None).await?;
     supabase.delete_google_tokens(&user_id).await?;
 
     Ok(())
@@ -175,6 +516,7 @@ pub async fn disconnect_google(
 
 /// Get Google Calendar connection status
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_google_status(
     app_state: State<'_, AppState>,
 ) -> Result<GoogleCalendarStatus> {
@@ -185,20 +527,25 @@ pub async fn get_google_status(
         Ok(Some(tokens)) => {
             Ok(GoogleCalendarStatus {
                 connected: true,
+                calendar_id: Some(sync_calendar_id(&tokens).to_string()),
                 email: tokens.email,
                 sync_enabled: tokens.sync_enabled.unwrap_or(true),
+                selected_calendar_ids: tokens.selected_calendar_ids,
             })
         }
         _ => Ok(GoogleCalendarStatus {
             connected: false,
             email: None,
             sync_enabled: false,
+            calendar_id: None,
+            selected_calendar_ids: Vec::new(),
         }),
     }
 }
 
 /// Sync a meeting to Google Calendar
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn sync_meeting_to_google(
     meeting_id: String,
     app_state: State<'_, AppState>,
@@ -224,10 +571,14 @@ pub async fn sync_meeting_to_google(
         .map_err(|e| Error::Parse(format!("Invalid date: {}", e)))?;
 
     let end_time = scheduled_at + chrono::Duration::minutes(meeting.duration_minutes as i64);
+    let time_zone = meeting
+        .time_zone
+        .clone()
+        .unwrap_or_else(|| EVENT_TIME_ZONE.to_string());
 
     // Build event body - attendees will be notified through SquadX Live system
     // Note: Google Calendar attendees require email, which we don't always have
-    let event_body = serde_json::json!({
+    let mut event_body = serde_json::json!({
         "summary": meeting.title,
         "description": format!(
             "{}\n\nParticipantes: {}",
@@ -236,21 +587,38 @@ pub async fn sync_meeting_to_google(
         ),
         "start": {
             "dateTime": scheduled_at.to_rfc3339(),
-            "timeZone": "America/Sao_Paulo"
+            "timeZone": time_zone
         },
         "end": {
             "dateTime": end_time.to_rfc3339(),
-            "timeZone": "America/Sao_Paulo"
-        }
+            "timeZone": time_zone
+        },
+        "location": event_location(&meeting),
+        "colorId": meeting.color.as_deref().and_then(colors::google_color_id)
     });
+    let meet_request = meet_request(&meeting);
+    if let Some(ref conference) = meet_request {
+        event_body["conferenceData"] = conference.clone();
+    }
+    let conference_version = if meet_request.is_some() { "1" } else { "0" };
+
+    #[derive(Deserialize)]
+    struct EventResponse {
+        id: String,
+        #[serde(rename = "hangoutLink")]
+        hangout_link: Option<String>,
+    }
 
     let client = reqwest::Client::new();
+    let calendar_id = sync_calendar_id(&tokens);
 
     // Check if event already exists
-    let google_event_id = if let Some(existing_id) = meeting.google_event_id {
-        // Update existing event
+    let event: EventResponse = if let Some(ref existing_id) = meeting.google_event_id {
+        // Update existing event, in the calendar it was created in
+        let event_calendar_id = meeting.google_calendar_id.as_deref().unwrap_or(calendar_id);
         let response = client
-            .put(format!("https://www.googleapis.com/calendar/v3/calendars/primary/events/{}", existing_id))
+            .put(format!("{}/{}", events_url(event_calendar_id), existing_id))
+            .query(&[("conferenceDataVersion", conference_version)])
             .bearer_auth(&access_token)
             .json(&event_body)
             .send()
@@ -262,11 +630,15 @@ pub async fn sync_meeting_to_google(
             return Err(Error::External(format!("Google Calendar error: {}", error)));
         }
 
-        existing_id
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(format!("Failed to parse response: {}", e)))?
     } else {
         // Create new event
         let response = client
-            .post("https://www.googleapis.com/calendar/v3/calendars/primary/events")
+            .post(events_url(calendar_id))
+            .query(&[("conferenceDataVersion", conference_version)])
             .bearer_auth(&access_token)
             .json(&event_body)
             .send()
@@ -278,25 +650,29 @@ pub async fn sync_meeting_to_google(
             return Err(Error::External(format!("Google Calendar error: {}", error)));
         }
 
-        #[derive(Deserialize)]
-        struct EventResponse {
-            id: String,
-        }
-
         let event: EventResponse = response.json().await
             .map_err(|e| Error::Parse(format!("Failed to parse response: {}", e)))?;
 
         // Save google_event_id to meeting
-        supabase.update_meeting_google_id(&meeting_id, &event.id, "primary").await?;
+        supabase
+            .update_meeting_google_id(&meeting_id, &event.id, calendar_id)
+            .await?;
 
-        event.id
+        event
     };
 
-    Ok(google_event_id)
+    // Attendees see the Meet link with the meeting
+    if save_meet_link(supabase, &meeting, event.hangout_link.as_deref()).await? {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting_id);
+    }
+
+    Ok(event.id)
 }
 
-/// Import events from Google Calendar
+/// Import events from the user's sync calendar and selected calendars
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn import_from_google(
     start_date: String,
     end_date: String,
@@ -312,39 +688,64 @@ pub async fn import_from_google(
     // Refresh token if needed
     let access_token = refresh_token_if_needed(supabase, &user_id, &tokens).await?;
 
-    // Fetch events from Google Calendar
-    let client = reqwest::Client::new();
-    let response = client
-        .get("https://www.googleapis.com/calendar/v3/calendars/primary/events")
-        .bearer_auth(&access_token)
-        .query(&[
-            ("timeMin", start_date.as_str()),
-            ("timeMax", end_date.as_str()),
-            ("singleEvents", "true"),
-            ("orderBy", "startTime"),
-        ])
-        .send()
-        .await
-        .map_err(|e| Error::Network(format!("Failed to fetch events: {}", e)))?;
-
-    if !response.status().is_success() {
-        let error = response.text().await.unwrap_or_default();
-        return Err(Error::External(format!("Google Calendar error: {}", error)));
-    }
-
     #[derive(Deserialize)]
     struct EventsResponse {
         items: Option<Vec<GoogleEvent>>,
     }
 
-    let events: EventsResponse = response.json().await
-        .map_err(|e| Error::Parse(format!("Failed to parse events: {}", e)))?;
+    // Fetch events from each Google calendar
+    let client = reqwest::Client::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut imported = Vec::new();
+    for calendar_id in read_calendar_ids(&tokens) {
+        let response = client
+            .get(events_url(calendar_id))
+            .bearer_auth(&access_token)
+            .query(&[
+                ("timeMin", start_date.as_str()),
+                ("timeMax", end_date.as_str()),
+                ("singleEvents", "true"),
+                ("orderBy", "startTime"),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to fetch events: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error = response.text().await.unwrap_or_default();
+            return Err(Error::External(format!("Google Calendar error: {}", error)));
+        }
 
-    Ok(events.items.unwrap_or_default())
+        let events: EventsResponse = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(format!("Failed to parse events: {}", e)))?;
+
+        // An event shared by two of the calendars is imported once
+        imported.extend(
+            events
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|event| seen.insert(event.id.clone()))
+                .map(|event| GoogleEvent {
+                    calendar_id: Some(calendar_id.to_string()),
+                    ..event
+                }),
+        );
+    }
+
+    imported.sort_by(|a, b| {
+        let start =
+            |event: &GoogleEvent| event.start.date_time.clone().or(event.start.date.clone());
+        start(a).cmp(&start(b))
+    });
+    Ok(imported)
 }
 
 /// Toggle sync with Google Calendar
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn toggle_google_sync(
     enabled: bool,
     app_state: State<'_, AppState>,
@@ -358,6 +759,104 @@ pub async fn toggle_google_sync(
     Ok(())
 }
 
+/// List the calendars of the user's Google account, marking the one
+/// meetings are synced to and the ones imports read from
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_google_calendars(
+    app_state: State<'_, AppState>,
+) -> Result<Vec<GoogleCalendarInfo>> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    let tokens = supabase
+        .get_google_tokens(&user_id)
+        .await?
+        .ok_or_else(|| Error::External("Google Calendar not connected".to_string()))?;
+    let access_token = refresh_token_if_needed(supabase, &user_id, &tokens).await?;
+
+    let sync_calendar = sync_calendar_id(&tokens);
+    let calendars = fetch_calendar_list(&access_token).await?;
+    Ok(calendars
+        .into_iter()
+        .map(|entry| GoogleCalendarInfo {
+            sync_target: is_calendar(&entry, sync_calendar),
+            selected: tokens
+                .selected_calendar_ids
+                .iter()
+                .any(|id| is_calendar(&entry, id)),
+            id: entry.id,
+            summary: entry.summary,
+            primary: entry.primary,
+            access_role: entry.access_role,
+            background_color: entry.background_color,
+        })
+        .collect())
+}
+
+/// Choose the calendar meetings are synced to, which the user must be able
+/// to write to, and the calendars imports and free/busy checks also read
+/// from. A new sync calendar starts the background sync over; events
+/// already in the previous one stay there
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_google_calendars(
+    calendar_id: String,
+    selected_calendar_ids: Vec<String>,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    let tokens = supabase
+        .get_google_tokens(&user_id)
+        .await?
+        .ok_or_else(|| Error::External("Google Calendar not connected".to_string()))?;
+    let access_token = refresh_token_if_needed(supabase, &user_id, &tokens).await?;
+    let calendars = fetch_calendar_list(&access_token).await?;
+
+    let sync_calendar = calendars
+        .iter()
+        .find(|entry| is_calendar(entry, &calendar_id))
+        .ok_or_else(|| Error::NotFound(format!("Google calendar {}", calendar_id)))?;
+    if !matches!(sync_calendar.access_role.as_str(), "owner" | "writer") {
+        return Err(Error::Parse(format!(
+            "Meetings can't be synced to the read-only calendar {}",
+            sync_calendar.summary
+        )));
+    }
+    let mut selected: Vec<String> = Vec::new();
+    for id in &selected_calendar_ids {
+        let entry = calendars
+            .iter()
+            .find(|entry| is_calendar(entry, id))
+            .ok_or_else(|| Error::NotFound(format!("Google calendar {}", id)))?;
+        if entry.id != sync_calendar.id && !selected.contains(&entry.id) {
+            selected.push(entry.id.clone());
+        }
+    }
+
+    let current = calendars
+        .iter()
+        .find(|entry| is_calendar(entry, sync_calendar_id(&tokens)))
+        .map(|entry| entry.id.as_str());
+    let restart_sync = current != Some(sync_calendar.id.as_str());
+    if restart_sync {
+        supabase.delete_google_event_links(&user_id, None).await?;
+    }
+    supabase
+        .update_google_calendars(&user_id, &sync_calendar.id, &selected, restart_sync)
+        .await?;
+
+    tracing::info!(
+        "Google calendars of user {}: syncing to {}, reading {} more",
+        user_id,
+        sync_calendar.id,
+        selected.len()
+    );
+    Ok(())
+}
+
 // Helper function to get user email from Google
 async fn get_google_email(access_token: &str) -> Result<String> {
     let client = reqwest::Client::new();
@@ -379,12 +878,16 @@ async fn get_google_email(access_token: &str) -> Result<String> {
     Ok(user_info.email)
 }
 
-// Helper function to refresh token if expired
-async fn refresh_token_if_needed(
+// Helper function to refresh token if expired. Stored tokens are opened
+// here, and tokens saved before they were sealed get sealed
+pub(crate) async fn refresh_token_if_needed(
     supabase: &crate::supabase::SupabaseClient,
     user_id: &str,
     tokens: &crate::supabase::GoogleTokensRow,
 ) -> Result<String> {
+    let access_token = open_token(user_id, &tokens.access_token)?;
+    let refresh_token = open_token(user_id, &tokens.refresh_token)?;
+
     let expires_at = chrono::DateTime::parse_from_rfc3339(&tokens.expires_at)
         .map_err(|e| Error::Parse(format!("Invalid expiration: {}", e)))?;
 
@@ -399,7 +902,7 @@ async fn refresh_token_if_needed(
         let response = client
             .post("https://oauth2.googleapis.com/token")
             .form(&[
-                ("refresh_token", tokens.refresh_token.as_str()),
+                ("refresh_token", refresh_token.as_str()),
                 ("client_id", client_id.as_str()),
                 ("client_secret", client_secret.as_str()),
                 ("grant_type", "refresh_token"),
@@ -424,16 +927,94 @@ async fn refresh_token_if_needed(
         let new_expires_at = chrono::Utc::now() + chrono::Duration::seconds(refresh.expires_in);
 
         // Update tokens in database
-        supabase.save_google_tokens(
+        save_tokens(
+            supabase,
             user_id,
             &refresh.access_token,
-            &tokens.refresh_token,
+            &refresh_token,
             &new_expires_at.to_rfc3339(),
             tokens.email.as_deref(),
-        ).await?;
+        )
+        .await?;
 
         Ok(refresh.access_token)
     } else {
-        Ok(tokens.access_token.clone())
+        if !token_crypto::is_sealed(&tokens.access_token)
+            || !token_crypto::is_sealed(&tokens.refresh_token)
+        {
+            save_tokens(
+                supabase,
+                user_id,
+                &access_token,
+                &refresh_token,
+                &tokens.expires_at,
+                tokens.email.as_deref(),
+            )
+            .await?;
+        }
+        Ok(access_token)
     }
 }
+
+/// Busy times of the user's Google calendars, as (start, end) pairs;
+/// empty when Google Calendar isn't connected
+pub(crate) async fn get_google_busy_times(
+    supabase: &crate::supabase::SupabaseClient,
+    user_id: &str,
+    time_min: &str,
+    time_max: &str,
+) -> Result<Vec<(String, String)>> {
+    let Some(tokens) = supabase.get_google_tokens(user_id).await? else {
+        return Ok(Vec::new());
+    };
+    let access_token = refresh_token_if_needed(supabase, user_id, &tokens).await?;
+
+    let response = reqwest::Client::new()
+        .post("https://www.googleapis.com/calendar/v3/freeBusy")
+        .bearer_auth(&access_token)
+        .json(&serde_json::json!({
+            "timeMin": time_min,
+            "timeMax": time_max,
+            "items": read_calendar_ids(&tokens)
+                .into_iter()
+                .map(|id| serde_json::json!({ "id": id }))
+                .collect::<Vec<_>>(),
+        }))
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to get free/busy: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(Error::External(format!("Google Calendar error: {}", error)));
+    }
+
+    #[derive(Deserialize)]
+    struct FreeBusyResponse {
+        calendars: std::collections::HashMap<String, FreeBusyCalendar>,
+    }
+
+    #[derive(Deserialize)]
+    struct FreeBusyCalendar {
+        #[serde(default)]
+        busy: Vec<BusyPeriod>,
+    }
+
+    #[derive(Deserialize)]
+    struct BusyPeriod {
+        start: String,
+        end: String,
+    }
+
+    let free_busy: FreeBusyResponse = response
+        .json()
+        .await
+        .map_err(|e| Error::Parse(format!("Failed to parse free/busy: {}", e)))?;
+
+    Ok(free_busy
+        .calendars
+        .into_values()
+        .flat_map(|calendar| calendar.busy)
+        .map(|period| (period.start, period.end))
+        .collect())
+}
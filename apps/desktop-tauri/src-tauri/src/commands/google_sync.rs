@@ -0,0 +1,853 @@
+//! Background two-way Google Calendar sync
+//!
+//! Every few minutes, the meetings the user organizes that changed since the
+//! last pass are copied to their primary Google calendar, and events changed
+//! there are copied back, as planned by [`calendar_sync::plan`]. Meeting
+//! changes come from the change feed and event changes from Google's
+//! incremental sync, so a pass only fetches what changed. Only organizers
+//! push meetings, so a shared meeting isn't duplicated in the calendar of
+//! every connected attendee.
+//!
+//! When an action fails, the pass doesn't move past the changes; the next
+//! pass retries them and the links skip what already went through. Status is
+//! emitted as `calendar:google-sync` whenever it changes.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::calendar_sync::{self, ConflictPolicy, LocalChange, RemoteChange, SyncAction, SyncLink};
+use crate::change_feed::{self, ChangeCursor};
+use crate::commands::calendar::extract_year_month;
+use crate::commands::google_calendar::{
+    event_location, events_url, get_supabase, meet_request, refresh_token_if_needed,
+    save_meet_link, sync_calendar_id, GoogleEventDateTime, EVENT_TIME_ZONE,
+};
+use crate::state::AppState;
+use crate::supabase::{GoogleEventLinkRow, MeetingLocationUpdate, MeetingRow, SupabaseClient};
+use crate::utils::rrule::{self, RecurrenceExceptions};
+use crate::utils::{colors, datetime, text};
+use crate::{Error, Result};
+
+/// How often the background sync runs
+const SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Change feed rows fetched per request
+const FEED_PAGE_SIZE: u32 = 200;
+
+/// Events fetched per request
+const EVENTS_PAGE_SIZE: u32 = 250;
+
+/// Private extended property holding the meeting an event was created for
+const MEETING_ID_PROPERTY: &str = "squadxMeetingId";
+
+/// Title of events without a summary
+const UNTITLED_MEETING: &str = "Untitled meeting";
+
+/// What a sync pass changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoogleSyncResult {
+    pub created_remote: usize,
+    pub updated_remote: usize,
+    pub deleted_remote: usize,
+    pub created_local: usize,
+    pub updated_local: usize,
+    pub cancelled_local: usize,
+    /// Meetings changed on both sides, resolved by the conflict policy
+    pub conflicts: usize,
+    /// Actions that failed and will be retried
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoogleSyncStatus {
+    pub running: bool,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+    pub last_result: Option<GoogleSyncResult>,
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// Status of the background sync, and a lock so passes don't overlap
+#[derive(Default)]
+pub struct GoogleSyncState {
+    status: RwLock<GoogleSyncStatus>,
+    pass: tokio::sync::Mutex<()>,
+    /// Authorization waiting for the browser's redirect
+    pending_auth: std::sync::Mutex<Option<PendingAuth>>,
+}
+
+/// An authorization waiting for the browser's redirect
+pub(crate) struct PendingAuth {
+    /// ID the frontend gave the attempt, echoed in its outcome
+    pub attempt_id: String,
+    pub task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl GoogleSyncState {
+    /// Track a new authorization, handing back the one it replaces
+    pub(crate) fn replace_pending_auth(&self, pending: PendingAuth) -> Option<PendingAuth> {
+        let mut pending_auth = self.pending_auth.lock().unwrap_or_else(|e| e.into_inner());
+        pending_auth.replace(pending)
+    }
+}
+
+/// Event as returned by Google's incremental sync; deleted events only carry
+/// their ID and status
+#[derive(Debug, Clone, Deserialize)]
+struct SyncedEvent {
+    id: String,
+    #[serde(default)]
+    etag: String,
+    updated: Option<String>,
+    status: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    start: Option<GoogleEventDateTime>,
+    end: Option<GoogleEventDateTime>,
+    recurrence: Option<Vec<String>>,
+    location: Option<String>,
+    #[serde(rename = "colorId")]
+    color_id: Option<String>,
+    #[serde(rename = "hangoutLink")]
+    hangout_link: Option<String>,
+    #[serde(rename = "conferenceData")]
+    conference_data: Option<ConferenceData>,
+    #[serde(rename = "extendedProperties")]
+    extended_properties: Option<ExtendedProperties>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ConferenceData {
+    #[serde(rename = "entryPoints", default)]
+    entry_points: Vec<ConferenceEntryPoint>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ConferenceEntryPoint {
+    #[serde(rename = "entryPointType")]
+    entry_point_type: String,
+    uri: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ExtendedProperties {
+    #[serde(default)]
+    private: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsPage {
+    #[serde(default)]
+    items: Vec<SyncedEvent>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+    #[serde(rename = "nextSyncToken")]
+    next_sync_token: Option<String>,
+}
+
+/// Access to Google and the database for one pass
+struct SyncContext<'a> {
+    supabase: &'a SupabaseClient,
+    client: reqwest::Client,
+    access_token: String,
+    user_id: String,
+    /// Calendar meetings are synced to
+    calendar_id: String,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn parse_time(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|v| v.with_timezone(&Utc))
+}
+
+impl SyncedEvent {
+    fn cancelled(&self) -> bool {
+        self.status.as_deref() == Some("cancelled")
+    }
+
+    fn meeting_id(&self) -> Option<&str> {
+        self.extended_properties
+            .as_ref()?
+            .private
+            .get(MEETING_ID_PROPERTY)
+            .map(String::as_str)
+    }
+
+    fn to_change(&self) -> RemoteChange {
+        RemoteChange {
+            event_id: self.id.clone(),
+            etag: self.etag.clone(),
+            updated: parse_time(self.updated.as_deref()).unwrap_or_else(Utc::now),
+            cancelled: self.cancelled(),
+            meeting_id: self.meeting_id().map(str::to_string),
+        }
+    }
+
+    fn span(&self) -> Result<(DateTime<Utc>, i32)> {
+        let (start, end) = self
+            .start
+            .as_ref()
+            .zip(self.end.as_ref())
+            .ok_or_else(|| Error::Parse(format!("Event {} has no start or end", self.id)))?;
+        calendar_sync::event_span(
+            start.date_time.as_deref(),
+            start.date.as_deref(),
+            end.date_time.as_deref(),
+            end.date.as_deref(),
+        )
+        .ok_or_else(|| Error::Parse(format!("Event {} has an invalid time", self.id)))
+    }
+
+    /// The event's RRULE when the app can repeat it
+    fn recurrence_rule(&self) -> Option<String> {
+        self.recurrence
+            .iter()
+            .flatten()
+            .find_map(|line| line.strip_prefix("RRULE:"))
+            .filter(|rule| rrule::validate_rrule(rule).is_ok())
+            .map(str::to_string)
+    }
+
+    /// Time zone the event repeats in, when Google knows it
+    fn time_zone(&self) -> Option<&str> {
+        self.start
+            .as_ref()
+            .and_then(|start| start.time_zone.as_deref())
+            .filter(|name| datetime::parse_time_zone(name).is_ok())
+    }
+
+    /// Video call link: the event's conference, or a link as its location
+    fn meeting_url(&self) -> Option<&str> {
+        self.hangout_link
+            .as_deref()
+            .or_else(|| {
+                self.conference_data
+                    .iter()
+                    .flat_map(|data| &data.entry_points)
+                    .find(|entry| entry.entry_point_type == "video")
+                    .map(|entry| entry.uri.as_str())
+            })
+            .or(self.location.as_deref())
+            .filter(|url| text::is_web_url(url))
+    }
+
+    /// Where the event is held, unless its location is only a link
+    fn location(&self) -> Option<&str> {
+        self.location
+            .as_deref()
+            .filter(|location| !location.trim().is_empty() && !text::is_web_url(location))
+    }
+
+    /// Location fields Google knows about; the others are left as they are
+    fn location_update(&self) -> MeetingLocationUpdate {
+        MeetingLocationUpdate {
+            location: self.location().map(|location| Some(location.to_string())),
+            meeting_url: self.meeting_url().map(|url| Some(url.trim().to_string())),
+            room: None,
+        }
+    }
+
+    fn title(&self) -> &str {
+        self.summary
+            .as_deref()
+            .filter(|summary| !summary.is_empty())
+            .unwrap_or(UNTITLED_MEETING)
+    }
+}
+
+fn event_body(meeting: &MeetingRow) -> Result<serde_json::Value> {
+    let start = DateTime::parse_from_rfc3339(&meeting.scheduled_at)
+        .map_err(|e| Error::Parse(format!("Invalid date: {}", e)))?;
+    let end = start + chrono::Duration::minutes(meeting.duration_minutes as i64);
+    let time_zone = meeting.time_zone.as_deref().unwrap_or(EVENT_TIME_ZONE);
+    let mut recurrence: Vec<String> = Vec::new();
+    if let Some(ref rule) = meeting.recurrence_rule {
+        recurrence.push(format!("RRULE:{}", rule.trim_start_matches("RRULE:")));
+        let exceptions = rrule::build_exception_lines(&RecurrenceExceptions {
+            exdates: meeting.recurrence_exdates.clone(),
+            rdates: meeting.recurrence_rdates.clone(),
+        })?;
+        recurrence.extend(exceptions.lines().map(str::to_string));
+    }
+
+    let mut body = serde_json::json!({
+        "summary": meeting.title,
+        "description": meeting.description,
+        "start": { "dateTime": start.to_rfc3339(), "timeZone": time_zone },
+        "end": { "dateTime": end.to_rfc3339(), "timeZone": time_zone },
+        "recurrence": recurrence,
+        "location": event_location(meeting),
+        "colorId": meeting.color.as_deref().and_then(colors::google_color_id),
+        "extendedProperties": { "private": { MEETING_ID_PROPERTY: meeting.id } },
+    });
+    if let Some(conference) = meet_request(meeting) {
+        body["conferenceData"] = conference;
+    }
+    Ok(body)
+}
+
+fn set_status(app_handle: &AppHandle, update: impl FnOnce(&mut GoogleSyncStatus)) {
+    let sync_state = app_handle.state::<GoogleSyncState>();
+    let status = {
+        let mut status = sync_state.status.write().unwrap_or_else(|e| e.into_inner());
+        update(&mut *status);
+        status.clone()
+    };
+    if let Err(e) = app_handle.emit("calendar:google-sync", &status) {
+        tracing::debug!("Failed to emit Google sync status: {}", e);
+    }
+}
+
+impl SyncContext<'_> {
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let error = response.text().await.unwrap_or_default();
+        Err(Error::External(format!("Google Calendar error: {}", error)))
+    }
+
+    /// Events changed since a sync token, or upcoming events without one,
+    /// and the token for the next pass
+    async fn fetch_remote_changes(
+        &self,
+        sync_token: Option<&str>,
+    ) -> Result<(Vec<SyncedEvent>, Option<String>)> {
+        let mut sync_token = sync_token;
+        let mut events = Vec::new();
+        let mut page_token: Option<String> = None;
+        let time_min = Utc::now().to_rfc3339();
+
+        loop {
+            let mut query = vec![("maxResults", EVENTS_PAGE_SIZE.to_string())];
+            match sync_token {
+                Some(token) => query.push(("syncToken", token.to_string())),
+                None => query.push(("timeMin", time_min.clone())),
+            }
+            if let Some(ref page) = page_token {
+                query.push(("pageToken", page.clone()));
+            }
+
+            let response = self
+                .client
+                .get(events_url(&self.calendar_id))
+                .bearer_auth(&self.access_token)
+                .query(&query)
+                .send()
+                .await
+                .map_err(|e| Error::Network(format!("Failed to fetch events: {}", e)))?;
+
+            // Google expires sync tokens; start over from upcoming events
+            if response.status() == reqwest::StatusCode::GONE && sync_token.is_some() {
+                tracing::info!("Google sync token expired, resyncing upcoming events");
+                sync_token = None;
+                page_token = None;
+                events.clear();
+                continue;
+            }
+
+            let page: EventsPage = Self::check(response)
+                .await?
+                .json()
+                .await
+                .map_err(|e| Error::Parse(format!("Failed to parse events: {}", e)))?;
+            events.extend(page.items);
+            match page.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => return Ok((events, page.next_sync_token)),
+            }
+        }
+    }
+
+    /// Meetings organized by the user and meeting tombstones after the
+    /// cursor, moving it past them
+    async fn fetch_local_changes(
+        &self,
+        cursor: &mut ChangeCursor,
+        upcoming_only: bool,
+    ) -> Result<(Vec<MeetingRow>, Vec<LocalChange>)> {
+        let mut meetings = Vec::new();
+        let mut changes = Vec::new();
+        let now = Utc::now();
+
+        loop {
+            let rows = self
+                .supabase
+                .get_changed_meetings(cursor.meetings.as_ref(), FEED_PAGE_SIZE)
+                .await?;
+            change_feed::advance(
+                &mut cursor.meetings,
+                rows.iter().map(|r| (&r.updated_at, r.id.as_str())),
+            );
+            let done = rows.len() < FEED_PAGE_SIZE as usize;
+            for row in rows {
+                if row.organizer_id != self.user_id {
+                    continue;
+                }
+                let Some(updated_at) = parse_time(row.updated_at.as_deref()) else {
+                    continue;
+                };
+                if upcoming_only
+                    && row.recurrence_rule.is_none()
+                    && parse_time(Some(row.scheduled_at.as_str())).is_some_and(|start| start < now)
+                {
+                    continue;
+                }
+                changes.push(LocalChange {
+                    meeting_id: row.id.clone(),
+                    event_id: row.google_event_id.clone(),
+                    updated_at,
+                    cancelled: row.status == "cancelled",
+                    deleted: false,
+                });
+                meetings.push(row);
+            }
+            if done {
+                break;
+            }
+        }
+
+        loop {
+            let rows = self
+                .supabase
+                .get_deleted_entities(cursor.deleted.as_ref(), FEED_PAGE_SIZE)
+                .await?;
+            change_feed::advance(
+                &mut cursor.deleted,
+                rows.iter().map(|r| (&r.updated_at, r.id.as_str())),
+            );
+            let done = rows.len() < FEED_PAGE_SIZE as usize;
+            changes.extend(
+                rows.into_iter()
+                    .filter(|row| row.entity_type == "meeting")
+                    .map(|row| LocalChange {
+                        meeting_id: row.entity_id,
+                        event_id: None,
+                        updated_at: parse_time(row.updated_at.as_deref()).unwrap_or(now),
+                        cancelled: false,
+                        deleted: true,
+                    }),
+            );
+            if done {
+                break;
+            }
+        }
+
+        Ok((meetings, changes))
+    }
+
+    async fn save_link(&self, meeting_id: &str, event: &SyncedEvent) -> Result<()> {
+        // Writes bump updated_at, so read it back to recognise them later
+        let meeting_updated_at = self
+            .supabase
+            .get_meeting(meeting_id)
+            .await?
+            .and_then(|meeting| meeting.updated_at);
+        self.supabase
+            .save_google_event_link(&GoogleEventLinkRow {
+                user_id: self.user_id.clone(),
+                meeting_id: meeting_id.to_string(),
+                google_event_id: event.id.clone(),
+                etag: Some(event.etag.clone()),
+                meeting_updated_at,
+            })
+            .await
+    }
+
+    /// Create or update the event of a meeting, returning whether the
+    /// meeting took the Meet link Google created for it
+    async fn push(&self, meeting: &MeetingRow, event_id: Option<&str>) -> Result<bool> {
+        let body = event_body(meeting)?;
+        let request = match event_id {
+            Some(event_id) => {
+                self.client
+                    .patch(format!("{}/{}", events_url(&self.calendar_id), event_id))
+            }
+            None => self.client.post(events_url(&self.calendar_id)),
+        };
+        let response = request
+            .query(&[("conferenceDataVersion", "1")])
+            .bearer_auth(&self.access_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to save event: {}", e)))?;
+        let event: SyncedEvent = Self::check(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| Error::Parse(format!("Failed to parse response: {}", e)))?;
+
+        if meeting.google_event_id.as_deref() != Some(event.id.as_str()) {
+            self.supabase
+                .update_meeting_google_id(&meeting.id, &event.id, &self.calendar_id)
+                .await?;
+        }
+        let meet_linked = save_meet_link(self.supabase, meeting, event.meeting_url()).await?;
+        self.save_link(&meeting.id, &event).await?;
+        Ok(meet_linked)
+    }
+
+    async fn delete_remote(&self, meeting_id: &str, event_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(format!("{}/{}", events_url(&self.calendar_id), event_id))
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to delete event: {}", e)))?;
+        // Already deleted in Google
+        if !matches!(
+            response.status(),
+            reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE
+        ) {
+            Self::check(response).await?;
+        }
+        self.supabase
+            .delete_google_event_links(&self.user_id, Some(meeting_id))
+            .await
+    }
+
+    async fn create_local(&self, event: &SyncedEvent) -> Result<MeetingRow> {
+        let (start, duration_minutes) = event.span()?;
+        let mut row = self
+            .supabase
+            .create_meeting(
+                &self.user_id,
+                event.title(),
+                event.description.as_deref(),
+                &start.to_rfc3339(),
+                duration_minutes,
+                event.recurrence_rule().as_deref(),
+            )
+            .await?;
+        self.supabase
+            .update_meeting_google_id(&row.id, &event.id, &self.calendar_id)
+            .await?;
+        if let Some(time_zone) = event.time_zone() {
+            self.supabase
+                .set_meeting_time_zone(&row.id, Some(time_zone))
+                .await?;
+            row.time_zone = Some(time_zone.to_string());
+        }
+        let location = event.location_update();
+        if !location.is_empty() {
+            self.supabase
+                .set_meeting_location(&row.id, &location)
+                .await?;
+            location.apply(&mut row);
+        }
+        if let Some(color) = event.color_id.as_deref().and_then(colors::google_color) {
+            self.supabase
+                .set_meeting_tag(&row.id, None, Some(color))
+                .await?;
+            row.color = Some(color.to_string());
+        }
+        self.save_link(&row.id, event).await?;
+        Ok(row)
+    }
+
+    async fn update_local(&self, meeting_id: &str, event: &SyncedEvent) -> Result<()> {
+        let (start, duration_minutes) = event.span()?;
+        self.supabase
+            .update_meeting(
+                meeting_id,
+                Some(event.title()),
+                event.description.as_deref(),
+                Some(&start.to_rfc3339()),
+                Some(duration_minutes),
+                None,
+            )
+            .await?;
+        if let Some(time_zone) = event.time_zone() {
+            self.supabase
+                .set_meeting_time_zone(meeting_id, Some(time_zone))
+                .await?;
+        }
+        let location = event.location_update();
+        if !location.is_empty() {
+            self.supabase
+                .set_meeting_location(meeting_id, &location)
+                .await?;
+        }
+        self.save_link(meeting_id, event).await
+    }
+
+    async fn cancel_local(&self, meeting_id: &str) -> Result<()> {
+        self.supabase
+            .update_meeting(meeting_id, None, None, None, None, Some("cancelled"))
+            .await?;
+        self.supabase
+            .delete_google_event_links(&self.user_id, Some(meeting_id))
+            .await
+    }
+}
+
+/// Run one sync pass; `None` when Google Calendar isn't connected or sync
+/// is off
+async fn sync_pass(app_handle: &AppHandle) -> Result<Option<GoogleSyncResult>> {
+    let app_state = app_handle.state::<AppState>();
+    let Some(user_id) = app_state
+        .inner
+        .read()
+        .await
+        .user
+        .as_ref()
+        .map(|user| user.id.clone())
+    else {
+        return Ok(None);
+    };
+    let supabase = get_supabase(&app_state)?;
+
+    let Some(tokens) = supabase.get_google_tokens(&user_id).await? else {
+        return Ok(None);
+    };
+    let policy = tokens
+        .conflict_policy
+        .as_deref()
+        .map(ConflictPolicy::parse)
+        .transpose()?
+        .unwrap_or_default();
+    set_status(app_handle, |status| status.conflict_policy = policy);
+    if tokens.sync_enabled == Some(false) {
+        return Ok(None);
+    }
+
+    let context = SyncContext {
+        supabase,
+        client: reqwest::Client::new(),
+        access_token: refresh_token_if_needed(supabase, &user_id, &tokens).await?,
+        user_id: user_id.clone(),
+        calendar_id: sync_calendar_id(&tokens).to_string(),
+    };
+
+    let first_pass = tokens.sync_cursor.is_none();
+    let mut cursor = ChangeCursor::decode(tokens.sync_cursor.as_deref())?;
+    let (meetings, local) = context.fetch_local_changes(&mut cursor, first_pass).await?;
+    let (events, next_sync_token) = context
+        .fetch_remote_changes(tokens.sync_token.as_deref())
+        .await?;
+    let links: Vec<SyncLink> = supabase
+        .get_google_event_links(&user_id)
+        .await?
+        .into_iter()
+        .map(|row| SyncLink {
+            meeting_id: row.meeting_id,
+            event_id: row.google_event_id,
+            etag: row.etag,
+            meeting_updated_at: parse_time(row.meeting_updated_at.as_deref()),
+        })
+        .collect();
+
+    let remote: Vec<RemoteChange> = events.iter().map(SyncedEvent::to_change).collect();
+    let plan = calendar_sync::plan(&local, &remote, &links, policy);
+
+    let meetings: HashMap<&str, &MeetingRow> =
+        meetings.iter().map(|m| (m.id.as_str(), m)).collect();
+    let events: HashMap<&str, &SyncedEvent> = events.iter().map(|e| (e.id.as_str(), e)).collect();
+    let mut result = GoogleSyncResult {
+        conflicts: plan.conflicts,
+        ..Default::default()
+    };
+    let mut touched: Vec<(String, Option<String>)> = Vec::new();
+
+    for action in &plan.actions {
+        let outcome = match action {
+            SyncAction::CreateRemote { meeting_id } => match meetings.get(meeting_id.as_str()) {
+                Some(meeting) => context.push(meeting, None).await.map(|meet_linked| {
+                    if meet_linked {
+                        touched.push((meeting.id.clone(), Some(meeting.scheduled_at.clone())));
+                    }
+                    result.created_remote += 1;
+                }),
+                None => Ok(()),
+            },
+            SyncAction::UpdateRemote {
+                meeting_id,
+                event_id,
+            } => match meetings.get(meeting_id.as_str()) {
+                Some(meeting) => context
+                    .push(meeting, Some(event_id))
+                    .await
+                    .map(|meet_linked| {
+                        if meet_linked {
+                            touched.push((meeting.id.clone(), Some(meeting.scheduled_at.clone())));
+                        }
+                        result.updated_remote += 1;
+                    }),
+                None => Ok(()),
+            },
+            SyncAction::DeleteRemote {
+                meeting_id,
+                event_id,
+            } => context.delete_remote(meeting_id, event_id).await.map(|_| {
+                result.deleted_remote += 1;
+            }),
+            SyncAction::CreateLocal { event_id } => match events.get(event_id.as_str()) {
+                Some(event) => context.create_local(event).await.map(|row| {
+                    touched.push((row.id, Some(row.scheduled_at)));
+                    result.created_local += 1;
+                }),
+                None => Ok(()),
+            },
+            SyncAction::UpdateLocal {
+                meeting_id,
+                event_id,
+            } => match events.get(event_id.as_str()) {
+                Some(event) => context.update_local(meeting_id, event).await.map(|_| {
+                    touched.push((meeting_id.clone(), None));
+                    result.updated_local += 1;
+                }),
+                None => Ok(()),
+            },
+            SyncAction::CancelLocal { meeting_id, .. } => {
+                context.cancel_local(meeting_id).await.map(|_| {
+                    touched.push((meeting_id.clone(), None));
+                    result.cancelled_local += 1;
+                })
+            }
+            SyncAction::Unlink { meeting_id } => {
+                supabase
+                    .delete_google_event_links(&user_id, Some(meeting_id))
+                    .await
+            }
+        };
+        if let Err(e) = outcome {
+            tracing::warn!("Google sync action {:?} failed: {}", action, e);
+            result.failed += 1;
+        }
+    }
+
+    if !touched.is_empty() {
+        let mut cache = app_state.cache.meetings.write().await;
+        for (meeting_id, scheduled_at) in &touched {
+            cache.invalidate_meeting(meeting_id);
+            if let Some((year, month)) = scheduled_at.as_deref().and_then(extract_year_month) {
+                cache.invalidate_month(year, month);
+            }
+        }
+    }
+
+    // Leave the positions where they were so failed actions are retried
+    if result.failed == 0 {
+        supabase
+            .update_google_sync_position(
+                &user_id,
+                next_sync_token.as_deref(),
+                Some(&cursor.encode()?),
+                &Utc::now().to_rfc3339(),
+            )
+            .await?;
+    }
+
+    tracing::info!(
+        "Google sync: {} pushed, {} pulled, {} conflicts, {} failed",
+        result.created_remote + result.updated_remote + result.deleted_remote,
+        result.created_local + result.updated_local + result.cancelled_local,
+        result.conflicts,
+        result.failed
+    );
+    Ok(Some(result))
+}
+
+/// Run a sync pass unless one is already running, reporting its status
+async fn run_sync(app_handle: &AppHandle) -> Result<()> {
+    let sync_state = app_handle.state::<GoogleSyncState>();
+    let Ok(_pass) = sync_state.pass.try_lock() else {
+        return Ok(());
+    };
+
+    set_status(app_handle, |status| status.running = true);
+    let outcome = sync_pass(app_handle).await;
+    set_status(app_handle, |status| {
+        status.running = false;
+        match &outcome {
+            Ok(Some(result)) => {
+                status.last_synced_at = Some(Utc::now().to_rfc3339());
+                status.last_error = None;
+                status.last_result = Some(result.clone());
+            }
+            Ok(None) => status.last_error = None,
+            Err(e) => status.last_error = Some(e.to_string()),
+        }
+    });
+    outcome.map(|_| ())
+}
+
+/// Sync with Google Calendar in the background from startup on
+pub async fn watch_google_sync(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(SYNC_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = run_sync(&app_handle).await {
+            tracing::warn!("Google Calendar sync failed: {}", e);
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the status of the background Google Calendar sync
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_google_sync_status(
+    sync_state: State<'_, GoogleSyncState>,
+) -> Result<GoogleSyncStatus> {
+    Ok(sync_state
+        .status
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone())
+}
+
+/// Sync with Google Calendar now instead of waiting for the next pass
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn sync_google_calendar_now(
+    app_handle: AppHandle,
+    sync_state: State<'_, GoogleSyncState>,
+) -> Result<GoogleSyncStatus> {
+    run_sync(&app_handle).await?;
+    Ok(sync_state
+        .status
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone())
+}
+
+/// Choose which side wins when a meeting and its event both changed
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_google_conflict_policy(
+    policy: ConflictPolicy,
+    app_handle: AppHandle,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let user_id = app_state
+        .inner
+        .read()
+        .await
+        .user
+        .as_ref()
+        .map(|user| user.id.clone())
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let supabase = get_supabase(&app_state)?;
+
+    supabase
+        .update_google_conflict_policy(&user_id, policy.as_str())
+        .await?;
+    set_status(&app_handle, |status| status.conflict_policy = policy);
+    Ok(())
+}
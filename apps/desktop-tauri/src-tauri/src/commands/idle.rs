@@ -0,0 +1,75 @@
+//! Idle detection commands
+//!
+//! `watch_idle` turns the user's presence to away once they've left
+//! keyboard and mouse alone past the threshold, and back to online on the
+//! next input. Only a chosen `online` is replaced, so a status picked by
+//! hand stays put. Changes are emitted as `presence:idle`.
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::chat::{self, ChatState};
+use crate::idle;
+use crate::Result;
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Follow the user's idle time for as long as the app runs
+pub async fn watch_idle(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(idle::CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(idle_for) = idle::idle_time() else {
+            // Nothing to follow on this platform
+            return;
+        };
+        let is_idle = idle::is_idle(idle_for, idle::threshold());
+
+        let chat_state = app_handle.state::<ChatState>();
+        let mut chat_inner = chat_state.inner.write().await;
+        if chat_inner.idle == is_idle {
+            continue;
+        }
+        chat_inner.idle = is_idle;
+        let connected = chat_inner.presence_status.is_some();
+        drop(chat_inner);
+
+        tracing::debug!("User is {}", if is_idle { "idle" } else { "active" });
+        if connected {
+            chat::refresh_presence(&app_handle).await;
+        }
+        if let Err(e) = app_handle.emit("presence:idle", is_idle) {
+            tracing::error!("Failed to emit idle event: {}", e);
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the minutes without input before presence turns to away, zero when
+/// automatic away is off
+#[tauri::command]
+pub async fn get_idle_threshold() -> Result<u32> {
+    Ok(idle::threshold_minutes())
+}
+
+/// Set the minutes without input before presence turns to away, zero to
+/// turn automatic away off; kept across restarts
+///
+/// Takes effect at the next idle check.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_idle_threshold(minutes: u32, app_handle: AppHandle) -> Result<u32> {
+    let path = app_handle
+        .path()
+        .app_config_dir()?
+        .join(idle::SETTINGS_FILE);
+    idle::save(&path, minutes)?;
+    idle::set_threshold_minutes(minutes)?;
+
+    tracing::info!("Idle threshold set to {} minutes", minutes);
+    Ok(minutes)
+}
@@ -1,10 +1,114 @@
-use tauri::State;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::RwLock;
 
-use crate::input::{self, InputEvent, Modifiers, MouseButton};
-use crate::state::AppState;
+use crate::capture;
+use crate::commands::signaling::{emit_participants, SignalingState};
+use crate::input::{self, AutoRevokeReason, InputEvent, Modifiers, MouseButton, WatchdogConfig};
+use crate::input_recording::{self, InputRecorder, InputRecording};
+use crate::realtime::SignalingMessage;
+use crate::state::{AppState, AppStateInner};
 use crate::{Error, Result};
 
+/// How often the watchdog checks for idleness and protected windows
+const WATCHDOG_TICK: Duration = Duration::from_secs(2);
+
+// ==========================================
+// Recording State
+// ==========================================
+
+pub struct InputRecordingState {
+    pub inner: Arc<RwLock<InputRecordingStateInner>>,
+}
+
+#[derive(Default)]
+pub struct InputRecordingStateInner {
+    pub recorder: Option<InputRecorder>,
+    pub is_replaying: bool,
+}
+
+impl Default for InputRecordingState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InputRecordingStateInner::default())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputRecordingSummary {
+    pub path: String,
+    pub event_count: usize,
+    pub duration_ms: u64,
+}
+
+// ==========================================
+// Watchdog State
+// ==========================================
+
+pub struct WatchdogState {
+    pub inner: Arc<RwLock<WatchdogStateInner>>,
+}
+
+#[derive(Default)]
+pub struct WatchdogStateInner {
+    pub config: WatchdogConfig,
+    pub last_activity: Option<Instant>,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Default for WatchdogState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(WatchdogStateInner::default())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AutoRevokedEvent {
+    #[serde(flatten)]
+    reason: AutoRevokeReason,
+    user_id: Option<String>,
+}
+
+/// Refuse remote input unless the locked application, if any, has focus
+fn ensure_locked_app_focused(state: &AppState) -> Result<()> {
+    let Some(lock) = capture::current_app_lock(&state.app_lock) else {
+        return Ok(());
+    };
+
+    match input::focused_app_name()? {
+        Some(app_name) if lock.matches(&app_name) => Ok(()),
+        _ => Err(Error::Input(format!(
+            "Session is locked to {}, which is not focused",
+            lock.app_name
+        ))),
+    }
+}
+
+/// Inject an event, note viewer activity and append it to the active recording, if any
+async fn inject_and_record(
+    event: InputEvent,
+    recording: &InputRecordingState,
+    watchdog: &WatchdogState,
+) -> Result<()> {
+    input::inject_event(event.clone())?;
+
+    watchdog.inner.write().await.last_activity = Some(Instant::now());
+
+    let mut rec = recording.inner.write().await;
+    if let Some(recorder) = rec.recorder.as_mut() {
+        recorder.record(event);
+    }
+    Ok(())
+}
+
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn inject_mouse_event(
     event_type: String,
     x: f64,
@@ -13,12 +117,15 @@ pub async fn inject_mouse_event(
     delta_x: Option<f64>,
     delta_y: Option<f64>,
     state: State<'_, AppState>,
+    recording: State<'_, InputRecordingState>,
+    watchdog: State<'_, WatchdogState>,
 ) -> Result<()> {
     let inner = state.inner.read().await;
     if !inner.is_input_enabled {
         return Err(Error::Input("Input injection is disabled".to_string()));
     }
     drop(inner);
+    ensure_locked_app_focused(&state)?;
 
     let button = button.map(|b| match b.as_str() {
         "right" => MouseButton::Right,
@@ -50,10 +157,11 @@ pub async fn inject_mouse_event(
         _ => return Err(Error::Input(format!("Unknown mouse event type: {}", event_type))),
     };
 
-    input::inject_event(event)
+    inject_and_record(event, &recording, &watchdog).await
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn inject_keyboard_event(
     event_type: String,
     key: String,
@@ -62,12 +170,15 @@ pub async fn inject_keyboard_event(
     shift: Option<bool>,
     meta: Option<bool>,
     state: State<'_, AppState>,
+    recording: State<'_, InputRecordingState>,
+    watchdog: State<'_, WatchdogState>,
 ) -> Result<()> {
     let inner = state.inner.read().await;
     if !inner.is_input_enabled {
         return Err(Error::Input("Input injection is disabled".to_string()));
     }
     drop(inner);
+    ensure_locked_app_focused(&state)?;
 
     let modifiers = Modifiers {
         ctrl: ctrl.unwrap_or(false),
@@ -83,13 +194,258 @@ pub async fn inject_keyboard_event(
         _ => return Err(Error::Input(format!("Unknown keyboard event type: {}", event_type))),
     };
 
-    input::inject_event(event)
+    inject_and_record(event, &recording, &watchdog).await
 }
 
 #[tauri::command]
-pub async fn set_input_enabled(enabled: bool, state: State<'_, AppState>) -> Result<()> {
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_input_enabled(
+    enabled: bool,
+    state: State<'_, AppState>,
+    watchdog: State<'_, WatchdogState>,
+    app_handle: AppHandle,
+) -> Result<()> {
     let mut inner = state.inner.write().await;
     inner.is_input_enabled = enabled;
+    drop(inner);
+
+    let mut wd = watchdog.inner.write().await;
+    if let Some(task) = wd.task.take() {
+        task.abort();
+    }
+    if enabled && wd.config.enabled {
+        wd.last_activity = Some(Instant::now());
+        wd.task = Some(spawn_watchdog(
+            state.inner.clone(),
+            watchdog.inner.clone(),
+            app_handle,
+        ));
+    }
+
     tracing::info!("Input injection {}", if enabled { "enabled" } else { "disabled" });
     Ok(())
 }
+
+// ==========================================
+// Watchdog
+// ==========================================
+
+/// Get the remote control watchdog configuration
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_watchdog_config(watchdog: State<'_, WatchdogState>) -> Result<WatchdogConfig> {
+    Ok(watchdog.inner.read().await.config.clone())
+}
+
+/// Update the remote control watchdog configuration
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_watchdog_config(
+    config: WatchdogConfig,
+    state: State<'_, AppState>,
+    watchdog: State<'_, WatchdogState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    if config.idle_timeout_minutes == 0 {
+        return Err(Error::Input("Idle timeout must be at least 1 minute".to_string()));
+    }
+
+    let is_input_enabled = state.inner.read().await.is_input_enabled;
+
+    let mut wd = watchdog.inner.write().await;
+    wd.config = config;
+    if let Some(task) = wd.task.take() {
+        task.abort();
+    }
+    if is_input_enabled && wd.config.enabled {
+        wd.task = Some(spawn_watchdog(
+            state.inner.clone(),
+            watchdog.inner.clone(),
+            app_handle,
+        ));
+    }
+
+    tracing::info!("Watchdog configuration updated");
+    Ok(())
+}
+
+fn spawn_watchdog(
+    app_inner: Arc<RwLock<AppStateInner>>,
+    watchdog_inner: Arc<RwLock<WatchdogStateInner>>,
+    app_handle: AppHandle,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(WATCHDOG_TICK);
+        loop {
+            interval.tick().await;
+
+            if !app_inner.read().await.is_input_enabled {
+                break;
+            }
+
+            let (config, last_activity) = {
+                let wd = watchdog_inner.read().await;
+                (wd.config.clone(), wd.last_activity)
+            };
+
+            let idle_limit = Duration::from_secs(config.idle_timeout_minutes as u64 * 60);
+            let reason = if last_activity.is_some_and(|t| t.elapsed() >= idle_limit) {
+                Some(AutoRevokeReason::Idle {
+                    idle_minutes: config.idle_timeout_minutes,
+                })
+            } else {
+                match input::focused_window_title() {
+                    Ok(Some(title)) => input::match_protected_title(
+                        &title,
+                        &config.protected_title_patterns,
+                    )
+                    .map(|pattern| AutoRevokeReason::ProtectedApp {
+                        pattern: pattern.to_string(),
+                        window_title: title.clone(),
+                    }),
+                    Ok(None) => None,
+                    Err(e) => {
+                        tracing::debug!("Watchdog could not read focused window: {}", e);
+                        None
+                    }
+                }
+            };
+
+            if let Some(reason) = reason {
+                auto_revoke(&app_inner, &app_handle, reason).await;
+                watchdog_inner.write().await.task = None;
+                break;
+            }
+        }
+    })
+}
+
+/// Disable input injection, tell the controlling viewer and notify the frontend
+pub(crate) async fn auto_revoke(
+    app_inner: &Arc<RwLock<AppStateInner>>,
+    app_handle: &AppHandle,
+    reason: AutoRevokeReason,
+) {
+    app_inner.write().await.is_input_enabled = false;
+
+    let signaling = app_handle.state::<SignalingState>();
+    let mut signaling = signaling.inner.write().await;
+    let user_id = signaling.release_control();
+    if let (Some(to_user_id), Some(tx)) = (user_id.clone(), signaling.signaling_tx.as_ref()) {
+        if let Err(e) = tx.send(SignalingMessage::ControlRevoke { to_user_id }).await {
+            tracing::error!("Failed to send automatic control revoke: {}", e);
+        }
+    }
+    emit_participants(app_handle, &signaling);
+    drop(signaling);
+
+    tracing::warn!("Remote control auto-revoked: {:?}", reason);
+    if let Err(e) = app_handle.emit("control:auto-revoked", &AutoRevokedEvent { reason, user_id }) {
+        tracing::error!("Failed to emit auto-revoke event: {}", e);
+    }
+}
+
+// ==========================================
+// Recording & Replay Commands
+// ==========================================
+
+/// Start recording injected input events
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_input_recording(
+    state: State<'_, AppState>,
+    recording: State<'_, InputRecordingState>,
+    watchdog: State<'_, WatchdogState>,
+) -> Result<()> {
+    let inner = state.inner.read().await;
+    let session_id = inner.session.as_ref().map(|s| s.id.clone());
+    drop(inner);
+
+    let mut rec = recording.inner.write().await;
+    if rec.recorder.is_some() {
+        return Err(Error::Input("Input recording already in progress".to_string()));
+    }
+    rec.recorder = Some(InputRecorder::new(session_id));
+
+    tracing::info!("Input recording started");
+    Ok(())
+}
+
+/// Stop recording and save the captured events to a local file
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn stop_input_recording(
+    recording: State<'_, InputRecordingState>,
+    app_handle: AppHandle,
+) -> Result<InputRecordingSummary> {
+    let mut rec = recording.inner.write().await;
+    let recorder = rec
+        .recorder
+        .take()
+        .ok_or_else(|| Error::Input("No input recording in progress".to_string()))?;
+    drop(rec);
+
+    let captured = recorder.finish();
+    let file_name = format!(
+        "input-{}.json",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = app_handle
+        .path()
+        .app_data_dir()?
+        .join("recordings")
+        .join(file_name);
+
+    captured.save(&path)?;
+
+    tracing::info!(
+        "Input recording saved to {} ({} events)",
+        path.display(),
+        captured.events.len()
+    );
+
+    Ok(InputRecordingSummary {
+        path: path.to_string_lossy().to_string(),
+        event_count: captured.events.len(),
+        duration_ms: captured.duration_ms(),
+    })
+}
+
+/// Replay a recorded input stream, preserving the original timing
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn replay_input_recording(
+    path: String,
+    speed: Option<f64>,
+    recording: State<'_, InputRecordingState>,
+) -> Result<usize> {
+    let captured = InputRecording::load(&PathBuf::from(&path))?;
+
+    {
+        let mut rec = recording.inner.write().await;
+        if rec.recorder.is_some() {
+            return Err(Error::Input("Cannot replay while recording".to_string()));
+        }
+        if rec.is_replaying {
+            return Err(Error::Input("A replay is already running".to_string()));
+        }
+        rec.is_replaying = true;
+    }
+
+    tracing::info!("Replaying {} input events from {}", captured.events.len(), path);
+
+    let delays = input_recording::replay_delays(&captured.events, speed.unwrap_or(1.0));
+    let mut result = Ok(captured.events.len());
+    for (recorded, delay) in captured.events.into_iter().zip(delays) {
+        if delay > 0 {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+        if let Err(e) = input::inject_event(recorded.event) {
+            result = Err(e);
+            break;
+        }
+    }
+
+    recording.inner.write().await.is_replaying = false;
+    result
+}
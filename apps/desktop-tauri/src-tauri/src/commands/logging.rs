@@ -0,0 +1,36 @@
+//! Log filtering commands
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::logging::{self, LogFilter};
+use crate::Result;
+
+/// Get the directives currently filtering the log
+#[tauri::command]
+pub async fn get_trace_filter(log_filter: State<'_, LogFilter>) -> Result<String> {
+    Ok(log_filter.current())
+}
+
+/// Filter the log with `EnvFilter` directives, kept across restarts
+///
+/// e.g. `info,squadx_live_desktop_lib::chat_realtime=trace`. Without
+/// directives, logging goes back to what `RUST_LOG` sets.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_trace_filter(
+    directives: Option<String>,
+    log_filter: State<'_, LogFilter>,
+    app_handle: AppHandle,
+) -> Result<String> {
+    log_filter.set(directives.as_deref())?;
+
+    let path = app_handle
+        .path()
+        .app_config_dir()?
+        .join(logging::FILTER_FILE);
+    logging::save(&path, directives.as_deref())?;
+
+    let current = log_filter.current();
+    tracing::info!("Trace filter set to {}", current);
+    Ok(current)
+}
@@ -0,0 +1,40 @@
+//! Low-bandwidth mode commands
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::stream::StreamState;
+use crate::low_bandwidth;
+use crate::Result;
+
+/// Get whether low-bandwidth mode is on
+#[tauri::command]
+pub async fn get_low_bandwidth() -> Result<bool> {
+    Ok(low_bandwidth::is_enabled())
+}
+
+/// Turn low-bandwidth mode on or off, kept across restarts
+///
+/// A running stream picks up the new frame rate and resolution caps right
+/// away. Emits `app:low-bandwidth` when the mode changes.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_low_bandwidth(
+    enabled: bool,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<bool> {
+    let path = app_handle
+        .path()
+        .app_config_dir()?
+        .join(low_bandwidth::SETTINGS_FILE);
+    low_bandwidth::save(&path, enabled)?;
+
+    if low_bandwidth::set_enabled(enabled) {
+        stream_state.inner.read().await.apply_capture_caps();
+        tracing::info!("Low-bandwidth mode {}", if enabled { "on" } else { "off" });
+        if let Err(e) = app_handle.emit("app:low-bandwidth", enabled) {
+            tracing::error!("Failed to emit low-bandwidth event: {}", e);
+        }
+    }
+    Ok(enabled)
+}
@@ -0,0 +1,445 @@
+//! Meeting agenda commands
+//!
+//! CRUD for time-boxed agenda items attached to a meeting, plus a tracker
+//! that follows the agenda during the linked session and emits
+//! `agenda:overrun` when the current item runs past its time box.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::RwLock;
+
+use crate::state::AppState;
+use crate::supabase::MeetingAgendaItemRow;
+use crate::{Error, Result};
+
+/// How often the tracker checks the current item for overruns
+const TRACKER_TICK: Duration = Duration::from_secs(1);
+
+// ==========================================
+// Agenda State
+// ==========================================
+
+pub struct AgendaState {
+    pub inner: Arc<RwLock<AgendaStateInner>>,
+}
+
+#[derive(Default)]
+pub struct AgendaStateInner {
+    pub tracker: Option<AgendaTracker>,
+}
+
+impl Default for AgendaState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(AgendaStateInner::default())),
+        }
+    }
+}
+
+/// Live tracking of an agenda during a session
+pub struct AgendaTracker {
+    pub meeting_id: String,
+    pub items: Vec<AgendaItem>,
+    pub current_index: usize,
+    /// Seconds spent on each item (finished items only; the current one is computed)
+    pub elapsed_seconds: Vec<u64>,
+    pub item_started_at: Instant,
+    pub overrun_notified: bool,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl AgendaTracker {
+    fn current_elapsed(&self) -> u64 {
+        self.elapsed_seconds[self.current_index] + self.item_started_at.elapsed().as_secs()
+    }
+
+    fn progress(&self) -> AgendaProgress {
+        let items = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let elapsed_seconds = if index == self.current_index {
+                    self.current_elapsed()
+                } else {
+                    self.elapsed_seconds[index]
+                };
+                let planned_seconds = item.duration_minutes.max(0) as u64 * 60;
+                AgendaItemProgress {
+                    item_id: item.id.clone(),
+                    title: item.title.clone(),
+                    planned_seconds,
+                    elapsed_seconds,
+                    is_overrun: elapsed_seconds > planned_seconds,
+                }
+            })
+            .collect();
+
+        AgendaProgress {
+            meeting_id: self.meeting_id.clone(),
+            current_index: self.current_index,
+            items,
+        }
+    }
+}
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaItem {
+    pub id: String,
+    pub meeting_id: String,
+    pub title: String,
+    pub duration_minutes: i32,
+    pub presenter_id: Option<String>,
+    pub position: i32,
+}
+
+impl From<MeetingAgendaItemRow> for AgendaItem {
+    fn from(row: MeetingAgendaItemRow) -> Self {
+        Self {
+            id: row.id,
+            meeting_id: row.meeting_id,
+            title: row.title,
+            duration_minutes: row.duration_minutes,
+            presenter_id: row.presenter_id,
+            position: row.position,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaItemParams {
+    pub title: String,
+    pub duration_minutes: i32,
+    pub presenter_id: Option<String>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAgendaItemParams {
+    pub title: Option<String>,
+    pub duration_minutes: Option<i32>,
+    pub presenter_id: Option<String>,
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaItemProgress {
+    pub item_id: String,
+    pub title: String,
+    pub planned_seconds: u64,
+    pub elapsed_seconds: u64,
+    pub is_overrun: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaProgress {
+    pub meeting_id: String,
+    pub current_index: usize,
+    pub items: Vec<AgendaItemProgress>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AgendaOverrun {
+    meeting_id: String,
+    item_id: String,
+    title: String,
+    planned_seconds: u64,
+    elapsed_seconds: u64,
+}
+
+// ==========================================
+// CRUD Commands
+// ==========================================
+
+/// Get the agenda of a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_agenda_items(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<AgendaItem>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let rows = supabase.get_agenda_items(&meeting_id).await?;
+    Ok(rows.into_iter().map(AgendaItem::from).collect())
+}
+
+/// Add an item to a meeting's agenda (appended when no position is given)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn add_agenda_item(
+    meeting_id: String,
+    params: AgendaItemParams,
+    app_state: State<'_, AppState>,
+) -> Result<AgendaItem> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    if params.duration_minutes <= 0 {
+        return Err(Error::Parse("Agenda item duration must be positive".to_string()));
+    }
+
+    let position = match params.position {
+        Some(p) => p,
+        None => {
+            let existing = supabase.get_agenda_items(&meeting_id).await?;
+            existing.iter().map(|i| i.position + 1).max().unwrap_or(0)
+        }
+    };
+
+    let row = supabase
+        .create_agenda_item(
+            &meeting_id,
+            &params.title,
+            params.duration_minutes,
+            params.presenter_id.as_deref(),
+            position,
+        )
+        .await?;
+
+    Ok(row.into())
+}
+
+/// Update an agenda item
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn update_agenda_item(
+    item_id: String,
+    params: UpdateAgendaItemParams,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    if params.duration_minutes.is_some_and(|d| d <= 0) {
+        return Err(Error::Parse("Agenda item duration must be positive".to_string()));
+    }
+
+    supabase
+        .update_agenda_item(
+            &item_id,
+            params.title.as_deref(),
+            params.duration_minutes,
+            params.presenter_id.as_deref(),
+            params.position,
+        )
+        .await
+}
+
+/// Delete an agenda item
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn delete_agenda_item(
+    item_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase.delete_agenda_item(&item_id).await
+}
+
+// ==========================================
+// Time Boxing Commands
+// ==========================================
+
+/// Start tracking the agenda of a meeting linked to a session
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_agenda_tracking(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+    agenda_state: State<'_, AgendaState>,
+    app_handle: AppHandle,
+) -> Result<AgendaProgress> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let meeting = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Meeting not found".to_string()))?;
+
+    if meeting.session_id.is_none() {
+        return Err(Error::Session(
+            "Meeting is not linked to a session".to_string(),
+        ));
+    }
+
+    let items: Vec<AgendaItem> = supabase
+        .get_agenda_items(&meeting_id)
+        .await?
+        .into_iter()
+        .map(AgendaItem::from)
+        .collect();
+
+    if items.is_empty() {
+        return Err(Error::NotFound("Meeting has no agenda items".to_string()));
+    }
+
+    let mut state = agenda_state.inner.write().await;
+    if let Some(previous) = state.tracker.take() {
+        if let Some(task) = previous.task {
+            task.abort();
+        }
+    }
+
+    let mut tracker = AgendaTracker {
+        meeting_id: meeting_id.clone(),
+        elapsed_seconds: vec![0; items.len()],
+        items,
+        current_index: 0,
+        item_started_at: Instant::now(),
+        overrun_notified: false,
+        task: None,
+    };
+
+    let state_clone = agenda_state.inner.clone();
+    tracker.task = Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TRACKER_TICK);
+        loop {
+            interval.tick().await;
+
+            let mut state = state_clone.write().await;
+            let tracker = match state.tracker.as_mut() {
+                Some(t) if t.meeting_id == meeting_id => t,
+                _ => break,
+            };
+
+            let item = &tracker.items[tracker.current_index];
+            let planned_seconds = item.duration_minutes.max(0) as u64 * 60;
+            let elapsed_seconds = tracker.current_elapsed();
+
+            if elapsed_seconds > planned_seconds && !tracker.overrun_notified {
+                let overrun = AgendaOverrun {
+                    meeting_id: tracker.meeting_id.clone(),
+                    item_id: item.id.clone(),
+                    title: item.title.clone(),
+                    planned_seconds,
+                    elapsed_seconds,
+                };
+                tracker.overrun_notified = true;
+
+                tracing::info!("Agenda item '{}' overran its time box", overrun.title);
+                if let Err(e) = app_handle.emit("agenda:overrun", &overrun) {
+                    tracing::error!("Failed to emit agenda overrun event: {}", e);
+                }
+            }
+        }
+    }));
+
+    let progress = tracker.progress();
+    state.tracker = Some(tracker);
+
+    tracing::info!("Started agenda tracking for meeting {}", progress.meeting_id);
+    Ok(progress)
+}
+
+/// Move on to the next agenda item
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn next_agenda_item(agenda_state: State<'_, AgendaState>) -> Result<AgendaProgress> {
+    let mut state = agenda_state.inner.write().await;
+    let tracker = state
+        .tracker
+        .as_mut()
+        .ok_or_else(|| Error::Session("Agenda tracking is not running".to_string()))?;
+
+    if tracker.current_index + 1 >= tracker.items.len() {
+        return Err(Error::Session("Already at the last agenda item".to_string()));
+    }
+
+    let spent = tracker.item_started_at.elapsed().as_secs();
+    tracker.elapsed_seconds[tracker.current_index] += spent;
+    tracker.current_index += 1;
+    tracker.item_started_at = Instant::now();
+    tracker.overrun_notified = false;
+
+    Ok(tracker.progress())
+}
+
+/// Get elapsed time per agenda item for the tracked meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_agenda_progress(
+    agenda_state: State<'_, AgendaState>,
+) -> Result<Option<AgendaProgress>> {
+    let state = agenda_state.inner.read().await;
+    Ok(state.tracker.as_ref().map(|t| t.progress()))
+}
+
+/// Stop tracking the agenda
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn stop_agenda_tracking(
+    agenda_state: State<'_, AgendaState>,
+) -> Result<Option<AgendaProgress>> {
+    let mut state = agenda_state.inner.write().await;
+
+    let progress = state.tracker.take().map(|tracker| {
+        if let Some(ref task) = tracker.task {
+            task.abort();
+        }
+        tracker.progress()
+    });
+
+    tracing::info!("Stopped agenda tracking");
+    Ok(progress)
+}
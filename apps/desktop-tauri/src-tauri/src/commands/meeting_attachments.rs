@@ -0,0 +1,256 @@
+//! Meeting attachment commands
+//!
+//! Files (agenda docs, specs) attached to a meeting, visible to its
+//! organizer and attendees. They are uploaded to the meeting attachments
+//! bucket and downloaded through the attachment cache, like chat files.
+//! Occurrences of a recurring meeting share the files of the meeting.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::attachments;
+use crate::commands::chat::AttachmentProgress;
+use crate::state::AppState;
+use crate::supabase::{MeetingAttachmentRow, NewMeetingAttachment};
+use crate::utils::rrule;
+use crate::{Error, Result};
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingAttachment {
+    pub id: String,
+    pub meeting_id: String,
+    pub uploaded_by: Option<String>,
+    /// Object path in the bucket, which download progress refers to
+    pub path: String,
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+    pub created_at: Option<String>,
+}
+
+impl From<MeetingAttachmentRow> for MeetingAttachment {
+    fn from(row: MeetingAttachmentRow) -> Self {
+        Self {
+            id: row.id,
+            meeting_id: row.meeting_id,
+            uploaded_by: row.uploaded_by,
+            path: row.path,
+            name: row.name,
+            content_type: row.content_type,
+            size: row.size,
+            created_at: row.created_at,
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the files attached to a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_meeting_attachments(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<MeetingAttachment>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+    let rows = supabase.get_meeting_attachments(&meeting_id).await?;
+    Ok(rows.into_iter().map(MeetingAttachment::from).collect())
+}
+
+/// Attach a local file to a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn add_meeting_attachment(
+    meeting_id: String,
+    file_path: String,
+    app_state: State<'_, AppState>,
+) -> Result<MeetingAttachment> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+
+    let path = PathBuf::from(&file_path);
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|_| Error::NotFound(format!("File not found: {}", file_path)))?;
+    if metadata.len() > attachments::MAX_ATTACHMENT_BYTES {
+        return Err(Error::Parse(format!(
+            "Attachments are limited to {} MB",
+            attachments::MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    let content_type = attachments::content_type(&name);
+    let bytes = tokio::fs::read(&path).await?;
+
+    let storage_path = attachments::storage_path(&meeting_id, &user_id, &name);
+    supabase
+        .upload_storage_object(
+            attachments::MEETING_ATTACHMENT_BUCKET,
+            &storage_path,
+            content_type,
+            bytes,
+        )
+        .await?;
+
+    let created = supabase
+        .create_meeting_attachment(&NewMeetingAttachment {
+            meeting_id: meeting_id.clone(),
+            uploaded_by: user_id,
+            path: storage_path.clone(),
+            name,
+            content_type: content_type.to_string(),
+            size: metadata.len(),
+        })
+        .await;
+    let row = match created {
+        Ok(row) => row,
+        Err(e) => {
+            // Don't leave the file behind without its record
+            if let Err(e) = supabase
+                .delete_storage_object(attachments::MEETING_ATTACHMENT_BUCKET, &storage_path)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to remove orphaned attachment {}: {}",
+                    storage_path,
+                    e
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    tracing::info!("Attached {} to meeting {}", row.name, meeting_id);
+    Ok(row.into())
+}
+
+/// Remove a file from a meeting (its uploader or the organizer)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn remove_meeting_attachment(
+    attachment_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let attachment = supabase
+        .get_meeting_attachment(&attachment_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Attachment {}", attachment_id)))?;
+
+    supabase.delete_meeting_attachment(&attachment.id).await?;
+    if let Err(e) = supabase
+        .delete_storage_object(attachments::MEETING_ATTACHMENT_BUCKET, &attachment.path)
+        .await
+    {
+        tracing::warn!(
+            "Failed to remove file of attachment {}: {}",
+            attachment.id,
+            e
+        );
+    }
+
+    Ok(())
+}
+
+/// Download a meeting attachment, or return it from the local cache
+///
+/// Progress is emitted as `meeting:attachment-progress`. Returns the local
+/// path of the file.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn download_meeting_attachment(
+    attachment_id: String,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let attachment = supabase
+        .get_meeting_attachment(&attachment_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Attachment {}", attachment_id)))?;
+
+    let cache_dir = app_handle.path().app_cache_dir()?;
+    let path = attachments::download_to_cache(
+        supabase,
+        attachments::MEETING_ATTACHMENT_BUCKET,
+        &attachment.path,
+        &attachment.name,
+        attachment.size,
+        &cache_dir,
+        |received, total| {
+            let progress = AttachmentProgress {
+                path: attachment.path.clone(),
+                received,
+                total,
+            };
+            if let Err(e) = app_handle.emit("meeting:attachment-progress", &progress) {
+                tracing::error!("Failed to emit attachment progress event: {}", e);
+            }
+        },
+    )
+    .await?;
+
+    Ok(path.to_string_lossy().to_string())
+}
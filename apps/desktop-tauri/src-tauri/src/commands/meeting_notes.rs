@@ -0,0 +1,415 @@
+//! Meeting notes commands
+//!
+//! Notes shared by everyone in a meeting and the action items that came out
+//! of it, so what a session decided lives next to its calendar entry. Notes
+//! are saved against the version they were edited from: a save over a newer
+//! version is refused and returns that version, so nobody's edit is lost.
+//! Occurrences of a recurring meeting share the notes of the meeting.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::state::AppState;
+use crate::supabase::{ActionItemUpdate, MeetingActionItemRow, MeetingNotesRow};
+use crate::utils::rrule;
+use crate::{Error, Result};
+
+/// Longest notes accepted, in bytes
+const MAX_NOTES_LENGTH: usize = 100_000;
+
+/// Longest action item title accepted, in characters
+const MAX_ACTION_ITEM_TITLE_LENGTH: usize = 500;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingNotes {
+    pub meeting_id: String,
+    pub content: String,
+    /// Version to save the next edit against
+    pub version: i32,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl From<MeetingNotesRow> for MeetingNotes {
+    fn from(row: MeetingNotesRow) -> Self {
+        Self {
+            meeting_id: row.meeting_id,
+            content: row.content,
+            version: row.version,
+            updated_by: row.updated_by,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveNotesResult {
+    /// False when someone saved a newer version first; `notes` is then that
+    /// version, to merge the edit into
+    pub saved: bool,
+    pub notes: MeetingNotes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub id: String,
+    pub meeting_id: String,
+    /// Title and time of the meeting, when listed across meetings
+    pub meeting_title: Option<String>,
+    pub meeting_scheduled_at: Option<String>,
+    pub title: String,
+    pub assignee_id: Option<String>,
+    /// YYYY-MM-DD
+    pub due_date: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<String>,
+    pub completed_by: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<MeetingActionItemRow> for ActionItem {
+    fn from(row: MeetingActionItemRow) -> Self {
+        let (meeting_title, meeting_scheduled_at) = row
+            .meetings
+            .map(|m| (Some(m.title), Some(m.scheduled_at)))
+            .unwrap_or_default();
+        Self {
+            id: row.id,
+            meeting_id: row.meeting_id,
+            meeting_title,
+            meeting_scheduled_at,
+            title: row.title,
+            assignee_id: row.assignee_id,
+            due_date: row.due_date,
+            completed: row.completed_at.is_some(),
+            completed_at: row.completed_at,
+            completed_by: row.completed_by,
+            created_by: row.created_by,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItemParams {
+    pub title: String,
+    pub assignee_id: Option<String>,
+    /// YYYY-MM-DD
+    pub due_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateActionItemParams {
+    pub title: Option<String>,
+    /// An empty string unassigns the item
+    pub assignee_id: Option<String>,
+    /// An empty string clears the due date
+    pub due_date: Option<String>,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Occurrence ids resolve to the recurring meeting they belong to
+fn notes_meeting_id(meeting_id: String) -> String {
+    rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id)
+}
+
+fn validate_title(title: &str) -> Result<String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(Error::Parse("Action item title is required".to_string()));
+    }
+    if title.chars().count() > MAX_ACTION_ITEM_TITLE_LENGTH {
+        return Err(Error::Parse(format!(
+            "Action item title is longer than {} characters",
+            MAX_ACTION_ITEM_TITLE_LENGTH
+        )));
+    }
+    Ok(title.to_string())
+}
+
+fn validate_due_date(due_date: &str) -> Result<()> {
+    chrono::NaiveDate::parse_from_str(due_date, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|e| Error::Parse(format!("Invalid due date {}: {}", due_date, e)))
+}
+
+/// `Some(None)` for an empty string, which clears the field
+fn clearable(value: Option<String>) -> Option<Option<String>> {
+    value.map(|v| Some(v.trim().to_string()).filter(|v| !v.is_empty()))
+}
+
+async fn current_user_id(app_state: &AppState) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    Ok(user.id.clone())
+}
+
+// ==========================================
+// Notes Commands
+// ==========================================
+
+/// Get the notes of a meeting; `None` until someone writes some
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_meeting_notes(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Option<MeetingNotes>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let notes = supabase
+        .get_meeting_notes(&notes_meeting_id(meeting_id))
+        .await?;
+    Ok(notes.map(MeetingNotes::from))
+}
+
+/// Save the notes of a meeting, edited from `base_version` (`None` for
+/// notes nobody wrote yet)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn save_meeting_notes(
+    meeting_id: String,
+    content: String,
+    base_version: Option<i32>,
+    app_state: State<'_, AppState>,
+) -> Result<SaveNotesResult> {
+    let user_id = current_user_id(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    if content.len() > MAX_NOTES_LENGTH {
+        return Err(Error::Parse(format!(
+            "Meeting notes are longer than {} bytes",
+            MAX_NOTES_LENGTH
+        )));
+    }
+
+    let meeting_id = notes_meeting_id(meeting_id);
+    let saved = match base_version {
+        Some(version) => {
+            supabase
+                .update_meeting_notes(&meeting_id, &content, &user_id, version)
+                .await?
+        }
+        None => match supabase.get_meeting_notes(&meeting_id).await? {
+            Some(_) => None,
+            None => Some(
+                supabase
+                    .create_meeting_notes(&meeting_id, &content, &user_id)
+                    .await?,
+            ),
+        },
+    };
+
+    if let Some(notes) = saved {
+        return Ok(SaveNotesResult {
+            saved: true,
+            notes: notes.into(),
+        });
+    }
+
+    // Someone saved first; hand back their version
+    let latest = supabase
+        .get_meeting_notes(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Notes of meeting {}", meeting_id)))?;
+    tracing::debug!(
+        "Notes of meeting {} are at version {}, not {:?}",
+        meeting_id,
+        latest.version,
+        base_version
+    );
+    Ok(SaveNotesResult {
+        saved: false,
+        notes: latest.into(),
+    })
+}
+
+// ==========================================
+// Action Item Commands
+// ==========================================
+
+/// Get the action items of a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_action_items(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<ActionItem>> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let rows = supabase
+        .get_action_items(&notes_meeting_id(meeting_id))
+        .await?;
+    Ok(rows.into_iter().map(ActionItem::from).collect())
+}
+
+/// Get the action items assigned to a user (the current one by default)
+/// across meetings, open ones only unless `include_completed`
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_assigned_action_items(
+    assignee_id: Option<String>,
+    include_completed: Option<bool>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<ActionItem>> {
+    let user_id = current_user_id(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let assignee_id = assignee_id.unwrap_or(user_id);
+    let rows = supabase
+        .get_assigned_action_items(&assignee_id, include_completed.unwrap_or(false))
+        .await?;
+    Ok(rows.into_iter().map(ActionItem::from).collect())
+}
+
+/// Add an action item to a meeting
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_action_item(
+    meeting_id: String,
+    params: ActionItemParams,
+    app_state: State<'_, AppState>,
+) -> Result<ActionItem> {
+    let user_id = current_user_id(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let title = validate_title(&params.title)?;
+    let assignee_id = clearable(params.assignee_id).flatten();
+    let due_date = clearable(params.due_date).flatten();
+    if let Some(ref due_date) = due_date {
+        validate_due_date(due_date)?;
+    }
+
+    let row = supabase
+        .create_action_item(
+            &notes_meeting_id(meeting_id),
+            &title,
+            assignee_id.as_deref(),
+            due_date.as_deref(),
+            &user_id,
+        )
+        .await?;
+
+    Ok(row.into())
+}
+
+/// Update an action item
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn update_action_item(
+    item_id: String,
+    params: UpdateActionItemParams,
+    app_state: State<'_, AppState>,
+) -> Result<ActionItem> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let update = ActionItemUpdate {
+        title: params.title.as_deref().map(validate_title).transpose()?,
+        assignee_id: clearable(params.assignee_id),
+        due_date: clearable(params.due_date),
+    };
+    if let Some(Some(ref due_date)) = update.due_date {
+        validate_due_date(due_date)?;
+    }
+
+    supabase
+        .update_action_item(&item_id, &update)
+        .await?
+        .map(ActionItem::from)
+        .ok_or_else(|| Error::NotFound(format!("Action item {}", item_id)))
+}
+
+/// Mark an action item done, or open it again
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn complete_action_item(
+    item_id: String,
+    completed: bool,
+    app_state: State<'_, AppState>,
+) -> Result<ActionItem> {
+    let user_id = current_user_id(&app_state).await?;
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let completed_by = completed.then_some(user_id.as_str());
+    supabase
+        .set_action_item_completed(&item_id, completed_by)
+        .await?
+        .map(ActionItem::from)
+        .ok_or_else(|| Error::NotFound(format!("Action item {}", item_id)))
+}
+
+/// Delete an action item
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn delete_action_item(item_id: String, app_state: State<'_, AppState>) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase.delete_action_item(&item_id).await
+}
@@ -0,0 +1,94 @@
+//! Meeting statistics commands
+//!
+//! Backs the personal "calendar health" dashboard. Stats are computed from
+//! the same month-cached meetings the calendar views load, so browsing the
+//! dashboard after the calendar costs no extra requests.
+
+use tauri::State;
+
+use crate::commands::calendar::{self, Meeting};
+use crate::state::AppState;
+use crate::utils::datetime;
+use crate::utils::meeting_stats::{self, MeetingStats, StatsMeeting};
+use crate::{Error, Result};
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// A meeting as seen by `user_id`, who either organizes it or attends it
+fn stats_meeting(meeting: Meeting, user_id: &str) -> StatsMeeting {
+    let organizes = meeting.organizer_id == user_id;
+    let response_status = if organizes {
+        None
+    } else {
+        Some(
+            meeting
+                .attendees
+                .iter()
+                .find(|attendee| attendee.user_id == user_id)
+                .map_or_else(
+                    || "invited".to_string(),
+                    |attendee| attendee.response_status.clone(),
+                ),
+        )
+    };
+    let attendee_responses = if organizes {
+        meeting
+            .attendees
+            .into_iter()
+            .filter(|attendee| attendee.user_id != user_id)
+            .map(|attendee| attendee.response_status)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    StatsMeeting {
+        organizer_id: meeting.organizer_id,
+        organizer_name: meeting.organizer_name,
+        scheduled_at: meeting.scheduled_at,
+        duration_minutes: meeting.duration_minutes,
+        status: meeting.status,
+        response_status,
+        attendee_responses,
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Summarize the current user's meetings between two dates: hours in
+/// meetings, meetings per organizer, acceptance and attendance rates and
+/// the busiest days, days taken in `time_zone` (the system one when unset)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_meeting_stats(
+    start_date: String,
+    end_date: String,
+    time_zone: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<MeetingStats> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    drop(inner);
+
+    let time_zone = time_zone
+        .as_deref()
+        .map(datetime::parse_time_zone)
+        .transpose()?;
+    let meetings =
+        calendar::meetings_in_range(&app_state, &user_id, &start_date, &end_date).await?;
+    let meetings: Vec<StatsMeeting> = meetings
+        .into_iter()
+        .map(|meeting| stats_meeting(meeting, &user_id))
+        .collect();
+
+    meeting_stats::compute_stats(&meetings, time_zone)
+}
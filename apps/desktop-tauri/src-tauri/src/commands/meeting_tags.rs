@@ -0,0 +1,271 @@
+//! Meeting tag commands
+//!
+//! Users define colored tags and categorize the meetings they organize
+//! with them. A tagged meeting takes its tag's color, which attendees see
+//! and which is synced to Google Calendar as the closest event color; a
+//! meeting can also be given a color without a tag.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::state::AppState;
+use crate::supabase::MeetingTagRow;
+use crate::utils::colors;
+use crate::utils::rrule;
+use crate::{Error, Result};
+
+/// Longest tag name accepted, in characters
+const MAX_TAG_NAME_LENGTH: usize = 50;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingTag {
+    pub id: String,
+    pub name: String,
+    /// `#rrggbb`
+    pub color: String,
+    pub created_at: Option<String>,
+}
+
+impl From<MeetingTagRow> for MeetingTag {
+    fn from(row: MeetingTagRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            color: row.color,
+            created_at: row.created_at,
+        }
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn validate_tag_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Parse("Tag name is empty".to_string()));
+    }
+    if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        return Err(Error::Parse(format!(
+            "Tag names are limited to {} characters",
+            MAX_TAG_NAME_LENGTH
+        )));
+    }
+    Ok(name)
+}
+
+/// Meetings show colors in lists and months, so every cached one may change
+async fn invalidate_meetings(app_state: &AppState) {
+    let mut cache = app_state.cache.meetings.write().await;
+    cache.invalidate_all();
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get the current user's meeting tags
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_meeting_tags(app_state: State<'_, AppState>) -> Result<Vec<MeetingTag>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let rows = supabase.get_meeting_tags(&user_id).await?;
+    Ok(rows.into_iter().map(MeetingTag::from).collect())
+}
+
+/// Create a meeting tag
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_meeting_tag(
+    name: String,
+    color: String,
+    app_state: State<'_, AppState>,
+) -> Result<MeetingTag> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let name = validate_tag_name(&name)?;
+    let color = colors::normalize_hex_color(&color)?;
+    let row = supabase.create_meeting_tag(&user_id, name, &color).await?;
+    Ok(row.into())
+}
+
+/// Rename or recolor a meeting tag; its meetings take the new color
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn update_meeting_tag(
+    tag_id: String,
+    name: Option<String>,
+    color: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<MeetingTag> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let name = name.as_deref().map(validate_tag_name).transpose()?;
+    let color = color
+        .as_deref()
+        .map(colors::normalize_hex_color)
+        .transpose()?;
+
+    let row = supabase
+        .update_meeting_tag(&tag_id, name, color.as_deref())
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Tag {}", tag_id)))?;
+    if color.is_some() {
+        invalidate_meetings(&app_state).await;
+    }
+
+    Ok(row.into())
+}
+
+/// Delete a meeting tag; its meetings keep their color, untagged
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn delete_meeting_tag(tag_id: String, app_state: State<'_, AppState>) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    supabase.delete_meeting_tag(&tag_id).await?;
+    invalidate_meetings(&app_state).await;
+
+    Ok(())
+}
+
+/// Tag a meeting with one of the organizer's tags, taking its color;
+/// `None` removes the tag and the color
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_meeting_tag(
+    meeting_id: String,
+    tag_id: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+    let tag = match tag_id {
+        Some(tag_id) => {
+            let tag = supabase
+                .get_meeting_tag(&tag_id)
+                .await?
+                .filter(|tag| tag.user_id == user_id)
+                .ok_or_else(|| Error::NotFound(format!("Tag {}", tag_id)))?;
+            Some(tag)
+        }
+        None => None,
+    };
+
+    supabase
+        .set_meeting_tag(
+            &meeting_id,
+            tag.as_ref().map(|tag| tag.id.as_str()),
+            tag.as_ref().map(|tag| tag.color.as_str()),
+        )
+        .await?;
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting_id);
+    }
+
+    Ok(())
+}
+
+/// Give a meeting a color of its own, removing its tag; `None` clears it
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_meeting_color(
+    meeting_id: String,
+    color: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let meeting_id = rrule::parse_occurrence_id(&meeting_id).map_or(meeting_id, |(id, _)| id);
+    let color = color
+        .as_deref()
+        .map(colors::normalize_hex_color)
+        .transpose()?;
+
+    supabase
+        .set_meeting_tag(&meeting_id, None, color.as_deref())
+        .await?;
+    {
+        let mut cache = app_state.cache.meetings.write().await;
+        cache.invalidate_meeting(&meeting_id);
+    }
+
+    Ok(())
+}
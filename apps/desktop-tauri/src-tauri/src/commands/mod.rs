@@ -1,12 +1,48 @@
+pub mod annotations;
+pub mod announcements;
 pub mod auth;
+pub mod automation;
+pub mod avatars;
+pub mod badge;
+pub mod blocking;
+pub mod availability;
 pub mod cache;
 pub mod calendar;
+pub mod calendar_feed;
+pub mod calendar_ics;
+pub mod camera;
 pub mod capture;
 pub mod chat;
+pub mod deep_link;
+pub mod diagnostics;
+pub mod emoji;
+pub mod encryption;
+pub mod file_transfer;
 pub mod google_calendar;
+pub mod google_sync;
+pub mod idle;
 pub mod input;
+pub mod logging;
+pub mod low_bandwidth;
+pub mod meeting_agenda;
+pub mod meeting_attachments;
+pub mod meeting_notes;
+pub mod meeting_stats;
+pub mod meeting_tags;
+pub mod notifications;
+pub mod outlook_calendar;
+pub mod perf;
+pub mod polls;
+pub mod preflight;
+pub mod reminders;
+pub mod remote_wipe;
+pub mod scheduling;
 pub mod session;
 pub mod signaling;
+pub mod stream;
+pub mod sync;
 pub mod utils;
 pub mod validation;
+pub mod voice;
+pub mod voice_message;
 pub mod window;
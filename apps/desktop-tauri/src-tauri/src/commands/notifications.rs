@@ -0,0 +1,714 @@
+//! Notification commands
+//!
+//! Native toasts on Windows, in-app notifications elsewhere; both lead to the
+//! same actions (inline chat reply, meeting accept/decline, open).
+//!
+//! Besides the frontend's own notifications, the backend raises them for
+//! new messages and mentions, meeting reminders, missed RSVP deadlines and
+//! control requests, all through [`notify`] so the user's settings apply
+//! alike.
+//!
+//! The same poll starts the organizer's auto-start meetings when they are
+//! due, and emits `meeting:starting` once for each meeting of the user whose
+//! session started, wherever it was started from.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::calendar::{self, MeetingStarting};
+use crate::commands::chat::{ChatState, Message};
+use crate::commands::reminders;
+use crate::notifications::{
+    self, Delivery, Notification, NotificationAction, NotificationCategory, NotificationKind,
+    NotificationLevel, NotificationSettings,
+};
+use crate::state::AppState;
+use crate::{Error, Result};
+
+/// How often upcoming meetings are checked for reminders
+const REMINDER_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Minutes a reminder is snoozed for by default
+const DEFAULT_SNOOZE_MINUTES: i32 = 5;
+
+/// How far from its scheduled time a meeting is announced as starting; an
+/// auto-start meeting starts on its own up to this long after it
+const MEETING_START_WINDOW_MINUTES: i64 = 15;
+
+pub struct NotificationState {
+    settings: RwLock<NotificationSettings>,
+    /// Reminders already shown since startup, by meeting, start time and offset
+    reminded: Mutex<HashSet<(String, String, i32)>>,
+    /// Meetings whose reminder was snoozed, and until when
+    snoozed: Mutex<HashMap<String, DateTime<Utc>>>,
+    /// Meeting starts already announced, by meeting and session
+    announced: Mutex<HashSet<(String, String)>>,
+}
+
+impl NotificationState {
+    /// State with the settings saved in the app config directory
+    pub fn load(app_handle: &AppHandle) -> Result<Self> {
+        Ok(Self {
+            settings: RwLock::new(NotificationSettings::load(&settings_path(app_handle)?)),
+            reminded: Mutex::new(HashSet::new()),
+            snoozed: Mutex::new(HashMap::new()),
+            announced: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn settings(&self) -> NotificationSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NotificationStatus {
+    /// Focus Assist, a full-screen app or a presentation holds notifications back
+    pub focus_assist_active: bool,
+    /// Notifications are shown as native toasts rather than by the frontend
+    pub native_toasts: bool,
+}
+
+/// Notification for the frontend to display, with the argument of a click
+#[derive(Debug, Clone, serde::Serialize)]
+struct FrontendNotification {
+    #[serde(flatten)]
+    notification: Notification,
+    launch: String,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf> {
+    Ok(app_handle
+        .path()
+        .app_config_dir()?
+        .join(notifications::SETTINGS_FILE))
+}
+
+/// Signed-in user's ID and email
+async fn current_user(app_handle: &AppHandle) -> Option<(String, String)> {
+    app_handle
+        .state::<AppState>()
+        .inner
+        .read()
+        .await
+        .user
+        .as_ref()
+        .map(|u| (u.id.clone(), u.email.clone()))
+}
+
+/// Names the user is mentioned by: the display name and the email's local part
+async fn mention_names(app_state: &AppState, user_id: &str, email: &str) -> Vec<String> {
+    let mut names = vec![email.split('@').next().unwrap_or_default().to_string()];
+    if let Some(ref supabase) = app_state.supabase {
+        let display_name = supabase
+            .get_user_profiles(&[user_id.to_string()])
+            .await
+            .ok()
+            .and_then(|profiles| profiles.into_iter().next())
+            .and_then(|profile| profile.display_name);
+        names.extend(display_name);
+    }
+    names
+}
+
+/// Whether the user's preference for a conversation lets a chat
+/// notification through; when it can't be read, it does
+async fn chat_notification_allowed(
+    app_handle: &AppHandle,
+    conversation_id: &str,
+    body: &str,
+) -> bool {
+    let app_state = app_handle.state::<AppState>();
+    let Some((user_id, email)) = current_user(app_handle).await else {
+        return true;
+    };
+    let Some(ref supabase) = app_state.supabase else {
+        return true;
+    };
+
+    let prefs = match supabase
+        .get_notification_prefs(conversation_id, &user_id)
+        .await
+    {
+        Ok(prefs) => prefs.unwrap_or_default(),
+        Err(e) => {
+            tracing::debug!("Failed to get notification preference: {}", e);
+            return true;
+        }
+    };
+    let now = chrono::Utc::now();
+    if prefs.level_at(now) != NotificationLevel::MentionsOnly {
+        return prefs.allows(false, now);
+    }
+
+    let names = mention_names(&app_state, &user_id, &email).await;
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    prefs.allows(notifications::mentions(body, &names), now)
+}
+
+/// Show a notification, unless its category is off, quiet hours are on,
+/// the conversation's preference drops it, or Focus Assist is on, the last
+/// two only for notifications that aren't urgent
+///
+/// Held back notifications are emitted as `notification:suppressed` so the
+/// frontend can list them.
+pub(crate) async fn notify(
+    app_handle: &AppHandle,
+    notification: Notification,
+    category: Option<NotificationCategory>,
+) -> Result<Delivery> {
+    let settings = app_handle.state::<NotificationState>().settings();
+    let now = chrono::Local::now().time();
+    if !settings.allows(category, notification.urgent, now) {
+        tracing::debug!("Notification silenced by settings: {:?}", category);
+        return Ok(Delivery::Silenced);
+    }
+
+    if let NotificationKind::Chat { conversation_id } = &notification.kind {
+        if !chat_notification_allowed(app_handle, conversation_id, &notification.body).await {
+            tracing::debug!(
+                "Conversation {} is muted, dropping notification",
+                conversation_id
+            );
+            return Ok(Delivery::Muted);
+        }
+    }
+
+    if !notification.urgent && notifications::focus_assist_active() {
+        tracing::debug!("Focus Assist is on, holding back notification");
+        if let Err(e) = app_handle.emit("notification:suppressed", &notification) {
+            tracing::error!("Failed to emit suppressed notification event: {}", e);
+        }
+        return Ok(Delivery::Suppressed);
+    }
+
+    let action_handle = app_handle.clone();
+    let app_id = app_handle.config().identifier.clone();
+    let shown = notifications::show_toast(&app_id, &notification, move |action| {
+        let app_handle = action_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = perform_action(&app_handle, action).await {
+                tracing::error!("Failed to act on notification: {}", e);
+            }
+        });
+    })?;
+    if shown {
+        return Ok(Delivery::Toast);
+    }
+
+    let payload = FrontendNotification {
+        launch: notification.kind.open_argument(),
+        notification,
+    };
+    app_handle.emit("notification:show", &payload)?;
+    Ok(Delivery::Frontend)
+}
+
+/// Notify the user of a new chat message, as a mention when it mentions
+/// them
+///
+/// Nothing is shown for the user's own messages, or while the conversation
+/// is open in a focused window.
+pub(crate) async fn notify_message(app_handle: AppHandle, message: Message) {
+    let Some((user_id, email)) = current_user(&app_handle).await else {
+        return;
+    };
+    if message.sender_id.as_deref() == Some(user_id.as_str()) {
+        return;
+    }
+    let focused = app_handle
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false));
+    let open = app_handle
+        .state::<ChatState>()
+        .inner
+        .read()
+        .await
+        .open_conversations
+        .ids()
+        .any(|id| id == message.conversation_id);
+    if focused && open {
+        return;
+    }
+
+    let app_state = app_handle.state::<AppState>();
+    let names = mention_names(&app_state, &user_id, &email).await;
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let (category, title) = if notifications::mentions(&message.content, &names) {
+        (
+            NotificationCategory::Mentions,
+            format!("{} mentioned you", message.sender_name),
+        )
+    } else {
+        (NotificationCategory::Messages, message.sender_name.clone())
+    };
+
+    let notification = Notification {
+        title,
+        body: message.content,
+        kind: NotificationKind::Chat {
+            conversation_id: message.conversation_id,
+        },
+        urgent: false,
+    };
+    if let Err(e) = notify(&app_handle, notification, Some(category)).await {
+        tracing::warn!("Failed to notify of message: {}", e);
+    }
+}
+
+/// Notify the host that a viewer asks for control, which is urgent since
+/// the request expires
+pub(crate) async fn notify_control_request(app_handle: AppHandle, user_id: String) {
+    let notification = Notification {
+        title: "Control requested".to_string(),
+        body: "A viewer is asking to control your screen".to_string(),
+        kind: NotificationKind::ControlRequest { user_id },
+        urgent: true,
+    };
+    if let Err(e) = notify(
+        &app_handle,
+        notification,
+        Some(NotificationCategory::ControlRequests),
+    )
+    .await
+    {
+        tracing::warn!("Failed to notify of control request: {}", e);
+    }
+}
+
+/// Remind the user of meetings at each of their reminder offsets, once per
+/// offset, and again when a snoozed reminder comes due
+///
+/// The organizer's last reminder marks the meeting `reminder_sent`, so a
+/// restart doesn't remind them again; attendees can't update the meeting.
+async fn check_reminders(app_handle: &AppHandle) -> Result<()> {
+    let Some((user_id, _)) = current_user(app_handle).await else {
+        return Ok(());
+    };
+    let app_state = app_handle.state::<AppState>();
+    let Some(ref supabase) = app_state.supabase else {
+        return Ok(());
+    };
+
+    let preferences = reminders::load_preferences(supabase, &user_id).await?;
+    let offsets = preferences.offsets();
+    let last_offset = offsets.first().copied().unwrap_or_default();
+    let window = offsets.last().copied().unwrap_or_default();
+    let now = chrono::Utc::now();
+    let meetings = supabase
+        .get_meetings_in_range(
+            &user_id,
+            &now.to_rfc3339(),
+            &(now + chrono::Duration::minutes(window.into())).to_rfc3339(),
+        )
+        .await?;
+
+    let notification_state = app_handle.state::<NotificationState>();
+    for meeting in meetings {
+        let is_organizer = meeting.organizer_id == user_id;
+        if meeting.status != "scheduled" || (is_organizer && meeting.reminder_sent == Some(true)) {
+            continue;
+        }
+
+        let snoozed_until = notification_state
+            .snoozed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&meeting.id)
+            .copied();
+        let stage = match snoozed_until {
+            Some(until) if until > now => continue,
+            Some(_) => {
+                notification_state
+                    .snoozed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&meeting.id);
+                None
+            }
+            None => {
+                let Some(stage) =
+                    notifications::reminder_stage(&meeting.scheduled_at, &offsets, now)
+                else {
+                    continue;
+                };
+                let first = notification_state
+                    .reminded
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert((meeting.id.clone(), meeting.scheduled_at.clone(), stage));
+                if !first {
+                    continue;
+                }
+                Some(stage)
+            }
+        };
+
+        let minutes = chrono::DateTime::parse_from_rfc3339(&meeting.scheduled_at)
+            .map(|start| (start.with_timezone(&chrono::Utc) - now).num_minutes())
+            .unwrap_or(stage.unwrap_or(last_offset).into());
+        let notification = Notification {
+            title: meeting.title.clone(),
+            body: format!("Starts in {}", notifications::format_lead_time(minutes)),
+            kind: NotificationKind::MeetingReminder {
+                meeting_id: meeting.id.clone(),
+            },
+            urgent: false,
+        };
+        let delivery = notify(
+            app_handle,
+            notification,
+            Some(NotificationCategory::MeetingReminders),
+        )
+        .await?;
+        if !matches!(delivery, Delivery::Toast | Delivery::Frontend) {
+            continue;
+        }
+
+        // Telemetry tunes the main offset, so only its reminder counts
+        if stage == Some(preferences.reminder_offset_minutes) {
+            if let Err(e) = supabase
+                .record_reminder_fired(&user_id, &meeting.id, preferences.reminder_offset_minutes)
+                .await
+            {
+                tracing::warn!("Failed to record reminder: {}", e);
+            }
+        }
+        if is_organizer && stage == Some(last_offset) {
+            if let Err(e) = supabase.set_meeting_reminder_sent(&meeting.id, true).await {
+                tracing::warn!("Failed to mark meeting reminder sent: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Tell the organizer which attendees let an RSVP deadline pass without
+/// answering, once per meeting and deadline
+async fn check_rsvp_deadlines(app_handle: &AppHandle) -> Result<()> {
+    let Some((user_id, _)) = current_user(app_handle).await else {
+        return Ok(());
+    };
+    let app_state = app_handle.state::<AppState>();
+    let Some(ref supabase) = app_state.supabase else {
+        return Ok(());
+    };
+
+    let missed = supabase.get_missed_rsvp_deadlines(&user_id).await?;
+    if missed.is_empty() {
+        return Ok(());
+    }
+
+    let mut user_ids: Vec<String> = missed.iter().map(|m| m.user_id.clone()).collect();
+    user_ids.sort();
+    user_ids.dedup();
+    let names: HashMap<String, String> = supabase
+        .get_user_profiles(&user_ids)
+        .await?
+        .into_iter()
+        .filter_map(|p| p.display_name.map(|name| (p.user_id, name)))
+        .collect();
+
+    // Rows come ordered by deadline; keep meetings in that order
+    let mut by_meeting: Vec<(String, String, Vec<String>)> = Vec::new();
+    for row in missed {
+        match by_meeting
+            .iter_mut()
+            .find(|(id, _, _)| *id == row.meeting_id)
+        {
+            Some((_, _, attendees)) => attendees.push(row.user_id),
+            None => by_meeting.push((row.meeting_id, row.meetings.title, vec![row.user_id])),
+        }
+    }
+
+    for (meeting_id, title, attendee_ids) in by_meeting {
+        let attendee_names: Vec<String> = attendee_ids
+            .iter()
+            .map(|id| names.get(id).cloned().unwrap_or_else(|| id.clone()))
+            .collect();
+        let notification = Notification {
+            title,
+            body: notifications::format_missed_rsvp(&attendee_names),
+            kind: NotificationKind::MeetingReminder {
+                meeting_id: meeting_id.clone(),
+            },
+            urgent: false,
+        };
+        notify(
+            app_handle,
+            notification,
+            Some(NotificationCategory::MeetingReminders),
+        )
+        .await?;
+
+        // Marked whether shown or silenced, so it isn't raised every poll
+        if let Err(e) = supabase
+            .set_rsvp_deadlines_notified(&meeting_id, &attendee_ids)
+            .await
+        {
+            tracing::warn!("Failed to mark RSVP deadlines notified: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Emit `meeting:starting` for a meeting whose session started, unless it
+/// was already announced
+pub(crate) fn announce_meeting_start(app_handle: &AppHandle, started: &MeetingStarting) {
+    let first = app_handle
+        .state::<NotificationState>()
+        .announced
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert((started.meeting_id.clone(), started.session_id.clone()));
+    if !first {
+        return;
+    }
+    if let Err(e) = app_handle.emit("meeting:starting", started) {
+        tracing::error!("Failed to emit meeting starting event: {}", e);
+    }
+}
+
+/// Start the organizer's auto-start meetings that are due, and announce the
+/// meetings of the user that started
+async fn check_meeting_starts(app_handle: &AppHandle) -> Result<()> {
+    let Some((user_id, _)) = current_user(app_handle).await else {
+        return Ok(());
+    };
+    let app_state = app_handle.state::<AppState>();
+    let Some(ref supabase) = app_state.supabase else {
+        return Ok(());
+    };
+
+    let now = chrono::Utc::now();
+    let meetings = supabase
+        .get_meetings_in_range(
+            &user_id,
+            &(now - chrono::Duration::minutes(MEETING_START_WINDOW_MINUTES)).to_rfc3339(),
+            &(now + chrono::Duration::minutes(MEETING_START_WINDOW_MINUTES)).to_rfc3339(),
+        )
+        .await?;
+
+    for meeting in meetings {
+        let due = chrono::DateTime::parse_from_rfc3339(&meeting.scheduled_at)
+            .is_ok_and(|start| start.with_timezone(&chrono::Utc) <= now);
+        let started = match (meeting.session_id.clone(), meeting.join_code.clone()) {
+            (Some(session_id), Some(join_code)) if meeting.status == "ongoing" => MeetingStarting {
+                meeting_id: meeting.id,
+                title: meeting.title,
+                session_id,
+                join_code,
+                meeting_url: meeting.meeting_url,
+            },
+            _ if due
+                && meeting.auto_start
+                && meeting.status == "scheduled"
+                && meeting.organizer_id == user_id =>
+            {
+                match calendar::start_meeting_session(&app_state, &user_id, &meeting).await {
+                    Ok(started) => started,
+                    Err(e) => {
+                        tracing::warn!("Failed to auto-start meeting {}: {}", meeting.id, e);
+                        continue;
+                    }
+                }
+            }
+            _ => continue,
+        };
+        announce_meeting_start(app_handle, &started);
+    }
+    Ok(())
+}
+
+/// Check for meeting reminders, missed RSVP deadlines and meetings starting
+/// from startup on
+pub async fn watch_meeting_reminders(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(REMINDER_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = check_reminders(&app_handle).await {
+            tracing::debug!("Failed to check meeting reminders: {}", e);
+        }
+        if let Err(e) = check_rsvp_deadlines(&app_handle).await {
+            tracing::debug!("Failed to check RSVP deadlines: {}", e);
+        }
+        if let Err(e) = check_meeting_starts(&app_handle).await {
+            tracing::debug!("Failed to check meetings starting: {}", e);
+        }
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Show a notification from the frontend, under the category of its kind
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn show_notification(
+    notification: Notification,
+    app_handle: AppHandle,
+) -> Result<Delivery> {
+    let category = notification.kind.category();
+    notify(&app_handle, notification, category).await
+}
+
+/// Get whether notifications are held back and how they are shown
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_notification_status() -> Result<NotificationStatus> {
+    Ok(NotificationStatus {
+        focus_assist_active: notifications::focus_assist_active(),
+        native_toasts: notifications::native_toasts(),
+    })
+}
+
+/// Get the notification settings
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_notification_settings(
+    notification_state: State<'_, NotificationState>,
+) -> Result<NotificationSettings> {
+    Ok(notification_state.settings())
+}
+
+/// Change the notification settings, kept across restarts
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_notification_settings(
+    settings: NotificationSettings,
+    notification_state: State<'_, NotificationState>,
+    app_handle: AppHandle,
+) -> Result<NotificationSettings> {
+    if let Some(ref quiet_hours) = settings.quiet_hours {
+        quiet_hours.validate()?;
+    }
+    settings.save(&settings_path(&app_handle)?)?;
+    *notification_state
+        .settings
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = settings.clone();
+    Ok(settings)
+}
+
+/// Remind the user of a meeting again in `minutes` (5 by default), at the
+/// latest a minute before it starts; returns when
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn snooze_meeting_reminder(
+    meeting_id: String,
+    minutes: Option<i32>,
+    notification_state: State<'_, NotificationState>,
+    app_state: State<'_, AppState>,
+) -> Result<String> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let minutes = minutes.unwrap_or(DEFAULT_SNOOZE_MINUTES);
+    if minutes <= 0 {
+        return Err(Error::Parse("Snooze must be positive".to_string()));
+    }
+
+    let meeting = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Meeting not found".to_string()))?;
+    let start = DateTime::parse_from_rfc3339(&meeting.scheduled_at)
+        .map_err(|e| Error::Parse(format!("Invalid date: {}", e)))?
+        .with_timezone(&Utc);
+
+    let now = Utc::now();
+    let until =
+        (now + chrono::Duration::minutes(minutes.into())).min(start - chrono::Duration::minutes(1));
+    if until <= now {
+        return Err(Error::Parse("Meeting is about to start".to_string()));
+    }
+
+    notification_state
+        .snoozed
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(meeting_id.clone(), until);
+
+    tracing::debug!("Snoozed reminder of meeting {} until {}", meeting_id, until);
+    Ok(until.to_rfc3339())
+}
+
+/// Act on a notification the frontend displayed, with the same arguments as toasts
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn handle_notification_action(
+    arguments: String,
+    reply: Option<String>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let action = notifications::parse_arguments(&arguments, reply)
+        .ok_or_else(|| Error::Parse(format!("Invalid notification action: {}", arguments)))?;
+    perform_action(&app_handle, action).await
+}
+
+/// Carry out a notification action, then tell the frontend so it can refresh
+async fn perform_action(app_handle: &AppHandle, action: NotificationAction) -> Result<()> {
+    let app_state = app_handle.state::<AppState>();
+    let user_id = {
+        let inner = app_state.inner.read().await;
+        inner
+            .user
+            .as_ref()
+            .map(|u| u.id.clone())
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?
+    };
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    match &action {
+        NotificationAction::Reply {
+            conversation_id,
+            text,
+        } => {
+            let row = supabase
+                .create_message(conversation_id, &user_id, text, "text")
+                .await?;
+            let mut cache = app_state.cache.messages.write().await;
+            cache.append_messages(conversation_id, vec![row]);
+        }
+        NotificationAction::AcceptMeeting { meeting_id } => {
+            supabase
+                .update_attendee_response(meeting_id, &user_id, "accepted")
+                .await?;
+        }
+        NotificationAction::DeclineMeeting { meeting_id } => {
+            supabase
+                .update_attendee_response(meeting_id, &user_id, "declined")
+                .await?;
+        }
+        NotificationAction::Open { .. } => {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                window.unminimize()?;
+                window.show()?;
+                window.set_focus()?;
+            }
+        }
+    }
+
+    tracing::info!("Notification action: {:?}", action);
+    app_handle.emit("notification:action", &action)?;
+    Ok(())
+}
@@ -0,0 +1,460 @@
+//! Outlook calendar commands
+//!
+//! Mirrors the Google Calendar integration over Microsoft Graph, for
+//! Office 365 and Outlook.com accounts: OAuth through the Microsoft identity
+//! platform's `common` endpoint, meetings pushed as events of the user's
+//! default calendar, and events read back for import. Times are exchanged
+//! in UTC.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::state::AppState;
+use crate::supabase::{OutlookTokensRow, SupabaseClient};
+use crate::{Error, Result};
+
+const AUTHORIZE_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/authorize";
+const TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+const GRAPH_URL: &str = "https://graph.microsoft.com/v1.0";
+const REDIRECT_URI: &str = "http://localhost:3000/auth/microsoft/callback";
+const SCOPE: &str = "offline_access User.Read Calendars.ReadWrite";
+
+/// Graph's date-time format, without offset
+const GRAPH_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookCalendarStatus {
+    pub connected: bool,
+    pub email: Option<String>,
+    pub sync_enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookEvent {
+    pub id: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(rename = "bodyPreview", default)]
+    pub body_preview: Option<String>,
+    pub start: OutlookEventDateTime,
+    pub end: OutlookEventDateTime,
+    #[serde(rename = "isAllDay", default)]
+    pub is_all_day: bool,
+    #[serde(rename = "isCancelled", default)]
+    pub is_cancelled: bool,
+    #[serde(default)]
+    pub attendees: Vec<OutlookEventAttendee>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookEventDateTime {
+    #[serde(rename = "dateTime")]
+    pub date_time: String,
+    #[serde(rename = "timeZone")]
+    pub time_zone: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookEventAttendee {
+    #[serde(rename = "emailAddress")]
+    pub email_address: OutlookEmailAddress,
+    pub status: Option<OutlookResponseStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookEmailAddress {
+    pub address: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookResponseStatus {
+    pub response: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+async fn get_current_user_id(app_state: &AppState) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    Ok(user.id.clone())
+}
+
+fn get_supabase(app_state: &AppState) -> Result<&SupabaseClient> {
+    app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))
+}
+
+fn client_credentials() -> Result<(String, String)> {
+    let client_id = std::env::var("MICROSOFT_CLIENT_ID")
+        .map_err(|_| Error::Config("MICROSOFT_CLIENT_ID not configured".to_string()))?;
+    let client_secret = std::env::var("MICROSOFT_CLIENT_SECRET")
+        .map_err(|_| Error::Config("MICROSOFT_CLIENT_SECRET not configured".to_string()))?;
+    Ok((client_id, client_secret))
+}
+
+/// Exchange a code or refresh token at the token endpoint
+async fn request_tokens(grant: &[(&str, &str)]) -> Result<TokenResponse> {
+    let (client_id, client_secret) = client_credentials()?;
+    let mut form = vec![
+        ("client_id", client_id.as_str()),
+        ("client_secret", client_secret.as_str()),
+        ("scope", SCOPE),
+    ];
+    form.extend_from_slice(grant);
+
+    let response = reqwest::Client::new()
+        .post(TOKEN_URL)
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to request tokens: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(Error::External(format!("Microsoft OAuth error: {}", error)));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| Error::Parse(format!("Failed to parse token response: {}", e)))
+}
+
+/// Email of the signed-in Microsoft account
+async fn get_outlook_email(access_token: &str) -> Result<String> {
+    let response = reqwest::Client::new()
+        .get(format!("{}/me", GRAPH_URL))
+        .bearer_auth(access_token)
+        .query(&[("$select", "mail,userPrincipalName")])
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to get user info: {}", e)))?;
+
+    #[derive(Deserialize)]
+    struct UserInfo {
+        mail: Option<String>,
+        #[serde(rename = "userPrincipalName")]
+        user_principal_name: Option<String>,
+    }
+
+    let user_info: UserInfo = response
+        .json()
+        .await
+        .map_err(|e| Error::Parse(format!("Failed to parse user info: {}", e)))?;
+
+    // Personal accounts leave `mail` empty
+    user_info
+        .mail
+        .or(user_info.user_principal_name)
+        .ok_or_else(|| Error::External("Microsoft account has no email".to_string()))
+}
+
+/// Access token of the user, refreshed when it expires within 5 minutes
+///
+/// Microsoft may rotate the refresh token, so the new one is saved too.
+async fn refresh_token_if_needed(
+    supabase: &SupabaseClient,
+    user_id: &str,
+    tokens: &OutlookTokensRow,
+) -> Result<String> {
+    let expires_at = chrono::DateTime::parse_from_rfc3339(&tokens.expires_at)
+        .map_err(|e| Error::Parse(format!("Invalid expiration: {}", e)))?;
+    if expires_at >= chrono::Utc::now() + chrono::Duration::minutes(5) {
+        return Ok(tokens.access_token.clone());
+    }
+
+    let refreshed = request_tokens(&[
+        ("grant_type", "refresh_token"),
+        ("refresh_token", tokens.refresh_token.as_str()),
+    ])
+    .await
+    .map_err(|_| {
+        Error::External("Failed to refresh Microsoft token - please reconnect".to_string())
+    })?;
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(refreshed.expires_in);
+    supabase
+        .save_outlook_tokens(
+            user_id,
+            &refreshed.access_token,
+            refreshed
+                .refresh_token
+                .as_deref()
+                .unwrap_or(&tokens.refresh_token),
+            &expires_at.to_rfc3339(),
+            tokens.email.as_deref(),
+        )
+        .await?;
+
+    Ok(refreshed.access_token)
+}
+
+/// Connected user's access token, refreshed if needed
+async fn connected_access_token(supabase: &SupabaseClient, user_id: &str) -> Result<String> {
+    let tokens = supabase
+        .get_outlook_tokens(user_id)
+        .await?
+        .ok_or_else(|| Error::External("Outlook calendar not connected".to_string()))?;
+    refresh_token_if_needed(supabase, user_id, &tokens).await
+}
+
+fn graph_datetime(datetime: chrono::DateTime<chrono::Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "dateTime": datetime.format(GRAPH_DATETIME_FORMAT).to_string(),
+        "timeZone": "UTC",
+    })
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get OAuth URL to start Outlook calendar authorization
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_outlook_auth(app_state: State<'_, AppState>) -> Result<String> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let (client_id, _) = client_credentials()?;
+
+    Ok(format!(
+        "{}?client_id={}&redirect_uri={}&response_type=code&response_mode=query&scope={}&prompt=select_account&state={}",
+        AUTHORIZE_URL,
+        urlencoding::encode(&client_id),
+        urlencoding::encode(REDIRECT_URI),
+        urlencoding::encode(SCOPE),
+        user_id
+    ))
+}
+
+/// Complete OAuth flow with authorization code
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn complete_outlook_auth(code: String, app_state: State<'_, AppState>) -> Result<()> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    let tokens = request_tokens(&[
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", REDIRECT_URI),
+    ])
+    .await?;
+    let refresh_token = tokens.refresh_token.ok_or_else(|| {
+        Error::External("No refresh token received - try authorizing again".to_string())
+    })?;
+
+    let email = get_outlook_email(&tokens.access_token).await.ok();
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(tokens.expires_in);
+
+    supabase
+        .save_outlook_tokens(
+            &user_id,
+            &tokens.access_token,
+            &refresh_token,
+            &expires_at.to_rfc3339(),
+            email.as_deref(),
+        )
+        .await?;
+
+    tracing::info!("Outlook calendar connected for user {}", user_id);
+    Ok(())
+}
+
+/// Disconnect the Outlook calendar
+///
+/// Microsoft has no endpoint to revoke a single token; the user can remove
+/// the app's access from their account settings.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn disconnect_outlook(app_state: State<'_, AppState>) -> Result<()> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    supabase.delete_outlook_tokens(&user_id).await?;
+
+    tracing::info!("Outlook calendar disconnected for user {}", user_id);
+    Ok(())
+}
+
+/// Get Outlook calendar connection status
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_outlook_status(app_state: State<'_, AppState>) -> Result<OutlookCalendarStatus> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    Ok(match supabase.get_outlook_tokens(&user_id).await? {
+        Some(tokens) => OutlookCalendarStatus {
+            connected: true,
+            email: tokens.email,
+            sync_enabled: tokens.sync_enabled.unwrap_or(true),
+        },
+        None => OutlookCalendarStatus {
+            connected: false,
+            email: None,
+            sync_enabled: false,
+        },
+    })
+}
+
+/// Sync a meeting to the Outlook calendar, returning its event ID
+///
+/// Attendees are listed in the description rather than invited, since
+/// their emails aren't known to the app.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn sync_meeting_to_outlook(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<String> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+    let access_token = connected_access_token(supabase, &user_id).await?;
+
+    let meeting = supabase
+        .get_meeting(&meeting_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Meeting not found".to_string()))?;
+    let attendees = supabase.get_meeting_attendees(&meeting_id).await?;
+
+    let start = chrono::DateTime::parse_from_rfc3339(&meeting.scheduled_at)
+        .map_err(|e| Error::Parse(format!("Invalid date: {}", e)))?
+        .with_timezone(&chrono::Utc);
+    let end = start + chrono::Duration::minutes(meeting.duration_minutes.into());
+
+    let mut description = meeting.description.clone().unwrap_or_default();
+    if !attendees.is_empty() {
+        description.push_str(&format!(
+            "\n\nAttendees: {}",
+            attendees
+                .iter()
+                .map(|a| a.display_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    let event_body = serde_json::json!({
+        "subject": meeting.title,
+        "body": {
+            "contentType": "text",
+            "content": description.trim(),
+        },
+        "start": graph_datetime(start),
+        "end": graph_datetime(end),
+    });
+
+    let client = reqwest::Client::new();
+    let request = match meeting.outlook_event_id {
+        Some(ref event_id) => client.patch(format!("{}/me/events/{}", GRAPH_URL, event_id)),
+        None => client.post(format!("{}/me/events", GRAPH_URL)),
+    };
+    let response = request
+        .bearer_auth(&access_token)
+        .json(&event_body)
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to sync event: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(Error::External(format!(
+            "Outlook calendar error: {}",
+            error
+        )));
+    }
+
+    if let Some(event_id) = meeting.outlook_event_id {
+        return Ok(event_id);
+    }
+
+    #[derive(Deserialize)]
+    struct EventResponse {
+        id: String,
+    }
+
+    let event: EventResponse = response
+        .json()
+        .await
+        .map_err(|e| Error::Parse(format!("Failed to parse response: {}", e)))?;
+    supabase
+        .update_meeting_outlook_id(&meeting_id, &event.id)
+        .await?;
+
+    Ok(event.id)
+}
+
+/// Import events from the Outlook calendar
+///
+/// Recurring events are expanded into their occurrences within the range.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn import_from_outlook(
+    start_date: String,
+    end_date: String,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<OutlookEvent>> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+    let access_token = connected_access_token(supabase, &user_id).await?;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/me/calendarView", GRAPH_URL))
+        .bearer_auth(&access_token)
+        .header("Prefer", "outlook.timezone=\"UTC\"")
+        .query(&[
+            ("startDateTime", start_date.as_str()),
+            ("endDateTime", end_date.as_str()),
+            ("$orderby", "start/dateTime"),
+            ("$top", "250"),
+        ])
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to fetch events: {}", e)))?;
+
+    if !response.status().is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(Error::External(format!(
+            "Outlook calendar error: {}",
+            error
+        )));
+    }
+
+    #[derive(Deserialize)]
+    struct EventsResponse {
+        value: Vec<OutlookEvent>,
+    }
+
+    let events: EventsResponse = response
+        .json()
+        .await
+        .map_err(|e| Error::Parse(format!("Failed to parse events: {}", e)))?;
+
+    Ok(events.value)
+}
+
+/// Toggle sync with the Outlook calendar
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn toggle_outlook_sync(enabled: bool, app_state: State<'_, AppState>) -> Result<()> {
+    let user_id = get_current_user_id(&app_state).await?;
+    let supabase = get_supabase(&app_state)?;
+
+    supabase
+        .update_outlook_sync_enabled(&user_id, enabled)
+        .await
+}
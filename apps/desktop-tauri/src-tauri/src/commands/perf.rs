@@ -0,0 +1,22 @@
+//! Command performance commands
+
+use tauri::State;
+
+use crate::perf::{CommandPerfStats, PerfState};
+use crate::Result;
+
+/// Get per-command timings, slowest (by total time) first
+#[tauri::command]
+pub async fn get_command_perf_stats(
+    perf_state: State<'_, PerfState>,
+) -> Result<Vec<CommandPerfStats>> {
+    Ok(perf_state.snapshot())
+}
+
+/// Clear the collected command timings
+#[tauri::command]
+pub async fn reset_command_perf_stats(perf_state: State<'_, PerfState>) -> Result<()> {
+    perf_state.reset();
+    tracing::info!("Command performance stats reset");
+    Ok(())
+}
@@ -0,0 +1,397 @@
+//! Scheduling poll commands
+//!
+//! Doodle-style polls posted in a chat conversation: the creator proposes
+//! candidate slots, participants vote from the poll message, and the meeting
+//! is created in the winning slot once everyone voted or the poll is closed.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::calendar::extract_year_month;
+use crate::commands::chat::{ChatState, Message};
+use crate::state::AppState;
+use crate::supabase::SupabaseClient;
+use crate::utils::poll::{self, OptionTally};
+use crate::{Error, Result};
+
+/// Maximum number of candidate slots per poll
+const MAX_POLL_SLOTS: usize = 20;
+
+// ==========================================
+// Request/Response Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePollParams {
+    pub title: String,
+    pub description: Option<String>,
+    pub duration_minutes: i32,
+    /// Candidate start times (RFC 3339)
+    pub slots: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollSummary {
+    pub id: String,
+    pub conversation_id: String,
+    pub created_by: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub duration_minutes: i32,
+    pub status: String,
+    pub meeting_id: Option<String>,
+    pub options: Vec<OptionTally>,
+    pub created_at: Option<String>,
+    pub closed_at: Option<String>,
+}
+
+/// Content of a chat message of type "poll"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PollMessageContent {
+    poll_id: String,
+    title: String,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+async fn load_poll(supabase: &SupabaseClient, poll_id: &str) -> Result<PollSummary> {
+    let row = supabase
+        .get_scheduling_poll(poll_id)
+        .await?
+        .ok_or_else(|| Error::Database(format!("Poll not found: {}", poll_id)))?;
+
+    let options: Vec<(String, String)> = supabase
+        .get_poll_options(poll_id)
+        .await?
+        .into_iter()
+        .map(|o| (o.id, o.starts_at))
+        .collect();
+    let votes: Vec<(String, String)> = supabase
+        .get_poll_votes(poll_id)
+        .await?
+        .into_iter()
+        .map(|v| (v.option_id, v.user_id))
+        .collect();
+
+    Ok(PollSummary {
+        id: row.id,
+        conversation_id: row.conversation_id,
+        created_by: row.created_by,
+        title: row.title,
+        description: row.description,
+        duration_minutes: row.duration_minutes,
+        status: row.status,
+        meeting_id: row.meeting_id,
+        options: poll::tally_votes(&options, &votes),
+        created_at: row.created_at,
+        closed_at: row.closed_at,
+    })
+}
+
+/// Broadcast the poll to the conversation and emit it locally
+async fn publish_poll(chat_state: &ChatState, app_handle: &AppHandle, summary: &PollSummary) {
+    let chat_inner = chat_state.inner.read().await;
+    if let Some(ref realtime) = chat_inner.realtime {
+        let _ = realtime
+            .broadcast_poll_update(&summary.conversation_id, summary)
+            .await;
+    }
+
+    let _ = app_handle.emit("chat:poll-update", summary);
+}
+
+/// Save a message to the poll's conversation
+async fn post_message(
+    supabase: &SupabaseClient,
+    conversation_id: &str,
+    sender_id: &str,
+    sender_name: String,
+    content: &str,
+    message_type: &str,
+) -> Result<Message> {
+    let row = supabase
+        .create_message(conversation_id, sender_id, content, message_type)
+        .await?;
+
+    Ok(Message {
+        id: row.id,
+        conversation_id: row.conversation_id,
+        sender_id: row.sender_id,
+        sender_name,
+        content: row.content,
+        message_type: row.message_type,
+        created_at: row.created_at,
+        attachments: row.attachments,
+        parent_message_id: row.parent_message_id,
+        reply_count: row.reply_count,
+        last_reply_at: row.last_reply_at,
+        session_id: row.session_id,
+    })
+}
+
+/// Broadcast a message to the conversation and emit it locally
+async fn publish_message(chat_state: &ChatState, app_handle: &AppHandle, message: &Message) {
+    let chat_inner = chat_state.inner.read().await;
+    if let Some(ref realtime) = chat_inner.realtime {
+        let _ = realtime
+            .broadcast_message(&message.conversation_id, message)
+            .await;
+    }
+
+    let _ = app_handle.emit("chat:new-message", message);
+}
+
+/// Create the meeting in the winning slot and close the poll.
+///
+/// The database function does the writes so the last voter can finalize a
+/// poll they did not create.
+async fn finalize_poll(
+    supabase: &SupabaseClient,
+    app_state: &AppState,
+    summary: &PollSummary,
+) -> Result<Option<String>> {
+    let meeting_id = supabase.finalize_scheduling_poll(&summary.id).await?;
+
+    // Invalidate relevant caches
+    if meeting_id.is_some() {
+        let mut cache = app_state.cache.meetings.write().await;
+        if let Some((year, month)) = poll::pick_winner(&summary.options)
+            .and_then(|winner| extract_year_month(&winner.starts_at))
+        {
+            cache.invalidate_month(year, month);
+        }
+        cache.set_upcoming(Vec::new()); // Force refresh on next request
+        tracing::debug!("Cache invalidated after poll {} closed", summary.id);
+    }
+
+    Ok(meeting_id)
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Create a scheduling poll and post it to a conversation
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_scheduling_poll(
+    conversation_id: String,
+    params: CreatePollParams,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    app_handle: AppHandle,
+) -> Result<PollSummary> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+    let user_email = user.email.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    if params.title.trim().is_empty() {
+        return Err(Error::Parse("Poll title is required".to_string()));
+    }
+    if params.duration_minutes <= 0 {
+        return Err(Error::Parse("Duration must be positive".to_string()));
+    }
+    if params.slots.is_empty() || params.slots.len() > MAX_POLL_SLOTS {
+        return Err(Error::Parse(format!(
+            "A poll needs between 1 and {} slots",
+            MAX_POLL_SLOTS
+        )));
+    }
+    for slot in &params.slots {
+        chrono::DateTime::parse_from_rfc3339(slot)
+            .map_err(|e| Error::Parse(format!("Invalid slot '{}': {}", slot, e)))?;
+    }
+
+    let row = supabase
+        .create_scheduling_poll(
+            &conversation_id,
+            &user_id,
+            params.title.trim(),
+            params.description.as_deref(),
+            params.duration_minutes,
+        )
+        .await?;
+    supabase.create_poll_options(&row.id, &params.slots).await?;
+
+    let content = serde_json::to_string(&PollMessageContent {
+        poll_id: row.id.clone(),
+        title: row.title.clone(),
+    })
+    .map_err(|e| Error::Parse(e.to_string()))?;
+    let message = post_message(
+        supabase,
+        &conversation_id,
+        &user_id,
+        user_email,
+        &content,
+        "poll",
+    )
+    .await?;
+    publish_message(&chat_state, &app_handle, &message).await;
+
+    let summary = load_poll(supabase, &row.id).await?;
+    publish_poll(&chat_state, &app_handle, &summary).await;
+
+    tracing::info!("Scheduling poll {} created in {}", summary.id, conversation_id);
+    Ok(summary)
+}
+
+/// Get a scheduling poll with its current tallies
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_scheduling_poll(
+    poll_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<PollSummary> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    load_poll(supabase, &poll_id).await
+}
+
+/// Vote for one or more slots, replacing the user's previous votes.
+///
+/// The poll closes automatically once every participant has voted.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn vote_on_poll(
+    poll_id: String,
+    option_ids: Vec<String>,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    app_handle: AppHandle,
+) -> Result<PollSummary> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let summary = load_poll(supabase, &poll_id).await?;
+    if summary.status != "open" {
+        return Err(Error::Database("Poll is closed".to_string()));
+    }
+    if let Some(unknown) = option_ids
+        .iter()
+        .find(|id| !summary.options.iter().any(|o| &o.option_id == *id))
+    {
+        return Err(Error::Parse(format!("Unknown poll option: {}", unknown)));
+    }
+
+    supabase
+        .set_poll_votes(&poll_id, &user_id, &option_ids)
+        .await?;
+
+    let mut summary = load_poll(supabase, &poll_id).await?;
+
+    let participant_ids: Vec<String> = supabase
+        .get_conversation_participants(&summary.conversation_id)
+        .await?
+        .into_iter()
+        .map(|p| p.user_id)
+        .collect();
+    if poll::everyone_voted(&participant_ids, &summary.options) {
+        tracing::info!("Everyone voted on poll {}, closing", poll_id);
+        if let Some(meeting_id) = finalize_poll(supabase, &app_state, &summary).await? {
+            let message = post_message(
+                supabase,
+                &summary.conversation_id,
+                &user_id,
+                "System".to_string(),
+                &format!("Poll \"{}\" closed, meeting scheduled", summary.title),
+                "system",
+            )
+            .await?;
+            publish_message(&chat_state, &app_handle, &message).await;
+            tracing::info!("Meeting {} created from poll {}", meeting_id, poll_id);
+        }
+        summary = load_poll(supabase, &poll_id).await?;
+    }
+
+    publish_poll(&chat_state, &app_handle, &summary).await;
+    Ok(summary)
+}
+
+/// Close a poll and schedule the meeting in the winning slot (creator only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn close_scheduling_poll(
+    poll_id: String,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    app_handle: AppHandle,
+) -> Result<PollSummary> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let summary = load_poll(supabase, &poll_id).await?;
+    if summary.created_by != user_id {
+        return Err(Error::Auth("Only the poll creator can close it".to_string()));
+    }
+    if summary.status != "open" {
+        return Err(Error::Database("Poll is already closed".to_string()));
+    }
+
+    let content = match finalize_poll(supabase, &app_state, &summary).await? {
+        Some(_) => format!("Poll \"{}\" closed, meeting scheduled", summary.title),
+        None => format!("Poll \"{}\" closed without votes", summary.title),
+    };
+    let message = post_message(
+        supabase,
+        &summary.conversation_id,
+        &user_id,
+        "System".to_string(),
+        &content,
+        "system",
+    )
+    .await?;
+    publish_message(&chat_state, &app_handle, &message).await;
+
+    let summary = load_poll(supabase, &poll_id).await?;
+    publish_poll(&chat_state, &app_handle, &summary).await;
+
+    tracing::info!("Scheduling poll {} closed", poll_id);
+    Ok(summary)
+}
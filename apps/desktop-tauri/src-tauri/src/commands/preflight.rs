@@ -0,0 +1,45 @@
+use tauri::State;
+
+use crate::commands::stream::{self, StreamState};
+use crate::preflight::{self, PreflightReport, SessionType};
+use crate::state::AppState;
+use crate::{Error, Result};
+
+/// Check devices, permissions and connectivity before a session starts
+///
+/// Returns a checklist rather than an error, so the UI can show everything
+/// that needs fixing at once; `ready` is false when a check failed.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn run_preflight(
+    session_type: SessionType,
+    source_id: Option<String>,
+    app_state: State<'_, AppState>,
+    stream_state: State<'_, StreamState>,
+) -> Result<PreflightReport> {
+    let local_checks = tokio::task::spawn_blocking(move || {
+        preflight::run_local_checks(session_type, source_id.as_deref())
+    });
+
+    // Signed-in users get short-lived TURN credentials, as a session would
+    let is_authenticated = app_state.inner.read().await.user.is_some();
+    let ice_servers = {
+        let mut state = stream_state.inner.write().await;
+        if let (true, Some(supabase)) = (is_authenticated, app_state.supabase.as_ref()) {
+            if let Err(e) = stream::refresh_turn_credentials(&mut state, supabase).await {
+                tracing::debug!("Preflight without fetched TURN credentials: {}", e);
+            }
+        }
+        state.effective_ice_servers()
+    };
+    let turn_check = preflight::check_turn(&ice_servers).await;
+
+    let mut checks = local_checks
+        .await
+        .map_err(|e| Error::Capture(format!("Failed to run preflight checks: {}", e)))?;
+    checks.push(turn_check);
+
+    let report = PreflightReport::new(checks);
+    tracing::info!("Preflight for {:?}: ready = {}", session_type, report.ready);
+    Ok(report)
+}
@@ -0,0 +1,248 @@
+//! Reminder telemetry commands
+//!
+//! Records when a meeting reminder fired and when the user actually joined,
+//! so the reminder offset can be tuned per user (optionally automatically).
+//! Earlier reminders (an hour or a day before) can be added on top of it.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::state::AppState;
+use crate::supabase::SupabaseClient;
+use crate::utils::reminders::{self, ReminderEffectiveness, ReminderSample};
+use crate::{Error, Result};
+
+/// Offset used when the user has no stored preference
+const DEFAULT_REMINDER_OFFSET_MINUTES: i32 = 15;
+
+/// Number of recent reminders considered for statistics
+const TELEMETRY_WINDOW: u32 = 50;
+
+/// Earliest reminder allowed, a week before the meeting
+const MAX_REMINDER_OFFSET_MINUTES: i32 = 7 * 24 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderPreferences {
+    pub reminder_offset_minutes: i32,
+    pub auto_adjust: bool,
+    /// Earlier reminders, in minutes before the meeting (e.g. 60, 1440)
+    #[serde(default)]
+    pub extra_offsets_minutes: Vec<i32>,
+}
+
+impl ReminderPreferences {
+    /// Every reminder offset, shortest first
+    pub(crate) fn offsets(&self) -> Vec<i32> {
+        let mut offsets = self.extra_offsets_minutes.clone();
+        offsets.push(self.reminder_offset_minutes);
+        offsets.sort_unstable();
+        offsets.dedup();
+        offsets
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+pub(crate) async fn load_preferences(
+    supabase: &SupabaseClient,
+    user_id: &str,
+) -> Result<ReminderPreferences> {
+    Ok(supabase
+        .get_reminder_preferences(user_id)
+        .await?
+        .map(|row| ReminderPreferences {
+            reminder_offset_minutes: row.reminder_offset_minutes,
+            auto_adjust: row.auto_adjust.unwrap_or(false),
+            extra_offsets_minutes: row.extra_offsets_minutes,
+        })
+        .unwrap_or(ReminderPreferences {
+            reminder_offset_minutes: DEFAULT_REMINDER_OFFSET_MINUTES,
+            auto_adjust: false,
+            extra_offsets_minutes: Vec::new(),
+        }))
+}
+
+async fn compute_for_user(
+    supabase: &SupabaseClient,
+    user_id: &str,
+) -> Result<ReminderEffectiveness> {
+    let preferences = load_preferences(supabase, user_id).await?;
+    let samples: Vec<ReminderSample> = supabase
+        .get_reminder_telemetry(user_id, TELEMETRY_WINDOW)
+        .await?
+        .into_iter()
+        .map(|row| ReminderSample {
+            fired_at: row.fired_at,
+            joined_at: row.joined_at,
+        })
+        .collect();
+
+    reminders::compute_effectiveness(
+        &samples,
+        preferences.reminder_offset_minutes,
+        preferences.auto_adjust,
+    )
+}
+
+/// Record that the user joined a meeting, then auto-adjust the offset if enabled.
+///
+/// Telemetry must never block joining, so failures are only logged.
+pub(crate) async fn record_meeting_join(supabase: &SupabaseClient, user_id: &str, meeting_id: &str) {
+    let joined_at = chrono::Utc::now().to_rfc3339();
+    if let Err(e) = supabase
+        .record_reminder_join(user_id, meeting_id, &joined_at)
+        .await
+    {
+        tracing::warn!("Failed to record reminder join: {}", e);
+        return;
+    }
+
+    match compute_for_user(supabase, user_id).await {
+        Ok(stats)
+            if stats.auto_adjust
+                && stats.suggested_offset_minutes != stats.current_offset_minutes =>
+        {
+            match supabase
+                .save_reminder_preferences(user_id, stats.suggested_offset_minutes, true, None)
+                .await
+            {
+                Ok(()) => tracing::info!(
+                    "Reminder offset auto-adjusted from {} to {} minutes",
+                    stats.current_offset_minutes,
+                    stats.suggested_offset_minutes
+                ),
+                Err(e) => tracing::warn!("Failed to auto-adjust reminder offset: {}", e),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to compute reminder effectiveness: {}", e),
+    }
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Record that a reminder for a meeting was shown to the user
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn record_reminder_fired(
+    meeting_id: String,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let preferences = load_preferences(supabase, &user_id).await?;
+    supabase
+        .record_reminder_fired(&user_id, &meeting_id, preferences.reminder_offset_minutes)
+        .await?;
+
+    tracing::debug!("Recorded reminder for meeting {}", meeting_id);
+    Ok(())
+}
+
+/// Get how quickly the user joins after reminders and the suggested offset
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_reminder_effectiveness(
+    app_state: State<'_, AppState>,
+) -> Result<ReminderEffectiveness> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    compute_for_user(supabase, &user_id).await
+}
+
+/// Get the user's reminder preferences
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_reminder_preferences(
+    app_state: State<'_, AppState>,
+) -> Result<ReminderPreferences> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    load_preferences(supabase, &user_id).await
+}
+
+/// Set the reminder offset, whether it should be tuned automatically and
+/// the earlier reminders
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_reminder_preferences(
+    preferences: ReminderPreferences,
+    app_state: State<'_, AppState>,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    if preferences.reminder_offset_minutes <= 0 {
+        return Err(Error::Parse("Reminder offset must be positive".to_string()));
+    }
+    if preferences
+        .offsets()
+        .iter()
+        .any(|&offset| offset <= 0 || offset > MAX_REMINDER_OFFSET_MINUTES)
+    {
+        return Err(Error::Parse(format!(
+            "Reminder offsets must be between 1 and {} minutes",
+            MAX_REMINDER_OFFSET_MINUTES
+        )));
+    }
+
+    supabase
+        .save_reminder_preferences(
+            &user_id,
+            preferences.reminder_offset_minutes,
+            preferences.auto_adjust,
+            Some(&preferences.extra_offsets_minutes),
+        )
+        .await
+}
@@ -0,0 +1,264 @@
+//! Remote wipe commands
+//!
+//! The wipe check runs at startup, then periodically, and after each
+//! sign-in, which also registers the device for the user. A flagged device
+//! wipes itself, reports the wipe and emits `app:remote-wipe` so the
+//! frontend drops back to the sign-in screen.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::automation::{self, AutomationState};
+use crate::commands::chat::ChatState;
+use crate::commands::google_calendar;
+use crate::remote_wipe::{self, WipeReport, DEVICE_ID_FILE};
+use crate::secure_storage::{self, CredentialKey};
+use crate::state::AppState;
+use crate::supabase::{PendingDeviceWipeRow, SupabaseClient};
+use crate::{Error, Result};
+
+/// How often a running app checks for a wipe
+const WIPE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Payload of `app:remote-wipe`
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteWipe {
+    pub requested_at: String,
+    pub reason: Option<String>,
+    /// Whether everything was removed
+    pub complete: bool,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+pub(crate) fn device_id_path(app_handle: &AppHandle) -> Result<PathBuf> {
+    Ok(app_handle.path().app_data_dir()?.join(DEVICE_ID_FILE))
+}
+
+/// Secret registered with the device ID, created on first use
+fn device_secret() -> Result<String> {
+    if let Some(secret) = secure_storage::get_credential(CredentialKey::DeviceSecret) {
+        return Ok(secret);
+    }
+    let secret = remote_wipe::generate_device_secret();
+    secure_storage::store_credential(CredentialKey::DeviceSecret, &secret)?;
+    Ok(secret)
+}
+
+/// Sign out, and drop everything held locally: keychain entries, webview
+/// storage, in-memory caches and the cache, config and data directories
+async fn wipe_local_data(app_handle: &AppHandle) -> WipeReport {
+    let mut report = WipeReport::default();
+
+    match secure_storage::clear_session()
+        .and_then(|()| secure_storage::delete_credential(CredentialKey::AutomationToken))
+        .and_then(|()| secure_storage::delete_credential(CredentialKey::DeviceKey))
+        .and_then(|()| secure_storage::delete_credential(CredentialKey::DeviceSecret))
+        .and_then(|()| google_calendar::delete_token_key())
+    {
+        Ok(()) => report.credentials = true,
+        Err(e) => report.failed.push(format!("credentials: {}", e)),
+    }
+    automation::stop(app_handle).await;
+    app_handle
+        .state::<AutomationState>()
+        .inner
+        .write()
+        .await
+        .settings = Default::default();
+
+    let app_state = app_handle.state::<AppState>();
+    {
+        let mut inner = app_state.inner.write().await;
+        inner.user = None;
+        inner.session = None;
+    }
+    if let Some(ref supabase) = app_state.supabase {
+        supabase.set_access_token(None).await;
+    }
+    app_state.cache.invalidate_all().await;
+
+    {
+        let chat_state = app_handle.state::<ChatState>();
+        let mut chat = chat_state.inner.write().await;
+        if let Some(realtime) = chat.realtime.take() {
+            realtime.disconnect().await;
+        }
+        chat.is_connected = false;
+        chat.presence_status = None;
+        if let Some(heartbeat) = chat.heartbeat.take() {
+            heartbeat.abort();
+        }
+    }
+
+    report.browsing_data = true;
+    for (label, window) in app_handle.webview_windows() {
+        if let Err(e) = window.clear_all_browsing_data() {
+            report
+                .failed
+                .push(format!("{} browsing data: {}", label, e));
+            report.browsing_data = false;
+        }
+    }
+
+    let path = app_handle.path();
+    let dirs = [
+        ("cache", path.app_cache_dir()),
+        ("config", path.app_config_dir()),
+        ("data", path.app_data_dir()),
+        ("local data", path.app_local_data_dir()),
+    ];
+    for (name, dir) in dirs {
+        match dir {
+            Ok(dir) => report.remove_dir(name, &dir),
+            Err(e) => report.failed.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    report
+}
+
+/// Wipe this device if an administrator asked for it, returning whether it
+/// was wiped
+async fn wipe_if_requested(app_handle: &AppHandle) -> Result<bool> {
+    let app_state = app_handle.state::<AppState>();
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let id_path = device_id_path(app_handle)?;
+    let device_id = remote_wipe::load_or_create_device_id(&id_path)?;
+    let secret = device_secret()?;
+    let Some(PendingDeviceWipeRow {
+        wipe_requested_at,
+        wipe_reason,
+    }) = supabase
+        .get_pending_device_wipe(&device_id, &secret)
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    tracing::warn!(
+        "Remote wipe of device {} requested at {}, wiping local data",
+        device_id,
+        wipe_requested_at
+    );
+    let report = wipe_local_data(app_handle).await;
+
+    match supabase
+        .complete_device_wipe(&device_id, &secret, &report)
+        .await
+    {
+        Ok(_) => tracing::warn!(
+            "Remote wipe of device {} done, removed {:?}, failed {:?}",
+            device_id,
+            report.removed,
+            report.failed
+        ),
+        Err(e) => {
+            tracing::error!("Failed to record remote wipe: {}", e);
+            if let Err(e) = remote_wipe::save_device_id(&id_path, &device_id).and_then(|()| {
+                secure_storage::store_credential(CredentialKey::DeviceSecret, &secret)
+            }) {
+                tracing::error!("Failed to keep device ID for the wipe report: {}", e);
+            }
+        }
+    }
+
+    let wipe = RemoteWipe {
+        requested_at: wipe_requested_at,
+        reason: wipe_reason,
+        complete: report.is_complete(),
+    };
+    if let Err(e) = app_handle.emit("app:remote-wipe", &wipe) {
+        tracing::error!("Failed to emit remote wipe event: {}", e);
+    }
+    Ok(true)
+}
+
+/// Replace the device ID saved at `path` and its secret with new ones
+fn renew_device_id(path: &Path) -> Result<()> {
+    remote_wipe::save_device_id(path, &uuid::Uuid::new_v4().to_string())?;
+    secure_storage::store_credential(
+        CredentialKey::DeviceSecret,
+        &remote_wipe::generate_device_secret(),
+    )
+}
+
+/// Register the device ID saved at `path`, creating it if there is none;
+/// false when the ID is registered to another user or with another secret
+async fn register_device(supabase: &SupabaseClient, path: &Path, version: &str) -> Result<bool> {
+    let device_id = remote_wipe::load_or_create_device_id(path)?;
+    let secret = device_secret()?;
+    supabase
+        .register_device(&device_id, &secret, std::env::consts::OS, version)
+        .await
+}
+
+/// Register the device for the user who just signed in, then wipe it if it
+/// is flagged; signing in on a wiped device fails. A device ID registered to
+/// another user or with another secret is replaced by a new one with a new
+/// secret, once any wipe pending for it has been carried out.
+pub async fn check_after_sign_in(app_handle: &AppHandle) -> Result<()> {
+    let app_state = app_handle.state::<AppState>();
+    let Some(ref supabase) = app_state.supabase else {
+        return Ok(());
+    };
+
+    let version = app_handle.package_info().version.to_string();
+    let id_path = device_id_path(app_handle);
+    let registered = match id_path {
+        Ok(ref path) => register_device(supabase, path, &version).await,
+        Err(ref e) => Err(Error::Storage(format!("No device ID: {}", e))),
+    };
+    // Whether the device ID is registered to another user
+    let taken = match registered {
+        Ok(registered) => !registered,
+        Err(e) => {
+            tracing::warn!("Failed to register device: {}", e);
+            false
+        }
+    };
+
+    match wipe_if_requested(app_handle).await {
+        Ok(true) => Err(Error::Auth(
+            "This device was wiped by an administrator".to_string(),
+        )),
+        Ok(false) => {
+            if let (true, Ok(path)) = (taken, id_path) {
+                let registered = match renew_device_id(&path) {
+                    Ok(()) => register_device(supabase, &path, &version).await,
+                    Err(e) => Err(e),
+                };
+                match registered {
+                    Ok(true) => tracing::info!("Registered device under a new ID"),
+                    Ok(false) => tracing::warn!("New device ID is already registered"),
+                    Err(e) => tracing::warn!("Failed to register device: {}", e),
+                }
+            }
+            Ok(())
+        }
+        Err(e) => {
+            tracing::warn!("Failed to check for a remote wipe: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Check for a wipe from startup on, while the app runs
+pub async fn watch_remote_wipe(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(WIPE_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = wipe_if_requested(&app_handle).await {
+            tracing::debug!("Failed to check for a remote wipe: {}", e);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Meeting conflict detection and free-slot suggestions
+//!
+//! Warns when a proposed meeting double-books the organizer or an attendee
+//! and proposes times everyone is free. Busy times come from the
+//! participants' meetings and, when asked, the organizer's Google calendar.
+//! Participants who set working hours are only offered times within them,
+//! and show up as conflicts when a meeting falls outside.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::google_calendar;
+use crate::state::AppState;
+use crate::supabase::{SupabaseClient, UserAvailabilityRow};
+use crate::utils::datetime;
+use crate::utils::rrule::RecurrenceExceptions;
+use crate::utils::scheduling::{
+    self, BusyInterval, BusyMeeting, BusySource, SlotSearch, SlotSuggestion, WorkingHours,
+};
+use crate::{Error, Result};
+
+const DEFAULT_STEP_MINUTES: i32 = 30;
+const DEFAULT_DAY_START_HOUR: u32 = 9;
+const DEFAULT_DAY_END_HOUR: u32 = 18;
+const DEFAULT_MAX_SUGGESTIONS: usize = 10;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+/// A participant already busy during a proposed meeting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingConflict {
+    pub user_id: String,
+    pub display_name: String,
+    pub starts_at: String,
+    pub ends_at: String,
+    pub source: BusySource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestMeetingSlotsParams {
+    pub attendee_ids: Vec<String>,
+    pub duration_minutes: i32,
+    pub range_start: String,
+    pub range_end: String,
+    /// IANA time zone of the working hours; unset fields default to the
+    /// organizer's working hours, then UTC 9:00-18:00
+    pub time_zone: Option<String>,
+    pub day_start_hour: Option<u32>,
+    pub day_end_hour: Option<u32>,
+    pub step_minutes: Option<i32>,
+    #[serde(default)]
+    pub include_weekends: bool,
+    pub max_results: Option<usize>,
+    /// Meeting being rescheduled, whose own time isn't busy
+    pub exclude_meeting_id: Option<String>,
+    /// Also avoid busy times of the organizer's Google calendar
+    #[serde(default)]
+    pub include_google: bool,
+}
+
+/// Working hours a user sets for themselves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityParams {
+    /// IANA time zone of the hours; UTC when unset or blank
+    pub time_zone: Option<String>,
+    pub day_start_hour: u32,
+    pub day_end_hour: u32,
+    /// ISO weekdays worked, Monday = 1 to Sunday = 7
+    pub working_days: Vec<u32>,
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// The current user followed by the attendees, without repeats
+fn participants(user_id: &str, attendee_ids: &[String]) -> Vec<String> {
+    let mut user_ids = vec![user_id.to_string()];
+    for attendee_id in attendee_ids {
+        if !user_ids.contains(attendee_id) {
+            user_ids.push(attendee_id.clone());
+        }
+    }
+    user_ids
+}
+
+fn working_hours_from_row(row: UserAvailabilityRow) -> WorkingHours {
+    WorkingHours {
+        user_id: row.user_id,
+        time_zone: row.time_zone,
+        day_start_hour: row.day_start_hour.max(0) as u32,
+        day_end_hour: row.day_end_hour.max(0) as u32,
+        working_days: row
+            .working_days
+            .into_iter()
+            .map(|day| day.max(0) as u32)
+            .collect(),
+    }
+}
+
+/// Working hours of the users who set them
+async fn load_working_hours(
+    supabase: &SupabaseClient,
+    user_ids: &[String],
+) -> Result<Vec<WorkingHours>> {
+    Ok(supabase
+        .get_user_availability(user_ids)
+        .await?
+        .into_iter()
+        .map(working_hours_from_row)
+        .collect())
+}
+
+/// Busy intervals of the given users within the range
+async fn collect_busy(
+    supabase: &SupabaseClient,
+    user_id: &str,
+    user_ids: &[String],
+    range_start: &str,
+    range_end: &str,
+    exclude_meeting_id: Option<&str>,
+    include_google: bool,
+) -> Result<Vec<BusyInterval>> {
+    let meetings: Vec<BusyMeeting> = supabase
+        .get_busy_meetings(user_ids, range_start, range_end, exclude_meeting_id)
+        .await?
+        .into_iter()
+        .map(|row| BusyMeeting {
+            user_id: row.user_id,
+            scheduled_at: row.scheduled_at,
+            duration_minutes: row.duration_minutes,
+            recurrence_rule: row.recurrence_rule,
+            exceptions: RecurrenceExceptions {
+                exdates: row.recurrence_exdates,
+                rdates: row.recurrence_rdates,
+            },
+            time_zone: row.time_zone,
+        })
+        .collect();
+    let mut busy = scheduling::busy_intervals(&meetings, range_start, range_end)?;
+
+    if include_google {
+        // Google is an extra source; without it meetings still count
+        match google_calendar::get_google_busy_times(supabase, user_id, range_start, range_end)
+            .await
+        {
+            Ok(periods) => {
+                busy.extend(
+                    periods
+                        .into_iter()
+                        .map(|(starts_at, ends_at)| BusyInterval {
+                            user_id: user_id.to_string(),
+                            starts_at,
+                            ends_at,
+                            source: BusySource::Google,
+                        }),
+                )
+            }
+            Err(e) => tracing::warn!("Failed to get Google free/busy: {}", e),
+        }
+    }
+
+    Ok(busy)
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Find who would be double-booked by a meeting at `scheduled_at`, or held
+/// outside their working hours, organizer included
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn check_conflicts(
+    scheduled_at: String,
+    duration_minutes: i32,
+    attendee_ids: Vec<String>,
+    exclude_meeting_id: Option<String>,
+    include_google: Option<bool>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<MeetingConflict>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let ends_at = datetime::calculate_end_time(&scheduled_at, duration_minutes)?;
+    let user_ids = participants(&user_id, &attendee_ids);
+    let (busy, working_hours) = tokio::try_join!(
+        collect_busy(
+            supabase,
+            &user_id,
+            &user_ids,
+            &scheduled_at,
+            &ends_at,
+            exclude_meeting_id.as_deref(),
+            include_google.unwrap_or(false),
+        ),
+        load_working_hours(supabase, &user_ids),
+    )?;
+    let mut conflicts = scheduling::find_conflicts(&busy, &scheduled_at, duration_minutes)?;
+    conflicts.extend(scheduling::outside_working_hours(
+        &working_hours,
+        &scheduled_at,
+        duration_minutes,
+    )?);
+    if conflicts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut user_ids: Vec<String> = conflicts.iter().map(|c| c.user_id.clone()).collect();
+    user_ids.sort();
+    user_ids.dedup();
+    let names: HashMap<String, String> = supabase
+        .get_user_profiles(&user_ids)
+        .await?
+        .into_iter()
+        .filter_map(|p| p.display_name.map(|name| (p.user_id, name)))
+        .collect();
+
+    Ok(conflicts
+        .into_iter()
+        .map(|conflict| MeetingConflict {
+            display_name: names
+                .get(&conflict.user_id)
+                .cloned()
+                .unwrap_or_else(|| conflict.user_id.clone()),
+            user_id: conflict.user_id,
+            starts_at: conflict.starts_at,
+            ends_at: conflict.ends_at,
+            source: conflict.source,
+        })
+        .collect())
+}
+
+/// Suggest times within working hours when the current user and every
+/// attendee are free, and within the working hours each of them set
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn suggest_meeting_slots(
+    params: SuggestMeetingSlotsParams,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<SlotSuggestion>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let user_ids = participants(&user_id, &params.attendee_ids);
+    let working_hours = load_working_hours(supabase, &user_ids).await?;
+    let own = working_hours.iter().find(|h| h.user_id == user_id);
+    let search = SlotSearch {
+        range_start: params.range_start,
+        range_end: params.range_end,
+        duration_minutes: params.duration_minutes,
+        step_minutes: params.step_minutes.unwrap_or(DEFAULT_STEP_MINUTES),
+        day_start_hour: params
+            .day_start_hour
+            .or(own.map(|h| h.day_start_hour))
+            .unwrap_or(DEFAULT_DAY_START_HOUR),
+        day_end_hour: params
+            .day_end_hour
+            .or(own.map(|h| h.day_end_hour))
+            .unwrap_or(DEFAULT_DAY_END_HOUR),
+        time_zone: params
+            .time_zone
+            .or_else(|| own.and_then(|h| h.time_zone.clone())),
+        include_weekends: params.include_weekends,
+        max_results: params.max_results.unwrap_or(DEFAULT_MAX_SUGGESTIONS),
+        working_hours,
+    };
+
+    let busy = collect_busy(
+        supabase,
+        &user_id,
+        &user_ids,
+        &search.range_start,
+        &search.range_end,
+        params.exclude_meeting_id.as_deref(),
+        params.include_google,
+    )
+    .await?;
+
+    scheduling::suggest_slots(&busy, &search)
+}
+
+/// Get the working hours of a user, the current one by default; `None` when
+/// they haven't set any
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_user_availability(
+    user_id: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<Option<WorkingHours>> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user_id.unwrap_or_else(|| user.id.clone());
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    Ok(load_working_hours(supabase, &[user_id])
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Set the current user's working hours
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_user_availability(
+    params: AvailabilityParams,
+    app_state: State<'_, AppState>,
+) -> Result<WorkingHours> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let time_zone = match params.time_zone.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => Some(datetime::parse_time_zone(name)?.name().to_string()),
+        _ => None,
+    };
+    let mut working_days = params.working_days;
+    working_days.sort_unstable();
+    working_days.dedup();
+    let hours = WorkingHours {
+        user_id,
+        time_zone,
+        day_start_hour: params.day_start_hour,
+        day_end_hour: params.day_end_hour,
+        working_days,
+    };
+    hours.validate()?;
+
+    supabase
+        .save_user_availability(&UserAvailabilityRow {
+            user_id: hours.user_id.clone(),
+            time_zone: hours.time_zone.clone(),
+            day_start_hour: hours.day_start_hour as i32,
+            day_end_hour: hours.day_end_hour as i32,
+            working_days: hours.working_days.iter().map(|&day| day as i32).collect(),
+        })
+        .await?;
+
+    Ok(hours)
+}
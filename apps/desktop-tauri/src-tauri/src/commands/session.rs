@@ -1,6 +1,19 @@
-use tauri::State;
+use std::time::{Duration, Instant};
 
-use crate::state::{AppState, Session, SessionStatus};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::signaling::{
+    self, connect_signaling, emit_participants, leave_signaling, Participant, SignalingState,
+    SignalingStateInner,
+};
+use crate::commands::stream::{start_stream, StreamState};
+use crate::join_throttle::JoinThrottle;
+use crate::lan;
+use crate::realtime::{ModerationAction, SignalingMessage};
+use crate::secure_storage::{self, StoredActiveSession};
+use crate::signaling_protocol::{CAP_COHOST_MODERATION, CAP_HOST_HANDOFF};
+use crate::state::{AppState, Session, SessionMode, SessionStatus};
+use crate::supabase::SupabaseClient;
 use crate::{Error, Result};
 
 #[derive(serde::Serialize)]
@@ -9,10 +22,150 @@ pub struct SessionInfo {
     pub join_code: String,
     pub is_host: bool,
     pub status: String,
+    pub mode: SessionMode,
+    pub idle_timeout_secs: u64,
+    pub expires_at: Option<String>,
+    pub locked: bool,
+    pub bridge_conversation_id: Option<String>,
+}
+
+/// Options of a new session
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct CreateSessionOptions {
+    /// `lan` to host on the local network when Supabase is unreachable
+    #[serde(default)]
+    pub mode: SessionMode,
+    /// Time alone in the session before it ends, 0 for never (default 30 minutes)
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+    /// Lifetime of the session regardless of activity, unlimited when unset
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// Conversation of the host to copy the session chat to, marked as
+    /// coming from the session
+    #[serde(default)]
+    pub bridge_conversation_id: Option<String>,
+}
+
+/// Viewer waiting for the host to let them into the session
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JoinRequest {
+    pub user_id: String,
+    pub requested_at: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct JoinDenied {
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct RemovedFromSession {
+    banned: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct ChatMuteChanged {
+    user_id: String,
+    muted: bool,
+}
+
+/// Moderation the host carried out for a co-host
+#[derive(Debug, Clone, serde::Serialize)]
+struct ModerationApplied {
+    by_user_id: String,
+    action: ModerationAction,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct HostChanged {
+    host_id: String,
+    /// `None` when a co-host took over after the host left
+    previous_host_id: Option<String>,
+    is_host: bool,
+}
+
+const BANNED_MESSAGE: &str = "You were banned from this session";
+
+const LOCKED_MESSAGE: &str = "This session is locked";
+
+/// Whether a failed lookup is Supabase turning the user away, rather than
+/// it being unreachable, so no other way of joining should be tried
+fn refuses_join(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::TooManyAttempts { .. } | Error::Banned | Error::SessionLocked
+    )
+}
+
+fn session_info(session: &Session) -> SessionInfo {
+    SessionInfo {
+        id: session.id.clone(),
+        join_code: session.join_code.clone(),
+        is_host: session.is_host,
+        status: match session.status {
+            SessionStatus::Waiting => "waiting",
+            SessionStatus::Active => "active",
+            SessionStatus::Paused => "paused",
+            SessionStatus::Ended => "ended",
+        }
+        .to_string(),
+        mode: session.mode,
+        idle_timeout_secs: session.idle_timeout_secs,
+        expires_at: session.expires_at.clone(),
+        locked: session.locked,
+        bridge_conversation_id: session.bridge_conversation_id.clone(),
+    }
+}
+
+/// Local session, used when Supabase is not configured or not reachable
+fn local_session(join_code: String, is_host: bool, mode: SessionMode) -> Session {
+    Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        join_code,
+        is_host,
+        status: if is_host {
+            SessionStatus::Active
+        } else {
+            SessionStatus::Waiting
+        },
+        mode,
+        lan_host: None,
+        idle_timeout_secs: 0,
+        expires_at: None,
+        locked: false,
+        bridge_conversation_id: None,
+    }
+}
+
+/// Session with a join code hosted on the local network, if any
+async fn find_lan_session(join_code: &str) -> Result<Option<Session>> {
+    let host = lan::discover(join_code, lan::DISCOVERY_TIMEOUT).await?;
+    Ok(host.map(|host| {
+        tracing::info!("Found LAN session {} at {}", host.session_id, host.address);
+        Session {
+            id: host.session_id.clone(),
+            // The code keys end-to-end encryption, so it must match the host's
+            join_code: join_code.trim().to_uppercase(),
+            is_host: false,
+            status: SessionStatus::Waiting,
+            mode: SessionMode::Lan,
+            lan_host: Some(host),
+            idle_timeout_secs: 0,
+            expires_at: None,
+            locked: false,
+            bridge_conversation_id: None,
+        }
+    }))
 }
 
 #[tauri::command]
-pub async fn create_session(state: State<'_, AppState>) -> Result<SessionInfo> {
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn create_session(
+    options: Option<CreateSessionOptions>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<SessionInfo> {
     let inner = state.inner.read().await;
     let user = inner
         .user
@@ -21,73 +174,142 @@ pub async fn create_session(state: State<'_, AppState>) -> Result<SessionInfo> {
     let user_id = user.id.clone();
     drop(inner);
 
+    let options = options.unwrap_or_default();
+    let mode = options.mode;
+    let idle_timeout_secs = options
+        .idle_timeout_secs
+        .unwrap_or(DEFAULT_IDLE_TIMEOUT_SECS);
+    let expires_at = options
+        .ttl_secs
+        .filter(|&ttl| ttl > 0)
+        .map(|ttl| (chrono::Utc::now() + chrono::Duration::seconds(ttl as i64)).to_rfc3339());
     let join_code = generate_join_code();
 
-    // Try to create session in Supabase if configured
-    let session = if let Some(ref supabase) = state.supabase {
-        match supabase.create_session(&user_id, &join_code).await {
-            Ok(row) => {
-                tracing::info!("Session created in Supabase: {}", row.id);
-                Session {
-                    id: row.id,
-                    join_code: row.join_code,
-                    is_host: true,
-                    status: SessionStatus::Active,
+    // Try to create session in Supabase if configured, LAN sessions stay local
+    let mut session = match (mode, state.supabase.as_ref()) {
+        (SessionMode::Cloud, Some(supabase)) => {
+            match supabase
+                .create_session(
+                    &user_id,
+                    &join_code,
+                    idle_timeout_secs,
+                    expires_at.as_deref(),
+                )
+                .await
+            {
+                Ok(row) => {
+                    tracing::info!("Session created in Supabase: {}", row.id);
+                    let bridge_conversation_id = match options.bridge_conversation_id {
+                        Some(conversation_id) => {
+                            match supabase
+                                .set_session_chat_bridge(&row.id, Some(&conversation_id))
+                                .await
+                            {
+                                Ok(()) => Some(conversation_id),
+                                Err(e) => {
+                                    tracing::warn!("Failed to bridge session chat: {}", e);
+                                    None
+                                }
+                            }
+                        }
+                        None => None,
+                    };
+                    Session {
+                        id: row.id,
+                        join_code: row.join_code,
+                        is_host: true,
+                        status: SessionStatus::Active,
+                        mode,
+                        lan_host: None,
+                        idle_timeout_secs: 0,
+                        expires_at: None,
+                        locked: false,
+                        bridge_conversation_id,
+                    }
                 }
-            }
-            Err(e) => {
-                tracing::warn!("Failed to create session in Supabase: {}, using local session", e);
-                // Fallback to local session
-                Session {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    join_code,
-                    is_host: true,
-                    status: SessionStatus::Active,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to create session in Supabase: {}, using local session",
+                        e
+                    );
+                    local_session(join_code, true, mode)
                 }
             }
         }
-    } else {
-        // Mock mode - create local session
-        Session {
-            id: uuid::Uuid::new_v4().to_string(),
-            join_code,
-            is_host: true,
-            status: SessionStatus::Active,
-        }
+        // Mock mode or LAN session - create local session
+        _ => local_session(join_code, true, mode),
     };
+    session.idle_timeout_secs = idle_timeout_secs;
+    session.expires_at = expires_at;
 
-    let info = SessionInfo {
-        id: session.id.clone(),
-        join_code: session.join_code.clone(),
-        is_host: session.is_host,
-        status: "active".to_string(),
-    };
+    let info = session_info(&session);
 
-    let mut inner = state.inner.write().await;
-    inner.session = Some(session);
+    state.inner.write().await.session = Some(session);
+    crate::commands::chat::refresh_presence(&app_handle).await;
 
     tracing::info!("Session created: {}", info.id);
     Ok(info)
 }
 
+/// Join a session by code
+///
+/// With `mode` set to `lan`, the host is looked for on the local network
+/// only; a cloud join falls back to it when Supabase is unreachable.
 #[tauri::command]
-pub async fn join_session(join_code: String, state: State<'_, AppState>) -> Result<SessionInfo> {
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn join_session(
+    join_code: String,
+    mode: Option<SessionMode>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<SessionInfo> {
     let inner = state.inner.read().await;
-    if inner.user.is_none() {
-        return Err(Error::Auth("Not authenticated".to_string()));
-    }
+    let user_id = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?
+        .id
+        .clone();
     drop(inner);
 
+    // Slow down guessing before looking the code up anywhere
+    throttle(&state).check(&user_id, Instant::now())?;
+
     // Try to find session in Supabase if configured
-    let session = if let Some(ref supabase) = state.supabase {
-        match supabase.get_session_by_code(&join_code).await {
+    let session = if mode == Some(SessionMode::Lan) {
+        let found = find_lan_session(&join_code).await?;
+        record_join_attempt(&state, &user_id, found.is_some());
+        found.ok_or_else(|| {
+            Error::Session(format!(
+                "No session with code '{}' found on the local network",
+                join_code
+            ))
+        })?
+    } else if let Some(ref supabase) = state.supabase {
+        let found = supabase.get_session_by_code(&join_code).await;
+        if let Ok(ref row) = found {
+            record_join_attempt(&state, &user_id, row.is_some());
+        }
+        match found {
+            Ok(Some(row)) if row.banned_user_ids.contains(&user_id) => {
+                return Err(Error::Banned);
+            }
+            Ok(Some(row)) if row.locked && !row.cohost_ids.contains(&user_id) => {
+                return Err(Error::SessionLocked);
+            }
             Ok(Some(row)) => {
                 tracing::info!("Found session in Supabase: {}", row.id);
                 Session {
                     id: row.id,
                     join_code: row.join_code,
                     is_host: false,
-                    status: SessionStatus::Active,
+                    status: SessionStatus::Waiting,
+                    mode: SessionMode::Cloud,
+                    lan_host: None,
+                    idle_timeout_secs: row.idle_timeout_secs.unwrap_or_default(),
+                    expires_at: row.expires_at,
+                    locked: row.locked,
+                    bridge_conversation_id: row.bridge_conversation_id,
                 }
             }
             Ok(None) => {
@@ -96,50 +318,55 @@ pub async fn join_session(join_code: String, state: State<'_, AppState>) -> Resu
                     join_code
                 )));
             }
+            Err(e) if refuses_join(&e) => return Err(e),
             Err(e) => {
-                tracing::warn!("Failed to find session in Supabase: {}, using local session", e);
-                // Fallback to local session (for development)
-                Session {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    join_code,
-                    is_host: false,
-                    status: SessionStatus::Active,
+                tracing::warn!(
+                    "Failed to find session in Supabase: {}, looking on the local network",
+                    e
+                );
+                match find_lan_session(&join_code).await {
+                    Ok(Some(session)) => session,
+                    result => {
+                        if let Err(e) = result {
+                            tracing::warn!("LAN discovery failed: {}", e);
+                        }
+                        // Fallback to local session (for development)
+                        local_session(join_code, false, SessionMode::Cloud)
+                    }
                 }
             }
         }
     } else {
         // Mock mode - create local session as viewer
-        Session {
-            id: uuid::Uuid::new_v4().to_string(),
-            join_code,
-            is_host: false,
-            status: SessionStatus::Active,
-        }
+        local_session(join_code, false, SessionMode::Cloud)
     };
 
-    let info = SessionInfo {
-        id: session.id.clone(),
-        join_code: session.join_code.clone(),
-        is_host: session.is_host,
-        status: "active".to_string(),
-    };
+    let info = session_info(&session);
 
-    let mut inner = state.inner.write().await;
-    inner.session = Some(session);
+    // Reminder telemetry: joining a session linked to a meeting counts as joining it
+    if let (SessionMode::Cloud, Some(supabase)) = (session.mode, state.supabase.as_ref()) {
+        if let Ok(Some(meeting)) = supabase.get_meeting_by_session_id(&info.id).await {
+            crate::commands::reminders::record_meeting_join(supabase, &user_id, &meeting.id).await;
+        }
+    }
+
+    state.inner.write().await.session = Some(session);
+    crate::commands::chat::refresh_presence(&app_handle).await;
 
     tracing::info!("Joined session: {}", info.id);
     Ok(info)
 }
 
 #[tauri::command]
-pub async fn end_session(state: State<'_, AppState>) -> Result<()> {
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn end_session(state: State<'_, AppState>, app_handle: AppHandle) -> Result<()> {
     let mut inner = state.inner.write().await;
 
     if let Some(session) = &inner.session {
         tracing::info!("Ending session: {}", session.id);
 
         // Update session status in Supabase if configured and is host
-        if session.is_host {
+        if session.is_host && session.mode == SessionMode::Cloud {
             if let Some(ref supabase) = state.supabase {
                 if let Err(e) = supabase.end_session(&session.id).await {
                     tracing::warn!("Failed to end session in Supabase: {}", e);
@@ -151,28 +378,1527 @@ pub async fn end_session(state: State<'_, AppState>) -> Result<()> {
     inner.session = None;
     inner.is_capturing = false;
     inner.is_input_enabled = false;
+    drop(inner);
+    forget_session();
+    crate::commands::chat::refresh_presence(&app_handle).await;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_session_status(state: State<'_, AppState>) -> Result<Option<SessionInfo>> {
     let inner = state.inner.read().await;
 
-    Ok(inner.session.as_ref().map(|s| SessionInfo {
-        id: s.id.clone(),
-        join_code: s.join_code.clone(),
-        is_host: s.is_host,
-        status: match s.status {
-            SessionStatus::Active => "active",
-            SessionStatus::Paused => "paused",
-            SessionStatus::Ended => "ended",
+    Ok(inner.session.as_ref().map(session_info))
+}
+
+// ==========================================
+// Expiry
+// ==========================================
+
+/// Time a host may stay alone in a session before it ends
+pub(crate) const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 30 * 60;
+
+/// Interval between expiry checks, also the activity heartbeat
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interval between cleanups of the user's abandoned sessions
+const STALE_CLEANUP_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ExpiryReason {
+    /// Nobody else was in the session for its idle timeout
+    Idle,
+    /// The session outlived its TTL
+    Expired,
+    /// Ended server side as stale
+    Stale,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct SessionExpired {
+    session_id: String,
+    reason: ExpiryReason,
+}
+
+/// Why a hosted session is due to end, if it is
+fn expiry_reason(session: &Session, idle_for: Option<Duration>) -> Option<ExpiryReason> {
+    let expired = session
+        .expires_at
+        .as_deref()
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+        .is_some_and(|at| at <= chrono::Utc::now());
+    if expired {
+        return Some(ExpiryReason::Expired);
+    }
+
+    let timeout = Duration::from_secs(session.idle_timeout_secs);
+    match idle_for {
+        Some(idle_for) if !timeout.is_zero() && idle_for >= timeout => Some(ExpiryReason::Idle),
+        _ => None,
+    }
+}
+
+/// End the hosted session once it idles out or expires
+///
+/// Runs for the app's lifetime. While others are in a cloud session the host
+/// keeps its activity fresh, so the server can tell abandoned sessions apart,
+/// and those the user left behind are cleaned up periodically.
+pub async fn watch_session_expiry(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+    // Session the host has been alone in, and since when
+    let mut alone_since: Option<(String, Instant)> = None;
+    let mut last_cleanup: Option<Instant> = None;
+
+    loop {
+        interval.tick().await;
+
+        let app_state = app_handle.state::<AppState>();
+        let (session, signed_in) = {
+            let inner = app_state.inner.read().await;
+            (inner.session.clone(), inner.user.is_some())
+        };
+
+        if signed_in && last_cleanup.is_none_or(|at| at.elapsed() >= STALE_CLEANUP_INTERVAL) {
+            last_cleanup = Some(Instant::now());
+            if let Err(e) = cleanup_stale(&app_handle).await {
+                tracing::debug!("Failed to clean up stale sessions: {}", e);
+            }
         }
-        .to_string(),
-    }))
+
+        let Some(session) =
+            session.filter(|s| s.is_host && matches!(s.status, SessionStatus::Active))
+        else {
+            alone_since = None;
+            continue;
+        };
+
+        let others = {
+            let signaling_state = app_handle.state::<SignalingState>();
+            let state = signaling_state.inner.read().await;
+            state.participants.len().saturating_sub(1)
+        };
+        if others > 0 {
+            alone_since = None;
+            if session.mode == SessionMode::Cloud {
+                if let Some(ref supabase) = app_state.supabase {
+                    if let Err(e) = supabase.touch_session(&session.id).await {
+                        tracing::debug!("Failed to record session activity: {}", e);
+                    }
+                }
+            }
+        } else if alone_since.as_ref().is_none_or(|(id, _)| *id != session.id) {
+            alone_since = Some((session.id.clone(), Instant::now()));
+        }
+
+        let idle_for = alone_since.as_ref().map(|(_, since)| since.elapsed());
+        if let Some(reason) = expiry_reason(&session, idle_for) {
+            alone_since = None;
+            expire_session(&app_handle, &session.id, reason).await;
+        }
+    }
+}
+
+/// Tear down a hosted session that ended on its own
+async fn expire_session(app_handle: &AppHandle, session_id: &str, reason: ExpiryReason) {
+    let app_state = app_handle.state::<AppState>();
+    let user_id = {
+        let mut inner = app_state.inner.write().await;
+        if inner.session.as_ref().map(|s| s.id.as_str()) != Some(session_id) {
+            return;
+        }
+        inner.session = None;
+        inner.is_capturing = false;
+        inner.is_input_enabled = false;
+        inner.user.as_ref().map(|u| u.id.clone())
+    };
+    forget_session();
+    tracing::info!("Session {} ended ({:?})", session_id, reason);
+
+    if reason != ExpiryReason::Stale {
+        if let Some(ref supabase) = app_state.supabase {
+            if let Err(e) = supabase.end_session(session_id).await {
+                tracing::warn!("Failed to end session in Supabase: {}", e);
+            }
+        }
+    }
+
+    {
+        let stream_state = app_handle.state::<StreamState>();
+        stream_state.inner.write().await.close_all().await;
+    }
+
+    if let Some(user_id) = user_id {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let mut state = signaling_state.inner.write().await;
+        if let Err(e) = leave_signaling(&mut state, &user_id).await {
+            tracing::warn!("Failed to leave signaling channel: {}", e);
+        }
+    }
+
+    let event = SessionExpired {
+        session_id: session_id.to_string(),
+        reason,
+    };
+    if let Err(e) = app_handle.emit("session:expired", &event) {
+        tracing::error!("Failed to emit session expiry event: {}", e);
+    }
+}
+
+/// End the user's stale sessions in Supabase, returning their IDs
+async fn cleanup_stale(app_handle: &AppHandle) -> Result<Vec<String>> {
+    let app_state = app_handle.state::<AppState>();
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let ended: Vec<String> = supabase
+        .end_stale_sessions()
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect();
+    if !ended.is_empty() {
+        tracing::info!("Ended {} stale session(s)", ended.len());
+    }
+
+    // Our own session may be among them if the app was asleep
+    let current = active_session_id(&app_state).await.ok();
+    if let Some(current) = current.filter(|id| ended.contains(id)) {
+        expire_session(app_handle, &current, ExpiryReason::Stale).await;
+    }
+    Ok(ended)
+}
+
+/// End the user's sessions left "active" past their idle timeout or TTL
+///
+/// Returns the IDs of the sessions ended.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn cleanup_stale_sessions(
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>> {
+    local_user_id(&app_state).await?;
+    cleanup_stale(&app_handle).await
+}
+
+// ==========================================
+// Resumption
+// ==========================================
+
+/// Save what the app needs to pick the session back up after a restart
+///
+/// The session's role travels with it, so hosts and viewers alike can
+/// resume. Forgets the saved session once the local user left it.
+pub(crate) async fn persist_session(app_handle: &AppHandle) {
+    let (user_id, session) = {
+        let app_state = app_handle.state::<AppState>();
+        let inner = app_state.inner.read().await;
+        (
+            inner.user.as_ref().map(|u| u.id.clone()),
+            inner.session.clone(),
+        )
+    };
+    let session = session.filter(|s| !matches!(s.status, SessionStatus::Ended));
+    let (Some(user_id), Some(session)) = (user_id, session) else {
+        forget_session();
+        return;
+    };
+
+    let (encrypted, mut admitted) = {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let state = signaling_state.inner.read().await;
+        (
+            state.encrypted,
+            state.admitted.iter().cloned().collect::<Vec<_>>(),
+        )
+    };
+    admitted.sort();
+    let stream = {
+        let stream_state = app_handle.state::<StreamState>();
+        let state = stream_state.inner.read().await;
+        state.source.clone()
+    };
+
+    let stored = StoredActiveSession {
+        user_id,
+        session,
+        encrypted,
+        admitted,
+        stream,
+        saved_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Err(e) = secure_storage::store_active_session(&stored) {
+        tracing::warn!("Failed to save session for resumption: {}", e);
+    }
+}
+
+fn forget_session() {
+    if let Err(e) = secure_storage::clear_active_session() {
+        tracing::warn!("Failed to forget saved session: {}", e);
+    }
+}
+
+/// Get the session the app was in before it restarted, if any
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_resumable_session(state: State<'_, AppState>) -> Result<Option<SessionInfo>> {
+    let inner = state.inner.read().await;
+    let Some(ref user) = inner.user else {
+        return Ok(None);
+    };
+    if inner.session.is_some() {
+        return Ok(None);
+    }
+
+    Ok(secure_storage::get_active_session()
+        .filter(|stored| stored.user_id == user.id)
+        .map(|stored| session_info(&stored.session)))
+}
+
+/// Pick up the session the app was in before it restarted
+///
+/// Rejoins signaling in the role the app had. A host restarts the capture
+/// that was streaming; viewers still in the channel answer its return, and
+/// those already let in skip the waiting room. A viewer is streamed to again
+/// once the host sees it rejoin.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn resume_session(
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<SessionInfo> {
+    let user_id = local_user_id(&app_state).await?;
+    if app_state.inner.read().await.session.is_some() {
+        return Err(Error::Session("Already in a session".to_string()));
+    }
+
+    let stored = secure_storage::get_active_session()
+        .filter(|stored| stored.user_id == user_id)
+        .ok_or_else(|| Error::NotFound("No session to resume".to_string()))?;
+    let mut session = stored.session;
+
+    // The session may have ended or expired while the app was down
+    if let (SessionMode::Cloud, Some(supabase)) = (session.mode, app_state.supabase.as_ref()) {
+        let Some(row) = supabase
+            .get_session(&session.id)
+            .await?
+            .filter(|row| row.status == "active")
+        else {
+            forget_session();
+            return Err(Error::Session("The session has already ended".to_string()));
+        };
+        if row.banned_user_ids.contains(&user_id) {
+            forget_session();
+            return Err(Error::Session(BANNED_MESSAGE.to_string()));
+        }
+    }
+
+    // A restarted LAN host may be listening on a new address
+    if session.mode == SessionMode::Lan && !session.is_host {
+        let found = find_lan_session(&session.join_code).await?.ok_or_else(|| {
+            Error::Session("The session's host was not found on the local network".to_string())
+        })?;
+        session.lan_host = found.lan_host;
+    }
+
+    if session.is_host {
+        session.status = SessionStatus::Active;
+    }
+    let session_id = session.id.clone();
+    app_state.inner.write().await.session = Some(session);
+    signaling_state.inner.write().await.admitted = stored.admitted.into_iter().collect();
+
+    if let Err(e) = connect_signaling(
+        session_id.clone(),
+        Some(stored.encrypted),
+        app_handle.state(),
+        app_handle.state(),
+        app_handle.clone(),
+    )
+    .await
+    {
+        app_state.inner.write().await.session = None;
+        signaling_state.inner.write().await.admitted.clear();
+        return Err(e);
+    }
+
+    // A co-host may have taken over meanwhile, leaving nothing to stream
+    let is_host = signaling_state.inner.read().await.is_host;
+    if let Some((source_id, fps)) = stored.stream.filter(|_| is_host) {
+        if let Err(e) = start_stream(
+            source_id,
+            Some(fps),
+            app_handle.state(),
+            app_handle.state(),
+            app_handle.state(),
+            app_handle.clone(),
+        )
+        .await
+        {
+            tracing::warn!("Failed to restart stream after resuming: {}", e);
+        }
+    }
+
+    let info = {
+        let inner = app_state.inner.read().await;
+        inner
+            .session
+            .as_ref()
+            .map(session_info)
+            .ok_or_else(|| Error::Session("No active session".to_string()))?
+    };
+    crate::commands::chat::refresh_presence(&app_handle).await;
+    tracing::info!("Resumed session: {}", session_id);
+    if let Err(e) = app_handle.emit("session:resumed", &info) {
+        tracing::error!("Failed to emit session resume event: {}", e);
+    }
+    Ok(info)
+}
+
+// ==========================================
+// Waiting Room
+// ==========================================
+
+/// Hold back messages from viewers the host hasn't admitted yet
+///
+/// Returns `false` when the message must be dropped. A waiting viewer's join
+/// becomes a join request for the host to approve or deny; banned users are
+/// turned away straight away.
+pub(crate) async fn screen_waiting_room(
+    app_handle: &AppHandle,
+    msg: &SignalingMessage,
+    local_user_id: &str,
+) -> bool {
+    let Some(sender) = msg.sender() else {
+        return true;
+    };
+
+    let locked = {
+        let app_state = app_handle.state::<AppState>();
+        let inner = app_state.inner.read().await;
+        inner.session.as_ref().is_some_and(|s| s.locked)
+    };
+
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    if state.is_admitted(sender) {
+        return true;
+    }
+
+    match msg {
+        SignalingMessage::UserJoined { user_id, .. }
+            if locked || state.banned.contains(user_id) =>
+        {
+            let reason = if state.banned.contains(user_id) {
+                BANNED_MESSAGE
+            } else {
+                LOCKED_MESSAGE
+            };
+            if let Some(ref tx) = state.signaling_tx {
+                let denial = SignalingMessage::JoinResponse {
+                    from_user_id: local_user_id.to_string(),
+                    to_user_id: user_id.clone(),
+                    approved: false,
+                    reason: Some(reason.to_string()),
+                };
+                if let Err(e) = tx.try_send(denial) {
+                    tracing::warn!("Failed to turn away banned user {}: {}", user_id, e);
+                }
+            }
+        }
+        SignalingMessage::UserJoined { user_id, .. }
+            if !state.join_requests.contains_key(user_id) =>
+        {
+            let request = JoinRequest {
+                user_id: user_id.clone(),
+                requested_at: chrono::Utc::now().to_rfc3339(),
+            };
+            state.join_requests.insert(user_id.clone(), request.clone());
+            share_waiting_room(&state, local_user_id);
+            drop(state);
+
+            tracing::info!("{} is waiting to join the session", user_id);
+            if let Err(e) = app_handle.emit("session:join-request", &request) {
+                tracing::error!("Failed to emit join request event: {}", e);
+            }
+        }
+        SignalingMessage::UserLeft { user_id } => {
+            if state.join_requests.remove(user_id).is_some() {
+                share_waiting_room(&state, local_user_id);
+                drop(state);
+                emit_join_cancelled(app_handle, user_id);
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
+/// Tell the host a waiting viewer left before being let in
+pub(crate) fn emit_join_cancelled(app_handle: &AppHandle, user_id: &str) {
+    if let Err(e) = app_handle.emit("session:join-request-cancelled", user_id) {
+        tracing::error!("Failed to emit join cancellation event: {}", e);
+    }
+}
+
+/// Apply the host's decision on our join request
+pub(crate) async fn handle_join_response(
+    app_handle: &AppHandle,
+    approved: bool,
+    reason: Option<String>,
+) {
+    let app_state = app_handle.state::<AppState>();
+    let mut inner = app_state.inner.write().await;
+
+    let result = if approved {
+        let Some(session) = inner.session.as_mut() else {
+            return;
+        };
+        session.status = SessionStatus::Active;
+        tracing::info!("Admitted to session: {}", session.id);
+        app_handle.emit("session:join-approved", &session_info(session))
+    } else {
+        inner.session = None;
+        tracing::info!("Join request denied: {:?}", reason);
+        app_handle.emit("session:join-denied", &JoinDenied { reason })
+    };
+    drop(inner);
+
+    if let Err(e) = result {
+        tracing::error!("Failed to emit join response event: {}", e);
+    }
+    persist_session(app_handle).await;
+}
+
+/// Get the viewers waiting to join, oldest first (host or co-host)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_join_requests(
+    signaling_state: State<'_, SignalingState>,
+) -> Result<Vec<JoinRequest>> {
+    let state = signaling_state.inner.read().await;
+    let mut requests: Vec<JoinRequest> = state.join_requests.values().cloned().collect();
+    requests.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+    Ok(requests)
+}
+
+/// Let a waiting viewer into the session (host or co-host)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn approve_join(
+    user_id: String,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let action = ModerationAction::ApproveJoin {
+        user_id: user_id.clone(),
+    };
+    if relay_to_host(&app_handle, action).await? {
+        tracing::info!("Asked the host to admit {}", user_id);
+        return Ok(());
+    }
+
+    let local_user_id = local_user_id(&app_state).await?;
+    admit(&app_handle, &local_user_id, &user_id).await
+}
+
+/// Let a waiting viewer in as the host
+async fn admit(app_handle: &AppHandle, local_user_id: &str, user_id: &str) -> Result<()> {
+    {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let mut state = signaling_state.inner.write().await;
+        let tx = pending_request_tx(&state, user_id)?;
+
+        // Introduce ourselves first so an encrypted session can key the response
+        tx.send(SignalingMessage::user_joined(
+            local_user_id,
+            true,
+            Some(user_id),
+        ))
+        .await
+        .map_err(|e| Error::Network(format!("Failed to send join approval: {}", e)))?;
+        tx.send(SignalingMessage::JoinResponse {
+            from_user_id: local_user_id.to_string(),
+            to_user_id: user_id.to_string(),
+            approved: true,
+            reason: None,
+        })
+        .await
+        .map_err(|e| Error::Network(format!("Failed to send join approval: {}", e)))?;
+
+        state.join_requests.remove(user_id);
+        share_waiting_room(&state, local_user_id);
+        state.admitted.insert(user_id.to_string());
+        state.participants.insert(
+            user_id.to_string(),
+            Participant {
+                user_id: user_id.to_string(),
+                is_host: false,
+                is_cohost: false,
+                has_control: false,
+                protocol: None,
+                is_muted: false,
+                is_chat_muted: false,
+                permissions: Vec::new(),
+                joined_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        emit_participants(app_handle, &state);
+
+        // The viewer missed the co-host and chat mute announcements while waiting
+        let announcements = state
+            .cohosts
+            .iter()
+            .map(|cohost| SignalingMessage::CohostChanged {
+                from_user_id: local_user_id.to_string(),
+                user_id: cohost.clone(),
+                is_cohost: true,
+            })
+            .chain(
+                state
+                    .chat_muted
+                    .iter()
+                    .map(|muted_user_id| SignalingMessage::ChatMuted {
+                        from_user_id: local_user_id.to_string(),
+                        user_id: muted_user_id.clone(),
+                        muted: true,
+                    }),
+            );
+        for announcement in announcements {
+            if let Err(e) = tx.send(announcement).await {
+                tracing::warn!("Failed to brief admitted viewer {}: {}", user_id, e);
+            }
+        }
+    }
+
+    // The native stream and the frontend pick the viewer up as a fresh join
+    let joined = SignalingMessage::user_joined(user_id, false, None);
+    crate::commands::stream::handle_signaling(app_handle, &joined).await;
+    if let Err(e) = app_handle.emit("signaling:user-joined", &joined) {
+        tracing::error!("Failed to emit signaling event: {}", e);
+    }
+
+    // Lets the viewer read the session and its chat, also after a restart
+    let app_state = app_handle.state::<AppState>();
+    if let (Some(supabase), Ok(session_id)) = (
+        session_store(&app_state).await,
+        active_session_id(&app_state).await,
+    ) {
+        if let Err(e) = supabase.admit_session_user(&session_id, user_id).await {
+            tracing::warn!("Failed to record admitting {}: {}", user_id, e);
+        }
+    }
+
+    persist_session(app_handle).await;
+
+    tracing::info!("Admitted {} to the session", user_id);
+    Ok(())
+}
+
+/// Turn a waiting viewer away (host or co-host)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn deny_join(
+    user_id: String,
+    reason: Option<String>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let action = ModerationAction::DenyJoin {
+        user_id: user_id.clone(),
+        reason: reason.clone(),
+    };
+    if relay_to_host(&app_handle, action).await? {
+        tracing::info!("Asked the host to turn {} away", user_id);
+        return Ok(());
+    }
+
+    let local_user_id = local_user_id(&app_state).await?;
+    deny(&app_handle, &local_user_id, &user_id, reason).await
+}
+
+/// Turn a waiting viewer away as the host
+async fn deny(
+    app_handle: &AppHandle,
+    local_user_id: &str,
+    user_id: &str,
+    reason: Option<String>,
+) -> Result<()> {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    let tx = pending_request_tx(&state, user_id)?;
+    tx.send(SignalingMessage::JoinResponse {
+        from_user_id: local_user_id.to_string(),
+        to_user_id: user_id.to_string(),
+        approved: false,
+        reason,
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send join denial: {}", e)))?;
+
+    state.join_requests.remove(user_id);
+    share_waiting_room(&state, local_user_id);
+    tracing::info!("Denied {} entry to the session", user_id);
+    Ok(())
+}
+
+/// Share the waiting room with co-hosts, so they can answer join requests
+/// (host only)
+pub(crate) fn share_waiting_room(state: &SignalingStateInner, local_user_id: &str) {
+    if !state.is_host || state.cohosts.is_empty() {
+        return;
+    }
+    let Some(ref tx) = state.signaling_tx else {
+        return;
+    };
+
+    let mut requests: Vec<&JoinRequest> = state.join_requests.values().collect();
+    requests.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+    let waiting_room = SignalingMessage::WaitingRoom {
+        from_user_id: local_user_id.to_string(),
+        user_ids: requests.into_iter().map(|r| r.user_id.clone()).collect(),
+    };
+    if let Err(e) = tx.try_send(waiting_room) {
+        tracing::warn!("Failed to share the waiting room: {}", e);
+    }
+}
+
+// ==========================================
+// Moderation
+// ==========================================
+
+/// Remove a participant from the session; they may ask to join again (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn kick_participant(
+    user_id: String,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let local_user_id = local_user_id(&app_state).await?;
+    remove_participant(
+        &app_handle,
+        &signaling_state,
+        &local_user_id,
+        &user_id,
+        false,
+    )
+    .await?;
+
+    tracing::info!("Kicked {} from the session", user_id);
+    Ok(())
+}
+
+/// Remove a participant and refuse their later join attempts (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn ban_participant(
+    user_id: String,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let local_user_id = local_user_id(&app_state).await?;
+    let session_id = active_session_id(&app_state).await?;
+
+    // Record the ban first, so join_session refuses the code from now on
+    if let Some(supabase) = session_store(&app_state).await {
+        supabase.ban_session_user(&session_id, &user_id).await?;
+    }
+    remove_participant(
+        &app_handle,
+        &signaling_state,
+        &local_user_id,
+        &user_id,
+        true,
+    )
+    .await?;
+
+    tracing::info!("Banned {} from the session", user_id);
+    Ok(())
+}
+
+/// Tell a participant they were removed and forget them
+async fn remove_participant(
+    app_handle: &AppHandle,
+    signaling_state: &SignalingState,
+    local_user_id: &str,
+    user_id: &str,
+    banned: bool,
+) -> Result<()> {
+    let had_control = {
+        let mut state = signaling_state.inner.write().await;
+        if !state.is_host {
+            return Err(Error::Session(
+                "Only the host can remove participants".to_string(),
+            ));
+        }
+        if user_id == local_user_id {
+            return Err(Error::Session(
+                "You can't remove yourself from the session".to_string(),
+            ));
+        }
+        let was_waiting = state.join_requests.contains_key(user_id);
+        if !banned && !was_waiting && !state.participants.contains_key(user_id) {
+            return Err(Error::NotFound(format!(
+                "{} is not in the session",
+                user_id
+            )));
+        }
+
+        let tx = state
+            .signaling_tx
+            .clone()
+            .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+        tx.send(SignalingMessage::ParticipantRemoved {
+            from_user_id: local_user_id.to_string(),
+            to_user_id: user_id.to_string(),
+            banned,
+        })
+        .await
+        .map_err(|e| Error::Network(format!("Failed to remove participant: {}", e)))?;
+
+        if banned {
+            state.banned.insert(user_id.to_string());
+        }
+        state.admitted.remove(user_id);
+        state.participants.remove(user_id);
+        state.remove_control_request(user_id);
+        if state.join_requests.remove(user_id).is_some() {
+            share_waiting_room(&state, local_user_id);
+            emit_join_cancelled(app_handle, user_id);
+        }
+        let had_control = state.controller_id.as_deref() == Some(user_id);
+        if had_control {
+            state.release_control();
+        }
+        emit_participants(app_handle, &state);
+        had_control
+    };
+
+    if had_control {
+        let app_state = app_handle.state::<AppState>();
+        app_state.inner.write().await.is_input_enabled = false;
+    }
+
+    // Tear down the participant's connection as if they had left
+    let left = SignalingMessage::UserLeft {
+        user_id: user_id.to_string(),
+    };
+    crate::commands::stream::handle_signaling(app_handle, &left).await;
+    if let Err(e) = app_handle.emit("signaling:user-left", &left) {
+        tracing::error!("Failed to emit signaling event: {}", e);
+    }
+    persist_session(app_handle).await;
+    Ok(())
+}
+
+/// Leave a session the host removed us from
+pub(crate) async fn handle_removed(app_handle: &AppHandle, local_user_id: &str, banned: bool) {
+    {
+        let app_state = app_handle.state::<AppState>();
+        let mut inner = app_state.inner.write().await;
+        inner.session = None;
+        inner.is_input_enabled = false;
+    }
+    forget_session();
+
+    {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let mut state = signaling_state.inner.write().await;
+        if let Err(e) = leave_signaling(&mut state, local_user_id).await {
+            tracing::warn!("Failed to leave signaling channel: {}", e);
+        }
+    }
+
+    tracing::info!("Removed from session by the host (banned: {})", banned);
+    if let Err(e) = app_handle.emit("session:removed", &RemovedFromSession { banned }) {
+        tracing::error!("Failed to emit session removal event: {}", e);
+    }
+}
+
+// ==========================================
+// Locking
+// ==========================================
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct LockChanged {
+    locked: bool,
+}
+
+/// Close the session to new joins once everyone expected is in (host only)
+///
+/// Participants already in the session stay; new join requests are turned
+/// away, and cloud lookups by join code are refused server-side.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn lock_session(
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    set_locked(&app_state, &signaling_state, &app_handle, true).await
+}
+
+/// Let new participants join the session again (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn unlock_session(
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    set_locked(&app_state, &signaling_state, &app_handle, false).await
+}
+
+async fn set_locked(
+    app_state: &AppState,
+    signaling_state: &SignalingState,
+    app_handle: &AppHandle,
+    locked: bool,
+) -> Result<()> {
+    let session_id = active_session_id(app_state).await?;
+    if !signaling_state.inner.read().await.is_host {
+        return Err(Error::Session(
+            "Only the host can lock the session".to_string(),
+        ));
+    }
+
+    if let Some(supabase) = session_store(app_state).await {
+        supabase.set_session_locked(&session_id, locked).await?;
+    }
+
+    if let Some(session) = app_state.inner.write().await.session.as_mut() {
+        session.locked = locked;
+    }
+    persist_session(app_handle).await;
+
+    tracing::info!(
+        "Session {} {}",
+        session_id,
+        if locked { "locked" } else { "unlocked" }
+    );
+    if let Err(e) = app_handle.emit("session:lock-changed", &LockChanged { locked }) {
+        tracing::error!("Failed to emit session lock event: {}", e);
+    }
+    Ok(())
+}
+
+// ==========================================
+// Co-host Moderation
+// ==========================================
+
+/// Mute or unmute a viewer in session chat (host or co-host)
+///
+/// Everyone drops the muted viewer's chat messages, and the viewer's own app
+/// refuses to send any.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn mute_chat(
+    user_id: String,
+    muted: bool,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let action = ModerationAction::MuteChat {
+        user_id: user_id.clone(),
+        muted,
+    };
+    if relay_to_host(&app_handle, action).await? {
+        tracing::info!("Asked the host to change the chat mute of {}", user_id);
+        return Ok(());
+    }
+
+    let local_user_id = local_user_id(&app_state).await?;
+    set_chat_muted(&app_handle, &local_user_id, &user_id, muted).await
+}
+
+/// Pass a moderation action to the host when the local user is a co-host
+///
+/// Returns `false` when the local user hosts and carries it out itself.
+pub(crate) async fn relay_to_host(
+    app_handle: &AppHandle,
+    action: ModerationAction,
+) -> Result<bool> {
+    let app_state = app_handle.state::<AppState>();
+    let local_user_id = local_user_id(&app_state).await?;
+
+    let signaling_state = app_handle.state::<SignalingState>();
+    let state = signaling_state.inner.read().await;
+    if state.is_host {
+        return Ok(false);
+    }
+    if !state.cohosts.contains(&local_user_id) {
+        return Err(Error::Session(
+            "Only the host or a co-host can do this".to_string(),
+        ));
+    }
+    let host_id = state
+        .host_id()
+        .map(String::from)
+        .ok_or_else(|| Error::Session("The host is not in the session".to_string()))?;
+    if state.peer_lacks(&host_id, CAP_COHOST_MODERATION) {
+        return Err(Error::Session(
+            "The host runs a build that doesn't take co-host moderation".to_string(),
+        ));
+    }
+    let tx = state
+        .signaling_tx
+        .clone()
+        .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+    drop(state);
+
+    tx.send(SignalingMessage::ModerationRequest {
+        from_user_id: local_user_id,
+        to_user_id: host_id,
+        action,
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send moderation request: {}", e)))?;
+    Ok(true)
+}
+
+/// Handle co-host moderation, the shared waiting room and chat mutes
+///
+/// Returns `true` when the message was one of these.
+pub(crate) async fn handle_moderation(
+    app_handle: &AppHandle,
+    msg: &SignalingMessage,
+    local_user_id: &str,
+) -> bool {
+    match msg {
+        SignalingMessage::ModerationRequest {
+            from_user_id,
+            action,
+            ..
+        } => carry_out_moderation(app_handle, from_user_id, action.clone(), local_user_id).await,
+        SignalingMessage::WaitingRoom {
+            from_user_id,
+            user_ids,
+        } => apply_waiting_room(app_handle, from_user_id, user_ids, local_user_id).await,
+        SignalingMessage::ChatMuted {
+            from_user_id,
+            user_id,
+            muted,
+        } => {
+            let signaling_state = app_handle.state::<SignalingState>();
+            let mut state = signaling_state.inner.write().await;
+            if state.host_id() != Some(from_user_id.as_str()) {
+                tracing::warn!("Ignoring chat mute from non-host {}", from_user_id);
+                return true;
+            }
+            if *muted {
+                state.chat_muted.insert(user_id.clone());
+            } else {
+                state.chat_muted.remove(user_id);
+            }
+            emit_participants(app_handle, &state);
+            emit_chat_mute_changed(app_handle, user_id, *muted);
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Carry out a co-host's moderation as the host, after checking its role
+async fn carry_out_moderation(
+    app_handle: &AppHandle,
+    cohost_id: &str,
+    action: ModerationAction,
+    local_user_id: &str,
+) {
+    {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let state = signaling_state.inner.read().await;
+        if !state.is_host || !state.cohosts.contains(cohost_id) {
+            tracing::warn!("Ignoring moderation from non-co-host {}", cohost_id);
+            return;
+        }
+        // Co-hosts moderate viewers, not the host or each other
+        if let ModerationAction::MuteChat { user_id, .. } = &action {
+            if user_id == local_user_id || state.cohosts.contains(user_id) {
+                tracing::warn!("Co-host {} can't mute {} in chat", cohost_id, user_id);
+                return;
+            }
+        }
+    }
+
+    let result = match action.clone() {
+        ModerationAction::ApproveJoin { user_id } => {
+            admit(app_handle, local_user_id, &user_id).await
+        }
+        ModerationAction::DenyJoin { user_id, reason } => {
+            deny(app_handle, local_user_id, &user_id, reason).await
+        }
+        ModerationAction::GrantControl {
+            user_id,
+            duration_secs,
+        } => signaling::grant(app_handle, user_id, duration_secs.filter(|s| *s > 0)).await,
+        ModerationAction::RevokeControl { user_id } => signaling::revoke(app_handle, user_id).await,
+        ModerationAction::MuteChat { user_id, muted } => {
+            set_chat_muted(app_handle, local_user_id, &user_id, muted).await
+        }
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to carry out moderation for {}: {}", cohost_id, e);
+        return;
+    }
+
+    tracing::info!("Carried out {:?} for co-host {}", action, cohost_id);
+    let applied = ModerationApplied {
+        by_user_id: cohost_id.to_string(),
+        action,
+    };
+    if let Err(e) = app_handle.emit("session:moderation", &applied) {
+        tracing::error!("Failed to emit moderation event: {}", e);
+    }
+}
+
+/// Follow the host's waiting room as a co-host
+async fn apply_waiting_room(
+    app_handle: &AppHandle,
+    from_user_id: &str,
+    user_ids: &[String],
+    local_user_id: &str,
+) {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    if state.host_id() != Some(from_user_id) || !state.cohosts.contains(local_user_id) {
+        return;
+    }
+
+    let cancelled: Vec<String> = state
+        .join_requests
+        .keys()
+        .filter(|id| !user_ids.contains(id))
+        .cloned()
+        .collect();
+    for user_id in &cancelled {
+        state.join_requests.remove(user_id);
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut new_requests = Vec::new();
+    for user_id in user_ids {
+        if !state.join_requests.contains_key(user_id) {
+            let request = JoinRequest {
+                user_id: user_id.clone(),
+                requested_at: now.clone(),
+            };
+            state.join_requests.insert(user_id.clone(), request.clone());
+            new_requests.push(request);
+        }
+    }
+    drop(state);
+
+    for user_id in &cancelled {
+        emit_join_cancelled(app_handle, user_id);
+    }
+    for request in new_requests {
+        if let Err(e) = app_handle.emit("session:join-request", &request) {
+            tracing::error!("Failed to emit join request event: {}", e);
+        }
+    }
 }
 
-fn generate_join_code() -> String {
+/// Mute or unmute a participant in chat as the host and tell everyone
+async fn set_chat_muted(
+    app_handle: &AppHandle,
+    local_user_id: &str,
+    user_id: &str,
+    muted: bool,
+) -> Result<()> {
+    if user_id == local_user_id {
+        return Err(Error::Session(
+            "The host can't be muted in chat".to_string(),
+        ));
+    }
+
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    if !state.participants.contains_key(user_id) {
+        return Err(Error::NotFound(format!(
+            "{} is not in the session",
+            user_id
+        )));
+    }
+    let tx = state
+        .signaling_tx
+        .clone()
+        .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+    tx.send(SignalingMessage::ChatMuted {
+        from_user_id: local_user_id.to_string(),
+        user_id: user_id.to_string(),
+        muted,
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send chat mute: {}", e)))?;
+
+    if muted {
+        state.chat_muted.insert(user_id.to_string());
+    } else {
+        state.chat_muted.remove(user_id);
+    }
+    emit_participants(app_handle, &state);
+    emit_chat_mute_changed(app_handle, user_id, muted);
+
+    tracing::info!(
+        "{} {} in chat",
+        user_id,
+        if muted { "muted" } else { "unmuted" }
+    );
+    Ok(())
+}
+
+fn emit_chat_mute_changed(app_handle: &AppHandle, user_id: &str, muted: bool) {
+    let change = ChatMuteChanged {
+        user_id: user_id.to_string(),
+        muted,
+    };
+    if let Err(e) = app_handle.emit("session:chat-mute-changed", &change) {
+        tracing::error!("Failed to emit chat mute event: {}", e);
+    }
+}
+
+// ==========================================
+// Host Handoff
+// ==========================================
+
+/// Name or drop a co-host, next in line should the host leave (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_cohost(
+    user_id: String,
+    is_cohost: bool,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let local_user_id = local_user_id(&app_state).await?;
+    let session_id = active_session_id(&app_state).await?;
+    if is_cohost {
+        let state = signaling_state.inner.read().await;
+        check_handoff_target(&state, &local_user_id, &user_id)?;
+    } else if !signaling_state.inner.read().await.is_host {
+        return Err(Error::Session(
+            "Only the host can change co-hosts".to_string(),
+        ));
+    }
+
+    if let Some(supabase) = session_store(&app_state).await {
+        supabase
+            .set_session_cohost(&session_id, &user_id, is_cohost)
+            .await?;
+    }
+
+    let mut state = signaling_state.inner.write().await;
+    let tx = state
+        .signaling_tx
+        .clone()
+        .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+    tx.send(SignalingMessage::CohostChanged {
+        from_user_id: local_user_id.clone(),
+        user_id: user_id.clone(),
+        is_cohost,
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send co-host change: {}", e)))?;
+
+    if is_cohost {
+        state.cohosts.insert(user_id.clone());
+        share_waiting_room(&state, &local_user_id);
+    } else {
+        state.cohosts.remove(&user_id);
+    }
+    emit_participants(&app_handle, &state);
+
+    tracing::info!("Set co-host {}: {}", user_id, is_cohost);
+    Ok(())
+}
+
+/// Hand the session over to another participant (host only)
+///
+/// The previous host stays on as a co-host. Control is released, since it
+/// was granted over the previous host's screen.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn transfer_host(
+    user_id: String,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let local_user_id = local_user_id(&app_state).await?;
+    let session_id = active_session_id(&app_state).await?;
+    {
+        let state = signaling_state.inner.read().await;
+        check_handoff_target(&state, &local_user_id, &user_id)?;
+    }
+
+    // Record the new host first, so a reconnect doesn't take the session back
+    if let Some(supabase) = session_store(&app_state).await {
+        supabase
+            .transfer_session_host(&session_id, &user_id)
+            .await?;
+    }
+
+    {
+        let mut state = signaling_state.inner.write().await;
+        let tx = state
+            .signaling_tx
+            .clone()
+            .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+        tx.send(SignalingMessage::HostTransferred {
+            from_user_id: local_user_id.clone(),
+            to_user_id: user_id.clone(),
+        })
+        .await
+        .map_err(|e| Error::Network(format!("Failed to send host transfer: {}", e)))?;
+
+        state.change_host(&user_id, &local_user_id);
+        emit_participants(&app_handle, &state);
+    }
+
+    host_changed(&app_handle, &user_id, Some(local_user_id)).await;
+    tracing::info!("Handed the session over to {}", user_id);
+    Ok(())
+}
+
+/// Apply co-host changes and host transfers
+///
+/// Returns `true` when the message was a role change and has been handled.
+/// Only the current host may change roles, except for a co-host announcing
+/// it took over after the host left.
+pub(crate) async fn handle_role_change(
+    app_handle: &AppHandle,
+    msg: &SignalingMessage,
+    local_user_id: &str,
+) -> bool {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    let host_id = state.host_id().map(String::from);
+
+    match msg {
+        SignalingMessage::CohostChanged {
+            from_user_id,
+            user_id,
+            is_cohost,
+        } => {
+            if host_id.as_deref() != Some(from_user_id.as_str()) {
+                tracing::warn!("Ignoring co-host change from non-host {}", from_user_id);
+                return true;
+            }
+            if *is_cohost {
+                state.cohosts.insert(user_id.clone());
+            } else {
+                state.cohosts.remove(user_id);
+                // A former co-host no longer answers join requests
+                if user_id == local_user_id && !state.is_host {
+                    state.join_requests.clear();
+                }
+            }
+            emit_participants(app_handle, &state);
+        }
+        SignalingMessage::HostTransferred {
+            from_user_id,
+            to_user_id,
+        } => {
+            let from_host = host_id.as_deref() == Some(from_user_id.as_str());
+            // A co-host only takes over for itself once the host is gone
+            let succeeding = host_id.is_none()
+                && from_user_id == to_user_id
+                && state.cohosts.contains(to_user_id);
+            if !from_host && !succeeding {
+                tracing::warn!("Ignoring host transfer from non-host {}", from_user_id);
+                return true;
+            }
+
+            state.change_host(to_user_id, local_user_id);
+            emit_participants(app_handle, &state);
+            drop(state);
+
+            let previous_host_id = host_id.filter(|_| from_host);
+            host_changed(app_handle, to_user_id, previous_host_id).await;
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Take over as host if the host left and we are the co-host next in line
+pub(crate) async fn succeed_host(app_handle: &AppHandle, local_user_id: &str) {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let tx = {
+        let mut state = signaling_state.inner.write().await;
+        if state.host_id().is_some() || state.successor() != Some(local_user_id) {
+            return;
+        }
+        let Some(tx) = state.signaling_tx.clone() else {
+            return;
+        };
+        state.change_host(local_user_id, local_user_id);
+        emit_participants(app_handle, &state);
+        tx
+    };
+
+    tracing::info!("Host left, taking over the session");
+    let announcement = SignalingMessage::HostTransferred {
+        from_user_id: local_user_id.to_string(),
+        to_user_id: local_user_id.to_string(),
+    };
+    if let Err(e) = tx.send(announcement).await {
+        tracing::warn!("Failed to announce host takeover: {}", e);
+    }
+
+    let app_state = app_handle.state::<AppState>();
+    if let (Some(supabase), Ok(session_id)) = (
+        session_store(&app_state).await,
+        active_session_id(&app_state).await,
+    ) {
+        if let Err(e) = supabase
+            .transfer_session_host(&session_id, local_user_id)
+            .await
+        {
+            tracing::warn!("Failed to record host takeover: {}", e);
+        }
+    }
+
+    host_changed(app_handle, local_user_id, None).await;
+}
+
+/// Follow a host change locally and tell the frontend
+///
+/// Control was released with the change, so input injection stops too.
+async fn host_changed(app_handle: &AppHandle, host_id: &str, previous_host_id: Option<String>) {
+    let local_user_id = {
+        let app_state = app_handle.state::<AppState>();
+        let mut inner = app_state.inner.write().await;
+        let local_user_id = inner.user.as_ref().map(|u| u.id.clone());
+        let is_host = local_user_id.as_deref() == Some(host_id);
+        if let Some(session) = inner.session.as_mut() {
+            session.is_host = is_host;
+        }
+        inner.is_input_enabled = false;
+        local_user_id
+    };
+
+    persist_session(app_handle).await;
+
+    let change = HostChanged {
+        host_id: host_id.to_string(),
+        previous_host_id,
+        is_host: local_user_id.as_deref() == Some(host_id),
+    };
+    tracing::info!("{} is now hosting the session", host_id);
+    if let Err(e) = app_handle.emit("session:host-changed", &change) {
+        tracing::error!("Failed to emit host change event: {}", e);
+    }
+}
+
+/// Check the local user hosts and may hand the session to a participant
+fn check_handoff_target(
+    state: &SignalingStateInner,
+    local_user_id: &str,
+    user_id: &str,
+) -> Result<()> {
+    if !state.is_host {
+        return Err(Error::Session(
+            "Only the host can hand the session over".to_string(),
+        ));
+    }
+    if user_id == local_user_id {
+        return Err(Error::Session("You are already the host".to_string()));
+    }
+    if !state.participants.contains_key(user_id) {
+        return Err(Error::NotFound(format!(
+            "{} is not in the session",
+            user_id
+        )));
+    }
+    if state.peer_lacks(user_id, CAP_HOST_HANDOFF) {
+        return Err(Error::Session(format!(
+            "{} runs a build that can't take over hosting",
+            user_id
+        )));
+    }
+    Ok(())
+}
+
+async fn active_session_id(app_state: &AppState) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    inner
+        .session
+        .as_ref()
+        .map(|s| s.id.clone())
+        .ok_or_else(|| Error::Session("No active session".to_string()))
+}
+
+/// Supabase client the active session is recorded in, `None` for LAN sessions
+async fn session_store(app_state: &AppState) -> Option<&SupabaseClient> {
+    let is_lan = app_state
+        .inner
+        .read()
+        .await
+        .session
+        .as_ref()
+        .is_some_and(|s| s.mode == SessionMode::Lan);
+    app_state.supabase.as_ref().filter(|_| !is_lan)
+}
+
+async fn local_user_id(app_state: &AppState) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    inner
+        .user
+        .as_ref()
+        .map(|u| u.id.clone())
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))
+}
+
+/// Signaling sender for answering a pending join request
+fn pending_request_tx(
+    state: &SignalingStateInner,
+    user_id: &str,
+) -> Result<tokio::sync::mpsc::Sender<SignalingMessage>> {
+    if !state.is_host {
+        return Err(Error::Session(
+            "Only the host can answer join requests".to_string(),
+        ));
+    }
+    if !state.join_requests.contains_key(user_id) {
+        return Err(Error::NotFound(format!("No join request from {}", user_id)));
+    }
+    state
+        .signaling_tx
+        .clone()
+        .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))
+}
+
+/// Join attempt throttle, recovering it if a panic poisoned the lock
+fn throttle(state: &AppState) -> std::sync::MutexGuard<'_, JoinThrottle> {
+    state
+        .join_throttle
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Count a join code lookup towards the user's throttle
+fn record_join_attempt(state: &AppState, user_id: &str, found: bool) {
+    let mut throttle = throttle(state);
+    if found {
+        throttle.record_success(user_id);
+    } else {
+        tracing::info!("Join attempt with an unknown code");
+        throttle.record_failure(user_id, Instant::now());
+    }
+}
+
+pub(crate) fn generate_join_code() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
 
     let timestamp = SystemTime::now()
@@ -192,3 +1918,38 @@ fn generate_join_code() -> String {
 
     code
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supabase::session_lookup_error;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_locked_session_refuses_join() {
+        let error = session_lookup_error(StatusCode::LOCKED, "");
+        assert!(matches!(error, Error::SessionLocked));
+        assert!(refuses_join(&error));
+    }
+
+    #[test]
+    fn test_lookup_refusals() {
+        let error = session_lookup_error(StatusCode::TOO_MANY_REQUESTS, r#"{"details":"8"}"#);
+        assert!(matches!(
+            error,
+            Error::TooManyAttempts {
+                retry_after_secs: 8
+            }
+        ));
+        assert!(refuses_join(&error));
+        assert!(refuses_join(&session_lookup_error(
+            StatusCode::FORBIDDEN,
+            ""
+        )));
+
+        // Anything else falls back to the local network
+        let error = session_lookup_error(StatusCode::BAD_GATEWAY, "upstream");
+        assert!(!refuses_join(&error));
+        assert!(!refuses_join(&Error::Network("timed out".to_string())));
+    }
+}
@@ -1,11 +1,140 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
 
-use crate::realtime::{RealtimeClient, SignalingMessage};
-use crate::state::AppState;
+use crate::commands::input::auto_revoke;
+use crate::commands::session::{self, JoinRequest};
+use crate::commands::{camera, notifications, voice};
+use crate::e2e::SessionCipher;
+use crate::input::AutoRevokeReason;
+use crate::lan::LanSignaling;
+use crate::realtime::{ModerationAction, PresenceUpdate, RealtimeClient, SignalingMessage};
+use crate::signaling_protocol::PeerProtocol;
+use crate::state::{AppState, SessionMode};
+use crate::supabase::SessionMessageRow;
 use crate::{Error, Result};
 
+/// Minimum interval between outgoing cursor position broadcasts
+const CURSOR_BROADCAST_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Chat history messages returned when no limit is given
+const DEFAULT_CHAT_HISTORY_LIMIT: u32 = 50;
+
+/// Most chat history messages returned at once
+const MAX_CHAT_HISTORY_LIMIT: u32 = 200;
+
+/// How long a control request waits for the host when no timeout is set
+const DEFAULT_CONTROL_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Palette used to color remote cursors, indexed by a hash of the user ID
+const CURSOR_COLORS: [&str; 8] = [
+    "#EF4444", "#F97316", "#EAB308", "#22C55E", "#06B6D4", "#3B82F6", "#8B5CF6", "#EC4899",
+];
+
+/// Remote cursor payload emitted to the frontend
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RemoteCursor {
+    pub user_id: String,
+    pub x: f64,
+    pub y: f64,
+    pub color: String,
+}
+
+/// Stored session chat message, shaped like a live `ChatMessage`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionChatMessage {
+    pub id: String,
+    pub from_user_id: String,
+    pub from_username: String,
+    pub content: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Time left on a timed control grant, emitted every second
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ControlCountdown {
+    pub user_id: String,
+    pub remaining_secs: u64,
+    pub expires_at: String,
+}
+
+/// Viewer's control request waiting for the host's answer
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PendingControlRequest {
+    pub user_id: String,
+    pub requested_at: String,
+    pub expires_at: String,
+    #[serde(skip)]
+    deadline: Instant,
+}
+
+/// Session participant as tracked by the roster
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Participant {
+    pub user_id: String,
+    pub is_host: bool,
+    /// Whether the participant takes over if the host leaves
+    pub is_cohost: bool,
+    /// Whether the participant currently holds remote control
+    pub has_control: bool,
+    /// Protocol version and capabilities the participant announced
+    pub protocol: Option<PeerProtocol>,
+    /// Whether the participant's microphone is muted
+    pub is_muted: bool,
+    /// Whether the host muted the participant in session chat
+    pub is_chat_muted: bool,
+    /// Moderation the participant may do
+    pub permissions: Vec<SessionPermission>,
+    pub joined_at: String,
+}
+
+/// Moderation in a session; the host may do all of it and delegates some
+/// to co-hosts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPermission {
+    /// Let viewers in from the waiting room or turn them away
+    AdmitJoin,
+    /// Grant and revoke remote control
+    ManageControl,
+    /// Mute viewers in session chat
+    MuteChat,
+    /// Kick and ban participants
+    RemoveParticipants,
+    /// Name co-hosts and hand the session over
+    ManageCohosts,
+}
+
+impl SessionPermission {
+    const ALL: [SessionPermission; 5] = [
+        SessionPermission::AdmitJoin,
+        SessionPermission::ManageControl,
+        SessionPermission::MuteChat,
+        SessionPermission::RemoveParticipants,
+        SessionPermission::ManageCohosts,
+    ];
+
+    /// Whether co-hosts may do it too
+    pub fn is_delegated(self) -> bool {
+        matches!(
+            self,
+            SessionPermission::AdmitJoin
+                | SessionPermission::ManageControl
+                | SessionPermission::MuteChat
+        )
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct IncompatiblePeer {
+    user_id: String,
+    protocol_version: u32,
+}
+
 /// Signaling state managed by Tauri
 pub struct SignalingState {
     pub inner: Arc<RwLock<SignalingStateInner>>,
@@ -14,8 +143,162 @@ pub struct SignalingState {
 #[derive(Default)]
 pub struct SignalingStateInner {
     pub realtime: Option<RealtimeClient>,
+    /// Direct signaling of a LAN session, in place of `realtime`
+    pub lan: Option<LanSignaling>,
     pub signaling_tx: Option<mpsc::Sender<SignalingMessage>>,
     pub is_connected: bool,
+    pub last_cursor_sent: Option<Instant>,
+    /// Participant currently holding remote control
+    pub controller_id: Option<String>,
+    /// Countdown of a timed control grant
+    pub control_timer: Option<JoinHandle<()>>,
+    /// Everyone currently in the session, including the local user
+    pub participants: HashMap<String, Participant>,
+    /// Whether the local user hosts the session and decides who gets in
+    pub is_host: bool,
+    /// Viewers the host let in from the waiting room
+    pub admitted: HashSet<String>,
+    /// Viewers waiting for the host to let them in, by user ID
+    pub join_requests: HashMap<String, JoinRequest>,
+    /// Users the host banned, whose join attempts are refused
+    pub banned: HashSet<String>,
+    /// Protocol each participant announced in their join
+    pub peer_protocols: HashMap<String, PeerProtocol>,
+    /// Peers on builds too old to talk to, whose messages are dropped
+    pub incompatible: HashSet<String>,
+    /// Participants the host named to take over should it leave
+    pub cohosts: HashSet<String>,
+    /// Participants whose microphone is muted, including the local user
+    pub muted: HashSet<String>,
+    /// Participants the host muted in session chat, whose messages are dropped
+    pub chat_muted: HashSet<String>,
+    /// Media stream of each participant's camera while it is on
+    pub cameras: HashMap<String, String>,
+    /// Whether signaling payloads are end-to-end encrypted
+    pub encrypted: bool,
+    /// Viewers' control requests waiting for an answer, oldest first (host only)
+    pub control_requests: Vec<PendingControlRequest>,
+    /// How long control requests wait before expiring, `None` for the default
+    pub control_request_timeout: Option<Duration>,
+}
+
+impl SignalingStateInner {
+    /// Roster sorted with the host first, then by join time
+    pub fn roster(&self) -> Vec<Participant> {
+        let mut roster: Vec<Participant> = self
+            .participants
+            .values()
+            .map(|p| Participant {
+                is_cohost: self.cohosts.contains(&p.user_id),
+                has_control: self.controller_id.as_deref() == Some(p.user_id.as_str()),
+                protocol: self.peer_protocols.get(&p.user_id).cloned(),
+                is_muted: self.muted.contains(&p.user_id),
+                is_chat_muted: self.chat_muted.contains(&p.user_id),
+                permissions: self.permissions(&p.user_id),
+                ..p.clone()
+            })
+            .collect();
+        roster.sort_by(|a, b| {
+            b.is_host
+                .cmp(&a.is_host)
+                .then_with(|| a.joined_at.cmp(&b.joined_at))
+        });
+        roster
+    }
+
+    /// User currently hosting the session, if they are still here
+    pub fn host_id(&self) -> Option<&str> {
+        self.participants
+            .values()
+            .find(|p| p.is_host)
+            .map(|p| p.user_id.as_str())
+    }
+
+    /// Moderation a participant may do: everything for the host, the
+    /// delegated part for co-hosts
+    pub fn permissions(&self, user_id: &str) -> Vec<SessionPermission> {
+        let is_host = self.participants.get(user_id).is_some_and(|p| p.is_host);
+        let is_cohost = self.cohosts.contains(user_id);
+        SessionPermission::ALL
+            .into_iter()
+            .filter(|permission| is_host || (is_cohost && permission.is_delegated()))
+            .collect()
+    }
+
+    /// Co-host next in line to take over, the one with the lowest user ID so
+    /// that every client agrees
+    pub fn successor(&self) -> Option<&str> {
+        self.cohosts
+            .iter()
+            .filter(|id| self.participants.contains_key(*id))
+            .min()
+            .map(String::as_str)
+    }
+
+    /// Make `new_host` the host; the previous host stays on as co-host
+    ///
+    /// Control is released, since it was granted over the previous host's
+    /// screen. A local user taking over admits everyone already in the roster.
+    pub fn change_host(&mut self, new_host: &str, local_user_id: &str) {
+        for p in self.participants.values_mut() {
+            if p.is_host && p.user_id != new_host {
+                self.cohosts.insert(p.user_id.clone());
+            }
+            p.is_host = p.user_id == new_host;
+        }
+        self.cohosts.remove(new_host);
+        self.release_control();
+        self.control_requests.clear();
+
+        self.is_host = new_host == local_user_id;
+        if self.is_host {
+            self.admitted = self
+                .participants
+                .keys()
+                .filter(|id| *id != local_user_id)
+                .cloned()
+                .collect();
+        } else {
+            self.admitted.clear();
+            self.join_requests.clear();
+        }
+    }
+
+    /// Whether a participant may take part; only the host keeps a waiting room
+    pub fn is_admitted(&self, user_id: &str) -> bool {
+        !self.is_host || self.admitted.contains(user_id)
+    }
+
+    /// Whether a peer is known to run a build without a capability
+    pub fn peer_lacks(&self, user_id: &str, capability: &str) -> bool {
+        self.peer_protocols
+            .get(user_id)
+            .is_some_and(|p| !p.supports(capability))
+    }
+
+    /// Drop a viewer's pending control request, returning whether there was one
+    pub fn remove_control_request(&mut self, user_id: &str) -> bool {
+        let before = self.control_requests.len();
+        self.control_requests.retain(|r| r.user_id != user_id);
+        self.control_requests.len() != before
+    }
+
+    /// Clear the controller and stop its grant countdown, returning who had control
+    pub fn release_control(&mut self) -> Option<String> {
+        if let Some(timer) = self.control_timer.take() {
+            timer.abort();
+        }
+        self.controller_id.take()
+    }
+
+    /// Viewers in the roster, excluding the local user
+    pub fn viewer_ids(&self, local_user_id: &str) -> Vec<String> {
+        self.participants
+            .values()
+            .filter(|p| !p.is_host && p.user_id != local_user_id)
+            .map(|p| p.user_id.clone())
+            .collect()
+    }
 }
 
 impl Default for SignalingState {
@@ -27,9 +310,16 @@ impl Default for SignalingState {
 }
 
 /// Connect to signaling channel for a session
+///
+/// With `encrypted`, signaling payloads are end-to-end encrypted with keys
+/// derived from the session join code, and plaintext ones are dropped.
+/// LAN sessions are signaled directly: the host serves the channel itself
+/// and viewers connect to it.
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn connect_signaling(
     session_id: String,
+    encrypted: Option<bool>,
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
     app_handle: AppHandle,
@@ -47,29 +337,234 @@ pub async fn connect_signaling(
         .as_ref()
         .ok_or_else(|| Error::Session("No active session".to_string()))?;
     let is_host = session.is_host;
+    let mode = session.mode;
+    let join_code = session.join_code.clone();
+    let lan_host = session.lan_host.clone();
+    let cipher = SessionCipher::new(
+        &session.join_code,
+        &session_id,
+        &user_id,
+        encrypted.unwrap_or(false),
+    );
     drop(inner);
 
-    // Create realtime client
-    let realtime = RealtimeClient::from_env()?;
-    realtime.set_access_token(Some(access_token)).await;
+    let row = match (
+        is_host && mode == SessionMode::Cloud,
+        app_state.supabase.as_ref(),
+    ) {
+        (true, Some(supabase)) => match supabase.get_session(&session_id).await {
+            Ok(row) => row,
+            Err(e) => {
+                tracing::warn!("Failed to load session: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
 
-    // Join the session channel
-    let (mut signaling_rx, signaling_tx) = realtime
-        .join_channel(&session_id, &user_id, is_host)
-        .await?;
+    // A host that handed the session over while away rejoins as a viewer
+    let handed_over = row.as_ref().is_some_and(|r| r.host_id != user_id);
+    let is_host = is_host && !handed_over;
+    if handed_over {
+        tracing::info!("Session was handed over, rejoining as a viewer");
+        if let Some(session) = app_state.inner.write().await.session.as_mut() {
+            session.is_host = false;
+        }
+    } else if let Some(ref row) = row {
+        // The lock outlives the channel as well
+        if let Some(session) = app_state.inner.write().await.session.as_mut() {
+            session.locked = row.locked;
+        }
+    }
+
+    // Bans and co-hosts outlive the channel, keep them after a reconnect
+    let (banned, cohosts) = row
+        .filter(|_| is_host)
+        .map(|r| (r.banned_user_ids, r.cohost_ids))
+        .unwrap_or_default();
+
+    // Join the session channel, or connect directly on the local network
+    let (mut realtime, mut lan, mut presence_rx) = (None, None, None);
+    let (mut signaling_rx, signaling_tx) = match mode {
+        SessionMode::Cloud => {
+            let client = RealtimeClient::from_env()?;
+            client.set_access_token(Some(access_token)).await;
+            presence_rx = Some(client.subscribe_presence().await);
+            let channels = client
+                .join_channel(&session_id, &user_id, is_host, cipher)
+                .await?;
+            realtime = Some(client);
+            channels
+        }
+        SessionMode::Lan => {
+            let (signaling, rx, tx) = if is_host {
+                LanSignaling::host(&session_id, &join_code, &user_id, cipher).await?
+            } else {
+                let host = lan_host
+                    .ok_or_else(|| Error::Session("LAN session host not found".to_string()))?;
+                LanSignaling::connect(&host, &user_id, cipher).await?
+            };
+            lan = Some(signaling);
+            (rx, tx)
+        }
+    };
 
     // Update signaling state
     {
         let mut state = signaling_state.inner.write().await;
-        state.realtime = Some(realtime);
+        state.realtime = realtime;
+        state.lan = lan;
         state.signaling_tx = Some(signaling_tx);
         state.is_connected = true;
+        state.is_host = is_host;
+        state.encrypted = encrypted.unwrap_or(false);
+        state.banned = banned.into_iter().collect();
+        state.cohosts = cohosts.into_iter().collect();
+        state
+            .peer_protocols
+            .insert(user_id.clone(), PeerProtocol::local());
+        state.participants.clear();
+        state.participants.insert(
+            user_id.clone(),
+            Participant {
+                user_id: user_id.clone(),
+                is_host,
+                is_cohost: false,
+                has_control: false,
+                protocol: None,
+                is_muted: false,
+                is_chat_muted: false,
+                permissions: Vec::new(),
+                joined_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+    }
+
+    // Spawn task to keep the roster in sync with channel presence; on a LAN
+    // the host reports viewers that drop off as having left
+    if let Some(mut presence_rx) = presence_rx {
+        let app_handle_presence = app_handle.clone();
+        let local_user_id = user_id.clone();
+        tokio::spawn(async move {
+            while let Ok(update) = presence_rx.recv().await {
+                apply_presence(&app_handle_presence, update, &local_user_id).await;
+                session::succeed_host(&app_handle_presence, &local_user_id).await;
+            }
+        });
     }
 
     // Spawn task to forward incoming signaling messages to frontend
     let app_handle_clone = app_handle.clone();
     tokio::spawn(async move {
         while let Ok(msg) = signaling_rx.recv().await {
+            // Messages addressed to another peer are not ours to handle
+            if !msg.is_for(&user_id) {
+                continue;
+            }
+
+            // Peers too old to understand us are refused
+            if !check_protocol(&app_handle_clone, &msg).await {
+                continue;
+            }
+
+            // The host keeps viewers in the waiting room until approved
+            if !session::screen_waiting_room(&app_handle_clone, &msg, &user_id).await {
+                continue;
+            }
+
+            if let SignalingMessage::ParticipantRemoved { banned, .. } = &msg {
+                session::handle_removed(&app_handle_clone, &user_id, *banned).await;
+                continue;
+            }
+
+            if let SignalingMessage::JoinResponse {
+                approved, reason, ..
+            } = &msg
+            {
+                session::handle_join_response(&app_handle_clone, *approved, reason.clone()).await;
+                continue;
+            }
+
+            if session::handle_role_change(&app_handle_clone, &msg, &user_id).await {
+                continue;
+            }
+
+            if session::handle_moderation(&app_handle_clone, &msg, &user_id).await {
+                continue;
+            }
+
+            // Chat from participants the host muted goes nowhere
+            if let SignalingMessage::ChatMessage { from_user_id, .. } = &msg {
+                let signaling_state = app_handle_clone.state::<SignalingState>();
+                if signaling_state
+                    .inner
+                    .read()
+                    .await
+                    .chat_muted
+                    .contains(from_user_id)
+                {
+                    continue;
+                }
+            }
+
+            if let SignalingMessage::AudioMuted {
+                from_user_id,
+                user_id: muted_user_id,
+                muted,
+            } = &msg
+            {
+                voice::handle_audio_muted(&app_handle_clone, from_user_id, muted_user_id, *muted)
+                    .await;
+                continue;
+            }
+
+            if let SignalingMessage::CameraChanged {
+                from_user_id,
+                enabled,
+                stream_id,
+            } = &msg
+            {
+                camera::handle_camera_changed(
+                    &app_handle_clone,
+                    from_user_id,
+                    *enabled,
+                    stream_id.clone(),
+                )
+                .await;
+                continue;
+            }
+
+            // Repeated control requests keep their place in the queue
+            if let SignalingMessage::ControlRequest { from_user_id } = &msg {
+                if !queue_control_request(&app_handle_clone, from_user_id).await {
+                    continue;
+                }
+            }
+
+            update_roster(&app_handle_clone, &msg, &user_id).await;
+            if let SignalingMessage::UserLeft { .. } = &msg {
+                session::succeed_host(&app_handle_clone, &user_id).await;
+            }
+
+            // Remote cursors get their own payload with a stable per-user color
+            if let SignalingMessage::CursorPosition { from_user_id, x, y } = &msg {
+                let cursor = RemoteCursor {
+                    user_id: from_user_id.clone(),
+                    x: *x,
+                    y: *y,
+                    color: cursor_color(from_user_id).to_string(),
+                };
+                if let Err(e) = app_handle_clone.emit("session:remote-cursor", &cursor) {
+                    tracing::error!("Failed to emit remote cursor event: {}", e);
+                }
+                continue;
+            }
+
+            // Answers and ICE candidates belong to the native peer while it streams
+            if crate::commands::stream::handle_signaling(&app_handle_clone, &msg).await {
+                continue;
+            }
+
             // Emit to frontend
             let event_name = match &msg {
                 SignalingMessage::Offer { .. } => "signaling:offer",
@@ -81,6 +576,22 @@ pub async fn connect_signaling(
                 SignalingMessage::UserJoined { .. } => "signaling:user-joined",
                 SignalingMessage::UserLeft { .. } => "signaling:user-left",
                 SignalingMessage::ChatMessage { .. } => "signaling:chat-message",
+                SignalingMessage::CaptureSourceSwitched { .. } => "capture:source-switched",
+                SignalingMessage::AudioMuted { .. } => "audio:mute-changed",
+                SignalingMessage::CameraChanged { .. } => "camera:layout-changed",
+                SignalingMessage::CursorPosition { .. }
+                | SignalingMessage::JoinResponse { .. }
+                | SignalingMessage::ParticipantRemoved { .. }
+                | SignalingMessage::CohostChanged { .. }
+                | SignalingMessage::HostTransferred { .. }
+                | SignalingMessage::ModerationRequest { .. }
+                | SignalingMessage::WaitingRoom { .. }
+                | SignalingMessage::ChatMuted { .. }
+                | SignalingMessage::Ack { .. } => continue,
+                SignalingMessage::Unknown => {
+                    tracing::debug!("Ignoring signaling message from a newer protocol");
+                    continue;
+                }
             };
 
             if let Err(e) = app_handle_clone.emit(event_name, &msg) {
@@ -89,12 +600,15 @@ pub async fn connect_signaling(
         }
     });
 
+    session::persist_session(&app_handle).await;
+
     tracing::info!("Connected to signaling channel: {}", session_id);
     Ok(())
 }
 
 /// Disconnect from signaling channel
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn disconnect_signaling(
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
@@ -108,24 +622,70 @@ pub async fn disconnect_signaling(
             .unwrap_or_default()
     };
 
+    // A host leaving co-hosts behind lets them take over
+    let leaving_host = {
+        let state = signaling_state.inner.read().await;
+        state.is_host && !state.cohosts.is_empty()
+    };
+    if leaving_host {
+        let session_id = {
+            let inner = app_state.inner.read().await;
+            inner
+                .session
+                .as_ref()
+                .filter(|s| s.mode == SessionMode::Cloud)
+                .map(|s| s.id.clone())
+        };
+        if let (Some(supabase), Some(session_id)) = (app_state.supabase.as_ref(), session_id) {
+            if let Err(e) = supabase.mark_host_left(&session_id).await {
+                tracing::warn!("Failed to record leaving as host: {}", e);
+            }
+        }
+    }
+
     let mut state = signaling_state.inner.write().await;
+    leave_signaling(&mut state, &user_id).await?;
+
+    tracing::info!("Disconnected from signaling channel");
+    Ok(())
+}
 
+/// Leave the session channel and reset the signaling state
+pub(crate) async fn leave_signaling(state: &mut SignalingStateInner, user_id: &str) -> Result<()> {
     if let Some(ref realtime) = state.realtime {
-        realtime.leave_channel(&user_id).await?;
+        realtime.leave_channel(user_id).await?;
+    }
+    if let Some(lan) = state.lan.take() {
+        lan.leave(user_id);
     }
 
     state.realtime = None;
     state.signaling_tx = None;
     state.is_connected = false;
-
-    tracing::info!("Disconnected from signaling channel");
+    state.last_cursor_sent = None;
+    state.release_control();
+    state.participants.clear();
+    state.is_host = false;
+    state.admitted.clear();
+    state.join_requests.clear();
+    state.banned.clear();
+    state.peer_protocols.clear();
+    state.incompatible.clear();
+    state.cohosts.clear();
+    state.muted.clear();
+    state.chat_muted.clear();
+    state.cameras.clear();
+    state.encrypted = false;
+    state.control_requests.clear();
     Ok(())
 }
 
 /// Send a WebRTC offer (host only)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn send_offer(
     sdp: String,
+    to_user_id: Option<String>,
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
 ) -> Result<()> {
@@ -148,6 +708,7 @@ pub async fn send_offer(
     tx.send(SignalingMessage::Offer {
         sdp,
         from_user_id: user_id,
+        to_user_id,
     })
     .await
     .map_err(|e| Error::Network(format!("Failed to send offer: {}", e)))?;
@@ -157,8 +718,10 @@ pub async fn send_offer(
 
 /// Send a WebRTC answer (viewer only)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn send_answer(
     sdp: String,
+    to_user_id: Option<String>,
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
 ) -> Result<()> {
@@ -181,6 +744,7 @@ pub async fn send_answer(
     tx.send(SignalingMessage::Answer {
         sdp,
         from_user_id: user_id,
+        to_user_id,
     })
     .await
     .map_err(|e| Error::Network(format!("Failed to send answer: {}", e)))?;
@@ -190,10 +754,12 @@ pub async fn send_answer(
 
 /// Send an ICE candidate
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn send_ice_candidate(
     candidate: String,
     sdp_mid: Option<String>,
     sdp_m_line_index: Option<u32>,
+    to_user_id: Option<String>,
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
 ) -> Result<()> {
@@ -218,6 +784,7 @@ pub async fn send_ice_candidate(
         sdp_mid,
         sdp_m_line_index,
         from_user_id: user_id,
+        to_user_id,
     })
     .await
     .map_err(|e| Error::Network(format!("Failed to send ICE candidate: {}", e)))?;
@@ -227,6 +794,7 @@ pub async fn send_ice_candidate(
 
 /// Request control (viewer only)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn request_control(
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
@@ -256,46 +824,149 @@ pub async fn request_control(
     Ok(())
 }
 
-/// Grant control to a viewer (host only)
+/// Grant control to a viewer (host or co-host)
+///
+/// With `duration_secs`, control is revoked and input injection disabled
+/// automatically once the time runs out. A co-host's grant is carried out
+/// by the host.
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn grant_control(
     to_user_id: String,
-    signaling_state: State<'_, SignalingState>,
+    duration_secs: Option<u64>,
+    app_handle: AppHandle,
 ) -> Result<()> {
-    let state = signaling_state.inner.read().await;
+    if duration_secs == Some(0) {
+        return Err(Error::Session(
+            "Control duration must be at least 1 second".to_string(),
+        ));
+    }
+
+    let action = ModerationAction::GrantControl {
+        user_id: to_user_id.clone(),
+        duration_secs,
+    };
+    if session::relay_to_host(&app_handle, action).await? {
+        tracing::info!("Asked the host to grant control to {}", to_user_id);
+        return Ok(());
+    }
+    grant(&app_handle, to_user_id, duration_secs).await
+}
+
+/// Hand control to a viewer as the host
+pub(crate) async fn grant(
+    app_handle: &AppHandle,
+    to_user_id: String,
+    duration_secs: Option<u64>,
+) -> Result<()> {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
     let tx = state
         .signaling_tx
         .as_ref()
         .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
 
-    tx.send(SignalingMessage::ControlGrant { to_user_id })
-        .await
-        .map_err(|e| Error::Network(format!("Failed to send control grant: {}", e)))?;
+    tx.send(SignalingMessage::ControlGrant {
+        to_user_id: to_user_id.clone(),
+        duration_secs,
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send control grant: {}", e)))?;
 
+    state.release_control();
+    state.remove_control_request(&to_user_id);
+    if let Some(secs) = duration_secs {
+        state.control_timer = Some(spawn_control_timer(
+            app_handle.clone(),
+            to_user_id.clone(),
+            secs,
+            true,
+        ));
+    }
+    state.controller_id = Some(to_user_id);
+    emit_participants(app_handle, &state);
     Ok(())
 }
 
-/// Revoke control from a viewer (host only)
+/// Revoke control from a viewer (host or co-host)
 #[tauri::command]
-pub async fn revoke_control(
-    to_user_id: String,
-    signaling_state: State<'_, SignalingState>,
-) -> Result<()> {
-    let state = signaling_state.inner.read().await;
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn revoke_control(to_user_id: String, app_handle: AppHandle) -> Result<()> {
+    let action = ModerationAction::RevokeControl {
+        user_id: to_user_id.clone(),
+    };
+    if session::relay_to_host(&app_handle, action).await? {
+        tracing::info!("Asked the host to revoke control from {}", to_user_id);
+        return Ok(());
+    }
+    revoke(&app_handle, to_user_id).await
+}
+
+/// Take control back from a viewer as the host
+pub(crate) async fn revoke(app_handle: &AppHandle, to_user_id: String) -> Result<()> {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
     let tx = state
         .signaling_tx
         .as_ref()
         .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
 
-    tx.send(SignalingMessage::ControlRevoke { to_user_id })
-        .await
-        .map_err(|e| Error::Network(format!("Failed to send control revoke: {}", e)))?;
+    tx.send(SignalingMessage::ControlRevoke {
+        to_user_id: to_user_id.clone(),
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send control revoke: {}", e)))?;
+
+    if state.controller_id.as_deref() == Some(to_user_id.as_str()) {
+        state.release_control();
+    }
+    emit_participants(app_handle, &state);
+    Ok(())
+}
+
+/// Get the viewers' pending control requests, oldest first (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_control_requests(
+    signaling_state: State<'_, SignalingState>,
+) -> Result<Vec<PendingControlRequest>> {
+    let state = signaling_state.inner.read().await;
+    Ok(state.control_requests.clone())
+}
+
+/// Set how long control requests wait for an answer before expiring
+///
+/// Applies to requests made from now on.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_control_request_timeout(
+    timeout_secs: u64,
+    signaling_state: State<'_, SignalingState>,
+) -> Result<()> {
+    if timeout_secs == 0 {
+        return Err(Error::Session(
+            "Control request timeout must be at least 1 second".to_string(),
+        ));
+    }
 
+    let mut state = signaling_state.inner.write().await;
+    state.control_request_timeout = Some(Duration::from_secs(timeout_secs));
     Ok(())
 }
 
+/// Get the participants currently in the session, host first
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_session_participants(
+    signaling_state: State<'_, SignalingState>,
+) -> Result<Vec<Participant>> {
+    let state = signaling_state.inner.read().await;
+    Ok(state.roster())
+}
+
 /// Get signaling connection status
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn get_signaling_status(
     signaling_state: State<'_, SignalingState>,
 ) -> Result<bool> {
@@ -304,19 +975,29 @@ pub async fn get_signaling_status(
 }
 
 /// Send a chat message
+///
+/// The message is also stored in Supabase, so late joiners can read it with
+/// `get_session_chat_history`; a failure to store it doesn't fail the send.
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn send_chat_message(
     content: String,
     app_state: State<'_, AppState>,
     signaling_state: State<'_, SignalingState>,
 ) -> Result<String> {
-    let (user_id, username) = {
+    let (user_id, username, session_id) = {
         let inner = app_state.inner.read().await;
         let user = inner
             .user
             .as_ref()
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-        (user.id.clone(), user.email.clone())
+        // LAN sessions have nowhere to store the message
+        let session_id = inner
+            .session
+            .as_ref()
+            .filter(|s| s.mode == SessionMode::Cloud)
+            .map(|s| s.id.clone());
+        (user.id.clone(), user.email.clone(), session_id)
     };
 
     let state = signaling_state.inner.read().await;
@@ -324,6 +1005,11 @@ pub async fn send_chat_message(
         .signaling_tx
         .as_ref()
         .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+    if state.chat_muted.contains(&user_id) {
+        return Err(Error::Session(
+            "You were muted in this session's chat".to_string(),
+        ));
+    }
 
     // Generate unique message ID
     let message_id = uuid::Uuid::new_v4().to_string();
@@ -336,13 +1022,489 @@ pub async fn send_chat_message(
 
     tx.send(SignalingMessage::ChatMessage {
         id: message_id.clone(),
-        from_user_id: user_id,
-        from_username: username,
-        content,
+        from_user_id: user_id.clone(),
+        from_username: username.clone(),
+        content: content.clone(),
         timestamp,
     })
     .await
     .map_err(|e| Error::Network(format!("Failed to send chat message: {}", e)))?;
+    drop(state);
+
+    if let (Some(supabase), Some(session_id)) = (app_state.supabase.as_ref(), session_id) {
+        let sent_at = chrono::DateTime::from_timestamp_millis(timestamp as i64)
+            .unwrap_or_else(chrono::Utc::now)
+            .to_rfc3339();
+        let row = SessionMessageRow {
+            id: message_id.clone(),
+            session_id,
+            sender_id: Some(user_id),
+            sender_name: username,
+            content,
+            sent_at,
+        };
+        if let Err(e) = supabase.create_session_message(&row).await {
+            tracing::warn!("Failed to store session chat message: {}", e);
+        }
+    }
 
     Ok(message_id)
 }
+
+/// Get a session's chat history, oldest first, in the shape of live chat messages
+///
+/// Returns up to `limit` (default 50) messages sent before `before`, an
+/// RFC 3339 timestamp, or the latest ones.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_session_chat_history(
+    session_id: String,
+    limit: Option<u32>,
+    before: Option<String>,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<SessionChatMessage>> {
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    let limit = limit
+        .unwrap_or(DEFAULT_CHAT_HISTORY_LIMIT)
+        .min(MAX_CHAT_HISTORY_LIMIT);
+    let rows = supabase
+        .get_session_messages(&session_id, limit, before.as_deref())
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .rev()
+        .map(|row| SessionChatMessage {
+            timestamp: chrono::DateTime::parse_from_rfc3339(&row.sent_at)
+                .map(|t| t.timestamp_millis() as u64)
+                .unwrap_or_default(),
+            id: row.id,
+            from_user_id: row.sender_id.unwrap_or_default(),
+            from_username: row.sender_name,
+            content: row.content,
+        })
+        .collect())
+}
+
+/// Broadcast the local pointer position (viewers without control)
+///
+/// Coordinates are relative (0-1) to the shared screen. Calls arriving faster
+/// than the broadcast interval are dropped to stay within Realtime rate limits.
+/// The host and a viewer in control move the real pointer, so their calls
+/// are ignored.
+///
+/// Positions go over signaling rather than a peer data channel: a viewer's
+/// peer connection lives in the webview, out of reach of this command, and
+/// a broadcast reaches the host whether it streams natively or from the
+/// webview, as well as the other viewers.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn send_cursor_position(
+    x: f64,
+    y: f64,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+) -> Result<()> {
+    let user_id = {
+        let inner = app_state.inner.read().await;
+        inner
+            .user
+            .as_ref()
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?
+            .id
+            .clone()
+    };
+
+    let mut state = signaling_state.inner.write().await;
+
+    if state.is_host || state.controller_id.as_deref() == Some(user_id.as_str()) {
+        return Ok(());
+    }
+
+    if let Some(last) = state.last_cursor_sent {
+        if last.elapsed() < CURSOR_BROADCAST_INTERVAL {
+            return Ok(());
+        }
+    }
+
+    let tx = state
+        .signaling_tx
+        .as_ref()
+        .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+
+    tx.send(SignalingMessage::CursorPosition {
+        from_user_id: user_id,
+        x: x.clamp(0.0, 1.0),
+        y: y.clamp(0.0, 1.0),
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send cursor position: {}", e)))?;
+
+    state.last_cursor_sent = Some(Instant::now());
+
+    Ok(())
+}
+
+/// Record the protocol a peer announces, refusing peers too old to talk to
+///
+/// Returns `false` when the message must be dropped.
+async fn check_protocol(app_handle: &AppHandle, msg: &SignalingMessage) -> bool {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+
+    let SignalingMessage::UserJoined {
+        user_id,
+        protocol_version,
+        capabilities,
+        ..
+    } = msg
+    else {
+        return !msg.sender().is_some_and(|s| state.incompatible.contains(s));
+    };
+
+    let protocol = PeerProtocol::from_announcement(*protocol_version, capabilities);
+    if !protocol.is_supported() {
+        if state.incompatible.insert(user_id.clone()) {
+            tracing::warn!(
+                "{} runs signaling protocol {}, which is no longer supported",
+                user_id,
+                protocol.version
+            );
+            let peer = IncompatiblePeer {
+                user_id: user_id.clone(),
+                protocol_version: protocol.version,
+            };
+            if let Err(e) = app_handle.emit("session:peer-incompatible", &peer) {
+                tracing::error!("Failed to emit incompatible peer event: {}", e);
+            }
+        }
+        return false;
+    }
+
+    let missing = protocol.missing_capabilities();
+    if !missing.is_empty() {
+        tracing::info!("{} runs an older build without {:?}", user_id, missing);
+    }
+    state.incompatible.remove(user_id);
+    state.peer_protocols.insert(user_id.clone(), protocol);
+    true
+}
+
+/// Track joins and leaves, answering broadcast joins so newcomers see who is here
+///
+/// The host also tells newcomers who its co-hosts are.
+async fn update_roster(app_handle: &AppHandle, msg: &SignalingMessage, local_user_id: &str) {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+
+    match msg {
+        SignalingMessage::UserJoined {
+            user_id,
+            is_host,
+            to_user_id,
+            ..
+        } => {
+            state
+                .participants
+                .entry(user_id.clone())
+                .and_modify(|p| p.is_host = *is_host)
+                .or_insert_with(|| Participant {
+                    user_id: user_id.clone(),
+                    is_host: *is_host,
+                    is_cohost: false,
+                    has_control: false,
+                    protocol: None,
+                    is_muted: false,
+                    is_chat_muted: false,
+                    permissions: Vec::new(),
+                    joined_at: chrono::Utc::now().to_rfc3339(),
+                });
+
+            if to_user_id.is_none() {
+                if let Some(ref tx) = state.signaling_tx {
+                    let mut replies = vec![SignalingMessage::user_joined(
+                        local_user_id,
+                        state.is_host,
+                        Some(user_id),
+                    )];
+                    if state.muted.contains(local_user_id) {
+                        replies.push(SignalingMessage::AudioMuted {
+                            from_user_id: local_user_id.to_string(),
+                            user_id: local_user_id.to_string(),
+                            muted: true,
+                        });
+                    }
+                    if let Some(stream_id) = state.cameras.get(local_user_id) {
+                        replies.push(SignalingMessage::CameraChanged {
+                            from_user_id: local_user_id.to_string(),
+                            enabled: true,
+                            stream_id: Some(stream_id.clone()),
+                        });
+                    }
+                    if state.is_host {
+                        replies.extend(state.cohosts.iter().map(|cohost| {
+                            SignalingMessage::CohostChanged {
+                                from_user_id: local_user_id.to_string(),
+                                user_id: cohost.clone(),
+                                is_cohost: true,
+                            }
+                        }));
+                        replies.extend(state.chat_muted.iter().map(|muted_user_id| {
+                            SignalingMessage::ChatMuted {
+                                from_user_id: local_user_id.to_string(),
+                                user_id: muted_user_id.clone(),
+                                muted: true,
+                            }
+                        }));
+                    }
+                    for reply in replies {
+                        if let Err(e) = tx.try_send(reply) {
+                            tracing::warn!("Failed to announce presence to {}: {}", user_id, e);
+                        }
+                    }
+                }
+            }
+        }
+        SignalingMessage::UserLeft { user_id } => {
+            state.participants.remove(user_id);
+            state.remove_control_request(user_id);
+            if state.controller_id.as_deref() == Some(user_id.as_str()) {
+                state.release_control();
+            }
+            if state.cameras.remove(user_id).is_some() {
+                camera::emit_layout(app_handle, &state, local_user_id);
+            }
+        }
+        // Viewers learn who holds control from the host's grants and revokes
+        SignalingMessage::ControlGrant {
+            to_user_id,
+            duration_secs,
+        } => {
+            state.release_control();
+            // The controlling viewer shows the time left; the host revokes
+            if let Some(secs) = duration_secs.filter(|_| to_user_id == local_user_id) {
+                state.control_timer = Some(spawn_control_timer(
+                    app_handle.clone(),
+                    to_user_id.clone(),
+                    secs,
+                    false,
+                ));
+            }
+            state.controller_id = Some(to_user_id.clone());
+        }
+        SignalingMessage::ControlRevoke { to_user_id } => {
+            if state.controller_id.as_deref() == Some(to_user_id.as_str()) {
+                state.release_control();
+            }
+        }
+        _ => return,
+    }
+
+    emit_participants(app_handle, &state);
+}
+
+/// Merge a presence change into the roster
+///
+/// Presence catches participants that disconnect without sending `UserLeft`
+/// (crash, network loss). The local user is always kept. Presence metadata is
+/// fixed when a participant joins, so roles come from signaling once known.
+async fn apply_presence(app_handle: &AppHandle, update: PresenceUpdate, local_user_id: &str) {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+
+    let (joins, leaves) = match update {
+        PresenceUpdate::Sync(present) => {
+            let leaves: Vec<String> = state
+                .participants
+                .keys()
+                .filter(|id| !present.iter().any(|p| &p.user_id == *id))
+                .cloned()
+                .collect();
+            (present, leaves)
+        }
+        PresenceUpdate::Diff { joins, leaves } => {
+            let leaves = leaves
+                .into_iter()
+                .filter(|left| !joins.iter().any(|p| p.user_id == left.user_id))
+                .map(|left| left.user_id)
+                .collect();
+            (joins, leaves)
+        }
+    };
+
+    for user_id in leaves {
+        if user_id == local_user_id {
+            continue;
+        }
+        if state.join_requests.remove(&user_id).is_some() {
+            session::share_waiting_room(&state, local_user_id);
+            session::emit_join_cancelled(app_handle, &user_id);
+        }
+        state.participants.remove(&user_id);
+        state.remove_control_request(&user_id);
+        if state.controller_id.as_deref() == Some(user_id.as_str()) {
+            state.release_control();
+        }
+        if state.cameras.remove(&user_id).is_some() {
+            camera::emit_layout(app_handle, &state, local_user_id);
+        }
+    }
+
+    for meta in joins {
+        // Waiting viewers stay off the roster until the host admits them
+        if meta.user_id != local_user_id && !state.is_admitted(&meta.user_id) {
+            continue;
+        }
+        state
+            .participants
+            .entry(meta.user_id.clone())
+            .or_insert_with(|| Participant {
+                user_id: meta.user_id.clone(),
+                is_host: meta.is_host,
+                is_cohost: false,
+                has_control: false,
+                protocol: None,
+                is_muted: false,
+                is_chat_muted: false,
+                permissions: Vec::new(),
+                joined_at: meta
+                    .online_at
+                    .clone()
+                    .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            });
+    }
+
+    emit_participants(app_handle, &state);
+}
+
+/// Queue a viewer's control request on the host until answered or expired,
+/// and notify the host of it
+///
+/// Returns `false` for a viewer whose request is already queued or who holds
+/// control, so the host isn't asked twice.
+async fn queue_control_request(app_handle: &AppHandle, user_id: &str) -> bool {
+    let signaling_state = app_handle.state::<SignalingState>();
+    let mut state = signaling_state.inner.write().await;
+    if !state.is_host {
+        return true;
+    }
+    if state.controller_id.as_deref() == Some(user_id)
+        || state.control_requests.iter().any(|r| r.user_id == user_id)
+    {
+        return false;
+    }
+
+    let timeout = state
+        .control_request_timeout
+        .unwrap_or(DEFAULT_CONTROL_REQUEST_TIMEOUT);
+    let now = chrono::Utc::now();
+    let deadline = Instant::now() + timeout;
+    state.control_requests.push(PendingControlRequest {
+        user_id: user_id.to_string(),
+        requested_at: now.to_rfc3339(),
+        expires_at: (now + chrono::Duration::seconds(timeout.as_secs() as i64)).to_rfc3339(),
+        deadline,
+    });
+    spawn_control_request_expiry(app_handle.clone(), user_id.to_string(), deadline);
+    tauri::async_runtime::spawn(notifications::notify_control_request(
+        app_handle.clone(),
+        user_id.to_string(),
+    ));
+    true
+}
+
+/// Drop a control request left unanswered past its deadline
+fn spawn_control_request_expiry(app_handle: AppHandle, user_id: String, deadline: Instant) {
+    tokio::spawn(async move {
+        tokio::time::sleep_until(deadline.into()).await;
+
+        let request = {
+            let signaling_state = app_handle.state::<SignalingState>();
+            let mut state = signaling_state.inner.write().await;
+            // Answered, or withdrawn and asked again since
+            let Some(index) = state
+                .control_requests
+                .iter()
+                .position(|r| r.user_id == user_id && r.deadline == deadline)
+            else {
+                return;
+            };
+            state.control_requests.remove(index)
+        };
+
+        tracing::info!("Control request from {} expired", user_id);
+        if let Err(e) = app_handle.emit("signaling:control-request-expired", &request) {
+            tracing::error!("Failed to emit control request expiry event: {}", e);
+        }
+    });
+}
+
+/// Emit the time left on a control grant every second
+///
+/// On the host (`revoke_on_expiry`), control is revoked when it runs out.
+fn spawn_control_timer(
+    app_handle: AppHandle,
+    user_id: String,
+    duration_secs: u64,
+    revoke_on_expiry: bool,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let expires_at =
+            (chrono::Utc::now() + chrono::Duration::seconds(duration_secs as i64)).to_rfc3339();
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+
+        for remaining_secs in (0..=duration_secs).rev() {
+            interval.tick().await;
+            let countdown = ControlCountdown {
+                user_id: user_id.clone(),
+                remaining_secs,
+                expires_at: expires_at.clone(),
+            };
+            if let Err(e) = app_handle.emit("control:countdown", &countdown) {
+                tracing::error!("Failed to emit control countdown event: {}", e);
+            }
+        }
+
+        // Detach before revoking so the revoke doesn't abort this task
+        {
+            let signaling_state = app_handle.state::<SignalingState>();
+            let mut state = signaling_state.inner.write().await;
+            state.control_timer = None;
+            if state.controller_id.as_deref() != Some(user_id.as_str()) {
+                return;
+            }
+        }
+
+        if revoke_on_expiry {
+            tracing::info!("Control grant for {} expired", user_id);
+            let app_state = app_handle.state::<AppState>();
+            auto_revoke(
+                &app_state.inner,
+                &app_handle,
+                AutoRevokeReason::Expired { duration_secs },
+            )
+            .await;
+        }
+    })
+}
+
+/// Notify the frontend of the current roster
+pub fn emit_participants(app_handle: &AppHandle, state: &SignalingStateInner) {
+    let roster = state.roster();
+    tracing::debug!("Session roster: {} participants", roster.len());
+    if let Err(e) = app_handle.emit("session:participants-changed", &roster) {
+        tracing::error!("Failed to emit participants event: {}", e);
+    }
+}
+
+/// Pick a stable cursor color for a user
+pub(crate) fn cursor_color(user_id: &str) -> &'static str {
+    let hash = user_id
+        .bytes()
+        .fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+    CURSOR_COLORS[(hash as usize) % CURSOR_COLORS.len()]
+}
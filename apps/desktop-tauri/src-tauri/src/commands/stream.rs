@@ -0,0 +1,800 @@
+//! Native stream commands
+//!
+//! High-level control of the backend-owned peer connections, one per viewer,
+//! all fed by a single capture/encode pipeline. While a native stream is
+//! running, answers and ICE candidates arriving over signaling are consumed
+//! by the matching peer instead of being forwarded to the frontend, and
+//! viewers joining mid-stream get their own offer.
+//!
+//! While streaming, every viewer's connection is sampled periodically and
+//! the stats emitted as `session:stats`; viewers report their own.
+//!
+//! Also owns the ICE server list (STUN plus optional TURN relays), which the
+//! frontend reuses for its own peer connections, and battery saver, which
+//! caps the capture frame rate while the machine runs low on battery.
+//! Low-bandwidth mode caps the frame rate and resolution further.
+//!
+//! The host's microphone and, when turned on, its camera go out on the same
+//! connections (see `camera`), and every viewer's voice is mixed into the
+//! host's speakers (see `voice`). Viewers' drawings come in on their own
+//! data channel (see `annotations`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use webrtc::track::track_remote::TrackRemote;
+
+use crate::audio::{AudioPipeline, AudioPlayback, VoiceDecoder};
+use crate::commands::annotations;
+use crate::commands::camera;
+use crate::commands::file_transfer;
+use crate::commands::signaling::SignalingState;
+use crate::commands::voice;
+use crate::connection_quality::{ConnectionStats, QualityTracker, StatsSample};
+use crate::ice::{self, IceServerConfig};
+use crate::low_bandwidth;
+use crate::peer::{CameraPipeline, NativePeer, VideoPipeline};
+use crate::realtime::SignalingMessage;
+use crate::resources::{self, BatterySaverMode, BatterySaverSettings, PowerStatus};
+use crate::state::AppState;
+use crate::supabase::SupabaseClient;
+use crate::{Error, Result};
+
+const DEFAULT_STREAM_FPS: u32 = 15;
+
+/// Interval between connection stats samples while streaming
+const STATS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// TURN credentials are refreshed when they expire within this margin
+const TURN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Interval between power source checks
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct StreamState {
+    pub inner: Arc<RwLock<StreamStateInner>>,
+}
+
+pub struct StreamStateInner {
+    pub pipeline: Option<VideoPipeline>,
+    /// Peer connections keyed by viewer user ID
+    pub peers: HashMap<String, NativePeer>,
+    pub ice_servers: Vec<IceServerConfig>,
+    pub turn_server: Option<IceServerConfig>,
+    pub turn_expires_at: Option<Instant>,
+    /// Stats of each connection, keyed by the peer at the other end
+    pub quality: QualityTracker,
+    /// Periodic sampling of the viewers' connections
+    pub stats_task: Option<JoinHandle<()>>,
+    pub battery_saver: BatterySaverSettings,
+    /// Power source as of the last check
+    pub power: PowerStatus,
+    /// Whether the frame rate is currently capped to save battery
+    pub battery_saver_active: bool,
+    /// Capture source and frame rate being streamed
+    pub source: Option<(String, u32)>,
+    /// Host camera track, sending while the camera is on
+    pub camera: Option<CameraPipeline>,
+    /// Host microphone sent to every viewer
+    pub audio: Option<AudioPipeline>,
+    /// Viewers' voices mixed to the host's output device
+    pub playback: Option<AudioPlayback>,
+}
+
+impl Default for StreamStateInner {
+    fn default() -> Self {
+        Self {
+            pipeline: None,
+            peers: HashMap::new(),
+            ice_servers: ice::ice_servers_from_env(),
+            turn_server: None,
+            turn_expires_at: None,
+            quality: QualityTracker::default(),
+            stats_task: None,
+            battery_saver: BatterySaverSettings::default(),
+            power: PowerStatus::default(),
+            battery_saver_active: false,
+            source: None,
+            camera: None,
+            audio: None,
+            playback: None,
+        }
+    }
+}
+
+impl StreamStateInner {
+    /// Fetched TURN server, if its credentials are still valid
+    fn valid_turn_server(&self) -> Option<&IceServerConfig> {
+        match self.turn_expires_at {
+            Some(expires_at) if Instant::now() + TURN_REFRESH_MARGIN < expires_at => {
+                self.turn_server.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Configured servers merged with the fetched TURN server
+    pub(crate) fn effective_ice_servers(&self) -> Vec<IceServerConfig> {
+        ice::merge_ice_servers(&self.ice_servers, self.valid_turn_server())
+    }
+
+    /// Battery saver settings, state and the power status behind it
+    fn battery_saver_status(&self) -> BatterySaverStatus {
+        BatterySaverStatus {
+            settings: self.battery_saver,
+            active: self.battery_saver_active,
+            power: self.power,
+        }
+    }
+
+    /// Frame rate cap of the running pipeline, the lowest of battery saver
+    /// and low-bandwidth mode while they are on
+    fn max_fps(&self) -> Option<u32> {
+        let saver_max_fps = self
+            .battery_saver_active
+            .then_some(self.battery_saver.max_fps);
+        let low_bandwidth_max_fps = low_bandwidth::is_enabled().then_some(low_bandwidth::MAX_FPS);
+        saver_max_fps.into_iter().chain(low_bandwidth_max_fps).min()
+    }
+
+    /// Cap the frame rate and size of the running pipeline for battery saver
+    /// and low-bandwidth mode
+    pub(crate) fn apply_capture_caps(&self) {
+        if let Some(pipeline) = &self.pipeline {
+            pipeline.set_max_fps(self.max_fps());
+            pipeline.set_max_width(
+                low_bandwidth::is_enabled().then_some(low_bandwidth::MAX_FRAME_WIDTH),
+            );
+        }
+    }
+
+    /// Close every viewer connection and stop the pipeline
+    pub(crate) async fn close_all(&mut self) {
+        for (viewer_id, peer) in self.peers.drain() {
+            if let Err(e) = peer.close().await {
+                tracing::warn!("Failed to close peer for {}: {}", viewer_id, e);
+            }
+        }
+        if let Some(pipeline) = self.pipeline.take() {
+            pipeline.stop();
+        }
+        if let Some(camera) = self.camera.take() {
+            camera.stop();
+        }
+        if let Some(audio) = self.audio.take() {
+            audio.stop();
+        }
+        if let Some(playback) = self.playback.take() {
+            playback.stop();
+        }
+        if let Some(task) = self.stats_task.take() {
+            task.abort();
+        }
+        self.quality.clear();
+        self.source = None;
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatterySaverStatus {
+    #[serde(flatten)]
+    pub settings: BatterySaverSettings,
+    pub active: bool,
+    #[serde(flatten)]
+    pub power: PowerStatus,
+}
+
+/// Connection state change of one viewer's peer
+#[derive(Debug, Clone, serde::Serialize)]
+struct ViewerConnectionState {
+    viewer_id: String,
+    state: String,
+}
+
+impl Default for StreamState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(StreamStateInner::default())),
+        }
+    }
+}
+
+/// Fetch TURN credentials unless the cached ones are still valid
+pub(crate) async fn refresh_turn_credentials(
+    state: &mut StreamStateInner,
+    supabase: &SupabaseClient,
+) -> Result<IceServerConfig> {
+    if let Some(server) = state.valid_turn_server() {
+        return Ok(server.clone());
+    }
+
+    let credentials = supabase.fetch_turn_credentials().await?;
+    state.turn_expires_at = Some(Instant::now() + Duration::from_secs(credentials.ttl_seconds));
+
+    let server = IceServerConfig::from(credentials);
+    ice::validate_ice_servers(std::slice::from_ref(&server))?;
+    state.turn_server = Some(server.clone());
+
+    tracing::info!("TURN credentials refreshed for {:?}", server.urls);
+    Ok(server)
+}
+
+/// Open a peer connection to a viewer and send it an addressed offer
+async fn connect_viewer(
+    app_handle: &AppHandle,
+    state: &mut StreamStateInner,
+    tx: &mpsc::Sender<SignalingMessage>,
+    local_user_id: &str,
+    viewer_id: &str,
+) -> Result<()> {
+    if let Some(previous) = state.peers.remove(viewer_id) {
+        previous.close().await?;
+    }
+
+    let (Some(pipeline), Some(camera), Some(audio)) = (
+        state.pipeline.as_ref(),
+        state.camera.as_ref(),
+        state.audio.as_ref(),
+    ) else {
+        return Err(Error::Session("Native stream is not running".to_string()));
+    };
+    let peer = NativePeer::new(&state.effective_ice_servers(), pipeline, camera, audio).await?;
+
+    if let Some(playback) = state.playback.clone() {
+        let audio_handle = app_handle.clone();
+        let audio_viewer_id = viewer_id.to_string();
+        peer.on_remote_audio(move |track| {
+            spawn_voice_playback(
+                audio_handle.clone(),
+                audio_viewer_id.clone(),
+                playback.clone(),
+                track,
+            );
+        });
+    }
+
+    // Trickle local candidates to this viewer only
+    let candidate_tx = tx.clone();
+    let candidate_user_id = local_user_id.to_string();
+    let candidate_viewer_id = viewer_id.to_string();
+    peer.on_local_candidate(move |c| {
+        let msg = SignalingMessage::IceCandidate {
+            candidate: c.candidate,
+            sdp_mid: c.sdp_mid,
+            sdp_m_line_index: c.sdp_m_line_index,
+            from_user_id: candidate_user_id.clone(),
+            to_user_id: Some(candidate_viewer_id.clone()),
+        };
+        if let Err(e) = candidate_tx.try_send(msg) {
+            tracing::warn!("Failed to send local ICE candidate: {}", e);
+        }
+    });
+
+    let state_handle = app_handle.clone();
+    let state_viewer_id = viewer_id.to_string();
+    peer.on_state_change(move |connection_state| {
+        tracing::info!(
+            "Native peer connection state for {}: {}",
+            state_viewer_id,
+            connection_state
+        );
+        let payload = ViewerConnectionState {
+            viewer_id: state_viewer_id.clone(),
+            state: connection_state,
+        };
+        if let Err(e) = state_handle.emit("stream:connection-state", &payload) {
+            tracing::error!("Failed to emit connection state event: {}", e);
+        }
+    });
+
+    file_transfer::attach_peer(app_handle, viewer_id, &peer);
+    annotations::attach_peer(app_handle, viewer_id, &peer);
+
+    let sdp = peer.create_offer().await?;
+    tx.send(SignalingMessage::Offer {
+        sdp,
+        from_user_id: local_user_id.to_string(),
+        to_user_id: Some(viewer_id.to_string()),
+    })
+    .await
+    .map_err(|e| Error::Network(format!("Failed to send offer: {}", e)))?;
+
+    state.peers.insert(viewer_id.to_string(), peer);
+    Ok(())
+}
+
+/// Play a viewer's microphone track and report when they speak
+fn spawn_voice_playback(
+    app_handle: AppHandle,
+    viewer_id: String,
+    playback: AudioPlayback,
+    track: Arc<TrackRemote>,
+) {
+    tokio::spawn(async move {
+        let mut decoder = match VoiceDecoder::new() {
+            Ok(decoder) => decoder,
+            Err(e) => {
+                tracing::error!("Failed to create voice decoder for {}: {}", viewer_id, e);
+                return;
+            }
+        };
+
+        while let Ok((packet, _)) = track.read_rtp().await {
+            if packet.payload.is_empty() {
+                continue;
+            }
+            match decoder.decode(&packet.payload) {
+                Ok((samples, speaking)) => {
+                    playback.push(&viewer_id, samples);
+                    if let Some(speaking) = speaking {
+                        voice::emit_speaking(&app_handle, &viewer_id, speaking);
+                    }
+                }
+                Err(e) => tracing::debug!("Dropped voice packet from {}: {}", viewer_id, e),
+            }
+        }
+
+        if let Some(speaking) = decoder.reset() {
+            voice::emit_speaking(&app_handle, &viewer_id, speaking);
+        }
+        playback.forget(&viewer_id);
+        tracing::debug!("Voice track of {} ended", viewer_id);
+    });
+}
+
+/// Route an incoming signaling message to the native peers.
+///
+/// Returns `true` when the message was consumed. Joins and leaves are acted
+/// upon but still forwarded to the frontend.
+pub(crate) async fn handle_signaling(app_handle: &AppHandle, msg: &SignalingMessage) -> bool {
+    let stream_state = app_handle.state::<StreamState>();
+    let mut state = stream_state.inner.write().await;
+    if state.pipeline.is_none() {
+        return false;
+    }
+
+    let result = match msg {
+        SignalingMessage::Answer {
+            sdp, from_user_id, ..
+        } => match state.peers.get(from_user_id) {
+            Some(peer) => peer.set_remote_answer(sdp.clone()).await,
+            None => return false,
+        },
+        SignalingMessage::IceCandidate {
+            candidate,
+            sdp_mid,
+            sdp_m_line_index,
+            from_user_id,
+            ..
+        } => match state.peers.get(from_user_id) {
+            Some(peer) => {
+                peer.add_remote_candidate(candidate.clone(), sdp_mid.clone(), *sdp_m_line_index)
+                    .await
+            }
+            None => return false,
+        },
+        SignalingMessage::UserJoined {
+            user_id, is_host, ..
+        } if !is_host && !state.peers.contains_key(user_id) => {
+            let local_user_id = {
+                let app_state = app_handle.state::<AppState>();
+                let inner = app_state.inner.read().await;
+                inner.user.as_ref().map(|u| u.id.clone())
+            };
+            let tx = {
+                let signaling_state = app_handle.state::<SignalingState>();
+                let signaling = signaling_state.inner.read().await;
+                signaling.signaling_tx.clone()
+            };
+            if let (Some(local_user_id), Some(tx)) = (local_user_id, tx) {
+                tracing::info!("Viewer {} joined mid-stream, sending offer", user_id);
+                if let Err(e) =
+                    connect_viewer(app_handle, &mut state, &tx, &local_user_id, user_id).await
+                {
+                    tracing::error!("Failed to connect viewer {}: {}", user_id, e);
+                }
+            }
+            return false;
+        }
+        SignalingMessage::UserLeft { user_id } => {
+            state.quality.forget(user_id);
+            if let Some(playback) = state.playback.as_ref() {
+                playback.forget(user_id);
+            }
+            if let Some(peer) = state.peers.remove(user_id) {
+                if let Err(e) = peer.close().await {
+                    tracing::warn!("Failed to close peer for {}: {}", user_id, e);
+                }
+            }
+            return false;
+        }
+        _ => return false,
+    };
+
+    if let Err(e) = result {
+        tracing::error!("Native peer failed to handle signaling message: {}", e);
+    }
+    true
+}
+
+/// Start streaming a capture source to every viewer over backend-owned peer connections (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn start_stream(
+    source_id: String,
+    fps: Option<u32>,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+
+    let session = inner
+        .session
+        .as_ref()
+        .ok_or_else(|| Error::Session("No active session".to_string()))?;
+    if !session.is_host {
+        return Err(Error::Session("Only the host can start a stream".to_string()));
+    }
+    drop(inner);
+
+    let (tx, viewer_ids, mut muted) = {
+        let signaling = signaling_state.inner.read().await;
+        let tx = signaling
+            .signaling_tx
+            .clone()
+            .ok_or_else(|| Error::Session("Not connected to signaling".to_string()))?;
+        (tx, signaling.viewer_ids(&user_id), signaling.muted.clone())
+    };
+
+    let mut state = stream_state.inner.write().await;
+    state.close_all().await;
+
+    // Without a relay, sessions behind symmetric NATs cannot connect, but
+    // a failing edge function should not prevent direct connections
+    if let Some(supabase) = app_state.supabase.as_ref() {
+        if let Err(e) = refresh_turn_credentials(&mut state, supabase).await {
+            tracing::warn!("Continuing without fetched TURN credentials: {}", e);
+        }
+    }
+
+    let fps = fps.unwrap_or(DEFAULT_STREAM_FPS);
+    let pipeline = VideoPipeline::new();
+    pipeline.start(source_id.clone(), fps, app_state.app_lock.clone());
+    state.pipeline = Some(pipeline);
+    state.apply_capture_caps();
+    state.source = Some((source_id.clone(), fps));
+    state.camera = Some(CameraPipeline::new());
+
+    let audio = AudioPipeline::new();
+    audio.set_muted(muted.remove(&user_id));
+    let speaking_handle = app_handle.clone();
+    let speaking_user_id = user_id.clone();
+    audio.start(move |speaking| {
+        voice::emit_speaking(&speaking_handle, &speaking_user_id, speaking);
+    });
+    state.audio = Some(audio);
+
+    let playback = AudioPlayback::new(muted);
+    playback.start();
+    state.playback = Some(playback);
+
+    state.stats_task = Some(spawn_stats_task(app_handle.clone()));
+
+    for viewer_id in &viewer_ids {
+        if let Err(e) = connect_viewer(&app_handle, &mut state, &tx, &user_id, viewer_id).await {
+            tracing::error!("Failed to connect viewer {}: {}", viewer_id, e);
+        }
+    }
+
+    let mut inner = app_state.inner.write().await;
+    inner.is_capturing = true;
+    drop(inner);
+
+    tracing::info!(
+        "Native stream started for source {} with {} viewers",
+        source_id,
+        state.peers.len()
+    );
+    drop(state);
+    crate::commands::session::persist_session(&app_handle).await;
+    Ok(())
+}
+
+/// Stop the native stream and close every viewer connection
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn stop_stream(
+    app_state: State<'_, AppState>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let mut state = stream_state.inner.write().await;
+    state.close_all().await;
+    drop(state);
+
+    let mut inner = app_state.inner.write().await;
+    inner.is_capturing = false;
+    let user_id = inner.user.as_ref().map(|u| u.id.clone());
+    drop(inner);
+    crate::commands::session::persist_session(&app_handle).await;
+
+    // The camera went out on the native connections, which are gone
+    if let Some(user_id) = user_id {
+        camera::native_stream_stopped(&app_handle, &user_id).await;
+    }
+    annotations::native_stream_stopped(&app_handle).await;
+
+    tracing::info!("Native stream stopped");
+    Ok(())
+}
+
+/// Stream another display or window without renegotiating: viewers keep
+/// their connections and get a `capture:source-switched` event (host only)
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn switch_capture_source(
+    session_id: String,
+    source_id: String,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let inner = app_state.inner.read().await;
+    let user_id = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?
+        .id
+        .clone();
+    let session = inner
+        .session
+        .as_ref()
+        .filter(|s| s.id == session_id)
+        .ok_or_else(|| Error::Session("Not in this session".to_string()))?;
+    if !session.is_host {
+        return Err(Error::Session(
+            "Only the host can switch the capture source".to_string(),
+        ));
+    }
+    drop(inner);
+
+    let source = crate::capture::get_available_sources()?
+        .into_iter()
+        .find(|s| s.id == source_id)
+        .ok_or_else(|| Error::NotFound(format!("Capture source {} not found", source_id)))?;
+
+    let mut state = stream_state.inner.write().await;
+    let pipeline = state
+        .pipeline
+        .as_ref()
+        .ok_or_else(|| Error::Session("Not streaming".to_string()))?;
+    pipeline.switch_source(source.id.clone());
+    let fps = state
+        .source
+        .as_ref()
+        .map_or(DEFAULT_STREAM_FPS, |(_, fps)| *fps);
+    state.source = Some((source.id.clone(), fps));
+    drop(state);
+
+    let msg = SignalingMessage::CaptureSourceSwitched {
+        from_user_id: user_id,
+        source_id: source.id,
+        source_name: source.name,
+    };
+    let tx = signaling_state.inner.read().await.signaling_tx.clone();
+    if let Some(tx) = tx {
+        if let Err(e) = tx.send(msg.clone()).await {
+            tracing::warn!("Failed to announce capture source switch: {}", e);
+        }
+    }
+    if let Err(e) = app_handle.emit("capture:source-switched", &msg) {
+        tracing::error!("Failed to emit capture source switched event: {}", e);
+    }
+    crate::commands::session::persist_session(&app_handle).await;
+
+    tracing::info!("Capture source switched to {}", source_id);
+    Ok(())
+}
+
+/// Get the latest stats of each connection: every viewer's while streaming,
+/// the host's as last reported on a viewer
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_connection_stats(
+    stream_state: State<'_, StreamState>,
+) -> Result<Vec<ConnectionStats>> {
+    let state = stream_state.inner.read().await;
+    Ok(state.quality.latest())
+}
+
+/// Report the counters of the webview's connection to the host (viewer only)
+///
+/// The frontend calls this periodically with values from `getStats()`;
+/// the resulting stats are emitted as `session:stats`.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn report_connection_stats(
+    host_id: String,
+    sample: StatsSample,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<ConnectionStats> {
+    let mut state = stream_state.inner.write().await;
+    let stats = state.quality.update(&host_id, sample, Instant::now());
+    emit_stats(&app_handle, &state.quality.latest());
+    Ok(stats)
+}
+
+/// Sample every viewer's connection and emit the stats
+fn spawn_stats_task(app_handle: AppHandle) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(STATS_INTERVAL);
+        loop {
+            interval.tick().await;
+            let stream_state = app_handle.state::<StreamState>();
+
+            let mut samples = Vec::new();
+            {
+                let state = stream_state.inner.read().await;
+                for (viewer_id, peer) in &state.peers {
+                    samples.push((viewer_id.clone(), peer.sample().await));
+                }
+            }
+
+            let mut state = stream_state.inner.write().await;
+            let now = Instant::now();
+            for (viewer_id, sample) in samples {
+                // Skip viewers that left while sampling
+                if state.peers.contains_key(&viewer_id) {
+                    state.quality.update(&viewer_id, sample, now);
+                }
+            }
+            emit_stats(&app_handle, &state.quality.latest());
+        }
+    })
+}
+
+fn emit_stats(app_handle: &AppHandle, stats: &[ConnectionStats]) {
+    if let Err(e) = app_handle.emit("session:stats", stats) {
+        tracing::error!("Failed to emit connection stats event: {}", e);
+    }
+}
+
+// ==========================================
+// Battery Saver
+// ==========================================
+
+/// Check the power source periodically and apply battery saver as it changes
+pub async fn watch_power(app_handle: AppHandle) {
+    let mut interval = tokio::time::interval(POWER_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        match tokio::task::spawn_blocking(resources::power_status).await {
+            Ok(power) => {
+                let stream_state = app_handle.state::<StreamState>();
+                let mut state = stream_state.inner.write().await;
+                state.power = power;
+                update_battery_saver(&app_handle, &mut state);
+            }
+            Err(e) => tracing::warn!("Failed to check power source: {}", e),
+        }
+    }
+}
+
+/// Turn battery saver on or off for the current settings and power source
+///
+/// Emits `stream:battery-saver` when it turns on or off.
+fn update_battery_saver(app_handle: &AppHandle, state: &mut StreamStateInner) {
+    let active = state.battery_saver.is_active(&state.power);
+    let changed = active != state.battery_saver_active;
+    state.battery_saver_active = active;
+    state.apply_capture_caps();
+
+    if changed {
+        tracing::info!("Battery saver {}", if active { "on" } else { "off" });
+        if let Err(e) = app_handle.emit("stream:battery-saver", state.battery_saver_status()) {
+            tracing::error!("Failed to emit battery saver event: {}", e);
+        }
+    }
+}
+
+/// Get battery saver settings and whether it is on
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_battery_saver(
+    stream_state: State<'_, StreamState>,
+) -> Result<BatterySaverStatus> {
+    let state = stream_state.inner.read().await;
+    Ok(state.battery_saver_status())
+}
+
+/// Force battery saver on or off, or leave it to the battery level with `auto`
+///
+/// The frame rate cap is kept within 10-15 fps.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_battery_saver(
+    mode: BatterySaverMode,
+    threshold_percent: Option<u8>,
+    max_fps: Option<u32>,
+    stream_state: State<'_, StreamState>,
+    app_handle: AppHandle,
+) -> Result<BatterySaverStatus> {
+    let mut state = stream_state.inner.write().await;
+    let current = state.battery_saver;
+    state.battery_saver = BatterySaverSettings {
+        mode,
+        threshold_percent: threshold_percent.unwrap_or(current.threshold_percent),
+        max_fps: max_fps.unwrap_or(current.max_fps),
+    }
+    .clamped();
+    update_battery_saver(&app_handle, &mut state);
+
+    tracing::info!("Battery saver settings updated: {:?}", state.battery_saver);
+    Ok(state.battery_saver_status())
+}
+
+/// Get the ICE servers to use for peer connections
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_ice_servers(
+    stream_state: State<'_, StreamState>,
+) -> Result<Vec<IceServerConfig>> {
+    let state = stream_state.inner.read().await;
+    Ok(state.effective_ice_servers())
+}
+
+/// Override the configured ICE servers; an empty list restores the environment defaults
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_ice_servers(
+    servers: Vec<IceServerConfig>,
+    stream_state: State<'_, StreamState>,
+) -> Result<()> {
+    ice::validate_ice_servers(&servers)?;
+
+    let mut state = stream_state.inner.write().await;
+    state.ice_servers = if servers.is_empty() {
+        ice::ice_servers_from_env()
+    } else {
+        servers
+    };
+
+    tracing::info!("ICE servers updated ({} entries)", state.ice_servers.len());
+    Ok(())
+}
+
+/// Fetch short-lived TURN credentials from the edge function
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn fetch_turn_credentials(
+    app_state: State<'_, AppState>,
+    stream_state: State<'_, StreamState>,
+) -> Result<IceServerConfig> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let mut state = stream_state.inner.write().await;
+    refresh_turn_credentials(&mut state, supabase).await
+}
@@ -0,0 +1,192 @@
+//! Change feed for sync clients
+//!
+//! Instead of refetching meetings and conversations, a client keeps the
+//! cursor of its last sync and asks for what changed since. Changes are also
+//! applied to the in-memory cache so it stays warm.
+
+use std::collections::HashMap;
+
+use chrono::Datelike;
+use serde::Serialize;
+use tauri::State;
+
+use crate::change_feed::{self, ChangeCursor};
+use crate::commands::calendar::{meeting_rows_to_meetings, Meeting};
+use crate::state::AppState;
+use crate::supabase::{ConversationRow, DeletedEntityRow, MessageRow};
+use crate::utils::datetime;
+use crate::{Error, Result};
+
+/// Rows fetched per table unless the client asks for fewer or more
+const DEFAULT_CHANGE_LIMIT: u32 = 200;
+
+/// Most rows fetched per table in one call
+const MAX_CHANGE_LIMIT: u32 = 1000;
+
+// ==========================================
+// Response Types
+// ==========================================
+
+/// Meeting, conversation or message the user can no longer see
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletedEntity {
+    /// "meeting", "conversation" or "message"
+    pub entity_type: String,
+    pub entity_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeSet {
+    pub meetings: Vec<Meeting>,
+    pub conversations: Vec<ConversationRow>,
+    pub messages: Vec<MessageRow>,
+    pub deleted: Vec<DeletedEntity>,
+    /// Pass back as `since_cursor` to get the changes after these
+    pub cursor: String,
+    /// Whether there are more changes already; call again right away
+    pub has_more: bool,
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Get meetings, conversations and messages changed since a cursor, and
+/// those deleted or no longer shared with the user
+///
+/// Without a cursor, everything is returned, `limit` rows per kind at a
+/// time. Changes come oldest first; keep calling while `has_more`.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_changes(
+    since_cursor: Option<String>,
+    limit: Option<u32>,
+    app_state: State<'_, AppState>,
+) -> Result<ChangeSet> {
+    let inner = app_state.inner.read().await;
+    let _user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let mut cursor = ChangeCursor::decode(since_cursor.as_deref())?;
+    let limit = limit
+        .unwrap_or(DEFAULT_CHANGE_LIMIT)
+        .clamp(1, MAX_CHANGE_LIMIT);
+
+    let (meeting_rows, conversations, messages, deleted_rows) = tokio::try_join!(
+        supabase.get_changed_meetings(cursor.meetings.as_ref(), limit),
+        supabase.get_changed_conversations(cursor.conversations.as_ref(), limit),
+        supabase.get_changed_messages(cursor.messages.as_ref(), limit),
+        supabase.get_deleted_entities(cursor.deleted.as_ref(), limit),
+    )?;
+
+    let has_more = [
+        meeting_rows.len(),
+        conversations.len(),
+        messages.len(),
+        deleted_rows.len(),
+    ]
+    .contains(&(limit as usize));
+
+    change_feed::advance(
+        &mut cursor.meetings,
+        meeting_rows.iter().map(|r| (&r.updated_at, r.id.as_str())),
+    );
+    change_feed::advance(
+        &mut cursor.conversations,
+        conversations.iter().map(|r| (&r.updated_at, r.id.as_str())),
+    );
+    change_feed::advance(
+        &mut cursor.messages,
+        messages.iter().map(|r| (&r.updated_at, r.id.as_str())),
+    );
+    change_feed::advance(
+        &mut cursor.deleted,
+        deleted_rows.iter().map(|r| (&r.updated_at, r.id.as_str())),
+    );
+
+    let meetings = meeting_rows_to_meetings(meeting_rows, &app_state).await?;
+    let deleted: Vec<DeletedEntity> = deleted_rows
+        .into_iter()
+        .map(|row: DeletedEntityRow| DeletedEntity {
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+        })
+        .collect();
+
+    apply_to_cache(&app_state, &meetings, &messages, &deleted).await;
+
+    tracing::debug!(
+        "Changes: {} meetings, {} conversations, {} messages, {} deleted",
+        meetings.len(),
+        conversations.len(),
+        messages.len(),
+        deleted.len()
+    );
+
+    Ok(ChangeSet {
+        meetings,
+        conversations,
+        messages,
+        deleted,
+        cursor: cursor.encode()?,
+        has_more,
+    })
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Bring cached meetings and messages up to date with a page of changes
+async fn apply_to_cache(
+    app_state: &AppState,
+    meetings: &[Meeting],
+    messages: &[MessageRow],
+    deleted: &[DeletedEntity],
+) {
+    if !meetings.is_empty() || deleted.iter().any(|d| d.entity_type == "meeting") {
+        let mut cache = app_state.cache.meetings.write().await;
+        for meeting in meetings {
+            // Months that held the meeting go with it; so does the one it is in now
+            cache.invalidate_meeting(&meeting.id);
+            if let Ok(at) = datetime::parse_datetime(&meeting.scheduled_at) {
+                cache.invalidate_month(at.year(), at.month());
+            }
+            cache.set_by_id(meeting.clone());
+        }
+        for entity in deleted.iter().filter(|d| d.entity_type == "meeting") {
+            cache.invalidate_meeting(&entity.entity_id);
+        }
+    }
+
+    if !messages.is_empty() || deleted.iter().any(|d| d.entity_type != "meeting") {
+        let mut by_conversation: HashMap<&str, Vec<MessageRow>> = HashMap::new();
+        for message in messages {
+            by_conversation
+                .entry(message.conversation_id.as_str())
+                .or_default()
+                .push(message.clone());
+        }
+
+        let mut cache = app_state.cache.messages.write().await;
+        for (conversation_id, messages) in by_conversation {
+            cache.upsert_messages(conversation_id, messages);
+        }
+        for entity in deleted {
+            match entity.entity_type.as_str() {
+                "conversation" => cache.invalidate_conversation(&entity.entity_id),
+                "message" => cache.remove_message(&entity.entity_id),
+                _ => {}
+            }
+        }
+    }
+}
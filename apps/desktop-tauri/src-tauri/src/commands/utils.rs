@@ -7,8 +7,9 @@ use crate::utils::{
     calendar_grid::{
         self, CalendarDay, CalendarGrid, WeekdayHeader,
     },
-    datetime::{self, DateTimeFormat, FormattedDateTime},
-    rrule::{self, ParsedRRule, RecurrenceOccurrence, RecurrenceRule},
+    datetime::{self, DateTimeFormat, FormattedDateTime, ZonedDateTime},
+    rrule::{self, ParsedRRule, RecurrenceExceptions, RecurrenceOccurrence, RecurrenceRule},
+    text,
 };
 use crate::Result;
 
@@ -18,100 +19,173 @@ use crate::Result;
 
 /// Format a datetime string according to the specified format
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn format_datetime(datetime: String, format: DateTimeFormat) -> Result<FormattedDateTime> {
     datetime::format_datetime(&datetime, format)
 }
 
 /// Format a time range (start - end)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn format_time_range(start: String, end: String) -> Result<String> {
     datetime::format_time_range(&start, &end)
 }
 
 /// Format a meeting time with duration
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn format_meeting_time(start: String, duration_minutes: i32) -> Result<String> {
     datetime::format_meeting_time(&start, duration_minutes)
 }
 
 /// Calculate end time from start and duration
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn calculate_end_time(start: String, duration_minutes: i32) -> Result<String> {
     datetime::calculate_end_time(&start, duration_minutes)
 }
 
 /// Check if a datetime is in the past
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn is_past(datetime: String) -> Result<bool> {
     datetime::is_past(&datetime)
 }
 
 /// Check if a datetime is today
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn is_today(datetime: String) -> Result<bool> {
     datetime::is_today(&datetime)
 }
 
 /// Get the start and end of a day in ISO format
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn get_day_bounds(date: String) -> Result<(String, String)> {
     datetime::get_day_bounds(&date)
 }
 
 /// Get the start and end of a month in ISO format
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn get_month_bounds(year: i32, month: u32) -> Result<(String, String)> {
     datetime::get_month_bounds(year, month)
 }
 
+/// Show a datetime as it is in an IANA time zone
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn convert_time_zone(datetime: String, time_zone: String) -> Result<ZonedDateTime> {
+    datetime::to_time_zone(&datetime, &time_zone)
+}
+
+/// Convert a wall-clock time in an IANA time zone to UTC
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn zoned_to_utc(local: String, time_zone: String) -> Result<String> {
+    datetime::zoned_to_utc(&local, &time_zone)
+}
+
+/// Format a meeting time with duration in an IANA time zone
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn format_meeting_time_in_zone(
+    start: String,
+    duration_minutes: i32,
+    time_zone: String,
+) -> Result<String> {
+    datetime::format_meeting_time_in_zone(&start, duration_minutes, &time_zone)
+}
+
 // ==========================================
 // RRULE Commands
 // ==========================================
 
 /// Build an RRULE string from a RecurrenceRule
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn build_rrule(rule: RecurrenceRule) -> String {
     rrule::build_rrule(&rule)
 }
 
 /// Parse an RRULE string into a RecurrenceRule
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn parse_rrule(rrule_str: String) -> Result<ParsedRRule> {
     rrule::parse_rrule(&rrule_str)
 }
 
 /// Validate an RRULE string
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_rrule(rrule_str: String) -> Result<bool> {
     rrule::validate_rrule(&rrule_str)
 }
 
-/// Expand a recurring event to get all occurrences within a date range
+/// Expand a recurring event to get all occurrences within a date range,
+/// skipping its EXDATE dates and adding its RDATE dates, in the event's
+/// time zone when given
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn expand_rrule(
     rrule_str: String,
     start_date: String,
     range_start: String,
     range_end: String,
     max_occurrences: Option<u32>,
+    exceptions: Option<RecurrenceExceptions>,
+    time_zone: Option<String>,
 ) -> Result<Vec<RecurrenceOccurrence>> {
-    rrule::expand_rrule(&rrule_str, &start_date, &range_start, &range_end, max_occurrences)
+    rrule::expand_rrule(
+        &rrule_str,
+        &start_date,
+        &range_start,
+        &range_end,
+        max_occurrences,
+        &exceptions.unwrap_or_default(),
+        time_zone.as_deref(),
+    )
 }
 
 /// Get a human-readable description of a recurrence rule
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn describe_rrule(rule: RecurrenceRule) -> String {
     rrule::describe_rrule(&rule)
 }
 
 /// Get the next occurrence of a recurring event after a given date
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn get_next_occurrence(
     rrule_str: String,
     start_date: String,
     after: String,
+    exceptions: Option<RecurrenceExceptions>,
+    time_zone: Option<String>,
 ) -> Result<Option<String>> {
-    rrule::get_next_occurrence(&rrule_str, &start_date, &after)
+    rrule::get_next_occurrence(
+        &rrule_str,
+        &start_date,
+        &after,
+        &exceptions.unwrap_or_default(),
+        time_zone.as_deref(),
+    )
+}
+
+/// Build the EXDATE and RDATE lines of a recurrence
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn build_recurrence_exceptions(exceptions: RecurrenceExceptions) -> Result<String> {
+    rrule::build_exception_lines(&exceptions)
+}
+
+/// Parse the EXDATE and RDATE lines of a recurrence
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn parse_recurrence_exceptions(text: String) -> Result<RecurrenceExceptions> {
+    rrule::parse_exception_lines(&text)
 }
 
 // ==========================================
@@ -120,48 +194,74 @@ pub fn get_next_occurrence(
 
 /// Generate a calendar grid for a specific month
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn generate_calendar_grid(year: i32, month: u32) -> Result<CalendarGrid> {
     calendar_grid::generate_calendar_grid(year, month)
 }
 
 /// Generate a minimal calendar grid (only current month days)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn generate_month_days(year: i32, month: u32) -> Result<Vec<CalendarDay>> {
     calendar_grid::generate_month_days(year, month)
 }
 
 /// Get weekday headers for calendar display
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn get_weekday_headers(start_on_sunday: bool) -> Vec<WeekdayHeader> {
     calendar_grid::get_weekday_headers(start_on_sunday)
 }
 
 /// Navigate to previous month
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn previous_month(year: i32, month: u32) -> (i32, u32) {
     calendar_grid::previous_month(year, month)
 }
 
 /// Navigate to next month
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn next_month(year: i32, month: u32) -> (i32, u32) {
     calendar_grid::next_month(year, month)
 }
 
 /// Get current year and month
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn current_month() -> (i32, u32) {
     calendar_grid::current_month()
 }
 
 /// Check if a date string falls within a specific month
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn is_in_month(date: String, year: i32, month: u32) -> bool {
     calendar_grid::is_in_month(&date, year, month)
 }
 
 /// Get the week number for a date
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn get_week_number(date: String) -> Result<u32> {
     calendar_grid::get_week_number(&date)
 }
+
+// ==========================================
+// Text Commands
+// ==========================================
+
+/// Truncate text to a number of user-perceived characters, adding an ellipsis
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn truncate_text(text: String, max_length: usize) -> String {
+    text::truncate_graphemes(&text, max_length)
+}
+
+/// Single-line, truncated preview of a message
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn message_preview(text: String, max_length: usize) -> String {
+    text::message_preview(&text, max_length)
+}
@@ -64,7 +64,7 @@ pub enum PasswordStrength {
 // Static Regex Patterns
 // ==========================================
 
-static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+pub(crate) static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap()
 });
 
@@ -83,6 +83,7 @@ static HAS_SPECIAL: LazyLock<Regex> =
 
 /// Validate an email address
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_email(email: String) -> Result<ValidationResult> {
     let email = email.trim();
 
@@ -131,6 +132,7 @@ pub fn validate_email(email: String) -> Result<ValidationResult> {
 
 /// Validate a password and return strength analysis
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_password(password: String) -> Result<PasswordValidation> {
     let mut errors = Vec::new();
     let mut suggestions = Vec::new();
@@ -220,6 +222,7 @@ pub fn validate_password(password: String) -> Result<PasswordValidation> {
 
 /// Validate a meeting title
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_meeting_title(title: String) -> Result<ValidationResult> {
     let title = title.trim();
 
@@ -256,6 +259,7 @@ pub fn validate_meeting_title(title: String) -> Result<ValidationResult> {
 
 /// Validate a username/display name
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_username(username: String) -> Result<ValidationResult> {
     let username = username.trim();
 
@@ -291,6 +295,7 @@ pub fn validate_username(username: String) -> Result<ValidationResult> {
 
 /// Validate a URL
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_url(url: String) -> Result<ValidationResult> {
     let url = url.trim();
 
@@ -324,6 +329,7 @@ pub fn validate_url(url: String) -> Result<ValidationResult> {
 
 /// Validate a session code format
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn validate_session_code(code: String) -> Result<ValidationResult> {
     let code = code.trim().to_uppercase();
 
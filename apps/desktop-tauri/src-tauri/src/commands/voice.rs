@@ -0,0 +1,214 @@
+//! Voice chat commands
+//!
+//! Everyone announces their own mute state over signaling, and the host can
+//! mute a participant for everyone. Changes reach the frontend as
+//! `audio:mute-changed` and the roster's `is_muted`; who is talking is
+//! emitted as `audio:speaking`.
+//!
+//! While the host streams natively, its microphone and the viewers' voices
+//! are handled here in the backend (see `stream`); viewers' own audio goes
+//! through the frontend's peer connection, which follows the same events.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::signaling::{self, SignalingState};
+use crate::commands::stream::StreamState;
+use crate::realtime::SignalingMessage;
+use crate::state::AppState;
+use crate::{Error, Result};
+
+// ==========================================
+// Event Types
+// ==========================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MuteChanged {
+    pub user_id: String,
+    pub muted: bool,
+    /// Participant who muted them, themselves unless the host did
+    pub by_user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakingChanged {
+    pub user_id: String,
+    pub speaking: bool,
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Mute or unmute the local microphone for everyone in the session
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn mute_self(
+    muted: bool,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let user_id = local_user_id(&app_state).await?;
+
+    apply_mute(&app_handle, &user_id, muted, &user_id).await;
+    announce(&signaling_state, &user_id, &user_id, muted).await;
+
+    tracing::info!("Microphone {}", if muted { "muted" } else { "unmuted" });
+    Ok(())
+}
+
+/// Mute or unmute a participant for everyone in the session (host only)
+///
+/// A participant the host muted can still unmute themselves.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn mute_participant(
+    user_id: String,
+    muted: bool,
+    app_state: State<'_, AppState>,
+    signaling_state: State<'_, SignalingState>,
+    app_handle: AppHandle,
+) -> Result<()> {
+    let local_user_id = local_user_id(&app_state).await?;
+
+    {
+        let state = signaling_state.inner.read().await;
+        if !state.is_host {
+            return Err(Error::Session(
+                "Only the host can mute participants".to_string(),
+            ));
+        }
+        if !state.participants.contains_key(&user_id) {
+            return Err(Error::NotFound(format!(
+                "Participant {} not in session",
+                user_id
+            )));
+        }
+    }
+
+    apply_mute(&app_handle, &user_id, muted, &local_user_id).await;
+    announce(&signaling_state, &local_user_id, &user_id, muted).await;
+
+    tracing::info!(
+        "Participant {} {}",
+        user_id,
+        if muted { "muted" } else { "unmuted" }
+    );
+    Ok(())
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Apply a mute announced over signaling
+///
+/// Only the participant themselves or the host may change someone's mute.
+pub(crate) async fn handle_audio_muted(
+    app_handle: &AppHandle,
+    from_user_id: &str,
+    user_id: &str,
+    muted: bool,
+) {
+    if from_user_id != user_id {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let state = signaling_state.inner.read().await;
+        if state.host_id() != Some(from_user_id) {
+            tracing::warn!(
+                "Ignoring mute of {} from non-host {}",
+                user_id,
+                from_user_id
+            );
+            return;
+        }
+    }
+
+    apply_mute(app_handle, user_id, muted, from_user_id).await;
+}
+
+/// Notify the frontend that a participant started or stopped talking
+pub(crate) fn emit_speaking(app_handle: &AppHandle, user_id: &str, speaking: bool) {
+    let payload = SpeakingChanged {
+        user_id: user_id.to_string(),
+        speaking,
+    };
+    if let Err(e) = app_handle.emit("audio:speaking", &payload) {
+        tracing::error!("Failed to emit speaking event: {}", e);
+    }
+}
+
+/// Record a participant's mute and apply it to the native audio, if any
+async fn apply_mute(app_handle: &AppHandle, user_id: &str, muted: bool, by_user_id: &str) {
+    let is_local = {
+        let app_state = app_handle.state::<AppState>();
+        let inner = app_state.inner.read().await;
+        inner.user.as_ref().is_some_and(|u| u.id == user_id)
+    };
+
+    {
+        let signaling_state = app_handle.state::<SignalingState>();
+        let mut state = signaling_state.inner.write().await;
+        let changed = if muted {
+            state.muted.insert(user_id.to_string())
+        } else {
+            state.muted.remove(user_id)
+        };
+        if changed {
+            signaling::emit_participants(app_handle, &state);
+        }
+    }
+
+    {
+        let stream_state = app_handle.state::<StreamState>();
+        let state = stream_state.inner.read().await;
+        if is_local {
+            if let Some(audio) = state.audio.as_ref() {
+                audio.set_muted(muted);
+            }
+        } else if let Some(playback) = state.playback.as_ref() {
+            playback.set_muted(user_id, muted);
+        }
+    }
+
+    let payload = MuteChanged {
+        user_id: user_id.to_string(),
+        muted,
+        by_user_id: by_user_id.to_string(),
+    };
+    if let Err(e) = app_handle.emit("audio:mute-changed", &payload) {
+        tracing::error!("Failed to emit mute changed event: {}", e);
+    }
+}
+
+/// Tell the other participants about a mute
+async fn announce(
+    signaling_state: &SignalingState,
+    from_user_id: &str,
+    user_id: &str,
+    muted: bool,
+) {
+    let tx = signaling_state.inner.read().await.signaling_tx.clone();
+    if let Some(tx) = tx {
+        let msg = SignalingMessage::AudioMuted {
+            from_user_id: from_user_id.to_string(),
+            user_id: user_id.to_string(),
+            muted,
+        };
+        if let Err(e) = tx.send(msg).await {
+            tracing::warn!("Failed to announce mute: {}", e);
+        }
+    }
+}
+
+async fn local_user_id(app_state: &AppState) -> Result<String> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    if inner.session.is_none() {
+        return Err(Error::Session("No active session".to_string()));
+    }
+    Ok(user.id.clone())
+}
@@ -0,0 +1,223 @@
+//! Voice message commands
+//!
+//! `record_voice_message` starts recording for a conversation and
+//! `stop_voice_recording` ends it, sending the recording as a `voice`
+//! message with its Ogg Opus file attached, or dropping it. The message
+//! reaches the frontend as `chat:new-message`, like any other.
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::RwLock;
+
+use crate::attachments;
+use crate::commands::chat::{ChatState, Message};
+use crate::state::AppState;
+use crate::supabase::MessageAttachment;
+use crate::voice_message::{self, Recording, VoiceRecorder};
+use crate::{Error, Result};
+
+pub struct VoiceMessageState {
+    pub inner: Arc<RwLock<VoiceMessageStateInner>>,
+}
+
+#[derive(Default)]
+pub struct VoiceMessageStateInner {
+    recording: Option<ActiveRecording>,
+}
+
+struct ActiveRecording {
+    conversation_id: String,
+    recorder: VoiceRecorder,
+}
+
+impl Default for VoiceMessageState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(VoiceMessageStateInner::default())),
+        }
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+/// Upload a recording and save it as a voice message
+async fn send_recording(
+    app_state: &AppState,
+    conversation_id: &str,
+    recording: Recording,
+) -> Result<Message> {
+    let inner = app_state.inner.read().await;
+    let user = inner
+        .user
+        .as_ref()
+        .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+    let user_id = user.id.clone();
+    let user_email = user.email.clone();
+
+    let supabase = app_state
+        .supabase
+        .as_ref()
+        .ok_or_else(|| Error::Config("Supabase not configured".to_string()))?;
+
+    drop(inner);
+
+    let name = format!("voice-{}.ogg", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    let content_type = attachments::content_type(&name);
+    let attachment = MessageAttachment {
+        path: attachments::storage_path(conversation_id, &user_id, &name),
+        name,
+        content_type: content_type.to_string(),
+        size: recording.audio.len() as u64,
+        width: None,
+        height: None,
+    };
+    supabase
+        .upload_storage_object(
+            attachments::ATTACHMENT_BUCKET,
+            &attachment.path,
+            content_type,
+            recording.audio,
+        )
+        .await?;
+
+    let content = serde_json::to_string(&recording.content)?;
+    let row = match supabase
+        .create_message_with_attachments(
+            conversation_id,
+            &user_id,
+            &content,
+            voice_message::MESSAGE_TYPE,
+            std::slice::from_ref(&attachment),
+            None,
+        )
+        .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            if let Err(e) = supabase
+                .delete_storage_object(attachments::ATTACHMENT_BUCKET, &attachment.path)
+                .await
+            {
+                tracing::warn!("Failed to remove voice message audio: {}", e);
+            }
+            return Err(e);
+        }
+    };
+
+    {
+        let mut cache = app_state.cache.messages.write().await;
+        cache.append_messages(conversation_id, vec![row.clone()]);
+    }
+
+    Ok(Message {
+        id: row.id,
+        conversation_id: row.conversation_id,
+        sender_id: row.sender_id,
+        sender_name: user_email,
+        content: row.content,
+        message_type: row.message_type,
+        created_at: row.created_at,
+        attachments: row.attachments,
+        parent_message_id: row.parent_message_id,
+        reply_count: row.reply_count,
+        last_reply_at: row.last_reply_at,
+        session_id: row.session_id,
+    })
+}
+
+/// Broadcast a message to the conversation and emit it locally
+async fn publish_message(chat_state: &ChatState, app_handle: &AppHandle, message: &Message) {
+    let chat_inner = chat_state.inner.read().await;
+    if let Some(ref realtime) = chat_inner.realtime {
+        let _ = realtime
+            .broadcast_message(&message.conversation_id, message)
+            .await;
+    }
+
+    let _ = app_handle.emit("chat:new-message", message);
+}
+
+// ==========================================
+// Commands
+// ==========================================
+
+/// Start recording a voice message for a conversation from the default
+/// microphone
+///
+/// Recording stops on its own after `voice_message::MAX_DURATION`; what was
+/// recorded is kept until `stop_voice_recording`.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn record_voice_message(
+    conversation_id: String,
+    app_state: State<'_, AppState>,
+    voice_state: State<'_, VoiceMessageState>,
+) -> Result<()> {
+    if app_state.inner.read().await.user.is_none() {
+        return Err(Error::Auth("Not authenticated".to_string()));
+    }
+
+    let mut state = voice_state.inner.write().await;
+    if state.recording.is_some() {
+        return Err(Error::Input(
+            "A voice message is already being recorded".to_string(),
+        ));
+    }
+
+    let recorder = tokio::task::spawn_blocking(VoiceRecorder::start)
+        .await
+        .map_err(|e| Error::Capture(e.to_string()))??;
+    state.recording = Some(ActiveRecording {
+        conversation_id: conversation_id.clone(),
+        recorder,
+    });
+
+    tracing::info!("Recording voice message for {}", conversation_id);
+    Ok(())
+}
+
+/// Stop recording, sending the voice message unless `send` is false
+///
+/// Returns the message sent. Recordings shorter than
+/// `voice_message::MIN_DURATION` are not sent.
+#[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn stop_voice_recording(
+    send: Option<bool>,
+    app_state: State<'_, AppState>,
+    chat_state: State<'_, ChatState>,
+    voice_state: State<'_, VoiceMessageState>,
+    app_handle: AppHandle,
+) -> Result<Option<Message>> {
+    let ActiveRecording {
+        conversation_id,
+        recorder,
+    } = voice_state
+        .inner
+        .write()
+        .await
+        .recording
+        .take()
+        .ok_or_else(|| Error::NotFound("No voice message is being recorded".to_string()))?;
+
+    let recording = tokio::task::spawn_blocking(move || recorder.stop())
+        .await
+        .map_err(|e| Error::Capture(e.to_string()))??;
+
+    if !send.unwrap_or(true) {
+        tracing::info!("Voice message discarded");
+        return Ok(None);
+    }
+    if recording.duration() < voice_message::MIN_DURATION {
+        return Err(Error::Input("Voice message is too short".to_string()));
+    }
+
+    let message = send_recording(&app_state, &conversation_id, recording).await?;
+    publish_message(&chat_state, &app_handle, &message).await;
+
+    tracing::info!("Voice message sent to {}", conversation_id);
+    Ok(Some(message))
+}
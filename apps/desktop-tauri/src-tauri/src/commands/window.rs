@@ -4,6 +4,7 @@ use crate::Result;
 
 /// Minimize the main window (used before screen sharing to avoid mirror effect)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn minimize_window(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         window.minimize()?;
@@ -14,6 +15,7 @@ pub async fn minimize_window(app: AppHandle) -> Result<()> {
 
 /// Restore the main window (used after stopping screen sharing)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn restore_window(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         window.unminimize()?;
@@ -26,6 +28,7 @@ pub async fn restore_window(app: AppHandle) -> Result<()> {
 /// Hide window from screen capture (Windows: WDA_EXCLUDEFROMCAPTURE, macOS: setSharingType)
 /// This makes the window invisible to screen recording/sharing
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn hide_from_capture(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         // set_content_protected(true) uses native APIs to exclude window from capture:
@@ -39,6 +42,7 @@ pub async fn hide_from_capture(app: AppHandle) -> Result<()> {
 
 /// Show window in screen capture again (restore normal behavior)
 #[tauri::command]
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn show_in_capture(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         window.set_content_protected(false)?;
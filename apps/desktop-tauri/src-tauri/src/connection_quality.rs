@@ -0,0 +1,211 @@
+//! Connection quality aggregation
+//!
+//! Peer connection stats are cumulative counters (bytes, packets, frames).
+//! The tracker keeps each connection's previous sample to turn them into
+//! rates over the last interval, and grades the connection so the UI can
+//! show a quality indicator. The host samples its native peers; viewers
+//! report the counters of their webview connection.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// Round trip time above which a connection is no longer good
+const FAIR_RTT_MS: f64 = 150.0;
+/// Round trip time above which a connection is poor
+const POOR_RTT_MS: f64 = 400.0;
+/// Packet loss above which a connection is no longer good
+const FAIR_LOSS_PERCENT: f64 = 2.0;
+/// Packet loss above which a connection is poor
+const POOR_LOSS_PERCENT: f64 = 8.0;
+
+/// Cumulative counters of one connection at a point in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsSample {
+    /// Media bytes sent (host) or received (viewer)
+    pub bytes: u64,
+    /// Packets that reached the receiving side
+    pub packets_received: u64,
+    pub packets_lost: u64,
+    pub round_trip_time_ms: Option<f64>,
+    /// Frames sent (host) or decoded (viewer)
+    pub frames: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Grade shown by the quality indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    Good,
+    Fair,
+    Poor,
+}
+
+impl Quality {
+    fn grade(round_trip_time_ms: Option<f64>, packet_loss_percent: f64) -> Self {
+        let rtt = round_trip_time_ms.unwrap_or(0.0);
+        if rtt > POOR_RTT_MS || packet_loss_percent > POOR_LOSS_PERCENT {
+            Quality::Poor
+        } else if rtt > FAIR_RTT_MS || packet_loss_percent > FAIR_LOSS_PERCENT {
+            Quality::Fair
+        } else {
+            Quality::Good
+        }
+    }
+}
+
+/// Stats of one connection over the last sampling interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    /// Viewer (on the host) or host (on a viewer) at the other end
+    pub peer_id: String,
+    pub round_trip_time_ms: Option<f64>,
+    pub packet_loss_percent: f64,
+    pub bitrate_kbps: f64,
+    pub frames_per_second: f64,
+    /// Total frames sent (host) or decoded (viewer)
+    pub frames: u64,
+    pub width: u32,
+    pub height: u32,
+    pub quality: Quality,
+}
+
+/// Previous sample and latest stats of every connection
+#[derive(Default)]
+pub struct QualityTracker {
+    previous: HashMap<String, (Instant, StatsSample)>,
+    latest: HashMap<String, ConnectionStats>,
+}
+
+impl QualityTracker {
+    /// Record a sample and compute the stats since the previous one
+    ///
+    /// The first sample of a connection has no interval, so its rates are 0.
+    pub fn update(&mut self, peer_id: &str, sample: StatsSample, now: Instant) -> ConnectionStats {
+        let (bitrate_kbps, frames_per_second, packet_loss_percent) =
+            match self.previous.get(peer_id) {
+                Some((at, previous)) => {
+                    let secs = now.duration_since(*at).as_secs_f64();
+                    let rate = |current: u64, before: u64| {
+                        if secs > 0.0 {
+                            current.saturating_sub(before) as f64 / secs
+                        } else {
+                            0.0
+                        }
+                    };
+                    let received = sample
+                        .packets_received
+                        .saturating_sub(previous.packets_received);
+                    let lost = sample.packets_lost.saturating_sub(previous.packets_lost);
+                    (
+                        rate(sample.bytes, previous.bytes) * 8.0 / 1000.0,
+                        rate(sample.frames, previous.frames),
+                        loss_percent(received, lost),
+                    )
+                }
+                None => (0.0, 0.0, 0.0),
+            };
+
+        let stats = ConnectionStats {
+            peer_id: peer_id.to_string(),
+            round_trip_time_ms: sample.round_trip_time_ms,
+            packet_loss_percent,
+            bitrate_kbps,
+            frames_per_second,
+            frames: sample.frames,
+            width: sample.width,
+            height: sample.height,
+            quality: Quality::grade(sample.round_trip_time_ms, packet_loss_percent),
+        };
+        self.previous.insert(peer_id.to_string(), (now, sample));
+        self.latest.insert(peer_id.to_string(), stats.clone());
+        stats
+    }
+
+    /// Latest stats of every connection, sorted by peer
+    pub fn latest(&self) -> Vec<ConnectionStats> {
+        let mut latest: Vec<ConnectionStats> = self.latest.values().cloned().collect();
+        latest.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        latest
+    }
+
+    /// Drop a connection that closed
+    pub fn forget(&mut self, peer_id: &str) {
+        self.previous.remove(peer_id);
+        self.latest.remove(peer_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.latest.clear();
+    }
+}
+
+fn loss_percent(received: u64, lost: u64) -> f64 {
+    let expected = received + lost;
+    if expected == 0 {
+        0.0
+    } else {
+        lost as f64 * 100.0 / expected as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rates_over_interval() {
+        let mut tracker = QualityTracker::default();
+        let start = Instant::now();
+
+        let first = tracker.update(
+            "viewer-1",
+            StatsSample {
+                bytes: 100_000,
+                packets_received: 100,
+                frames: 30,
+                width: 1920,
+                height: 1080,
+                ..Default::default()
+            },
+            start,
+        );
+        assert_eq!(first.bitrate_kbps, 0.0);
+        assert_eq!(first.quality, Quality::Good);
+
+        let second = tracker.update(
+            "viewer-1",
+            StatsSample {
+                bytes: 350_000,
+                packets_received: 190,
+                packets_lost: 10,
+                round_trip_time_ms: Some(80.0),
+                frames: 60,
+                width: 1920,
+                height: 1080,
+            },
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(second.bitrate_kbps, 1000.0);
+        assert_eq!(second.frames_per_second, 15.0);
+        assert_eq!(second.packet_loss_percent, 10.0);
+        assert_eq!(second.quality, Quality::Poor);
+        assert_eq!(tracker.latest(), vec![second]);
+
+        tracker.forget("viewer-1");
+        assert!(tracker.latest().is_empty());
+    }
+
+    #[test]
+    fn test_quality_grades() {
+        assert_eq!(Quality::grade(None, 0.0), Quality::Good);
+        assert_eq!(Quality::grade(Some(200.0), 0.5), Quality::Fair);
+        assert_eq!(Quality::grade(Some(50.0), 3.0), Quality::Fair);
+        assert_eq!(Quality::grade(Some(500.0), 0.0), Quality::Poor);
+    }
+}
@@ -0,0 +1,76 @@
+//! Conversation realtime channels
+//!
+//! Broadcasts for a conversation go to its own channel. The app joins it
+//! while the conversation is open somewhere, a chat panel or a pop-out
+//! window, and leaves once the last of them closes. Open conversations are
+//! counted apart from the socket, so their channels are joined again after
+//! chat reconnects.
+
+use std::collections::HashMap;
+
+/// Realtime topic of a conversation's channel
+pub fn topic(conversation_id: &str) -> String {
+    format!("realtime:chat:{}", conversation_id)
+}
+
+/// How many times each conversation is open
+#[derive(Debug, Default)]
+pub struct OpenConversations {
+    counts: HashMap<String, u32>,
+}
+
+impl OpenConversations {
+    /// Count a conversation as opened, returning whether it just opened and
+    /// its channel should be joined
+    pub fn open(&mut self, conversation_id: &str) -> bool {
+        let count = self.counts.entry(conversation_id.to_string()).or_insert(0);
+        *count += 1;
+        *count == 1
+    }
+
+    /// Count a conversation as closed, returning whether it is no longer
+    /// open anywhere and its channel should be left
+    pub fn close(&mut self, conversation_id: &str) -> bool {
+        let Some(count) = self.counts.get_mut(conversation_id) else {
+            return false;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return false;
+        }
+        self.counts.remove(conversation_id);
+        true
+    }
+
+    /// Conversations open anywhere
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.counts.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic() {
+        assert_eq!(topic("c1"), "realtime:chat:c1");
+    }
+
+    #[test]
+    fn test_open_and_close() {
+        let mut open = OpenConversations::default();
+        assert!(open.open("c1"));
+        assert!(!open.open("c1"));
+        assert!(open.open("c2"));
+
+        assert!(!open.close("c1"));
+        assert!(open.close("c1"));
+        let ids: Vec<&str> = open.ids().collect();
+        assert_eq!(ids, ["c2"]);
+
+        // Closing more than opened changes nothing
+        assert!(!open.close("c1"));
+        assert!(open.open("c1"));
+    }
+}
@@ -0,0 +1,120 @@
+//! `squadxlive://` invite links
+//!
+//! `squadxlive://session/<join code>` joins a session (`?mode=lan` to look
+//! for it on the local network) and `squadxlive://meeting/<id>` opens a
+//! meeting.
+
+use serde::Serialize;
+
+use crate::state::SessionMode;
+use crate::{Error, Result};
+
+/// Scheme registered with the OS for the app
+pub const SCHEME: &str = "squadxlive";
+
+/// What an invite link points at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeepLink {
+    Session {
+        join_code: String,
+        mode: SessionMode,
+    },
+    Meeting {
+        meeting_id: String,
+    },
+}
+
+impl DeepLink {
+    /// Parse a `squadxlive://` URL
+    pub fn parse(url: &str) -> Result<Self> {
+        let rest = url
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.strip_prefix("://"))
+            .ok_or_else(|| Error::Parse(format!("Not a {} link: {}", SCHEME, url)))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut segments = path.trim_matches('/').split('/');
+
+        let link = match (segments.next(), segments.next(), segments.next()) {
+            (Some("session"), Some(code), None) if is_join_code(code) => DeepLink::Session {
+                join_code: code.to_uppercase(),
+                mode: if query.split('&').any(|param| param == "mode=lan") {
+                    SessionMode::Lan
+                } else {
+                    SessionMode::Cloud
+                },
+            },
+            (Some("meeting"), Some(id), None) if uuid::Uuid::parse_str(id).is_ok() => {
+                DeepLink::Meeting {
+                    meeting_id: id.to_lowercase(),
+                }
+            }
+            _ => return Err(Error::Parse(format!("Unrecognized link: {}", url))),
+        };
+        Ok(link)
+    }
+
+    /// URL of the link
+    pub fn url(&self) -> String {
+        match self {
+            DeepLink::Session {
+                join_code,
+                mode: SessionMode::Lan,
+            } => format!("{}://session/{}?mode=lan", SCHEME, join_code),
+            DeepLink::Session { join_code, .. } => format!("{}://session/{}", SCHEME, join_code),
+            DeepLink::Meeting { meeting_id } => format!("{}://meeting/{}", SCHEME, meeting_id),
+        }
+    }
+}
+
+/// Join codes are short and alphanumeric; anything else is not worth a lookup
+fn is_join_code(code: &str) -> bool {
+    (4..=12).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_links() {
+        let link = DeepLink::parse("squadxlive://session/ab3def").unwrap();
+        assert_eq!(
+            link,
+            DeepLink::Session {
+                join_code: "AB3DEF".to_string(),
+                mode: SessionMode::Cloud,
+            }
+        );
+        assert_eq!(link.url(), "squadxlive://session/AB3DEF");
+
+        let lan = DeepLink::parse("squadxlive://session/AB3DEF/?mode=lan").unwrap();
+        assert_eq!(DeepLink::parse(&lan.url()).unwrap(), lan);
+    }
+
+    #[test]
+    fn test_meeting_links() {
+        let id = "6f1c2b6e-8a77-4c3e-9a53-2f0b7a1d9c10";
+        let link = DeepLink::parse(&format!("squadxlive://meeting/{}", id)).unwrap();
+        assert_eq!(
+            link,
+            DeepLink::Meeting {
+                meeting_id: id.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_links() {
+        for url in [
+            "https://squadx.live/session/AB3DEF",
+            "squadxlive://session/",
+            "squadxlive://session/AB3DEF/extra",
+            "squadxlive://session/AB-3DE",
+            "squadxlive://meeting/not-a-uuid",
+            "squadxlive://settings",
+        ] {
+            assert!(DeepLink::parse(url).is_err(), "{}", url);
+        }
+    }
+}
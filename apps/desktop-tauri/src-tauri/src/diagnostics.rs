@@ -0,0 +1,159 @@
+//! Diagnostics bundles
+//!
+//! Everything a bug report needs in one zip: recent logs, the configuration
+//! with secrets redacted, cache, connection and command stats, the state of
+//! background tasks and the app and OS versions.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+
+use crate::{Error, Result};
+
+/// Environment variables worth reporting, by name fragment
+const CONFIG_VARS: [&str; 7] = [
+    "SUPABASE",
+    "GOOGLE_",
+    "TURN_",
+    "ICE_",
+    "SQUADX",
+    "RUST_LOG",
+    "RUST_BACKTRACE",
+];
+
+/// Name fragments of variables whose values must not leave the machine
+const SECRET_VARS: [&str; 6] = [
+    "KEY",
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "CREDENTIAL",
+    // Carries TURN credentials
+    "ICE_SERVERS",
+];
+
+const REDACTED: &str = "[redacted]";
+
+/// App and OS the bundle was made on
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub app_version: String,
+    pub tauri_version: &'static str,
+    pub os: &'static str,
+    pub os_family: &'static str,
+    pub arch: &'static str,
+    pub created_at: String,
+}
+
+impl SystemInfo {
+    pub fn collect(app_version: String) -> Self {
+        Self {
+            app_version,
+            tauri_version: tauri::VERSION,
+            os: std::env::consts::OS,
+            os_family: std::env::consts::FAMILY,
+            arch: std::env::consts::ARCH,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Configuration read from the environment, secrets redacted
+pub fn config_snapshot() -> BTreeMap<String, String> {
+    std::env::vars()
+        .filter(|(name, _)| CONFIG_VARS.iter().any(|var| name.contains(var)))
+        .map(|(name, value)| {
+            let value = redact(&name, value);
+            (name, value)
+        })
+        .collect()
+}
+
+/// Value of a variable, or a placeholder if it holds a secret
+fn redact(name: &str, value: String) -> String {
+    let name = name.to_uppercase();
+    if SECRET_VARS.iter().any(|secret| name.contains(secret)) {
+        REDACTED.to_string()
+    } else {
+        value
+    }
+}
+
+/// Files of a bundle, in the order they are written
+#[derive(Default)]
+pub struct DiagnosticsBundle {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl DiagnosticsBundle {
+    /// Add a plain text file
+    pub fn add_text(&mut self, name: &str, text: String) {
+        self.files.push((name.to_string(), text.into_bytes()));
+    }
+
+    /// Add a value as a pretty-printed JSON file
+    pub fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_vec_pretty(value)?;
+        self.files.push((name.to_string(), json));
+        Ok(())
+    }
+
+    /// Write the bundle as a zip archive
+    pub fn write_zip(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut zip = zip::ZipWriter::new(File::create(path)?);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, contents) in &self.files {
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| Error::Storage(format!("Failed to add {}: {}", name, e)))?;
+            zip.write_all(contents)?;
+        }
+        zip.finish()
+            .map_err(|e| Error::Storage(format!("Failed to write diagnostics bundle: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets() {
+        assert_eq!(
+            redact("VITE_SUPABASE_ANON_KEY", "eyJ...".to_string()),
+            REDACTED
+        );
+        assert_eq!(redact("GOOGLE_CLIENT_SECRET", "s".to_string()), REDACTED);
+        assert_eq!(redact("VITE_TURN_CREDENTIAL", "c".to_string()), REDACTED);
+        assert_eq!(redact("VITE_ICE_SERVERS", "[]".to_string()), REDACTED);
+        assert_eq!(
+            redact("VITE_SUPABASE_URL", "https://x.supabase.co".to_string()),
+            "https://x.supabase.co"
+        );
+    }
+
+    #[test]
+    fn test_write_zip() {
+        let mut bundle = DiagnosticsBundle::default();
+        bundle.add_text("logs.txt", "line".to_string());
+        bundle.add_json("config.json", &config_snapshot()).unwrap();
+
+        let path = std::env::temp_dir().join(format!("diagnostics-{}.zip", uuid::Uuid::new_v4()));
+        bundle.write_zip(&path).unwrap();
+
+        let archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"logs.txt"));
+        assert!(names.contains(&"config.json"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
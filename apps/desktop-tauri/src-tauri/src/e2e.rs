@@ -0,0 +1,338 @@
+//! End-to-end encryption of signaling payloads
+//!
+//! Everything on a session channel (SDP, ICE candidates, session chat) goes
+//! through the Supabase Realtime relay. With encryption on, each payload is
+//! sealed with XChaCha20-Poly1305 before it leaves the client:
+//!
+//! - broadcasts use a group key derived with HKDF-SHA256 from the join code,
+//!   which only participants know, salted with the session ID;
+//! - every client also holds an X25519 key pair for the connection and
+//!   announces its public key in `UserJoined` (itself sealed with the group
+//!   key, so the relay can't swap it). Messages addressed to one peer are
+//!   sealed with a pairwise key derived from the X25519 shared secret, which
+//!   stays private even to someone who later learns the join code.
+//!
+//! Sender and recipient IDs travel in clear and are bound to the ciphertext
+//! as associated data.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::realtime::SignalingMessage;
+use crate::signaling_delivery::SignalingEnvelope;
+use crate::{Error, Result};
+
+const GROUP_KEY_INFO: &[u8] = b"squadx-live/signaling/group/v1";
+const PAIR_KEY_INFO: &[u8] = b"squadx-live/signaling/pair/v1";
+
+/// Marker of encrypted frames on the wire
+const ENCRYPTED_TYPE: &str = "encrypted";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum KeyKind {
+    Group,
+    Pair,
+}
+
+/// Sealed signaling payload as broadcast on the channel
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedFrame {
+    #[serde(rename = "type")]
+    kind: String,
+    from_user_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to_user_id: Option<String>,
+    key: KeyKind,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Per-connection encryption state
+pub struct SessionCipher {
+    user_id: String,
+    group_key: [u8; 32],
+    secret: StaticSecret,
+    public_key: PublicKey,
+    /// Pairwise keys by peer user ID, learned from their `UserJoined`
+    pair_keys: HashMap<String, [u8; 32]>,
+    /// Encrypt outgoing payloads and drop plaintext ones
+    required: bool,
+}
+
+impl std::fmt::Debug for SessionCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCipher")
+            .field("user_id", &self.user_id)
+            .field("peers", &self.pair_keys.len())
+            .field("required", &self.required)
+            .finish_non_exhaustive()
+    }
+}
+
+fn hkdf_key(ikm: &[u8], salt: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(info, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn associated_data(from_user_id: &str, to_user_id: Option<&str>) -> Vec<u8> {
+    format!("{}|{}", from_user_id, to_user_id.unwrap_or_default()).into_bytes()
+}
+
+impl SessionCipher {
+    pub fn new(join_code: &str, session_id: &str, user_id: &str, required: bool) -> Self {
+        let normalized = join_code.trim().to_uppercase();
+        let secret = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let public_key = PublicKey::from(&secret);
+
+        Self {
+            user_id: user_id.to_string(),
+            group_key: hkdf_key(normalized.as_bytes(), session_id.as_bytes(), GROUP_KEY_INFO),
+            secret,
+            public_key,
+            pair_keys: HashMap::new(),
+            required,
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    pub fn public_key(&self) -> String {
+        BASE64.encode(self.public_key.as_bytes())
+    }
+
+    /// Derive the pairwise key shared with a peer
+    fn add_peer(&mut self, user_id: &str, public_key: &str) -> Result<()> {
+        let bytes: [u8; 32] = BASE64
+            .decode(public_key)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| Error::Parse(format!("Invalid public key from {}", user_id)))?;
+        let shared = self.secret.diffie_hellman(&PublicKey::from(bytes));
+
+        // Both sides must use the same info, whoever derives it
+        let (a, b) = if self.user_id.as_str() < user_id {
+            (self.user_id.as_str(), user_id)
+        } else {
+            (user_id, self.user_id.as_str())
+        };
+        let info = [PAIR_KEY_INFO, b"|", a.as_bytes(), b"|", b.as_bytes()].concat();
+
+        self.pair_keys.insert(
+            user_id.to_string(),
+            hkdf_key(shared.as_bytes(), &self.group_key, &info),
+        );
+        Ok(())
+    }
+
+    /// Payload to broadcast for an outgoing envelope
+    ///
+    /// Stamps our public key on `UserJoined`; encrypts when required.
+    pub fn seal(&self, mut envelope: SignalingEnvelope) -> Result<serde_json::Value> {
+        if let SignalingMessage::UserJoined { public_key, .. } = &mut envelope.message {
+            *public_key = Some(self.public_key());
+        }
+        if !self.required {
+            return Ok(serde_json::to_value(&envelope)?);
+        }
+
+        let from_user_id = envelope
+            .message
+            .sender()
+            .unwrap_or(&self.user_id)
+            .to_string();
+        let to_user_id = envelope.message.recipient().map(String::from);
+        // Joins carry the public key the pairwise key is derived from
+        let pair_key = match &envelope.message {
+            SignalingMessage::UserJoined { .. } => None,
+            _ => to_user_id.as_deref().and_then(|to| self.pair_keys.get(to)),
+        };
+        let (key_kind, key) = match pair_key {
+            Some(key) => (KeyKind::Pair, key),
+            None => (KeyKind::Group, &self.group_key),
+        };
+
+        let mut nonce = [0u8; 24];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let plaintext = serde_json::to_vec(&envelope)?;
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &associated_data(&from_user_id, to_user_id.as_deref()),
+                },
+            )
+            .map_err(|_| Error::Session("Failed to encrypt signaling message".to_string()))?;
+
+        Ok(serde_json::to_value(EncryptedFrame {
+            kind: ENCRYPTED_TYPE.to_string(),
+            from_user_id,
+            to_user_id,
+            key: key_kind,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })?)
+    }
+
+    /// Envelope carried by an incoming payload
+    ///
+    /// Returns `None` for frames addressed to someone else. Plaintext is
+    /// rejected when encryption is required.
+    pub fn open(&mut self, payload: &serde_json::Value) -> Result<Option<SignalingEnvelope>> {
+        let envelope = if payload.get("type").and_then(|t| t.as_str()) == Some(ENCRYPTED_TYPE) {
+            let frame: EncryptedFrame = serde_json::from_value(payload.clone())?;
+            if matches!(frame.to_user_id.as_deref(), Some(to) if to != self.user_id) {
+                return Ok(None);
+            }
+            self.decrypt(&frame)?
+        } else if self.required {
+            return Err(Error::Session(
+                "Dropped unencrypted signaling message".to_string(),
+            ));
+        } else {
+            serde_json::from_value::<SignalingEnvelope>(payload.clone())?
+        };
+
+        if let SignalingMessage::UserJoined {
+            user_id,
+            public_key: Some(public_key),
+            ..
+        } = &envelope.message
+        {
+            if user_id != &self.user_id {
+                self.add_peer(user_id, public_key)?;
+            }
+        }
+
+        Ok(Some(envelope))
+    }
+
+    fn decrypt(&self, frame: &EncryptedFrame) -> Result<SignalingEnvelope> {
+        let key = match frame.key {
+            KeyKind::Group => &self.group_key,
+            KeyKind::Pair => self.pair_keys.get(&frame.from_user_id).ok_or_else(|| {
+                Error::Session(format!("No pairwise key for {}", frame.from_user_id))
+            })?,
+        };
+
+        let nonce = BASE64
+            .decode(&frame.nonce)
+            .ok()
+            .filter(|n| n.len() == 24)
+            .ok_or_else(|| Error::Parse("Invalid nonce".to_string()))?;
+        let ciphertext = BASE64
+            .decode(&frame.ciphertext)
+            .map_err(|e| Error::Parse(format!("Invalid ciphertext: {}", e)))?;
+
+        let plaintext = XChaCha20Poly1305::new(Key::from_slice(key))
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &associated_data(&frame.from_user_id, frame.to_user_id.as_deref()),
+                },
+            )
+            .map_err(|_| {
+                Error::Session(format!(
+                    "Failed to decrypt signaling message from {}",
+                    frame.from_user_id
+                ))
+            })?;
+
+        let envelope: SignalingEnvelope = serde_json::from_slice(&plaintext)?;
+        // The clear sender must match the sealed one
+        if envelope
+            .message
+            .sender()
+            .is_some_and(|from| from != frame.from_user_id)
+        {
+            return Err(Error::Session("Signaling sender mismatch".to_string()));
+        }
+        Ok(envelope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(user_id: &str, to: Option<&str>) -> SignalingEnvelope {
+        SignalingEnvelope::unsequenced(SignalingMessage::user_joined(user_id, false, to))
+    }
+
+    fn offer(to: &str) -> SignalingEnvelope {
+        SignalingEnvelope {
+            message: SignalingMessage::Offer {
+                sdp: "v=0 secret sdp".to_string(),
+                from_user_id: "host".to_string(),
+                to_user_id: Some(to.to_string()),
+            },
+            seq: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_group_and_pairwise_roundtrip() {
+        let mut host = SessionCipher::new("abc123", "session-1", "host", true);
+        let mut viewer = SessionCipher::new("ABC123 ", "session-1", "viewer", true);
+
+        // Key exchange through sealed joins
+        let viewer_join = viewer.seal(joined("viewer", None)).unwrap();
+        assert_eq!(viewer_join["type"], "encrypted");
+        assert_eq!(viewer_join["key"], "group");
+        host.open(&viewer_join).unwrap().unwrap();
+        let host_join = host.seal(joined("host", Some("viewer"))).unwrap();
+        viewer.open(&host_join).unwrap().unwrap();
+
+        let sealed = host.seal(offer("viewer")).unwrap();
+        assert_eq!(sealed["key"], "pair");
+        assert!(!sealed.to_string().contains("secret sdp"));
+
+        let opened = viewer.open(&sealed).unwrap().unwrap();
+        assert_eq!(opened.seq, Some(1));
+        assert!(matches!(opened.message, SignalingMessage::Offer { .. }));
+
+        // Frames addressed to someone else are skipped
+        let mut other = SessionCipher::new("abc123", "session-1", "other", true);
+        assert!(other.open(&sealed).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_wrong_join_code_and_plaintext_are_rejected() {
+        let host = SessionCipher::new("abc123", "session-1", "host", true);
+        let mut intruder = SessionCipher::new("zzz999", "session-1", "intruder", true);
+        let sealed = host.seal(joined("host", None)).unwrap();
+        assert!(intruder.open(&sealed).is_err());
+
+        let plaintext = serde_json::to_value(joined("host", None)).unwrap();
+        assert!(intruder.open(&plaintext).is_err());
+
+        // Without encryption required, plaintext still flows
+        let mut relaxed = SessionCipher::new("abc123", "session-1", "viewer", false);
+        assert!(relaxed.open(&plaintext).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_tampered_sender_is_rejected() {
+        let host = SessionCipher::new("abc123", "session-1", "host", true);
+        let mut viewer = SessionCipher::new("abc123", "session-1", "viewer", true);
+        let mut sealed = host.seal(joined("host", None)).unwrap();
+        sealed["from_user_id"] = serde_json::json!("mallory");
+        assert!(viewer.open(&sealed).is_err());
+    }
+}
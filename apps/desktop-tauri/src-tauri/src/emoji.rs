@@ -0,0 +1,174 @@
+//! Custom emoji shortcodes
+//!
+//! Messages keep custom emoji as `:shortcode:` text, so they read fine in
+//! notifications and on clients that don't know the emoji. Rendering splits
+//! a message into text and shortcodes of emoji the team has; shortcodes in
+//! inline code or code blocks stay text, as do unknown ones (standard emoji
+//! like `:smile:` are left to the frontend).
+
+use crate::{Error, Result};
+
+/// Shortcode length bounds, matching the `custom_emojis` check constraint
+pub const MIN_SHORTCODE_LEN: usize = 2;
+pub const MAX_SHORTCODE_LEN: usize = 32;
+
+/// Piece of a message, borrowed from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextPart<'a> {
+    Text(&'a str),
+    /// Shortcode of a known emoji, without the colons
+    Shortcode(&'a str),
+}
+
+/// Whether `shortcode` (without colons) is well-formed
+pub fn is_valid_shortcode(shortcode: &str) -> bool {
+    (MIN_SHORTCODE_LEN..=MAX_SHORTCODE_LEN).contains(&shortcode.len())
+        && shortcode
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"_+-".contains(&b))
+}
+
+/// Shortcode as typed by a user (`:Party-Parrot:`, `party-parrot`), lowercased
+/// and without colons
+pub fn normalize_shortcode(input: &str) -> Result<String> {
+    let shortcode = input
+        .trim()
+        .trim_start_matches(':')
+        .trim_end_matches(':')
+        .to_lowercase();
+    if !is_valid_shortcode(&shortcode) {
+        return Err(Error::Parse(format!(
+            "Shortcodes are {} to {} lowercase letters, digits, '_', '+' or '-'",
+            MIN_SHORTCODE_LEN, MAX_SHORTCODE_LEN
+        )));
+    }
+    Ok(shortcode)
+}
+
+/// Split a message into text and the shortcodes `is_known` accepts
+pub fn split_shortcodes<'a>(text: &'a str, is_known: impl Fn(&str) -> bool) -> Vec<TextPart<'a>> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                // A code span ends at the next run of as many backticks; an
+                // unmatched run is literal
+                let run = backtick_run(&bytes[i..]);
+                i = closing_backticks(bytes, i + run, run).unwrap_or(i + run);
+            }
+            b':' => {
+                let rest = &text[i + 1..];
+                let end = rest
+                    .bytes()
+                    .take(MAX_SHORTCODE_LEN + 1)
+                    .position(|b| b == b':');
+                match end {
+                    Some(len) if is_valid_shortcode(&rest[..len]) && is_known(&rest[..len]) => {
+                        if text_start < i {
+                            parts.push(TextPart::Text(&text[text_start..i]));
+                        }
+                        parts.push(TextPart::Shortcode(&rest[..len]));
+                        i += len + 2;
+                        text_start = i;
+                    }
+                    _ => i += 1,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    if text_start < bytes.len() {
+        parts.push(TextPart::Text(&text[text_start..]));
+    }
+    parts
+}
+
+fn backtick_run(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|&&b| b == b'`').count()
+}
+
+/// End of the first run of exactly `run` backticks from `from`
+fn closing_backticks(bytes: &[u8], from: usize, run: usize) -> Option<usize> {
+    let mut j = from;
+    while j < bytes.len() {
+        if bytes[j] == b'`' {
+            let len = backtick_run(&bytes[j..]);
+            if len == run {
+                return Some(j + len);
+            }
+            j += len;
+        } else {
+            j += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known(shortcode: &str) -> bool {
+        matches!(shortcode, "parrot" | "ship-it")
+    }
+
+    #[test]
+    fn test_normalize_shortcode() {
+        assert_eq!(
+            normalize_shortcode(":Party-Parrot:").unwrap(),
+            "party-parrot"
+        );
+        assert_eq!(normalize_shortcode(" ship_it ").unwrap(), "ship_it");
+        assert!(normalize_shortcode(":a:").is_err());
+        assert!(normalize_shortcode("has space").is_err());
+        assert!(normalize_shortcode(&"x".repeat(MAX_SHORTCODE_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_split_known_shortcodes() {
+        assert_eq!(
+            split_shortcodes("nice :parrot: time to :ship-it:", known),
+            vec![
+                TextPart::Text("nice "),
+                TextPart::Shortcode("parrot"),
+                TextPart::Text(" time to "),
+                TextPart::Shortcode("ship-it"),
+            ]
+        );
+        assert_eq!(
+            split_shortcodes(":parrot::parrot:", known),
+            vec![TextPart::Shortcode("parrot"), TextPart::Shortcode("parrot")]
+        );
+    }
+
+    #[test]
+    fn test_unknown_shortcodes_stay_text() {
+        assert_eq!(
+            split_shortcodes(":smile: at 10:30:00", known),
+            vec![TextPart::Text(":smile: at 10:30:00")]
+        );
+        assert_eq!(split_shortcodes("", known), vec![]);
+    }
+
+    #[test]
+    fn test_code_is_not_resolved() {
+        assert_eq!(
+            split_shortcodes("`:parrot:` :parrot:", known),
+            vec![TextPart::Text("`:parrot:` "), TextPart::Shortcode("parrot")]
+        );
+        assert_eq!(
+            split_shortcodes("```\n:parrot:\n```", known),
+            vec![TextPart::Text("```\n:parrot:\n```")]
+        );
+        // An unmatched backtick is literal
+        assert_eq!(
+            split_shortcodes("it`s :parrot:", known),
+            vec![TextPart::Text("it`s "), TextPart::Shortcode("parrot")]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -37,6 +38,21 @@ pub enum Error {
     #[error("External service error: {0}")]
     External(String),
 
+    #[error("WebRTC error: {0}")]
+    WebRtc(String),
+
+    #[error("Too many attempts, try again in {retry_after_secs} seconds")]
+    TooManyAttempts { retry_after_secs: u64 },
+
+    #[error("You were banned from this session")]
+    Banned,
+
+    #[error("This session is locked")]
+    SessionLocked,
+
+    #[error("Request failed after {attempts} attempts: {last_error}")]
+    RetriesExhausted { attempts: u32, last_error: String },
+
     #[error("Tauri error: {0}")]
     Tauri(#[from] tauri::Error),
 
@@ -55,6 +71,24 @@ impl Serialize for Error {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        match self {
+            // Structured so the frontend can count down to the next attempt
+            Error::TooManyAttempts { retry_after_secs } => {
+                let mut error = serializer.serialize_struct("Error", 3)?;
+                error.serialize_field("code", "too_many_attempts")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("retry_after_secs", retry_after_secs)?;
+                error.end()
+            }
+            // Structured so the frontend can tell an outage from a failure
+            Error::RetriesExhausted { attempts, .. } => {
+                let mut error = serializer.serialize_struct("Error", 3)?;
+                error.serialize_field("code", "retries_exhausted")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("attempts", attempts)?;
+                error.end()
+            }
+            _ => serializer.serialize_str(&self.to_string()),
+        }
     }
 }
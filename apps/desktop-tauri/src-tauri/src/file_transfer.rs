@@ -0,0 +1,206 @@
+//! File transfer wire protocol
+//!
+//! Files travel over a dedicated, ordered data channel of the peer
+//! connection. Control messages are JSON text frames; file contents are
+//! binary frames of at most `CHUNK_SIZE` bytes, each prefixed with the raw
+//! transfer UUID (16 bytes) and the big-endian byte offset of the chunk
+//! (8 bytes). The receiver answers an offer with the offset it already has,
+//! which is how interrupted transfers resume.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+/// Payload bytes per binary frame, the largest size every browser accepts
+pub const CHUNK_SIZE: usize = 16 * 1024;
+
+const FRAME_HEADER_LEN: usize = 16 + 8;
+
+/// Control message exchanged on the file channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransferMessage {
+    /// Sender proposes a file
+    Offer {
+        transfer_id: String,
+        file_name: String,
+        size: u64,
+        sha256: String,
+    },
+    /// Receiver accepts, asking for the bytes from `offset` on
+    Accept { transfer_id: String, offset: u64 },
+    /// Either side gives up on the transfer
+    Cancel {
+        transfer_id: String,
+        reason: Option<String>,
+    },
+    /// Receiver got every byte and the checksum matches
+    Complete { transfer_id: String },
+    /// Receiver got every byte but the checksum doesn't match
+    Failed { transfer_id: String, reason: String },
+}
+
+/// Build a binary chunk frame
+pub fn encode_chunk(transfer_id: &Uuid, offset: u64, data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + data.len());
+    frame.extend_from_slice(transfer_id.as_bytes());
+    frame.extend_from_slice(&offset.to_be_bytes());
+    frame.extend_from_slice(data);
+    frame
+}
+
+/// Split a binary chunk frame into transfer ID, offset and payload
+pub fn decode_chunk(frame: &[u8]) -> Result<(Uuid, u64, &[u8])> {
+    if frame.len() < FRAME_HEADER_LEN {
+        return Err(Error::Parse("File chunk frame is too short".to_string()));
+    }
+
+    let transfer_id = Uuid::from_slice(&frame[..16])
+        .map_err(|e| Error::Parse(format!("Invalid transfer ID: {}", e)))?;
+    let mut offset = [0u8; 8];
+    offset.copy_from_slice(&frame[16..FRAME_HEADER_LEN]);
+
+    Ok((
+        transfer_id,
+        u64::from_be_bytes(offset),
+        &frame[FRAME_HEADER_LEN..],
+    ))
+}
+
+/// Hex SHA-256 of a file, read in chunks (blocking)
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Strip directories and characters that aren't valid in file names, so a
+/// peer can't make us write outside the download directory
+pub fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| match c {
+            ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.');
+
+    if cleaned.is_empty() {
+        "file".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Whether `value` is a hex SHA-256 digest, as a peer must offer
+pub fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Partial download of a file, keyed by content so any offer of the same
+/// file resumes it. The digest comes from the peer, so anything but hex is
+/// refused rather than joined onto the download directory.
+pub fn partial_path(dir: &Path, sha256: &str) -> Result<PathBuf> {
+    if !is_sha256_hex(sha256) {
+        return Err(Error::Parse("Invalid file checksum".to_string()));
+    }
+    Ok(dir.join(format!(".squadx-{}.part", &sha256[..16])))
+}
+
+/// Destination in `dir` that doesn't overwrite an existing file
+pub fn unique_destination(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_frame_roundtrip() {
+        let id = Uuid::new_v4();
+        let frame = encode_chunk(&id, 32768, b"patch contents");
+        let (decoded_id, offset, data) = decode_chunk(&frame).unwrap();
+        assert_eq!(decoded_id, id);
+        assert_eq!(offset, 32768);
+        assert_eq!(data, b"patch contents");
+
+        assert!(decode_chunk(&frame[..10]).is_err());
+    }
+
+    #[test]
+    fn test_message_json_shape() {
+        let msg = TransferMessage::Accept {
+            transfer_id: "t1".to_string(),
+            offset: 42,
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "accept");
+        assert_eq!(json["offset"], 42);
+        assert_eq!(
+            serde_json::from_value::<TransferMessage>(json).unwrap(),
+            msg
+        );
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("fix.patch"), "fix.patch");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("C:\\logs\\app:1.log"), "app_1.log");
+        assert_eq!(sanitize_file_name(".bashrc"), "bashrc");
+        assert_eq!(sanitize_file_name("../"), "file");
+    }
+
+    #[test]
+    fn test_partial_path_requires_hex_digest() {
+        let dir = Path::new("/downloads");
+        let digest = "ab".repeat(32);
+        assert_eq!(
+            partial_path(dir, &digest).unwrap(),
+            dir.join(".squadx-abababababababab.part")
+        );
+        assert!(partial_path(dir, "../../x").is_err());
+        assert!(partial_path(dir, &format!("../../{}", &digest[6..])).is_err());
+        assert!(partial_path(dir, &digest[..63]).is_err());
+        assert!(!is_sha256_hex(&"é".repeat(32)));
+    }
+}
@@ -0,0 +1,233 @@
+//! Group conversation roles
+//!
+//! Group members are admins or members. Admins rename the group, add and
+//! remove members and promote or demote them; members can only leave. The
+//! creator owns the group: they stay an admin, can't be removed, and hand
+//! ownership to another member before leaving. The database enforces the
+//! same rules on role and ownership changes.
+
+use std::collections::HashMap;
+
+use crate::supabase::{ConversationRow, ParticipantWithProfile};
+use crate::{Error, Result};
+
+pub const ADMIN: &str = "admin";
+pub const MEMBER: &str = "member";
+
+/// Something a member does to a group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupAction<'a> {
+    /// Change the name or avatar
+    Edit,
+    AddMember,
+    RemoveMember(&'a str),
+    Promote(&'a str),
+    Demote(&'a str),
+    TransferOwnership(&'a str),
+    Leave,
+}
+
+/// Owner and member roles of a group conversation
+#[derive(Debug, Clone)]
+pub struct GroupRoles {
+    owner_id: Option<String>,
+    roles: HashMap<String, String>,
+}
+
+impl GroupRoles {
+    /// Roles of a conversation, which must be a group
+    pub fn new(
+        conversation: &ConversationRow,
+        participants: &[ParticipantWithProfile],
+    ) -> Result<Self> {
+        if conversation.conversation_type != "group" {
+            return Err(Error::Input("Not a group conversation".to_string()));
+        }
+        Ok(Self {
+            owner_id: conversation.created_by.clone(),
+            roles: participants
+                .iter()
+                .map(|p| (p.user_id.clone(), p.role.clone()))
+                .collect(),
+        })
+    }
+
+    pub fn is_owner(&self, user_id: &str) -> bool {
+        self.owner_id.as_deref() == Some(user_id)
+    }
+
+    pub fn is_admin(&self, user_id: &str) -> bool {
+        self.roles.get(user_id).map(String::as_str) == Some(ADMIN)
+    }
+
+    fn check_member(&self, user_id: &str) -> Result<()> {
+        if !self.roles.contains_key(user_id) {
+            return Err(Error::NotFound("Not a member of this group".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check that `user_id` may do `action`
+    pub fn check(&self, user_id: &str, action: GroupAction) -> Result<()> {
+        self.check_member(user_id)?;
+
+        match action {
+            GroupAction::Leave => {
+                if self.is_owner(user_id) && self.roles.len() > 1 {
+                    return Err(Error::Input(
+                        "Transfer ownership before leaving the group".to_string(),
+                    ));
+                }
+                return Ok(());
+            }
+            GroupAction::TransferOwnership(target) => {
+                if !self.is_owner(user_id) {
+                    return Err(Error::Auth(
+                        "Only the group owner can transfer ownership".to_string(),
+                    ));
+                }
+                self.check_member(target)?;
+                if target == user_id {
+                    return Err(Error::Input("You already own this group".to_string()));
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        if !self.is_admin(user_id) {
+            return Err(Error::Auth(
+                "Only group admins can change the group".to_string(),
+            ));
+        }
+
+        match action {
+            GroupAction::RemoveMember(target) => {
+                self.check_member(target)?;
+                if self.is_owner(target) {
+                    return Err(Error::Input("The group owner can't be removed".to_string()));
+                }
+            }
+            GroupAction::Promote(target) => self.check_member(target)?,
+            GroupAction::Demote(target) => {
+                self.check_member(target)?;
+                if self.is_owner(target) {
+                    return Err(Error::Input("The group owner stays an admin".to_string()));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(conversation_type: &str, created_by: Option<&str>) -> ConversationRow {
+        ConversationRow {
+            id: "c1".to_string(),
+            conversation_type: conversation_type.to_string(),
+            name: Some("Team".to_string()),
+            avatar_url: None,
+            created_by: created_by.map(str::to_string),
+            created_at: None,
+            updated_at: None,
+            meeting_id: None,
+            archived_at: None,
+            encrypted: false,
+        }
+    }
+
+    fn participant(user_id: &str, role: &str) -> ParticipantWithProfile {
+        ParticipantWithProfile {
+            user_id: user_id.to_string(),
+            display_name: user_id.to_string(),
+            avatar_url: None,
+            role: role.to_string(),
+            is_online: false,
+            status: "offline".to_string(),
+            status_message: None,
+            last_seen_at: None,
+        }
+    }
+
+    fn group() -> GroupRoles {
+        GroupRoles::new(
+            &conversation("group", Some("owner")),
+            &[
+                participant("owner", ADMIN),
+                participant("admin", ADMIN),
+                participant("member", MEMBER),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_groups() {
+        assert!(GroupRoles::new(&conversation("direct", Some("owner")), &[]).is_err());
+    }
+
+    #[test]
+    fn test_admin_actions() {
+        let group = group();
+        for action in [
+            GroupAction::Edit,
+            GroupAction::AddMember,
+            GroupAction::RemoveMember("member"),
+            GroupAction::Promote("member"),
+            GroupAction::Demote("admin"),
+        ] {
+            assert!(group.check("admin", action).is_ok(), "{:?}", action);
+            assert!(group.check("member", action).is_err(), "{:?}", action);
+        }
+        assert!(group.check("stranger", GroupAction::Edit).is_err());
+        assert!(group
+            .check("admin", GroupAction::Promote("stranger"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_owner_is_protected() {
+        let group = group();
+        assert!(group
+            .check("admin", GroupAction::RemoveMember("owner"))
+            .is_err());
+        assert!(group.check("admin", GroupAction::Demote("owner")).is_err());
+        assert!(group.check("owner", GroupAction::Demote("admin")).is_ok());
+    }
+
+    #[test]
+    fn test_transfer_ownership() {
+        let group = group();
+        assert!(group
+            .check("owner", GroupAction::TransferOwnership("member"))
+            .is_ok());
+        assert!(group
+            .check("owner", GroupAction::TransferOwnership("owner"))
+            .is_err());
+        assert!(group
+            .check("owner", GroupAction::TransferOwnership("stranger"))
+            .is_err());
+        assert!(group
+            .check("admin", GroupAction::TransferOwnership("member"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_leave() {
+        let group = group();
+        assert!(group.check("member", GroupAction::Leave).is_ok());
+        assert!(group.check("admin", GroupAction::Leave).is_ok());
+        assert!(group.check("owner", GroupAction::Leave).is_err());
+
+        let alone = GroupRoles::new(
+            &conversation("group", Some("owner")),
+            &[participant("owner", ADMIN)],
+        )
+        .unwrap();
+        assert!(alone.check("owner", GroupAction::Leave).is_ok());
+    }
+}
@@ -0,0 +1,177 @@
+//! ICE server configuration
+//!
+//! STUN alone fails behind symmetric NATs, so the ICE server list can be
+//! extended with TURN relays, either statically from the environment or with
+//! short-lived credentials issued by the `turn-credentials` edge function.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+const DEFAULT_STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+
+/// JSON array of ICE servers, e.g. `[{"urls":["turn:turn.example.com:3478"],"username":"u","credential":"p"}]`
+const ICE_SERVERS_ENV: &str = "VITE_ICE_SERVERS";
+/// Single static TURN server, used when no JSON list is configured
+const TURN_URL_ENV: &str = "VITE_TURN_URL";
+const TURN_USERNAME_ENV: &str = "VITE_TURN_USERNAME";
+const TURN_CREDENTIAL_ENV: &str = "VITE_TURN_CREDENTIAL";
+
+/// ICE server entry, in the same shape as the browser's `RTCIceServer`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IceServerConfig {
+    pub urls: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
+impl IceServerConfig {
+    pub fn stun(url: &str) -> Self {
+        Self {
+            urls: vec![url.to_string()],
+            username: None,
+            credential: None,
+        }
+    }
+
+    /// Whether this entry points to a TURN relay
+    pub fn is_turn(&self) -> bool {
+        self.urls
+            .iter()
+            .any(|u| u.starts_with("turn:") || u.starts_with("turns:"))
+    }
+}
+
+/// Short-lived TURN credentials returned by the edge function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnCredentials {
+    pub urls: Vec<String>,
+    pub username: String,
+    pub credential: String,
+    pub ttl_seconds: u64,
+}
+
+impl From<TurnCredentials> for IceServerConfig {
+    fn from(creds: TurnCredentials) -> Self {
+        Self {
+            urls: creds.urls,
+            username: Some(creds.username),
+            credential: Some(creds.credential),
+        }
+    }
+}
+
+/// Parse a JSON ICE server list
+pub fn parse_ice_servers(json: &str) -> Result<Vec<IceServerConfig>> {
+    let servers: Vec<IceServerConfig> =
+        serde_json::from_str(json).map_err(|e| Error::Config(format!("Invalid ICE servers: {}", e)))?;
+    validate_ice_servers(&servers)?;
+    Ok(servers)
+}
+
+/// Check URL schemes and that TURN entries carry credentials
+pub fn validate_ice_servers(servers: &[IceServerConfig]) -> Result<()> {
+    for server in servers {
+        if server.urls.is_empty() {
+            return Err(Error::Config("ICE server without URLs".to_string()));
+        }
+        if let Some(url) = server.urls.iter().find(|u| {
+            !["stun:", "stuns:", "turn:", "turns:"]
+                .iter()
+                .any(|scheme| u.starts_with(scheme))
+        }) {
+            return Err(Error::Config(format!("Unsupported ICE server URL: {}", url)));
+        }
+        if server.is_turn() && (server.username.is_none() || server.credential.is_none()) {
+            return Err(Error::Config(format!(
+                "TURN server {} requires username and credential",
+                server.urls[0]
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Load the ICE server list from the environment, falling back to the default STUN server
+pub fn ice_servers_from_env() -> Vec<IceServerConfig> {
+    if let Ok(json) = std::env::var(ICE_SERVERS_ENV) {
+        match parse_ice_servers(&json) {
+            Ok(servers) if !servers.is_empty() => return servers,
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Ignoring {}: {}", ICE_SERVERS_ENV, e),
+        }
+    }
+
+    let mut servers = vec![IceServerConfig::stun(DEFAULT_STUN_SERVER)];
+    if let Ok(url) = std::env::var(TURN_URL_ENV) {
+        servers.push(IceServerConfig {
+            urls: vec![url],
+            username: std::env::var(TURN_USERNAME_ENV).ok(),
+            credential: std::env::var(TURN_CREDENTIAL_ENV).ok(),
+        });
+    }
+    servers
+}
+
+/// Merge configured servers with fetched TURN credentials, dropping duplicate URLs
+pub fn merge_ice_servers(
+    configured: &[IceServerConfig],
+    turn: Option<&IceServerConfig>,
+) -> Vec<IceServerConfig> {
+    let mut servers: Vec<IceServerConfig> = configured.to_vec();
+    if let Some(turn) = turn {
+        // Fetched credentials replace static entries for the same relay
+        servers.retain(|s| !s.urls.iter().any(|u| turn.urls.contains(u)));
+        servers.push(turn.clone());
+    }
+    servers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ice_servers() {
+        let servers = parse_ice_servers(
+            r#"[{"urls":["stun:stun.example.com:3478"]},
+                {"urls":["turn:turn.example.com:3478"],"username":"u","credential":"p"}]"#,
+        )
+        .unwrap();
+        assert_eq!(servers.len(), 2);
+        assert!(!servers[0].is_turn());
+        assert!(servers[1].is_turn());
+    }
+
+    #[test]
+    fn test_turn_requires_credentials() {
+        assert!(parse_ice_servers(r#"[{"urls":["turn:turn.example.com"]}]"#).is_err());
+        assert!(parse_ice_servers(r#"[{"urls":["http://example.com"]}]"#).is_err());
+        assert!(parse_ice_servers(r#"[{"urls":[]}]"#).is_err());
+    }
+
+    #[test]
+    fn test_merge_replaces_same_relay() {
+        let configured = vec![
+            IceServerConfig::stun(DEFAULT_STUN_SERVER),
+            IceServerConfig {
+                urls: vec!["turn:turn.example.com:3478".to_string()],
+                username: Some("static".to_string()),
+                credential: Some("static".to_string()),
+            },
+        ];
+        let turn = IceServerConfig {
+            urls: vec!["turn:turn.example.com:3478".to_string()],
+            username: Some("1700000000:user".to_string()),
+            credential: Some("secret".to_string()),
+        };
+
+        let merged = merge_ice_servers(&configured, Some(&turn));
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1], turn);
+
+        assert_eq!(merge_ice_servers(&configured, None), configured);
+    }
+}
@@ -0,0 +1,192 @@
+//! Idle detection
+//!
+//! Reads how long the user has left keyboard and mouse alone, so presence
+//! can turn to `away` on its own after a threshold and back to `online` on
+//! the next input. The threshold applies process-wide and is kept across
+//! restarts; zero turns automatic away off.
+//!
+//! Idle time comes from CoreGraphics on macOS and `GetLastInputInfo` on
+//! Windows. Other platforms report none, leaving presence as the user set it.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// File in the app config directory holding the threshold
+pub const SETTINGS_FILE: &str = "idle_away";
+
+/// Minutes without input before presence turns to away, unless changed
+pub const DEFAULT_THRESHOLD_MINUTES: u32 = 5;
+
+/// Longest threshold that can be set
+pub const MAX_THRESHOLD_MINUTES: u32 = 240;
+
+/// How often idle time is checked, and so how soon activity is noticed
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+static THRESHOLD_MINUTES: AtomicU32 = AtomicU32::new(DEFAULT_THRESHOLD_MINUTES);
+
+/// Minutes without input before presence turns to away, zero when off
+pub fn threshold_minutes() -> u32 {
+    THRESHOLD_MINUTES.load(Ordering::Relaxed)
+}
+
+/// Time without input before presence turns to away, `None` when off
+pub fn threshold() -> Option<Duration> {
+    match threshold_minutes() {
+        0 => None,
+        minutes => Some(Duration::from_secs(u64::from(minutes) * 60)),
+    }
+}
+
+/// Set the threshold in minutes, zero to turn automatic away off
+pub fn set_threshold_minutes(minutes: u32) -> Result<()> {
+    validate_threshold(minutes)?;
+    THRESHOLD_MINUTES.store(minutes, Ordering::Relaxed);
+    Ok(())
+}
+
+fn validate_threshold(minutes: u32) -> Result<()> {
+    if minutes > MAX_THRESHOLD_MINUTES {
+        return Err(Error::Input(format!(
+            "Idle threshold can be at most {} minutes",
+            MAX_THRESHOLD_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// Whether input has been missing for at least `threshold`
+pub fn is_idle(idle_for: Duration, threshold: Option<Duration>) -> bool {
+    threshold.is_some_and(|threshold| idle_for >= threshold)
+}
+
+/// Time since the last keyboard or mouse input, `None` where it can't be read
+pub fn idle_time() -> Option<Duration> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::idle_time()
+    }
+    #[cfg(windows)]
+    {
+        windows_input::idle_time()
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        None
+    }
+}
+
+/// Read the threshold saved by `save`, the default when there is none
+pub fn load(path: &Path) -> u32 {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .filter(|minutes| validate_threshold(*minutes).is_ok())
+        .unwrap_or(DEFAULT_THRESHOLD_MINUTES)
+}
+
+/// Save the threshold; the default removes the file
+pub fn save(path: &Path, minutes: u32) -> Result<()> {
+    validate_threshold(minutes)?;
+    if minutes != DEFAULT_THRESHOLD_MINUTES {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, minutes.to_string())?;
+    } else if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::time::Duration;
+
+    /// `kCGEventSourceStateCombinedSessionState`
+    const COMBINED_SESSION_STATE: i32 = 0;
+    /// `kCGAnyInputEventType`
+    const ANY_INPUT_EVENT: u32 = u32::MAX;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+    }
+
+    pub fn idle_time() -> Option<Duration> {
+        // SAFETY: takes plain values and only reads the event source state
+        let seconds = unsafe {
+            CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT)
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+#[cfg(windows)]
+mod windows_input {
+    use std::time::Duration;
+
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    pub fn idle_time() -> Option<Duration> {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        // SAFETY: `info` is a properly sized LASTINPUTINFO the call fills in
+        if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+            return None;
+        }
+        // SAFETY: takes no arguments
+        let now = unsafe { GetTickCount() };
+        // Both are tick counts, which wrap after 49.7 days
+        let idle_ms = now.wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(u64::from(idle_ms)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_idle() {
+        let threshold = Some(Duration::from_secs(300));
+        assert!(!is_idle(Duration::from_secs(299), threshold));
+        assert!(is_idle(Duration::from_secs(300), threshold));
+        assert!(!is_idle(Duration::from_secs(3600), None));
+    }
+
+    #[test]
+    fn test_validate_threshold() {
+        assert!(validate_threshold(0).is_ok());
+        assert!(validate_threshold(MAX_THRESHOLD_MINUTES).is_ok());
+        assert!(validate_threshold(MAX_THRESHOLD_MINUTES + 1).is_err());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("squadx-idle-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SETTINGS_FILE);
+        assert_eq!(load(&path), DEFAULT_THRESHOLD_MINUTES);
+
+        save(&path, 15).unwrap();
+        assert_eq!(load(&path), 15);
+
+        save(&path, 0).unwrap();
+        assert_eq!(load(&path), 0);
+
+        assert!(save(&path, MAX_THRESHOLD_MINUTES + 1).is_err());
+        std::fs::write(&path, "forever").unwrap();
+        assert_eq!(load(&path), DEFAULT_THRESHOLD_MINUTES);
+
+        save(&path, DEFAULT_THRESHOLD_MINUTES).unwrap();
+        assert!(!path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
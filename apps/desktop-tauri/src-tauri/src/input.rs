@@ -1,6 +1,6 @@
 use enigo::{Enigo, Keyboard, Mouse, Settings};
 use serde::{Deserialize, Serialize};
-use xcap::Monitor;
+use xcap::{Monitor, Window};
 
 use crate::{Error, Result};
 
@@ -37,6 +37,76 @@ pub struct Modifiers {
     pub meta: bool,
 }
 
+/// Remote control watchdog configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    /// Revoke control after this many minutes without viewer input
+    pub idle_timeout_minutes: u32,
+    /// Case-insensitive fragments of window titles that must never be controlled remotely
+    pub protected_title_patterns: Vec<String>,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_timeout_minutes: 5,
+            protected_title_patterns: [
+                "1password",
+                "bitwarden",
+                "lastpass",
+                "keepass",
+                "dashlane",
+                "keychain access",
+                "internet banking",
+                "online banking",
+                "banco",
+            ]
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
+        }
+    }
+}
+
+/// Why the watchdog revoked control
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum AutoRevokeReason {
+    Idle { idle_minutes: u32 },
+    ProtectedApp { window_title: String, pattern: String },
+    Expired { duration_secs: u64 },
+}
+
+/// Return the protected pattern matched by a window title, if any
+pub fn match_protected_title<'a>(title: &str, patterns: &'a [String]) -> Option<&'a str> {
+    let title = title.to_lowercase();
+    patterns
+        .iter()
+        .filter(|p| !p.trim().is_empty())
+        .find(|p| title.contains(&p.to_lowercase()))
+        .map(|p| p.as_str())
+}
+
+/// Title of the window that currently has focus
+pub fn focused_window_title() -> Result<Option<String>> {
+    let windows = Window::all().map_err(|e| Error::Input(e.to_string()))?;
+    Ok(windows
+        .into_iter()
+        .find(|w| w.is_focused().unwrap_or(false))
+        .and_then(|w| w.title().ok()))
+}
+
+/// Application that owns the focused window
+pub fn focused_app_name() -> Result<Option<String>> {
+    let windows = Window::all().map_err(|e| Error::Input(e.to_string()))?;
+    Ok(windows
+        .into_iter()
+        .find(|w| w.is_focused().unwrap_or(false))
+        .and_then(|w| w.app_name().ok()))
+}
+
 fn get_screen_dimensions() -> Result<(i32, i32)> {
     let monitors = Monitor::all().map_err(|e| Error::Input(e.to_string()))?;
     let primary = monitors.first().ok_or_else(|| Error::Input("No monitor found".to_string()))?;
@@ -200,3 +270,24 @@ pub fn inject_event(event: InputEvent) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_protected_title() {
+        let patterns = WatchdogConfig::default().protected_title_patterns;
+        assert_eq!(
+            match_protected_title("Vault - 1Password", &patterns),
+            Some("1password")
+        );
+        assert_eq!(match_protected_title("Visual Studio Code", &patterns), None);
+    }
+
+    #[test]
+    fn test_match_protected_title_ignores_empty_patterns() {
+        let patterns = vec!["".to_string(), "  ".to_string()];
+        assert_eq!(match_protected_title("anything", &patterns), None);
+    }
+}
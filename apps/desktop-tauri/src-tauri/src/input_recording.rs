@@ -0,0 +1,156 @@
+//! Input event recording for QA sessions
+//!
+//! Captures the stream of injected input events with their timing so a
+//! remote debugging session can be saved to a local file and replayed later.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::input::InputEvent;
+use crate::{Error, Result};
+
+const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// An injected event with its offset from the start of the recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub offset_ms: u64,
+    pub event: InputEvent,
+}
+
+/// A recorded input stream as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub version: u32,
+    pub session_id: Option<String>,
+    pub recorded_at: String,
+    pub events: Vec<RecordedEvent>,
+}
+
+impl InputRecording {
+    /// Total duration of the recording in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map(|e| e.offset_ms).unwrap_or(0)
+    }
+
+    /// Write the recording to a JSON file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Read a recording from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let recording: Self = serde_json::from_str(&json)?;
+
+        if recording.version > RECORDING_FORMAT_VERSION {
+            return Err(Error::Parse(format!(
+                "Unsupported recording version: {}",
+                recording.version
+            )));
+        }
+
+        Ok(recording)
+    }
+}
+
+/// Recorder accumulating events while a recording is active
+#[derive(Debug)]
+pub struct InputRecorder {
+    started_at: Instant,
+    session_id: Option<String>,
+    recorded_at: String,
+    events: Vec<RecordedEvent>,
+}
+
+impl InputRecorder {
+    pub fn new(session_id: Option<String>) -> Self {
+        Self {
+            started_at: Instant::now(),
+            session_id,
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Record an event at the current offset
+    pub fn record(&mut self, event: InputEvent) {
+        let offset_ms = self.started_at.elapsed().as_millis() as u64;
+        self.events.push(RecordedEvent { offset_ms, event });
+    }
+
+    pub fn finish(self) -> InputRecording {
+        InputRecording {
+            version: RECORDING_FORMAT_VERSION,
+            session_id: self.session_id,
+            recorded_at: self.recorded_at,
+            events: self.events,
+        }
+    }
+}
+
+/// Delays (in ms) to wait before each event when replaying at the given speed
+pub fn replay_delays(events: &[RecordedEvent], speed: f64) -> Vec<u64> {
+    let speed = if speed > 0.0 { speed } else { 1.0 };
+    let mut previous = 0;
+
+    events
+        .iter()
+        .map(|e| {
+            let gap = e.offset_ms.saturating_sub(previous);
+            previous = e.offset_ms;
+            (gap as f64 / speed) as u64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_at(offset_ms: u64) -> RecordedEvent {
+        RecordedEvent {
+            offset_ms,
+            event: InputEvent::MouseMove { x: 0.5, y: 0.5 },
+        }
+    }
+
+    #[test]
+    fn test_replay_delays() {
+        let events = vec![event_at(0), event_at(100), event_at(250)];
+        assert_eq!(replay_delays(&events, 1.0), vec![0, 100, 150]);
+        assert_eq!(replay_delays(&events, 2.0), vec![0, 50, 75]);
+    }
+
+    #[test]
+    fn test_replay_delays_invalid_speed() {
+        let events = vec![event_at(0), event_at(100)];
+        assert_eq!(replay_delays(&events, 0.0), vec![0, 100]);
+    }
+
+    #[test]
+    fn test_recording_roundtrip() {
+        let mut recorder = InputRecorder::new(Some("session-1".to_string()));
+        recorder.record(InputEvent::MouseMove { x: 0.1, y: 0.2 });
+        recorder.record(InputEvent::KeyPress {
+            key: "a".to_string(),
+            modifiers: Default::default(),
+        });
+        let recording = recorder.finish();
+
+        let path = std::env::temp_dir().join(format!("squadx-recording-{}.json", uuid::Uuid::new_v4()));
+        recording.save(&path).unwrap();
+        let loaded = InputRecording::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.events.len(), 2);
+        assert_eq!(loaded.session_id.as_deref(), Some("session-1"));
+        assert!(matches!(loaded.events[1].event, InputEvent::KeyPress { .. }));
+    }
+}
@@ -0,0 +1,168 @@
+//! Join code attempt throttling
+//!
+//! Join codes are short enough to guess, so failed joins are counted per user
+//! and for the whole device. After a few failures every further attempt has
+//! to wait twice as long as the last, and too many lock joining out for a
+//! while. Supabase enforces the same limits per user and IP address.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Error, Result};
+
+/// Failures allowed before attempts are delayed
+const FREE_ATTEMPTS: u32 = 3;
+
+/// Failures after which joining is locked out
+const MAX_FAILURES: u32 = 10;
+
+/// Failures older than this are forgotten
+const FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// How long joining stays locked out
+const LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Key under which every user's failures on this device add up
+const DEVICE_KEY: &str = "";
+
+/// Throttle shared between join attempts
+pub type SharedJoinThrottle = Arc<Mutex<JoinThrottle>>;
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    last: Instant,
+}
+
+impl Failures {
+    /// Time left before another attempt is allowed, if any
+    fn retry_after(&self, now: Instant) -> Option<Duration> {
+        if now.saturating_duration_since(self.last) >= FAILURE_WINDOW {
+            return None;
+        }
+        let wait = if self.count >= MAX_FAILURES {
+            LOCKOUT
+        } else if self.count >= FREE_ATTEMPTS {
+            Duration::from_secs(1 << (self.count - FREE_ATTEMPTS))
+        } else {
+            return None;
+        };
+        (self.last + wait)
+            .checked_duration_since(now)
+            .filter(|left| !left.is_zero())
+    }
+}
+
+/// Failed join attempts per user and for the device
+#[derive(Debug, Default)]
+pub struct JoinThrottle {
+    failures: HashMap<String, Failures>,
+}
+
+impl JoinThrottle {
+    /// Time left before `user_id` may try another code, if any
+    pub fn retry_after(&self, user_id: &str, now: Instant) -> Option<Duration> {
+        [user_id, DEVICE_KEY]
+            .iter()
+            .filter_map(|key| self.failures.get(*key))
+            .filter_map(|failures| failures.retry_after(now))
+            .max()
+    }
+
+    /// Refuse an attempt made too soon after failed ones
+    pub fn check(&self, user_id: &str, now: Instant) -> Result<()> {
+        match self.retry_after(user_id, now) {
+            Some(left) => Err(Error::TooManyAttempts {
+                retry_after_secs: left.as_secs_f64().ceil() as u64,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Count a join with a code that matched no session
+    pub fn record_failure(&mut self, user_id: &str, now: Instant) {
+        for key in [user_id, DEVICE_KEY] {
+            let failures = self.failures.entry(key.to_string()).or_insert(Failures {
+                count: 0,
+                last: now,
+            });
+            if now.saturating_duration_since(failures.last) >= FAILURE_WINDOW {
+                failures.count = 0;
+            }
+            failures.count += 1;
+            failures.last = now;
+        }
+    }
+
+    /// Forget a user's failures once they joined; the device's still count
+    pub fn record_success(&mut self, user_id: &str) {
+        self.failures.remove(user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_after_free_attempts() {
+        let mut throttle = JoinThrottle::default();
+        let start = Instant::now();
+
+        for _ in 0..FREE_ATTEMPTS - 1 {
+            throttle.record_failure("user", start);
+        }
+        assert!(throttle.check("user", start).is_ok());
+
+        throttle.record_failure("user", start);
+        assert_eq!(
+            throttle.retry_after("user", start),
+            Some(Duration::from_secs(1))
+        );
+        throttle.record_failure("user", start);
+        assert_eq!(
+            throttle.retry_after("user", start),
+            Some(Duration::from_secs(2))
+        );
+        assert!(throttle
+            .check("user", start + Duration::from_secs(2))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_lockout_and_window() {
+        let mut throttle = JoinThrottle::default();
+        let start = Instant::now();
+        for _ in 0..MAX_FAILURES {
+            throttle.record_failure("user", start);
+        }
+
+        match throttle.check("user", start + Duration::from_secs(60)) {
+            Err(Error::TooManyAttempts { retry_after_secs }) => {
+                assert_eq!(retry_after_secs, LOCKOUT.as_secs() - 60)
+            }
+            other => panic!("expected a lockout, got {:?}", other),
+        }
+        assert!(throttle.check("user", start + LOCKOUT).is_ok());
+
+        // Failures past the window start over
+        throttle.record_failure("user", start + FAILURE_WINDOW);
+        assert!(throttle.check("user", start + FAILURE_WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_device_failures_span_users() {
+        let mut throttle = JoinThrottle::default();
+        let start = Instant::now();
+        for i in 0..FREE_ATTEMPTS {
+            throttle.record_failure(&format!("user-{}", i), start);
+        }
+
+        // Each user failed once, but the device as a whole is throttled
+        assert!(throttle.retry_after("someone-else", start).is_some());
+
+        throttle.record_success("user-0");
+        assert!(throttle.retry_after("user-0", start).is_some());
+    }
+}
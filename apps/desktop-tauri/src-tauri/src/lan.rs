@@ -0,0 +1,605 @@
+//! LAN direct-connect signaling
+//!
+//! When Supabase is out of reach, two machines on the same network can still
+//! hold a session: the host advertises it over mDNS and serves signaling on a
+//! WebSocket of its own, relaying every frame to the other connected viewers
+//! the way the Realtime channel would. Viewers find the host by join code.
+//!
+//! Frames are the same sealed envelopes as Realtime broadcasts, so end-to-end
+//! encryption and reliable delivery work unchanged. Anyone on the network can
+//! connect to the host's socket, so LAN sessions should be encrypted; the
+//! advertisement only carries a short tag of the join code, enough to tell
+//! sessions apart without giving the code away.
+//!
+//! The channel lives on the machine that started the session: handing the
+//! host role over changes who decides, not who relays.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::{SinkExt, StreamExt};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{accept_async, connect_async};
+
+use crate::e2e::SessionCipher;
+use crate::realtime::SignalingMessage;
+use crate::signaling_delivery::{Delivery, SignalingEnvelope, RESEND_INTERVAL};
+use crate::{Error, Result};
+
+/// mDNS service type hosts advertise their sessions under
+const SERVICE_TYPE: &str = "_squadx-live._tcp.local.";
+
+/// How long a viewer browses the network for a session
+pub const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Bytes of the join code hash advertised to match sessions
+const CODE_TAG_LENGTH: usize = 2;
+
+/// Host of a LAN session, as found on the network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanHost {
+    pub session_id: String,
+    pub address: SocketAddr,
+}
+
+/// Short tag of a join code, advertised so viewers can pick the right host
+fn code_tag(join_code: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(b"squadx-live/lan/")
+        .chain_update(join_code.trim().to_uppercase().as_bytes())
+        .finalize();
+    digest[..CODE_TAG_LENGTH]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn mdns_error(e: mdns_sd::Error) -> Error {
+    Error::Network(format!("mDNS failed: {}", e))
+}
+
+/// Browse the network for the host of the session with a join code
+///
+/// Returns `None` when no host answers within `timeout`.
+pub async fn discover(join_code: &str, timeout: Duration) -> Result<Option<LanHost>> {
+    let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+    let events = daemon.browse(SERVICE_TYPE).map_err(mdns_error)?;
+    let tag = code_tag(join_code);
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    let mut found = None;
+    while found.is_none() {
+        let event = match tokio::time::timeout_at(deadline, events.recv_async()).await {
+            Ok(Ok(event)) => event,
+            _ => break,
+        };
+        if let ServiceEvent::ServiceResolved(info) = event {
+            found = lan_host(&info, &tag);
+        }
+    }
+
+    if let Err(e) = daemon.shutdown() {
+        tracing::debug!("Failed to stop mDNS browsing: {}", e);
+    }
+    Ok(found)
+}
+
+fn lan_host(info: &ServiceInfo, tag: &str) -> Option<LanHost> {
+    if info.get_property_val_str("code")? != tag {
+        return None;
+    }
+    let session_id = info.get_property_val_str("session_id")?.to_string();
+    // Link-local IPv6 addresses need a scope a WebSocket URL can't carry
+    let addresses = info.get_addresses();
+    let ip = addresses
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| addresses.iter().next())?;
+    Some(LanHost {
+        session_id,
+        address: SocketAddr::new(*ip, info.get_port()),
+    })
+}
+
+/// Advertise a hosted session, returning the daemon and service name to withdraw it
+fn advertise(session_id: &str, join_code: &str, port: u16) -> Result<(ServiceDaemon, String)> {
+    let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+    let instance = format!(
+        "squadx-{}",
+        session_id.split('-').next().unwrap_or(session_id)
+    );
+    let host_name = format!("{}.local.", instance);
+    let tag = code_tag(join_code);
+    let properties = [("session_id", session_id), ("code", tag.as_str())];
+
+    let info = ServiceInfo::new(
+        SERVICE_TYPE,
+        &instance,
+        &host_name,
+        "",
+        port,
+        &properties[..],
+    )
+    .map_err(mdns_error)?
+    .enable_addr_auto();
+    let fullname = info.get_fullname().to_string();
+    daemon.register(info).map_err(mdns_error)?;
+
+    Ok((daemon, fullname))
+}
+
+/// Sealing and reliable delivery shared by both ends of a LAN connection
+#[derive(Clone)]
+struct Link {
+    local_user_id: String,
+    cipher: Arc<Mutex<SessionCipher>>,
+    delivery: Arc<Mutex<Delivery>>,
+    incoming: broadcast::Sender<SignalingMessage>,
+}
+
+impl Link {
+    fn new(user_id: &str, cipher: SessionCipher) -> Self {
+        Self {
+            local_user_id: user_id.to_string(),
+            cipher: Arc::new(Mutex::new(cipher)),
+            delivery: Arc::new(Mutex::new(Delivery::default())),
+            incoming: broadcast::channel(100).0,
+        }
+    }
+
+    fn seal(&self, envelope: SignalingEnvelope) -> Option<String> {
+        let sealed = self
+            .cipher
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .seal(envelope)
+            .and_then(|payload| serde_json::to_string(&payload).map_err(Error::from));
+        match sealed {
+            Ok(frame) => Some(frame),
+            Err(e) => {
+                tracing::error!("Failed to seal signaling message: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Frame for an outgoing message, sequenced when it needs an ack
+    fn prepare(&self, message: SignalingMessage) -> Option<String> {
+        let envelope = self
+            .delivery
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .prepare(message, Instant::now());
+        self.seal(envelope)
+    }
+
+    /// Frames of messages whose ack is overdue
+    fn due(&self) -> Vec<String> {
+        let due = self
+            .delivery
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .due(Instant::now());
+        due.into_iter()
+            .filter_map(|envelope| self.seal(envelope))
+            .collect()
+    }
+
+    /// Open an incoming frame, returning its message and the ack frame to send
+    fn receive(&self, frame: &str) -> (Option<SignalingMessage>, Option<String>) {
+        let opened = serde_json::from_str::<serde_json::Value>(frame)
+            .map_err(Error::from)
+            .and_then(|payload| {
+                self.cipher
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .open(&payload)
+            });
+        let envelope = match opened {
+            Ok(Some(envelope)) => envelope,
+            Ok(None) => return (None, None),
+            Err(e) => {
+                tracing::warn!("Dropped signaling message: {}", e);
+                return (None, None);
+            }
+        };
+
+        let received = self
+            .delivery
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .receive(envelope, &self.local_user_id);
+        let ack = received
+            .ack
+            .and_then(|ack| self.seal(SignalingEnvelope::unsequenced(ack)));
+        (received.message, ack)
+    }
+
+    fn deliver(&self, message: SignalingMessage) {
+        let _ = self.incoming.send(message);
+    }
+}
+
+/// Viewers connected to the host, by connection
+#[derive(Clone, Default)]
+struct Hub {
+    peers: Arc<Mutex<HashMap<u64, mpsc::UnboundedSender<Message>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Hub {
+    fn add(&self, tx: mpsc::UnboundedSender<Message>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.peers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, tx);
+        id
+    }
+
+    /// Drop a connection, returning whether it was still open
+    fn remove(&self, id: u64) -> bool {
+        self.peers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id)
+            .is_some()
+    }
+
+    /// Send a frame to every connection but the one it came from
+    fn relay(&self, frame: &str, from: Option<u64>) {
+        let peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        for (id, tx) in peers.iter() {
+            if Some(*id) != from {
+                let _ = tx.send(Message::Text(frame.to_string()));
+            }
+        }
+    }
+
+    fn close_all(&self) {
+        let mut peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        for tx in peers.values() {
+            let _ = tx.send(Message::Close(None));
+        }
+        peers.clear();
+    }
+}
+
+/// Signaling over the local network, as host or viewer
+pub struct LanSignaling {
+    link: Link,
+    /// Connected viewers, when hosting
+    hub: Option<Hub>,
+    /// Frames to the host, when viewing
+    host_tx: Option<mpsc::UnboundedSender<Message>>,
+    /// mDNS advertisement of the hosted session
+    advertisement: Option<(ServiceDaemon, String)>,
+    /// Tasks stopped on leave; socket writers finish on their own
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for LanSignaling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LanSignaling")
+            .field("is_host", &self.hub.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl LanSignaling {
+    /// Serve signaling for a session and advertise it on the network
+    pub async fn host(
+        session_id: &str,
+        join_code: &str,
+        user_id: &str,
+        cipher: SessionCipher,
+    ) -> Result<(
+        Self,
+        broadcast::Receiver<SignalingMessage>,
+        mpsc::Sender<SignalingMessage>,
+    )> {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).await?;
+        let port = listener.local_addr()?.port();
+        let advertisement = advertise(session_id, join_code, port)?;
+
+        let link = Link::new(user_id, cipher);
+        let hub = Hub::default();
+        let signaling_rx = link.incoming.subscribe();
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<SignalingMessage>(100);
+
+        let accept_link = link.clone();
+        let accept_hub = hub.clone();
+        let accept_task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, address)) => {
+                        tracing::info!("LAN viewer connected from {}", address);
+                        tokio::spawn(serve_viewer(
+                            stream,
+                            accept_link.clone(),
+                            accept_hub.clone(),
+                        ));
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to accept LAN connection: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        let send_hub = hub.clone();
+        let send_task = tokio::spawn(run_outgoing(
+            link.clone(),
+            true,
+            outgoing_rx,
+            move |frame| {
+                send_hub.relay(&frame, None);
+                true
+            },
+        ));
+
+        tracing::info!("Hosting LAN session {} on port {}", session_id, port);
+        let lan = Self {
+            link,
+            hub: Some(hub),
+            host_tx: None,
+            advertisement: Some(advertisement),
+            tasks: vec![accept_task, send_task],
+        };
+        Ok((lan, signaling_rx, outgoing_tx))
+    }
+
+    /// Connect to the host of a LAN session
+    pub async fn connect(
+        host: &LanHost,
+        user_id: &str,
+        cipher: SessionCipher,
+    ) -> Result<(
+        Self,
+        broadcast::Receiver<SignalingMessage>,
+        mpsc::Sender<SignalingMessage>,
+    )> {
+        let url = format!("ws://{}", host.address);
+        tracing::info!("Connecting to LAN host: {}", url);
+        let (ws_stream, _) = connect_async(&url)
+            .await
+            .map_err(|e| Error::Network(format!("LAN connection failed: {}", e)))?;
+        let (write, mut read) = ws_stream.split();
+
+        let link = Link::new(user_id, cipher);
+        let signaling_rx = link.incoming.subscribe();
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<SignalingMessage>(100);
+        let (host_tx, host_rx) = mpsc::unbounded_channel::<Message>();
+
+        tokio::spawn(write_frames(write, host_rx));
+
+        // Spawn task to handle incoming frames, acking sequenced ones
+        let read_link = link.clone();
+        let ack_tx = host_tx.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = read.next().await {
+                match msg {
+                    Ok(Message::Text(frame)) => {
+                        let (message, ack) = read_link.receive(&frame);
+                        if let Some(ack) = ack {
+                            let _ = ack_tx.send(Message::Text(ack));
+                        }
+                        if let Some(message) = message {
+                            read_link.deliver(message);
+                        }
+                    }
+                    Ok(Message::Close(_)) => {
+                        tracing::info!("LAN host closed the connection");
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::error!("LAN connection error: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        let send_tx = host_tx.clone();
+        let send_task = tokio::spawn(run_outgoing(
+            link.clone(),
+            false,
+            outgoing_rx,
+            move |frame| send_tx.send(Message::Text(frame)).is_ok(),
+        ));
+
+        let lan = Self {
+            link,
+            hub: None,
+            host_tx: Some(host_tx),
+            advertisement: None,
+            tasks: vec![send_task],
+        };
+        Ok((lan, signaling_rx, outgoing_tx))
+    }
+
+    /// Tell peers we left, then close the connections and withdraw the advertisement
+    pub fn leave(self, user_id: &str) {
+        let left = SignalingEnvelope::unsequenced(SignalingMessage::UserLeft {
+            user_id: user_id.to_string(),
+        });
+        let frame = self.link.seal(left);
+
+        if let Some(hub) = &self.hub {
+            if let Some(frame) = &frame {
+                hub.relay(frame, None);
+            }
+            hub.close_all();
+        }
+        if let Some(tx) = &self.host_tx {
+            if let Some(frame) = frame {
+                let _ = tx.send(Message::Text(frame));
+            }
+            let _ = tx.send(Message::Close(None));
+        }
+
+        if let Some((daemon, fullname)) = self.advertisement {
+            if let Err(e) = daemon.unregister(&fullname) {
+                tracing::debug!("Failed to withdraw LAN advertisement: {}", e);
+            }
+            if let Err(e) = daemon.shutdown() {
+                tracing::debug!("Failed to stop mDNS daemon: {}", e);
+            }
+        }
+        for task in self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Relay a viewer's frames to everyone else and handle those meant for the host
+async fn serve_viewer(stream: TcpStream, link: Link, hub: Hub) {
+    let ws_stream = match accept_async(stream).await {
+        Ok(ws_stream) => ws_stream,
+        Err(e) => {
+            tracing::warn!("LAN WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+    let (write, mut read) = ws_stream.split();
+    let (tx, rx) = mpsc::unbounded_channel::<Message>();
+    let connection_id = hub.add(tx);
+    tokio::spawn(write_frames(write, rx));
+
+    // Viewer behind the connection, as announced in its join
+    let mut viewer_id = None;
+    while let Some(msg) = read.next().await {
+        match msg {
+            Ok(Message::Text(frame)) => {
+                hub.relay(&frame, Some(connection_id));
+                let (message, ack) = link.receive(&frame);
+                if let Some(ack) = ack {
+                    hub.relay(&ack, None);
+                }
+                match &message {
+                    Some(SignalingMessage::UserJoined {
+                        user_id,
+                        to_user_id: None,
+                        ..
+                    }) => viewer_id = Some(user_id.clone()),
+                    Some(SignalingMessage::UserLeft { .. }) => viewer_id = None,
+                    _ => {}
+                }
+                if let Some(message) = message {
+                    link.deliver(message);
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Err(e) => {
+                tracing::warn!("LAN viewer connection error: {}", e);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    // A viewer that dropped off without saying goodbye has left all the same,
+    // unless the host is the one who closed the session
+    if !hub.remove(connection_id) {
+        return;
+    }
+    if let Some(user_id) = viewer_id {
+        let left = SignalingMessage::UserLeft { user_id };
+        if let Some(frame) = link.seal(SignalingEnvelope::unsequenced(left.clone())) {
+            hub.relay(&frame, None);
+        }
+        link.deliver(left);
+    }
+}
+
+/// Write queued frames to a socket until the queue closes or a close frame goes out
+async fn write_frames<S>(mut write: S, mut rx: mpsc::UnboundedReceiver<Message>)
+where
+    S: futures_util::Sink<Message> + Unpin,
+{
+    while let Some(msg) = rx.recv().await {
+        let close = matches!(msg, Message::Close(_));
+        if write.send(msg).await.is_err() || close {
+            break;
+        }
+    }
+}
+
+/// Announce our join, then send outgoing messages and retransmissions
+async fn run_outgoing<F>(
+    link: Link,
+    is_host: bool,
+    mut outgoing_rx: mpsc::Receiver<SignalingMessage>,
+    send: F,
+) where
+    F: Fn(String) -> bool,
+{
+    let joined = SignalingMessage::user_joined(&link.local_user_id, is_host, None);
+    if let Some(frame) = link.seal(SignalingEnvelope::unsequenced(joined)) {
+        send(frame);
+    }
+
+    let mut resend_interval = tokio::time::interval(RESEND_INTERVAL);
+    loop {
+        tokio::select! {
+            Some(message) = outgoing_rx.recv() => {
+                if let Some(frame) = link.prepare(message) {
+                    if !send(frame) {
+                        break;
+                    }
+                }
+            }
+            _ = resend_interval.tick() => {
+                // Retransmit messages the recipient hasn't acked yet
+                for frame in link.due() {
+                    if !send(frame) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_tag() {
+        let tag = code_tag("ABC234");
+        assert_eq!(tag.len(), CODE_TAG_LENGTH * 2);
+        // Codes typed in lowercase or with stray spaces match the host's
+        assert_eq!(code_tag(" abc234 "), tag);
+        assert_ne!(code_tag("ABC235"), tag);
+    }
+
+    #[test]
+    fn test_hub_relays_to_other_connections() {
+        let hub = Hub::default();
+        let (tx_a, mut rx_a) = mpsc::unbounded_channel();
+        let (tx_b, mut rx_b) = mpsc::unbounded_channel();
+        let a = hub.add(tx_a);
+        hub.add(tx_b);
+
+        hub.relay("frame", Some(a));
+        assert!(rx_a.try_recv().is_err());
+        assert_eq!(rx_b.try_recv().unwrap(), Message::Text("frame".to_string()));
+
+        // Connections the host closed no longer count as dropped
+        hub.close_all();
+        assert_eq!(rx_a.try_recv().unwrap(), Message::Close(None));
+        assert!(!hub.remove(a));
+    }
+}
@@ -1,36 +1,171 @@
 use tauri::Manager;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tracing_subscriber::{filter, layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
+mod annotations;
+mod announcements;
+mod attachments;
+mod audio;
+mod automation;
+mod avatars;
+mod badge;
+mod blocking;
 mod cache;
+mod calendar_sync;
+mod camera;
 mod capture;
+mod change_feed;
+mod chat_crypto;
 mod chat_realtime;
 mod commands;
+mod connection_quality;
+mod conversation_channels;
+mod deep_link;
+mod diagnostics;
+mod e2e;
+mod emoji;
 mod error;
+mod file_transfer;
+mod group_roles;
+mod ice;
+mod idle;
 mod input;
+mod input_recording;
+mod join_throttle;
+mod lan;
+mod link_preview;
+mod logging;
+mod low_bandwidth;
+mod notifications;
+mod oauth_loopback;
+mod pagination;
+mod peer;
+mod perf;
+mod preflight;
+mod presence;
 mod realtime;
+mod remote_wipe;
+mod request_policy;
+mod resources;
 mod secure_storage;
+mod signaling_delivery;
+mod signaling_protocol;
 mod state;
 mod supabase;
+mod token_crypto;
 mod utils;
+mod voice_message;
 
 pub use error::{Error, Result};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize tracing
+    // Initialize tracing; command timing is collected regardless of RUST_LOG,
+    // and the log filter can be changed at runtime
+    let perf_state = perf::PerfState::default();
+    let (log_filter_layer, log_filter) = logging::LogFilter::new();
+    let recent_logs = logging::RecentLogs::default();
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(tracing_subscriber::fmt::layer().with_filter(log_filter_layer))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(recent_logs.clone())
+                .with_filter(tracing_subscriber::EnvFilter::new(
+                    logging::RECENT_LOG_DIRECTIVES,
+                )),
+        )
+        .with(
+            perf::PerfLayer::new(perf_state.clone())
+                .with_filter(filter::filter_fn(perf::is_perf_metadata)),
+        )
         .init();
 
     tracing::info!("Starting SquadX Live Desktop...");
 
     tauri::Builder::default()
+        // Links opened while running start a second instance on Windows and
+        // Linux; it hands them over here and exits
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .manage(state::AppState::default())
+        .manage(perf_state)
+        .manage(log_filter)
+        .manage(recent_logs)
+        .manage(commands::input::InputRecordingState::default())
+        .manage(commands::input::WatchdogState::default())
         .manage(commands::signaling::SignalingState::default())
+        .manage(commands::stream::StreamState::default())
+        .manage(commands::file_transfer::FileTransferState::default())
         .manage(commands::chat::ChatState::default())
+        .manage(commands::meeting_agenda::AgendaState::default())
+        .manage(commands::announcements::AnnouncementState::default())
+        .manage(commands::annotations::AnnotationState::default())
+        .manage(commands::badge::BadgeState::default())
+        .manage(commands::automation::AutomationState::default())
+        .manage(commands::voice_message::VoiceMessageState::default())
+        .manage(commands::google_sync::GoogleSyncState::default())
         .setup(|app| {
+            let filter_path = app.path().app_config_dir()?.join(logging::FILTER_FILE);
+            app.state::<logging::LogFilter>().restore(&filter_path);
+            let low_bandwidth_path = app
+                .path()
+                .app_config_dir()?
+                .join(low_bandwidth::SETTINGS_FILE);
+            low_bandwidth::set_enabled(low_bandwidth::load(&low_bandwidth_path));
+            let idle_path = app.path().app_config_dir()?.join(idle::SETTINGS_FILE);
+            idle::set_threshold_minutes(idle::load(&idle_path))?;
+            if let Err(e) = commands::encryption::load_device_key(app.handle()) {
+                tracing::warn!("Chat encryption unavailable: {}", e);
+            }
+            app.manage(commands::notifications::NotificationState::load(
+                app.handle(),
+            )?);
+            let avatar_dir = app.path().app_cache_dir()?.join("avatars");
+            app.manage(avatars::AvatarCache::new(avatar_dir));
+            tauri::async_runtime::spawn(commands::stream::watch_power(app.handle().clone()));
+            tauri::async_runtime::spawn(commands::session::watch_session_expiry(
+                app.handle().clone(),
+            ));
+            tauri::async_runtime::spawn(commands::announcements::watch_announcements(
+                app.handle().clone(),
+            ));
+            tauri::async_runtime::spawn(commands::remote_wipe::watch_remote_wipe(
+                app.handle().clone(),
+            ));
+            tauri::async_runtime::spawn(commands::badge::watch_badge(app.handle().clone()));
+            tauri::async_runtime::spawn(commands::idle::watch_idle(app.handle().clone()));
+            tauri::async_runtime::spawn(commands::automation::restore(app.handle().clone()));
+            tauri::async_runtime::spawn(commands::notifications::watch_meeting_reminders(
+                app.handle().clone(),
+            ));
+            tauri::async_runtime::spawn(commands::google_sync::watch_google_sync(
+                app.handle().clone(),
+            ));
+
+            // squadxlive:// invite links, at launch and while running
+            #[cfg(any(windows, target_os = "linux"))]
+            app.deep_link().register_all()?;
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                let urls = event.urls().iter().map(|url| url.to_string()).collect();
+                tauri::async_runtime::spawn(commands::deep_link::open_urls(handle.clone(), urls));
+            });
+            if let Some(urls) = app.deep_link().get_current()? {
+                let urls = urls.iter().map(|url| url.to_string()).collect();
+                tauri::async_runtime::spawn(commands::deep_link::open_urls(
+                    app.handle().clone(),
+                    urls,
+                ));
+            }
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -43,10 +178,18 @@ pub fn run() {
             commands::capture::get_sources,
             commands::capture::start_capture,
             commands::capture::stop_capture,
+            commands::capture::get_running_apps,
+            commands::capture::get_app_lock,
+            commands::capture::set_app_lock,
             // Input commands
             commands::input::inject_mouse_event,
             commands::input::inject_keyboard_event,
             commands::input::set_input_enabled,
+            commands::input::get_watchdog_config,
+            commands::input::set_watchdog_config,
+            commands::input::start_input_recording,
+            commands::input::stop_input_recording,
+            commands::input::replay_input_recording,
             // Auth commands
             commands::auth::login,
             commands::auth::signup,
@@ -60,6 +203,22 @@ pub fn run() {
             commands::session::join_session,
             commands::session::end_session,
             commands::session::get_session_status,
+            commands::session::get_join_requests,
+            commands::session::approve_join,
+            commands::session::deny_join,
+            commands::session::mute_chat,
+            commands::session::kick_participant,
+            commands::session::ban_participant,
+            commands::session::set_cohost,
+            commands::session::transfer_host,
+            commands::session::lock_session,
+            commands::session::unlock_session,
+            commands::session::cleanup_stale_sessions,
+            commands::session::get_resumable_session,
+            commands::session::resume_session,
+            // Invite link commands
+            commands::deep_link::create_session_link,
+            commands::deep_link::create_meeting_link,
             // Signaling commands
             commands::signaling::connect_signaling,
             commands::signaling::disconnect_signaling,
@@ -69,38 +228,175 @@ pub fn run() {
             commands::signaling::request_control,
             commands::signaling::grant_control,
             commands::signaling::revoke_control,
+            commands::signaling::list_control_requests,
+            commands::signaling::set_control_request_timeout,
             commands::signaling::get_signaling_status,
+            commands::signaling::get_session_participants,
             commands::signaling::send_chat_message,
+            commands::signaling::get_session_chat_history,
+            commands::signaling::send_cursor_position,
+            // Preflight commands
+            commands::preflight::run_preflight,
+            // Native stream commands
+            commands::stream::start_stream,
+            commands::stream::stop_stream,
+            commands::stream::switch_capture_source,
+            commands::stream::get_connection_stats,
+            commands::stream::report_connection_stats,
+            commands::stream::get_ice_servers,
+            commands::stream::set_ice_servers,
+            commands::stream::fetch_turn_credentials,
+            commands::stream::get_battery_saver,
+            commands::stream::set_battery_saver,
+            // Camera commands
+            commands::camera::list_cameras,
+            commands::camera::start_camera,
+            commands::camera::stop_camera,
+            commands::camera::get_video_layout,
+            // Voice commands
+            commands::voice::mute_self,
+            commands::voice::mute_participant,
+            // Voice message commands
+            commands::voice_message::record_voice_message,
+            commands::voice_message::stop_voice_recording,
+            // Annotation commands
+            commands::annotations::open_annotation_overlay,
+            commands::annotations::close_annotation_overlay,
+            commands::annotations::get_annotations,
+            commands::annotations::clear_annotations,
+            // File transfer commands
+            commands::file_transfer::send_file,
+            commands::file_transfer::accept_file,
+            commands::file_transfer::cancel_transfer,
+            commands::file_transfer::get_file_transfers,
             // Chat commands
             commands::chat::get_conversations,
+            commands::chat::get_archived_conversations,
+            commands::chat::archive_conversation,
+            commands::chat::unarchive_conversation,
             commands::chat::get_conversation,
             commands::chat::create_direct_conversation,
             commands::chat::create_group_conversation,
+            commands::chat::create_conversation_from_meeting,
             commands::chat::update_group,
             commands::chat::add_group_member,
             commands::chat::remove_group_member,
+            commands::chat::promote_member,
+            commands::chat::demote_member,
+            commands::chat::transfer_group_ownership,
             commands::chat::leave_group,
             commands::chat::get_messages,
             commands::chat::chat_send_message,
+            commands::chat::download_attachment,
+            commands::chat::get_conversation_links,
+            commands::chat::reply_to_message,
+            commands::chat::get_thread,
+            commands::chat::mark_thread_read,
+            commands::chat::get_message_read_by,
+            commands::chat::get_conversation_notification_prefs,
+            commands::chat::set_conversation_notification_prefs,
             commands::chat::mark_as_read,
             commands::chat::update_presence,
+            commands::chat::set_status_message,
+            commands::chat::get_presence_history,
             commands::chat::get_team_members,
             commands::chat::connect_chat,
             commands::chat::disconnect_chat,
             commands::chat::get_chat_status,
+            commands::chat::open_conversation,
+            commands::chat::close_conversation,
+            // Blocking commands
+            commands::blocking::block_user,
+            commands::blocking::unblock_user,
+            commands::blocking::get_blocked_users,
+            commands::blocking::report_user,
+            // Encrypted conversation commands
+            commands::encryption::enable_conversation_encryption,
             // Calendar commands
             commands::calendar::get_meetings,
             commands::calendar::get_meeting,
             commands::calendar::get_upcoming_meetings,
+            commands::calendar::get_agenda,
+            commands::calendar::get_week_meetings,
             commands::calendar::create_meeting,
+            commands::calendar::duplicate_meeting,
             commands::calendar::update_meeting,
+            commands::calendar::toggle_description_checkbox,
+            commands::calendar::skip_meeting_occurrence,
+            commands::calendar::set_meeting_recurrence_exceptions,
             commands::calendar::cancel_meeting,
             commands::calendar::delete_meeting,
             commands::calendar::respond_to_meeting,
             commands::calendar::add_meeting_attendee,
+            commands::calendar::set_attendee_attendance,
             commands::calendar::remove_meeting_attendee,
+            commands::calendar::get_meeting_guests,
+            commands::calendar::add_meeting_guest,
+            commands::calendar::remove_meeting_guest,
             commands::calendar::start_meeting,
+            commands::calendar::start_meeting_everywhere,
             commands::calendar::get_meeting_by_session,
+            commands::calendar::bulk_update_meetings,
+            commands::calendar::bulk_shift_meetings,
+            commands::calendar::bulk_cancel_meetings,
+            // Calendar feed commands
+            commands::calendar_feed::get_calendar_feed,
+            commands::calendar_feed::rotate_calendar_feed_token,
+            commands::calendar_feed::disable_calendar_feed,
+            // iCalendar commands
+            commands::calendar_ics::export_meeting_ics,
+            commands::calendar_ics::export_calendar_ics,
+            commands::calendar_ics::import_ics,
+            // Meeting agenda commands
+            commands::meeting_agenda::get_agenda_items,
+            commands::meeting_agenda::add_agenda_item,
+            commands::meeting_agenda::update_agenda_item,
+            commands::meeting_agenda::delete_agenda_item,
+            commands::meeting_agenda::start_agenda_tracking,
+            commands::meeting_agenda::next_agenda_item,
+            commands::meeting_agenda::get_agenda_progress,
+            commands::meeting_agenda::stop_agenda_tracking,
+            commands::meeting_notes::get_meeting_notes,
+            commands::meeting_notes::save_meeting_notes,
+            commands::meeting_notes::get_action_items,
+            commands::meeting_notes::get_assigned_action_items,
+            commands::meeting_notes::create_action_item,
+            commands::meeting_notes::update_action_item,
+            commands::meeting_notes::complete_action_item,
+            commands::meeting_notes::delete_action_item,
+            commands::meeting_attachments::list_meeting_attachments,
+            commands::meeting_attachments::add_meeting_attachment,
+            commands::meeting_attachments::remove_meeting_attachment,
+            commands::meeting_attachments::download_meeting_attachment,
+            commands::meeting_tags::list_meeting_tags,
+            commands::meeting_tags::create_meeting_tag,
+            commands::meeting_tags::update_meeting_tag,
+            commands::meeting_tags::delete_meeting_tag,
+            commands::meeting_tags::set_meeting_tag,
+            commands::meeting_tags::set_meeting_color,
+            commands::meeting_stats::get_meeting_stats,
+            // Scheduling poll commands
+            commands::polls::create_scheduling_poll,
+            commands::polls::get_scheduling_poll,
+            commands::polls::vote_on_poll,
+            commands::polls::close_scheduling_poll,
+            // Reminder telemetry commands
+            commands::reminders::record_reminder_fired,
+            commands::reminders::get_reminder_effectiveness,
+            commands::reminders::get_reminder_preferences,
+            commands::reminders::set_reminder_preferences,
+            // Availability block commands
+            commands::availability::get_my_availability_blocks,
+            commands::availability::get_availability_blocks,
+            commands::availability::create_availability_block,
+            commands::availability::delete_availability_block,
+            commands::availability::get_available_slots,
+            commands::availability::book_availability_slot,
+            // Scheduling commands
+            commands::scheduling::check_conflicts,
+            commands::scheduling::suggest_meeting_slots,
+            commands::scheduling::get_user_availability,
+            commands::scheduling::set_user_availability,
             // Google Calendar commands
             commands::google_calendar::start_google_auth,
             commands::google_calendar::complete_google_auth,
@@ -109,6 +405,19 @@ pub fn run() {
             commands::google_calendar::sync_meeting_to_google,
             commands::google_calendar::import_from_google,
             commands::google_calendar::toggle_google_sync,
+            commands::google_calendar::list_google_calendars,
+            commands::google_calendar::set_google_calendars,
+            commands::google_sync::get_google_sync_status,
+            commands::google_sync::sync_google_calendar_now,
+            commands::google_sync::set_google_conflict_policy,
+            // Outlook calendar commands
+            commands::outlook_calendar::start_outlook_auth,
+            commands::outlook_calendar::complete_outlook_auth,
+            commands::outlook_calendar::disconnect_outlook,
+            commands::outlook_calendar::get_outlook_status,
+            commands::outlook_calendar::sync_meeting_to_outlook,
+            commands::outlook_calendar::import_from_outlook,
+            commands::outlook_calendar::toggle_outlook_sync,
             // Utility commands - DateTime
             commands::utils::format_datetime,
             commands::utils::format_time_range,
@@ -118,6 +427,9 @@ pub fn run() {
             commands::utils::is_today,
             commands::utils::get_day_bounds,
             commands::utils::get_month_bounds,
+            commands::utils::convert_time_zone,
+            commands::utils::zoned_to_utc,
+            commands::utils::format_meeting_time_in_zone,
             // Utility commands - RRULE
             commands::utils::build_rrule,
             commands::utils::parse_rrule,
@@ -125,6 +437,8 @@ pub fn run() {
             commands::utils::expand_rrule,
             commands::utils::describe_rrule,
             commands::utils::get_next_occurrence,
+            commands::utils::build_recurrence_exceptions,
+            commands::utils::parse_recurrence_exceptions,
             // Utility commands - Calendar Grid
             commands::utils::generate_calendar_grid,
             commands::utils::generate_month_days,
@@ -134,6 +448,9 @@ pub fn run() {
             commands::utils::current_month,
             commands::utils::is_in_month,
             commands::utils::get_week_number,
+            // Utility commands - Text
+            commands::utils::truncate_text,
+            commands::utils::message_preview,
             // Cache commands
             commands::cache::get_cache_stats,
             commands::cache::invalidate_all_caches,
@@ -141,6 +458,34 @@ pub fn run() {
             commands::cache::invalidate_meeting_month,
             commands::cache::invalidate_conversation_messages,
             commands::cache::invalidate_presence_cache,
+            // Avatar commands
+            commands::avatars::get_avatar_thumbnail,
+            commands::avatars::get_avatar_thumbnails,
+            commands::avatars::clear_avatar_cache,
+            // Custom emoji commands
+            commands::emoji::list_custom_emojis,
+            commands::emoji::add_custom_emoji,
+            commands::emoji::delete_custom_emoji,
+            commands::emoji::resolve_emoji_shortcodes,
+            // Performance commands
+            commands::perf::get_command_perf_stats,
+            commands::perf::reset_command_perf_stats,
+            // Logging commands
+            commands::logging::get_trace_filter,
+            commands::logging::set_trace_filter,
+            // Low-bandwidth mode commands
+            commands::low_bandwidth::get_low_bandwidth,
+            commands::low_bandwidth::set_low_bandwidth,
+            // Idle detection commands
+            commands::idle::get_idle_threshold,
+            commands::idle::set_idle_threshold,
+            // Diagnostics commands
+            commands::diagnostics::create_diagnostics_bundle,
+            // Announcement commands
+            commands::announcements::get_announcements,
+            commands::announcements::dismiss_announcement,
+            // Sync commands
+            commands::sync::get_changes,
             // Validation commands
             commands::validation::validate_email,
             commands::validation::validate_password,
@@ -156,6 +501,22 @@ pub fn run() {
             commands::calendar::filter_meetings,
             commands::calendar::get_meetings_for_date,
             commands::calendar::search_meetings,
+            // Notification commands
+            commands::notifications::show_notification,
+            commands::notifications::get_notification_status,
+            commands::notifications::handle_notification_action,
+            commands::notifications::get_notification_settings,
+            commands::notifications::set_notification_settings,
+            commands::notifications::snooze_meeting_reminder,
+            // Badge commands
+            commands::badge::get_badge,
+            // Automation API commands
+            commands::automation::get_automation_api,
+            commands::automation::set_automation_api_enabled,
+            commands::automation::get_automation_token,
+            commands::automation::regenerate_automation_token,
+            commands::automation::set_automation_permission,
+            commands::automation::respond_to_automation_request,
             // Window commands
             commands::window::minimize_window,
             commands::window::restore_window,
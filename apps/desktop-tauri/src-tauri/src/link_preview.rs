@@ -0,0 +1,193 @@
+//! Link previews for the conversation links index
+//!
+//! URLs in a sent message are extracted when it is written and stored in
+//! `conversation_links` with the page title, so a conversation's Links tab is
+//! a single query instead of a scan of its history. Titles come from the
+//! page's `og:title` or `<title>`, fetched with a short timeout and a size
+//! cap; a page that can't be fetched is indexed without one.
+
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::utils::text;
+
+/// Links indexed per message; the rest are ignored
+pub const MAX_LINKS_PER_MESSAGE: usize = 10;
+
+/// Bytes of a page read while looking for its title
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest title kept, in user-perceived characters
+const MAX_TITLE_LENGTH: usize = 200;
+
+static URL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\bhttps?://[^\s<>"'`]+"#).unwrap());
+
+static OG_TITLE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?is)<meta\s[^>]*?(?:property|name)\s*=\s*["']og:title["'][^>]*?content\s*=\s*["']([^"']*)["']|<meta\s[^>]*?content\s*=\s*["']([^"']*)["'][^>]*?(?:property|name)\s*=\s*["']og:title["']"#,
+    )
+    .unwrap()
+});
+
+static TITLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap());
+
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("SquadX-Live/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default()
+});
+
+/// Distinct http(s) URLs in a message, in order of appearance
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for found in URL_REGEX.find_iter(text) {
+        let url = trim_trailing_punctuation(found.as_str());
+        let valid = reqwest::Url::parse(url).is_ok_and(|parsed| parsed.host_str().is_some());
+        if valid && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+            if urls.len() == MAX_LINKS_PER_MESSAGE {
+                break;
+            }
+        }
+    }
+    urls
+}
+
+/// Drop sentence punctuation after a URL, and a closing parenthesis that
+/// wraps it rather than belongs to it
+fn trim_trailing_punctuation(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', ']', '}', '*', '_', '~']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(rest) if trimmed.matches('(').count() < trimmed.matches(')').count() => rest,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// Title of an HTML page: `og:title` when present, `<title>` otherwise
+pub fn parse_title(html: &str) -> Option<String> {
+    let raw = OG_TITLE_REGEX
+        .captures(html)
+        .and_then(|c| c.get(1).or_else(|| c.get(2)))
+        .or_else(|| TITLE_REGEX.captures(html).and_then(|c| c.get(1)))?
+        .as_str();
+
+    let title = text::message_preview(&decode_entities(raw), MAX_TITLE_LENGTH);
+    (!title.is_empty()).then_some(title)
+}
+
+/// Decode the entities common in titles
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Fetch the title of the page at `url`; `None` if it isn't an HTML page or
+/// can't be fetched
+pub async fn fetch_title(url: &str) -> Option<String> {
+    let mut response = match HTTP.get(url).send().await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            tracing::debug!("No preview for {}: {}", url, response.status());
+            return None;
+        }
+        Err(e) => {
+            tracing::debug!("No preview for {}: {}", url, e);
+            return None;
+        }
+    };
+
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.to_ascii_lowercase().contains("html"));
+    if !is_html {
+        return None;
+    }
+
+    // The title sits in the head, so a bounded prefix of the page is enough
+    let mut page = Vec::new();
+    while page.len() < MAX_PAGE_BYTES {
+        match response.chunk().await {
+            Ok(Some(chunk)) => page.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(e) => {
+                tracing::debug!("Failed to read {}: {}", url, e);
+                break;
+            }
+        }
+        if TITLE_REGEX.is_match(&String::from_utf8_lossy(&page)) {
+            break;
+        }
+    }
+
+    parse_title(&String::from_utf8_lossy(&page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_urls() {
+        let urls = extract_urls(
+            "See https://example.com/docs. Also (https://en.wikipedia.org/wiki/Rust_(programming_language)) \
+             and http://example.com/docs, plus https://example.com/docs again; not ftp://x.org",
+        );
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/docs",
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+                "http://example.com/docs",
+            ]
+        );
+        assert!(extract_urls("no links here").is_empty());
+        assert!(extract_urls("https://").is_empty());
+    }
+
+    #[test]
+    fn test_extract_urls_caps_count() {
+        let message: Vec<String> = (0..20)
+            .map(|i| format!("https://example.com/{}", i))
+            .collect();
+        assert_eq!(
+            extract_urls(&message.join(" ")).len(),
+            MAX_LINKS_PER_MESSAGE
+        );
+    }
+
+    #[test]
+    fn test_parse_title() {
+        let page = r#"<html><head><title>Fallback</title>
+            <meta content="Tom &amp; Jerry&#39;s page" property="og:title"></head></html>"#;
+        assert_eq!(parse_title(page).as_deref(), Some("Tom & Jerry's page"));
+
+        let page = "<html><head><TITLE>\n  Release   notes\n</TITLE></head></html>";
+        assert_eq!(parse_title(page).as_deref(), Some("Release notes"));
+
+        assert_eq!(parse_title("<title>  </title>"), None);
+        assert_eq!(parse_title("<p>No head</p>"), None);
+    }
+}
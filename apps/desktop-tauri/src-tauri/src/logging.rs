@@ -0,0 +1,173 @@
+//! Runtime log filtering
+//!
+//! The console log goes through an `EnvFilter` that can be swapped while the
+//! app runs, so verbose logging can be turned on for one subsystem (e.g.
+//! `squadx_live_desktop_lib::chat_realtime=trace`) without restarting with
+//! `RUST_LOG`. The chosen directives are saved and applied again on the next
+//! launch, unless `RUST_LOG` is set.
+//!
+//! Independently of that filter, recent log lines are kept in memory for
+//! diagnostics bundles.
+
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::{Error, Result};
+
+/// File in the app config directory holding the saved directives
+pub const FILTER_FILE: &str = "trace_filter";
+
+/// What the in-memory log keeps, whatever the console shows
+pub const RECENT_LOG_DIRECTIVES: &str = "info,squadx_live_desktop_lib=debug";
+
+/// Lines the in-memory log keeps
+const RECENT_LOG_LINES: usize = 2000;
+
+/// Filter of the console log, reloadable at runtime
+pub type ReloadableFilter = reload::Layer<EnvFilter, Registry>;
+
+/// Handle for swapping the console log filter
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// Directives set at runtime, `None` while `RUST_LOG` applies
+    directives: Arc<Mutex<Option<String>>>,
+}
+
+impl LogFilter {
+    /// Console log filter from `RUST_LOG`, and the handle to change it
+    pub fn new() -> (ReloadableFilter, Self) {
+        let (layer, handle) = reload::Layer::new(EnvFilter::from_default_env());
+        let filter = Self {
+            handle,
+            directives: Arc::new(Mutex::new(None)),
+        };
+        (layer, filter)
+    }
+
+    /// Directives currently filtering the console log
+    pub fn current(&self) -> String {
+        self.directives
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_else(default_directives)
+    }
+
+    /// Filter the console log with `directives`, or go back to `RUST_LOG`
+    /// when `None`
+    pub fn set(&self, directives: Option<&str>) -> Result<()> {
+        let directives = directives.map(str::trim).filter(|d| !d.is_empty());
+        let filter = match directives {
+            Some(directives) => EnvFilter::try_new(directives)
+                .map_err(|e| Error::Config(format!("Invalid trace filter: {}", e)))?,
+            None => EnvFilter::from_default_env(),
+        };
+        self.handle
+            .reload(filter)
+            .map_err(|e| Error::Config(format!("Failed to apply trace filter: {}", e)))?;
+
+        *self.directives.lock().unwrap_or_else(|e| e.into_inner()) = directives.map(str::to_string);
+        Ok(())
+    }
+
+    /// Apply the directives saved by an earlier run, unless `RUST_LOG` is set
+    pub fn restore(&self, path: &Path) {
+        if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+            return;
+        }
+        let Ok(saved) = std::fs::read_to_string(path) else {
+            return;
+        };
+        match self.set(Some(&saved)) {
+            Ok(()) => tracing::info!("Restored trace filter: {}", saved.trim()),
+            Err(e) => tracing::warn!("Ignoring saved trace filter: {}", e),
+        }
+    }
+}
+
+/// Save the directives for the next launch, or forget them when `None`
+pub fn save(path: &Path, directives: Option<&str>) -> Result<()> {
+    match directives.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(directives) => {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, directives)?;
+        }
+        None => {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Directives applied without a runtime filter
+fn default_directives() -> String {
+    std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_else(|_| "error".to_string())
+}
+
+/// Most recent log lines, oldest first
+#[derive(Clone, Default)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl RecentLogs {
+    /// Copy of the lines kept so far
+    pub fn snapshot(&self) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.iter().cloned().collect()
+    }
+
+    fn push(&self, text: &str) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            if lines.len() == RECENT_LOG_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RecentLogs {
+    type Writer = RecentLogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentLogWriter {
+            logs: self.clone(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Writer of one event, added to the recent lines once formatted
+pub struct RecentLogWriter {
+    logs: RecentLogs,
+    buf: Vec<u8>,
+}
+
+impl io::Write for RecentLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentLogWriter {
+    fn drop(&mut self) {
+        self.logs.push(&String::from_utf8_lossy(&self.buf));
+    }
+}
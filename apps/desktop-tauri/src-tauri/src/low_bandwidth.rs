@@ -0,0 +1,110 @@
+//! Low-bandwidth mode
+//!
+//! One switch for users on hotspots and metered links. While it is on:
+//! - the native stream is captured at a lower frame rate and resolution
+//! - avatars are only served from the thumbnail cache, never downloaded
+//! - link titles of sent messages are not fetched
+//! - presence changes ride along with a slower heartbeat instead of being
+//!   sent one by one
+//! - cached data is kept several times longer before being fetched again
+//!
+//! The setting applies process-wide and is kept across restarts.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::Result;
+
+/// File in the app config directory holding the setting
+pub const SETTINGS_FILE: &str = "low_bandwidth";
+
+/// Frame rate cap of the native stream
+pub const MAX_FPS: u32 = 5;
+
+/// Widest frame the native stream sends, taller frames keep their aspect
+pub const MAX_FRAME_WIDTH: u32 = 1280;
+
+/// How much longer cached data stays fresh
+const CACHE_TTL_FACTOR: u32 = 4;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether low-bandwidth mode is on
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Turn low-bandwidth mode on or off, returning whether it changed
+pub fn set_enabled(enabled: bool) -> bool {
+    ENABLED.swap(enabled, Ordering::Relaxed) != enabled
+}
+
+/// Time-to-live of a cache entry stored with `ttl`, under the current mode
+pub fn cache_ttl(ttl: Duration) -> Duration {
+    if is_enabled() {
+        ttl.saturating_mul(CACHE_TTL_FACTOR)
+    } else {
+        ttl
+    }
+}
+
+/// Size of a frame scaled down to `max_width`, kept even for the encoder;
+/// narrower frames are left as they are
+pub fn fit_width(width: u32, height: u32, max_width: u32) -> (u32, u32) {
+    if max_width == 0 || width <= max_width {
+        return (width, height);
+    }
+    let scaled_height = (u64::from(height) * u64::from(max_width) / u64::from(width)) as u32;
+    (max_width & !1, (scaled_height & !1).max(2))
+}
+
+/// Read the setting saved by `save`, off when there is none
+pub fn load(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|contents| contents.trim() == "on")
+}
+
+/// Save the setting; off removes the file
+pub fn save(path: &Path, enabled: bool) -> Result<()> {
+    if enabled {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, "on")?;
+    } else if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_width() {
+        assert_eq!(fit_width(1920, 1080, 1280), (1280, 720));
+        assert_eq!(fit_width(1280, 800, 1280), (1280, 800));
+        assert_eq!(fit_width(800, 600, 1280), (800, 600));
+        assert_eq!(fit_width(2560, 1081, 1280), (1280, 540));
+        assert_eq!(fit_width(1920, 1080, 0), (1920, 1080));
+        assert_eq!(fit_width(10000, 1, 1280), (1280, 2));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir =
+            std::env::temp_dir().join(format!("squadx-low-bandwidth-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SETTINGS_FILE);
+        assert!(!load(&path));
+
+        save(&path, true).unwrap();
+        assert!(load(&path));
+
+        save(&path, false).unwrap();
+        assert!(!load(&path));
+        assert!(!path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,818 @@
+//! Desktop notifications
+//!
+//! On Windows, chat messages and meeting invitations are shown as native
+//! toasts with an inline reply box or accept/decline buttons, and held back
+//! while Focus Assist (or a full-screen app or presentation) keeps the user
+//! busy. Other platforms hand notifications to the frontend to display.
+//!
+//! Toast buttons carry their action as an argument string, parsed back into
+//! a [`NotificationAction`] when the toast is activated. Activations only
+//! reach the app while it is running.
+//!
+//! Chat notifications also follow the user's preference for the
+//! conversation: every message, only mentions, or none.
+//!
+//! On top of that, each category (messages, mentions, meeting reminders,
+//! control requests) can be turned off, and quiet hours hold back all but
+//! urgent notifications. These settings are kept on this device.
+
+use std::path::Path;
+
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::text;
+use crate::{Error, Result};
+
+/// File in the app config directory holding the settings
+pub const SETTINGS_FILE: &str = "notifications.json";
+
+/// ID of the inline reply box in chat toasts
+pub const REPLY_INPUT_ID: &str = "reply";
+
+/// Longest toast body, Windows truncates past a few lines anyway
+const MAX_BODY_LENGTH: usize = 200;
+
+/// What a notification is about, deciding the actions it offers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// New chat message, repliable inline
+    Chat {
+        conversation_id: String,
+    },
+    /// Meeting invitation, with accept and decline buttons
+    MeetingInvite {
+        meeting_id: String,
+    },
+    /// Meeting about to start
+    MeetingReminder {
+        meeting_id: String,
+    },
+    /// Viewer asking to control the shared screen
+    ControlRequest {
+        user_id: String,
+    },
+    General,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    #[serde(flatten)]
+    pub kind: NotificationKind,
+    /// Shown even while Focus Assist is on
+    #[serde(default)]
+    pub urgent: bool,
+}
+
+/// Action the user took on a toast
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum NotificationAction {
+    Reply {
+        conversation_id: String,
+        text: String,
+    },
+    AcceptMeeting {
+        meeting_id: String,
+    },
+    DeclineMeeting {
+        meeting_id: String,
+    },
+    /// Toast body clicked, open what it is about
+    Open {
+        #[serde(flatten)]
+        kind: NotificationKind,
+    },
+}
+
+/// How a notification reached the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Delivery {
+    /// Shown as a native toast
+    Toast,
+    /// Handed to the frontend to display
+    Frontend,
+    /// Held back while Focus Assist is on
+    Suppressed,
+    /// Dropped by the conversation's notification preference
+    Muted,
+    /// Dropped because its category is off or quiet hours are on
+    Silenced,
+}
+
+/// Kind of notification that can be turned off on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    Messages,
+    Mentions,
+    MeetingReminders,
+    ControlRequests,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Daily span during which only urgent notifications are shown, as local
+/// `HH:MM` times; a span whose end comes before its start runs past midnight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    fn parse(time: &str) -> Result<NaiveTime> {
+        NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(|_| Error::Input(format!("Invalid time, expected HH:MM: {}", time)))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        Self::parse(&self.start)?;
+        Self::parse(&self.end)?;
+        Ok(())
+    }
+
+    /// Whether a local time falls within quiet hours
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (Self::parse(&self.start), Self::parse(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Notification settings kept across restarts, everything on by default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default = "enabled")]
+    pub messages: bool,
+    #[serde(default = "enabled")]
+    pub mentions: bool,
+    #[serde(default = "enabled")]
+    pub meeting_reminders: bool,
+    #[serde(default = "enabled")]
+    pub control_requests: bool,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            messages: true,
+            mentions: true,
+            meeting_reminders: true,
+            control_requests: true,
+            quiet_hours: None,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn category_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::Messages => self.messages,
+            NotificationCategory::Mentions => self.mentions,
+            NotificationCategory::MeetingReminders => self.meeting_reminders,
+            NotificationCategory::ControlRequests => self.control_requests,
+        }
+    }
+
+    /// Whether a notification is shown at a local time; urgent ones get
+    /// through quiet hours but not a category turned off
+    pub fn allows(
+        &self,
+        category: Option<NotificationCategory>,
+        urgent: bool,
+        time: NaiveTime,
+    ) -> bool {
+        if category.is_some_and(|c| !self.category_enabled(c)) {
+            return false;
+        }
+        urgent || !self.quiet_hours.as_ref().is_some_and(|q| q.contains(time))
+    }
+
+    /// Read the settings saved by `save`, the defaults when there are none
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Whether a meeting's reminder is due: within `offset_minutes` of its
+/// start and not started yet
+pub fn reminder_due(scheduled_at: &str, offset_minutes: i32, now: DateTime<Utc>) -> bool {
+    let Ok(start) = DateTime::parse_from_rfc3339(scheduled_at) else {
+        return false;
+    };
+    let start = start.with_timezone(&Utc);
+    now < start && start - chrono::Duration::minutes(offset_minutes.into()) <= now
+}
+
+/// Which of a meeting's reminders is due: the shortest offset whose window
+/// has opened, so a meeting noticed late is reminded once rather than for
+/// every offset already passed
+pub fn reminder_stage(
+    scheduled_at: &str,
+    offsets_minutes: &[i32],
+    now: DateTime<Utc>,
+) -> Option<i32> {
+    offsets_minutes
+        .iter()
+        .copied()
+        .filter(|&offset| reminder_due(scheduled_at, offset, now))
+        .min()
+}
+
+/// How long until a meeting, for a reminder: "10 min", "1 h 30 min", "1 day"
+pub fn format_lead_time(minutes: i64) -> String {
+    let minutes = minutes.max(1);
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+    match (days, hours, minutes) {
+        (0, 0, m) => format!("{} min", m),
+        (0, h, 0) => format!("{} h", h),
+        (0, h, m) => format!("{} h {} min", h, m),
+        (1, 0, _) => "1 day".to_string(),
+        (d, 0, _) => format!("{} days", d),
+        (d, h, _) => format!("{} d {} h", d, h),
+    }
+}
+
+/// Who let a meeting's RSVP deadline pass, for the organizer:
+/// "Ana and Bruno haven't answered", "Ana, Bruno and 2 others haven't answered"
+pub fn format_missed_rsvp(names: &[String]) -> String {
+    let who = match names {
+        [] => "Attendees".to_string(),
+        [name] => name.clone(),
+        [first, second] => format!("{} and {}", first, second),
+        [first, second, third] => format!("{}, {} and {}", first, second, third),
+        [first, second, rest @ ..] => format!("{}, {} and {} others", first, second, rest.len()),
+    };
+    let verb = if names.len() == 1 {
+        "hasn't"
+    } else {
+        "haven't"
+    };
+    format!("{} {} answered", who, verb)
+}
+
+/// Which messages of a conversation notify the user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationLevel {
+    #[default]
+    All,
+    MentionsOnly,
+    Muted,
+}
+
+/// A participant's notification preference for a conversation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationNotificationPrefs {
+    #[serde(default)]
+    pub notification_level: NotificationLevel,
+    /// When the level falls back to `All`; unset keeps it until changed
+    #[serde(default)]
+    pub muted_until: Option<String>,
+}
+
+impl ConversationNotificationPrefs {
+    /// Level in effect at `now`
+    pub fn level_at(&self, now: DateTime<Utc>) -> NotificationLevel {
+        let expired = self
+            .muted_until
+            .as_deref()
+            .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+            .is_some_and(|until| until.with_timezone(&Utc) <= now);
+        if expired {
+            NotificationLevel::All
+        } else {
+            self.notification_level
+        }
+    }
+
+    /// Whether a message notifies the user at `now`
+    pub fn allows(&self, mentioned: bool, now: DateTime<Utc>) -> bool {
+        match self.level_at(now) {
+            NotificationLevel::All => true,
+            NotificationLevel::MentionsOnly => mentioned,
+            NotificationLevel::Muted => false,
+        }
+    }
+}
+
+/// Whether a message mentions one of `names` as `@name`, ignoring case
+pub fn mentions(text: &str, names: &[&str]) -> bool {
+    let text = text.to_lowercase();
+    names
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .any(|name| {
+            text.match_indices('@').any(|(at, _)| {
+                let rest = &text[at + 1..];
+                rest.starts_with(&name)
+                    && !rest[name.len()..]
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            })
+        })
+}
+
+impl NotificationKind {
+    /// Action argument opening the notification's subject
+    pub fn open_argument(&self) -> String {
+        match self {
+            NotificationKind::Chat { conversation_id } => format!("open-chat:{}", conversation_id),
+            NotificationKind::MeetingInvite { meeting_id }
+            | NotificationKind::MeetingReminder { meeting_id } => {
+                format!("open-meeting:{}", meeting_id)
+            }
+            NotificationKind::ControlRequest { user_id } => format!("open-control:{}", user_id),
+            NotificationKind::General => "open".to_string(),
+        }
+    }
+
+    /// Category the kind falls under, `None` for notifications always shown
+    ///
+    /// Chat messages count as mentions when they mention the user, which
+    /// takes the message to decide.
+    pub fn category(&self) -> Option<NotificationCategory> {
+        match self {
+            NotificationKind::Chat { .. } => Some(NotificationCategory::Messages),
+            NotificationKind::MeetingInvite { .. } | NotificationKind::MeetingReminder { .. } => {
+                Some(NotificationCategory::MeetingReminders)
+            }
+            NotificationKind::ControlRequest { .. } => Some(NotificationCategory::ControlRequests),
+            NotificationKind::General => None,
+        }
+    }
+}
+
+/// Parse the argument of an activated toast, with the text typed in its reply box
+///
+/// The frontend uses the same arguments for the notifications it displays.
+pub fn parse_arguments(arguments: &str, reply: Option<String>) -> Option<NotificationAction> {
+    if arguments == "open" {
+        return Some(NotificationAction::Open {
+            kind: NotificationKind::General,
+        });
+    }
+
+    let (verb, id) = arguments.split_once(':')?;
+    let id = id.to_string();
+    if id.is_empty() {
+        return None;
+    }
+
+    match verb {
+        "reply" => {
+            let text = reply
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())?;
+            Some(NotificationAction::Reply {
+                conversation_id: id,
+                text,
+            })
+        }
+        "accept" => Some(NotificationAction::AcceptMeeting { meeting_id: id }),
+        "decline" => Some(NotificationAction::DeclineMeeting { meeting_id: id }),
+        "open-chat" => Some(NotificationAction::Open {
+            kind: NotificationKind::Chat {
+                conversation_id: id,
+            },
+        }),
+        "open-meeting" => Some(NotificationAction::Open {
+            kind: NotificationKind::MeetingInvite { meeting_id: id },
+        }),
+        "open-control" => Some(NotificationAction::Open {
+            kind: NotificationKind::ControlRequest { user_id: id },
+        }),
+        _ => None,
+    }
+}
+
+/// Toast XML for a notification
+#[cfg(any(windows, test))]
+pub fn toast_xml(notification: &Notification) -> String {
+    let actions = match &notification.kind {
+        NotificationKind::Chat { conversation_id } => format!(
+            r#"<actions><input id="{input}" type="text" placeHolderContent="Reply"/><action content="Send" arguments="reply:{id}" hint-inputId="{input}" activationType="foreground"/></actions>"#,
+            input = REPLY_INPUT_ID,
+            id = escape_xml(conversation_id),
+        ),
+        NotificationKind::MeetingInvite { meeting_id } => format!(
+            r#"<actions><action content="Accept" arguments="accept:{id}" activationType="foreground"/><action content="Decline" arguments="decline:{id}" activationType="foreground"/></actions>"#,
+            id = escape_xml(meeting_id),
+        ),
+        NotificationKind::MeetingReminder { .. }
+        | NotificationKind::ControlRequest { .. }
+        | NotificationKind::General => String::new(),
+    };
+    let body = text::truncate_graphemes(&notification.body, MAX_BODY_LENGTH);
+
+    format!(
+        r#"<toast launch="{launch}"><visual><binding template="ToastGeneric"><text>{title}</text><text>{body}</text></binding></visual>{actions}</toast>"#,
+        launch = escape_xml(&notification.kind.open_argument()),
+        title = escape_xml(&notification.title),
+        body = escape_xml(&body),
+        actions = actions,
+    )
+}
+
+#[cfg(any(windows, test))]
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Whether Focus Assist, a full-screen app or a presentation keeps the user busy
+pub fn focus_assist_active() -> bool {
+    #[cfg(windows)]
+    {
+        windows_toast::focus_assist_active()
+    }
+    #[cfg(not(windows))]
+    {
+        false
+    }
+}
+
+/// Whether notifications are shown as native toasts on this platform
+pub fn native_toasts() -> bool {
+    cfg!(windows)
+}
+
+/// Show a notification as a native toast, calling `on_action` when the user acts on it
+///
+/// Returns `Ok(false)` on platforms without native toasts.
+#[cfg_attr(not(windows), allow(unused_variables))]
+pub fn show_toast<F>(app_id: &str, notification: &Notification, on_action: F) -> crate::Result<bool>
+where
+    F: Fn(NotificationAction) + Send + Sync + 'static,
+{
+    #[cfg(windows)]
+    {
+        let on_activated = move |arguments: String, reply: Option<String>| {
+            let action = parse_arguments(&arguments, reply);
+            match action {
+                Some(action) => on_action(action),
+                None => tracing::debug!("Ignoring toast activation: {}", arguments),
+            }
+        };
+        windows_toast::show(app_id, &toast_xml(notification), on_activated)
+            .map_err(|e| crate::Error::External(format!("Failed to show toast: {}", e)))?;
+        Ok(true)
+    }
+    #[cfg(not(windows))]
+    {
+        Ok(false)
+    }
+}
+
+#[cfg(windows)]
+mod windows_toast {
+    use windows::core::{IInspectable, Interface, HSTRING};
+    use windows::Data::Xml::Dom::XmlDocument;
+    use windows::Foundation::{IPropertyValue, TypedEventHandler};
+    use windows::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+        QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+    use windows::UI::Notifications::{
+        ToastActivatedEventArgs, ToastNotification, ToastNotificationManager,
+    };
+
+    use super::REPLY_INPUT_ID;
+
+    pub fn focus_assist_active() -> bool {
+        // SAFETY: takes no arguments and only reads the shell's state
+        match unsafe { SHQueryUserNotificationState() } {
+            Ok(state) => matches!(
+                state,
+                QUNS_QUIET_TIME | QUNS_BUSY | QUNS_PRESENTATION_MODE | QUNS_RUNNING_D3D_FULL_SCREEN
+            ),
+            Err(e) => {
+                tracing::debug!("Failed to query notification state: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Show a toast, passing its activation arguments and reply text to a callback
+    pub fn show<F>(app_id: &str, xml: &str, on_activated: F) -> windows::core::Result<()>
+    where
+        F: Fn(String, Option<String>) + Send + Sync + 'static,
+    {
+        let document = XmlDocument::new()?;
+        document.LoadXml(&HSTRING::from(xml))?;
+        let toast = ToastNotification::CreateToastNotification(&document)?;
+
+        toast.Activated(&TypedEventHandler::new(
+            move |_: &Option<ToastNotification>, args: &Option<IInspectable>| {
+                let Some(args) = args
+                    .as_ref()
+                    .and_then(|a| a.cast::<ToastActivatedEventArgs>().ok())
+                else {
+                    return Ok(());
+                };
+                let reply = args
+                    .UserInput()
+                    .and_then(|input| input.Lookup(&HSTRING::from(REPLY_INPUT_ID)))
+                    .and_then(|value| value.cast::<IPropertyValue>())
+                    .and_then(|value| value.GetString())
+                    .ok()
+                    .map(|value| value.to_string());
+                on_activated(args.Arguments()?.to_string(), reply);
+                Ok(())
+            },
+        ))?;
+
+        ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))?.Show(&toast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(
+            parse_arguments("reply:c1", Some("  on my way ".to_string())),
+            Some(NotificationAction::Reply {
+                conversation_id: "c1".to_string(),
+                text: "on my way".to_string(),
+            })
+        );
+        // An empty reply box sends nothing
+        assert_eq!(parse_arguments("reply:c1", Some(" ".to_string())), None);
+        assert_eq!(
+            parse_arguments("decline:m1", None),
+            Some(NotificationAction::DeclineMeeting {
+                meeting_id: "m1".to_string()
+            })
+        );
+        assert_eq!(
+            parse_arguments("open-meeting:m1", None),
+            Some(NotificationAction::Open {
+                kind: NotificationKind::MeetingInvite {
+                    meeting_id: "m1".to_string()
+                }
+            })
+        );
+        assert_eq!(
+            parse_arguments("open-control:u1", None),
+            Some(NotificationAction::Open {
+                kind: NotificationKind::ControlRequest {
+                    user_id: "u1".to_string()
+                }
+            })
+        );
+        assert_eq!(parse_arguments("accept:", None), None);
+        assert_eq!(parse_arguments("snooze:m1", None), None);
+    }
+
+    #[test]
+    fn test_toast_xml_actions_round_trip() {
+        let notification = Notification {
+            title: "Ana <QA>".to_string(),
+            body: "Ready & waiting".to_string(),
+            kind: NotificationKind::MeetingInvite {
+                meeting_id: "m1".to_string(),
+            },
+            urgent: false,
+        };
+        let xml = toast_xml(&notification);
+        assert!(xml.contains("<text>Ana &lt;QA&gt;</text>"));
+        assert!(xml.contains("<text>Ready &amp; waiting</text>"));
+        assert!(xml.contains(r#"launch="open-meeting:m1""#));
+        assert!(xml.contains(r#"arguments="accept:m1""#));
+        assert!(xml.contains(r#"arguments="decline:m1""#));
+
+        let chat = Notification {
+            kind: NotificationKind::Chat {
+                conversation_id: "c1".to_string(),
+            },
+            ..notification
+        };
+        let xml = toast_xml(&chat);
+        assert!(xml.contains(r#"<input id="reply" type="text""#));
+        assert!(xml.contains(r#"arguments="reply:c1" hint-inputId="reply""#));
+    }
+
+    #[test]
+    fn test_conversation_prefs() {
+        let now = DateTime::parse_from_rfc3339("2026-03-02T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(ConversationNotificationPrefs::default().allows(false, now));
+
+        let mentions_only = ConversationNotificationPrefs {
+            notification_level: NotificationLevel::MentionsOnly,
+            muted_until: None,
+        };
+        assert!(!mentions_only.allows(false, now));
+        assert!(mentions_only.allows(true, now));
+
+        let muted = ConversationNotificationPrefs {
+            notification_level: NotificationLevel::Muted,
+            muted_until: Some("2026-03-02T11:00:00+00:00".to_string()),
+        };
+        assert!(!muted.allows(true, now));
+        // Past the time, every message notifies again
+        let later = now + chrono::Duration::hours(2);
+        assert_eq!(muted.level_at(later), NotificationLevel::All);
+        assert!(muted.allows(false, later));
+    }
+
+    #[test]
+    fn test_mentions() {
+        let names = ["Ana Silva", "ana.silva"];
+        assert!(mentions("hey @ana silva, got a minute?", &names));
+        assert!(mentions("@Ana.Silva", &names));
+        assert!(!mentions("@ana silvan is here", &names));
+        assert!(!mentions("ana silva, no at sign", &names));
+        assert!(!mentions("@ana", &names));
+        assert!(!mentions("@", &["", " "]));
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let at = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        let overnight = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:30".to_string(),
+        };
+        assert!(overnight.contains(at("23:15")));
+        assert!(overnight.contains(at("03:00")));
+        assert!(!overnight.contains(at("07:30")));
+        assert!(!overnight.contains(at("12:00")));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(at("12:30")));
+        assert!(!lunch.contains(at("13:30")));
+
+        let invalid = QuietHours {
+            start: "25:00".to_string(),
+            end: "07:00".to_string(),
+        };
+        assert!(invalid.validate().is_err());
+        assert!(!invalid.contains(at("03:00")));
+    }
+
+    #[test]
+    fn test_settings_allow() {
+        let night = NaiveTime::from_hms_opt(23, 0, 0).unwrap();
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let mut settings = NotificationSettings {
+            messages: false,
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert!(!settings.allows(Some(NotificationCategory::Messages), true, noon));
+        assert!(settings.allows(Some(NotificationCategory::Mentions), false, noon));
+        assert!(!settings.allows(Some(NotificationCategory::Mentions), false, night));
+        // Urgent notifications get through quiet hours
+        assert!(settings.allows(Some(NotificationCategory::ControlRequests), true, night));
+        assert!(!settings.allows(None, false, night));
+
+        settings.quiet_hours = None;
+        assert!(settings.allows(None, false, night));
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let dir =
+            std::env::temp_dir().join(format!("squadx-notifications-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SETTINGS_FILE);
+        assert_eq!(
+            NotificationSettings::load(&path),
+            NotificationSettings::default()
+        );
+
+        let settings = NotificationSettings {
+            meeting_reminders: false,
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+            }),
+            ..Default::default()
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(NotificationSettings::load(&path), settings);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Categories missing from older files stay on
+        let partial: NotificationSettings = serde_json::from_str(r#"{"messages":false}"#).unwrap();
+        assert!(!partial.messages);
+        assert!(partial.control_requests);
+    }
+
+    #[test]
+    fn test_reminder_due() {
+        let now = DateTime::parse_from_rfc3339("2026-03-02T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(reminder_due("2026-03-02T10:10:00Z", 15, now));
+        assert!(reminder_due("2026-03-02T10:15:00+00:00", 15, now));
+        assert!(!reminder_due("2026-03-02T10:20:00Z", 15, now));
+        assert!(!reminder_due("2026-03-02T10:00:00Z", 15, now));
+        assert!(!reminder_due("not a date", 15, now));
+    }
+
+    #[test]
+    fn test_reminder_stage() {
+        let start = "2026-03-02T10:00:00Z";
+        let at = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let offsets = [10, 60, 1440];
+        assert_eq!(
+            reminder_stage(start, &offsets, at("2026-02-28T10:00:00Z")),
+            None
+        );
+        assert_eq!(
+            reminder_stage(start, &offsets, at("2026-03-01T11:00:00Z")),
+            Some(1440)
+        );
+        assert_eq!(
+            reminder_stage(start, &offsets, at("2026-03-02T09:30:00Z")),
+            Some(60)
+        );
+        // Noticed late: only the last reminder
+        assert_eq!(
+            reminder_stage(start, &offsets, at("2026-03-02T09:55:00Z")),
+            Some(10)
+        );
+        assert_eq!(
+            reminder_stage(start, &offsets, at("2026-03-02T10:00:00Z")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_lead_time() {
+        assert_eq!(format_lead_time(0), "1 min");
+        assert_eq!(format_lead_time(10), "10 min");
+        assert_eq!(format_lead_time(60), "1 h");
+        assert_eq!(format_lead_time(90), "1 h 30 min");
+        assert_eq!(format_lead_time(1440), "1 day");
+        assert_eq!(format_lead_time(1500), "1 d 1 h");
+        assert_eq!(format_lead_time(2880), "2 days");
+    }
+
+    #[test]
+    fn test_format_missed_rsvp() {
+        let names: Vec<String> = ["Ana", "Bruno", "Carla", "Davi"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(format_missed_rsvp(&names[..1]), "Ana hasn't answered");
+        assert_eq!(
+            format_missed_rsvp(&names[..2]),
+            "Ana and Bruno haven't answered"
+        );
+        assert_eq!(
+            format_missed_rsvp(&names[..3]),
+            "Ana, Bruno and Carla haven't answered"
+        );
+        assert_eq!(
+            format_missed_rsvp(&names),
+            "Ana, Bruno and 2 others haven't answered"
+        );
+    }
+}
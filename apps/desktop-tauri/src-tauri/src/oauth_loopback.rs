@@ -0,0 +1,246 @@
+//! OAuth loopback redirects
+//!
+//! Instead of asking the user to paste an authorization code, an OAuth flow
+//! binds an ephemeral port on the loopback interface, gives the provider
+//! `http://127.0.0.1:<port><path>` as its redirect URI and waits for the
+//! browser to be sent there with the code. The random `state` of the flow
+//! tells its redirect apart from any other request reaching the port.
+
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{Error, Result};
+
+/// How long the user has to authorize in the browser
+pub const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long the browser has to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest request head read
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Random bytes of a flow's `state`
+const STATE_BYTES: usize = 16;
+
+/// Pages the browser is left on
+const AUTHORIZED_PAGE: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>SquadX Live</title></head><body><p>Authorization complete. You can close this window and return to SquadX Live.</p></body></html>";
+
+const DENIED_PAGE: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>SquadX Live</title></head><body><p>Authorization was not granted. You can close this window and try again from SquadX Live.</p></body></html>";
+
+const NOT_FOUND_PAGE: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>SquadX Live</title></head><body><p>Not found.</p></body></html>";
+
+/// What a request reaching the listener means for the flow
+#[derive(Debug, Clone, PartialEq)]
+pub enum Callback {
+    /// The user authorized; the code to exchange for tokens
+    Code(String),
+    /// The provider sent back an error, e.g. `access_denied`
+    Denied(String),
+    /// Not the flow's redirect (another path, another state)
+    Ignored,
+}
+
+/// A loopback listener waiting for the redirect of one flow
+pub struct LoopbackRedirect {
+    listener: TcpListener,
+    path: &'static str,
+    pub redirect_uri: String,
+    pub state: String,
+}
+
+impl LoopbackRedirect {
+    /// Listen on an ephemeral loopback port for redirects to `path`
+    pub async fn bind(path: &'static str) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(|e| Error::Network(format!("Failed to listen for the redirect: {}", e)))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| Error::Network(format!("Failed to listen for the redirect: {}", e)))?
+            .port();
+        Ok(Self {
+            listener,
+            path,
+            redirect_uri: format!("http://127.0.0.1:{}{}", port, path),
+            state: generate_state(),
+        })
+    }
+
+    /// Wait up to `CALLBACK_TIMEOUT` for the redirect, answering the
+    /// browser with a page telling the user to return to the app
+    pub async fn wait_for_code(self) -> Result<String> {
+        tokio::time::timeout(CALLBACK_TIMEOUT, self.accept_callback())
+            .await
+            .map_err(|_| Error::Auth("Timed out waiting for authorization".to_string()))?
+    }
+
+    async fn accept_callback(&self) -> Result<String> {
+        loop {
+            let (mut stream, _) = self
+                .listener
+                .accept()
+                .await
+                .map_err(|e| Error::Network(format!("Failed to accept the redirect: {}", e)))?;
+            let target = match tokio::time::timeout(READ_TIMEOUT, read_target(&mut stream)).await {
+                Ok(Some(target)) => target,
+                _ => continue,
+            };
+
+            let callback = parse_callback(&target, self.path, &self.state);
+            let (status, page) = match callback {
+                Callback::Code(_) => (200, AUTHORIZED_PAGE),
+                Callback::Denied(_) => (200, DENIED_PAGE),
+                Callback::Ignored => (404, NOT_FOUND_PAGE),
+            };
+            if let Err(e) = stream.write_all(&response(status, page)).await {
+                tracing::debug!("Failed to answer the OAuth redirect: {}", e);
+            }
+
+            match callback {
+                Callback::Code(code) => return Ok(code),
+                Callback::Denied(error) => {
+                    return Err(Error::Auth(format!("Authorization denied: {}", error)))
+                }
+                Callback::Ignored => continue,
+            }
+        }
+    }
+}
+
+/// Make sense of the request target of a request reaching the listener
+pub fn parse_callback(target: &str, path: &str, state: &str) -> Callback {
+    let (target_path, query) = target.split_once('?').unwrap_or((target, ""));
+    if target_path != path {
+        return Callback::Ignored;
+    }
+
+    let mut code = None;
+    let mut error = None;
+    let mut state_matches = false;
+    for pair in query.split('&') {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let Ok(value) = urlencoding::decode(value) else {
+            continue;
+        };
+        match name {
+            "code" => code = Some(value.into_owned()),
+            "error" => error = Some(value.into_owned()),
+            "state" => state_matches = value == state,
+            _ => {}
+        }
+    }
+
+    match (state_matches, code, error) {
+        (false, _, _) => Callback::Ignored,
+        (true, _, Some(error)) => Callback::Denied(error),
+        (true, Some(code), None) if !code.is_empty() => Callback::Code(code),
+        _ => Callback::Ignored,
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn generate_state() -> String {
+    (0..STATE_BYTES)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect()
+}
+
+/// Request target of a GET request, once its head is read
+async fn read_target(stream: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&buffer);
+    let mut request_line = head.lines().next()?.split(' ');
+    match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+fn response(status: u16, page: &str) -> Vec<u8> {
+    let reason = if status == 200 { "OK" } else { "Not Found" };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        page.len(),
+        page
+    )
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/auth/google/callback";
+
+    #[test]
+    fn test_parse_callback_code() {
+        assert_eq!(
+            parse_callback(
+                "/auth/google/callback?state=abc&code=4%2F0Ab_cd&scope=email",
+                PATH,
+                "abc"
+            ),
+            Callback::Code("4/0Ab_cd".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_callback_denied() {
+        assert_eq!(
+            parse_callback(
+                "/auth/google/callback?error=access_denied&state=abc",
+                PATH,
+                "abc"
+            ),
+            Callback::Denied("access_denied".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_callback_ignored() {
+        assert_eq!(
+            parse_callback("/favicon.ico", PATH, "abc"),
+            Callback::Ignored
+        );
+        // Another flow's redirect, or none at all
+        assert_eq!(
+            parse_callback("/auth/google/callback?code=xyz&state=other", PATH, "abc"),
+            Callback::Ignored
+        );
+        assert_eq!(
+            parse_callback("/auth/google/callback?code=xyz", PATH, "abc"),
+            Callback::Ignored
+        );
+        assert_eq!(
+            parse_callback("/auth/google/callback?state=abc&code=", PATH, "abc"),
+            Callback::Ignored
+        );
+    }
+
+    #[test]
+    fn test_generate_state() {
+        let state = generate_state();
+        assert_eq!(state.len(), STATE_BYTES * 2);
+        assert_ne!(state, generate_state());
+    }
+}
@@ -0,0 +1,244 @@
+//! Message pagination cursors
+//!
+//! Message lists are paged by keyset on `(created_at, id)`: a page starts
+//! right past the message its cursor points at, so pages stay put while new
+//! messages arrive and messages sharing a timestamp are neither skipped nor
+//! repeated. Like change feed cursors, they are handed out opaque (base64
+//! JSON) so their shape can change without breaking clients.
+
+use std::cmp::Ordering;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Which way a page runs from its cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageDirection {
+    /// Messages sent before the cursor, when scrolling back
+    Older,
+    /// Messages sent after the cursor
+    Newer,
+}
+
+/// Position of a message in its conversation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageKey {
+    pub created_at: String,
+    pub id: String,
+}
+
+impl MessageKey {
+    /// Key of a message, `None` for messages without a timestamp
+    pub fn of(created_at: &Option<String>, id: &str) -> Option<Self> {
+        created_at.as_ref().map(|created_at| Self {
+            created_at: created_at.clone(),
+            id: id.to_string(),
+        })
+    }
+
+    fn timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.created_at).ok()
+    }
+}
+
+impl Ord for MessageKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Timestamps differ in precision, so compare instants when possible
+        match (self.timestamp(), other.timestamp()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => self.created_at.cmp(&other.created_at),
+        }
+        .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for MessageKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Where a page starts: the messages past `position` in `direction`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageCursor {
+    pub direction: PageDirection,
+    pub position: MessageKey,
+}
+
+impl MessageCursor {
+    /// Cursor handed out by `encode`
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let json = BASE64
+            .decode(cursor)
+            .map_err(|_| Error::Parse("Invalid message cursor".to_string()))?;
+        serde_json::from_slice(&json)
+            .map_err(|_| Error::Parse("Invalid message cursor".to_string()))
+    }
+
+    pub fn encode(&self) -> Result<String> {
+        Ok(BASE64.encode(serde_json::to_vec(self)?))
+    }
+
+    /// Whether a message belongs past the cursor
+    pub fn includes(&self, key: &MessageKey) -> bool {
+        match self.direction {
+            PageDirection::Older => *key < self.position,
+            PageDirection::Newer => *key > self.position,
+        }
+    }
+}
+
+/// Cut rows fetched one past `limit` down to a page, newest first
+///
+/// Rows come nearest the cursor first, so newer pages are turned around.
+/// Returns whether more messages lie beyond the page.
+pub fn trim_page<T>(
+    mut rows: Vec<T>,
+    limit: usize,
+    direction: Option<PageDirection>,
+) -> (Vec<T>, bool) {
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    if direction == Some(PageDirection::Newer) {
+        rows.reverse();
+    }
+    (rows, has_more)
+}
+
+/// Cursors to the older and the newer page next to a page
+///
+/// `newest` and `oldest` are the ends of the page. The first page has
+/// nothing newer, and whatever lies behind the cursor a page came from is
+/// known to exist, so `has_more` only decides the other side.
+pub fn page_cursors(
+    cursor: Option<&MessageCursor>,
+    newest: Option<MessageKey>,
+    oldest: Option<MessageKey>,
+    has_more: bool,
+) -> (Option<MessageCursor>, Option<MessageCursor>) {
+    let direction = cursor.map(|c| c.direction);
+    let has_older = direction == Some(PageDirection::Newer) || has_more;
+    let has_newer = match direction {
+        None => false,
+        Some(PageDirection::Older) => true,
+        Some(PageDirection::Newer) => has_more,
+    };
+
+    let older = oldest.filter(|_| has_older).map(|position| MessageCursor {
+        direction: PageDirection::Older,
+        position,
+    });
+    let newer = newest.filter(|_| has_newer).map(|position| MessageCursor {
+        direction: PageDirection::Newer,
+        position,
+    });
+    (older, newer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(created_at: &str, id: &str) -> MessageKey {
+        MessageKey {
+            created_at: created_at.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_key_order() {
+        assert!(key("2026-02-07T10:00:00+00:00", "b") < key("2026-02-07T10:00:00.5+00:00", "a"));
+        assert!(key("2026-02-07T10:00:00.5+00:00", "a") < key("2026-02-07T10:00:00.5+00:00", "b"));
+        assert!(key("2026-02-07T11:00:00+01:00", "z") < key("2026-02-07T10:30:00+00:00", "a"));
+        assert_eq!(
+            key("2026-02-07T10:00:00+00:00", "a").cmp(&key("2026-02-07T10:00:00.000+00:00", "a")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = MessageCursor {
+            direction: PageDirection::Older,
+            position: key("2026-02-07T14:30:00.123+00:00", "m1"),
+        };
+        let encoded = cursor.encode().unwrap();
+        assert_eq!(MessageCursor::decode(&encoded).unwrap(), cursor);
+        assert!(MessageCursor::decode("not a cursor").is_err());
+        assert!(MessageCursor::decode("").is_err());
+    }
+
+    #[test]
+    fn test_cursor_includes() {
+        let position = key("2026-02-07T10:00:00+00:00", "m");
+        let older = MessageCursor {
+            direction: PageDirection::Older,
+            position: position.clone(),
+        };
+        assert!(older.includes(&key("2026-02-07T10:00:00+00:00", "a")));
+        assert!(!older.includes(&position));
+        assert!(!older.includes(&key("2026-02-07T10:00:00+00:00", "z")));
+
+        let newer = MessageCursor {
+            direction: PageDirection::Newer,
+            position: position.clone(),
+        };
+        assert!(newer.includes(&key("2026-02-07T10:00:01+00:00", "a")));
+        assert!(!newer.includes(&position));
+    }
+
+    #[test]
+    fn test_trim_page() {
+        assert_eq!(trim_page(vec![5, 4, 3], 2, None), (vec![5, 4], true));
+        assert_eq!(
+            trim_page(vec![5, 4], 2, Some(PageDirection::Older)),
+            (vec![5, 4], false)
+        );
+        assert_eq!(
+            trim_page(vec![6, 7, 8], 2, Some(PageDirection::Newer)),
+            (vec![7, 6], true)
+        );
+    }
+
+    #[test]
+    fn test_page_cursors() {
+        let newest = Some(key("2026-02-07T10:00:02+00:00", "n"));
+        let oldest = Some(key("2026-02-07T10:00:01+00:00", "o"));
+
+        // First page: only older messages, if there are any
+        let (older, newer) = page_cursors(None, newest.clone(), oldest.clone(), true);
+        assert_eq!(older.unwrap().position, oldest.clone().unwrap());
+        assert!(newer.is_none());
+        let (older, _) = page_cursors(None, newest.clone(), oldest.clone(), false);
+        assert!(older.is_none());
+
+        // Scrolling back: the page it came from is newer
+        let back = MessageCursor {
+            direction: PageDirection::Older,
+            position: key("2026-02-07T10:00:03+00:00", "c"),
+        };
+        let (older, newer) = page_cursors(Some(&back), newest.clone(), oldest.clone(), false);
+        assert!(older.is_none());
+        let newer = newer.unwrap();
+        assert_eq!(newer.direction, PageDirection::Newer);
+        assert_eq!(newer.position, newest.clone().unwrap());
+
+        // Paging forward: the page it came from is older
+        let forward = MessageCursor {
+            direction: PageDirection::Newer,
+            position: key("2026-02-07T10:00:00+00:00", "c"),
+        };
+        let (older, newer) = page_cursors(Some(&forward), newest, oldest, false);
+        assert!(older.is_some());
+        assert!(newer.is_none());
+
+        // Empty pages have no ends to page from
+        assert_eq!(page_cursors(Some(&back), None, None, false), (None, None));
+    }
+}
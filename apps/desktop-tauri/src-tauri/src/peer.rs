@@ -0,0 +1,686 @@
+//! Native WebRTC peer connection
+//!
+//! The host side of a session owns its peer connections here instead of in
+//! the webview: SDP offer/answer and ICE candidates are handled in Rust and
+//! captured frames are encoded to H.264 once and written to a local video
+//! track shared by every viewer's connection. The host's webcam, when on,
+//! goes out the same way on a second video track. Voice goes both ways on an
+//! Opus track (see `audio`). Each connection also carries a pre-negotiated
+//! data channel for file transfers.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use image::imageops::FilterType;
+use openh264::encoder::Encoder;
+use openh264::formats::{RgbaSliceU8, YUVBuffer};
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_H264};
+use webrtc::api::APIBuilder;
+use webrtc::data_channel::data_channel_init::RTCDataChannelInit;
+use webrtc::data_channel::data_channel_message::DataChannelMessage;
+use webrtc::data_channel::RTCDataChannel;
+use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::interceptor::registry::Registry;
+use webrtc::media::Sample;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTPCodecType};
+use webrtc::stats::StatsReportType;
+use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+use webrtc::track::track_local::TrackLocal;
+use webrtc::track::track_remote::TrackRemote;
+
+use crate::audio::AudioPipeline;
+use crate::camera::CameraCapture;
+use crate::capture::{self, AppLock, SharedAppLock};
+use crate::connection_quality::StatsSample;
+use crate::ice::IceServerConfig;
+use crate::low_bandwidth;
+use crate::{Error, Result};
+
+/// Media stream ID of the screen track, as the viewer sees it in `ontrack`
+pub const SCREEN_STREAM_ID: &str = "squadx-screen";
+
+/// Media stream ID of the host's camera track
+pub const CAMERA_STREAM_ID: &str = "squadx-camera";
+
+/// Label of the data channel carrying file transfers
+pub const FILE_CHANNEL_LABEL: &str = "squadx-files";
+
+/// Pre-negotiated stream ID of the file channel; the viewer opens it with
+/// `createDataChannel("squadx-files", { negotiated: true, id: 1 })`
+pub const FILE_CHANNEL_ID: u16 = 1;
+
+/// Label of the data channel carrying viewers' annotations
+pub const ANNOTATION_CHANNEL_LABEL: &str = "squadx-annotations";
+
+/// Pre-negotiated stream ID of the annotation channel; the viewer opens it
+/// with `createDataChannel("squadx-annotations", { negotiated: true, id: 2 })`
+pub const ANNOTATION_CHANNEL_ID: u16 = 2;
+
+/// Activity on the file channel
+#[derive(Debug)]
+pub enum FileChannelEvent {
+    Text(String),
+    Binary(bytes::Bytes),
+    Closed,
+}
+
+/// ICE candidate gathered locally, ready to be sent over signaling
+#[derive(Debug, Clone)]
+pub struct LocalIceCandidate {
+    pub candidate: String,
+    pub sdp_mid: Option<String>,
+    pub sdp_m_line_index: Option<u32>,
+}
+
+/// Counters updated by the frame loop
+#[derive(Default)]
+struct StreamCounters {
+    frames_sent: AtomicU64,
+    width: AtomicU32,
+    height: AtomicU32,
+}
+
+/// Capture/encode loop feeding the shared H.264 video track
+///
+/// The source can be swapped while running: the track, and so every viewer's
+/// connection, stays the same and only the frames change.
+pub struct VideoPipeline {
+    video_track: Arc<TrackLocalStaticSample>,
+    counters: Arc<StreamCounters>,
+    stop_flag: Arc<AtomicBool>,
+    /// Capture source read on every frame
+    source_id: Arc<Mutex<String>>,
+    /// Frame rate cap read on every frame, 0 for none
+    max_fps: Arc<AtomicU32>,
+    /// Frame width cap read on every frame, 0 for none
+    max_width: Arc<AtomicU32>,
+}
+
+impl Default for VideoPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VideoPipeline {
+    pub fn new() -> Self {
+        let video_track = Arc::new(TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_H264.to_string(),
+                ..Default::default()
+            },
+            "video".to_string(),
+            SCREEN_STREAM_ID.to_string(),
+        ));
+
+        Self {
+            video_track,
+            counters: Arc::new(StreamCounters::default()),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            source_id: Arc::new(Mutex::new(String::new())),
+            max_fps: Arc::new(AtomicU32::new(0)),
+            max_width: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Start capturing, encoding and sending frames from a capture source
+    pub fn start(&self, source_id: String, fps: u32, app_lock: SharedAppLock) {
+        self.switch_source(source_id);
+        let track = self.video_track.clone();
+        let counters = self.counters.clone();
+        let stop_flag = self.stop_flag.clone();
+        let current_source = self.source_id.clone();
+        let max_fps = self.max_fps.clone();
+        let max_width = self.max_width.clone();
+        let runtime = tokio::runtime::Handle::current();
+
+        // Capture and encoding are blocking, keep them off the async workers
+        tokio::task::spawn_blocking(move || {
+            let mut encoder = match Encoder::new() {
+                Ok(encoder) => encoder,
+                Err(e) => {
+                    tracing::error!("Failed to create H.264 encoder: {}", e);
+                    return;
+                }
+            };
+
+            let mut source_id = String::new();
+            let mut frame_width_cap = 0;
+            while !stop_flag.load(Ordering::Relaxed) {
+                let frame_start = Instant::now();
+                let frame_interval = frame_interval(fps, max_fps.load(Ordering::Relaxed));
+
+                // A fresh encoder starts the new source or width cap on a
+                // keyframe, at its own resolution
+                let next_source = current_source
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone();
+                let next_width_cap = max_width.load(Ordering::Relaxed);
+                if next_source != source_id || next_width_cap != frame_width_cap {
+                    if !source_id.is_empty() {
+                        match Encoder::new() {
+                            Ok(fresh) => encoder = fresh,
+                            Err(e) => tracing::error!("Failed to reset H.264 encoder: {}", e),
+                        }
+                    }
+                    source_id = next_source;
+                    frame_width_cap = next_width_cap;
+                }
+
+                let lock = capture::current_app_lock(&app_lock);
+                match encode_frame(&mut encoder, &source_id, lock.as_ref(), frame_width_cap) {
+                    Ok((data, width, height)) => {
+                        let sample = Sample {
+                            data: bytes::Bytes::from(data),
+                            duration: frame_interval,
+                            ..Default::default()
+                        };
+                        if let Err(e) = runtime.block_on(track.write_sample(&sample)) {
+                            tracing::warn!("Failed to write video sample: {}", e);
+                        } else {
+                            counters.frames_sent.fetch_add(1, Ordering::Relaxed);
+                            counters.width.store(width, Ordering::Relaxed);
+                            counters.height.store(height, Ordering::Relaxed);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to capture frame: {}", e),
+                }
+
+                if let Some(remaining) = frame_interval.checked_sub(frame_start.elapsed()) {
+                    std::thread::sleep(remaining);
+                }
+            }
+
+            tracing::info!("Native video stream stopped");
+        });
+    }
+
+    /// Capture frames from another source from the next frame on
+    pub fn switch_source(&self, source_id: String) {
+        *self.source_id.lock().unwrap_or_else(|e| e.into_inner()) = source_id;
+    }
+
+    /// Stop the frame loop
+    pub fn stop(&self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Cap the frame rate below the requested one, or lift the cap with `None`
+    pub fn set_max_fps(&self, max_fps: Option<u32>) {
+        self.max_fps.store(max_fps.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Scale wider frames down to `max_width`, or lift the cap with `None`
+    pub fn set_max_width(&self, max_width: Option<u32>) {
+        self.max_width
+            .store(max_width.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+/// Camera/encode loop feeding the shared camera track
+///
+/// The track is attached to every connection from the start, so turning the
+/// camera on or off needs no renegotiation; while off, nothing is sent and
+/// viewers see the track muted.
+pub struct CameraPipeline {
+    camera_track: Arc<TrackLocalStaticSample>,
+    /// Stop flag of the running frame loop
+    stop_flag: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl Default for CameraPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraPipeline {
+    pub fn new() -> Self {
+        let camera_track = Arc::new(TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_H264.to_string(),
+                ..Default::default()
+            },
+            "camera".to_string(),
+            CAMERA_STREAM_ID.to_string(),
+        ));
+
+        Self {
+            camera_track,
+            stop_flag: Mutex::new(None),
+        }
+    }
+
+    /// Open a camera and send its frames, replacing any camera already on
+    ///
+    /// Returns once the camera is open, so a busy or missing camera fails here.
+    pub async fn start(&self, camera_id: String, fps: u32) -> Result<()> {
+        self.stop();
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let track = self.camera_track.clone();
+        let loop_stop_flag = stop_flag.clone();
+        let runtime = tokio::runtime::Handle::current();
+        let (opened_tx, opened_rx) = tokio::sync::oneshot::channel();
+
+        // Cameras are read blocking and are not Send on every platform, so
+        // the device lives on this thread for as long as it is on
+        tokio::task::spawn_blocking(move || {
+            let mut camera = match CameraCapture::open(&camera_id) {
+                Ok(camera) => {
+                    let _ = opened_tx.send(Ok(()));
+                    camera
+                }
+                Err(e) => {
+                    let _ = opened_tx.send(Err(e));
+                    return;
+                }
+            };
+            let mut encoder = match Encoder::new() {
+                Ok(encoder) => encoder,
+                Err(e) => {
+                    tracing::error!("Failed to create H.264 encoder: {}", e);
+                    return;
+                }
+            };
+
+            let frame_interval = frame_interval(fps, 0);
+            while !loop_stop_flag.load(Ordering::Relaxed) {
+                let frame_start = Instant::now();
+
+                match camera
+                    .frame()
+                    .and_then(|image| encode_image(&mut encoder, image))
+                {
+                    Ok((data, _, _)) => {
+                        let sample = Sample {
+                            data: bytes::Bytes::from(data),
+                            duration: frame_interval,
+                            ..Default::default()
+                        };
+                        if let Err(e) = runtime.block_on(track.write_sample(&sample)) {
+                            tracing::warn!("Failed to write camera sample: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to read camera frame: {}", e),
+                }
+
+                if let Some(remaining) = frame_interval.checked_sub(frame_start.elapsed()) {
+                    std::thread::sleep(remaining);
+                }
+            }
+
+            tracing::info!("Camera {} stopped", camera_id);
+        });
+
+        opened_rx
+            .await
+            .map_err(|_| Error::Capture("Camera task ended before opening".to_string()))??;
+        *self.stop_flag.lock().unwrap_or_else(|e| e.into_inner()) = Some(stop_flag);
+        Ok(())
+    }
+
+    /// Whether a camera is on
+    pub fn is_running(&self) -> bool {
+        self.stop_flag
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// Stop the frame loop and release the camera
+    pub fn stop(&self) {
+        if let Some(stop_flag) = self
+            .stop_flag
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        {
+            stop_flag.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Time between frames at the requested rate, capped unless `max_fps` is 0
+fn frame_interval(fps: u32, max_fps: u32) -> Duration {
+    let fps = match max_fps {
+        0 => fps,
+        max_fps => fps.min(max_fps),
+    };
+    Duration::from_millis(1000 / fps.clamp(1, 60) as u64)
+}
+
+/// Host-side peer connection to one viewer, sending the shared video track
+pub struct NativePeer {
+    connection: Arc<RTCPeerConnection>,
+    file_channel: Arc<RTCDataChannel>,
+    annotation_channel: Arc<RTCDataChannel>,
+    counters: Arc<StreamCounters>,
+}
+
+impl NativePeer {
+    /// Create a peer connection to a viewer with the pipelines' tracks attached
+    pub async fn new(
+        ice_servers: &[IceServerConfig],
+        pipeline: &VideoPipeline,
+        camera: &CameraPipeline,
+        audio: &AudioPipeline,
+    ) -> Result<Self> {
+        let mut media_engine = MediaEngine::default();
+        media_engine
+            .register_default_codecs()
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        let registry = register_default_interceptors(Registry::new(), &mut media_engine)
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        let api = APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .build();
+
+        let config = RTCConfiguration {
+            ice_servers: ice_servers
+                .iter()
+                .map(|server| RTCIceServer {
+                    urls: server.urls.clone(),
+                    username: server.username.clone().unwrap_or_default(),
+                    credential: server.credential.clone().unwrap_or_default(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let connection = Arc::new(
+            api.new_peer_connection(config)
+                .await
+                .map_err(|e| Error::WebRtc(e.to_string()))?,
+        );
+
+        let rtp_sender = connection
+            .add_track(Arc::clone(&pipeline.video_track) as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        let camera_sender = connection
+            .add_track(Arc::clone(&camera.camera_track) as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        // Sent and received: the viewer answers with its microphone
+        let audio_sender = connection
+            .add_track(audio.track() as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        // RTCP packets must be read for interceptors (NACK, reports) to work
+        for sender in [rtp_sender, camera_sender, audio_sender] {
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 1500];
+                while sender.read(&mut buf).await.is_ok() {}
+            });
+        }
+
+        let file_channel = connection
+            .create_data_channel(
+                FILE_CHANNEL_LABEL,
+                Some(RTCDataChannelInit {
+                    ordered: Some(true),
+                    negotiated: Some(FILE_CHANNEL_ID),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        let annotation_channel = connection
+            .create_data_channel(
+                ANNOTATION_CHANNEL_LABEL,
+                Some(RTCDataChannelInit {
+                    ordered: Some(true),
+                    negotiated: Some(ANNOTATION_CHANNEL_ID),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        Ok(Self {
+            connection,
+            file_channel,
+            annotation_channel,
+            counters: pipeline.counters.clone(),
+        })
+    }
+
+    /// Forward locally gathered ICE candidates to a callback
+    pub fn on_local_candidate<F>(&self, callback: F)
+    where
+        F: Fn(LocalIceCandidate) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.connection
+            .on_ice_candidate(Box::new(move |candidate: Option<RTCIceCandidate>| {
+                let callback = callback.clone();
+                Box::pin(async move {
+                    let Some(candidate) = candidate else { return };
+                    match candidate.to_json() {
+                        Ok(init) => callback(LocalIceCandidate {
+                            candidate: init.candidate,
+                            sdp_mid: init.sdp_mid,
+                            sdp_m_line_index: init.sdp_mline_index.map(u32::from),
+                        }),
+                        Err(e) => tracing::error!("Failed to serialize ICE candidate: {}", e),
+                    }
+                })
+            }));
+    }
+
+    /// Notify a callback when the connection state changes
+    pub fn on_state_change<F>(&self, callback: F)
+    where
+        F: Fn(String) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.connection
+            .on_peer_connection_state_change(Box::new(move |state: RTCPeerConnectionState| {
+                let callback = callback.clone();
+                Box::pin(async move {
+                    callback(state.to_string());
+                })
+            }));
+    }
+
+    /// Hand the audio track the viewer sends (their microphone) to a callback
+    pub fn on_remote_audio<F>(&self, callback: F)
+    where
+        F: Fn(Arc<TrackRemote>) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.connection
+            .on_track(Box::new(move |track: Arc<TrackRemote>, _, _| {
+                if track.kind() == RTPCodecType::Audio {
+                    callback(track);
+                }
+                Box::pin(async {})
+            }));
+    }
+
+    /// Data channel used for file transfers with this viewer
+    pub fn file_channel(&self) -> Arc<RTCDataChannel> {
+        self.file_channel.clone()
+    }
+
+    /// Forward file channel messages, in order, and its closing to a callback
+    pub fn on_file_channel_event<F>(&self, callback: F)
+    where
+        F: Fn(FileChannelEvent) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+
+        let on_message = callback.clone();
+        self.file_channel
+            .on_message(Box::new(move |msg: DataChannelMessage| {
+                let event = if msg.is_string {
+                    FileChannelEvent::Text(String::from_utf8_lossy(&msg.data).to_string())
+                } else {
+                    FileChannelEvent::Binary(msg.data)
+                };
+                on_message(event);
+                Box::pin(async {})
+            }));
+
+        self.file_channel.on_close(Box::new(move || {
+            callback(FileChannelEvent::Closed);
+            Box::pin(async {})
+        }));
+    }
+
+    /// Data channel carrying this viewer's annotations
+    pub fn annotation_channel(&self) -> Arc<RTCDataChannel> {
+        self.annotation_channel.clone()
+    }
+
+    /// Forward text messages on the annotation channel to a callback
+    pub fn on_annotation_message<F>(&self, callback: F)
+    where
+        F: Fn(String) + Send + Sync + 'static,
+    {
+        self.annotation_channel
+            .on_message(Box::new(move |msg: DataChannelMessage| {
+                if msg.is_string {
+                    callback(String::from_utf8_lossy(&msg.data).to_string());
+                }
+                Box::pin(async {})
+            }));
+    }
+
+    /// Create the SDP offer and set it as local description
+    pub async fn create_offer(&self) -> Result<String> {
+        let offer = self
+            .connection
+            .create_offer(None)
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        let sdp = offer.sdp.clone();
+        self.connection
+            .set_local_description(offer)
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))?;
+
+        Ok(sdp)
+    }
+
+    /// Apply the viewer's SDP answer
+    pub async fn set_remote_answer(&self, sdp: String) -> Result<()> {
+        let answer =
+            RTCSessionDescription::answer(sdp).map_err(|e| Error::WebRtc(e.to_string()))?;
+        self.connection
+            .set_remote_description(answer)
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))
+    }
+
+    /// Add an ICE candidate received from the viewer
+    pub async fn add_remote_candidate(
+        &self,
+        candidate: String,
+        sdp_mid: Option<String>,
+        sdp_m_line_index: Option<u32>,
+    ) -> Result<()> {
+        self.connection
+            .add_ice_candidate(RTCIceCandidateInit {
+                candidate,
+                sdp_mid,
+                sdp_mline_index: sdp_m_line_index.map(|i| i as u16),
+                username_fragment: None,
+            })
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))
+    }
+
+    /// Current counters of the connection
+    ///
+    /// Bytes, loss and round trip time come from the viewer's RTCP receiver
+    /// reports; frames and resolution are shared by all viewers of the pipeline.
+    pub async fn sample(&self) -> StatsSample {
+        let mut sample = StatsSample {
+            frames: self.counters.frames_sent.load(Ordering::Relaxed),
+            width: self.counters.width.load(Ordering::Relaxed),
+            height: self.counters.height.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+
+        let report = self.connection.get_stats().await;
+        for stats in report.reports.values() {
+            match stats {
+                StatsReportType::OutboundRTP(outbound) => sample.bytes += outbound.bytes_sent,
+                StatsReportType::RemoteInboundRTP(remote) => {
+                    sample.packets_received += remote.packets_received;
+                    sample.packets_lost += remote.packets_lost.max(0) as u64;
+                    if let Some(rtt) = remote.round_trip_time {
+                        sample.round_trip_time_ms = Some(rtt * 1000.0);
+                    }
+                }
+                _ => {}
+            }
+        }
+        sample
+    }
+
+    /// Close the connection
+    pub async fn close(&self) -> Result<()> {
+        self.connection
+            .close()
+            .await
+            .map_err(|e| Error::WebRtc(e.to_string()))
+    }
+}
+
+/// Capture one frame, no wider than `max_width` unless it is 0, and encode
+/// it to an H.264 access unit, with its size
+fn encode_frame(
+    encoder: &mut Encoder,
+    source_id: &str,
+    lock: Option<&AppLock>,
+    max_width: u32,
+) -> Result<(Vec<u8>, u32, u32)> {
+    let image = capture::capture_source(source_id, lock)?;
+    let (width, height) = low_bandwidth::fit_width(image.width(), image.height(), max_width);
+    let image = if width != image.width() {
+        image::imageops::resize(&image, width, height, FilterType::Triangle)
+    } else {
+        image
+    };
+    encode_image(encoder, image)
+}
+
+/// Encode one RGBA frame, returning the bitstream and the encoded size
+fn encode_image(encoder: &mut Encoder, image: image::RgbaImage) -> Result<(Vec<u8>, u32, u32)> {
+    // The encoder works on 4:2:0 YUV, which needs even dimensions
+    let width = image.width() & !1;
+    let height = image.height() & !1;
+    let image = if width != image.width() || height != image.height() {
+        image::imageops::crop_imm(&image, 0, 0, width, height).to_image()
+    } else {
+        image
+    };
+
+    let rgba = RgbaSliceU8::new(image.as_raw(), (width as usize, height as usize));
+    let yuv = YUVBuffer::from_rgb_source(rgba);
+    let bitstream = encoder
+        .encode(&yuv)
+        .map_err(|e| Error::WebRtc(format!("H.264 encoding failed: {}", e)))?;
+
+    Ok((bitstream.to_vec(), width, height))
+}
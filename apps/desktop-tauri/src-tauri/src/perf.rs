@@ -0,0 +1,214 @@
+//! Per-command performance tracking
+//!
+//! Every Tauri command runs in a tracing span (`#[tracing::instrument]`).
+//! A dedicated layer times those spans, counts the Supabase requests and
+//! cache hits recorded while they are active, logs a summary at debug level
+//! when they close and aggregates the results for `get_command_perf_stats`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Target of the marker events emitted by `record_*`
+const PERF_TARGET: &str = "squadx::perf";
+
+/// Module prefix of command spans
+const COMMANDS_TARGET: &str = concat!(env!("CARGO_CRATE_NAME"), "::commands");
+
+/// Note that a Supabase request is being issued by the current command
+pub fn record_supabase_request() {
+    tracing::trace!(target: PERF_TARGET, perf = "supabase_request");
+}
+
+/// Note that the current command was served from the cache
+pub fn record_cache_hit() {
+    tracing::trace!(target: PERF_TARGET, perf = "cache_hit");
+}
+
+/// Aggregated timings of one command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandPerfStats {
+    pub command: String,
+    pub calls: u64,
+    pub total_ms: f64,
+    pub average_ms: f64,
+    pub max_ms: f64,
+    pub supabase_requests: u64,
+    pub cache_hits: u64,
+}
+
+/// Shared aggregate, written by the layer and read by the commands
+#[derive(Clone, Default)]
+pub struct PerfState {
+    pub inner: Arc<Mutex<HashMap<String, CommandPerfStats>>>,
+}
+
+impl PerfState {
+    /// Stats sorted by total time spent, slowest first
+    pub fn snapshot(&self) -> Vec<CommandPerfStats> {
+        let stats = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut snapshot: Vec<CommandPerfStats> = stats.values().cloned().collect();
+        snapshot.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        snapshot
+    }
+
+    pub fn reset(&self) {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn record(&self, command: &str, duration_ms: f64, supabase_requests: u64, cache_hits: u64) {
+        let mut stats = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats
+            .entry(command.to_string())
+            .or_insert_with(|| CommandPerfStats {
+                command: command.to_string(),
+                ..Default::default()
+            });
+        entry.calls += 1;
+        entry.total_ms += duration_ms;
+        entry.max_ms = entry.max_ms.max(duration_ms);
+        entry.average_ms = entry.total_ms / entry.calls as f64;
+        entry.supabase_requests += supabase_requests;
+        entry.cache_hits += cache_hits;
+    }
+}
+
+/// Timing data attached to an open command span
+struct CommandTiming {
+    started_at: Instant,
+    supabase_requests: u64,
+    cache_hits: u64,
+}
+
+/// Extracts the `perf` field of marker events
+#[derive(Default)]
+struct PerfVisitor {
+    kind: Option<String>,
+}
+
+impl Visit for PerfVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "perf" {
+            self.kind = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "perf" {
+            self.kind = Some(format!("{:?}", value).trim_matches('"').to_string());
+        }
+    }
+}
+
+/// Whether the layer cares about a span or event
+pub fn is_perf_metadata(metadata: &Metadata<'_>) -> bool {
+    if metadata.is_span() {
+        metadata.target().starts_with(COMMANDS_TARGET)
+    } else {
+        metadata.target() == PERF_TARGET
+    }
+}
+
+pub struct PerfLayer {
+    state: PerfState,
+}
+
+impl PerfLayer {
+    pub fn new(state: PerfState) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for PerfLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(CommandTiming {
+                started_at: Instant::now(),
+                supabase_requests: 0,
+                cache_hits: 0,
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = PerfVisitor::default();
+        event.record(&mut visitor);
+        let Some(kind) = visitor.kind else { return };
+
+        // Credit the innermost command span
+        let Some(scope) = ctx.event_scope(event) else { return };
+        for span in scope {
+            let mut extensions = span.extensions_mut();
+            if let Some(timing) = extensions.get_mut::<CommandTiming>() {
+                match kind.as_str() {
+                    "supabase_request" => timing.supabase_requests += 1,
+                    "cache_hit" => timing.cache_hits += 1,
+                    _ => {}
+                }
+                break;
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(timing) = span.extensions_mut().remove::<CommandTiming>() else {
+            return;
+        };
+
+        let command = span.name();
+        let duration_ms = timing.started_at.elapsed().as_secs_f64() * 1000.0;
+        self.state
+            .record(command, duration_ms, timing.supabase_requests, timing.cache_hits);
+
+        tracing::debug!(
+            command,
+            duration_ms,
+            supabase_requests = timing.supabase_requests,
+            cache_hits = timing.cache_hits,
+            "Command finished"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_aggregates_per_command() {
+        let state = PerfState::default();
+        state.record("get_meetings", 120.0, 2, 0);
+        state.record("get_meetings", 40.0, 0, 1);
+        state.record("get_meeting", 10.0, 1, 0);
+
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        let meetings = &snapshot[0];
+        assert_eq!(meetings.command, "get_meetings");
+        assert_eq!(meetings.calls, 2);
+        assert_eq!(meetings.total_ms, 160.0);
+        assert_eq!(meetings.average_ms, 80.0);
+        assert_eq!(meetings.max_ms, 120.0);
+        assert_eq!(meetings.supabase_requests, 2);
+        assert_eq!(meetings.cache_hits, 1);
+
+        state.reset();
+        assert!(state.snapshot().is_empty());
+    }
+}
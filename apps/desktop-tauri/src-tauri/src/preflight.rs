@@ -0,0 +1,471 @@
+//! Pre-session checks
+//!
+//! What usually goes wrong after the user clicks "Start sharing" (a missing
+//! screen recording permission, a display unplugged since the source was
+//! picked, a blocked microphone, a firewall dropping TURN traffic, a locked
+//! keychain) is checked up front, so the UI can show a checklist first.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpStream, UdpSocket};
+
+use crate::capture;
+use crate::ice::IceServerConfig;
+use crate::secure_storage;
+
+/// How long a TURN server gets to answer
+const TURN_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+const DEFAULT_TURN_PORT: u16 = 3478;
+const DEFAULT_TURNS_PORT: u16 = 5349;
+
+/// STUN magic cookie (RFC 5389)
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_SUCCESS: u16 = 0x0101;
+const STUN_BINDING_ERROR: u16 = 0x0111;
+
+/// What the user is about to do, deciding which checks apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionType {
+    /// Share the screen
+    Share,
+    /// Share the screen and talk
+    ShareWithAudio,
+    /// Watch someone else's screen
+    View,
+}
+
+impl SessionType {
+    fn captures(self) -> bool {
+        matches!(self, SessionType::Share | SessionType::ShareWithAudio)
+    }
+
+    fn uses_audio(self) -> bool {
+        self == SessionType::ShareWithAudio
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckId {
+    CapturePermission,
+    CaptureSource,
+    Microphone,
+    TurnReachability,
+    KeychainAccess,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    /// The session can start, possibly degraded
+    Warning,
+    /// The session would fail
+    Failed,
+    /// Not applicable to the session type or platform
+    Skipped,
+}
+
+/// One line of the checklist
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    pub id: CheckId,
+    pub status: CheckStatus,
+    /// What was found, or what to do about it
+    pub detail: Option<String>,
+}
+
+impl PreflightCheck {
+    fn new(id: CheckId, status: CheckStatus, detail: Option<&str>) -> Self {
+        Self {
+            id,
+            status,
+            detail: detail.map(String::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    /// No check failed
+    pub ready: bool,
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    pub fn new(checks: Vec<PreflightCheck>) -> Self {
+        Self {
+            ready: !checks.iter().any(|c| c.status == CheckStatus::Failed),
+            checks,
+        }
+    }
+}
+
+/// Run the checks that don't need the network; they block, run them off the async runtime
+pub fn run_local_checks(session_type: SessionType, source_id: Option<&str>) -> Vec<PreflightCheck> {
+    let mut checks = Vec::new();
+    if session_type.captures() {
+        checks.push(check_capture_permission());
+        checks.push(check_capture_source(source_id));
+    } else {
+        checks.push(skipped(CheckId::CapturePermission));
+        checks.push(skipped(CheckId::CaptureSource));
+    }
+    checks.push(if session_type.uses_audio() {
+        check_microphone()
+    } else {
+        skipped(CheckId::Microphone)
+    });
+    checks.push(check_keychain());
+    checks
+}
+
+fn skipped(id: CheckId) -> PreflightCheck {
+    PreflightCheck::new(id, CheckStatus::Skipped, None)
+}
+
+fn check_capture_permission() -> PreflightCheck {
+    #[cfg(target_os = "macos")]
+    if !macos::screen_capture_allowed() {
+        return PreflightCheck::new(
+            CheckId::CapturePermission,
+            CheckStatus::Failed,
+            Some("Allow screen recording in System Settings > Privacy & Security"),
+        );
+    }
+
+    match xcap::Monitor::all() {
+        Ok(monitors) if !monitors.is_empty() => {
+            PreflightCheck::new(CheckId::CapturePermission, CheckStatus::Passed, None)
+        }
+        Ok(_) => PreflightCheck::new(
+            CheckId::CapturePermission,
+            CheckStatus::Failed,
+            Some("No display can be captured"),
+        ),
+        Err(e) => PreflightCheck {
+            id: CheckId::CapturePermission,
+            status: CheckStatus::Failed,
+            detail: Some(format!("Screen capture is unavailable: {}", e)),
+        },
+    }
+}
+
+fn check_capture_source(source_id: Option<&str>) -> PreflightCheck {
+    let Some(source_id) = source_id else {
+        return PreflightCheck::new(
+            CheckId::CaptureSource,
+            CheckStatus::Failed,
+            Some("No screen selected"),
+        );
+    };
+
+    match capture::get_available_sources() {
+        Ok(sources) => match sources.iter().find(|s| s.id == source_id) {
+            Some(source) => PreflightCheck {
+                id: CheckId::CaptureSource,
+                status: CheckStatus::Passed,
+                detail: Some(source.name.clone()),
+            },
+            None => PreflightCheck::new(
+                CheckId::CaptureSource,
+                CheckStatus::Failed,
+                Some("The selected screen is no longer available"),
+            ),
+        },
+        Err(e) => PreflightCheck {
+            id: CheckId::CaptureSource,
+            status: CheckStatus::Failed,
+            detail: Some(format!("Failed to list screens: {}", e)),
+        },
+    }
+}
+
+fn check_microphone() -> PreflightCheck {
+    #[cfg(target_os = "macos")]
+    {
+        match macos::microphone_status() {
+            macos::AUTHORIZED => {
+                PreflightCheck::new(CheckId::Microphone, CheckStatus::Passed, None)
+            }
+            macos::NOT_DETERMINED => PreflightCheck::new(
+                CheckId::Microphone,
+                CheckStatus::Warning,
+                Some("You will be asked for microphone access when audio starts"),
+            ),
+            _ => PreflightCheck::new(
+                CheckId::Microphone,
+                CheckStatus::Failed,
+                Some("Allow microphone access in System Settings > Privacy & Security"),
+            ),
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        PreflightCheck::new(
+            CheckId::Microphone,
+            CheckStatus::Skipped,
+            Some("Microphone access is confirmed when audio starts"),
+        )
+    }
+}
+
+fn check_keychain() -> PreflightCheck {
+    match secure_storage::check_access() {
+        Ok(()) => PreflightCheck::new(CheckId::KeychainAccess, CheckStatus::Passed, None),
+        Err(e) => PreflightCheck {
+            id: CheckId::KeychainAccess,
+            status: CheckStatus::Failed,
+            detail: Some(e.to_string()),
+        },
+    }
+}
+
+/// Check that at least one TURN relay answers
+///
+/// Without TURN, peers behind strict NATs can't connect, but others can.
+pub async fn check_turn(servers: &[IceServerConfig]) -> PreflightCheck {
+    let endpoints: Vec<(String, TurnEndpoint)> = servers
+        .iter()
+        .flat_map(|s| s.urls.iter())
+        .filter_map(|url| parse_turn_url(url).map(|endpoint| (url.clone(), endpoint)))
+        .collect();
+    if endpoints.is_empty() {
+        return PreflightCheck::new(
+            CheckId::TurnReachability,
+            CheckStatus::Warning,
+            Some("No TURN server configured, viewers behind strict firewalls may not connect"),
+        );
+    }
+
+    let probes = endpoints.iter().map(|(_, endpoint)| probe_turn(endpoint));
+    let results = futures_util::future::join_all(probes).await;
+    match endpoints
+        .iter()
+        .zip(results)
+        .find(|(_, reachable)| *reachable)
+    {
+        Some(((url, _), _)) => PreflightCheck {
+            id: CheckId::TurnReachability,
+            status: CheckStatus::Passed,
+            detail: Some(url.clone()),
+        },
+        None => PreflightCheck::new(
+            CheckId::TurnReachability,
+            CheckStatus::Failed,
+            Some("No TURN server is reachable, check the firewall"),
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TurnTransport {
+    Udp,
+    Tcp,
+    Tls,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TurnEndpoint {
+    host: String,
+    port: u16,
+    transport: TurnTransport,
+}
+
+/// Parse a `turn:` or `turns:` URL (RFC 7065), `None` for other ICE URLs
+fn parse_turn_url(url: &str) -> Option<TurnEndpoint> {
+    let (secure, rest) = if let Some(rest) = url.strip_prefix("turns:") {
+        (true, rest)
+    } else {
+        (false, url.strip_prefix("turn:")?)
+    };
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let transport = match (secure, query.strip_prefix("transport=")) {
+        (true, _) => TurnTransport::Tls,
+        (false, Some("tcp")) => TurnTransport::Tcp,
+        _ => TurnTransport::Udp,
+    };
+    let default_port = if secure {
+        DEFAULT_TURNS_PORT
+    } else {
+        DEFAULT_TURN_PORT
+    };
+
+    // Bracketed IPv6 hosts contain colons of their own
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+        _ => (address, default_port),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+
+    Some(TurnEndpoint {
+        host: host.to_string(),
+        port,
+        transport,
+    })
+}
+
+/// Whether a TURN server answers a STUN binding request (UDP) or accepts a connection
+async fn probe_turn(endpoint: &TurnEndpoint) -> bool {
+    let address = (endpoint.host.as_str(), endpoint.port);
+    let probe = async {
+        match endpoint.transport {
+            TurnTransport::Tcp | TurnTransport::Tls => TcpStream::connect(address).await.is_ok(),
+            TurnTransport::Udp => {
+                let Ok(socket) = UdpSocket::bind(("0.0.0.0", 0)).await else {
+                    return false;
+                };
+                let transaction_id: [u8; 12] = rand::random();
+                if socket.connect(address).await.is_err()
+                    || socket.send(&binding_request(transaction_id)).await.is_err()
+                {
+                    return false;
+                }
+                let mut buffer = [0u8; 512];
+                loop {
+                    match socket.recv(&mut buffer).await {
+                        Ok(len) if is_binding_response(&buffer[..len], transaction_id) => {
+                            return true
+                        }
+                        Ok(_) => continue,
+                        Err(_) => return false,
+                    }
+                }
+            }
+        }
+    };
+
+    let reachable = tokio::time::timeout(TURN_PROBE_TIMEOUT, probe)
+        .await
+        .unwrap_or(false);
+    if !reachable {
+        tracing::debug!(
+            "TURN server {}:{} did not answer",
+            endpoint.host,
+            endpoint.port
+        );
+    }
+    reachable
+}
+
+/// STUN binding request without attributes
+fn binding_request(transaction_id: [u8; 12]) -> [u8; 20] {
+    let mut packet = [0u8; 20];
+    packet[0..2].copy_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    // Attribute length stays 0
+    packet[4..8].copy_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    packet[8..20].copy_from_slice(&transaction_id);
+    packet
+}
+
+/// Whether a packet answers our binding request; an error answer still
+/// proves the server is reachable
+fn is_binding_response(packet: &[u8], transaction_id: [u8; 12]) -> bool {
+    if packet.len() < 20 || packet[8..20] != transaction_id {
+        return false;
+    }
+    let message_type = u16::from_be_bytes([packet[0], packet[1]]);
+    let cookie = u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]);
+    cookie == STUN_MAGIC_COOKIE && matches!(message_type, STUN_BINDING_SUCCESS | STUN_BINDING_ERROR)
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    /// `AVAuthorizationStatus` values
+    pub const NOT_DETERMINED: isize = 0;
+    pub const AUTHORIZED: isize = 3;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: *mut Object;
+    }
+
+    /// Whether screen recording is allowed, without prompting
+    pub fn screen_capture_allowed() -> bool {
+        // SAFETY: takes no arguments and only reads the permission state
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+
+    /// Microphone `AVAuthorizationStatus`, without prompting
+    pub fn microphone_status() -> isize {
+        // SAFETY: class method taking a framework-provided media type constant
+        unsafe {
+            msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_turn_url() {
+        assert_eq!(
+            parse_turn_url("turn:turn.example.com"),
+            Some(TurnEndpoint {
+                host: "turn.example.com".to_string(),
+                port: 3478,
+                transport: TurnTransport::Udp,
+            })
+        );
+        assert_eq!(
+            parse_turn_url("turn:10.0.0.1:80?transport=tcp"),
+            Some(TurnEndpoint {
+                host: "10.0.0.1".to_string(),
+                port: 80,
+                transport: TurnTransport::Tcp,
+            })
+        );
+        assert_eq!(
+            parse_turn_url("turns:[2001:db8::1]"),
+            Some(TurnEndpoint {
+                host: "2001:db8::1".to_string(),
+                port: 5349,
+                transport: TurnTransport::Tls,
+            })
+        );
+        assert_eq!(parse_turn_url("stun:stun.l.google.com:19302"), None);
+        assert_eq!(parse_turn_url("turn:host:port"), None);
+    }
+
+    #[test]
+    fn test_binding_response_matches_transaction() {
+        let transaction_id = [7u8; 12];
+        let request = binding_request(transaction_id);
+        assert_eq!(&request[0..2], &[0x00, 0x01]);
+        assert_eq!(&request[4..8], &[0x21, 0x12, 0xA4, 0x42]);
+        // A request is not a response
+        assert!(!is_binding_response(&request, transaction_id));
+
+        let mut response = request;
+        response[0..2].copy_from_slice(&STUN_BINDING_SUCCESS.to_be_bytes());
+        assert!(is_binding_response(&response, transaction_id));
+        assert!(!is_binding_response(&response, [8u8; 12]));
+        assert!(!is_binding_response(&response[..12], transaction_id));
+    }
+
+    #[test]
+    fn test_report_ready_unless_a_check_failed() {
+        let warning = PreflightCheck::new(CheckId::TurnReachability, CheckStatus::Warning, None);
+        assert!(PreflightReport::new(vec![warning.clone(), skipped(CheckId::Microphone)]).ready);
+
+        let failed = PreflightCheck::new(CheckId::KeychainAccess, CheckStatus::Failed, None);
+        assert!(!PreflightReport::new(vec![warning, failed]).ready);
+    }
+}
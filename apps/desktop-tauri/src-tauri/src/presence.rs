@@ -0,0 +1,195 @@
+//! Presence heartbeat
+//!
+//! While chat is connected the app re-sends its status every
+//! `HEARTBEAT_INTERVAL`, which keeps `user_presence.last_seen_at` current; the
+//! database stamps it with the server clock and records status changes in
+//! `presence_history`. A row still claiming to be online or away after
+//! several missed heartbeats belongs to an app that stopped without signing
+//! off, so it reads as offline.
+//!
+//! Users pick online, away, busy or offline and may add a short custom
+//! message. While they are in a session, online is published as
+//! `in_session` instead, and goes back to online when the session ends.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::supabase::UserPresenceRow;
+use crate::{Error, Result};
+
+/// How often a running app refreshes its presence
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Heartbeat interval in low-bandwidth mode, which also carries the status
+/// changes made since the previous heartbeat; still well within
+/// `STALE_AFTER_SECS`
+pub const LOW_BANDWIDTH_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Age of the last heartbeat after which a user counts as offline; the
+/// `record_presence` trigger uses the same lapse
+pub const STALE_AFTER_SECS: i64 = 180;
+
+/// Statuses a user can pick
+pub const SELECTABLE_STATUSES: [&str; 4] = ["online", "away", "busy", "offline"];
+
+/// Status published for an online user who is in a session
+pub const IN_SESSION: &str = "in_session";
+
+/// Longest custom status message, in characters
+pub const MAX_STATUS_MESSAGE_CHARS: usize = 100;
+
+/// Time until the next heartbeat
+pub fn heartbeat_interval(low_bandwidth: bool) -> Duration {
+    if low_bandwidth {
+        LOW_BANDWIDTH_HEARTBEAT_INTERVAL
+    } else {
+        HEARTBEAT_INTERVAL
+    }
+}
+
+/// Status to publish for the one the user picked
+///
+/// Only `online` is ever replaced. Being in a session wins over being idle,
+/// since watching a stream leaves keyboard and mouse alone.
+pub fn published_status(chosen: &str, in_session: bool, idle: bool) -> &str {
+    if chosen != "online" {
+        chosen
+    } else if in_session {
+        IN_SESSION
+    } else if idle {
+        "away"
+    } else {
+        chosen
+    }
+}
+
+/// Custom status message as stored, `None` to clear it
+pub fn status_message(message: Option<&str>) -> Result<Option<String>> {
+    let Some(message) = message.map(str::trim).filter(|m| !m.is_empty()) else {
+        return Ok(None);
+    };
+    if message.chars().count() > MAX_STATUS_MESSAGE_CHARS {
+        return Err(Error::Input(format!(
+            "Status messages are limited to {} characters",
+            MAX_STATUS_MESSAGE_CHARS
+        )));
+    }
+    Ok(Some(message.to_string()))
+}
+
+/// Status a presence row stands for at `now`
+pub fn effective_status(row: &UserPresenceRow, now: DateTime<Utc>) -> &str {
+    if row.status == "offline" {
+        return "offline";
+    }
+    let fresh = row
+        .last_seen_at
+        .as_deref()
+        .and_then(|seen| DateTime::parse_from_rfc3339(seen).ok())
+        .is_some_and(|seen| (now - seen.with_timezone(&Utc)).num_seconds() <= STALE_AFTER_SECS);
+    if fresh {
+        &row.status
+    } else {
+        "offline"
+    }
+}
+
+/// Whether a presence row stands for a user at the app at `now`; busy and
+/// in-session users are online, away ones are not
+pub fn is_online(row: &UserPresenceRow, now: DateTime<Utc>) -> bool {
+    matches!(effective_status(row, now), "online" | "busy" | IN_SESSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(status: &str, last_seen_at: Option<&str>) -> UserPresenceRow {
+        UserPresenceRow {
+            user_id: "user-1".to_string(),
+            status: status.to_string(),
+            status_message: None,
+            last_seen_at: last_seen_at.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_recent_heartbeat_keeps_status() {
+        let now = DateTime::parse_from_rfc3339("2026-01-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(is_online(
+            &row("online", Some("2026-01-10T11:59:00.123+00:00")),
+            now
+        ));
+        assert_eq!(
+            effective_status(&row("away", Some("2026-01-10T11:58:00+00:00")), now),
+            "away"
+        );
+    }
+
+    #[test]
+    fn test_missed_heartbeats_read_offline() {
+        let now = DateTime::parse_from_rfc3339("2026-01-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(!is_online(
+            &row("online", Some("2026-01-10T11:50:00+00:00")),
+            now
+        ));
+        assert!(!is_online(&row("online", None), now));
+        assert!(!is_online(&row("online", Some("yesterday")), now));
+        assert_eq!(
+            effective_status(&row("offline", Some("2026-01-10T12:00:00+00:00")), now),
+            "offline"
+        );
+    }
+
+    #[test]
+    fn test_busy_and_in_session_count_as_online() {
+        let now = DateTime::parse_from_rfc3339("2026-01-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let seen = Some("2026-01-10T11:59:30+00:00");
+        assert!(is_online(&row("busy", seen), now));
+        assert!(is_online(&row(IN_SESSION, seen), now));
+        assert!(!is_online(&row("away", seen), now));
+        assert!(!is_online(
+            &row(IN_SESSION, Some("2026-01-10T11:00:00+00:00")),
+            now
+        ));
+    }
+
+    #[test]
+    fn test_published_status() {
+        assert_eq!(published_status("online", false, false), "online");
+        assert_eq!(published_status("online", true, false), IN_SESSION);
+        assert_eq!(published_status("busy", true, false), "busy");
+        assert_eq!(published_status("offline", true, false), "offline");
+        assert_eq!(published_status("online", false, true), "away");
+        assert_eq!(published_status("online", true, true), IN_SESSION);
+        assert_eq!(published_status("busy", false, true), "busy");
+    }
+
+    #[test]
+    fn test_status_message() {
+        assert_eq!(status_message(None).unwrap(), None);
+        assert_eq!(status_message(Some("   ")).unwrap(), None);
+        assert_eq!(
+            status_message(Some(" In a meeting until 3 "))
+                .unwrap()
+                .as_deref(),
+            Some("In a meeting until 3")
+        );
+        assert!(status_message(Some(&"é".repeat(MAX_STATUS_MESSAGE_CHARS))).is_ok());
+        assert!(status_message(Some(&"x".repeat(MAX_STATUS_MESSAGE_CHARS + 1))).is_err());
+    }
+
+    #[test]
+    fn test_heartbeat_comes_before_staleness() {
+        for low_bandwidth in [false, true] {
+            assert!(heartbeat_interval(low_bandwidth).as_secs() < STALE_AFTER_SECS as u64);
+        }
+    }
+}
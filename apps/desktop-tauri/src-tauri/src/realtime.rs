@@ -1,15 +1,23 @@
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+use crate::e2e::SessionCipher;
+use crate::signaling_delivery::{Delivery, SignalingEnvelope, RESEND_INTERVAL};
+use crate::signaling_protocol::{local_capabilities, PROTOCOL_VERSION};
 use crate::{Error, Result};
 
 const REALTIME_VERSION: &str = "1.0.0";
-const HEARTBEAT_INTERVAL_MS: u64 = 30000;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Signaling message types for WebRTC
+///
+/// Messages carrying a `to_user_id: Option<String>` are addressed to a single
+/// peer when set and broadcast to everyone otherwise, so a host can negotiate
+/// a separate connection with each viewer over the shared channel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SignalingMessage {
@@ -17,11 +25,15 @@ pub enum SignalingMessage {
     Offer {
         sdp: String,
         from_user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to_user_id: Option<String>,
     },
     /// WebRTC answer from viewer
     Answer {
         sdp: String,
         from_user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to_user_id: Option<String>,
     },
     /// ICE candidate
     IceCandidate {
@@ -29,23 +41,39 @@ pub enum SignalingMessage {
         sdp_mid: Option<String>,
         sdp_m_line_index: Option<u32>,
         from_user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to_user_id: Option<String>,
     },
     /// Control request from viewer
     ControlRequest {
         from_user_id: String,
     },
-    /// Control grant from host
+    /// Control grant from host, revoked automatically after `duration_secs`
     ControlGrant {
         to_user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_secs: Option<u64>,
     },
     /// Control revoke from host
     ControlRevoke {
         to_user_id: String,
     },
-    /// User joined the session
+    /// User joined the session; participants answer a broadcast join with
+    /// an addressed one so the newcomer learns who is already there
     UserJoined {
         user_id: String,
         is_host: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to_user_id: Option<String>,
+        /// X25519 public key for end-to-end encryption (base64)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public_key: Option<String>,
+        /// Signaling protocol version, 0 from builds that predate versioning
+        #[serde(default)]
+        protocol_version: u32,
+        /// Optional features the sender supports
+        #[serde(default)]
+        capabilities: Vec<String>,
     },
     /// User left the session
     UserLeft {
@@ -59,6 +87,243 @@ pub enum SignalingMessage {
         content: String,
         timestamp: u64,
     },
+    /// Pointer position of a participant without control (relative 0-1 coordinates)
+    CursorPosition {
+        from_user_id: String,
+        x: f64,
+        y: f64,
+    },
+    /// Host's decision on a participant waiting to be admitted
+    JoinResponse {
+        from_user_id: String,
+        to_user_id: String,
+        approved: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// Host removed a participant from the session, for good when `banned`
+    ParticipantRemoved {
+        from_user_id: String,
+        to_user_id: String,
+        banned: bool,
+    },
+    /// Host named or dropped a co-host, next in line should the host leave
+    CohostChanged {
+        from_user_id: String,
+        user_id: String,
+        is_cohost: bool,
+    },
+    /// Host handed the session over; a co-host taking over after the host
+    /// left announces itself with `from_user_id == to_user_id`
+    HostTransferred {
+        from_user_id: String,
+        to_user_id: String,
+    },
+    /// Participant muted or unmuted their microphone; the host may also
+    /// mute someone else
+    AudioMuted {
+        from_user_id: String,
+        user_id: String,
+        muted: bool,
+    },
+    /// Participant turned their camera on or off; `stream_id` is the media
+    /// stream carrying it
+    CameraChanged {
+        from_user_id: String,
+        enabled: bool,
+        stream_id: Option<String>,
+    },
+    /// Co-host asking the host to moderate on its behalf
+    ModerationRequest {
+        from_user_id: String,
+        to_user_id: String,
+        action: ModerationAction,
+    },
+    /// Viewers in the host's waiting room, shared so co-hosts can admit them
+    WaitingRoom {
+        from_user_id: String,
+        user_ids: Vec<String>,
+    },
+    /// Host muted or unmuted a participant in session chat
+    ChatMuted {
+        from_user_id: String,
+        user_id: String,
+        muted: bool,
+    },
+    /// Host started streaming another display or window on the same tracks
+    CaptureSourceSwitched {
+        from_user_id: String,
+        source_id: String,
+        source_name: String,
+    },
+    /// Receipt of a sequenced offer, answer, ICE candidate, join response,
+    /// removal, role change or moderation
+    Ack {
+        seq: u64,
+        from_user_id: String,
+        to_user_id: String,
+    },
+    /// Message type from a newer protocol version, ignored
+    #[serde(other)]
+    Unknown,
+}
+
+/// Moderation a co-host may do, carried out by the host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ModerationAction {
+    ApproveJoin {
+        user_id: String,
+    },
+    DenyJoin {
+        user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    GrantControl {
+        user_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_secs: Option<u64>,
+    },
+    RevokeControl {
+        user_id: String,
+    },
+    MuteChat {
+        user_id: String,
+        muted: bool,
+    },
+}
+
+impl SignalingMessage {
+    /// Join announcement carrying this build's protocol version and capabilities
+    pub fn user_joined(user_id: &str, is_host: bool, to_user_id: Option<&str>) -> Self {
+        SignalingMessage::UserJoined {
+            user_id: user_id.to_string(),
+            is_host,
+            to_user_id: to_user_id.map(String::from),
+            public_key: None,
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: local_capabilities(),
+        }
+    }
+
+    /// Peer the message is addressed to, `None` for broadcasts
+    pub fn recipient(&self) -> Option<&str> {
+        match self {
+            SignalingMessage::Offer { to_user_id, .. }
+            | SignalingMessage::Answer { to_user_id, .. }
+            | SignalingMessage::IceCandidate { to_user_id, .. }
+            | SignalingMessage::UserJoined { to_user_id, .. } => to_user_id.as_deref(),
+            SignalingMessage::Ack { to_user_id, .. }
+            | SignalingMessage::JoinResponse { to_user_id, .. }
+            | SignalingMessage::ParticipantRemoved { to_user_id, .. }
+            | SignalingMessage::ModerationRequest { to_user_id, .. } => Some(to_user_id.as_str()),
+            _ => None,
+        }
+    }
+
+    /// User who sent the message, `None` for host control messages
+    pub fn sender(&self) -> Option<&str> {
+        match self {
+            SignalingMessage::Offer { from_user_id, .. }
+            | SignalingMessage::Answer { from_user_id, .. }
+            | SignalingMessage::IceCandidate { from_user_id, .. }
+            | SignalingMessage::ControlRequest { from_user_id }
+            | SignalingMessage::ChatMessage { from_user_id, .. }
+            | SignalingMessage::CursorPosition { from_user_id, .. }
+            | SignalingMessage::JoinResponse { from_user_id, .. }
+            | SignalingMessage::ParticipantRemoved { from_user_id, .. }
+            | SignalingMessage::CohostChanged { from_user_id, .. }
+            | SignalingMessage::HostTransferred { from_user_id, .. }
+            | SignalingMessage::CaptureSourceSwitched { from_user_id, .. }
+            | SignalingMessage::AudioMuted { from_user_id, .. }
+            | SignalingMessage::CameraChanged { from_user_id, .. }
+            | SignalingMessage::ModerationRequest { from_user_id, .. }
+            | SignalingMessage::WaitingRoom { from_user_id, .. }
+            | SignalingMessage::ChatMuted { from_user_id, .. }
+            | SignalingMessage::Ack { from_user_id, .. } => Some(from_user_id),
+            SignalingMessage::UserJoined { user_id, .. }
+            | SignalingMessage::UserLeft { user_id } => Some(user_id),
+            SignalingMessage::ControlGrant { .. }
+            | SignalingMessage::ControlRevoke { .. }
+            | SignalingMessage::Unknown => None,
+        }
+    }
+
+    /// Whether the message should be processed by the given user
+    pub fn is_for(&self, user_id: &str) -> bool {
+        !matches!(self.recipient(), Some(to) if to != user_id)
+    }
+}
+
+/// Presence metadata a participant tracks on the session channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresenceMeta {
+    pub user_id: String,
+    pub is_host: bool,
+    /// When the participant started tracking presence (RFC 3339)
+    pub online_at: Option<String>,
+}
+
+/// Presence change parsed from `presence_state` / `presence_diff` events
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresenceUpdate {
+    /// Full snapshot, sent by the server right after joining
+    Sync(Vec<PresenceMeta>),
+    /// Incremental change
+    Diff {
+        joins: Vec<PresenceMeta>,
+        leaves: Vec<PresenceMeta>,
+    },
+}
+
+/// Parse a Phoenix presence event payload
+///
+/// Both events key entries by presence key (the user ID) with a `metas` list;
+/// a user connected from several clients has several metas, the first wins.
+pub fn parse_presence_event(event: &str, payload: &serde_json::Value) -> Option<PresenceUpdate> {
+    match event {
+        "presence_state" => Some(PresenceUpdate::Sync(parse_presences(payload))),
+        "presence_diff" => Some(PresenceUpdate::Diff {
+            joins: payload
+                .get("joins")
+                .map(parse_presences)
+                .unwrap_or_default(),
+            leaves: payload
+                .get("leaves")
+                .map(parse_presences)
+                .unwrap_or_default(),
+        }),
+        _ => None,
+    }
+}
+
+fn parse_presences(entries: &serde_json::Value) -> Vec<PresenceMeta> {
+    let Some(entries) = entries.as_object() else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(|(key, entry)| {
+            let meta = entry.get("metas")?.as_array()?.first()?;
+            Some(PresenceMeta {
+                user_id: meta
+                    .get("user_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(key)
+                    .to_string(),
+                is_host: meta
+                    .get("is_host")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                online_at: meta
+                    .get("online_at")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            })
+        })
+        .collect()
 }
 
 /// Supabase Realtime message format
@@ -71,6 +336,36 @@ struct RealtimeMessage {
     reference: Option<String>,
 }
 
+impl RealtimeMessage {
+    /// Signaling broadcast on a session channel, sealed by the cipher
+    fn signaling(
+        topic: &str,
+        envelope: SignalingEnvelope,
+        cipher: &Mutex<SessionCipher>,
+    ) -> Option<Self> {
+        let sealed = cipher
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .seal(envelope);
+        match sealed {
+            Ok(payload) => Some(Self {
+                topic: topic.to_string(),
+                event: "broadcast".to_string(),
+                payload: serde_json::json!({
+                    "type": "broadcast",
+                    "event": "signaling",
+                    "payload": payload
+                }),
+                reference: None,
+            }),
+            Err(e) => {
+                tracing::error!("Failed to seal signaling message: {}", e);
+                None
+            }
+        }
+    }
+}
+
 /// Channel state
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChannelState {
@@ -95,6 +390,8 @@ struct RealtimeClientInner {
     state: ChannelState,
     current_channel: Option<String>,
     message_tx: Option<mpsc::Sender<RealtimeMessage>>,
+    cipher: Option<Arc<Mutex<SessionCipher>>>,
+    presence_tx: broadcast::Sender<PresenceUpdate>,
 }
 
 impl RealtimeClient {
@@ -107,6 +404,8 @@ impl RealtimeClient {
                 state: ChannelState::Disconnected,
                 current_channel: None,
                 message_tx: None,
+                cipher: None,
+                presence_tx: broadcast::channel(32).0,
             })),
         }
     }
@@ -133,6 +432,13 @@ impl RealtimeClient {
         inner.access_token = token;
     }
 
+    /// Subscribe to presence changes of the joined channel
+    ///
+    /// Subscribe before `join_channel` to receive the initial snapshot.
+    pub async fn subscribe_presence(&self) -> broadcast::Receiver<PresenceUpdate> {
+        self.inner.read().await.presence_tx.subscribe()
+    }
+
     #[allow(dead_code)]
     pub async fn get_state(&self) -> ChannelState {
         let inner = self.inner.read().await;
@@ -140,11 +446,15 @@ impl RealtimeClient {
     }
 
     /// Connect to Supabase Realtime and join a session channel
+    ///
+    /// Every signaling payload goes through `cipher`, which encrypts it
+    /// when the session requires end-to-end encryption.
     pub async fn join_channel(
         &self,
         session_id: &str,
         user_id: &str,
         is_host: bool,
+        cipher: SessionCipher,
     ) -> Result<(
         broadcast::Receiver<SignalingMessage>,
         mpsc::Sender<SignalingMessage>,
@@ -164,6 +474,7 @@ impl RealtimeClient {
             "{}/realtime/v1/websocket?apikey={}&vsn={}",
             ws_url, inner.anon_key, REALTIME_VERSION
         );
+        let presence_tx = inner.presence_tx.clone();
 
         drop(inner);
 
@@ -180,6 +491,7 @@ impl RealtimeClient {
         let (signaling_tx, signaling_rx) = broadcast::channel::<SignalingMessage>(100);
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<SignalingMessage>(100);
         let (internal_tx, mut internal_rx) = mpsc::channel::<RealtimeMessage>(100);
+        let cipher = Arc::new(Mutex::new(cipher));
 
         // Update state
         {
@@ -187,10 +499,17 @@ impl RealtimeClient {
             inner.state = ChannelState::Connecting;
             inner.current_channel = Some(session_id.to_string());
             inner.message_tx = Some(internal_tx.clone());
+            inner.cipher = Some(cipher.clone());
         }
 
         let channel_topic = format!("realtime:session:{}", session_id);
         let user_id_clone = user_id.to_string();
+        let delivery = Arc::new(Mutex::new(Delivery::default()));
+        let delivery_read = delivery.clone();
+        let cipher_read = cipher.clone();
+        let local_user_id = user_id.to_string();
+        let ack_topic = channel_topic.clone();
+        let ack_tx = internal_tx.clone();
         let signaling_tx_clone = signaling_tx.clone();
         let inner_clone = self.inner.clone();
 
@@ -215,20 +534,47 @@ impl RealtimeClient {
                                     }
                                 }
                                 "broadcast" => {
-                                    // Signaling message
+                                    // Signaling message, acked when sequenced
                                     if let Some(payload) = realtime_msg.payload.get("payload") {
-                                        if let Ok(signaling) =
-                                            serde_json::from_value::<SignalingMessage>(
-                                                payload.clone(),
-                                            )
-                                        {
-                                            let _ = signaling_tx_clone.send(signaling);
+                                        let opened = cipher_read
+                                            .lock()
+                                            .unwrap_or_else(|e| e.into_inner())
+                                            .open(payload);
+                                        let envelope = match opened {
+                                            Ok(envelope) => envelope,
+                                            Err(e) => {
+                                                tracing::warn!("Dropped signaling message: {}", e);
+                                                None
+                                            }
+                                        };
+                                        if let Some(envelope) = envelope {
+                                            let received = delivery_read
+                                                .lock()
+                                                .unwrap_or_else(|e| e.into_inner())
+                                                .receive(envelope, &local_user_id);
+                                            if let Some(ack_msg) = received.ack.and_then(|ack| {
+                                                RealtimeMessage::signaling(
+                                                    &ack_topic,
+                                                    SignalingEnvelope::unsequenced(ack),
+                                                    &cipher_read,
+                                                )
+                                            }) {
+                                                let _ = ack_tx.send(ack_msg).await;
+                                            }
+                                            if let Some(signaling) = received.message {
+                                                let _ = signaling_tx_clone.send(signaling);
+                                            }
                                         }
                                     }
                                 }
                                 "presence_diff" | "presence_state" => {
-                                    // Presence updates - could be used for user list
                                     tracing::debug!("Presence update: {:?}", realtime_msg.payload);
+                                    if let Some(update) = parse_presence_event(
+                                        &realtime_msg.event,
+                                        &realtime_msg.payload,
+                                    ) {
+                                        let _ = presence_tx.send(update);
+                                    }
                                 }
                                 _ => {}
                             }
@@ -278,38 +624,55 @@ impl RealtimeClient {
                 let _ = write.send(Message::Text(json)).await;
             }
 
-            // Send user joined notification
-            let joined_msg = SignalingMessage::UserJoined {
-                user_id: user_id_clone.clone(),
-                is_host,
-            };
-            let broadcast_msg = RealtimeMessage {
+            // Track presence so everyone's roster follows joins and disconnects
+            let track_msg = RealtimeMessage {
                 topic: channel_topic_clone.clone(),
-                event: "broadcast".to_string(),
+                event: "presence".to_string(),
                 payload: serde_json::json!({
-                    "type": "broadcast",
-                    "event": "signaling",
-                    "payload": joined_msg
+                    "type": "presence",
+                    "event": "track",
+                    "payload": PresenceMeta {
+                        user_id: user_id_clone.clone(),
+                        is_host,
+                        online_at: Some(chrono::Utc::now().to_rfc3339()),
+                    }
                 }),
                 reference: None,
             };
-            if let Ok(json) = serde_json::to_string(&broadcast_msg) {
+            if let Ok(json) = serde_json::to_string(&track_msg) {
                 let _ = write.send(Message::Text(json)).await;
             }
 
+            // Send user joined notification
+            let joined_msg = SignalingMessage::user_joined(&user_id_clone, is_host, None);
+            if let Some(broadcast_msg) = RealtimeMessage::signaling(
+                &channel_topic_clone,
+                SignalingEnvelope::unsequenced(joined_msg),
+                &cipher,
+            ) {
+                if let Ok(json) = serde_json::to_string(&broadcast_msg) {
+                    let _ = write.send(Message::Text(json)).await;
+                }
+            }
+
+            let mut heartbeat_interval = tokio::time::interval_at(
+                tokio::time::Instant::now() + HEARTBEAT_INTERVAL,
+                HEARTBEAT_INTERVAL,
+            );
+            let mut resend_interval = tokio::time::interval(RESEND_INTERVAL);
+
             // Handle outgoing signaling messages
             loop {
                 tokio::select! {
                     Some(signaling) = outgoing_rx.recv() => {
-                        let broadcast_msg = RealtimeMessage {
-                            topic: channel_topic_clone.clone(),
-                            event: "broadcast".to_string(),
-                            payload: serde_json::json!({
-                                "type": "broadcast",
-                                "event": "signaling",
-                                "payload": signaling
-                            }),
-                            reference: None,
+                        let envelope = delivery
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .prepare(signaling, Instant::now());
+                        let Some(broadcast_msg) =
+                            RealtimeMessage::signaling(&channel_topic_clone, envelope, &cipher)
+                        else {
+                            continue;
                         };
                         if let Ok(json) = serde_json::to_string(&broadcast_msg) {
                             if write.send(Message::Text(json)).await.is_err() {
@@ -317,6 +680,26 @@ impl RealtimeClient {
                             }
                         }
                     }
+                    _ = resend_interval.tick() => {
+                        // Retransmit messages the recipient hasn't acked yet
+                        let due = delivery
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .due(Instant::now());
+                        for envelope in due {
+                            tracing::debug!("Resending signaling message {:?}", envelope.seq);
+                            let Some(broadcast_msg) =
+                                RealtimeMessage::signaling(&channel_topic_clone, envelope, &cipher)
+                            else {
+                                continue;
+                            };
+                            if let Ok(json) = serde_json::to_string(&broadcast_msg) {
+                                if write.send(Message::Text(json)).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    }
                     Some(internal_msg) = internal_rx.recv() => {
                         if let Ok(json) = serde_json::to_string(&internal_msg) {
                             if write.send(Message::Text(json)).await.is_err() {
@@ -324,7 +707,7 @@ impl RealtimeClient {
                             }
                         }
                     }
-                    _ = tokio::time::sleep(tokio::time::Duration::from_millis(HEARTBEAT_INTERVAL_MS)) => {
+                    _ = heartbeat_interval.tick() => {
                         // Send heartbeat
                         let heartbeat = RealtimeMessage {
                             topic: "phoenix".to_string(),
@@ -356,25 +739,23 @@ impl RealtimeClient {
 
         if let Some(ref channel) = inner.current_channel {
             // Send user left notification
-            if let Some(ref tx) = inner.message_tx {
-                let leave_msg = RealtimeMessage {
-                    topic: format!("realtime:session:{}", channel),
-                    event: "broadcast".to_string(),
-                    payload: serde_json::json!({
-                        "type": "broadcast",
-                        "event": "signaling",
-                        "payload": SignalingMessage::UserLeft {
-                            user_id: user_id.to_string()
-                        }
+            if let (Some(tx), Some(cipher)) = (&inner.message_tx, &inner.cipher) {
+                let leave_msg = RealtimeMessage::signaling(
+                    &format!("realtime:session:{}", channel),
+                    SignalingEnvelope::unsequenced(SignalingMessage::UserLeft {
+                        user_id: user_id.to_string(),
                     }),
-                    reference: None,
-                };
-                let _ = tx.send(leave_msg).await;
+                    cipher,
+                );
+                if let Some(leave_msg) = leave_msg {
+                    let _ = tx.send(leave_msg).await;
+                }
             }
         }
 
         inner.current_channel = None;
         inner.message_tx = None;
+        inner.cipher = None;
         inner.state = ChannelState::Disconnected;
 
         Ok(())
@@ -388,3 +769,68 @@ impl Clone for RealtimeClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_presence_state_and_diff() {
+        let state = serde_json::json!({
+            "host-1": { "metas": [{ "phx_ref": "a", "user_id": "host-1", "is_host": true, "online_at": "2026-01-01T10:00:00Z" }] },
+            "viewer-1": { "metas": [{ "phx_ref": "b" }] }
+        });
+        let Some(PresenceUpdate::Sync(mut metas)) = parse_presence_event("presence_state", &state)
+        else {
+            panic!("expected a presence snapshot");
+        };
+        metas.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        assert_eq!(metas.len(), 2);
+        assert!(metas[0].is_host);
+        assert_eq!(metas[0].online_at.as_deref(), Some("2026-01-01T10:00:00Z"));
+        // Metas without user_id fall back to the presence key
+        assert_eq!(metas[1].user_id, "viewer-1");
+        assert!(!metas[1].is_host);
+
+        let diff = serde_json::json!({
+            "joins": { "viewer-2": { "metas": [{ "user_id": "viewer-2", "is_host": false }] } },
+            "leaves": {}
+        });
+        assert_eq!(
+            parse_presence_event("presence_diff", &diff),
+            Some(PresenceUpdate::Diff {
+                joins: vec![PresenceMeta {
+                    user_id: "viewer-2".to_string(),
+                    is_host: false,
+                    online_at: None,
+                }],
+                leaves: vec![],
+            })
+        );
+        assert_eq!(parse_presence_event("broadcast", &diff), None);
+    }
+
+    #[test]
+    fn test_moderation_request_is_addressed_to_host() {
+        let msg: SignalingMessage = serde_json::from_value(serde_json::json!({
+            "type": "moderation_request",
+            "from_user_id": "cohost-1",
+            "to_user_id": "host-1",
+            "action": { "kind": "grant_control", "user_id": "viewer-1" }
+        }))
+        .unwrap();
+        assert_eq!(msg.sender(), Some("cohost-1"));
+        assert!(msg.is_for("host-1"));
+        assert!(!msg.is_for("viewer-1"));
+        let SignalingMessage::ModerationRequest { action, .. } = msg else {
+            panic!("expected a moderation request");
+        };
+        assert_eq!(
+            action,
+            ModerationAction::GrantControl {
+                user_id: "viewer-1".to_string(),
+                duration_secs: None,
+            }
+        );
+    }
+}
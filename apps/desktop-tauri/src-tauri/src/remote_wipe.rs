@@ -0,0 +1,132 @@
+//! Remote wipe of a lost or stolen machine
+//!
+//! Each install has a random device ID, kept in the app data directory and
+//! registered for the signed-in user. An administrator flags the device from
+//! the dashboard; the app checks at startup, periodically and after signing
+//! in, and the next time it is online deletes its keychain entries, webview
+//! storage, caches and settings. What was removed is reported back to
+//! `device_wipe_audit`. Removing the data directory drops the device ID too,
+//! so a wiped machine signs in again as a new device.
+//!
+//! The check and the report go out signed out, so alongside the ID the app
+//! sends a random secret generated with it and kept in the keychain; the
+//! server only keeps its hash.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::Result;
+
+/// File in the app data directory holding the device ID
+pub const DEVICE_ID_FILE: &str = "device_id";
+
+/// Length of the device secret in bytes
+const DEVICE_SECRET_BYTES: usize = 32;
+
+/// The device ID saved by a previous launch, or a new one if there is none
+pub fn load_or_create_device_id(path: &Path) -> Result<String> {
+    if let Ok(saved) = std::fs::read_to_string(path) {
+        if let Ok(id) = uuid::Uuid::parse_str(saved.trim()) {
+            return Ok(id.to_string());
+        }
+        tracing::warn!("Replacing unreadable device ID");
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    save_device_id(path, &id)?;
+    Ok(id)
+}
+
+/// Save the device ID, also to keep it across a wipe that couldn't be
+/// reported, so the next launch finds the wipe still pending and reports it
+pub fn save_device_id(path: &Path, id: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, id)?;
+    Ok(())
+}
+
+/// New random device secret
+pub fn generate_device_secret() -> String {
+    (0..DEVICE_SECRET_BYTES)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect()
+}
+
+/// What a wipe removed, reported for the audit log
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WipeReport {
+    /// Whether the keychain entries were deleted
+    pub credentials: bool,
+    /// Whether the webview's storage was cleared
+    pub browsing_data: bool,
+    /// Local directories removed, by name
+    pub removed: Vec<String>,
+    /// What could not be removed, and why
+    pub failed: Vec<String>,
+}
+
+impl WipeReport {
+    /// Remove a directory and everything in it; one that doesn't exist
+    /// counts as removed
+    pub fn remove_dir(&mut self, name: &str, dir: &Path) {
+        match std::fs::remove_dir_all(dir) {
+            Ok(()) => self.removed.push(name.to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.removed.push(name.to_string())
+            }
+            Err(e) => self.failed.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.credentials && self.browsing_data && self.failed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_id_is_kept() {
+        let dir = std::env::temp_dir().join(format!("squadx-wipe-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(DEVICE_ID_FILE);
+
+        let id = load_or_create_device_id(&path).unwrap();
+        assert_eq!(load_or_create_device_id(&path).unwrap(), id);
+
+        std::fs::write(&path, "not a uuid").unwrap();
+        let replaced = load_or_create_device_id(&path).unwrap();
+        assert_ne!(replaced, id);
+        assert!(uuid::Uuid::parse_str(&replaced).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_device_secret() {
+        let secret = generate_device_secret();
+        assert_eq!(secret.len(), DEVICE_SECRET_BYTES * 2);
+        assert!(secret.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(generate_device_secret(), secret);
+    }
+
+    #[test]
+    fn test_remove_dir() {
+        let dir = std::env::temp_dir().join(format!("squadx-wipe-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("avatars")).unwrap();
+        std::fs::write(dir.join("avatars").join("a.png"), b"png").unwrap();
+
+        let mut report = WipeReport::default();
+        report.remove_dir("cache", &dir);
+        report.remove_dir("config", &dir.join("missing"));
+
+        assert!(!dir.exists());
+        assert_eq!(report.removed, vec!["cache", "config"]);
+        assert!(report.failed.is_empty());
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,308 @@
+//! Timeouts and retries of HTTP requests
+//!
+//! Requests go out with a timeout and, when the server is overloaded or
+//! briefly unavailable (5xx, or 429 with a `Retry-After`) or the connection
+//! fails, are sent again after an exponential backoff with jitter, honoring
+//! `Retry-After`. A 429 without one is an answer of its own (e.g. a
+//! throttled join code lookup) and goes back to the caller as is. A
+//! request that may have been applied on the server (a POST or PATCH that
+//! got a 500, 502 or 504, or timed out) is only sent again when its policy
+//! says it's safe to. When every attempt fails the caller gets
+//! `Error::RetriesExhausted`.
+
+use std::future::Future;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+
+use crate::perf;
+use crate::{Error, Result};
+
+/// Longest wait honored from a `Retry-After` header
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// How a request is sent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RequestPolicy {
+    /// Time allowed for each attempt, up to the end of the response body;
+    /// `None` for responses read as a stream
+    pub timeout: Option<Duration>,
+    /// Attempts after the first one
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for each later one
+    pub base_delay: Duration,
+    /// Longest backoff
+    pub max_delay: Duration,
+    /// Whether sending the request twice does no harm, whatever its method
+    pub idempotent: bool,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(15)),
+            max_retries: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+            idempotent: false,
+        }
+    }
+}
+
+impl RequestPolicy {
+    pub fn with_timeout(self, timeout: Option<Duration>) -> Self {
+        Self { timeout, ..self }
+    }
+
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Retry on any failure, e.g. for read-only RPCs sent as POST
+    pub fn idempotent(self) -> Self {
+        Self {
+            idempotent: true,
+            ..self
+        }
+    }
+
+    /// Backoff before retry number `retry` (0 for the first one), `jitter`
+    /// in `0.0..=1.0` picking a delay between half and all of it
+    pub fn backoff_delay(&self, retry: u32, jitter: f64) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        ceiling / 2 + ceiling.mul_f64(jitter.clamp(0.0, 1.0)) / 2
+    }
+}
+
+/// Send a request with its policy
+pub(crate) trait SendWithPolicy {
+    fn send_with(self, policy: &RequestPolicy) -> impl Future<Output = Result<Response>> + Send;
+}
+
+impl SendWithPolicy for RequestBuilder {
+    fn send_with(self, policy: &RequestPolicy) -> impl Future<Output = Result<Response>> + Send {
+        send(self, *policy)
+    }
+}
+
+async fn send(builder: RequestBuilder, policy: RequestPolicy) -> Result<Response> {
+    let (client, request) = builder.build_split();
+    let mut request = request.map_err(|e| Error::Network(e.to_string()))?;
+    let idempotent = policy.idempotent || is_idempotent(request.method());
+    let mut retries = 0;
+
+    loop {
+        // Bodies that can't be copied (streams) are sent once
+        let next = if retries < policy.max_retries {
+            request.try_clone()
+        } else {
+            None
+        };
+        *request.timeout_mut() = policy.timeout;
+        let outcome = client.execute(request).await;
+
+        let delay = match &outcome {
+            Ok(response)
+                if is_retryable_status(response.status(), response.headers(), idempotent) =>
+            {
+                retry_after(response.headers())
+            }
+            Err(e) if is_retryable_error(e, idempotent) => None,
+            _ => return outcome.map_err(|e| Error::Network(e.to_string())),
+        };
+        let Some(next) = next else {
+            return exhausted(outcome, retries).await;
+        };
+
+        let delay = delay.unwrap_or_else(|| policy.backoff_delay(retries, rand::random()));
+        tracing::debug!(
+            "Retrying {} in {:?} ({})",
+            next.url().path(),
+            delay,
+            describe(&outcome)
+        );
+        tokio::time::sleep(delay).await;
+
+        retries += 1;
+        request = next;
+        perf::record_supabase_request();
+    }
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+/// Statuses worth another attempt. 429 and 503 mean the request wasn't
+/// handled; after the other ones it may have been. A 429 is only retried
+/// when the server said when to, otherwise it's a refusal from the database
+/// (`PT429`) that another attempt would only count against
+fn is_retryable_status(status: StatusCode, headers: &HeaderMap, idempotent: bool) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => retry_after(headers).is_some(),
+        StatusCode::SERVICE_UNAVAILABLE => true,
+        StatusCode::INTERNAL_SERVER_ERROR
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::GATEWAY_TIMEOUT => idempotent,
+        _ => false,
+    }
+}
+
+/// Failures worth another attempt; a request that failed to connect was
+/// never sent
+fn is_retryable_error(error: &reqwest::Error, idempotent: bool) -> bool {
+    error.is_connect() || (idempotent && (error.is_timeout() || error.is_request()))
+}
+
+/// Wait asked for by a `Retry-After` header in seconds
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs: u64 = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+fn describe(outcome: &std::result::Result<Response, reqwest::Error>) -> String {
+    match outcome {
+        Ok(response) => response.status().to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// Outcome of a request that failed on its last attempt. A request that was
+/// sent only once keeps its usual response or error
+async fn exhausted(
+    outcome: std::result::Result<Response, reqwest::Error>,
+    retries: u32,
+) -> Result<Response> {
+    if retries == 0 {
+        return outcome.map_err(|e| Error::Network(e.to_string()));
+    }
+    let last_error = match outcome {
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            format!("{} - {}", status, body)
+        }
+        Err(e) => e.to_string(),
+    };
+    Err(Error::RetriesExhausted {
+        attempts: retries + 1,
+        last_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_delay() {
+        let policy = RequestPolicy::default();
+        assert_eq!(policy.backoff_delay(0, 0.0), Duration::from_millis(125));
+        assert_eq!(policy.backoff_delay(0, 1.0), Duration::from_millis(250));
+        assert_eq!(policy.backoff_delay(2, 1.0), Duration::from_secs(1));
+        // Capped at the longest backoff
+        assert_eq!(policy.backoff_delay(10, 1.0), Duration::from_secs(5));
+        assert_eq!(policy.backoff_delay(40, 0.5), Duration::from_millis(3750));
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        let headers = HeaderMap::new();
+        assert!(is_retryable_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &headers,
+            false
+        ));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY, &headers, true));
+        assert!(!is_retryable_status(
+            StatusCode::BAD_GATEWAY,
+            &headers,
+            false
+        ));
+        assert!(!is_retryable_status(
+            StatusCode::NOT_IMPLEMENTED,
+            &headers,
+            true
+        ));
+        assert!(!is_retryable_status(StatusCode::CONFLICT, &headers, true));
+    }
+
+    #[test]
+    fn test_too_many_requests_needs_retry_after() {
+        // A throttled lookup (PT429) has no Retry-After and isn't retried
+        let mut headers = HeaderMap::new();
+        assert!(!is_retryable_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers,
+            false
+        ));
+        assert!(!is_retryable_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers,
+            true
+        ));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("1"));
+        assert!(is_retryable_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers,
+            false
+        ));
+    }
+
+    #[test]
+    fn test_idempotent_methods() {
+        assert!(is_idempotent(&Method::GET));
+        assert!(is_idempotent(&Method::DELETE));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+        assert_eq!(retry_after(&headers), Some(MAX_RETRY_AFTER));
+        // HTTP dates fall back to the backoff
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2026 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_policy_overrides() {
+        let policy = RequestPolicy::default()
+            .with_timeout(None)
+            .with_max_retries(0)
+            .idempotent();
+        assert_eq!(policy.timeout, None);
+        assert_eq!(policy.max_retries, 0);
+        assert!(policy.idempotent);
+        assert_eq!(policy.base_delay, RequestPolicy::default().base_delay);
+    }
+}
@@ -0,0 +1,159 @@
+//! System resources
+//!
+//! Power source and battery level, read through the OS battery APIs, and the
+//! battery saver settings deciding when streaming slows down to save power.
+
+use serde::{Deserialize, Serialize};
+use starship_battery::units::ratio::percent;
+
+/// Frame rate range battery saver may cap capture at
+const MIN_SAVER_FPS: u32 = 10;
+const MAX_SAVER_FPS: u32 = 15;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct PowerStatus {
+    /// Running on battery rather than mains power
+    pub on_battery: bool,
+    /// Charge left across all batteries, `None` without a battery
+    pub battery_percent: Option<f32>,
+}
+
+/// Read the current power source; machines without a battery are on mains
+pub fn power_status() -> PowerStatus {
+    let batteries: Vec<starship_battery::Battery> =
+        match starship_battery::Manager::new().and_then(|manager| manager.batteries()) {
+            Ok(batteries) => batteries.flatten().collect(),
+            Err(e) => {
+                tracing::debug!("Failed to read battery state: {}", e);
+                return PowerStatus::default();
+            }
+        };
+    if batteries.is_empty() {
+        return PowerStatus::default();
+    }
+
+    let total: f32 = batteries
+        .iter()
+        .map(|b| b.state_of_charge().get::<percent>())
+        .sum();
+    PowerStatus {
+        on_battery: batteries
+            .iter()
+            .any(|b| b.state() == starship_battery::State::Discharging),
+        battery_percent: Some(total / batteries.len() as f32),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatterySaverMode {
+    /// On while on battery below the threshold
+    #[default]
+    Auto,
+    /// Forced on
+    On,
+    /// Forced off
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatterySaverSettings {
+    pub mode: BatterySaverMode,
+    /// Battery level under which `Auto` turns battery saver on
+    pub threshold_percent: u8,
+    /// Frame rate capture is capped at while battery saver is on
+    pub max_fps: u32,
+}
+
+impl Default for BatterySaverSettings {
+    fn default() -> Self {
+        Self {
+            mode: BatterySaverMode::Auto,
+            threshold_percent: 30,
+            max_fps: 12,
+        }
+    }
+}
+
+impl BatterySaverSettings {
+    /// Settings with the frame rate and threshold brought into range
+    pub fn clamped(self) -> Self {
+        Self {
+            threshold_percent: self.threshold_percent.min(100),
+            max_fps: self.max_fps.clamp(MIN_SAVER_FPS, MAX_SAVER_FPS),
+            ..self
+        }
+    }
+
+    /// Whether battery saver applies under the given power status
+    pub fn is_active(&self, power: &PowerStatus) -> bool {
+        match self.mode {
+            BatterySaverMode::On => true,
+            BatterySaverMode::Off => false,
+            BatterySaverMode::Auto => {
+                power.on_battery
+                    && power
+                        .battery_percent
+                        .is_some_and(|p| p < f32::from(self.threshold_percent))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_battery_saver_follows_battery_level() {
+        let settings = BatterySaverSettings::default();
+        let low = PowerStatus {
+            on_battery: true,
+            battery_percent: Some(20.0),
+        };
+        assert!(settings.is_active(&low));
+        // Charging, or still plenty left
+        assert!(!settings.is_active(&PowerStatus {
+            on_battery: false,
+            ..low
+        }));
+        assert!(!settings.is_active(&PowerStatus {
+            battery_percent: Some(80.0),
+            ..low
+        }));
+        // Desktops have no battery
+        assert!(!settings.is_active(&PowerStatus::default()));
+
+        let forced = BatterySaverSettings {
+            mode: BatterySaverMode::On,
+            ..settings
+        };
+        assert!(forced.is_active(&PowerStatus::default()));
+        let disabled = BatterySaverSettings {
+            mode: BatterySaverMode::Off,
+            ..settings
+        };
+        assert!(!disabled.is_active(&low));
+    }
+
+    #[test]
+    fn test_settings_clamped() {
+        let settings = BatterySaverSettings {
+            mode: BatterySaverMode::Auto,
+            threshold_percent: 150,
+            max_fps: 30,
+        }
+        .clamped();
+        assert_eq!(settings.threshold_percent, 100);
+        assert_eq!(settings.max_fps, 15);
+        assert_eq!(
+            BatterySaverSettings {
+                max_fps: 1,
+                ..settings
+            }
+            .clamped()
+            .max_fps,
+            10
+        );
+    }
+}
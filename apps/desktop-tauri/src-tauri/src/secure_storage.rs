@@ -6,10 +6,14 @@
 use keyring::Entry;
 use serde::{Deserialize, Serialize};
 
+use crate::state::Session;
 use crate::{Error, Result};
 
 const SERVICE_NAME: &str = "live.squadx.desktop";
 
+/// Throwaway entry written to check keychain access
+const PROBE_KEY: &str = "preflight_probe";
+
 /// Keys for stored credentials
 #[derive(Debug, Clone, Copy)]
 pub enum CredentialKey {
@@ -18,6 +22,15 @@ pub enum CredentialKey {
     UserId,
     Email,
     TokenExpiry,
+    ActiveSession,
+    /// Bearer token of the local automation API
+    AutomationToken,
+    /// This device's chat encryption key, with its device ID
+    DeviceKey,
+    /// Secret proving this is the device registered for remote wipe
+    DeviceSecret,
+    /// Key sealing this device's Google Calendar tokens
+    OAuthTokenKey,
 }
 
 impl CredentialKey {
@@ -28,6 +41,11 @@ impl CredentialKey {
             CredentialKey::UserId => "user_id",
             CredentialKey::Email => "email",
             CredentialKey::TokenExpiry => "token_expiry",
+            CredentialKey::ActiveSession => "active_session",
+            CredentialKey::AutomationToken => "automation_token",
+            CredentialKey::DeviceKey => "device_key",
+            CredentialKey::DeviceSecret => "device_secret",
+            CredentialKey::OAuthTokenKey => "oauth_token_key",
         }
     }
 }
@@ -42,6 +60,22 @@ pub struct StoredSession {
     pub expires_at: Option<i64>,
 }
 
+/// Session the app was in, kept so it can be resumed after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredActiveSession {
+    /// User the session belongs to
+    pub user_id: String,
+    /// Session ID, join code and whether the user hosts it
+    pub session: Session,
+    /// Whether signaling was end-to-end encrypted
+    pub encrypted: bool,
+    /// Viewers already let in, who skip the waiting room on resume
+    pub admitted: Vec<String>,
+    /// Capture source and frame rate being streamed, if any
+    pub stream: Option<(String, u32)>,
+    pub saved_at: String,
+}
+
 /// Public user info (safe to return to frontend)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SafeUserInfo {
@@ -118,11 +152,36 @@ pub fn clear_session() -> Result<()> {
     delete_credential(CredentialKey::UserId)?;
     delete_credential(CredentialKey::Email)?;
     delete_credential(CredentialKey::TokenExpiry)?;
+    delete_credential(CredentialKey::ActiveSession)?;
 
     tracing::debug!("Session cleared from keychain");
     Ok(())
 }
 
+/// Store the session to resume after a restart
+pub fn store_active_session(session: &StoredActiveSession) -> Result<()> {
+    let json = serde_json::to_string(session)?;
+    store_credential(CredentialKey::ActiveSession, &json)
+}
+
+/// Retrieve the session to resume, if any
+pub fn get_active_session() -> Option<StoredActiveSession> {
+    let json = get_credential(CredentialKey::ActiveSession)?;
+    match serde_json::from_str(&json) {
+        Ok(session) => Some(session),
+        Err(e) => {
+            tracing::warn!("Discarding unreadable stored session: {}", e);
+            let _ = delete_credential(CredentialKey::ActiveSession);
+            None
+        }
+    }
+}
+
+/// Forget the session to resume
+pub fn clear_active_session() -> Result<()> {
+    delete_credential(CredentialKey::ActiveSession)
+}
+
 /// Get safe user info (without tokens)
 pub fn get_safe_user_info() -> Option<SafeUserInfo> {
     let user_id = get_credential(CredentialKey::UserId)?;
@@ -148,6 +207,22 @@ pub fn is_session_expired() -> bool {
     false
 }
 
+/// Check the keychain can be written and read, with a throwaway entry
+pub fn check_access() -> Result<()> {
+    let entry = Entry::new(SERVICE_NAME, PROBE_KEY)
+        .map_err(|e| Error::Storage(format!("Failed to create keyring entry: {}", e)))?;
+
+    entry
+        .set_password("ok")
+        .map_err(|e| Error::Storage(format!("Keychain is not writable: {}", e)))?;
+    let read = entry
+        .get_password()
+        .map_err(|e| Error::Storage(format!("Keychain is not readable: {}", e)));
+    let _ = entry.delete_credential();
+
+    read.map(|_| ())
+}
+
 /// Check if we have stored credentials
 pub fn has_stored_credentials() -> bool {
     get_credential(CredentialKey::AccessToken).is_some()
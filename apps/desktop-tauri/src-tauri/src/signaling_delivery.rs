@@ -0,0 +1,311 @@
+//! Reliable delivery of signaling messages
+//!
+//! Realtime broadcasts are fire-and-forget: a frame sent before the peer has
+//! finished joining the channel, or while its socket is down, is lost, and a
+//! single missing offer, answer or ICE candidate stalls the negotiation.
+//! Those messages are therefore sent with a sequence number, acknowledged by
+//! their recipient and retransmitted until the ack arrives. Peers on builds
+//! without acks are sent the same messages unsequenced.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::realtime::SignalingMessage;
+use crate::signaling_protocol::CAP_RELIABLE_DELIVERY;
+
+/// Delay before an unacknowledged message is sent again
+pub const RESEND_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Transmissions of a message before giving up on its recipient
+const MAX_ATTEMPTS: u32 = 8;
+
+/// Wire format of a signaling broadcast
+///
+/// The sequence number sits next to the message's own fields, so clients that
+/// don't know about it still parse the message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalingEnvelope {
+    #[serde(flatten)]
+    pub message: SignalingMessage,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+}
+
+impl SignalingEnvelope {
+    pub fn unsequenced(message: SignalingMessage) -> Self {
+        Self { message, seq: None }
+    }
+}
+
+/// Outcome of processing an incoming envelope
+#[derive(Debug, Default)]
+pub struct Received {
+    /// Ack to send back to the sender
+    pub ack: Option<SignalingMessage>,
+    /// Message to hand to the application, `None` for acks and duplicates
+    pub message: Option<SignalingMessage>,
+}
+
+struct Pending {
+    envelope: SignalingEnvelope,
+    last_sent: Instant,
+    attempts: u32,
+}
+
+/// Per-connection sequencing, ack and retransmission bookkeeping
+#[derive(Default)]
+pub struct Delivery {
+    next_seq: u64,
+    pending: BTreeMap<u64, Pending>,
+    /// Sequence numbers already delivered, per sender
+    received: HashMap<String, HashSet<u64>>,
+    /// Peers whose build never acks
+    no_ack_peers: HashSet<String>,
+}
+
+/// Sender of a message that must be acknowledged
+fn reliable_sender(message: &SignalingMessage) -> Option<&str> {
+    match message {
+        SignalingMessage::Offer { from_user_id, .. }
+        | SignalingMessage::Answer { from_user_id, .. }
+        | SignalingMessage::IceCandidate { from_user_id, .. }
+        | SignalingMessage::JoinResponse { from_user_id, .. }
+        | SignalingMessage::ParticipantRemoved { from_user_id, .. }
+        | SignalingMessage::CohostChanged { from_user_id, .. }
+        | SignalingMessage::HostTransferred { from_user_id, .. }
+        | SignalingMessage::ModerationRequest { from_user_id, .. }
+        | SignalingMessage::ChatMuted { from_user_id, .. } => Some(from_user_id),
+        _ => None,
+    }
+}
+
+impl Delivery {
+    /// Wrap an outgoing message, tracking it until acked when it needs one
+    pub fn prepare(&mut self, message: SignalingMessage, now: Instant) -> SignalingEnvelope {
+        let expects_ack = reliable_sender(&message).is_some()
+            && !matches!(message.recipient(), Some(to) if self.no_ack_peers.contains(to));
+        if !expects_ack {
+            return SignalingEnvelope::unsequenced(message);
+        }
+
+        self.next_seq += 1;
+        let envelope = SignalingEnvelope {
+            message,
+            seq: Some(self.next_seq),
+        };
+        self.pending.insert(
+            self.next_seq,
+            Pending {
+                envelope: envelope.clone(),
+                last_sent: now,
+                attempts: 1,
+            },
+        );
+        envelope
+    }
+
+    /// Process an incoming envelope
+    ///
+    /// A broadcast message is acked by every recipient; the first ack clears it.
+    pub fn receive(&mut self, envelope: SignalingEnvelope, local_user_id: &str) -> Received {
+        let SignalingEnvelope { message, seq } = envelope;
+
+        match &message {
+            SignalingMessage::Ack {
+                seq, to_user_id, ..
+            } => {
+                if to_user_id == local_user_id {
+                    self.pending.remove(seq);
+                }
+                return Received::default();
+            }
+            SignalingMessage::UserJoined {
+                user_id,
+                to_user_id,
+                capabilities,
+                ..
+            } => {
+                // A rejoining peer restarts its sequence numbers
+                if to_user_id.is_none() {
+                    self.received.remove(user_id);
+                }
+                if capabilities.iter().any(|c| c == CAP_RELIABLE_DELIVERY) {
+                    self.no_ack_peers.remove(user_id);
+                } else {
+                    self.no_ack_peers.insert(user_id.clone());
+                }
+            }
+            _ => {}
+        }
+
+        let (Some(seq), Some(sender)) = (seq, reliable_sender(&message)) else {
+            return Received {
+                ack: None,
+                message: Some(message),
+            };
+        };
+        if !message.is_for(local_user_id) {
+            return Received {
+                ack: None,
+                message: Some(message),
+            };
+        }
+
+        let ack = SignalingMessage::Ack {
+            seq,
+            from_user_id: local_user_id.to_string(),
+            to_user_id: sender.to_string(),
+        };
+        let is_new = self
+            .received
+            .entry(sender.to_string())
+            .or_default()
+            .insert(seq);
+
+        Received {
+            ack: Some(ack),
+            message: is_new.then_some(message),
+        }
+    }
+
+    /// Messages whose ack is overdue, to be sent again
+    pub fn due(&mut self, now: Instant) -> Vec<SignalingEnvelope> {
+        let mut expired = Vec::new();
+        let mut due = Vec::new();
+
+        for (seq, pending) in self.pending.iter_mut() {
+            if now.duration_since(pending.last_sent) < RESEND_INTERVAL {
+                continue;
+            }
+            if pending.attempts >= MAX_ATTEMPTS {
+                expired.push(*seq);
+                continue;
+            }
+            pending.attempts += 1;
+            pending.last_sent = now;
+            due.push(pending.envelope.clone());
+        }
+
+        for seq in expired {
+            tracing::warn!(
+                "Signaling message {} was never acknowledged, giving up",
+                seq
+            );
+            self.pending.remove(&seq);
+        }
+
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(to: Option<&str>) -> SignalingMessage {
+        SignalingMessage::Offer {
+            sdp: "v=0".to_string(),
+            from_user_id: "host".to_string(),
+            to_user_id: to.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_envelope_keeps_message_shape() {
+        let envelope = SignalingEnvelope {
+            message: offer(Some("viewer")),
+            seq: Some(3),
+        };
+        let json = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(json["type"], "offer");
+        assert_eq!(json["from_user_id"], "host");
+        assert_eq!(json["seq"], 3);
+
+        // Clients without sequencing parse the same frame
+        let message: SignalingMessage = serde_json::from_value(json.clone()).unwrap();
+        assert!(matches!(message, SignalingMessage::Offer { .. }));
+        let parsed: SignalingEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.seq, Some(3));
+    }
+
+    #[test]
+    fn test_ack_clears_pending_and_duplicates_are_dropped() {
+        let start = Instant::now();
+        let mut host = Delivery::default();
+        let mut viewer = Delivery::default();
+
+        let envelope = host.prepare(offer(Some("viewer")), start);
+        assert_eq!(envelope.seq, Some(1));
+
+        let received = viewer.receive(envelope.clone(), "viewer");
+        assert!(received.message.is_some());
+        let ack = received.ack.expect("offer must be acked");
+
+        // A retransmission is acked again but not delivered twice
+        let duplicate = viewer.receive(envelope, "viewer");
+        assert!(duplicate.ack.is_some());
+        assert!(duplicate.message.is_none());
+
+        let ack_received = host.receive(SignalingEnvelope::unsequenced(ack), "host");
+        assert!(ack_received.message.is_none());
+        assert!(host.due(start + RESEND_INTERVAL * 2).is_empty());
+    }
+
+    #[test]
+    fn test_unacked_messages_are_resent_then_dropped() {
+        let start = Instant::now();
+        let mut delivery = Delivery::default();
+        delivery.prepare(offer(None), start);
+
+        // Unreliable messages are never tracked
+        let control = delivery.prepare(
+            SignalingMessage::ControlRequest {
+                from_user_id: "viewer".to_string(),
+            },
+            start,
+        );
+        assert_eq!(control.seq, None);
+
+        assert!(delivery.due(start).is_empty());
+        let mut now = start;
+        for _ in 1..MAX_ATTEMPTS {
+            now += RESEND_INTERVAL;
+            assert_eq!(delivery.due(now).len(), 1);
+        }
+        now += RESEND_INTERVAL;
+        assert!(delivery.due(now).is_empty());
+        assert!(delivery.pending.is_empty());
+    }
+
+    #[test]
+    fn test_peers_without_acks_get_unsequenced_messages() {
+        let start = Instant::now();
+        let mut host = Delivery::default();
+
+        let legacy_join = SignalingMessage::UserJoined {
+            user_id: "viewer".to_string(),
+            is_host: false,
+            to_user_id: None,
+            public_key: None,
+            protocol_version: 0,
+            capabilities: Vec::new(),
+        };
+        host.receive(SignalingEnvelope::unsequenced(legacy_join), "host");
+        assert_eq!(host.prepare(offer(Some("viewer")), start).seq, None);
+        assert!(host.pending.is_empty());
+
+        // The same user back on a current build gets reliable delivery again
+        let join = SignalingMessage::user_joined("viewer", false, None);
+        host.receive(SignalingEnvelope::unsequenced(join), "host");
+        assert_eq!(host.prepare(offer(Some("viewer")), start).seq, Some(1));
+    }
+
+    #[test]
+    fn test_unknown_message_types_parse_as_unknown() {
+        let json = serde_json::json!({ "type": "screen_annotation", "from_user_id": "viewer" });
+        let envelope: SignalingEnvelope = serde_json::from_value(json).unwrap();
+        assert!(matches!(envelope.message, SignalingMessage::Unknown));
+    }
+}
@@ -0,0 +1,135 @@
+//! Signaling protocol versioning
+//!
+//! Every client announces the protocol version it speaks and the optional
+//! features it supports in its `UserJoined`. Builds that predate versioning
+//! send neither and count as version 1 without capabilities. Peers below
+//! `MIN_PROTOCOL_VERSION` are refused; a peer missing a capability gets the
+//! fallback for that feature instead (no acks expected, no file offers...).
+
+use serde::Serialize;
+
+/// Protocol version spoken by this build
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest protocol version we still talk to
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Version assumed for peers that don't announce one
+const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// Sequenced offers, answers and ICE candidates are acked
+pub const CAP_RELIABLE_DELIVERY: &str = "reliable_delivery";
+/// Signaling payloads may be end-to-end encrypted
+pub const CAP_E2E: &str = "e2e";
+/// Understands join responses from the waiting room
+pub const CAP_WAITING_ROOM: &str = "waiting_room";
+/// Shows the countdown of timed control grants
+pub const CAP_TIMED_CONTROL: &str = "timed_control";
+/// Sends and receives files over the peer data channel
+pub const CAP_FILE_TRANSFER: &str = "file_transfer";
+/// Follows host transfers and co-host promotions
+pub const CAP_HOST_HANDOFF: &str = "host_handoff";
+/// Sends and plays voice on the peer connection, follows mutes
+pub const CAP_VOICE: &str = "voice";
+/// Announces camera streams and renders camera tiles
+pub const CAP_CAMERA: &str = "camera";
+/// Sends drawings on the annotation data channel
+pub const CAP_ANNOTATIONS: &str = "annotations";
+/// Carries out co-hosts' moderation and shares the waiting room with them
+pub const CAP_COHOST_MODERATION: &str = "cohost_moderation";
+
+const LOCAL_CAPABILITIES: [&str; 10] = [
+    CAP_RELIABLE_DELIVERY,
+    CAP_E2E,
+    CAP_WAITING_ROOM,
+    CAP_TIMED_CONTROL,
+    CAP_FILE_TRANSFER,
+    CAP_HOST_HANDOFF,
+    CAP_VOICE,
+    CAP_CAMERA,
+    CAP_ANNOTATIONS,
+    CAP_COHOST_MODERATION,
+];
+
+/// Capabilities announced by this build
+pub fn local_capabilities() -> Vec<String> {
+    LOCAL_CAPABILITIES.iter().map(|c| c.to_string()).collect()
+}
+
+/// Protocol a peer announced
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeerProtocol {
+    pub version: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl PeerProtocol {
+    /// Protocol of this build
+    pub fn local() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            capabilities: local_capabilities(),
+        }
+    }
+
+    /// Protocol from the fields of a `UserJoined`
+    pub fn from_announcement(version: u32, capabilities: &[String]) -> Self {
+        Self {
+            version: if version == 0 {
+                LEGACY_PROTOCOL_VERSION
+            } else {
+                version
+            },
+            capabilities: capabilities.to_vec(),
+        }
+    }
+
+    /// Whether we can talk to the peer at all
+    pub fn is_supported(&self) -> bool {
+        self.version >= MIN_PROTOCOL_VERSION
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Our capabilities the peer lacks
+    pub fn missing_capabilities(&self) -> Vec<&'static str> {
+        LOCAL_CAPABILITIES
+            .iter()
+            .copied()
+            .filter(|c| !self.supports(c))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unversioned_peer_is_legacy() {
+        let peer = PeerProtocol::from_announcement(0, &[]);
+        assert_eq!(peer.version, LEGACY_PROTOCOL_VERSION);
+        assert!(peer.is_supported());
+        assert!(!peer.supports(CAP_RELIABLE_DELIVERY));
+        assert_eq!(peer.missing_capabilities().len(), LOCAL_CAPABILITIES.len());
+    }
+
+    #[test]
+    fn test_missing_capabilities() {
+        let peer = PeerProtocol::from_announcement(
+            2,
+            &[
+                CAP_RELIABLE_DELIVERY.to_string(),
+                "screen_annotations".to_string(),
+            ],
+        );
+        assert!(peer.supports(CAP_RELIABLE_DELIVERY));
+        assert!(!peer.supports(CAP_FILE_TRANSFER));
+        assert!(peer.missing_capabilities().contains(&CAP_FILE_TRANSFER));
+        assert!(!peer.missing_capabilities().contains(&CAP_RELIABLE_DELIVERY));
+
+        assert!(PeerProtocol::local().missing_capabilities().is_empty());
+    }
+}
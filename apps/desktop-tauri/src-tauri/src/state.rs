@@ -2,6 +2,9 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::cache::SharedCache;
+use crate::capture::SharedAppLock;
+use crate::join_throttle::SharedJoinThrottle;
+use crate::lan::LanHost;
 use crate::supabase::SupabaseClient;
 
 #[derive(Debug, Clone)]
@@ -9,6 +12,9 @@ pub struct AppState {
     pub inner: Arc<RwLock<AppStateInner>>,
     pub supabase: Option<SupabaseClient>,
     pub cache: SharedCache,
+    pub app_lock: SharedAppLock,
+    /// Failed join attempts, to slow down join code guessing
+    pub join_throttle: SharedJoinThrottle,
 }
 
 impl Default for AppState {
@@ -17,6 +23,8 @@ impl Default for AppState {
             inner: Arc::new(RwLock::new(AppStateInner::default())),
             supabase: SupabaseClient::from_env_optional(),
             cache: crate::cache::create_shared_cache(),
+            app_lock: SharedAppLock::default(),
+            join_throttle: SharedJoinThrottle::default(),
         }
     }
 }
@@ -43,11 +51,41 @@ pub struct Session {
     pub join_code: String,
     pub is_host: bool,
     pub status: SessionStatus,
+    #[serde(default)]
+    pub mode: SessionMode,
+    /// Host to connect to, for viewers of a LAN session
+    #[serde(default)]
+    pub lan_host: Option<LanHost>,
+    /// Time alone in the session after which the host ends it, 0 for never
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+    /// When the session ends regardless of activity (RFC 3339)
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Whether the host closed the session to new joins
+    #[serde(default)]
+    pub locked: bool,
+    /// Conversation the session chat is copied to
+    #[serde(default)]
+    pub bridge_conversation_id: Option<String>,
+}
+
+/// How the peers of a session reach each other
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionMode {
+    /// Stored and signaled through Supabase
+    #[default]
+    Cloud,
+    /// Signaled directly on the local network, without Supabase
+    Lan,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SessionStatus {
+    /// Joined with a valid code, waiting for the host to admit us
+    Waiting,
     Active,
     Paused,
     Ended,
@@ -1,24 +1,68 @@
-use reqwest::Client;
+use reqwest::{Client, IntoUrl, RequestBuilder};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+use crate::change_feed::FeedPosition;
+use crate::ice::TurnCredentials;
+use crate::notifications::ConversationNotificationPrefs;
+use crate::pagination::{MessageCursor, PageDirection};
+use crate::perf;
+use crate::request_policy::{RequestPolicy, SendWithPolicy};
 use crate::{Error, Result};
 
 const SUPABASE_URL_ENV: &str = "VITE_SUPABASE_URL";
 const SUPABASE_ANON_KEY_ENV: &str = "VITE_SUPABASE_ANON_KEY";
+const SUPABASE_TIMEOUT_ENV: &str = "SUPABASE_TIMEOUT_SECS";
+const SUPABASE_MAX_RETRIES_ENV: &str = "SUPABASE_MAX_RETRIES";
+
+/// Time allowed to connect, whatever the request's policy
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed to upload a storage object
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(300);
 
 #[derive(Debug, Clone)]
 pub struct SupabaseClient {
     inner: Arc<SupabaseClientInner>,
 }
 
+/// HTTP client that counts the requests issued by each command
+#[derive(Debug)]
+struct InstrumentedClient(Client);
+
+impl InstrumentedClient {
+    fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        perf::record_supabase_request();
+        self.0.get(url)
+    }
+
+    fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        perf::record_supabase_request();
+        self.0.post(url)
+    }
+
+    fn patch<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        perf::record_supabase_request();
+        self.0.patch(url)
+    }
+
+    fn delete<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        perf::record_supabase_request();
+        self.0.delete(url)
+    }
+}
+
 #[derive(Debug)]
 struct SupabaseClientInner {
-    client: Client,
+    client: InstrumentedClient,
     base_url: String,
     anon_key: String,
     access_token: RwLock<Option<String>>,
+    /// How requests are sent unless a call says otherwise
+    policy: RequestPolicy,
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,6 +78,27 @@ pub struct SessionRow {
     pub join_code: String,
     pub status: String,
     pub created_at: Option<String>,
+    /// Users the host banned from the session
+    #[serde(default)]
+    pub banned_user_ids: Vec<String>,
+    /// Participants named to take over if the host leaves
+    #[serde(default)]
+    pub cohost_ids: Vec<String>,
+    /// Last time the host reported the session in use
+    #[serde(default)]
+    pub last_activity_at: Option<String>,
+    /// Idle time after which the session ends, 0 for never
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+    /// When the session ends regardless of activity
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Whether the host closed the session to new joins
+    #[serde(default)]
+    pub locked: bool,
+    /// Conversation the session chat is copied to
+    #[serde(default)]
+    pub bridge_conversation_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -41,6 +106,20 @@ struct CreateSessionPayload {
     host_id: String,
     join_code: String,
     status: String,
+    idle_timeout_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
+}
+
+/// Chat message sent during a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessageRow {
+    pub id: String,
+    pub session_id: String,
+    pub sender_id: Option<String>,
+    pub sender_name: String,
+    pub content: String,
+    pub sent_at: String,
 }
 
 // ==========================================
@@ -57,6 +136,16 @@ pub struct ConversationRow {
     pub created_by: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    /// Meeting the conversation was created from
+    #[serde(default)]
+    pub meeting_id: Option<String>,
+    /// When the current user archived the conversation, from their
+    /// participant row
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    /// Whether messages are end-to-end encrypted
+    #[serde(default)]
+    pub encrypted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +167,72 @@ pub struct MessageRow {
     pub message_type: String,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<MessageAttachment>,
+    /// Top-level message this replies to
+    #[serde(default)]
+    pub parent_message_id: Option<String>,
+    #[serde(default)]
+    pub reply_count: u32,
+    #[serde(default)]
+    pub last_reply_at: Option<String>,
+    /// Session whose chat the message was copied from
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Participant who has seen a message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageReadRow {
+    pub user_id: String,
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub read_at: String,
+}
+
+/// Unread messages of a conversation, as counted by `get_unread_counts`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnreadCountRow {
+    pub conversation_id: String,
+    /// Unread messages in the main list
+    pub unread_count: u32,
+    /// Unread replies in threads the user follows
+    pub unread_thread_replies: u32,
+}
+
+/// File attached to a message, stored in the chat attachments bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageAttachment {
+    /// Object path in the bucket
+    pub path: String,
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+    /// Pixel size of images
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// URL shared in a conversation, indexed when its message was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationLinkRow {
+    pub id: String,
+    pub conversation_id: String,
+    pub message_id: String,
+    pub sender_id: Option<String>,
+    pub url: String,
+    /// Page title, when it could be fetched
+    pub title: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewConversationLink {
+    pub conversation_id: String,
+    pub message_id: String,
+    pub sender_id: String,
+    pub url: String,
+    pub title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,9 +248,36 @@ pub struct UserProfileRow {
 pub struct UserPresenceRow {
     pub user_id: String,
     pub status: String,
+    /// Custom text shown next to the status
+    #[serde(default)]
+    pub status_message: Option<String>,
     pub last_seen_at: Option<String>,
 }
 
+/// Span of time a user held one presence status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresencePeriodRow {
+    pub status: String,
+    pub started_at: String,
+    /// None while this is the user's current status
+    pub ended_at: Option<String>,
+}
+
+/// A user the signed-in user blocked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserBlockRow {
+    pub blocked_id: String,
+    pub created_at: String,
+}
+
+/// A device's published chat encryption key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceKeyRow {
+    pub user_id: String,
+    pub device_id: String,
+    pub public_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParticipantWithProfile {
     pub user_id: String,
@@ -103,6 +285,8 @@ pub struct ParticipantWithProfile {
     pub avatar_url: Option<String>,
     pub role: String,
     pub is_online: bool,
+    pub status: String,
+    pub status_message: Option<String>,
     pub last_seen_at: Option<String>,
 }
 
@@ -112,6 +296,8 @@ struct CreateConversationPayload {
     conversation_type: String,
     name: Option<String>,
     created_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meeting_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -127,6 +313,10 @@ struct CreateMessagePayload {
     sender_id: String,
     content: String,
     message_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<MessageAttachment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_message_id: Option<String>,
 }
 
 // ==========================================
@@ -145,11 +335,49 @@ pub struct MeetingRow {
     pub session_id: Option<String>,
     pub recurrence_rule: Option<String>,
     pub recurrence_parent_id: Option<String>,
+    /// Occurrences removed from the recurrence (EXDATE)
+    #[serde(default)]
+    pub recurrence_exdates: Vec<String>,
+    /// Occurrences added to the recurrence (RDATE)
+    #[serde(default)]
+    pub recurrence_rdates: Vec<String>,
+    /// IANA time zone the meeting is scheduled in; UTC when unset
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Address or place the meeting is held at
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Video call link
+    #[serde(default)]
+    pub meeting_url: Option<String>,
+    #[serde(default)]
+    pub room: Option<String>,
+    /// Start the meeting's session at the scheduled time
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Ask Google Calendar for a Meet link when syncing the meeting
+    #[serde(default)]
+    pub google_meet: bool,
+    /// Join code of the session the meeting started
+    #[serde(default)]
+    pub join_code: Option<String>,
+    /// Tag of the organizer the meeting is categorized under
+    #[serde(default)]
+    pub tag_id: Option<String>,
+    /// `#rrggbb` color, the tag's when tagged
+    #[serde(default)]
+    pub color: Option<String>,
     pub google_event_id: Option<String>,
     pub google_calendar_id: Option<String>,
+    /// Outlook event the meeting was synced to
+    #[serde(default)]
+    pub outlook_event_id: Option<String>,
     pub reminder_sent: Option<bool>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    /// Group conversation created for the meeting
+    #[serde(default)]
+    pub conversation_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -161,6 +389,11 @@ pub struct MeetingAttendeeRow {
     pub responded_at: Option<String>,
     pub notification_sent: Option<bool>,
     pub created_at: Option<String>,
+    /// "required" or "optional"; required when unset
+    #[serde(default)]
+    pub attendance_type: Option<String>,
+    #[serde(default)]
+    pub rsvp_deadline: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,6 +403,34 @@ pub struct AttendeeWithProfile {
     pub avatar_url: Option<String>,
     pub response_status: String,
     pub responded_at: Option<String>,
+    pub attendance_type: String,
+    pub rsvp_deadline: Option<String>,
+}
+
+/// An invited attendee who let the RSVP deadline pass without answering
+/// External attendee invited by email rather than by user id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingGuestRow {
+    pub id: String,
+    pub meeting_id: String,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub response_status: String,
+    pub responded_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissedRsvpRow {
+    pub meeting_id: String,
+    pub user_id: String,
+    pub rsvp_deadline: String,
+    pub meetings: MissedRsvpMeeting,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissedRsvpMeeting {
+    pub title: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,11 +439,293 @@ pub struct GoogleTokensRow {
     pub access_token: String,
     pub refresh_token: String,
     pub expires_at: String,
+    /// Calendar meetings are synced to; the primary one when unset
     pub calendar_id: Option<String>,
+    /// Calendars imports and free/busy checks read from, besides
+    /// `calendar_id`
+    #[serde(default)]
+    pub selected_calendar_ids: Vec<String>,
+    pub email: Option<String>,
+    pub sync_enabled: Option<bool>,
+    /// Google's token for the events changed since the last background sync
+    #[serde(default)]
+    pub sync_token: Option<String>,
+    /// Change feed cursor for the meetings changed since the last sync
+    #[serde(default)]
+    pub sync_cursor: Option<String>,
+    #[serde(default)]
+    pub last_synced_at: Option<String>,
+    /// "latest_wins", "prefer_local" or "prefer_remote"
+    #[serde(default)]
+    pub conflict_policy: Option<String>,
+}
+
+/// Meeting kept in sync with a Google event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleEventLinkRow {
+    pub user_id: String,
+    pub meeting_id: String,
+    pub google_event_id: String,
+    /// Event etag as of the last sync
+    pub etag: Option<String>,
+    /// Meeting `updated_at` as of the last sync
+    pub meeting_updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlookTokensRow {
+    pub user_id: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: String,
     pub email: Option<String>,
     pub sync_enabled: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingAgendaItemRow {
+    pub id: String,
+    pub meeting_id: String,
+    pub title: String,
+    pub duration_minutes: i32,
+    pub presenter_id: Option<String>,
+    pub position: i32,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingNotesRow {
+    pub meeting_id: String,
+    pub content: String,
+    /// Bumped by the database on every update
+    pub version: i32,
+    pub updated_by: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingActionItemRow {
+    pub id: String,
+    pub meeting_id: String,
+    pub title: String,
+    pub assignee_id: Option<String>,
+    /// YYYY-MM-DD
+    pub due_date: Option<String>,
+    pub completed_at: Option<String>,
+    pub completed_by: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Meeting the item came from, when embedded
+    #[serde(default)]
+    pub meetings: Option<ActionItemMeeting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItemMeeting {
+    pub title: String,
+    pub scheduled_at: String,
+}
+
+/// File attached to a meeting, stored in the meeting attachments bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingAttachmentRow {
+    pub id: String,
+    pub meeting_id: String,
+    pub uploaded_by: Option<String>,
+    /// Object path in the bucket
+    pub path: String,
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+    pub created_at: Option<String>,
+}
+
+/// Colored tag a user categorizes their meetings with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingTagRow {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub color: String,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewMeetingAttachment {
+    pub meeting_id: String,
+    pub uploaded_by: String,
+    pub path: String,
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+}
+
+/// Changes to an action item: `Some(None)` clears a field and `None` leaves
+/// it unchanged
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ActionItemUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee_id: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Option<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityBlockRow {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub starts_at: String,
+    pub duration_minutes: i32,
+    pub slot_minutes: i32,
+    pub recurrence_rule: String,
+    /// IANA time zone the block repeats in
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    pub is_active: Option<bool>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityBookingRow {
+    pub scheduled_at: String,
+    pub duration_minutes: i32,
+}
+
+/// A meeting keeping one of the given users busy, without its details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusyMeetingRow {
+    pub user_id: String,
+    pub scheduled_at: String,
+    pub duration_minutes: i32,
+    pub recurrence_rule: Option<String>,
+    #[serde(default)]
+    pub recurrence_exdates: Vec<String>,
+    #[serde(default)]
+    pub recurrence_rdates: Vec<String>,
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderTelemetryRow {
+    pub id: String,
+    pub user_id: String,
+    pub meeting_id: String,
+    pub offset_minutes: i32,
+    pub fired_at: String,
+    pub joined_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderPreferencesRow {
+    pub user_id: String,
+    pub reminder_offset_minutes: i32,
+    pub auto_adjust: Option<bool>,
+    /// Earlier reminders, in minutes before the meeting
+    #[serde(default)]
+    pub extra_offsets_minutes: Vec<i32>,
+}
+
+/// Working hours of a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserAvailabilityRow {
+    pub user_id: String,
+    pub time_zone: Option<String>,
+    pub day_start_hour: i32,
+    pub day_end_hour: i32,
+    /// ISO weekdays, Monday = 1 to Sunday = 7
+    pub working_days: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulingPollRow {
+    pub id: String,
+    pub conversation_id: String,
+    pub created_by: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub duration_minutes: i32,
+    pub status: String,
+    pub meeting_id: Option<String>,
+    pub created_at: Option<String>,
+    pub closed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulingPollOptionRow {
+    pub id: String,
+    pub poll_id: String,
+    pub starts_at: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulingPollVoteRow {
+    pub poll_id: String,
+    pub option_id: String,
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeedTokenRow {
+    pub user_id: String,
+    pub token: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub last_accessed_at: Option<String>,
+}
+
+/// Team-wide custom emoji, written `:shortcode:` in messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomEmojiRow {
+    pub id: String,
+    pub shortcode: String,
+    pub storage_path: String,
+    pub image_url: String,
+    pub created_by: String,
+    pub created_at: Option<String>,
+}
+
+/// Maintenance, deprecation or general notice polled by the app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnouncementRow {
+    pub id: String,
+    pub kind: String,
+    pub severity: String,
+    pub title: String,
+    pub message: String,
+    pub url: Option<String>,
+    pub starts_at: String,
+    pub ends_at: Option<String>,
+    pub maintenance_starts_at: Option<String>,
+    pub maintenance_ends_at: Option<String>,
+    pub below_version: Option<String>,
+    pub platforms: Option<Vec<String>>,
+    pub dismissible: bool,
+}
+
+/// Wipe an administrator requested for this device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDeviceWipeRow {
+    pub wipe_requested_at: String,
+    pub wipe_reason: Option<String>,
+}
+
+/// Tombstone of a meeting, conversation or message the user can no longer see
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedEntityRow {
+    pub id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub updated_at: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 struct CreateMeetingPayload {
     organizer_id: String,
@@ -195,6 +738,37 @@ struct CreateMeetingPayload {
     recurrence_rule: Option<String>,
 }
 
+/// Changes to where a meeting takes place: `Some(None)` clears a field and
+/// `None` leaves it unchanged
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MeetingLocationUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meeting_url: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<Option<String>>,
+}
+
+impl MeetingLocationUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.location.is_none() && self.meeting_url.is_none() && self.room.is_none()
+    }
+
+    /// Apply the changes to a row already loaded
+    pub fn apply(&self, row: &mut MeetingRow) {
+        if let Some(ref location) = self.location {
+            row.location = location.clone();
+        }
+        if let Some(ref meeting_url) = self.meeting_url {
+            row.meeting_url = meeting_url.clone();
+        }
+        if let Some(ref room) = self.room {
+            row.room = room.clone();
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct AddMeetingAttendeePayload {
     meeting_id: String,
@@ -202,6 +776,23 @@ struct AddMeetingAttendeePayload {
     response_status: String,
 }
 
+/// Error of a failed `find_session_by_code` call, by HTTP status
+pub(crate) fn session_lookup_error(status: reqwest::StatusCode, body: &str) -> Error {
+    match status {
+        reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            // The function puts the seconds left in the error details
+            let retry_after_secs = serde_json::from_str::<serde_json::Value>(body)
+                .ok()
+                .and_then(|body| body["details"].as_str()?.parse().ok())
+                .unwrap_or(60);
+            Error::TooManyAttempts { retry_after_secs }
+        }
+        reqwest::StatusCode::FORBIDDEN => Error::Banned,
+        reqwest::StatusCode::LOCKED => Error::SessionLocked,
+        _ => Error::Database(format!("Failed to get session: {} - {}", status, body)),
+    }
+}
+
 impl SupabaseClient {
     pub fn new() -> Result<Self> {
         let base_url = std::env::var(SUPABASE_URL_ENV)
@@ -213,15 +804,17 @@ impl SupabaseClient {
             .map_err(|_| Error::Config("SUPABASE_ANON_KEY not set".to_string()))?;
 
         let client = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
             .build()
             .map_err(|e| Error::Network(e.to_string()))?;
 
         Ok(Self {
             inner: Arc::new(SupabaseClientInner {
-                client,
+                client: InstrumentedClient(client),
                 base_url,
                 anon_key,
                 access_token: RwLock::new(None),
+                policy: policy_from_env(),
             }),
         })
     }
@@ -250,9 +843,8 @@ impl SupabaseClient {
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -272,7 +864,13 @@ impl SupabaseClient {
     }
 
     /// Create a new session in the database
-    pub async fn create_session(&self, host_id: &str, join_code: &str) -> Result<SessionRow> {
+    pub async fn create_session(
+        &self,
+        host_id: &str,
+        join_code: &str,
+        idle_timeout_secs: u64,
+        expires_at: Option<&str>,
+    ) -> Result<SessionRow> {
         let token = self
             .get_access_token()
             .await
@@ -284,6 +882,8 @@ impl SupabaseClient {
             host_id: host_id.to_string(),
             join_code: join_code.to_string(),
             status: "active".to_string(),
+            idle_timeout_secs,
+            expires_at: expires_at.map(str::to_string),
         };
 
         let response = self
@@ -295,9 +895,8 @@ impl SupabaseClient {
             .header("Content-Type", "application/json")
             .header("Prefer", "return=representation")
             .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -319,35 +918,40 @@ impl SupabaseClient {
             .ok_or_else(|| Error::Database("No session returned".to_string()))
     }
 
-    /// Get a session by join code
+    /// Get an active session by join code
+    ///
+    /// Goes through `find_session_by_code`, which counts failed lookups per
+    /// user and IP address and refuses them with `TooManyAttempts` while
+    /// throttled, `Banned` for users banned from the session and
+    /// `SessionLocked` for a locked one.
     pub async fn get_session_by_code(&self, join_code: &str) -> Result<Option<SessionRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/sessions?join_code=eq.{}&status=eq.active&limit=1",
-            self.inner.base_url, join_code
-        );
+        let url = format!("{}/rest/v1/rpc/find_session_by_code", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            code: &'a str,
+        }
 
         let response = self
             .inner
             .client
-            .get(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams { code: join_code })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(Error::Database(format!(
-                "Failed to get session: {} - {}",
-                status, body
-            )));
+            return Err(session_lookup_error(status, &body));
         }
 
         let sessions: Vec<SessionRow> = response
@@ -385,9 +989,8 @@ impl SupabaseClient {
             .json(&StatusUpdate {
                 status: status.to_string(),
             })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status_code = response.status();
@@ -401,116 +1004,67 @@ impl SupabaseClient {
         Ok(())
     }
 
-    /// End a session
-    pub async fn end_session(&self, session_id: &str) -> Result<()> {
-        self.update_session_status(session_id, "ended").await
-    }
-
-    // ==========================================
-    // Chat-related methods
-    // ==========================================
-
-    /// Get all conversations for the current user
-    pub async fn get_user_conversations(&self, user_id: &str) -> Result<Vec<ConversationRow>> {
+    /// Close a session to new joins, or open it again
+    pub async fn set_session_locked(&self, session_id: &str, locked: bool) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        // Get conversation IDs where user is a participant
         let url = format!(
-            "{}/rest/v1/conversation_participants?user_id=eq.{}&select=conversation_id",
-            self.inner.base_url, user_id
+            "{}/rest/v1/sessions?id=eq.{}",
+            self.inner.base_url, session_id
         );
 
-        let response = self
-            .inner
-            .client
-            .get(&url)
-            .header("apikey", &self.inner.anon_key)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::Database(format!(
-                "Failed to get conversations: {} - {}",
-                status, body
-            )));
-        }
-
-        #[derive(Deserialize)]
-        struct ParticipantRow {
-            conversation_id: String,
-        }
-
-        let participants: Vec<ParticipantRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        if participants.is_empty() {
-            return Ok(vec![]);
+        #[derive(Serialize)]
+        struct LockUpdate {
+            locked: bool,
         }
 
-        // Get full conversation details
-        let conv_ids: Vec<String> = participants.iter().map(|p| p.conversation_id.clone()).collect();
-        let ids_param = conv_ids.join(",");
-
-        let url = format!(
-            "{}/rest/v1/conversations?id=in.({})",
-            self.inner.base_url, ids_param
-        );
-
         let response = self
             .inner
             .client
-            .get(&url)
+            .patch(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&LockUpdate { locked })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to get conversation details: {} - {}",
+                "Failed to lock session: {} - {}",
                 status, body
             )));
         }
 
-        let conversations: Vec<ConversationRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        Ok(conversations)
+        Ok(())
     }
 
-    /// Create a new conversation
-    pub async fn create_conversation(
+    /// Copy a session's chat into a conversation, or stop with `None`
+    pub async fn set_session_chat_bridge(
         &self,
-        conversation_type: &str,
-        name: Option<&str>,
-        created_by: &str,
-    ) -> Result<ConversationRow> {
+        session_id: &str,
+        conversation_id: Option<&str>,
+    ) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/conversations", self.inner.base_url);
+        let url = format!(
+            "{}/rest/v1/rpc/set_session_chat_bridge",
+            self.inner.base_url
+        );
 
-        let payload = CreateConversationPayload {
-            conversation_type: conversation_type.to_string(),
-            name: name.map(|s| s.to_string()),
-            created_by: created_by.to_string(),
-        };
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_session_id: &'a str,
+            target_conversation_id: Option<&'a str>,
+        }
 
         let response = self
             .inner
@@ -519,69 +1073,68 @@ impl SupabaseClient {
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .header("Prefer", "return=representation")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .json(&FunctionParams {
+                target_session_id: session_id,
+                target_conversation_id: conversation_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to create conversation: {} - {}",
+                "Failed to bridge session chat: {} - {}",
                 status, body
             )));
         }
 
-        let conversations: Vec<ConversationRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
+        Ok(())
+    }
 
-        conversations
-            .into_iter()
-            .next()
-            .ok_or_else(|| Error::Database("No conversation returned".to_string()))
+    /// End a session
+    pub async fn end_session(&self, session_id: &str) -> Result<()> {
+        self.update_session_status(session_id, "ended").await
     }
 
-    /// Add a participant to a conversation
-    pub async fn add_participant(
-        &self,
-        conversation_id: &str,
-        user_id: &str,
-        role: &str,
-    ) -> Result<()> {
+    /// Record that a session is still in use, holding off its idle timeout
+    pub async fn touch_session(&self, session_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/conversation_participants", self.inner.base_url);
+        let url = format!(
+            "{}/rest/v1/sessions?id=eq.{}",
+            self.inner.base_url, session_id
+        );
 
-        let payload = AddParticipantPayload {
-            conversation_id: conversation_id.to_string(),
-            user_id: user_id.to_string(),
-            role: role.to_string(),
-        };
+        #[derive(Serialize)]
+        struct ActivityUpdate {
+            last_activity_at: String,
+            /// A host still sending activity is back
+            host_left_at: Option<String>,
+        }
 
         let response = self
             .inner
             .client
-            .post(&url)
+            .patch(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .json(&ActivityUpdate {
+                last_activity_at: chrono::Utc::now().to_rfc3339(),
+                host_left_at: None,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to add participant: {} - {}",
+                "Failed to touch session: {} - {}",
                 status, body
             )));
         }
@@ -589,58 +1142,51 @@ impl SupabaseClient {
         Ok(())
     }
 
-    /// Remove a participant from a conversation
-    pub async fn remove_participant(
-        &self,
-        conversation_id: &str,
-        user_id: &str,
-    ) -> Result<()> {
+    /// End the user's active sessions that went idle or expired, returning them
+    pub async fn end_stale_sessions(&self) -> Result<Vec<SessionRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}",
-            self.inner.base_url, conversation_id, user_id
-        );
+        let url = format!("{}/rest/v1/rpc/end_stale_sessions", self.inner.base_url);
 
         let response = self
             .inner
             .client
-            .delete(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({}))
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to remove participant: {} - {}",
+                "Failed to end stale sessions: {} - {}",
                 status, body
             )));
         }
 
-        Ok(())
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
     }
 
-    /// Get participants of a conversation
-    pub async fn get_conversation_participants(
-        &self,
-        conversation_id: &str,
-    ) -> Result<Vec<ParticipantWithProfile>> {
+    /// Get a session by ID
+    pub async fn get_session(&self, session_id: &str) -> Result<Option<SessionRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        // Get participants
         let url = format!(
-            "{}/rest/v1/conversation_participants?conversation_id=eq.{}",
-            self.inner.base_url, conversation_id
+            "{}/rest/v1/sessions?id=eq.{}&limit=1",
+            self.inner.base_url, session_id
         );
 
         let response = self
@@ -649,200 +1195,170 @@ impl SupabaseClient {
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to get participants: {} - {}",
+                "Failed to get session: {} - {}",
                 status, body
             )));
         }
 
-        let participants: Vec<ConversationParticipantRow> = response
+        let sessions: Vec<SessionRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        // Get user profiles for participants
-        let user_ids: Vec<String> = participants.iter().map(|p| p.user_id.clone()).collect();
-        if user_ids.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let profiles = self.get_user_profiles(&user_ids).await?;
-        let presence = self.get_users_presence(&user_ids).await?;
-
-        // Combine participants with profiles
-        let result: Vec<ParticipantWithProfile> = participants
-            .into_iter()
-            .map(|p| {
-                let profile = profiles.iter().find(|pr| pr.user_id == p.user_id);
-                let pres = presence.iter().find(|pr| pr.user_id == p.user_id);
-                ParticipantWithProfile {
-                    user_id: p.user_id.clone(),
-                    display_name: profile
-                        .and_then(|pr| pr.display_name.clone())
-                        .unwrap_or_else(|| p.user_id.clone()),
-                    avatar_url: profile.and_then(|pr| pr.avatar_url.clone()),
-                    role: p.role,
-                    is_online: pres.map(|pr| pr.status == "online").unwrap_or(false),
-                    last_seen_at: pres.and_then(|pr| pr.last_seen_at.clone()),
-                }
-            })
-            .collect();
-
-        Ok(result)
+        Ok(sessions.into_iter().next())
     }
 
-    /// Get user profiles by IDs
-    pub async fn get_user_profiles(&self, user_ids: &[String]) -> Result<Vec<UserProfileRow>> {
+    /// Add a user to a session's ban list
+    pub async fn ban_session_user(&self, session_id: &str, user_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let ids_param = user_ids.join(",");
-        let url = format!(
-            "{}/rest/v1/user_profiles?user_id=in.({})",
-            self.inner.base_url, ids_param
-        );
+        let url = format!("{}/rest/v1/rpc/ban_session_user", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_session_id: &'a str,
+            target_user_id: &'a str,
+        }
 
         let response = self
             .inner
             .client
-            .get(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_session_id: session_id,
+                target_user_id: user_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to get user profiles: {} - {}",
+                "Failed to ban session user: {} - {}",
                 status, body
             )));
         }
 
-        let profiles: Vec<UserProfileRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        Ok(profiles)
+        Ok(())
     }
 
-    /// Get presence status for users
-    pub async fn get_users_presence(&self, user_ids: &[String]) -> Result<Vec<UserPresenceRow>> {
+    /// Record a viewer the host let in, who can read the session from then on
+    pub async fn admit_session_user(&self, session_id: &str, user_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let ids_param = user_ids.join(",");
-        let url = format!(
-            "{}/rest/v1/user_presence?user_id=in.({})",
-            self.inner.base_url, ids_param
-        );
+        let url = format!("{}/rest/v1/rpc/admit_session_user", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_session_id: &'a str,
+            target_user_id: &'a str,
+        }
 
         let response = self
             .inner
             .client
-            .get(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_session_id: session_id,
+                target_user_id: user_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            // Return empty if presence table doesn't exist yet
-            return Ok(vec![]);
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to admit session user: {} - {}",
+                status, body
+            )));
         }
 
-        let presence: Vec<UserPresenceRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        Ok(presence)
+        Ok(())
     }
 
-    /// Get messages for a conversation
-    pub async fn get_messages(
+    /// Add a user to or drop them from a session's co-hosts
+    pub async fn set_session_cohost(
         &self,
-        conversation_id: &str,
-        limit: u32,
-        before: Option<&str>,
-    ) -> Result<Vec<MessageRow>> {
+        session_id: &str,
+        user_id: &str,
+        is_cohost: bool,
+    ) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let mut url = format!(
-            "{}/rest/v1/messages?conversation_id=eq.{}&order=created_at.desc&limit={}",
-            self.inner.base_url, conversation_id, limit
-        );
+        let url = format!("{}/rest/v1/rpc/set_session_cohost", self.inner.base_url);
 
-        if let Some(before_id) = before {
-            url.push_str(&format!("&created_at=lt.{}", before_id));
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_session_id: &'a str,
+            target_user_id: &'a str,
+            make_cohost: bool,
         }
 
         let response = self
             .inner
             .client
-            .get(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_session_id: session_id,
+                target_user_id: user_id,
+                make_cohost: is_cohost,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to get messages: {} - {}",
+                "Failed to update session co-host: {} - {}",
                 status, body
             )));
         }
 
-        let messages: Vec<MessageRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        Ok(messages)
+        Ok(())
     }
 
-    /// Create a new message
-    pub async fn create_message(
-        &self,
-        conversation_id: &str,
-        sender_id: &str,
-        content: &str,
-        message_type: &str,
-    ) -> Result<MessageRow> {
+    /// Record that the host left the session to its co-hosts, which lets
+    /// one of them take over
+    pub async fn mark_host_left(&self, session_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/messages", self.inner.base_url);
+        let url = format!("{}/rest/v1/rpc/mark_host_left", self.inner.base_url);
 
-        let payload = CreateMessagePayload {
-            conversation_id: conversation_id.to_string(),
-            sender_id: sender_id.to_string(),
-            content: content.to_string(),
-            message_type: message_type.to_string(),
-        };
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_session_id: &'a str,
+        }
 
         let response = self
             .inner
@@ -851,55 +1367,42 @@ impl SupabaseClient {
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .header("Prefer", "return=representation")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .json(&FunctionParams {
+                target_session_id: session_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to create message: {} - {}",
+                "Failed to record host leaving: {} - {}",
                 status, body
             )));
         }
 
-        let messages: Vec<MessageRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        messages
-            .into_iter()
-            .next()
-            .ok_or_else(|| Error::Database("No message returned".to_string()))
+        Ok(())
     }
 
-    /// Update user presence status
-    pub async fn update_presence(&self, user_id: &str, status: &str) -> Result<()> {
+    /// Make another participant the session host
+    ///
+    /// Allowed for the current host, and for a co-host taking over itself
+    /// once the host recorded leaving or stopped sending activity.
+    pub async fn transfer_session_host(&self, session_id: &str, new_host_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/user_presence", self.inner.base_url);
+        let url = format!("{}/rest/v1/rpc/transfer_session_host", self.inner.base_url);
 
         #[derive(Serialize)]
-        struct PresencePayload {
-            user_id: String,
-            status: String,
-            last_seen_at: String,
+        struct FunctionParams<'a> {
+            target_session_id: &'a str,
+            new_host_id: &'a str,
         }
 
-        let payload = PresencePayload {
-            user_id: user_id.to_string(),
-            status: status.to_string(),
-            last_seen_at: chrono::Utc::now().to_rfc3339(),
-        };
-
-        // Use upsert
         let response = self
             .inner
             .client
@@ -907,79 +1410,79 @@ impl SupabaseClient {
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .header("Prefer", "resolution=merge-duplicates")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .json(&FunctionParams {
+                target_session_id: session_id,
+                new_host_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            let status_code = response.status();
+            let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to update presence: {} - {}",
-                status_code, body
+                "Failed to transfer session host: {} - {}",
+                status, body
             )));
         }
 
         Ok(())
     }
 
-    /// Update last_read_at for a participant
-    pub async fn update_last_read(
-        &self,
-        conversation_id: &str,
-        user_id: &str,
-    ) -> Result<()> {
+    /// Store a chat message sent during a session
+    pub async fn create_session_message(&self, message: &SessionMessageRow) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}",
-            self.inner.base_url, conversation_id, user_id
-        );
-
-        #[derive(Serialize)]
-        struct LastReadUpdate {
-            last_read_at: String,
-        }
+        let url = format!("{}/rest/v1/session_messages", self.inner.base_url);
 
+        // A retried insert of the same message is not an error
         let response = self
             .inner
             .client
-            .patch(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .json(&LastReadUpdate {
-                last_read_at: chrono::Utc::now().to_rfc3339(),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Prefer", "resolution=ignore-duplicates")
+            .json(message)
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            let status_code = response.status();
+            let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to update last read: {} - {}",
-                status_code, body
+                "Failed to store session message: {} - {}",
+                status, body
             )));
         }
 
         Ok(())
     }
 
-    /// Get all team members (users)
-    pub async fn get_team_members(&self) -> Result<Vec<UserProfileRow>> {
+    /// Get a session's chat messages, newest first
+    pub async fn get_session_messages(
+        &self,
+        session_id: &str,
+        limit: u32,
+        before: Option<&str>,
+    ) -> Result<Vec<SessionMessageRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/user_profiles?select=*", self.inner.base_url);
+        let mut url = format!(
+            "{}/rest/v1/session_messages?session_id=eq.{}&order=sent_at.desc&limit={}",
+            self.inner.base_url, session_id, limit
+        );
+
+        if let Some(before) = before {
+            url.push_str(&format!("&sent_at=lt.{}", urlencoding::encode(before)));
+        }
 
         let response = self
             .inner
@@ -987,80 +1490,83 @@ impl SupabaseClient {
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to get team members: {} - {}",
+                "Failed to get session messages: {} - {}",
                 status, body
             )));
         }
 
-        let profiles: Vec<UserProfileRow> = response
+        let messages: Vec<SessionMessageRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        Ok(profiles)
+        Ok(messages)
     }
 
-    /// Find or create a direct conversation between two users
-    pub async fn find_or_create_direct_conversation(
-        &self,
-        user1_id: &str,
-        user2_id: &str,
-    ) -> Result<ConversationRow> {
+    // ==========================================
+    // Chat-related methods
+    // ==========================================
+
+    /// Get all conversations for the current user
+    pub async fn get_user_conversations(&self, user_id: &str) -> Result<Vec<ConversationRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        // Call the database function
-        let url = format!("{}/rest/v1/rpc/find_or_create_direct_conversation", self.inner.base_url);
-
-        #[derive(Serialize)]
-        struct FunctionParams {
-            user1_id: String,
-            user2_id: String,
-        }
+        // Get conversation IDs where user is a participant
+        let url = format!(
+            "{}/rest/v1/conversation_participants?user_id=eq.{}&select=conversation_id,archived_at",
+            self.inner.base_url, user_id
+        );
 
         let response = self
             .inner
             .client
-            .post(&url)
+            .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&FunctionParams {
-                user1_id: user1_id.to_string(),
-                user2_id: user2_id.to_string(),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to find/create conversation: {} - {}",
+                "Failed to get conversations: {} - {}",
                 status, body
             )));
         }
 
-        let conv_id: String = response
+        #[derive(Deserialize)]
+        struct ParticipantRow {
+            conversation_id: String,
+            archived_at: Option<String>,
+        }
+
+        let participants: Vec<ParticipantRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        // Get the full conversation details
+        if participants.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Get full conversation details
+        let conv_ids: Vec<String> = participants.iter().map(|p| p.conversation_id.clone()).collect();
+        let ids_param = conv_ids.join(",");
+
         let url = format!(
-            "{}/rest/v1/conversations?id=eq.{}",
-            self.inner.base_url, conv_id
+            "{}/rest/v1/conversations?id=in.({})",
+            self.inner.base_url, ids_param
         );
 
         let response = self
@@ -1069,168 +1575,366 @@ impl SupabaseClient {
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
-        let conversations: Vec<ConversationRow> = response
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get conversation details: {} - {}",
+                status, body
+            )));
+        }
+
+        let mut conversations: Vec<ConversationRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        conversations
-            .into_iter()
-            .next()
-            .ok_or_else(|| Error::Database("Conversation not found".to_string()))
+        for conversation in &mut conversations {
+            conversation.archived_at = participants
+                .iter()
+                .find(|p| p.conversation_id == conversation.id)
+                .and_then(|p| p.archived_at.clone());
+        }
+
+        Ok(conversations)
     }
 
-    /// Update a group conversation
-    pub async fn update_group(
+    /// Create a new conversation, optionally linked to the meeting it is for
+    pub async fn create_conversation(
         &self,
-        conversation_id: &str,
+        conversation_type: &str,
         name: Option<&str>,
-        avatar_url: Option<&str>,
-    ) -> Result<()> {
+        created_by: &str,
+        meeting_id: Option<&str>,
+    ) -> Result<ConversationRow> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/conversations?id=eq.{}",
-            self.inner.base_url, conversation_id
-        );
+        let url = format!("{}/rest/v1/conversations", self.inner.base_url);
 
-        #[derive(Serialize)]
-        struct GroupUpdate {
-            #[serde(skip_serializing_if = "Option::is_none")]
-            name: Option<String>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            avatar_url: Option<String>,
-        }
+        let payload = CreateConversationPayload {
+            conversation_type: conversation_type.to_string(),
+            name: name.map(|s| s.to_string()),
+            created_by: created_by.to_string(),
+            meeting_id: meeting_id.map(|s| s.to_string()),
+        };
 
         let response = self
             .inner
             .client
-            .patch(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .json(&GroupUpdate {
-                name: name.map(|s| s.to_string()),
-                avatar_url: avatar_url.map(|s| s.to_string()),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            let status_code = response.status();
+            let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to update group: {} - {}",
-                status_code, body
+                "Failed to create conversation: {} - {}",
+                status, body
             )));
         }
 
-        Ok(())
-    }
+        let conversations: Vec<ConversationRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
 
-    // ==========================================
-    // Calendar-related methods
-    // ==========================================
+        conversations
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No conversation returned".to_string()))
+    }
 
-    /// Get meetings in a date range for a user
-    pub async fn get_meetings_in_range(
+    /// Add a participant to a conversation
+    pub async fn add_participant(
         &self,
+        conversation_id: &str,
         user_id: &str,
-        start_date: &str,
-        end_date: &str,
-    ) -> Result<Vec<MeetingRow>> {
+        role: &str,
+    ) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        // Get meeting IDs where user is organizer or attendee
-        let attendee_url = format!(
-            "{}/rest/v1/meeting_attendees?user_id=eq.{}&select=meeting_id",
-            self.inner.base_url, user_id
-        );
+        let url = format!("{}/rest/v1/conversation_participants", self.inner.base_url);
+
+        let payload = AddParticipantPayload {
+            conversation_id: conversation_id.to_string(),
+            user_id: user_id.to_string(),
+            role: role.to_string(),
+        };
 
         let response = self
             .inner
             .client
-            .get(&attendee_url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to add participant: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Add several participants to a conversation in one request
+    pub async fn add_participants(
+        &self,
+        conversation_id: &str,
+        user_ids: &[String],
+        role: &str,
+    ) -> Result<()> {
+        if user_ids.is_empty() {
+            return Ok(());
+        }
+
+        let token = self
+            .get_access_token()
             .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        #[derive(Deserialize)]
-        struct AttendeeRow {
-            meeting_id: String,
+        let url = format!("{}/rest/v1/conversation_participants", self.inner.base_url);
+
+        let payload: Vec<AddParticipantPayload> = user_ids
+            .iter()
+            .map(|user_id| AddParticipantPayload {
+                conversation_id: conversation_id.to_string(),
+                user_id: user_id.clone(),
+                role: role.to_string(),
+            })
+            .collect();
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to add participants: {} - {}",
+                status, body
+            )));
         }
 
-        let attendees: Vec<AttendeeRow> = if response.status().is_success() {
-            response.json().await.unwrap_or_default()
-        } else {
-            vec![]
-        };
+        Ok(())
+    }
 
-        let meeting_ids: Vec<String> = attendees.iter().map(|a| a.meeting_id.clone()).collect();
+    /// Remove a participant from a conversation
+    pub async fn remove_participant(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        // Build query for meetings
-        let mut url = format!(
-            "{}/rest/v1/meetings?scheduled_at=gte.{}&scheduled_at=lte.{}&status=neq.cancelled&order=scheduled_at.asc",
-            self.inner.base_url, start_date, end_date
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, conversation_id, user_id
         );
 
-        if !meeting_ids.is_empty() {
-            let ids_param = meeting_ids.join(",");
-            url.push_str(&format!("&or=(organizer_id.eq.{},id.in.({}))", user_id, ids_param));
-        } else {
-            url.push_str(&format!("&organizer_id=eq.{}", user_id));
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to remove participant: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get participants of a conversation
+    pub async fn get_conversation_participants(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Vec<ParticipantWithProfile>> {
+        Ok(self
+            .get_participants_for_conversations(&[conversation_id.to_string()])
+            .await?
+            .remove(conversation_id)
+            .unwrap_or_default())
+    }
+
+    /// Get participants of several conversations, by conversation ID, with
+    /// the profiles and presence of all of them fetched at once
+    pub async fn get_participants_for_conversations(
+        &self,
+        conversation_ids: &[String],
+    ) -> Result<HashMap<String, Vec<ParticipantWithProfile>>> {
+        if conversation_ids.is_empty() {
+            return Ok(HashMap::new());
         }
 
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Get participants
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=in.({})",
+            self.inner.base_url,
+            conversation_ids.join(",")
+        );
+
         let response = self
             .inner
             .client
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get participants: {} - {}",
+                status, body
+            )));
+        }
+
+        let participants: Vec<ConversationParticipantRow> = response
+            .json()
             .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        // Get user profiles for participants, each user once
+        let mut user_ids: Vec<String> = participants.iter().map(|p| p.user_id.clone()).collect();
+        user_ids.sort();
+        user_ids.dedup();
+        if user_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let (profiles, presence) = tokio::try_join!(
+            self.get_user_profiles(&user_ids),
+            self.get_users_presence(&user_ids),
+        )?;
+
+        // Combine participants with profiles
+        let now = chrono::Utc::now();
+        let mut result: HashMap<String, Vec<ParticipantWithProfile>> = HashMap::new();
+        for p in participants {
+            let profile = profiles.iter().find(|pr| pr.user_id == p.user_id);
+            let pres = presence.iter().find(|pr| pr.user_id == p.user_id);
+            result
+                .entry(p.conversation_id)
+                .or_default()
+                .push(ParticipantWithProfile {
+                    user_id: p.user_id.clone(),
+                    display_name: profile
+                        .and_then(|pr| pr.display_name.clone())
+                        .unwrap_or_else(|| p.user_id.clone()),
+                    avatar_url: profile.and_then(|pr| pr.avatar_url.clone()),
+                    role: p.role,
+                    is_online: pres.is_some_and(|pr| crate::presence::is_online(pr, now)),
+                    status: pres
+                        .map(|pr| crate::presence::effective_status(pr, now))
+                        .unwrap_or("offline")
+                        .to_string(),
+                    status_message: pres.and_then(|pr| pr.status_message.clone()),
+                    last_seen_at: pres.and_then(|pr| pr.last_seen_at.clone()),
+                });
+        }
+
+        Ok(result)
+    }
+
+    /// Get user profiles by IDs
+    pub async fn get_user_profiles(&self, user_ids: &[String]) -> Result<Vec<UserProfileRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let ids_param = user_ids.join(",");
+        let url = format!(
+            "{}/rest/v1/user_profiles?user_id=in.({})",
+            self.inner.base_url, ids_param
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to get meetings: {} - {}",
+                "Failed to get user profiles: {} - {}",
                 status, body
             )));
         }
 
-        let meetings: Vec<MeetingRow> = response
+        let profiles: Vec<UserProfileRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        Ok(meetings)
+        Ok(profiles)
     }
 
-    /// Get a single meeting by ID
-    pub async fn get_meeting(&self, meeting_id: &str) -> Result<Option<MeetingRow>> {
+    /// Get presence status for users
+    pub async fn get_users_presence(&self, user_ids: &[String]) -> Result<Vec<UserPresenceRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
+        let ids_param = user_ids.join(",");
         let url = format!(
-            "{}/rest/v1/meetings?id=eq.{}&limit=1",
-            self.inner.base_url, meeting_id
+            "{}/rest/v1/user_presence?user_id=in.({})",
+            self.inner.base_url, ids_param
         );
 
         let response = self
@@ -1239,47 +1943,5629 @@ impl SupabaseClient {
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            // Return empty if presence table doesn't exist yet
+            return Ok(vec![]);
+        }
+
+        let presence: Vec<UserPresenceRow> = response
+            .json()
             .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(presence)
+    }
+
+    /// Get a user's presence periods overlapping the time since `since`,
+    /// newest first
+    pub async fn get_presence_history(
+        &self,
+        user_id: &str,
+        since: &str,
+    ) -> Result<Vec<PresencePeriodRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let since = urlencoding::encode(since).into_owned();
+        let url = format!(
+            "{}/rest/v1/presence_history?select=status,started_at,ended_at&user_id=eq.{}&or=(ended_at.is.null,ended_at.gte.{})&order=started_at.desc",
+            self.inner.base_url, user_id, since
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            return Ok(None);
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get presence history: {} - {}",
+                status, body
+            )));
         }
 
-        let meetings: Vec<MeetingRow> = response
+        let periods: Vec<PresencePeriodRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        Ok(meetings.into_iter().next())
+        Ok(periods)
     }
 
-    /// Create a new meeting
-    pub async fn create_meeting(
+    /// Get messages for a conversation, thread replies excepted
+    ///
+    /// Without a cursor the newest messages come back. Rows come nearest the
+    /// cursor first: newest first for older pages, oldest first for newer.
+    pub async fn get_messages(
         &self,
-        organizer_id: &str,
-        title: &str,
-        description: Option<&str>,
-        scheduled_at: &str,
-        duration_minutes: i32,
-        recurrence_rule: Option<&str>,
-    ) -> Result<MeetingRow> {
+        conversation_id: &str,
+        limit: u32,
+        cursor: Option<&MessageCursor>,
+    ) -> Result<Vec<MessageRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let (order, op) = match cursor.map(|c| c.direction) {
+            Some(PageDirection::Newer) => ("asc", "gt"),
+            _ => ("desc", "lt"),
+        };
+        let mut url = format!(
+            "{}/rest/v1/messages?conversation_id=eq.{}&parent_message_id=is.null&order=created_at.{},id.{}&limit={}",
+            self.inner.base_url, conversation_id, order, order, limit
+        );
+
+        if let Some(cursor) = cursor {
+            let created_at = urlencoding::encode(&cursor.position.created_at);
+            url.push_str(&format!(
+                "&or=(created_at.{}.\"{}\",and(created_at.eq.\"{}\",id.{}.{}))",
+                op, created_at, created_at, op, cursor.position.id
+            ));
+        }
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get messages: {} - {}",
+                status, body
+            )));
+        }
+
+        let messages: Vec<MessageRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(messages)
+    }
+
+    /// Get the latest top-level message of each of several conversations
+    pub async fn get_last_messages(&self, conversation_ids: &[String]) -> Result<Vec<MessageRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/get_last_messages", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            conversation_ids: &'a [String],
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams { conversation_ids })
+            .send_with(&self.inner.policy.idempotent())
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get last messages: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Create a new message
+    pub async fn create_message(
+        &self,
+        conversation_id: &str,
+        sender_id: &str,
+        content: &str,
+        message_type: &str,
+    ) -> Result<MessageRow> {
+        self.create_message_with_attachments(
+            conversation_id,
+            sender_id,
+            content,
+            message_type,
+            &[],
+            None,
+        )
+        .await
+    }
+
+    /// Create a new message carrying already uploaded attachments, as a reply
+    /// when `parent_message_id` is set
+    pub async fn create_message_with_attachments(
+        &self,
+        conversation_id: &str,
+        sender_id: &str,
+        content: &str,
+        message_type: &str,
+        attachments: &[MessageAttachment],
+        parent_message_id: Option<&str>,
+    ) -> Result<MessageRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/messages", self.inner.base_url);
+
+        let payload = CreateMessagePayload {
+            conversation_id: conversation_id.to_string(),
+            sender_id: sender_id.to_string(),
+            content: content.to_string(),
+            message_type: message_type.to_string(),
+            attachments: attachments.to_vec(),
+            parent_message_id: parent_message_id.map(String::from),
+        };
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create message: {} - {}",
+                status, body
+            )));
+        }
+
+        let messages: Vec<MessageRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        messages
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No message returned".to_string()))
+    }
+
+    /// Index the links of a message; links already indexed are skipped
+    pub async fn add_conversation_links(&self, links: &[NewConversationLink]) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversation_links?on_conflict=message_id,url",
+            self.inner.base_url
+        );
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=ignore-duplicates")
+            .json(links)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to index links: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the links shared in a conversation, newest first
+    pub async fn get_conversation_links(
+        &self,
+        conversation_id: &str,
+        limit: u32,
+        before: Option<&str>,
+    ) -> Result<Vec<ConversationLinkRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let mut url = format!(
+            "{}/rest/v1/conversation_links?conversation_id=eq.{}&order=created_at.desc&limit={}",
+            self.inner.base_url, conversation_id, limit
+        );
+
+        if let Some(before) = before {
+            url.push_str(&format!("&created_at=lt.{}", urlencoding::encode(before)));
+        }
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get conversation links: {} - {}",
+                status, body
+            )));
+        }
+
+        let links: Vec<ConversationLinkRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(links)
+    }
+
+    /// Update user presence status
+    pub async fn update_presence(&self, user_id: &str, status: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_presence", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct PresencePayload {
+            user_id: String,
+            status: String,
+            last_seen_at: String,
+        }
+
+        let payload = PresencePayload {
+            user_id: user_id.to_string(),
+            status: status.to_string(),
+            last_seen_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        // Use upsert
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update presence: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set or clear (with `None`) the user's custom status message
+    pub async fn update_status_message(&self, user_id: &str, message: Option<&str>) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_presence", self.inner.base_url);
+
+        // Upsert touching only the message, so the status stays as it is
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(&serde_json::json!({
+                "user_id": user_id,
+                "status_message": message,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update status message: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the users a user blocked, most recent first
+    pub async fn get_blocked_users(&self, blocker_id: &str) -> Result<Vec<UserBlockRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_blocks?blocker_id=eq.{}&select=blocked_id,created_at&order=created_at.desc",
+            self.inner.base_url, blocker_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get blocked users: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Block a user; blocking them again changes nothing
+    pub async fn block_user(&self, blocker_id: &str, blocked_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_blocks", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=ignore-duplicates")
+            .json(&serde_json::json!({
+                "blocker_id": blocker_id,
+                "blocked_id": blocked_id,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to block user: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Unblock a user; unblocking a user who isn't blocked changes nothing
+    pub async fn unblock_user(&self, blocker_id: &str, blocked_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_blocks?blocker_id=eq.{}&blocked_id=eq.{}",
+            self.inner.base_url, blocker_id, blocked_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to unblock user: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// File a report about a user
+    pub async fn report_user(
+        &self,
+        reporter_id: &str,
+        reported_id: &str,
+        reason: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_reports", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "reporter_id": reporter_id,
+                "reported_id": reported_id,
+                "reason": reason,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to report user: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// When a participant last read a conversation, if ever
+    pub async fn get_last_read(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+    ) -> Result<Option<String>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}&select=last_read_at",
+            self.inner.base_url, conversation_id, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get last read: {} - {}",
+                status, body
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct LastRead {
+            last_read_at: Option<String>,
+        }
+
+        let reads: Vec<LastRead> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(reads.into_iter().next().and_then(|r| r.last_read_at))
+    }
+
+    /// Update last_read_at for a participant
+    pub async fn update_last_read(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, conversation_id, user_id
+        );
+
+        #[derive(Serialize)]
+        struct LastReadUpdate {
+            last_read_at: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&LastReadUpdate {
+                last_read_at: chrono::Utc::now().to_rfc3339(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update last read: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Archive a conversation for a participant, or bring it back
+    pub async fn set_conversation_archived(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+        archived: bool,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, conversation_id, user_id
+        );
+
+        #[derive(Serialize)]
+        struct ArchivedUpdate {
+            archived_at: Option<String>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&ArchivedUpdate {
+                archived_at: archived.then(|| chrono::Utc::now().to_rfc3339()),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to archive conversation: {} - {}",
+                status, body
+            )));
+        }
+
+        let updated: Vec<serde_json::Value> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+        if updated.is_empty() {
+            return Err(Error::NotFound(
+                "Not a participant of the conversation".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Get a participant's notification preference for a conversation
+    pub async fn get_notification_prefs(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+    ) -> Result<Option<ConversationNotificationPrefs>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}&select=notification_level,muted_until",
+            self.inner.base_url, conversation_id, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get notification preference: {} - {}",
+                status, body
+            )));
+        }
+
+        let prefs: Vec<ConversationNotificationPrefs> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(prefs.into_iter().next())
+    }
+
+    /// Update a participant's notification preference for a conversation
+    pub async fn update_notification_prefs(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+        prefs: &ConversationNotificationPrefs,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversation_participants?conversation_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, conversation_id, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(prefs)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update notification preference: {} - {}",
+                status, body
+            )));
+        }
+
+        let updated: Vec<serde_json::Value> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+        if updated.is_empty() {
+            return Err(Error::NotFound(
+                "Not a participant of the conversation".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Get a single message by ID
+    pub async fn get_message(&self, message_id: &str) -> Result<Option<MessageRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/messages?id=eq.{}&limit=1",
+            self.inner.base_url, message_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get message: {} - {}",
+                status, body
+            )));
+        }
+
+        let messages: Vec<MessageRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(messages.into_iter().next())
+    }
+
+    /// Get the replies to a message, oldest first
+    pub async fn get_thread_replies(
+        &self,
+        parent_message_id: &str,
+        limit: u32,
+    ) -> Result<Vec<MessageRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/messages?parent_message_id=eq.{}&order=created_at.asc&limit={}",
+            self.inner.base_url, parent_message_id, limit
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get thread: {} - {}",
+                status, body
+            )));
+        }
+
+        let messages: Vec<MessageRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(messages)
+    }
+
+    /// When the user last read a thread, if ever
+    pub async fn get_thread_read(
+        &self,
+        user_id: &str,
+        parent_message_id: &str,
+    ) -> Result<Option<String>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/thread_reads?user_id=eq.{}&parent_message_id=eq.{}&select=last_read_at",
+            self.inner.base_url, user_id, parent_message_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get thread read: {} - {}",
+                status, body
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct ThreadRead {
+            last_read_at: String,
+        }
+
+        let reads: Vec<ThreadRead> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(reads.into_iter().next().map(|r| r.last_read_at))
+    }
+
+    /// Record that the user read a thread up to now
+    pub async fn mark_thread_read(&self, user_id: &str, parent_message_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/thread_reads", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct ThreadRead<'a> {
+            user_id: &'a str,
+            parent_message_id: &'a str,
+            last_read_at: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(&ThreadRead {
+                user_id,
+                parent_message_id,
+                last_read_at: chrono::Utc::now().to_rfc3339(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to mark thread read: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Unread counts of every conversation of the user
+    pub async fn get_unread_counts(&self) -> Result<Vec<UnreadCountRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/get_unread_counts", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({}))
+            .send_with(&self.inner.policy.idempotent())
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get unread counts: {} - {}",
+                status, body
+            )));
+        }
+
+        let counts: Vec<UnreadCountRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(counts)
+    }
+
+    /// Participants other than the sender who have seen a message
+    pub async fn get_message_read_by(&self, message_id: &str) -> Result<Vec<MessageReadRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/get_message_read_by", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "target_message_id": message_id }))
+            .send_with(&self.inner.policy.idempotent())
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get message read receipts: {} - {}",
+                status, body
+            )));
+        }
+
+        let reads: Vec<MessageReadRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(reads)
+    }
+
+    /// Get all team members (users)
+    pub async fn get_team_members(&self) -> Result<Vec<UserProfileRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_profiles?select=*", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get team members: {} - {}",
+                status, body
+            )));
+        }
+
+        let profiles: Vec<UserProfileRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(profiles)
+    }
+
+    /// Find or create a direct conversation between two users
+    pub async fn find_or_create_direct_conversation(
+        &self,
+        user1_id: &str,
+        user2_id: &str,
+    ) -> Result<ConversationRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Call the database function
+        let url = format!("{}/rest/v1/rpc/find_or_create_direct_conversation", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams {
+            user1_id: String,
+            user2_id: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                user1_id: user1_id.to_string(),
+                user2_id: user2_id.to_string(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        // The other user blocked this one
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(Error::Auth(
+                "You can't start a conversation with this user".to_string(),
+            ));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to find/create conversation: {} - {}",
+                status, body
+            )));
+        }
+
+        let conv_id: String = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        // Get the full conversation details
+        let url = format!(
+            "{}/rest/v1/conversations?id=eq.{}",
+            self.inner.base_url, conv_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        let conversations: Vec<ConversationRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        conversations
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("Conversation not found".to_string()))
+    }
+
+    /// Update a group conversation
+    pub async fn update_group(
+        &self,
+        conversation_id: &str,
+        name: Option<&str>,
+        avatar_url: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversations?id=eq.{}",
+            self.inner.base_url, conversation_id
+        );
+
+        #[derive(Serialize)]
+        struct GroupUpdate {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            name: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            avatar_url: Option<String>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&GroupUpdate {
+                name: name.map(|s| s.to_string()),
+                avatar_url: avatar_url.map(|s| s.to_string()),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update group: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get a conversation the user takes part in
+    pub async fn get_conversation(&self, conversation_id: &str) -> Result<Option<ConversationRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/conversations?id=eq.{}",
+            self.inner.base_url, conversation_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get conversation: {} - {}",
+                status, body
+            )));
+        }
+
+        let conversations: Vec<ConversationRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(conversations.into_iter().next())
+    }
+
+    /// Make a group member an admin or a plain member again
+    pub async fn set_participant_role(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+        role: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/set_participant_role", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_conversation_id: &'a str,
+            target_user_id: &'a str,
+            new_role: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_conversation_id: conversation_id,
+                target_user_id: user_id,
+                new_role: role,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to change member role: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Make another group member the owner
+    pub async fn transfer_group_ownership(
+        &self,
+        conversation_id: &str,
+        new_owner_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/rpc/transfer_group_ownership",
+            self.inner.base_url
+        );
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_conversation_id: &'a str,
+            new_owner_id: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_conversation_id: conversation_id,
+                new_owner_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to transfer group ownership: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Publish this device's chat encryption key, replacing an earlier one
+    pub async fn publish_device_key(&self, key: &DeviceKeyRow) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/device_keys", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(key)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to publish device key: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the chat encryption keys of all devices of the given users
+    pub async fn get_device_keys(&self, user_ids: &[String]) -> Result<Vec<DeviceKeyRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/device_keys?user_id=in.({})&select=user_id,device_id,public_key",
+            self.inner.base_url,
+            user_ids.join(",")
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get device keys: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Turn on end-to-end encryption for a conversation, for good
+    pub async fn enable_conversation_encryption(&self, conversation_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/rpc/enable_conversation_encryption",
+            self.inner.base_url
+        );
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_conversation_id: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_conversation_id: conversation_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(Error::Auth(
+                "Only group admins can turn on encryption".to_string(),
+            ));
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to enable encryption: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Calendar-related methods
+    // ==========================================
+
+    /// Get meetings in a date range for a user
+    pub async fn get_meetings_in_range(
+        &self,
+        user_id: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Get meeting IDs where user is organizer or attendee
+        let attendee_url = format!(
+            "{}/rest/v1/meeting_attendees?user_id=eq.{}&select=meeting_id",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&attendee_url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        #[derive(Deserialize)]
+        struct AttendeeRow {
+            meeting_id: String,
+        }
+
+        let attendees: Vec<AttendeeRow> = if response.status().is_success() {
+            response.json().await.unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        let meeting_ids: Vec<String> = attendees.iter().map(|a| a.meeting_id.clone()).collect();
+
+        // Build query for meetings
+        let mut url = format!(
+            "{}/rest/v1/meetings?scheduled_at=gte.{}&scheduled_at=lte.{}&status=neq.cancelled&order=scheduled_at.asc",
+            self.inner.base_url, start_date, end_date
+        );
+
+        if !meeting_ids.is_empty() {
+            let ids_param = meeting_ids.join(",");
+            url.push_str(&format!("&or=(organizer_id.eq.{},id.in.({}))", user_id, ids_param));
+        } else {
+            url.push_str(&format!("&organizer_id=eq.{}", user_id));
+        }
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meetings: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings)
+    }
+
+    /// Get recurring meetings of a user, those starting after `end_date`
+    /// left out; their occurrences may fall in a range their first one
+    /// precedes
+    pub async fn get_recurring_meetings(
+        &self,
+        user_id: &str,
+        end_date: Option<&str>,
+    ) -> Result<Vec<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Get meeting IDs where user is organizer or attendee
+        let attendee_url = format!(
+            "{}/rest/v1/meeting_attendees?user_id=eq.{}&select=meeting_id",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&attendee_url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        #[derive(Deserialize)]
+        struct AttendeeRow {
+            meeting_id: String,
+        }
+
+        let attendees: Vec<AttendeeRow> = if response.status().is_success() {
+            response.json().await.unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        let meeting_ids: Vec<String> = attendees.iter().map(|a| a.meeting_id.clone()).collect();
+
+        let mut url = format!(
+            "{}/rest/v1/meetings?recurrence_rule=not.is.null&status=neq.cancelled&order=scheduled_at.asc",
+            self.inner.base_url
+        );
+        if let Some(end_date) = end_date {
+            url.push_str(&format!("&scheduled_at=lte.{}", end_date));
+        }
+
+        if !meeting_ids.is_empty() {
+            let ids_param = meeting_ids.join(",");
+            url.push_str(&format!(
+                "&or=(organizer_id.eq.{},id.in.({}))",
+                user_id, ids_param
+            ));
+        } else {
+            url.push_str(&format!("&organizer_id=eq.{}", user_id));
+        }
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get recurring meetings: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings)
+    }
+
+    /// Get a single meeting by ID
+    pub async fn get_meeting(&self, meeting_id: &str) -> Result<Option<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}&limit=1",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings.into_iter().next())
+    }
+
+    /// Replace a meeting description whose only change is checklist states
+    ///
+    /// Allowed for attendees. Returns `None` when the description is no
+    /// longer `previous`, i.e. someone changed it since it was read.
+    pub async fn set_meeting_checklist(
+        &self,
+        meeting_id: &str,
+        previous: &str,
+        description: &str,
+    ) -> Result<Option<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/set_meeting_checklist", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_meeting_id: &'a str,
+            previous_description: &'a str,
+            new_description: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_meeting_id: meeting_id,
+                previous_description: previous,
+                new_description: description,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting checklist: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings.into_iter().next())
+    }
+
+    /// Create a new meeting
+    pub async fn create_meeting(
+        &self,
+        organizer_id: &str,
+        title: &str,
+        description: Option<&str>,
+        scheduled_at: &str,
+        duration_minutes: i32,
+        recurrence_rule: Option<&str>,
+    ) -> Result<MeetingRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meetings", self.inner.base_url);
+
+        let payload = CreateMeetingPayload {
+            organizer_id: organizer_id.to_string(),
+            title: title.to_string(),
+            description: description.map(|s| s.to_string()),
+            scheduled_at: scheduled_at.to_string(),
+            duration_minutes,
+            recurrence_rule: recurrence_rule.map(|s| s.to_string()),
+        };
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create meeting: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        meetings
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No meeting returned".to_string()))
+    }
+
+    /// Update a meeting
+    pub async fn update_meeting(
+        &self,
+        meeting_id: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+        scheduled_at: Option<&str>,
+        duration_minutes: Option<i32>,
+        status: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct MeetingUpdate {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            title: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            scheduled_at: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            duration_minutes: Option<i32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            status: Option<String>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&MeetingUpdate {
+                title: title.map(|s| s.to_string()),
+                description: description.map(|s| s.to_string()),
+                scheduled_at: scheduled_at.map(|s| s.to_string()),
+                duration_minutes,
+                status: status.map(|s| s.to_string()),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set the IANA time zone of a meeting, or clear it back to UTC
+    pub async fn set_meeting_time_zone(
+        &self,
+        meeting_id: &str,
+        time_zone: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "time_zone": time_zone }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting time zone: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Turn starting a meeting's session at its scheduled time on or off
+    pub async fn set_meeting_auto_start(&self, meeting_id: &str, auto_start: bool) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "auto_start": auto_start }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting auto-start: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set whether a meeting asks Google Calendar for a Meet link
+    pub async fn set_meeting_google_meet(&self, meeting_id: &str, google_meet: bool) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "google_meet": google_meet }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting Google Meet: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set the location, video call link and room of a meeting
+    pub async fn set_meeting_location(
+        &self,
+        meeting_id: &str,
+        update: &MeetingLocationUpdate,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(update)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting location: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set the dates removed from and added to a meeting's recurrence
+    pub async fn set_meeting_recurrence_exceptions(
+        &self,
+        meeting_id: &str,
+        exdates: &[String],
+        rdates: &[String],
+    ) -> Result<Option<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct ExceptionsUpdate<'a> {
+            recurrence_exdates: &'a [String],
+            recurrence_rdates: &'a [String],
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&ExceptionsUpdate {
+                recurrence_exdates: exdates,
+                recurrence_rdates: rdates,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting recurrence: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings.into_iter().next())
+    }
+
+    /// Link a meeting to the group conversation created for it
+    pub async fn set_meeting_conversation(
+        &self,
+        meeting_id: &str,
+        conversation_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct MeetingConversationUpdate<'a> {
+            conversation_id: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&MeetingConversationUpdate { conversation_id })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to link meeting conversation: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Delete a meeting
+    pub async fn delete_meeting(&self, meeting_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete meeting: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Add an attendee to a meeting
+    pub async fn add_meeting_attendee(
+        &self,
+        meeting_id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_attendees", self.inner.base_url);
+
+        let payload = AddMeetingAttendeePayload {
+            meeting_id: meeting_id.to_string(),
+            user_id: user_id.to_string(),
+            response_status: "invited".to_string(),
+        };
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to add attendee: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set whether an attendee is required or optional, and by when they
+    /// should answer
+    pub async fn set_attendee_attendance(
+        &self,
+        meeting_id: &str,
+        user_id: &str,
+        attendance_type: &str,
+        rsvp_deadline: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, meeting_id, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "attendance_type": attendance_type,
+                "rsvp_deadline": rsvp_deadline,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update attendance: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Invited attendees of the organizer's scheduled meetings whose RSVP
+    /// deadline passed without an answer and who weren't notified about yet
+    pub async fn get_missed_rsvp_deadlines(
+        &self,
+        organizer_id: &str,
+    ) -> Result<Vec<MissedRsvpRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let now = urlencoding::encode(&chrono::Utc::now().to_rfc3339()).into_owned();
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?response_status=eq.invited&rsvp_deadline=lt.{}&rsvp_deadline_notified=eq.false&select=meeting_id,user_id,rsvp_deadline,meetings!inner(title)&meetings.organizer_id=eq.{}&meetings.status=eq.scheduled&order=rsvp_deadline.asc",
+            self.inner.base_url, now, organizer_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get missed RSVP deadlines: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Mark the missed RSVP deadlines of a meeting's attendees as notified
+    pub async fn set_rsvp_deadlines_notified(
+        &self,
+        meeting_id: &str,
+        user_ids: &[String],
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}&user_id=in.({})",
+            self.inner.base_url,
+            meeting_id,
+            user_ids.join(",")
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "rsvp_deadline_notified": true }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to mark RSVP deadlines notified: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the external guests of a meeting
+    pub async fn get_meeting_guests(&self, meeting_id: &str) -> Result<Vec<MeetingGuestRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_guests?meeting_id=eq.{}&order=created_at.asc",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meeting guests: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Invite an external guest to a meeting by email
+    ///
+    /// A database trigger queues the invitation email with the guest's RSVP
+    /// link.
+    pub async fn add_meeting_guest(
+        &self,
+        meeting_id: &str,
+        email: &str,
+        display_name: Option<&str>,
+    ) -> Result<MeetingGuestRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_guests", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({
+                "meeting_id": meeting_id,
+                "email": email,
+                "display_name": display_name,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to add guest: {} - {}",
+                status, body
+            )));
+        }
+
+        let guests: Vec<MeetingGuestRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        guests
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No guest returned".to_string()))
+    }
+
+    /// Remove an external guest from a meeting
+    pub async fn remove_meeting_guest(&self, meeting_id: &str, guest_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_guests?meeting_id=eq.{}&id=eq.{}",
+            self.inner.base_url, meeting_id, guest_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to remove guest: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Remove an attendee from a meeting
+    pub async fn remove_meeting_attendee(
+        &self,
+        meeting_id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, meeting_id, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to remove attendee: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Update attendee response status
+    pub async fn update_attendee_response(
+        &self,
+        meeting_id: &str,
+        user_id: &str,
+        response_status: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, meeting_id, user_id
+        );
+
+        #[derive(Serialize)]
+        struct ResponseUpdate {
+            response_status: String,
+            responded_at: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&ResponseUpdate {
+                response_status: response_status.to_string(),
+                responded_at: chrono::Utc::now().to_rfc3339(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update response: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Invitations to upcoming meetings the user hasn't answered yet, while
+    /// their RSVP deadline hasn't passed
+    pub async fn count_pending_meeting_responses(&self, user_id: &str) -> Result<u32> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let now = urlencoding::encode(&chrono::Utc::now().to_rfc3339()).into_owned();
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?user_id=eq.{}&response_status=eq.invited&or=(rsvp_deadline.is.null,rsvp_deadline.gt.{})&select=meeting_id,meetings!inner(id)&meetings.status=eq.scheduled&meetings.scheduled_at=gte.{}",
+            self.inner.base_url, user_id, now, now
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to count pending meeting responses: {} - {}",
+                status, body
+            )));
+        }
+
+        let pending: Vec<serde_json::Value> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(pending.len() as u32)
+    }
+
+    /// Get meeting attendees with profiles
+    pub async fn get_meeting_attendees(
+        &self,
+        meeting_id: &str,
+    ) -> Result<Vec<AttendeeWithProfile>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(vec![]);
+        }
+
+        let attendees: Vec<MeetingAttendeeRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        // Get user profiles
+        let user_ids: Vec<String> = attendees.iter().map(|a| a.user_id.clone()).collect();
+        if user_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let profiles = self.get_user_profiles(&user_ids).await?;
+
+        let result: Vec<AttendeeWithProfile> = attendees
+            .into_iter()
+            .map(|a| {
+                let profile = profiles.iter().find(|p| p.user_id == a.user_id);
+                AttendeeWithProfile {
+                    user_id: a.user_id.clone(),
+                    display_name: profile
+                        .and_then(|p| p.display_name.clone())
+                        .unwrap_or_else(|| a.user_id.clone()),
+                    avatar_url: profile.and_then(|p| p.avatar_url.clone()),
+                    response_status: a.response_status,
+                    responded_at: a.responded_at,
+                    attendance_type: a.attendance_type.unwrap_or_else(|| "required".to_string()),
+                    rsvp_deadline: a.rsvp_deadline,
+                }
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Get upcoming meetings for a user
+    pub async fn get_upcoming_meetings(
+        &self,
+        user_id: &str,
+        limit: u32,
+    ) -> Result<Vec<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let now = chrono::Utc::now().to_rfc3339();
+
+        // Get meeting IDs where user is attendee
+        let attendee_url = format!(
+            "{}/rest/v1/meeting_attendees?user_id=eq.{}&select=meeting_id",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&attendee_url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        #[derive(Deserialize)]
+        struct AttendeeRow {
+            meeting_id: String,
+        }
+
+        let attendees: Vec<AttendeeRow> = if response.status().is_success() {
+            response.json().await.unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        let meeting_ids: Vec<String> = attendees.iter().map(|a| a.meeting_id.clone()).collect();
+
+        let mut url = format!(
+            "{}/rest/v1/meetings?scheduled_at=gte.{}&status=eq.scheduled&order=scheduled_at.asc&limit={}",
+            self.inner.base_url, now, limit
+        );
+
+        if !meeting_ids.is_empty() {
+            let ids_param = meeting_ids.join(",");
+            url.push_str(&format!("&or=(organizer_id.eq.{},id.in.({}))", user_id, ids_param));
+        } else {
+            url.push_str(&format!("&organizer_id=eq.{}", user_id));
+        }
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(vec![]);
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings)
+    }
+
+    /// Link a meeting to the session it started, with the join code
+    pub async fn link_meeting_to_session(
+        &self,
+        meeting_id: &str,
+        session_id: &str,
+        join_code: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct SessionLink {
+            session_id: String,
+            join_code: String,
+            status: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&SessionLink {
+                session_id: session_id.to_string(),
+                join_code: join_code.to_string(),
+                status: "ongoing".to_string(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to link session: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Save Google OAuth tokens
+    pub async fn save_google_tokens(
+        &self,
+        user_id: &str,
+        access_token: &str,
+        refresh_token: &str,
+        expires_at: &str,
+        email: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_google_tokens", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct GoogleTokensPayload {
+            user_id: String,
+            access_token: String,
+            refresh_token: String,
+            expires_at: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            email: Option<String>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(&GoogleTokensPayload {
+                user_id: user_id.to_string(),
+                access_token: access_token.to_string(),
+                refresh_token: refresh_token.to_string(),
+                expires_at: expires_at.to_string(),
+                email: email.map(|s| s.to_string()),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save Google tokens: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Escrow the key sealing the user's Google tokens for the sync edge
+    /// function; it can't be read back through the API
+    pub async fn set_google_token_key(&self, key: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/set_google_token_key", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            key: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams { key })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save Google token key: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get Google OAuth tokens for a user
+    pub async fn get_google_tokens(&self, user_id: &str) -> Result<Option<GoogleTokensRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_google_tokens?user_id=eq.{}&limit=1",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let tokens: Vec<GoogleTokensRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(tokens.into_iter().next())
+    }
+
+    /// Delete Google OAuth tokens for a user
+    pub async fn delete_google_tokens(&self, user_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete Google tokens: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Update Google sync enabled status
+    pub async fn update_google_sync_enabled(&self, user_id: &str, enabled: bool) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        #[derive(Serialize)]
+        struct SyncUpdate {
+            sync_enabled: bool,
+            updated_at: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&SyncUpdate {
+                sync_enabled: enabled,
+                updated_at: chrono::Utc::now().to_rfc3339(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update sync status: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set the calendar the Google sync writes to and the calendars read
+    /// from; `restart_sync` clears where the sync left off, for a new
+    /// sync calendar
+    pub async fn update_google_calendars(
+        &self,
+        user_id: &str,
+        calendar_id: &str,
+        selected_calendar_ids: &[String],
+        restart_sync: bool,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        let mut update = serde_json::json!({
+            "calendar_id": calendar_id,
+            "selected_calendar_ids": selected_calendar_ids,
+            "updated_at": chrono::Utc::now().to_rfc3339(),
+        });
+        if restart_sync {
+            update["sync_token"] = serde_json::Value::Null;
+            update["sync_cursor"] = serde_json::Value::Null;
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&update)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update Google calendars: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Update meeting's Google event ID
+    pub async fn update_meeting_google_id(
+        &self,
+        meeting_id: &str,
+        google_event_id: &str,
+        google_calendar_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct GoogleEventUpdate {
+            google_event_id: String,
+            google_calendar_id: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&GoogleEventUpdate {
+                google_event_id: google_event_id.to_string(),
+                google_calendar_id: google_calendar_id.to_string(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update Google event ID: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Record where the background Google sync left off
+    pub async fn update_google_sync_position(
+        &self,
+        user_id: &str,
+        sync_token: Option<&str>,
+        sync_cursor: Option<&str>,
+        last_synced_at: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        #[derive(Serialize)]
+        struct SyncPositionUpdate<'a> {
+            sync_token: Option<&'a str>,
+            sync_cursor: Option<&'a str>,
+            last_synced_at: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&SyncPositionUpdate {
+                sync_token,
+                sync_cursor,
+                last_synced_at,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update Google sync position: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set which side wins Google sync conflicts
+    pub async fn update_google_conflict_policy(&self, user_id: &str, policy: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "conflict_policy": policy }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update conflict policy: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the meetings a user keeps in sync with Google events
+    pub async fn get_google_event_links(&self, user_id: &str) -> Result<Vec<GoogleEventLinkRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/google_event_links?user_id=eq.{}&select=user_id,meeting_id,google_event_id,etag,meeting_updated_at",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get Google event links: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Create or update the link between a meeting and its Google event
+    pub async fn save_google_event_link(&self, link: &GoogleEventLinkRow) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/google_event_links?on_conflict=user_id,meeting_id",
+            self.inner.base_url
+        );
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(link)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save Google event link: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Forget the Google event of one meeting, or of all of a user's meetings
+    pub async fn delete_google_event_links(
+        &self,
+        user_id: &str,
+        meeting_id: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let mut url = format!(
+            "{}/rest/v1/google_event_links?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+        if let Some(meeting_id) = meeting_id {
+            url.push_str(&format!("&meeting_id=eq.{}", meeting_id));
+        }
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete Google event links: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Save Microsoft Graph OAuth tokens
+    pub async fn save_outlook_tokens(
+        &self,
+        user_id: &str,
+        access_token: &str,
+        refresh_token: &str,
+        expires_at: &str,
+        email: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_outlook_tokens", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct OutlookTokensPayload<'a> {
+            user_id: &'a str,
+            access_token: &'a str,
+            refresh_token: &'a str,
+            expires_at: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            email: Option<&'a str>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(&OutlookTokensPayload {
+                user_id,
+                access_token,
+                refresh_token,
+                expires_at,
+                email,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save Outlook tokens: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get Microsoft Graph OAuth tokens for a user
+    pub async fn get_outlook_tokens(&self, user_id: &str) -> Result<Option<OutlookTokensRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_outlook_tokens?user_id=eq.{}&limit=1",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get Outlook tokens: {} - {}",
+                status, body
+            )));
+        }
+
+        let tokens: Vec<OutlookTokensRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(tokens.into_iter().next())
+    }
+
+    /// Delete Microsoft Graph OAuth tokens for a user
+    pub async fn delete_outlook_tokens(&self, user_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_outlook_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete Outlook tokens: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Update Outlook sync enabled status
+    pub async fn update_outlook_sync_enabled(&self, user_id: &str, enabled: bool) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_outlook_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
+        );
+
+        #[derive(Serialize)]
+        struct SyncUpdate {
+            sync_enabled: bool,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&SyncUpdate {
+                sync_enabled: enabled,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update Outlook sync status: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Update meeting's Outlook event ID
+    pub async fn update_meeting_outlook_id(
+        &self,
+        meeting_id: &str,
+        outlook_event_id: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct OutlookEventUpdate<'a> {
+            outlook_event_id: &'a str,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&OutlookEventUpdate { outlook_event_id })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update Outlook event ID: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Meeting agenda methods
+    // ==========================================
+
+    /// Get agenda items of a meeting ordered by position
+    pub async fn get_agenda_items(&self, meeting_id: &str) -> Result<Vec<MeetingAgendaItemRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_agenda_items?meeting_id=eq.{}&order=position.asc",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get agenda items: {} - {}",
+                status, body
+            )));
+        }
+
+        let items: Vec<MeetingAgendaItemRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(items)
+    }
+
+    /// Create an agenda item
+    pub async fn create_agenda_item(
+        &self,
+        meeting_id: &str,
+        title: &str,
+        duration_minutes: i32,
+        presenter_id: Option<&str>,
+        position: i32,
+    ) -> Result<MeetingAgendaItemRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_agenda_items", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct AgendaItemPayload {
+            meeting_id: String,
+            title: String,
+            duration_minutes: i32,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            presenter_id: Option<String>,
+            position: i32,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&AgendaItemPayload {
+                meeting_id: meeting_id.to_string(),
+                title: title.to_string(),
+                duration_minutes,
+                presenter_id: presenter_id.map(|s| s.to_string()),
+                position,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create agenda item: {} - {}",
+                status, body
+            )));
+        }
+
+        let items: Vec<MeetingAgendaItemRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        items
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No agenda item returned".to_string()))
+    }
+
+    /// Update an agenda item
+    pub async fn update_agenda_item(
+        &self,
+        item_id: &str,
+        title: Option<&str>,
+        duration_minutes: Option<i32>,
+        presenter_id: Option<&str>,
+        position: Option<i32>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_agenda_items?id=eq.{}",
+            self.inner.base_url, item_id
+        );
+
+        #[derive(Serialize)]
+        struct AgendaItemUpdate {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            title: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            duration_minutes: Option<i32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            presenter_id: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            position: Option<i32>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&AgendaItemUpdate {
+                title: title.map(|s| s.to_string()),
+                duration_minutes,
+                presenter_id: presenter_id.map(|s| s.to_string()),
+                position,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update agenda item: {} - {}",
+                status_code, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Delete an agenda item
+    pub async fn delete_agenda_item(&self, item_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_agenda_items?id=eq.{}",
+            self.inner.base_url, item_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete agenda item: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Meeting notes methods
+    // ==========================================
+
+    /// Get the notes of a meeting, if anyone wrote some
+    pub async fn get_meeting_notes(&self, meeting_id: &str) -> Result<Option<MeetingNotesRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_notes?meeting_id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meeting notes: {} - {}",
+                status, body
+            )));
+        }
+
+        let notes: Vec<MeetingNotesRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(notes.into_iter().next())
+    }
+
+    /// Write the first version of a meeting's notes
+    ///
+    /// Fails with a conflict when someone else wrote them first.
+    pub async fn create_meeting_notes(
+        &self,
+        meeting_id: &str,
+        content: &str,
+        user_id: &str,
+    ) -> Result<MeetingNotesRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_notes", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({
+                "meeting_id": meeting_id,
+                "content": content,
+                "updated_by": user_id,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create meeting notes: {} - {}",
+                status, body
+            )));
+        }
+
+        let notes: Vec<MeetingNotesRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        notes
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No meeting notes returned".to_string()))
+    }
+
+    /// Replace a meeting's notes if they're still at `version`; `None` when
+    /// they changed meanwhile
+    pub async fn update_meeting_notes(
+        &self,
+        meeting_id: &str,
+        content: &str,
+        user_id: &str,
+        version: i32,
+    ) -> Result<Option<MeetingNotesRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_notes?meeting_id=eq.{}&version=eq.{}",
+            self.inner.base_url, meeting_id, version
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({
+                "content": content,
+                "updated_by": user_id,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting notes: {} - {}",
+                status, body
+            )));
+        }
+
+        let notes: Vec<MeetingNotesRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(notes.into_iter().next())
+    }
+
+    /// Get the action items of a meeting, oldest first
+    pub async fn get_action_items(&self, meeting_id: &str) -> Result<Vec<MeetingActionItemRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_action_items?meeting_id=eq.{}&order=created_at.asc",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get action items: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Get the action items assigned to a user across meetings, with the
+    /// meeting each came from, soonest due first
+    pub async fn get_assigned_action_items(
+        &self,
+        assignee_id: &str,
+        include_completed: bool,
+    ) -> Result<Vec<MeetingActionItemRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let mut url = format!(
+            "{}/rest/v1/meeting_action_items?assignee_id=eq.{}&select=*,meetings!inner(title,scheduled_at)&order=due_date.asc.nullslast,created_at.asc",
+            self.inner.base_url, assignee_id
+        );
+        if !include_completed {
+            url.push_str("&completed_at=is.null");
+        }
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get assigned action items: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Create an action item
+    pub async fn create_action_item(
+        &self,
+        meeting_id: &str,
+        title: &str,
+        assignee_id: Option<&str>,
+        due_date: Option<&str>,
+        created_by: &str,
+    ) -> Result<MeetingActionItemRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_action_items", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({
+                "meeting_id": meeting_id,
+                "title": title,
+                "assignee_id": assignee_id,
+                "due_date": due_date,
+                "created_by": created_by,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create action item: {} - {}",
+                status, body
+            )));
+        }
+
+        let items: Vec<MeetingActionItemRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        items
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No action item returned".to_string()))
+    }
+
+    /// Update an action item; `None` when there's no such item
+    pub async fn update_action_item(
+        &self,
+        item_id: &str,
+        update: &ActionItemUpdate,
+    ) -> Result<Option<MeetingActionItemRow>> {
+        self.patch_action_item(item_id, &serde_json::to_value(update)?)
+            .await
+    }
+
+    /// Mark an action item done by `completed_by`, or open again when `None`;
+    /// `None` when there's no such item
+    pub async fn set_action_item_completed(
+        &self,
+        item_id: &str,
+        completed_by: Option<&str>,
+    ) -> Result<Option<MeetingActionItemRow>> {
+        let completed_at = completed_by.map(|_| chrono::Utc::now().to_rfc3339());
+        self.patch_action_item(
+            item_id,
+            &serde_json::json!({
+                "completed_at": completed_at,
+                "completed_by": completed_by,
+            }),
+        )
+        .await
+    }
+
+    async fn patch_action_item(
+        &self,
+        item_id: &str,
+        changes: &serde_json::Value,
+    ) -> Result<Option<MeetingActionItemRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_action_items?id=eq.{}",
+            self.inner.base_url, item_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(changes)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update action item: {} - {}",
+                status, body
+            )));
+        }
+
+        let items: Vec<MeetingActionItemRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(items.into_iter().next())
+    }
+
+    /// Delete an action item
+    pub async fn delete_action_item(&self, item_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_action_items?id=eq.{}",
+            self.inner.base_url, item_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete action item: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Meeting attachment methods
+    // ==========================================
+
+    /// Get the files attached to a meeting, oldest first
+    pub async fn get_meeting_attachments(
+        &self,
+        meeting_id: &str,
+    ) -> Result<Vec<MeetingAttachmentRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attachments?meeting_id=eq.{}&order=created_at.asc",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meeting attachments: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Get a meeting attachment by ID
+    pub async fn get_meeting_attachment(
+        &self,
+        attachment_id: &str,
+    ) -> Result<Option<MeetingAttachmentRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attachments?id=eq.{}",
+            self.inner.base_url, attachment_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meeting attachment: {} - {}",
+                status, body
+            )));
+        }
+
+        let attachments: Vec<MeetingAttachmentRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(attachments.into_iter().next())
+    }
+
+    /// Record a file uploaded to the meeting attachments bucket
+    pub async fn create_meeting_attachment(
+        &self,
+        attachment: &NewMeetingAttachment,
+    ) -> Result<MeetingAttachmentRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_attachments", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(attachment)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create meeting attachment: {} - {}",
+                status, body
+            )));
+        }
+
+        let attachments: Vec<MeetingAttachmentRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        attachments
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No meeting attachment returned".to_string()))
+    }
+
+    /// Delete the record of a meeting attachment; the file is removed apart
+    pub async fn delete_meeting_attachment(&self, attachment_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_attachments?id=eq.{}",
+            self.inner.base_url, attachment_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete meeting attachment: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Meeting tag methods
+    // ==========================================
+
+    /// Get a user's meeting tags, by name
+    pub async fn get_meeting_tags(&self, user_id: &str) -> Result<Vec<MeetingTagRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_tags?user_id=eq.{}&order=name.asc",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meeting tags: {} - {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Get a meeting tag by ID
+    pub async fn get_meeting_tag(&self, tag_id: &str) -> Result<Option<MeetingTagRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_tags?id=eq.{}",
+            self.inner.base_url, tag_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get meeting tag: {} - {}",
+                status, body
+            )));
+        }
+
+        let tags: Vec<MeetingTagRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(tags.into_iter().next())
+    }
+
+    /// Create a meeting tag
+    pub async fn create_meeting_tag(
+        &self,
+        user_id: &str,
+        name: &str,
+        color: &str,
+    ) -> Result<MeetingTagRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/meeting_tags", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&serde_json::json!({
+                "user_id": user_id,
+                "name": name,
+                "color": color,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create meeting tag: {} - {}",
+                status, body
+            )));
+        }
+
+        let tags: Vec<MeetingTagRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        tags.into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No meeting tag returned".to_string()))
+    }
+
+    /// Rename or recolor a meeting tag; its meetings take the new color.
+    /// `None` when the tag doesn't exist
+    pub async fn update_meeting_tag(
+        &self,
+        tag_id: &str,
+        name: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<Option<MeetingTagRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_tags?id=eq.{}",
+            self.inner.base_url, tag_id
+        );
+
+        #[derive(Serialize)]
+        struct MeetingTagUpdate<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            name: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            color: Option<&'a str>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&MeetingTagUpdate { name, color })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting tag: {} - {}",
+                status, body
+            )));
+        }
+
+        let tags: Vec<MeetingTagRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(tags.into_iter().next())
+    }
+
+    /// Delete a meeting tag; its meetings keep their color, untagged
+    pub async fn delete_meeting_tag(&self, tag_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meeting_tags?id=eq.{}",
+            self.inner.base_url, tag_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete meeting tag: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Set the tag and color of a meeting; `None` clears them
+    pub async fn set_meeting_tag(
+        &self,
+        meeting_id: &str,
+        tag_id: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "tag_id": tag_id, "color": color }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to update meeting tag: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Availability block methods
+    // ==========================================
+
+    /// Get active availability blocks of a user
+    pub async fn get_availability_blocks(&self, user_id: &str) -> Result<Vec<AvailabilityBlockRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/availability_blocks?user_id=eq.{}&is_active=eq.true&order=starts_at.asc",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get availability blocks: {} - {}",
+                status, body
+            )));
+        }
+
+        let blocks: Vec<AvailabilityBlockRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(blocks)
+    }
+
+    /// Get a single availability block by ID
+    pub async fn get_availability_block(&self, block_id: &str) -> Result<Option<AvailabilityBlockRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/availability_blocks?id=eq.{}&limit=1",
+            self.inner.base_url, block_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let blocks: Vec<AvailabilityBlockRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(blocks.into_iter().next())
+    }
+
+    /// Create an availability block
+    pub async fn create_availability_block(
+        &self,
+        user_id: &str,
+        title: &str,
+        starts_at: &str,
+        duration_minutes: i32,
+        slot_minutes: i32,
+        recurrence_rule: &str,
+        time_zone: Option<&str>,
+    ) -> Result<AvailabilityBlockRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/availability_blocks", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct AvailabilityBlockPayload {
+            user_id: String,
+            title: String,
+            starts_at: String,
+            duration_minutes: i32,
+            slot_minutes: i32,
+            recurrence_rule: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            time_zone: Option<String>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&AvailabilityBlockPayload {
+                user_id: user_id.to_string(),
+                title: title.to_string(),
+                starts_at: starts_at.to_string(),
+                duration_minutes,
+                slot_minutes,
+                recurrence_rule: recurrence_rule.to_string(),
+                time_zone: time_zone.map(str::to_string),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create availability block: {} - {}",
+                status, body
+            )));
+        }
+
+        let blocks: Vec<AvailabilityBlockRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        blocks
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No availability block returned".to_string()))
+    }
+
+    /// Delete an availability block
+    pub async fn delete_availability_block(&self, block_id: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/availability_blocks?id=eq.{}",
+            self.inner.base_url, block_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to delete availability block: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get times already booked inside an availability block
+    pub async fn get_availability_bookings(
+        &self,
+        block_id: &str,
+        range_start: &str,
+        range_end: &str,
+    ) -> Result<Vec<AvailabilityBookingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Call the database function (meeting details stay private)
+        let url = format!("{}/rest/v1/rpc/get_availability_bookings", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams {
+            block_id: String,
+            range_start: String,
+            range_end: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                block_id: block_id.to_string(),
+                range_start: range_start.to_string(),
+                range_end: range_end.to_string(),
+            })
+            .send_with(&self.inner.policy.idempotent())
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get availability bookings: {} - {}",
+                status, body
+            )));
+        }
+
+        let bookings: Vec<AvailabilityBookingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(bookings)
+    }
+
+    /// Meetings the users organize or haven't declined that may overlap the
+    /// range; recurring meetings are returned whole, to be expanded
+    pub async fn get_busy_meetings(
+        &self,
+        user_ids: &[String],
+        range_start: &str,
+        range_end: &str,
+        exclude_meeting_id: Option<&str>,
+    ) -> Result<Vec<BusyMeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Call the database function (meeting details stay private)
+        let url = format!("{}/rest/v1/rpc/get_busy_meetings", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            user_ids: &'a [String],
+            range_start: &'a str,
+            range_end: &'a str,
+            exclude_meeting_id: Option<&'a str>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                user_ids,
+                range_start,
+                range_end,
+                exclude_meeting_id,
+            })
+            .send_with(&self.inner.policy.idempotent())
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get busy meetings: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<BusyMeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings)
+    }
+
+    /// Book a slot in someone's availability block, creating the meeting
+    /// with the block's owner invited
+    pub async fn book_availability_slot(
+        &self,
+        block_id: &str,
+        title: &str,
+        description: Option<&str>,
+        scheduled_at: &str,
+        duration_minutes: i32,
+    ) -> Result<MeetingRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/rpc/book_availability_slot", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_block_id: &'a str,
+            meeting_title: &'a str,
+            meeting_description: Option<&'a str>,
+            slot_start: &'a str,
+            slot_duration_minutes: i32,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_block_id: block_id,
+                meeting_title: title,
+                meeting_description: description,
+                slot_start: scheduled_at,
+                slot_duration_minutes: duration_minutes,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => {
+                return Err(Error::NotFound("Availability block not found".to_string()));
+            }
+            reqwest::StatusCode::BAD_REQUEST => {
+                return Err(Error::Parse(
+                    "Cannot book your own availability block".to_string(),
+                ));
+            }
+            reqwest::StatusCode::CONFLICT => {
+                return Err(Error::Parse("Requested slot is already booked".to_string()));
+            }
+            _ => {}
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to book availability slot: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        meetings
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No meeting returned".to_string()))
+    }
+
+    // ==========================================
+    // Reminder telemetry methods
+    // ==========================================
+
+    /// Record that a meeting reminder fired for a user
+    pub async fn record_reminder_fired(
+        &self,
+        user_id: &str,
+        meeting_id: &str,
+        offset_minutes: i32,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/reminder_telemetry", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct ReminderFiredPayload {
+            user_id: String,
+            meeting_id: String,
+            offset_minutes: i32,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&ReminderFiredPayload {
+                user_id: user_id.to_string(),
+                meeting_id: meeting_id.to_string(),
+                offset_minutes,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to record reminder: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Record that a user joined a meeting after its reminder fired
+    pub async fn record_reminder_join(
+        &self,
+        user_id: &str,
+        meeting_id: &str,
+        joined_at: &str,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/reminder_telemetry?user_id=eq.{}&meeting_id=eq.{}&joined_at=is.null",
+            self.inner.base_url, user_id, meeting_id
+        );
+
+        #[derive(Serialize)]
+        struct ReminderJoinUpdate {
+            joined_at: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&ReminderJoinUpdate {
+                joined_at: joined_at.to_string(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to record reminder join: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the most recent reminder telemetry of a user
+    pub async fn get_reminder_telemetry(
+        &self,
+        user_id: &str,
+        limit: u32,
+    ) -> Result<Vec<ReminderTelemetryRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/reminder_telemetry?user_id=eq.{}&order=fired_at.desc&limit={}",
+            self.inner.base_url, user_id, limit
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get reminder telemetry: {} - {}",
+                status, body
+            )));
+        }
+
+        let rows: Vec<ReminderTelemetryRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(rows)
+    }
+
+    /// Get a user's reminder preferences
+    pub async fn get_reminder_preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<ReminderPreferencesRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_reminder_preferences?user_id=eq.{}&limit=1",
+            self.inner.base_url, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let rows: Vec<ReminderPreferencesRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(rows.into_iter().next())
+    }
+
+    /// Create or update a user's reminder preferences; earlier reminders are
+    /// kept unless given
+    pub async fn save_reminder_preferences(
+        &self,
+        user_id: &str,
+        reminder_offset_minutes: i32,
+        auto_adjust: bool,
+        extra_offsets_minutes: Option<&[i32]>,
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_reminder_preferences", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct ReminderPreferencesPayload<'a> {
+            user_id: String,
+            reminder_offset_minutes: i32,
+            auto_adjust: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            extra_offsets_minutes: Option<&'a [i32]>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(&ReminderPreferencesPayload {
+                user_id: user_id.to_string(),
+                reminder_offset_minutes,
+                auto_adjust,
+                extra_offsets_minutes,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save reminder preferences: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the working hours of users who set them
+    pub async fn get_user_availability(
+        &self,
+        user_ids: &[String],
+    ) -> Result<Vec<UserAvailabilityRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/user_availability?user_id=in.({})",
+            self.inner.base_url,
+            user_ids.join(",")
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get user availability: {} - {}",
+                status, body
+            )));
+        }
+
+        let rows: Vec<UserAvailabilityRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(rows)
+    }
+
+    /// Create or replace the current user's working hours
+    pub async fn save_user_availability(&self, availability: &UserAvailabilityRow) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/user_availability", self.inner.base_url);
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "resolution=merge-duplicates")
+            .json(availability)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save user availability: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Mark whether a meeting's last reminder went out (organizer only)
+    pub async fn set_meeting_reminder_sent(&self, meeting_id: &str, sent: bool) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=eq.{}",
+            self.inner.base_url, meeting_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "reminder_sent": sent }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to mark meeting reminder: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get the meeting linked to a session
+    pub async fn get_meeting_by_session_id(&self, session_id: &str) -> Result<Option<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?session_id=eq.{}&limit=1",
+            self.inner.base_url, session_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings.into_iter().next())
+    }
+
+    // ==========================================
+    // Bulk meeting methods
+    // ==========================================
+
+    /// Update several meetings in a single call, returning the updated rows
+    pub async fn bulk_update_meetings(
+        &self,
+        meeting_ids: &[String],
+        shift_minutes: Option<i32>,
+        title: Option<&str>,
+        description: Option<&str>,
+        duration_minutes: Option<i32>,
+    ) -> Result<Vec<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Call the database function (one statement for all meetings)
+        let url = format!("{}/rest/v1/rpc/bulk_update_meetings", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams {
+            meeting_ids: Vec<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            shift_minutes: Option<i32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            new_title: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            new_description: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            new_duration_minutes: Option<i32>,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                meeting_ids: meeting_ids.to_vec(),
+                shift_minutes,
+                new_title: title.map(|s| s.to_string()),
+                new_description: description.map(|s| s.to_string()),
+                new_duration_minutes: duration_minutes,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to bulk update meetings: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings)
+    }
+
+    /// Cancel several meetings in a single call, returning the cancelled rows
+    pub async fn bulk_cancel_meetings(&self, meeting_ids: &[String]) -> Result<Vec<MeetingRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/meetings?id=in.({})",
+            self.inner.base_url,
+            meeting_ids.join(",")
+        );
+
+        #[derive(Serialize)]
+        struct MeetingStatusUpdate {
+            status: String,
+        }
+
+        let response = self
+            .inner
+            .client
+            .patch(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&MeetingStatusUpdate {
+                status: "cancelled".to_string(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to bulk cancel meetings: {} - {}",
+                status, body
+            )));
+        }
+
+        let meetings: Vec<MeetingRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(meetings)
+    }
+
+    // ==========================================
+    // Scheduling poll methods
+    // ==========================================
+
+    /// Create a scheduling poll in a conversation
+    pub async fn create_scheduling_poll(
+        &self,
+        conversation_id: &str,
+        created_by: &str,
+        title: &str,
+        description: Option<&str>,
+        duration_minutes: i32,
+    ) -> Result<SchedulingPollRow> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/scheduling_polls", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct PollPayload {
+            conversation_id: String,
+            created_by: String,
+            title: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<String>,
+            duration_minutes: i32,
+        }
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&PollPayload {
+                conversation_id: conversation_id.to_string(),
+                created_by: created_by.to_string(),
+                title: title.to_string(),
+                description: description.map(|s| s.to_string()),
+                duration_minutes,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create poll: {} - {}",
+                status, body
+            )));
+        }
+
+        let polls: Vec<SchedulingPollRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        polls
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No poll returned".to_string()))
+    }
+
+    /// Add candidate slots to a poll (single batched insert)
+    pub async fn create_poll_options(
+        &self,
+        poll_id: &str,
+        slots: &[String],
+    ) -> Result<Vec<SchedulingPollOptionRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!("{}/rest/v1/scheduling_poll_options", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct PollOptionPayload {
+            poll_id: String,
+            starts_at: String,
+            position: i32,
+        }
+
+        let payload: Vec<PollOptionPayload> = slots
+            .iter()
+            .enumerate()
+            .map(|(i, starts_at)| PollOptionPayload {
+                poll_id: poll_id.to_string(),
+                starts_at: starts_at.clone(),
+                position: i as i32,
+            })
+            .collect();
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to create poll options: {} - {}",
+                status, body
+            )));
+        }
+
+        let options: Vec<SchedulingPollOptionRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(options)
+    }
+
+    /// Get a scheduling poll by ID
+    pub async fn get_scheduling_poll(&self, poll_id: &str) -> Result<Option<SchedulingPollRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/scheduling_polls?id=eq.{}&limit=1",
+            self.inner.base_url, poll_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+
+        let polls: Vec<SchedulingPollRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(polls.into_iter().next())
+    }
+
+    /// Get the candidate slots of a poll
+    pub async fn get_poll_options(&self, poll_id: &str) -> Result<Vec<SchedulingPollOptionRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/scheduling_poll_options?poll_id=eq.{}&order=position.asc",
+            self.inner.base_url, poll_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get poll options: {} - {}",
+                status, body
+            )));
+        }
+
+        let options: Vec<SchedulingPollOptionRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(options)
+    }
+
+    /// Get all votes of a poll
+    pub async fn get_poll_votes(&self, poll_id: &str) -> Result<Vec<SchedulingPollVoteRow>> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/rest/v1/scheduling_poll_votes?poll_id=eq.{}&select=poll_id,option_id,user_id",
+            self.inner.base_url, poll_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get poll votes: {} - {}",
+                status, body
+            )));
+        }
+
+        let votes: Vec<SchedulingPollVoteRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(votes)
+    }
+
+    /// Replace a user's votes on a poll
+    pub async fn set_poll_votes(
+        &self,
+        poll_id: &str,
+        user_id: &str,
+        option_ids: &[String],
+    ) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        // Remove previous votes
+        let url = format!(
+            "{}/rest/v1/scheduling_poll_votes?poll_id=eq.{}&user_id=eq.{}",
+            self.inner.base_url, poll_id, user_id
+        );
+
+        let response = self
+            .inner
+            .client
+            .delete(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to clear poll votes: {} - {}",
+                status, body
+            )));
+        }
+
+        if option_ids.is_empty() {
+            return Ok(());
+        }
+
+        // Insert the new selection
+        let url = format!("{}/rest/v1/scheduling_poll_votes", self.inner.base_url);
+
+        let payload: Vec<SchedulingPollVoteRow> = option_ids
+            .iter()
+            .map(|option_id| SchedulingPollVoteRow {
+                poll_id: poll_id.to_string(),
+                option_id: option_id.clone(),
+                user_id: user_id.to_string(),
+            })
+            .collect();
+
+        let response = self
+            .inner
+            .client
+            .post(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to save poll votes: {} - {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Close a poll and create the meeting in its winning slot.
+    ///
+    /// Returns the new meeting id, or `None` when the poll had no votes or
+    /// was already closed.
+    pub async fn finalize_scheduling_poll(&self, poll_id: &str) -> Result<Option<String>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/meetings", self.inner.base_url);
+        let url = format!(
+            "{}/rest/v1/rpc/finalize_scheduling_poll",
+            self.inner.base_url
+        );
 
-        let payload = CreateMeetingPayload {
-            organizer_id: organizer_id.to_string(),
-            title: title.to_string(),
-            description: description.map(|s| s.to_string()),
-            scheduled_at: scheduled_at.to_string(),
-            duration_minutes,
-            recurrence_rule: recurrence_rule.map(|s| s.to_string()),
-        };
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_poll_id: &'a str,
+        }
 
         let response = self
             .inner
@@ -1288,148 +7574,135 @@ impl SupabaseClient {
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .header("Prefer", "return=representation")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .json(&FunctionParams {
+                target_poll_id: poll_id,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(Error::Auth(
+                "Only the poll creator can close it".to_string(),
+            ));
+        }
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to create meeting: {} - {}",
+                "Failed to close poll: {} - {}",
                 status, body
             )));
         }
 
-        let meetings: Vec<MeetingRow> = response
+        response
             .json()
             .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        meetings
-            .into_iter()
-            .next()
-            .ok_or_else(|| Error::Database("No meeting returned".to_string()))
+            .map_err(|e| Error::Parse(e.to_string()))
     }
 
-    /// Update a meeting
-    pub async fn update_meeting(
-        &self,
-        meeting_id: &str,
-        title: Option<&str>,
-        description: Option<&str>,
-        scheduled_at: Option<&str>,
-        duration_minutes: Option<i32>,
-        status: Option<&str>,
-    ) -> Result<()> {
+    // ==========================================
+    // Edge function methods
+    // ==========================================
+
+    /// Fetch short-lived TURN credentials from the `turn-credentials` edge function
+    pub async fn fetch_turn_credentials(&self) -> Result<TurnCredentials> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/meetings?id=eq.{}",
-            self.inner.base_url, meeting_id
-        );
-
-        #[derive(Serialize)]
-        struct MeetingUpdate {
-            #[serde(skip_serializing_if = "Option::is_none")]
-            title: Option<String>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            description: Option<String>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            scheduled_at: Option<String>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            duration_minutes: Option<i32>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            status: Option<String>,
-        }
+        let url = self.function_url("turn-credentials");
 
         let response = self
             .inner
             .client
-            .patch(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&MeetingUpdate {
-                title: title.map(|s| s.to_string()),
-                description: description.map(|s| s.to_string()),
-                scheduled_at: scheduled_at.map(|s| s.to_string()),
-                duration_minutes,
-                status: status.map(|s| s.to_string()),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            let status_code = response.status();
+            let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(Error::Database(format!(
-                "Failed to update meeting: {} - {}",
-                status_code, body
+            return Err(Error::Network(format!(
+                "Failed to fetch TURN credentials: {} - {}",
+                status, body
             )));
         }
 
-        Ok(())
+        response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))
     }
 
-    /// Delete a meeting
-    pub async fn delete_meeting(&self, meeting_id: &str) -> Result<()> {
+    // ==========================================
+    // Calendar feed methods
+    // ==========================================
+
+    /// Public URL of an edge function
+    pub fn function_url(&self, name: &str) -> String {
+        format!("{}/functions/v1/{}", self.inner.base_url, name)
+    }
+
+    /// Get the user's ICS feed token
+    pub async fn get_calendar_feed_token(&self, user_id: &str) -> Result<Option<CalendarFeedTokenRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
         let url = format!(
-            "{}/rest/v1/meetings?id=eq.{}",
-            self.inner.base_url, meeting_id
+            "{}/rest/v1/calendar_feed_tokens?user_id=eq.{}&limit=1",
+            self.inner.base_url, user_id
         );
 
         let response = self
             .inner
             .client
-            .delete(&url)
+            .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to delete meeting: {} - {}",
+                "Failed to get calendar feed token: {} - {}",
                 status, body
             )));
         }
 
-        Ok(())
+        let rows: Vec<CalendarFeedTokenRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(rows.into_iter().next())
     }
 
-    /// Add an attendee to a meeting
-    pub async fn add_meeting_attendee(
+    /// Create or replace the user's ICS feed token
+    pub async fn save_calendar_feed_token(
         &self,
-        meeting_id: &str,
         user_id: &str,
-    ) -> Result<()> {
+        feed_token: &str,
+    ) -> Result<CalendarFeedTokenRow> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/meeting_attendees", self.inner.base_url);
+        let url = format!("{}/rest/v1/calendar_feed_tokens", self.inner.base_url);
 
-        let payload = AddMeetingAttendeePayload {
-            meeting_id: meeting_id.to_string(),
-            user_id: user_id.to_string(),
-            response_status: "invited".to_string(),
-        };
+        #[derive(Serialize)]
+        struct FeedTokenPayload {
+            user_id: String,
+            token: String,
+        }
 
         let response = self
             .inner
@@ -1438,37 +7711,43 @@ impl SupabaseClient {
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Prefer", "resolution=merge-duplicates,return=representation")
+            .json(&FeedTokenPayload {
+                user_id: user_id.to_string(),
+                token: feed_token.to_string(),
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to add attendee: {} - {}",
+                "Failed to save calendar feed token: {} - {}",
                 status, body
             )));
         }
 
-        Ok(())
+        let rows: Vec<CalendarFeedTokenRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        rows.into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No calendar feed token returned".to_string()))
     }
 
-    /// Remove an attendee from a meeting
-    pub async fn remove_meeting_attendee(
-        &self,
-        meeting_id: &str,
-        user_id: &str,
-    ) -> Result<()> {
+    /// Delete the user's ICS feed token, disabling the feed
+    pub async fn delete_calendar_feed_token(&self, user_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
         let url = format!(
-            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}&user_id=eq.{}",
-            self.inner.base_url, meeting_id, user_id
+            "{}/rest/v1/calendar_feed_tokens?user_id=eq.{}",
+            self.inner.base_url, user_id
         );
 
         let response = self
@@ -1477,15 +7756,14 @@ impl SupabaseClient {
             .delete(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to remove attendee: {} - {}",
+                "Failed to delete calendar feed token: {} - {}",
                 status, body
             )));
         }
@@ -1493,198 +7771,226 @@ impl SupabaseClient {
         Ok(())
     }
 
-    /// Update attendee response status
-    pub async fn update_attendee_response(
-        &self,
-        meeting_id: &str,
-        user_id: &str,
-        response_status: &str,
-    ) -> Result<()> {
+    // ==========================================
+    // Announcement methods
+    // ==========================================
+
+    /// Get announcements that have started and not ended yet
+    ///
+    /// Announcements are public, so they also reach signed-out clients.
+    pub async fn get_active_announcements(&self) -> Result<Vec<AnnouncementRow>> {
         let token = self
             .get_access_token()
             .await
-            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+            .unwrap_or_else(|| self.inner.anon_key.clone());
 
+        let now = urlencoding::encode(&chrono::Utc::now().to_rfc3339()).into_owned();
         let url = format!(
-            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}&user_id=eq.{}",
-            self.inner.base_url, meeting_id, user_id
+            "{}/rest/v1/app_announcements?select=*&starts_at=lte.{}&or=(ends_at.is.null,ends_at.gt.{})&order=starts_at.asc",
+            self.inner.base_url, now, now
         );
 
-        #[derive(Serialize)]
-        struct ResponseUpdate {
-            response_status: String,
-            responded_at: String,
-        }
-
         let response = self
             .inner
             .client
-            .patch(&url)
+            .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&ResponseUpdate {
-                response_status: response_status.to_string(),
-                responded_at: chrono::Utc::now().to_rfc3339(),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            let status_code = response.status();
+            let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to update response: {} - {}",
-                status_code, body
+                "Failed to get announcements: {} - {}",
+                status, body
             )));
         }
 
-        Ok(())
+        let announcements: Vec<AnnouncementRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(announcements)
     }
 
-    /// Get meeting attendees with profiles
-    pub async fn get_meeting_attendees(
+    // ==========================================
+    // Device methods
+    // ==========================================
+
+    /// Record this device for the signed-in user; false when the device ID
+    /// is registered to another user or with another secret
+    pub async fn register_device(
         &self,
-        meeting_id: &str,
-    ) -> Result<Vec<AttendeeWithProfile>> {
+        device_id: &str,
+        device_secret: &str,
+        platform: &str,
+        app_version: &str,
+    ) -> Result<bool> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/meeting_attendees?meeting_id=eq.{}",
-            self.inner.base_url, meeting_id
-        );
+        let url = format!("{}/rest/v1/rpc/register_device", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct FunctionParams<'a> {
+            target_device_id: &'a str,
+            device_secret: &'a str,
+            device_platform: &'a str,
+            device_app_version: &'a str,
+        }
 
         let response = self
             .inner
             .client
-            .get(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_device_id: device_id,
+                device_secret,
+                device_platform: platform,
+                device_app_version: app_version,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
-        if !response.status().is_success() {
-            return Ok(vec![]);
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Ok(false);
         }
-
-        let attendees: Vec<MeetingAttendeeRow> = response
-            .json()
-            .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
-
-        // Get user profiles
-        let user_ids: Vec<String> = attendees.iter().map(|a| a.user_id.clone()).collect();
-        if user_ids.is_empty() {
-            return Ok(vec![]);
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to register device: {} - {}",
+                status, body
+            )));
         }
 
-        let profiles = self.get_user_profiles(&user_ids).await?;
-
-        let result: Vec<AttendeeWithProfile> = attendees
-            .into_iter()
-            .map(|a| {
-                let profile = profiles.iter().find(|p| p.user_id == a.user_id);
-                AttendeeWithProfile {
-                    user_id: a.user_id.clone(),
-                    display_name: profile
-                        .and_then(|p| p.display_name.clone())
-                        .unwrap_or_else(|| a.user_id.clone()),
-                    avatar_url: profile.and_then(|p| p.avatar_url.clone()),
-                    response_status: a.response_status,
-                    responded_at: a.responded_at,
-                }
-            })
-            .collect();
-
-        Ok(result)
+        Ok(true)
     }
 
-    /// Get upcoming meetings for a user
-    pub async fn get_upcoming_meetings(
+    /// The wipe requested for a device and not carried out yet
+    ///
+    /// Works signed out, so a device whose sessions were revoked still wipes.
+    pub async fn get_pending_device_wipe(
         &self,
-        user_id: &str,
-        limit: u32,
-    ) -> Result<Vec<MeetingRow>> {
+        device_id: &str,
+        device_secret: &str,
+    ) -> Result<Option<PendingDeviceWipeRow>> {
         let token = self
             .get_access_token()
             .await
-            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
-
-        let now = chrono::Utc::now().to_rfc3339();
+            .unwrap_or_else(|| self.inner.anon_key.clone());
 
-        // Get meeting IDs where user is attendee
-        let attendee_url = format!(
-            "{}/rest/v1/meeting_attendees?user_id=eq.{}&select=meeting_id",
-            self.inner.base_url, user_id
+        let url = format!(
+            "{}/rest/v1/rpc/get_pending_device_wipe",
+            self.inner.base_url
         );
 
         let response = self
             .inner
             .client
-            .get(&attendee_url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
-
-        #[derive(Deserialize)]
-        struct AttendeeRow {
-            meeting_id: String,
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "target_device_id": device_id,
+                "device_secret": device_secret,
+            }))
+            .send_with(&self.inner.policy)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to check device wipe: {} - {}",
+                status, body
+            )));
         }
 
-        let attendees: Vec<AttendeeRow> = if response.status().is_success() {
-            response.json().await.unwrap_or_default()
-        } else {
-            vec![]
-        };
+        let wipes: Vec<PendingDeviceWipeRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
 
-        let meeting_ids: Vec<String> = attendees.iter().map(|a| a.meeting_id.clone()).collect();
+        Ok(wipes.into_iter().next())
+    }
 
-        let mut url = format!(
-            "{}/rest/v1/meetings?scheduled_at=gte.{}&status=eq.scheduled&order=scheduled_at.asc&limit={}",
-            self.inner.base_url, now, limit
-        );
+    /// Mark a device's pending wipe done, auditing what was removed
+    ///
+    /// Sent with the anon key, as the wipe has just deleted the session.
+    pub async fn complete_device_wipe<T: Serialize>(
+        &self,
+        device_id: &str,
+        device_secret: &str,
+        report: &T,
+    ) -> Result<bool> {
+        let url = format!("{}/rest/v1/rpc/complete_device_wipe", self.inner.base_url);
 
-        if !meeting_ids.is_empty() {
-            let ids_param = meeting_ids.join(",");
-            url.push_str(&format!("&or=(organizer_id.eq.{},id.in.({}))", user_id, ids_param));
-        } else {
-            url.push_str(&format!("&organizer_id=eq.{}", user_id));
+        #[derive(Serialize)]
+        struct FunctionParams<'a, T> {
+            target_device_id: &'a str,
+            device_secret: &'a str,
+            wipe_report: &'a T,
         }
 
         let response = self
             .inner
             .client
-            .get(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Authorization", format!("Bearer {}", self.inner.anon_key))
+            .header("Content-Type", "application/json")
+            .json(&FunctionParams {
+                target_device_id: device_id,
+                device_secret,
+                wipe_report: report,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            return Ok(vec![]);
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to record device wipe: {} - {}",
+                status, body
+            )));
         }
 
-        let meetings: Vec<MeetingRow> = response
+        response
             .json()
             .await
-            .map_err(|e| Error::Parse(e.to_string()))?;
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
 
-        Ok(meetings)
+    // ==========================================
+    // Storage methods
+    // ==========================================
+
+    /// Public URL of an object in a public bucket
+    pub fn storage_public_url(&self, bucket: &str, path: &str) -> String {
+        format!(
+            "{}/storage/v1/object/public/{}/{}",
+            self.inner.base_url, bucket, path
+        )
     }
 
-    /// Link a meeting to a session
-    pub async fn link_meeting_to_session(
+    /// Upload an object to a storage bucket, failing if the path is taken
+    pub async fn upload_storage_object(
         &self,
-        meeting_id: &str,
-        session_id: &str,
+        bucket: &str,
+        path: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
     ) -> Result<()> {
         let token = self
             .get_access_token()
@@ -1692,110 +7998,119 @@ impl SupabaseClient {
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
         let url = format!(
-            "{}/rest/v1/meetings?id=eq.{}",
-            self.inner.base_url, meeting_id
+            "{}/storage/v1/object/{}/{}",
+            self.inner.base_url, bucket, path
         );
 
-        #[derive(Serialize)]
-        struct SessionLink {
-            session_id: String,
-            status: String,
-        }
-
         let response = self
             .inner
             .client
-            .patch(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&SessionLink {
-                session_id: session_id.to_string(),
-                status: "ongoing".to_string(),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", content_type)
+            .header("x-upsert", "false")
+            .body(bytes)
+            .send_with(&self.inner.policy.with_timeout(Some(UPLOAD_TIMEOUT)))
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to link session: {} - {}",
-                status, body
+                "Failed to upload {} to {}: {} - {}",
+                path, bucket, status, body
             )));
         }
 
         Ok(())
     }
 
-    /// Save Google OAuth tokens
-    pub async fn save_google_tokens(
+    /// Download an object from a private bucket, returning the response so
+    /// the body can be read in chunks
+    pub async fn download_storage_object(
         &self,
-        user_id: &str,
-        access_token: &str,
-        refresh_token: &str,
-        expires_at: &str,
-        email: Option<&str>,
-    ) -> Result<()> {
+        bucket: &str,
+        path: &str,
+    ) -> Result<reqwest::Response> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!("{}/rest/v1/user_google_tokens", self.inner.base_url);
+        let url = format!(
+            "{}/storage/v1/object/authenticated/{}/{}",
+            self.inner.base_url, bucket, path
+        );
 
-        #[derive(Serialize)]
-        struct GoogleTokensPayload {
-            user_id: String,
-            access_token: String,
-            refresh_token: String,
-            expires_at: String,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            email: Option<String>,
+        let response = self
+            .inner
+            .client
+            .get(&url)
+            .header("apikey", &self.inner.anon_key)
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with(&self.inner.policy.with_timeout(None))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to download {} from {}: {} - {}",
+                path, bucket, status, body
+            )));
         }
 
+        Ok(response)
+    }
+
+    /// Delete an object from a storage bucket
+    pub async fn delete_storage_object(&self, bucket: &str, path: &str) -> Result<()> {
+        let token = self
+            .get_access_token()
+            .await
+            .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
+
+        let url = format!(
+            "{}/storage/v1/object/{}/{}",
+            self.inner.base_url, bucket, path
+        );
+
         let response = self
             .inner
             .client
-            .post(&url)
+            .delete(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .header("Prefer", "resolution=merge-duplicates")
-            .json(&GoogleTokensPayload {
-                user_id: user_id.to_string(),
-                access_token: access_token.to_string(),
-                refresh_token: refresh_token.to_string(),
-                expires_at: expires_at.to_string(),
-                email: email.map(|s| s.to_string()),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to save Google tokens: {} - {}",
-                status, body
+                "Failed to delete {} from {}: {} - {}",
+                path, bucket, status, body
             )));
         }
 
         Ok(())
     }
 
-    /// Get Google OAuth tokens for a user
-    pub async fn get_google_tokens(&self, user_id: &str) -> Result<Option<GoogleTokensRow>> {
+    // ==========================================
+    // Custom emoji methods
+    // ==========================================
+
+    /// Get every custom emoji of the team
+    pub async fn get_custom_emojis(&self) -> Result<Vec<CustomEmojiRow>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
         let url = format!(
-            "{}/rest/v1/user_google_tokens?user_id=eq.{}&limit=1",
-            self.inner.base_url, user_id
+            "{}/rest/v1/custom_emojis?select=*&order=shortcode.asc",
+            self.inner.base_url
         );
 
         let response = self
@@ -1804,94 +8119,111 @@ impl SupabaseClient {
             .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            return Ok(None);
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Database(format!(
+                "Failed to get custom emoji: {} - {}",
+                status, body
+            )));
         }
 
-        let tokens: Vec<GoogleTokensRow> = response
+        let emojis: Vec<CustomEmojiRow> = response
             .json()
             .await
             .map_err(|e| Error::Parse(e.to_string()))?;
 
-        Ok(tokens.into_iter().next())
+        Ok(emojis)
     }
 
-    /// Delete Google OAuth tokens for a user
-    pub async fn delete_google_tokens(&self, user_id: &str) -> Result<()> {
+    /// Add a custom emoji whose image is already uploaded
+    pub async fn create_custom_emoji(
+        &self,
+        shortcode: &str,
+        storage_path: &str,
+        image_url: &str,
+        created_by: &str,
+    ) -> Result<CustomEmojiRow> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
-            self.inner.base_url, user_id
-        );
+        let url = format!("{}/rest/v1/custom_emojis", self.inner.base_url);
+
+        #[derive(Serialize)]
+        struct CustomEmojiPayload<'a> {
+            shortcode: &'a str,
+            storage_path: &'a str,
+            image_url: &'a str,
+            created_by: &'a str,
+        }
 
         let response = self
             .inner
             .client
-            .delete(&url)
+            .post(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=representation")
+            .json(&CustomEmojiPayload {
+                shortcode,
+                storage_path,
+                image_url,
+                created_by,
+            })
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to delete Google tokens: {} - {}",
+                "Failed to create custom emoji: {} - {}",
                 status, body
             )));
         }
 
-        Ok(())
+        let rows: Vec<CustomEmojiRow> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        rows.into_iter()
+            .next()
+            .ok_or_else(|| Error::Database("No custom emoji returned".to_string()))
     }
 
-    /// Update Google sync enabled status
-    pub async fn update_google_sync_enabled(&self, user_id: &str, enabled: bool) -> Result<()> {
+    /// Delete a custom emoji; its image is removed separately
+    pub async fn delete_custom_emoji(&self, emoji_id: &str) -> Result<()> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
         let url = format!(
-            "{}/rest/v1/user_google_tokens?user_id=eq.{}",
-            self.inner.base_url, user_id
+            "{}/rest/v1/custom_emojis?id=eq.{}",
+            self.inner.base_url, emoji_id
         );
 
-        #[derive(Serialize)]
-        struct SyncUpdate {
-            sync_enabled: bool,
-            updated_at: String,
-        }
-
         let response = self
             .inner
             .client
-            .patch(&url)
+            .delete(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&SyncUpdate {
-                sync_enabled: enabled,
-                updated_at: chrono::Utc::now().to_rfc3339(),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to update sync status: {} - {}",
+                "Failed to delete custom emoji: {} - {}",
                 status, body
             )));
         }
@@ -1899,54 +8231,97 @@ impl SupabaseClient {
         Ok(())
     }
 
-    /// Update meeting's Google event ID
-    pub async fn update_meeting_google_id(
+    // ==========================================
+    // Change feed methods
+    // ==========================================
+
+    /// Get meetings changed after a feed position, oldest change first
+    pub async fn get_changed_meetings(
         &self,
-        meeting_id: &str,
-        google_event_id: &str,
-        google_calendar_id: &str,
-    ) -> Result<()> {
+        after: Option<&FeedPosition>,
+        limit: u32,
+    ) -> Result<Vec<MeetingRow>> {
+        self.get_changed_rows("meetings", after, limit).await
+    }
+
+    /// Get conversations changed after a feed position, oldest change first
+    pub async fn get_changed_conversations(
+        &self,
+        after: Option<&FeedPosition>,
+        limit: u32,
+    ) -> Result<Vec<ConversationRow>> {
+        self.get_changed_rows("conversations", after, limit).await
+    }
+
+    /// Get messages changed after a feed position, oldest change first
+    pub async fn get_changed_messages(
+        &self,
+        after: Option<&FeedPosition>,
+        limit: u32,
+    ) -> Result<Vec<MessageRow>> {
+        self.get_changed_rows("messages", after, limit).await
+    }
+
+    /// Get tombstones recorded after a feed position, oldest first
+    pub async fn get_deleted_entities(
+        &self,
+        after: Option<&FeedPosition>,
+        limit: u32,
+    ) -> Result<Vec<DeletedEntityRow>> {
+        self.get_changed_rows("deleted_entities", after, limit)
+            .await
+    }
+
+    /// Page through a table by `(updated_at, id)`; RLS keeps it to the rows
+    /// the user can see
+    async fn get_changed_rows<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        after: Option<&FeedPosition>,
+        limit: u32,
+    ) -> Result<Vec<T>> {
         let token = self
             .get_access_token()
             .await
             .ok_or_else(|| Error::Auth("Not authenticated".to_string()))?;
 
-        let url = format!(
-            "{}/rest/v1/meetings?id=eq.{}",
-            self.inner.base_url, meeting_id
+        let mut url = format!(
+            "{}/rest/v1/{}?updated_at=not.is.null&order=updated_at.asc,id.asc&limit={}",
+            self.inner.base_url, table, limit
         );
 
-        #[derive(Serialize)]
-        struct GoogleEventUpdate {
-            google_event_id: String,
-            google_calendar_id: String,
+        if let Some(after) = after {
+            let updated_at = urlencoding::encode(&after.updated_at);
+            url.push_str(&format!(
+                "&or=(updated_at.gt.\"{}\",and(updated_at.eq.\"{}\",id.gt.{}))",
+                updated_at, updated_at, after.id
+            ));
         }
 
         let response = self
             .inner
             .client
-            .patch(&url)
+            .get(&url)
             .header("apikey", &self.inner.anon_key)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&GoogleEventUpdate {
-                google_event_id: google_event_id.to_string(),
-                google_calendar_id: google_calendar_id.to_string(),
-            })
-            .send()
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+            .send_with(&self.inner.policy)
+            .await?;
 
         if !response.status().is_success() {
-            let status_code = response.status();
+            let status = response.status();
             let body = response.text().await.unwrap_or_default();
             return Err(Error::Database(format!(
-                "Failed to update Google event ID: {} - {}",
-                status_code, body
+                "Failed to get changed {}: {} - {}",
+                table, status, body
             )));
         }
 
-        Ok(())
+        let rows: Vec<T> = response
+            .json()
+            .await
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        Ok(rows)
     }
 }
 
@@ -1956,6 +8331,23 @@ impl Default for SupabaseClient {
     }
 }
 
+/// Default request policy, with the timeout and retries overridable from
+/// the environment
+fn policy_from_env() -> RequestPolicy {
+    let mut policy = RequestPolicy::default();
+    if let Some(secs) = env_number(SUPABASE_TIMEOUT_ENV) {
+        policy = policy.with_timeout(Some(Duration::from_secs(secs)));
+    }
+    if let Some(retries) = env_number(SUPABASE_MAX_RETRIES_ENV) {
+        policy = policy.with_max_retries(retries);
+    }
+    policy
+}
+
+fn env_number<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok()?.trim().parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1971,4 +8363,17 @@ mod tests {
         let result = SupabaseClient::new();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_policy_from_env() {
+        std::env::set_var(SUPABASE_TIMEOUT_ENV, "30");
+        std::env::set_var(SUPABASE_MAX_RETRIES_ENV, "not a number");
+
+        let policy = policy_from_env();
+        assert_eq!(policy.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(policy.max_retries, RequestPolicy::default().max_retries);
+
+        std::env::remove_var(SUPABASE_TIMEOUT_ENV);
+        std::env::remove_var(SUPABASE_MAX_RETRIES_ENV);
+    }
 }
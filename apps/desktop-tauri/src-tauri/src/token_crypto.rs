@@ -0,0 +1,146 @@
+//! Encryption of OAuth tokens at rest
+//!
+//! Google Calendar tokens live in `user_google_tokens`, so anyone able to
+//! read the row could act on the user's calendar. They are sealed with
+//! XChaCha20-Poly1305 before they leave the client, under a key kept in
+//! this device's keychain; the user ID is bound as associated data, so a
+//! sealed token copied into another user's row doesn't open. The key is
+//! also escrowed in `user_google_token_keys`, readable only with the
+//! service role, so the `google-calendar-sync` edge function can refresh
+//! and sync in the background.
+//!
+//! Values without the sealed prefix are tokens stored before encryption,
+//! read as they are until they are saved again.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+
+use crate::{Error, Result};
+
+/// Prefix of sealed tokens, followed by the base64 nonce and ciphertext
+const SEALED_PREFIX: &str = "tok.v1:";
+
+const NONCE_BYTES: usize = 24;
+
+/// New random key, base64 encoded for the keychain
+pub fn generate_key() -> String {
+    let mut key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    encode_key(&key)
+}
+
+/// Key as stored in the keychain and escrowed
+pub fn encode_key(key: &[u8; 32]) -> String {
+    BASE64.encode(key)
+}
+
+/// Key as stored in the keychain
+pub fn decode_key(stored: &str) -> Result<[u8; 32]> {
+    BASE64
+        .decode(stored)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| Error::Parse("Invalid token key".to_string()))
+}
+
+/// Whether a stored token is sealed
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Seal a token of a user
+pub fn seal(key: &[u8; 32], user_id: &str, token: &str) -> Result<String> {
+    let mut nonce = [0u8; NONCE_BYTES];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: token.as_bytes(),
+                aad: user_id.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::External("Failed to encrypt token".to_string()))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(format!("{}{}", SEALED_PREFIX, BASE64.encode(sealed)))
+}
+
+/// Open a stored token of a user; tokens stored before encryption come
+/// back as they are
+pub fn open(key: &[u8; 32], user_id: &str, value: &str) -> Result<String> {
+    let Some(encoded) = value.strip_prefix(SEALED_PREFIX) else {
+        return Ok(value.to_string());
+    };
+    let sealed = BASE64
+        .decode(encoded)
+        .map_err(|_| Error::Parse("Invalid sealed token".to_string()))?;
+    if sealed.len() <= NONCE_BYTES {
+        return Err(Error::Parse("Invalid sealed token".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_BYTES);
+
+    let token = XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: user_id.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::Auth("Token was sealed with another key".to_string()))?;
+    String::from_utf8(token).map_err(|_| Error::Parse("Invalid sealed token".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> [u8; 32] {
+        decode_key(&generate_key()).unwrap()
+    }
+
+    #[test]
+    fn test_seal_round_trip() {
+        let key = key();
+        let sealed = seal(&key, "user-1", "ya29.token").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("ya29"));
+        assert_eq!(open(&key, "user-1", &sealed).unwrap(), "ya29.token");
+    }
+
+    #[test]
+    fn test_seal_uses_fresh_nonces() {
+        let key = key();
+        assert_ne!(
+            seal(&key, "user-1", "token").unwrap(),
+            seal(&key, "user-1", "token").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_open_rejects_other_user_or_key() {
+        let key = key();
+        let sealed = seal(&key, "user-1", "token").unwrap();
+        assert!(open(&key, "user-2", &sealed).is_err());
+        assert!(open(&self::key(), "user-1", &sealed).is_err());
+        assert!(open(&key, "user-1", "tok.v1:AAAA").is_err());
+    }
+
+    #[test]
+    fn test_open_plaintext() {
+        assert!(!is_sealed("ya29.token"));
+        assert_eq!(open(&key(), "user-1", "ya29.token").unwrap(), "ya29.token");
+    }
+
+    #[test]
+    fn test_decode_key() {
+        assert!(decode_key("short").is_err());
+        assert_eq!(decode_key(&BASE64.encode([7u8; 32])).unwrap(), [7u8; 32]);
+        assert_eq!(decode_key(&encode_key(&[7u8; 32])).unwrap(), [7u8; 32]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Agenda and week list utilities
+//!
+//! Expands meetings into the occurrences that fall within a span of days
+//! and groups them by the day they start on, in the viewer's time zone (the
+//! system one when unset), so list views need no month stitching.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use super::datetime::{parse_datetime, parse_time_zone};
+use super::rrule::{occurrences_between, RecurrenceExceptions, MAX_RANGE_OCCURRENCES};
+use crate::{Error, Result};
+
+/// Longest span an agenda covers
+pub const MAX_AGENDA_DAYS: u32 = 62;
+
+/// A day of the agenda and what happens on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaDay<T> {
+    /// The date in YYYY-MM-DD format
+    pub date: String,
+    pub items: Vec<T>,
+}
+
+/// A span of whole days in a time zone
+#[derive(Debug, Clone)]
+pub struct AgendaRange {
+    pub first_day: NaiveDate,
+    pub days: u32,
+    time_zone: Option<Tz>,
+}
+
+impl AgendaRange {
+    /// `days` days from `start_date` (YYYY-MM-DD) in `time_zone`
+    pub fn new(start_date: &str, days: u32, time_zone: Option<&str>) -> Result<Self> {
+        let first_day = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+            .map_err(|e| Error::Parse(format!("Invalid date format: {}", e)))?;
+        if days == 0 || days > MAX_AGENDA_DAYS {
+            return Err(Error::Parse(format!(
+                "Agenda must cover 1 to {} days",
+                MAX_AGENDA_DAYS
+            )));
+        }
+        let time_zone = time_zone.map(parse_time_zone).transpose()?;
+        Ok(Self {
+            first_day,
+            days,
+            time_zone,
+        })
+    }
+
+    /// The week holding `date`, from Monday or Sunday
+    pub fn week(date: &str, start_on_sunday: bool, time_zone: Option<&str>) -> Result<Self> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| Error::Parse(format!("Invalid date format: {}", e)))?;
+        let into_week = if start_on_sunday {
+            date.weekday().num_days_from_sunday()
+        } else {
+            date.weekday().num_days_from_monday()
+        };
+        let first_day = date - Duration::days(into_week as i64);
+        Self::new(&first_day.format("%Y-%m-%d").to_string(), 7, time_zone)
+    }
+
+    /// First instant of the range and first instant after it, in UTC
+    pub fn bounds(&self) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let end_day = self.first_day + Duration::days(self.days as i64);
+        Ok((self.midnight(self.first_day)?, self.midnight(end_day)?))
+    }
+
+    /// Group items by the day their start falls on; every day of the range
+    /// is listed, empty or not. Items already running when the range starts
+    /// go on its first day, and items after it are dropped
+    pub fn group<T>(&self, mut items: Vec<(DateTime<Utc>, T)>) -> Vec<AgendaDay<T>> {
+        items.sort_by_key(|(start, _)| *start);
+
+        let mut days: Vec<AgendaDay<T>> = (0..self.days)
+            .map(|offset| AgendaDay {
+                date: (self.first_day + Duration::days(offset as i64))
+                    .format("%Y-%m-%d")
+                    .to_string(),
+                items: Vec::new(),
+            })
+            .collect();
+        for (start, item) in items {
+            let index = (self.local_date(start) - self.first_day).num_days().max(0);
+            if let Some(day) = days.get_mut(index as usize) {
+                day.items.push(item);
+            }
+        }
+        days
+    }
+
+    fn local_date(&self, dt: DateTime<Utc>) -> NaiveDate {
+        match self.time_zone {
+            Some(tz) => dt.with_timezone(&tz).date_naive(),
+            None => dt.with_timezone(&Local).date_naive(),
+        }
+    }
+
+    /// Start of a day; where DST skips midnight, the first time that exists
+    fn midnight(&self, date: NaiveDate) -> Result<DateTime<Utc>> {
+        let invalid = || Error::Parse(format!("Invalid local date: {}", date));
+        for hour in 0..3 {
+            let naive = date.and_hms_opt(hour, 0, 0).ok_or_else(invalid)?;
+            let start = match self.time_zone {
+                Some(tz) => tz
+                    .from_local_datetime(&naive)
+                    .earliest()
+                    .map(|dt| dt.with_timezone(&Utc)),
+                None => Local
+                    .from_local_datetime(&naive)
+                    .earliest()
+                    .map(|dt| dt.with_timezone(&Utc)),
+            };
+            if let Some(start) = start {
+                return Ok(start);
+            }
+        }
+        Err(invalid())
+    }
+}
+
+/// Starts of a meeting's occurrences overlapping `[range_start, range_end)`
+pub fn occurrences_in_range(
+    scheduled_at: &str,
+    duration_minutes: i32,
+    recurrence_rule: Option<&str>,
+    exceptions: &RecurrenceExceptions,
+    time_zone: Option<&str>,
+    range: (DateTime<Utc>, DateTime<Utc>),
+) -> Result<Vec<DateTime<Utc>>> {
+    let (range_start, range_end) = range;
+    let duration = Duration::minutes(duration_minutes.max(0) as i64);
+
+    let starts = match recurrence_rule {
+        Some(rule) => occurrences_between(
+            rule,
+            scheduled_at,
+            (range_start - duration, range_end),
+            MAX_RANGE_OCCURRENCES,
+            exceptions,
+            time_zone,
+        )?,
+        None => vec![parse_datetime(scheduled_at)?],
+    };
+
+    Ok(starts
+        .into_iter()
+        .filter(|start| {
+            *start < range_end && (*start >= range_start || *start + duration > range_start)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_datetime(s).unwrap()
+    }
+
+    #[test]
+    fn test_range_bounds_in_time_zone() {
+        let range = AgendaRange::new("2026-03-09", 3, Some("America/Sao_Paulo")).unwrap();
+        let (start, end) = range.bounds().unwrap();
+        assert_eq!(start, at("2026-03-09T03:00:00Z"));
+        assert_eq!(end, at("2026-03-12T03:00:00Z"));
+
+        assert!(AgendaRange::new("2026-03-09", 0, None).is_err());
+        assert!(AgendaRange::new("2026-03-09", 90, None).is_err());
+        assert!(AgendaRange::new("09/03/2026", 3, None).is_err());
+    }
+
+    #[test]
+    fn test_week_range() {
+        // Wednesday
+        let monday = AgendaRange::week("2026-03-11", false, Some("UTC")).unwrap();
+        assert_eq!(monday.first_day.to_string(), "2026-03-09");
+        let sunday = AgendaRange::week("2026-03-11", true, Some("UTC")).unwrap();
+        assert_eq!(sunday.first_day.to_string(), "2026-03-08");
+        assert_eq!(sunday.days, 7);
+    }
+
+    #[test]
+    fn test_group_by_local_day() {
+        let range = AgendaRange::new("2026-03-09", 2, Some("America/Sao_Paulo")).unwrap();
+        let days = range.group(vec![
+            // 23:30 on the 9th in São Paulo
+            (at("2026-03-10T02:30:00Z"), "late"),
+            (at("2026-03-09T12:00:00Z"), "standup"),
+            (at("2026-03-10T12:00:00Z"), "standup"),
+            (at("2026-03-12T12:00:00Z"), "outside"),
+            // Still running at midnight
+            (at("2026-03-09T02:30:00Z"), "overnight"),
+        ]);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, "2026-03-09");
+        assert_eq!(days[0].items, vec!["overnight", "standup", "late"]);
+        assert_eq!(days[1].items, vec!["standup"]);
+    }
+
+    #[test]
+    fn test_occurrences_in_range() {
+        let range = (at("2026-03-09T00:00:00Z"), at("2026-03-16T00:00:00Z"));
+        let starts = occurrences_in_range(
+            "2026-02-02T12:00:00Z",
+            30,
+            Some("FREQ=WEEKLY;BYDAY=MO,WE"),
+            &RecurrenceExceptions {
+                exdates: vec!["2026-03-11T12:00:00Z".to_string()],
+                rdates: Vec::new(),
+            },
+            None,
+            range,
+        )
+        .unwrap();
+        assert_eq!(starts, vec![at("2026-03-09T12:00:00Z")]);
+
+        // Started before the range and still running
+        let starts = occurrences_in_range(
+            "2026-03-08T23:30:00Z",
+            60,
+            None,
+            &RecurrenceExceptions::default(),
+            None,
+            range,
+        )
+        .unwrap();
+        assert_eq!(starts.len(), 1);
+
+        let starts = occurrences_in_range(
+            "2026-03-16T00:00:00Z",
+            60,
+            None,
+            &RecurrenceExceptions::default(),
+            None,
+            range,
+        )
+        .unwrap();
+        assert!(starts.is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+//! Availability block (office hours) utilities
+//!
+//! Expands recurring "available for pairing" blocks into bookable slots
+//! and checks that a booking stays inside a block occurrence. Blocks repeat
+//! in their own time zone, so office hours keep their local time across
+//! daylight saving changes.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::rrule::{occurrences_between, RecurrenceExceptions};
+use crate::{Error, Result};
+
+/// Upper bound of occurrences expanded per block within a range
+const MAX_BLOCK_OCCURRENCES: u16 = 1000;
+
+/// A recurring availability block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityBlockSpec {
+    pub starts_at: String,
+    pub duration_minutes: i32,
+    pub slot_minutes: i32,
+    pub recurrence_rule: String,
+    /// IANA time zone the block repeats in; UTC when unset
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+/// A bookable slot inside an availability block occurrence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AvailabilitySlot {
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+/// Split every occurrence of a block within the range into slots
+pub fn expand_block_slots(
+    block: &AvailabilityBlockSpec,
+    range_start: &str,
+    range_end: &str,
+) -> Result<Vec<AvailabilitySlot>> {
+    if block.duration_minutes <= 0 || block.slot_minutes <= 0 {
+        return Err(Error::Parse("Invalid availability block durations".to_string()));
+    }
+
+    let range_start_dt = parse_datetime(range_start)?;
+    let range_end_dt = parse_datetime(range_end)?;
+    let block_duration = Duration::minutes(block.duration_minutes as i64);
+    let slot_duration = Duration::minutes(block.slot_minutes as i64);
+
+    // Include occurrences that started before the range but still overlap it
+    let occurrences = occurrences_between(
+        &block.recurrence_rule,
+        &block.starts_at,
+        (range_start_dt - block_duration, range_end_dt),
+        MAX_BLOCK_OCCURRENCES,
+        &RecurrenceExceptions::default(),
+        block.time_zone.as_deref(),
+    )?;
+
+    let mut slots = Vec::new();
+    for occurrence_start in occurrences {
+        let occurrence_end = occurrence_start + block_duration;
+
+        let mut slot_start = occurrence_start;
+        while slot_start + slot_duration <= occurrence_end {
+            let slot_end = slot_start + slot_duration;
+            if slot_start >= range_start_dt && slot_end <= range_end_dt {
+                slots.push(AvailabilitySlot {
+                    starts_at: format_datetime(&slot_start),
+                    ends_at: format_datetime(&slot_end),
+                });
+            }
+            slot_start = slot_end;
+        }
+    }
+
+    Ok(slots)
+}
+
+/// Check whether a booking fits entirely inside one occurrence of the block
+pub fn fits_in_block(
+    block: &AvailabilityBlockSpec,
+    starts_at: &str,
+    duration_minutes: i32,
+) -> Result<bool> {
+    if duration_minutes <= 0 {
+        return Ok(false);
+    }
+
+    let start = parse_datetime(starts_at)?;
+    let end = start + Duration::minutes(duration_minutes as i64);
+    let block_duration = Duration::minutes(block.duration_minutes as i64);
+
+    let occurrences = occurrences_between(
+        &block.recurrence_rule,
+        &block.starts_at,
+        (start - block_duration, start),
+        MAX_BLOCK_OCCURRENCES,
+        &RecurrenceExceptions::default(),
+        block.time_zone.as_deref(),
+    )?;
+
+    for occurrence_start in occurrences {
+        if occurrence_start <= start && end <= occurrence_start + block_duration {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Check whether two time ranges overlap
+pub fn ranges_overlap(
+    a_start: &str,
+    a_duration_minutes: i32,
+    b_start: &str,
+    b_duration_minutes: i32,
+) -> Result<bool> {
+    let a_start = parse_datetime(a_start)?;
+    let a_end = a_start + Duration::minutes(a_duration_minutes as i64);
+    let b_start = parse_datetime(b_start)?;
+    let b_end = b_start + Duration::minutes(b_duration_minutes as i64);
+
+    Ok(a_start < b_end && b_start < a_end)
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn format_datetime(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    // Try ISO 8601 with Z suffix
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    // Try without timezone (assume UTC)
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Ok(Utc.from_utc_datetime(&dt));
+    }
+
+    Err(Error::Parse(format!("Unable to parse datetime: {}", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weekly_block() -> AvailabilityBlockSpec {
+        // Mondays 14:00-16:00 UTC, 30 minute slots
+        AvailabilityBlockSpec {
+            starts_at: "2025-01-06T14:00:00Z".to_string(),
+            duration_minutes: 120,
+            slot_minutes: 30,
+            recurrence_rule: "FREQ=WEEKLY;BYDAY=MO".to_string(),
+            time_zone: None,
+        }
+    }
+
+    #[test]
+    fn test_expand_block_slots() {
+        let slots = expand_block_slots(
+            &weekly_block(),
+            "2025-01-13T00:00:00Z",
+            "2025-01-19T23:59:59Z",
+        )
+        .unwrap();
+
+        assert_eq!(slots.len(), 4);
+        assert_eq!(slots[0].starts_at, "2025-01-13T14:00:00Z");
+        assert_eq!(slots[3].ends_at, "2025-01-13T16:00:00Z");
+    }
+
+    #[test]
+    fn test_old_block_in_local_time() {
+        // Daily 09:00-10:00 New York office hours, started years earlier
+        let block = AvailabilityBlockSpec {
+            starts_at: "2025-01-06T14:00:00Z".to_string(),
+            duration_minutes: 60,
+            slot_minutes: 30,
+            recurrence_rule: "FREQ=DAILY".to_string(),
+            time_zone: Some("America/New_York".to_string()),
+        };
+        let slots =
+            expand_block_slots(&block, "2028-07-10T00:00:00Z", "2028-07-10T23:59:59Z").unwrap();
+
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0].starts_at, "2028-07-10T13:00:00Z");
+        assert!(fits_in_block(&block, "2028-07-10T13:30:00Z", 30).unwrap());
+        assert!(!fits_in_block(&block, "2028-07-10T14:00:00Z", 30).unwrap());
+    }
+
+    #[test]
+    fn test_fits_in_block() {
+        let block = weekly_block();
+        assert!(fits_in_block(&block, "2025-01-13T14:30:00Z", 60).unwrap());
+        assert!(!fits_in_block(&block, "2025-01-13T15:30:00Z", 60).unwrap());
+        assert!(!fits_in_block(&block, "2025-01-14T14:30:00Z", 30).unwrap());
+    }
+
+    #[test]
+    fn test_ranges_overlap() {
+        assert!(ranges_overlap("2025-01-13T14:00:00Z", 30, "2025-01-13T14:15:00Z", 30).unwrap());
+        assert!(!ranges_overlap("2025-01-13T14:00:00Z", 30, "2025-01-13T14:30:00Z", 30).unwrap());
+    }
+}
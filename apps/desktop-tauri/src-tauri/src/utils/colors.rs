@@ -0,0 +1,97 @@
+//! Meeting colors
+//!
+//! Meetings and tags carry a `#rrggbb` color. Google Calendar only knows
+//! its own eleven event colors, so a meeting synced there gets the one
+//! closest to its color, and an event's color comes back as that color.
+
+use crate::{Error, Result};
+
+/// Google Calendar event colors, by `colorId`
+const GOOGLE_EVENT_COLORS: [(&str, &str); 11] = [
+    ("1", "#7986cb"),  // Lavender
+    ("2", "#33b679"),  // Sage
+    ("3", "#8e24aa"),  // Grape
+    ("4", "#e67c73"),  // Flamingo
+    ("5", "#f6bf26"),  // Banana
+    ("6", "#f4511e"),  // Tangerine
+    ("7", "#039be5"),  // Peacock
+    ("8", "#616161"),  // Graphite
+    ("9", "#3f51b5"),  // Blueberry
+    ("10", "#0b8043"), // Basil
+    ("11", "#d50000"), // Tomato
+];
+
+/// A `#rrggbb` color, lowercased
+pub fn normalize_hex_color(color: &str) -> Result<String> {
+    let color = color.trim();
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(Error::Parse(format!("Invalid color: {}", color)));
+    }
+    Ok(color.to_ascii_lowercase())
+}
+
+/// Google event `colorId` closest to a color
+pub fn google_color_id(color: &str) -> Option<&'static str> {
+    let rgb = parse_rgb(color)?;
+    GOOGLE_EVENT_COLORS
+        .iter()
+        .filter_map(|(id, hex)| parse_rgb(hex).map(|google| (*id, distance(rgb, google))))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(id, _)| id)
+}
+
+/// Color of a Google event `colorId`
+pub fn google_color(color_id: &str) -> Option<&'static str> {
+    GOOGLE_EVENT_COLORS
+        .iter()
+        .find(|(id, _)| *id == color_id)
+        .map(|(_, hex)| *hex)
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn parse_rgb(color: &str) -> Option<(i32, i32, i32)> {
+    let hex = normalize_hex_color(color).ok()?;
+    let channel = |i: usize| i32::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(1)?, channel(3)?, channel(5)?))
+}
+
+fn distance(a: (i32, i32, i32), b: (i32, i32, i32)) -> i32 {
+    (a.0 - b.0).pow(2) + (a.1 - b.1).pow(2) + (a.2 - b.2).pow(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_hex_color() {
+        assert_eq!(normalize_hex_color(" #33B679 ").unwrap(), "#33b679");
+        assert!(normalize_hex_color("33b679").is_err());
+        assert!(normalize_hex_color("#fff").is_err());
+        assert!(normalize_hex_color("#gggggg").is_err());
+    }
+
+    #[test]
+    fn test_google_color_id_nearest() {
+        assert_eq!(google_color_id("#d50000"), Some("11"));
+        assert_eq!(google_color_id("#ff0000"), Some("11"));
+        assert_eq!(google_color_id("#1a73e8"), Some("7"));
+        assert_eq!(google_color_id("#0b8043"), Some("10"));
+        assert_eq!(google_color_id("red"), None);
+    }
+
+    #[test]
+    fn test_google_color_round_trip() {
+        for (id, hex) in GOOGLE_EVENT_COLORS {
+            assert_eq!(google_color(id), Some(hex));
+            assert_eq!(google_color_id(hex), Some(id));
+        }
+        assert_eq!(google_color("12"), None);
+    }
+}
@@ -4,6 +4,7 @@
 //! that can be called from the frontend via Tauri commands.
 
 use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
 use crate::{Error, Result};
@@ -30,6 +31,21 @@ pub enum DateTimeFormat {
     Iso8601,
 }
 
+/// A moment as seen in a time zone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZonedDateTime {
+    /// IANA name: "America/Sao_Paulo"
+    pub time_zone: String,
+    /// Local date and time with offset: "2026-02-07T11:30:00-03:00"
+    pub local: String,
+    /// UTC offset: "-03:00"
+    pub utc_offset: String,
+    /// Zone abbreviation: "-03", "CET", "EDT"
+    pub abbreviation: String,
+    /// Date and time: "07/02/2026 11:30"
+    pub formatted: String,
+}
+
 /// Formatted date/time result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FormattedDateTime {
@@ -100,6 +116,70 @@ pub fn format_meeting_time(start_str: &str, duration_minutes: i32) -> Result<Str
     ))
 }
 
+/// Look up an IANA time zone by name
+pub fn parse_time_zone(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse()
+        .map_err(|_| Error::Parse(format!("Unknown time zone: {}", name)))
+}
+
+/// Show a datetime as it is in a time zone
+pub fn to_time_zone(datetime_str: &str, time_zone: &str) -> Result<ZonedDateTime> {
+    let tz = parse_time_zone(time_zone)?;
+    let zoned = parse_datetime(datetime_str)?.with_timezone(&tz);
+
+    Ok(ZonedDateTime {
+        time_zone: tz.name().to_string(),
+        local: zoned.format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+        utc_offset: zoned.format("%:z").to_string(),
+        abbreviation: zoned.format("%Z").to_string(),
+        formatted: zoned.format("%d/%m/%Y %H:%M").to_string(),
+    })
+}
+
+/// Convert a wall-clock time in a time zone ("2026-03-09T09:00:00") to UTC
+///
+/// Times skipped by a DST change don't exist and are rejected; times
+/// repeated by one resolve to the first.
+pub fn zoned_to_utc(local_str: &str, time_zone: &str) -> Result<String> {
+    let tz = parse_time_zone(time_zone)?;
+    let naive = chrono::NaiveDateTime::parse_from_str(local_str, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(local_str, "%Y-%m-%dT%H:%M"))
+        .map_err(|_| Error::Parse(format!("Unable to parse local time: {}", local_str)))?;
+
+    let zoned = tz.from_local_datetime(&naive).earliest().ok_or_else(|| {
+        Error::Parse(format!(
+            "{} does not exist in {} (daylight saving time change)",
+            local_str,
+            tz.name()
+        ))
+    })?;
+    Ok(zoned
+        .with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string())
+}
+
+/// Format a meeting time with duration in a time zone (e.g.,
+/// "11:30 - 12:30 -03 (60 min)")
+pub fn format_meeting_time_in_zone(
+    start_str: &str,
+    duration_minutes: i32,
+    time_zone: &str,
+) -> Result<String> {
+    let tz = parse_time_zone(time_zone)?;
+    let start = parse_datetime(start_str)?.with_timezone(&tz);
+    let end = start + Duration::minutes(duration_minutes as i64);
+
+    Ok(format!(
+        "{} - {} {} ({} min)",
+        start.format("%H:%M"),
+        end.format("%H:%M"),
+        end.format("%Z"),
+        duration_minutes
+    ))
+}
+
 /// Calculate end time from start and duration
 pub fn calculate_end_time(start_str: &str, duration_minutes: i32) -> Result<String> {
     let start = parse_datetime(start_str)?;
@@ -171,11 +251,48 @@ pub fn get_month_bounds(year: i32, month: u32) -> Result<(String, String)> {
     ))
 }
 
+/// Get the start and end of a UTC month, both inclusive
+pub fn get_utc_month_bounds(year: i32, month: u32) -> Result<(String, String)> {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| Error::Parse("Invalid year/month".to_string()))?;
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .ok_or_else(|| Error::Parse("Invalid date calculation".to_string()))?;
+    let last_day = next_month - Duration::days(1);
+
+    Ok((
+        format!("{}T00:00:00Z", first_day.format("%Y-%m-%d")),
+        format!("{}T23:59:59.999Z", last_day.format("%Y-%m-%d")),
+    ))
+}
+
+/// Get the UTC months a date range touches, in order
+pub fn get_months_in_range(start_str: &str, end_str: &str) -> Result<Vec<(i32, u32)>> {
+    let start = parse_datetime(start_str)?;
+    let end = parse_datetime(end_str)?;
+
+    let mut months = Vec::new();
+    let (mut year, mut month) = (start.year(), start.month());
+    while (year, month) <= (end.year(), end.month()) {
+        months.push((year, month));
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+    }
+    Ok(months)
+}
+
 // ==========================================
 // Helper Functions
 // ==========================================
 
-fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+pub(crate) fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
     // Try ISO 8601 with Z suffix
     if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
         return Ok(dt.with_timezone(&Utc));
@@ -290,10 +407,68 @@ mod tests {
         assert!(result.contains("60 min"));
     }
 
+    #[test]
+    fn test_to_time_zone() {
+        let zoned = to_time_zone("2026-02-07T14:30:00Z", "America/Sao_Paulo").unwrap();
+        assert_eq!(zoned.local, "2026-02-07T11:30:00-03:00");
+        assert_eq!(zoned.utc_offset, "-03:00");
+        assert_eq!(zoned.formatted, "07/02/2026 11:30");
+
+        let zoned = to_time_zone("2026-07-07T14:30:00Z", "Europe/Berlin").unwrap();
+        assert_eq!(zoned.local, "2026-07-07T16:30:00+02:00");
+        assert_eq!(zoned.abbreviation, "CEST");
+
+        assert!(to_time_zone("2026-02-07T14:30:00Z", "Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_zoned_to_utc_across_dst() {
+        assert_eq!(
+            zoned_to_utc("2026-03-02T09:00:00", "America/New_York").unwrap(),
+            "2026-03-02T14:00:00Z"
+        );
+        assert_eq!(
+            zoned_to_utc("2026-03-09T09:00", "America/New_York").unwrap(),
+            "2026-03-09T13:00:00Z"
+        );
+        // Skipped when clocks sprang forward
+        assert!(zoned_to_utc("2026-03-08T02:30:00", "America/New_York").is_err());
+        // Repeated when clocks fell back; the first, still EDT
+        assert_eq!(
+            zoned_to_utc("2026-11-01T01:30:00", "America/New_York").unwrap(),
+            "2026-11-01T05:30:00Z"
+        );
+    }
+
+    #[test]
+    fn test_meeting_time_in_zone() {
+        let result =
+            format_meeting_time_in_zone("2026-02-07T14:30:00Z", 60, "America/Sao_Paulo").unwrap();
+        assert_eq!(result, "11:30 - 12:30 -03 (60 min)");
+    }
+
     #[test]
     fn test_month_bounds() {
         let (start, end) = get_month_bounds(2026, 2).unwrap();
         assert!(start.contains("2026-02-01"));
         assert!(end.contains("2026-02-28"));
     }
+
+    #[test]
+    fn test_utc_month_bounds() {
+        let (start, end) = get_utc_month_bounds(2024, 12).unwrap();
+        assert_eq!(start, "2024-12-01T00:00:00Z");
+        assert_eq!(end, "2024-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn test_months_in_range() {
+        let months = get_months_in_range("2025-11-15T00:00:00Z", "2026-01-31T23:59:59Z").unwrap();
+        assert_eq!(months, vec![(2025, 11), (2025, 12), (2026, 1)]);
+        let months = get_months_in_range("2026-03-01", "2026-03-31").unwrap();
+        assert_eq!(months, vec![(2026, 3)]);
+        assert!(get_months_in_range("2026-04-01", "2026-03-01")
+            .unwrap()
+            .is_empty());
+    }
 }
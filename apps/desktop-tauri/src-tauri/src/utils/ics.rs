@@ -0,0 +1,619 @@
+//! iCalendar (RFC 5545) files
+//!
+//! Writes meetings as `VEVENT`s, with their recurrence rule, organizer and
+//! attendees, and reads the events of `.ics` files other calendar apps
+//! produce. Only what meetings can hold is kept: times are converted to UTC
+//! (`TZID` zones included), all-day events start at midnight UTC, and
+//! alarms, exceptions and other components are ignored.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::{Error, Result};
+
+const PRODID: &str = "-//SquadX Live//Desktop//EN";
+
+/// Domain of the UIDs of exported meetings
+pub const UID_DOMAIN: &str = "squadx.live";
+
+/// Lines are folded past this many octets
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Duration of events that give neither an end nor a duration
+const DEFAULT_DURATION_MINUTES: i32 = 30;
+
+/// Organizer or attendee of an event
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsPerson {
+    /// Calendar address, `mailto:` or `urn:uuid:` URI
+    pub address: String,
+    pub name: Option<String>,
+    /// Participation status (`PARTSTAT`), attendees only
+    pub status: Option<String>,
+}
+
+impl IcsPerson {
+    /// Email of a `mailto:` address
+    pub fn email(&self) -> Option<&str> {
+        strip_prefix_ignore_case(&self.address, "mailto:")
+    }
+
+    /// User ID of a `urn:uuid:` address, as exported by this app
+    pub fn user_id(&self) -> Option<&str> {
+        strip_prefix_ignore_case(&self.address, "urn:uuid:")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: DateTime<Utc>,
+    pub duration_minutes: i32,
+    /// Recurrence rule, without the `RRULE:` prefix
+    pub recurrence_rule: Option<String>,
+    pub cancelled: bool,
+    pub organizer: Option<IcsPerson>,
+    pub attendees: Vec<IcsPerson>,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl IcsEvent {
+    /// Meeting ID of an event exported by this app
+    pub fn meeting_id(&self) -> Option<&str> {
+        self.uid
+            .strip_suffix(UID_DOMAIN)
+            .and_then(|id| id.strip_suffix('@'))
+    }
+}
+
+/// UID of an exported meeting
+pub fn meeting_uid(meeting_id: &str) -> String {
+    format!("{}@{}", meeting_id, UID_DOMAIN)
+}
+
+/// `PARTSTAT` of an attendee response status
+pub fn partstat(response_status: &str) -> &'static str {
+    match response_status {
+        "accepted" => "ACCEPTED",
+        "declined" => "DECLINED",
+        "tentative" => "TENTATIVE",
+        _ => "NEEDS-ACTION",
+    }
+}
+
+/// Attendee response status of a `PARTSTAT`
+pub fn response_status(partstat: &str) -> &'static str {
+    match partstat.to_ascii_uppercase().as_str() {
+        "ACCEPTED" => "accepted",
+        "DECLINED" => "declined",
+        "TENTATIVE" => "tentative",
+        _ => "invited",
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    value
+        .get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &value[prefix.len()..])
+}
+
+// ==========================================
+// Writing
+// ==========================================
+
+/// TEXT value escaping
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Parameter value, quoted when it holds separators
+fn param_value(value: &str) -> String {
+    let value = value.replace('"', "'");
+    if value.contains([':', ';', ',']) {
+        format!("\"{}\"", value)
+    } else {
+        value
+    }
+}
+
+/// Fold a content line at 75 octets, never inside a character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+fn format_datetime(datetime: &DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn person_line(property: &str, person: &IcsPerson) -> String {
+    let mut line = property.to_string();
+    if let Some(ref name) = person.name {
+        line.push_str(&format!(";CN={}", param_value(name)));
+    }
+    if let Some(ref status) = person.status {
+        line.push_str(&format!(";PARTSTAT={}", status));
+    }
+    format!("{}:{}", line, person.address)
+}
+
+fn event_lines(event: &IcsEvent, stamp: &DateTime<Utc>) -> Vec<String> {
+    let end = event.start + Duration::minutes(event.duration_minutes.into());
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", format_datetime(stamp)),
+        format!("DTSTART:{}", format_datetime(&event.start)),
+        format!("DTEND:{}", format_datetime(&end)),
+        format!("SUMMARY:{}", escape_text(&event.summary)),
+        format!(
+            "STATUS:{}",
+            if event.cancelled {
+                "CANCELLED"
+            } else {
+                "CONFIRMED"
+            }
+        ),
+    ];
+    if let Some(ref description) = event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    if let Some(ref rule) = event.recurrence_rule {
+        lines.push(format!("RRULE:{}", rule));
+    }
+    if let Some(ref modified) = event.last_modified {
+        lines.push(format!("LAST-MODIFIED:{}", format_datetime(modified)));
+    }
+    if let Some(ref organizer) = event.organizer {
+        lines.push(person_line("ORGANIZER", organizer));
+    }
+    for attendee in &event.attendees {
+        lines.push(person_line("ATTENDEE", attendee));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// Calendar file holding the events, stamped at `now`
+pub fn write_calendar(events: &[IcsEvent], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+    for event in events {
+        lines.extend(event_lines(event, &now));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut contents = lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    contents.push_str("\r\n");
+    contents
+}
+
+// ==========================================
+// Reading
+// ==========================================
+
+/// A content line: name, parameters and value
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Undo line folding
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let (Some(rest), Some(last)) = (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            last.push_str(rest);
+        } else if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+/// Split a content line, honouring quoted parameter values
+fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut value_start = None;
+    let mut parts = Vec::new();
+    let mut part_start = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                parts.push(&line[part_start..i]);
+                part_start = i + 1;
+            }
+            ':' if !in_quotes => {
+                parts.push(&line[part_start..i]);
+                value_start = Some(i + 1);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value = line[value_start?..].to_string();
+
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+/// A DATE or DATE-TIME value in UTC, and whether it was a date
+fn parse_datetime(property: &Property) -> Result<(DateTime<Utc>, bool)> {
+    let value = property.value.trim();
+    let invalid = || Error::Parse(format!("Invalid {} value: {}", property.name, value));
+
+    if property
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+        || value.len() == 8
+    {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        let midnight = date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?;
+        return Ok((Utc.from_utc_datetime(&midnight), true));
+    }
+
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok((Utc.from_utc_datetime(&naive), false));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    // Floating times and unknown zones are taken as UTC
+    let zone = property
+        .param("TZID")
+        .and_then(|tzid| chrono_tz::Tz::from_str(tzid.trim_start_matches('/')).ok());
+    let datetime = match zone {
+        Some(zone) => zone
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(invalid)?
+            .with_timezone(&Utc),
+        None => Utc.from_utc_datetime(&naive),
+    };
+    Ok((datetime, false))
+}
+
+/// Minutes of a DURATION value such as `PT1H30M` or `P1D`
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix(['P', 'p'])?;
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c.to_ascii_uppercase() {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += n * match unit {
+                    'W' => 7 * 24 * 3600,
+                    'D' => 24 * 3600,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    let minutes = seconds / 60;
+    Some(if negative { -minutes } else { minutes })
+}
+
+fn parse_person(property: &Property) -> IcsPerson {
+    IcsPerson {
+        address: property.value.trim().to_string(),
+        name: property.param("CN").map(str::to_string),
+        status: property.param("PARTSTAT").map(str::to_ascii_uppercase),
+    }
+}
+
+/// Event from the properties of a `VEVENT`
+fn parse_event(properties: &[Property]) -> Result<IcsEvent> {
+    let get = |name: &str| properties.iter().find(|p| p.name == name);
+
+    let start_property =
+        get("DTSTART").ok_or_else(|| Error::Parse("Event has no DTSTART".to_string()))?;
+    let (start, all_day) = parse_datetime(start_property)?;
+
+    let end_minutes = match (get("DTEND"), get("DURATION")) {
+        (Some(end), _) => Some((parse_datetime(end)?.0 - start).num_minutes()),
+        (None, Some(duration)) => parse_duration(&duration.value),
+        (None, None) if all_day => Some(24 * 60),
+        (None, None) => None,
+    };
+    let duration_minutes = end_minutes
+        .filter(|minutes| *minutes > 0)
+        .and_then(|minutes| i32::try_from(minutes).ok())
+        .unwrap_or(DEFAULT_DURATION_MINUTES);
+
+    let text = |name: &str| {
+        get(name)
+            .map(|p| unescape_text(&p.value).trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    Ok(IcsEvent {
+        uid: get("UID")
+            .map(|p| p.value.trim().to_string())
+            .unwrap_or_default(),
+        summary: text("SUMMARY").unwrap_or_default(),
+        description: text("DESCRIPTION"),
+        start,
+        duration_minutes,
+        recurrence_rule: get("RRULE")
+            .map(|p| p.value.trim().to_string())
+            .filter(|rule| !rule.is_empty()),
+        cancelled: get("STATUS").is_some_and(|p| p.value.trim().eq_ignore_ascii_case("CANCELLED")),
+        organizer: get("ORGANIZER").map(parse_person),
+        attendees: properties
+            .iter()
+            .filter(|p| p.name == "ATTENDEE")
+            .map(parse_person)
+            .collect(),
+        last_modified: get("LAST-MODIFIED")
+            .and_then(|p| parse_datetime(p).ok())
+            .map(|(datetime, _)| datetime),
+    })
+}
+
+/// Events of a calendar file, with how many were unreadable
+///
+/// Only top-level `VEVENT`s are read; alarms nested in them are skipped.
+pub fn parse_calendar(contents: &str) -> Result<(Vec<IcsEvent>, usize)> {
+    let lines = unfold(contents);
+    if !lines
+        .first()
+        .is_some_and(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err(Error::Parse("Not an iCalendar file".to_string()));
+    }
+
+    let mut events = Vec::new();
+    let mut unreadable = 0;
+    // Components entered, innermost last
+    let mut stack: Vec<String> = Vec::new();
+    let mut properties = Vec::new();
+    for line in &lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" => stack.push(property.value.trim().to_ascii_uppercase()),
+            "END" => {
+                let ended = stack.pop();
+                if ended.as_deref() == Some("VEVENT") && stack.len() == 1 {
+                    match parse_event(&properties) {
+                        Ok(event) => events.push(event),
+                        Err(e) => {
+                            tracing::debug!("Skipping unreadable event: {}", e);
+                            unreadable += 1;
+                        }
+                    }
+                    properties.clear();
+                }
+            }
+            _ if stack.len() == 2 && stack[1] == "VEVENT" => properties.push(property),
+            _ => {}
+        }
+    }
+    Ok((events, unreadable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn sample_event() -> IcsEvent {
+        IcsEvent {
+            uid: meeting_uid("m1"),
+            summary: "Sprint review; demo, Q&A".to_string(),
+            description: Some("Agenda:\n- demo\n- C:\\notes".to_string()),
+            start: utc("2026-03-02T14:00:00Z"),
+            duration_minutes: 45,
+            recurrence_rule: Some("FREQ=WEEKLY;BYDAY=MO".to_string()),
+            cancelled: false,
+            organizer: Some(IcsPerson {
+                address: "mailto:ana@example.com".to_string(),
+                name: Some("Ana Silva".to_string()),
+                status: None,
+            }),
+            attendees: vec![IcsPerson {
+                address: "urn:uuid:u2".to_string(),
+                name: Some("Silva, Bruno".to_string()),
+                status: Some("ACCEPTED".to_string()),
+            }],
+            last_modified: Some(utc("2026-03-01T09:30:00Z")),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let event = sample_event();
+        let contents = write_calendar(&[event.clone()], utc("2026-03-01T10:00:00Z"));
+        assert!(contents.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(contents.contains("SUMMARY:Sprint review\\; demo\\, Q&A\r\n"));
+        assert!(contents.contains("ATTENDEE;CN=\"Silva, Bruno\";PARTSTAT=ACCEPTED:urn:uuid:u2"));
+        assert!(contents.lines().all(|line| line.len() <= MAX_LINE_OCTETS));
+
+        let (events, unreadable) = parse_calendar(&contents).unwrap();
+        assert_eq!(unreadable, 0);
+        assert_eq!(events, vec![event]);
+        assert_eq!(events[0].meeting_id(), Some("m1"));
+        assert_eq!(events[0].attendees[0].user_id(), Some("u2"));
+        assert_eq!(
+            events[0].organizer.as_ref().unwrap().email(),
+            Some("ana@example.com")
+        );
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(60));
+        let folded = fold_line(&line);
+        assert!(folded
+            .split("\r\n")
+            .all(|part| part.len() <= MAX_LINE_OCTETS));
+        assert_eq!(unfold(&folded), vec![line]);
+    }
+
+    #[test]
+    fn test_parse_external_invitation() {
+        let contents = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            PRODID:-//Example//EN\r\n\
+            BEGIN:VTIMEZONE\r\n\
+            TZID:America/Sao_Paulo\r\n\
+            END:VTIMEZONE\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:abc@example.com\r\n\
+            DTSTART;TZID=America/Sao_Paulo:20260302T090000\r\n\
+            DURATION:PT1H30M\r\n\
+            SUMMARY:Planning\r\n\
+            ORGANIZER;CN=\"Doe: John\":MAILTO:john@example.com\r\n\
+            ATTENDEE;PARTSTAT=tentative;CN=Ana:mailto:ana@example.com\r\n\
+            BEGIN:VALARM\r\n\
+            TRIGGER:-PT15M\r\n\
+            DESCRIPTION:Reminder\r\n\
+            END:VALARM\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:all-day@example.com\r\n\
+            DTSTART;VALUE=DATE:20260305\r\n\
+            SUMMARY:Offsite\r\n\
+            STATUS:CANCELLED\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:broken@example.com\r\n\
+            SUMMARY:No start\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let (events, unreadable) = parse_calendar(contents).unwrap();
+        assert_eq!(unreadable, 1);
+        assert_eq!(events.len(), 2);
+
+        let planning = &events[0];
+        assert_eq!(planning.start, utc("2026-03-02T12:00:00Z"));
+        assert_eq!(planning.duration_minutes, 90);
+        // The alarm's description isn't the event's
+        assert_eq!(planning.description, None);
+        assert_eq!(planning.meeting_id(), None);
+        let organizer = planning.organizer.as_ref().unwrap();
+        assert_eq!(organizer.name.as_deref(), Some("Doe: John"));
+        assert_eq!(organizer.email(), Some("john@example.com"));
+        assert_eq!(planning.attendees[0].status.as_deref(), Some("TENTATIVE"));
+
+        let offsite = &events[1];
+        assert_eq!(offsite.start, utc("2026-03-05T00:00:00Z"));
+        assert_eq!(offsite.duration_minutes, 24 * 60);
+        assert!(offsite.cancelled);
+
+        assert!(parse_calendar("hello").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT45M"), Some(45));
+        assert_eq!(parse_duration("P1DT2H"), Some(26 * 60));
+        assert_eq!(parse_duration("P1W"), Some(7 * 24 * 60));
+        assert_eq!(parse_duration("-PT15M"), Some(-15));
+        assert_eq!(parse_duration("PT15"), None);
+        assert_eq!(parse_duration("15M"), None);
+    }
+
+    #[test]
+    fn test_partstat_mapping() {
+        for status in ["invited", "accepted", "declined", "tentative"] {
+            assert_eq!(response_status(partstat(status)), status);
+        }
+        assert_eq!(response_status("DELEGATED"), "invited");
+    }
+}
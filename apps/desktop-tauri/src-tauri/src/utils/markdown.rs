@@ -0,0 +1,152 @@
+//! Markdown in meeting descriptions
+//!
+//! Descriptions are CommonMark with GitHub-style task lists (`- [ ] item`),
+//! tables and strikethrough. The backend renders them to sanitized HTML so
+//! the frontend never injects raw user HTML, and lists their checkboxes by
+//! position so attendees can tick off prep items.
+
+use std::ops::Range;
+
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Checkbox of a task list, numbered in document order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub index: usize,
+    pub text: String,
+    pub checked: bool,
+}
+
+fn options() -> Options {
+    Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH
+}
+
+/// Render Markdown to HTML that is safe to insert into the page
+///
+/// Task list checkboxes come out as disabled `<input type="checkbox">` in
+/// document order, matching `ChecklistItem::index`.
+pub fn render(markdown: &str) -> String {
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options()));
+
+    ammonia::Builder::default()
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["checked", "disabled"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Every checkbox in the document, in order
+pub fn checklist(markdown: &str) -> Vec<ChecklistItem> {
+    let mut items: Vec<ChecklistItem> = Vec::new();
+    // Collecting the text of the last item until its nested list or end
+    let mut collecting = false;
+
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::TaskListMarker(checked) => {
+                items.push(ChecklistItem {
+                    index: items.len(),
+                    text: String::new(),
+                    checked,
+                });
+                collecting = true;
+            }
+            Event::Start(Tag::List(_)) | Event::End(TagEnd::Item) => collecting = false,
+            Event::Text(text) | Event::Code(text) if collecting => {
+                if let Some(item) = items.last_mut() {
+                    item.text.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak if collecting => {
+                if let Some(item) = items.last_mut() {
+                    item.text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for item in &mut items {
+        item.text = item.text.trim().to_string();
+    }
+    items
+}
+
+/// Source ranges of the checkbox markers, in order
+fn marker_ranges(markdown: &str) -> Vec<Range<usize>> {
+    Parser::new_ext(markdown, options())
+        .into_offset_iter()
+        .filter(|(event, _)| matches!(event, Event::TaskListMarker(_)))
+        .map(|(_, range)| range)
+        .collect()
+}
+
+/// Tick or untick the checkbox at `index`, leaving the rest of the source
+/// untouched
+pub fn set_checkbox(markdown: &str, index: usize, checked: bool) -> Result<String> {
+    let range = marker_ranges(markdown)
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| Error::NotFound(format!("Checklist item {}", index)))?;
+
+    // The box state is the character after the marker's `[`
+    let state = markdown[range.clone()]
+        .find('[')
+        .map(|i| range.start + i + 1)
+        .filter(|&i| matches!(markdown.as_bytes().get(i), Some(b' ' | b'x' | b'X')))
+        .ok_or_else(|| Error::Parse(format!("Malformed checklist item {}", index)))?;
+
+    let mut updated = String::with_capacity(markdown.len());
+    updated.push_str(&markdown[..state]);
+    updated.push(if checked { 'x' } else { ' ' });
+    updated.push_str(&markdown[state + 1..]);
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGENDA: &str = "## Prep\n\n- [ ] Read the `RFC`\n- [x] Book room\n  - [ ] Check projector\n- plain item\n\n1. [X] Send invite\n";
+
+    #[test]
+    fn test_checklist_in_order() {
+        let items = checklist(AGENDA);
+        let summary: Vec<(&str, bool)> =
+            items.iter().map(|i| (i.text.as_str(), i.checked)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Read the RFC", false),
+                ("Book room", true),
+                ("Check projector", false),
+                ("Send invite", true),
+            ]
+        );
+        assert_eq!(items[3].index, 3);
+    }
+
+    #[test]
+    fn test_set_checkbox_changes_only_that_box() {
+        let updated = set_checkbox(AGENDA, 2, true).unwrap();
+        assert_eq!(updated, AGENDA.replace("  - [ ] Check", "  - [x] Check"));
+
+        let updated = set_checkbox(AGENDA, 3, false).unwrap();
+        assert!(updated.contains("1. [ ] Send invite"));
+        assert!(set_checkbox(AGENDA, 4, true).is_err());
+    }
+
+    #[test]
+    fn test_render_sanitizes() {
+        let html = render("- [x] Done <script>alert(1)</script>\n\n<img src=x onerror=alert(1)>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(html.contains("type=\"checkbox\""));
+        assert!(html.contains("checked"));
+    }
+}
@@ -0,0 +1,319 @@
+//! Calendar health statistics
+//!
+//! Summarizes a user's meetings over a range: time spent in meetings, who
+//! organizes them, how invitations get answered and which days are the
+//! busiest. Cancelled meetings and meetings the user declined don't count
+//! as time in meetings.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use super::datetime::parse_datetime;
+use crate::Result;
+
+/// Number of busiest days reported
+pub const MAX_BUSIEST_DAYS: usize = 5;
+
+/// A meeting occurrence as seen by the user the stats are for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsMeeting {
+    pub organizer_id: String,
+    pub organizer_name: String,
+    pub scheduled_at: String,
+    pub duration_minutes: i32,
+    pub status: String,
+    /// The user's answer; `None` when they organize the meeting
+    pub response_status: Option<String>,
+    /// Answers of the other attendees
+    pub attendee_responses: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCounts {
+    pub accepted: usize,
+    pub tentative: usize,
+    pub declined: usize,
+    /// Not answered yet
+    pub pending: usize,
+}
+
+impl ResponseCounts {
+    fn add(&mut self, response_status: &str) {
+        match response_status {
+            "accepted" => self.accepted += 1,
+            "tentative" => self.tentative += 1,
+            "declined" => self.declined += 1,
+            _ => self.pending += 1,
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.accepted + self.tentative + self.declined + self.pending
+    }
+
+    fn acceptance_rate(&self) -> Option<f64> {
+        match self.total() {
+            0 => None,
+            total => Some(self.accepted as f64 / total as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizerCount {
+    pub organizer_id: String,
+    pub organizer_name: String,
+    pub meeting_count: usize,
+    pub hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayLoad {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub meeting_count: usize,
+    pub hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekdayLoad {
+    /// ISO weekday, Monday = 1 to Sunday = 7
+    pub weekday: u32,
+    pub meeting_count: usize,
+    pub hours: f64,
+}
+
+/// Calendar health of a user over a range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingStats {
+    pub meeting_count: usize,
+    pub hours_in_meetings: f64,
+    pub cancelled_count: usize,
+    /// Most meetings first
+    pub by_organizer: Vec<OrganizerCount>,
+    /// The user's answers to the meetings they were invited to
+    pub invitations: ResponseCounts,
+    /// Share of invitations the user accepted
+    pub acceptance_rate: Option<f64>,
+    /// Answers of the attendees of the meetings the user organizes
+    pub attendee_responses: ResponseCounts,
+    /// Share of those attendees who accepted
+    pub attendance_rate: Option<f64>,
+    /// Most hours first
+    pub busiest_days: Vec<DayLoad>,
+    /// Monday to Sunday
+    pub by_weekday: Vec<WeekdayLoad>,
+}
+
+/// Compute the stats of meetings, days taken in `time_zone` (the system's
+/// when unset)
+pub fn compute_stats(meetings: &[StatsMeeting], time_zone: Option<Tz>) -> Result<MeetingStats> {
+    let mut meeting_count = 0;
+    let mut total_minutes = 0i64;
+    let mut cancelled_count = 0;
+    let mut organizers: HashMap<&str, (&str, usize, i64)> = HashMap::new();
+    let mut invitations = ResponseCounts::default();
+    let mut attendee_responses = ResponseCounts::default();
+    let mut days: HashMap<NaiveDate, (usize, i64)> = HashMap::new();
+    let mut weekdays = [(0usize, 0i64); 7];
+
+    for meeting in meetings {
+        if meeting.status == "cancelled" {
+            cancelled_count += 1;
+            continue;
+        }
+        match meeting.response_status.as_deref() {
+            Some(response_status) => invitations.add(response_status),
+            None => meeting
+                .attendee_responses
+                .iter()
+                .for_each(|response_status| attendee_responses.add(response_status)),
+        }
+        if meeting.response_status.as_deref() == Some("declined") {
+            continue;
+        }
+
+        let start = parse_datetime(&meeting.scheduled_at)?;
+        let minutes = i64::from(meeting.duration_minutes.max(0));
+        meeting_count += 1;
+        total_minutes += minutes;
+
+        let organizer = organizers.entry(meeting.organizer_id.as_str()).or_insert((
+            meeting.organizer_name.as_str(),
+            0,
+            0,
+        ));
+        organizer.1 += 1;
+        organizer.2 += minutes;
+
+        let date = local_date(start, time_zone);
+        let day = days.entry(date).or_default();
+        day.0 += 1;
+        day.1 += minutes;
+
+        let weekday = &mut weekdays[date.weekday().num_days_from_monday() as usize];
+        weekday.0 += 1;
+        weekday.1 += minutes;
+    }
+
+    let mut by_organizer: Vec<OrganizerCount> = organizers
+        .into_iter()
+        .map(|(id, (name, count, minutes))| OrganizerCount {
+            organizer_id: id.to_string(),
+            organizer_name: name.to_string(),
+            meeting_count: count,
+            hours: hours(minutes),
+        })
+        .collect();
+    by_organizer.sort_by(|a, b| {
+        b.meeting_count
+            .cmp(&a.meeting_count)
+            .then_with(|| a.organizer_name.cmp(&b.organizer_name))
+    });
+
+    let mut busiest: Vec<(NaiveDate, (usize, i64))> = days.into_iter().collect();
+    busiest.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)));
+    let busiest_days = busiest
+        .into_iter()
+        .take(MAX_BUSIEST_DAYS)
+        .map(|(date, (count, minutes))| DayLoad {
+            date: date.format("%Y-%m-%d").to_string(),
+            meeting_count: count,
+            hours: hours(minutes),
+        })
+        .collect();
+
+    let by_weekday = weekdays
+        .iter()
+        .zip(1..)
+        .map(|(&(count, minutes), weekday)| WeekdayLoad {
+            weekday,
+            meeting_count: count,
+            hours: hours(minutes),
+        })
+        .collect();
+
+    Ok(MeetingStats {
+        meeting_count,
+        hours_in_meetings: hours(total_minutes),
+        cancelled_count,
+        by_organizer,
+        acceptance_rate: invitations.acceptance_rate(),
+        invitations,
+        attendance_rate: attendee_responses.acceptance_rate(),
+        attendee_responses,
+        busiest_days,
+        by_weekday,
+    })
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn local_date(dt: DateTime<Utc>, time_zone: Option<Tz>) -> NaiveDate {
+    match time_zone {
+        Some(tz) => dt.with_timezone(&tz).date_naive(),
+        None => dt.with_timezone(&Local).date_naive(),
+    }
+}
+
+/// Minutes as hours, to the hundredth
+fn hours(minutes: i64) -> f64 {
+    (minutes as f64 / 60.0 * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meeting(organizer: &str, at: &str, minutes: i32, response: Option<&str>) -> StatsMeeting {
+        StatsMeeting {
+            organizer_id: organizer.to_string(),
+            organizer_name: organizer.to_uppercase(),
+            scheduled_at: at.to_string(),
+            duration_minutes: minutes,
+            status: "scheduled".to_string(),
+            response_status: response.map(str::to_string),
+            attendee_responses: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_hours_exclude_cancelled_and_declined() {
+        let mut cancelled = meeting("a", "2024-03-04T10:00:00Z", 60, Some("accepted"));
+        cancelled.status = "cancelled".to_string();
+        let meetings = vec![
+            meeting("a", "2024-03-04T10:00:00Z", 90, Some("accepted")),
+            meeting("a", "2024-03-05T10:00:00Z", 30, Some("declined")),
+            cancelled,
+        ];
+        let stats = compute_stats(&meetings, Some(Tz::UTC)).unwrap();
+        assert_eq!(stats.meeting_count, 1);
+        assert_eq!(stats.hours_in_meetings, 1.5);
+        assert_eq!(stats.cancelled_count, 1);
+        assert_eq!(stats.invitations.accepted, 1);
+        assert_eq!(stats.invitations.declined, 1);
+        assert_eq!(stats.acceptance_rate, Some(0.5));
+    }
+
+    #[test]
+    fn test_by_organizer_sorted() {
+        let meetings = vec![
+            meeting("a", "2024-03-04T10:00:00Z", 30, Some("accepted")),
+            meeting("b", "2024-03-04T11:00:00Z", 30, None),
+            meeting("b", "2024-03-05T11:00:00Z", 30, None),
+        ];
+        let stats = compute_stats(&meetings, Some(Tz::UTC)).unwrap();
+        assert_eq!(stats.by_organizer[0].organizer_id, "b");
+        assert_eq!(stats.by_organizer[0].meeting_count, 2);
+        assert_eq!(stats.by_organizer[0].hours, 1.0);
+        assert_eq!(stats.by_organizer[1].organizer_id, "a");
+    }
+
+    #[test]
+    fn test_attendance_rate_of_own_meetings() {
+        let mut own = meeting("me", "2024-03-04T10:00:00Z", 30, None);
+        own.attendee_responses = vec![
+            "accepted".to_string(),
+            "accepted".to_string(),
+            "declined".to_string(),
+            "invited".to_string(),
+        ];
+        let stats = compute_stats(&[own], Some(Tz::UTC)).unwrap();
+        assert_eq!(stats.attendee_responses.pending, 1);
+        assert_eq!(stats.attendance_rate, Some(0.5));
+        assert_eq!(stats.acceptance_rate, None);
+    }
+
+    #[test]
+    fn test_busiest_days_in_time_zone() {
+        // 01:00 UTC on Tuesday is still Monday in Sao Paulo
+        let meetings = vec![
+            meeting("a", "2024-03-05T01:00:00Z", 60, Some("accepted")),
+            meeting("a", "2024-03-04T15:00:00Z", 60, Some("accepted")),
+            meeting("a", "2024-03-06T15:00:00Z", 30, Some("accepted")),
+        ];
+        let stats = compute_stats(&meetings, Some(Tz::America__Sao_Paulo)).unwrap();
+        assert_eq!(stats.busiest_days[0].date, "2024-03-04");
+        assert_eq!(stats.busiest_days[0].meeting_count, 2);
+        assert_eq!(stats.busiest_days[0].hours, 2.0);
+        assert_eq!(stats.busiest_days[1].date, "2024-03-06");
+        assert_eq!(stats.by_weekday.len(), 7);
+        assert_eq!(stats.by_weekday[0].weekday, 1);
+        assert_eq!(stats.by_weekday[0].meeting_count, 2);
+        assert_eq!(stats.by_weekday[2].hours, 0.5);
+    }
+
+    #[test]
+    fn test_empty() {
+        let stats = compute_stats(&[], None).unwrap();
+        assert_eq!(stats.meeting_count, 0);
+        assert_eq!(stats.hours_in_meetings, 0.0);
+        assert!(stats.busiest_days.is_empty());
+        assert_eq!(stats.attendance_rate, None);
+    }
+}
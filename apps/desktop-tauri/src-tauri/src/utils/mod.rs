@@ -1,3 +1,13 @@
+pub mod agenda;
+pub mod availability;
 pub mod calendar_grid;
+pub mod colors;
 pub mod datetime;
+pub mod ics;
+pub mod markdown;
+pub mod meeting_stats;
+pub mod poll;
+pub mod reminders;
 pub mod rrule;
+pub mod scheduling;
+pub mod text;
@@ -0,0 +1,109 @@
+//! Scheduling poll utilities
+//!
+//! Vote tallying and winner selection for Doodle-style scheduling polls.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Vote count of a candidate slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionTally {
+    pub option_id: String,
+    pub starts_at: String,
+    pub votes: usize,
+    pub voter_ids: Vec<String>,
+}
+
+/// Count votes per option, keeping the options' order
+pub fn tally_votes(
+    options: &[(String, String)],
+    votes: &[(String, String)],
+) -> Vec<OptionTally> {
+    let mut voters: HashMap<&str, Vec<String>> = HashMap::new();
+    for (option_id, user_id) in votes {
+        voters
+            .entry(option_id.as_str())
+            .or_default()
+            .push(user_id.clone());
+    }
+
+    options
+        .iter()
+        .map(|(option_id, starts_at)| {
+            let voter_ids = voters.remove(option_id.as_str()).unwrap_or_default();
+            OptionTally {
+                option_id: option_id.clone(),
+                starts_at: starts_at.clone(),
+                votes: voter_ids.len(),
+                voter_ids,
+            }
+        })
+        .collect()
+}
+
+/// Pick the winning option: most votes, ties broken by the earliest slot
+pub fn pick_winner(tallies: &[OptionTally]) -> Option<&OptionTally> {
+    tallies
+        .iter()
+        .filter(|t| t.votes > 0)
+        .max_by(|a, b| a.votes.cmp(&b.votes).then_with(|| b.starts_at.cmp(&a.starts_at)))
+}
+
+/// Whether every expected participant has cast at least one vote
+pub fn everyone_voted(participant_ids: &[String], tallies: &[OptionTally]) -> bool {
+    !participant_ids.is_empty()
+        && participant_ids
+            .iter()
+            .all(|p| tallies.iter().any(|t| t.voter_ids.contains(p)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> Vec<(String, String)> {
+        vec![
+            ("a".to_string(), "2025-01-13T14:00:00Z".to_string()),
+            ("b".to_string(), "2025-01-14T14:00:00Z".to_string()),
+            ("c".to_string(), "2025-01-15T14:00:00Z".to_string()),
+        ]
+    }
+
+    fn vote(option_id: &str, user_id: &str) -> (String, String) {
+        (option_id.to_string(), user_id.to_string())
+    }
+
+    #[test]
+    fn test_tally_votes() {
+        let votes = vec![vote("a", "u1"), vote("b", "u1"), vote("b", "u2")];
+        let tallies = tally_votes(&options(), &votes);
+
+        assert_eq!(tallies.len(), 3);
+        assert_eq!(tallies[0].votes, 1);
+        assert_eq!(tallies[1].votes, 2);
+        assert_eq!(tallies[2].votes, 0);
+    }
+
+    #[test]
+    fn test_pick_winner_breaks_ties_by_earliest_slot() {
+        let votes = vec![vote("c", "u1"), vote("b", "u2")];
+        let tallies = tally_votes(&options(), &votes);
+
+        assert_eq!(pick_winner(&tallies).unwrap().option_id, "b");
+    }
+
+    #[test]
+    fn test_pick_winner_without_votes() {
+        let tallies = tally_votes(&options(), &[]);
+        assert!(pick_winner(&tallies).is_none());
+    }
+
+    #[test]
+    fn test_everyone_voted() {
+        let votes = vec![vote("a", "u1"), vote("b", "u2")];
+        let tallies = tally_votes(&options(), &votes);
+
+        assert!(everyone_voted(&["u1".to_string(), "u2".to_string()], &tallies));
+        assert!(!everyone_voted(&["u1".to_string(), "u3".to_string()], &tallies));
+    }
+}
@@ -0,0 +1,176 @@
+//! Reminder effectiveness utilities
+//!
+//! Turns reminder telemetry (when a reminder fired and when the user
+//! actually joined) into statistics and a suggested reminder offset.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Minimum number of joined samples before suggesting a new offset
+pub const MIN_SAMPLES_FOR_SUGGESTION: usize = 5;
+
+/// Minutes users should ideally have before the meeting starts
+const JOIN_BUFFER_MINUTES: i64 = 2;
+
+const MIN_OFFSET_MINUTES: i32 = 5;
+const MAX_OFFSET_MINUTES: i32 = 60;
+
+/// A reminder that fired, and when (if ever) the user joined afterwards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderSample {
+    pub fired_at: String,
+    pub joined_at: Option<String>,
+}
+
+/// Aggregated reminder effectiveness for a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderEffectiveness {
+    pub sample_count: usize,
+    pub joined_count: usize,
+    pub join_rate: f64,
+    pub median_delta_seconds: Option<i64>,
+    pub average_delta_seconds: Option<i64>,
+    pub current_offset_minutes: i32,
+    pub suggested_offset_minutes: i32,
+    pub auto_adjust: bool,
+}
+
+/// Compute effectiveness statistics from telemetry samples
+pub fn compute_effectiveness(
+    samples: &[ReminderSample],
+    current_offset_minutes: i32,
+    auto_adjust: bool,
+) -> Result<ReminderEffectiveness> {
+    let mut deltas = Vec::new();
+    for sample in samples {
+        if let Some(ref joined_at) = sample.joined_at {
+            let fired = parse_datetime(&sample.fired_at)?;
+            let joined = parse_datetime(joined_at)?;
+            let delta = (joined - fired).num_seconds();
+            if delta >= 0 {
+                deltas.push(delta);
+            }
+        }
+    }
+    deltas.sort_unstable();
+
+    let median_delta_seconds = median(&deltas);
+    let average_delta_seconds = if deltas.is_empty() {
+        None
+    } else {
+        Some(deltas.iter().sum::<i64>() / deltas.len() as i64)
+    };
+
+    let suggested_offset_minutes = if deltas.len() >= MIN_SAMPLES_FOR_SUGGESTION {
+        suggest_offset(median_delta_seconds.unwrap_or(0))
+    } else {
+        current_offset_minutes
+    };
+
+    Ok(ReminderEffectiveness {
+        sample_count: samples.len(),
+        joined_count: deltas.len(),
+        join_rate: if samples.is_empty() {
+            0.0
+        } else {
+            deltas.len() as f64 / samples.len() as f64
+        },
+        median_delta_seconds,
+        average_delta_seconds,
+        current_offset_minutes,
+        suggested_offset_minutes,
+        auto_adjust,
+    })
+}
+
+/// Suggest a reminder offset so the typical user joins just before the start
+pub fn suggest_offset(median_delta_seconds: i64) -> i32 {
+    let minutes = (median_delta_seconds + 59) / 60 + JOIN_BUFFER_MINUTES;
+    // Round up to the next multiple of 5 minutes
+    let rounded = ((minutes + 4) / 5 * 5) as i32;
+    rounded.clamp(MIN_OFFSET_MINUTES, MAX_OFFSET_MINUTES)
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn median(sorted: &[i64]) -> Option<i64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / 2)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    // Try ISO 8601 with Z suffix
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    // Try without timezone (assume UTC)
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Ok(Utc.from_utc_datetime(&dt));
+    }
+
+    Err(Error::Parse(format!("Unable to parse datetime: {}", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(fired_at: &str, joined_at: Option<&str>) -> ReminderSample {
+        ReminderSample {
+            fired_at: fired_at.to_string(),
+            joined_at: joined_at.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_suggest_offset() {
+        assert_eq!(suggest_offset(0), 5);
+        assert_eq!(suggest_offset(10 * 60), 15);
+        assert_eq!(suggest_offset(22 * 60), 25);
+        assert_eq!(suggest_offset(3 * 3600), 60);
+    }
+
+    #[test]
+    fn test_compute_effectiveness() {
+        let samples = vec![
+            sample("2025-01-13T13:45:00Z", Some("2025-01-13T13:55:00Z")),
+            sample("2025-01-14T13:45:00Z", Some("2025-01-14T13:53:00Z")),
+            sample("2025-01-15T13:45:00Z", None),
+        ];
+        let stats = compute_effectiveness(&samples, 15, false).unwrap();
+
+        assert_eq!(stats.sample_count, 3);
+        assert_eq!(stats.joined_count, 2);
+        assert_eq!(stats.median_delta_seconds, Some(540));
+        // Not enough samples yet: keep the current offset
+        assert_eq!(stats.suggested_offset_minutes, 15);
+    }
+
+    #[test]
+    fn test_compute_effectiveness_suggests_after_enough_samples() {
+        let samples: Vec<ReminderSample> = (10..15)
+            .map(|day| {
+                sample(
+                    &format!("2025-01-{}T13:45:00Z", day),
+                    Some(&format!("2025-01-{}T13:47:00Z", day)),
+                )
+            })
+            .collect();
+        let stats = compute_effectiveness(&samples, 15, true).unwrap();
+
+        assert_eq!(stats.median_delta_seconds, Some(120));
+        assert_eq!(stats.suggested_offset_minutes, 5);
+    }
+}
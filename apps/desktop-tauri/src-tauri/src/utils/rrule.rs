@@ -1,14 +1,20 @@
 //! RRULE (RFC 5545) parsing and building utilities
 //!
 //! Provides functions to parse, build, and expand recurrence rules
-//! for calendar events.
+//! for calendar events, with EXDATE dates skipped and RDATE dates added.
+//! Rules expand in the meeting's time zone when it has one, so a 09:00
+//! meeting stays at 09:00 local across daylight saving changes.
 
 use chrono::{DateTime, TimeZone, Utc};
 use rrule::{RRuleSet, Tz};
 use serde::{Deserialize, Serialize};
 
+use super::datetime::parse_time_zone;
 use crate::{Error, Result};
 
+/// Upper bound of occurrences expanded per recurring meeting within a range
+pub const MAX_RANGE_OCCURRENCES: u16 = 2000;
+
 /// Recurrence frequency
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -50,6 +56,19 @@ pub struct RecurrenceRule {
     /// Days of week (0 = Sunday, 6 = Saturday)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub weekdays: Option<Vec<u32>>,
+    /// Days of week within the month or year, like the second Tuesday
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nth_weekdays: Option<Vec<NthWeekday>>,
+    /// Days of the month (1 to 31, or -1 for the last day)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub month_days: Option<Vec<i32>>,
+    /// Months of the year (1 = January)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub months: Option<Vec<u32>>,
+    /// Which of the days matched in each period to keep (1 for the first,
+    /// -1 for the last)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_positions: Option<Vec<i32>>,
     /// End date in ISO format
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_date: Option<String>,
@@ -58,6 +77,17 @@ pub struct RecurrenceRule {
     pub count: Option<u32>,
 }
 
+/// A day of the week counted within the month or year (BYDAY=2TU)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NthWeekday {
+    /// 1 for the first, 2 for the second, -1 for the last
+    pub ordinal: i32,
+    /// 0 = Sunday, 6 = Saturday
+    pub weekday: u32,
+}
+
+const DAY_CODES: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
 fn default_interval() -> u32 {
     1
 }
@@ -70,6 +100,40 @@ pub struct RecurrenceOccurrence {
     pub occurrence_index: u32,
 }
 
+/// Dates removed from (EXDATE) and added to (RDATE) a recurrence, in ISO
+/// format
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurrenceExceptions {
+    #[serde(default)]
+    pub exdates: Vec<String>,
+    #[serde(default)]
+    pub rdates: Vec<String>,
+}
+
+impl RecurrenceExceptions {
+    pub fn is_empty(&self) -> bool {
+        self.exdates.is_empty() && self.rdates.is_empty()
+    }
+
+    /// Dates in UTC, sorted and without duplicates
+    pub fn normalized(&self) -> Result<Self> {
+        Ok(RecurrenceExceptions {
+            exdates: normalize_dates(&self.exdates)?,
+            rdates: normalize_dates(&self.rdates)?,
+        })
+    }
+
+    /// Skip an occurrence, dropping it from the added dates if it was one
+    pub fn skip(&mut self, occurrence: &str) -> Result<()> {
+        let occurrence = format_utc(&parse_datetime(occurrence)?);
+        self.rdates = normalize_dates(&self.rdates)?;
+        self.rdates.retain(|date| *date != occurrence);
+        self.exdates.push(occurrence);
+        self.exdates = normalize_dates(&self.exdates)?;
+        Ok(())
+    }
+}
+
 /// Result of parsing an RRULE string
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedRRule {
@@ -96,17 +160,37 @@ pub fn build_rrule(rule: &RecurrenceRule) -> String {
         parts.push(format!("INTERVAL={}", rule.interval));
     }
 
-    // Weekdays (for weekly recurrence)
-    if let Some(ref weekdays) = rule.weekdays {
-        if !weekdays.is_empty() {
-            let day_names = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
-            let days: Vec<&str> = weekdays
-                .iter()
-                .filter_map(|&d| day_names.get(d as usize).copied())
-                .collect();
-            if !days.is_empty() {
-                parts.push(format!("BYDAY={}", days.join(",")));
-            }
+    if let Some(ref months) = rule.months {
+        if !months.is_empty() {
+            parts.push(format!("BYMONTH={}", join_numbers(months)));
+        }
+    }
+
+    if let Some(ref month_days) = rule.month_days {
+        if !month_days.is_empty() {
+            parts.push(format!("BYMONTHDAY={}", join_numbers(month_days)));
+        }
+    }
+
+    // Weekdays, plain and counted within the period
+    let mut days: Vec<String> = rule
+        .weekdays
+        .iter()
+        .flatten()
+        .filter_map(|&d| DAY_CODES.get(d as usize).map(|code| code.to_string()))
+        .collect();
+    days.extend(rule.nth_weekdays.iter().flatten().filter_map(|nth| {
+        DAY_CODES
+            .get(nth.weekday as usize)
+            .map(|code| format!("{}{}", nth.ordinal, code))
+    }));
+    if !days.is_empty() {
+        parts.push(format!("BYDAY={}", days.join(",")));
+    }
+
+    if let Some(ref set_positions) = rule.set_positions {
+        if !set_positions.is_empty() {
+            parts.push(format!("BYSETPOS={}", join_numbers(set_positions)));
         }
     }
 
@@ -133,6 +217,10 @@ pub fn parse_rrule(rrule_str: &str) -> Result<ParsedRRule> {
         frequency: RecurrenceFrequency::Daily,
         interval: 1,
         weekdays: None,
+        nth_weekdays: None,
+        month_days: None,
+        months: None,
+        set_positions: None,
         end_date: None,
         count: None,
     };
@@ -159,30 +247,60 @@ pub fn parse_rrule(rrule_str: &str) -> Result<ParsedRRule> {
                     .map_err(|_| Error::Parse(format!("Invalid interval: {}", value)))?;
             }
             "BYDAY" => {
-                let day_map = [
-                    ("SU", 0u32),
-                    ("MO", 1),
-                    ("TU", 2),
-                    ("WE", 3),
-                    ("TH", 4),
-                    ("FR", 5),
-                    ("SA", 6),
-                ];
-                let weekdays: Vec<u32> = value
-                    .split(',')
-                    .filter_map(|d| {
-                        let d = d.trim().to_uppercase();
-                        // Handle prefixed days like "1MO" or "-1FR"
-                        let day_code = d.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
-                        day_map
-                            .iter()
-                            .find(|(name, _)| *name == day_code)
-                            .map(|(_, num)| *num)
-                    })
-                    .collect();
+                let mut weekdays = Vec::new();
+                let mut nth_weekdays = Vec::new();
+                for d in value.split(',') {
+                    let d = d.trim().to_uppercase();
+                    // Prefixed days like "1MO" or "-1FR" are counted within the period
+                    let split = d.len().saturating_sub(2);
+                    let (Some(ordinal), Some(day_code)) = (d.get(..split), d.get(split..)) else {
+                        continue;
+                    };
+                    let Some(weekday) = DAY_CODES.iter().position(|code| *code == day_code) else {
+                        continue;
+                    };
+                    if ordinal.is_empty() {
+                        weekdays.push(weekday as u32);
+                    } else {
+                        nth_weekdays.push(NthWeekday {
+                            ordinal: parse_number(ordinal, "weekday position", 53)?,
+                            weekday: weekday as u32,
+                        });
+                    }
+                }
                 if !weekdays.is_empty() {
                     rule.weekdays = Some(weekdays);
                 }
+                if !nth_weekdays.is_empty() {
+                    rule.nth_weekdays = Some(nth_weekdays);
+                }
+            }
+            "BYMONTHDAY" => {
+                rule.month_days = Some(
+                    value
+                        .split(',')
+                        .map(|d| parse_number(d, "month day", 31))
+                        .collect::<Result<_>>()?,
+                );
+            }
+            "BYMONTH" => {
+                rule.months = Some(
+                    value
+                        .split(',')
+                        .map(|m| match m.trim().parse::<u32>() {
+                            Ok(month @ 1..=12) => Ok(month),
+                            _ => Err(Error::Parse(format!("Invalid month: {}", m))),
+                        })
+                        .collect::<Result<_>>()?,
+                );
+            }
+            "BYSETPOS" => {
+                rule.set_positions = Some(
+                    value
+                        .split(',')
+                        .map(|p| parse_number(p, "set position", 366))
+                        .collect::<Result<_>>()?,
+                );
             }
             "UNTIL" => {
                 // Parse UNTIL in format YYYYMMDDTHHMMSSZ
@@ -219,6 +337,66 @@ pub fn parse_rrule(rrule_str: &str) -> Result<ParsedRRule> {
     })
 }
 
+/// Build the EXDATE and RDATE lines of a recurrence, one line per kind
+pub fn build_exception_lines(exceptions: &RecurrenceExceptions) -> Result<String> {
+    let mut lines = Vec::new();
+    for (name, dates) in [
+        ("EXDATE", &exceptions.exdates),
+        ("RDATE", &exceptions.rdates),
+    ] {
+        if dates.is_empty() {
+            continue;
+        }
+        let values = normalize_dates(dates)?
+            .iter()
+            .map(|date| parse_datetime(date).map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        lines.push(format!("{}:{}", name, values.join(",")));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Parse the EXDATE and RDATE lines of a recurrence, ignoring other lines
+///
+/// Accepts UTC, floating and `TZID` date-times as well as `VALUE=DATE`
+/// dates, which are taken as midnight UTC.
+pub fn parse_exception_lines(text: &str) -> Result<RecurrenceExceptions> {
+    let mut exceptions = RecurrenceExceptions::default();
+
+    for line in text.lines().map(str::trim) {
+        let Some((head, values)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = head.split(';');
+        let name = params.next().unwrap_or_default().to_uppercase();
+        let target = match name.as_str() {
+            "EXDATE" => &mut exceptions.exdates,
+            "RDATE" => &mut exceptions.rdates,
+            _ => continue,
+        };
+
+        let mut tz: Option<chrono_tz::Tz> = None;
+        for param in params {
+            if let Some((key, value)) = param.split_once('=') {
+                if key.eq_ignore_ascii_case("TZID") {
+                    tz = Some(
+                        value
+                            .trim_matches('"')
+                            .parse()
+                            .map_err(|_| Error::Parse(format!("Unknown time zone: {}", value)))?,
+                    );
+                }
+            }
+        }
+
+        for value in values.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            target.push(format_utc(&parse_ical_datetime(value, tz)?));
+        }
+    }
+
+    exceptions.normalized()
+}
+
 /// Validate an RRULE string
 pub fn validate_rrule(rrule_str: &str) -> Result<bool> {
     parse_rrule(rrule_str)?;
@@ -232,21 +410,14 @@ pub fn expand_rrule(
     range_start: &str,
     range_end: &str,
     max_occurrences: Option<u32>,
+    exceptions: &RecurrenceExceptions,
+    time_zone: Option<&str>,
 ) -> Result<Vec<RecurrenceOccurrence>> {
     let start_dt = parse_datetime(start_date)?;
     let range_start_dt = parse_datetime(range_start)?;
     let range_end_dt = parse_datetime(range_end)?;
 
-    // Build RRULE with DTSTART
-    let rrule_full = format!(
-        "DTSTART:{}\nRRULE:{}",
-        start_dt.format("%Y%m%dT%H%M%SZ"),
-        rrule_str
-    );
-
-    let rrule_set: RRuleSet = rrule_full
-        .parse()
-        .map_err(|e| Error::Parse(format!("Invalid RRULE: {:?}", e)))?;
+    let rrule_set = build_rrule_set(rrule_str, &start_dt, exceptions, time_zone)?;
 
     let max = max_occurrences.unwrap_or(100) as usize;
 
@@ -280,9 +451,42 @@ pub fn expand_rrule(
     Ok(results)
 }
 
+/// Starts of a recurrence's occurrences within `[range_start, range_end]`,
+/// at most `limit` of them
+///
+/// Unlike `expand_rrule`, the limit counts from the start of the range
+/// rather than from the first occurrence, so a recurrence that began long
+/// ago still expands.
+pub fn occurrences_between(
+    rrule_str: &str,
+    start_date: &str,
+    range: (DateTime<Utc>, DateTime<Utc>),
+    limit: u16,
+    exceptions: &RecurrenceExceptions,
+    time_zone: Option<&str>,
+) -> Result<Vec<DateTime<Utc>>> {
+    let (range_start, range_end) = range;
+    if range_end < range_start {
+        return Ok(Vec::new());
+    }
+    let start_dt = parse_datetime(start_date)?;
+
+    let rrule_set = build_rrule_set(rrule_str, &start_dt, exceptions, time_zone)?;
+
+    Ok(rrule_set
+        .after(range_start.with_timezone(&Tz::UTC))
+        .before(range_end.with_timezone(&Tz::UTC))
+        .all(limit)
+        .dates
+        .into_iter()
+        .map(|dt| dt.with_timezone(&Utc))
+        .filter(|dt| *dt >= range_start && *dt <= range_end)
+        .collect())
+}
+
 /// Get a human-readable description of a recurrence rule
 pub fn describe_rrule(rule: &RecurrenceRule) -> String {
-    let freq_text = match rule.frequency {
+    let base = match rule.frequency {
         RecurrenceFrequency::Daily => {
             if rule.interval == 1 {
                 "Repete diariamente".to_string()
@@ -291,25 +495,10 @@ pub fn describe_rrule(rule: &RecurrenceRule) -> String {
             }
         }
         RecurrenceFrequency::Weekly => {
-            let base = if rule.interval == 1 {
+            if rule.interval == 1 {
                 "Repete semanalmente".to_string()
             } else {
                 format!("Repete a cada {} semanas", rule.interval)
-            };
-
-            if let Some(ref weekdays) = rule.weekdays {
-                if !weekdays.is_empty() {
-                    let day_names = ["dom", "seg", "ter", "qua", "qui", "sex", "sáb"];
-                    let days: Vec<&str> = weekdays
-                        .iter()
-                        .filter_map(|&d| day_names.get(d as usize).copied())
-                        .collect();
-                    format!("{} ({})", base, days.join(", "))
-                } else {
-                    base
-                }
-            } else {
-                base
             }
         }
         RecurrenceFrequency::Monthly => {
@@ -328,6 +517,68 @@ pub fn describe_rrule(rule: &RecurrenceRule) -> String {
         }
     };
 
+    let day_names = ["dom", "seg", "ter", "qua", "qui", "sex", "sáb"];
+    let mut details = Vec::new();
+
+    if let Some(ref month_days) = rule.month_days {
+        if !month_days.is_empty() {
+            let days: Vec<String> = month_days.iter().map(|&d| month_day_label(d)).collect();
+            details.push(days.join(", "));
+        }
+    }
+
+    if let Some(ref weekdays) = rule.weekdays {
+        let days: Vec<&str> = weekdays
+            .iter()
+            .filter_map(|&d| day_names.get(d as usize).copied())
+            .collect();
+        if !days.is_empty() {
+            match rule.set_positions.as_deref() {
+                Some(positions) if !positions.is_empty() => {
+                    let positions: Vec<String> =
+                        positions.iter().map(|&p| position_label(p)).collect();
+                    details.push(format!("{} {}", positions.join(" e "), days.join(", ")));
+                }
+                _ => details.push(days.join(", ")),
+            }
+        }
+    }
+
+    if let Some(ref nth_weekdays) = rule.nth_weekdays {
+        let days: Vec<String> = nth_weekdays
+            .iter()
+            .filter_map(|nth| {
+                day_names
+                    .get(nth.weekday as usize)
+                    .map(|day| format!("{} {}", position_label(nth.ordinal), day))
+            })
+            .collect();
+        if !days.is_empty() {
+            details.push(days.join(", "));
+        }
+    }
+
+    let mut detail_text = details.join(" e ");
+    if let Some(ref months) = rule.months {
+        let month_names = [
+            "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+        ];
+        let names: Vec<&str> = months
+            .iter()
+            .filter_map(|&m| month_names.get((m as usize).wrapping_sub(1)).copied())
+            .collect();
+        if !names.is_empty() {
+            let preposition = if detail_text.is_empty() { "em" } else { " de" };
+            detail_text = format!("{}{} {}", detail_text, preposition, names.join(", "));
+        }
+    }
+
+    let freq_text = if detail_text.is_empty() {
+        base
+    } else {
+        format!("{} ({})", base, detail_text)
+    };
+
     let end_text = if let Some(ref end_date) = rule.end_date {
         if let Ok(dt) = parse_datetime(end_date) {
             format!(", até {}", dt.format("%d/%m/%Y"))
@@ -344,19 +595,17 @@ pub fn describe_rrule(rule: &RecurrenceRule) -> String {
 }
 
 /// Get the next occurrence of a recurring event after a given date
-pub fn get_next_occurrence(rrule_str: &str, start_date: &str, after: &str) -> Result<Option<String>> {
+pub fn get_next_occurrence(
+    rrule_str: &str,
+    start_date: &str,
+    after: &str,
+    exceptions: &RecurrenceExceptions,
+    time_zone: Option<&str>,
+) -> Result<Option<String>> {
     let start_dt = parse_datetime(start_date)?;
     let after_dt = parse_datetime(after)?;
 
-    let rrule_full = format!(
-        "DTSTART:{}\nRRULE:{}",
-        start_dt.format("%Y%m%dT%H%M%SZ"),
-        rrule_str
-    );
-
-    let rrule_set: RRuleSet = rrule_full
-        .parse()
-        .map_err(|e| Error::Parse(format!("Invalid RRULE: {:?}", e)))?;
+    let rrule_set = build_rrule_set(rrule_str, &start_dt, exceptions, time_zone)?;
 
     for dt in rrule_set.into_iter().take(1000) {
         let dt_utc = dt.with_timezone(&Utc);
@@ -368,10 +617,147 @@ pub fn get_next_occurrence(rrule_str: &str, start_date: &str, after: &str) -> Re
     Ok(None)
 }
 
+/// Id of one occurrence of a recurring meeting: the meeting id and the
+/// occurrence start in UTC, e.g. `<meeting id>_20260309T120000Z`
+pub fn occurrence_id(meeting_id: &str, occurrence_date: &str) -> Result<String> {
+    let occurrence = parse_datetime(occurrence_date)?;
+    Ok(format!(
+        "{}_{}",
+        meeting_id,
+        occurrence.format("%Y%m%dT%H%M%SZ")
+    ))
+}
+
+/// Split an occurrence id into the meeting id and the occurrence start;
+/// `None` for plain meeting ids
+pub fn parse_occurrence_id(id: &str) -> Option<(String, String)> {
+    let (meeting_id, stamp) = id.rsplit_once('_')?;
+    let utc = stamp.strip_suffix('Z')?;
+    let occurrence = chrono::NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+    Some((
+        meeting_id.to_string(),
+        format_utc(&Utc.from_utc_datetime(&occurrence)),
+    ))
+}
+
 // ==========================================
 // Helper Functions
 // ==========================================
 
+fn join_numbers<T: ToString>(numbers: &[T]) -> String {
+    numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parse a non-zero number between -max and max
+fn parse_number(value: &str, what: &str, max: i32) -> Result<i32> {
+    match value.trim().parse::<i32>() {
+        Ok(n) if n != 0 && n.abs() <= max => Ok(n),
+        _ => Err(Error::Parse(format!("Invalid {}: {}", what, value))),
+    }
+}
+
+/// "2ª", "última"... for a position within a period
+fn position_label(position: i32) -> String {
+    match position {
+        -1 => "última".to_string(),
+        -2 => "penúltima".to_string(),
+        p if p > 0 => format!("{}ª", p),
+        p => format!("{}ª a partir do fim", -p),
+    }
+}
+
+fn month_day_label(day: i32) -> String {
+    match day {
+        -1 => "último dia".to_string(),
+        -2 => "penúltimo dia".to_string(),
+        d if d > 0 => format!("dia {}", d),
+        d => format!("{}º dia antes do fim", -d),
+    }
+}
+
+/// Recurrence set of a rule from DTSTART, with its excluded and added dates;
+/// DTSTART is local to `time_zone` when given, UTC otherwise
+fn build_rrule_set(
+    rrule_str: &str,
+    start_dt: &DateTime<Utc>,
+    exceptions: &RecurrenceExceptions,
+    time_zone: Option<&str>,
+) -> Result<RRuleSet> {
+    let (dtstart, tz) = match time_zone {
+        Some(name) => {
+            let zone = parse_time_zone(name)?;
+            (
+                format!(
+                    "DTSTART;TZID={}:{}",
+                    zone.name(),
+                    start_dt.with_timezone(&zone).format("%Y%m%dT%H%M%S")
+                ),
+                Tz::Tz(zone),
+            )
+        }
+        None => (
+            format!("DTSTART:{}", start_dt.format("%Y%m%dT%H%M%SZ")),
+            Tz::UTC,
+        ),
+    };
+    let rrule_full = format!("{}\nRRULE:{}", dtstart, rrule_str);
+
+    let mut rrule_set: RRuleSet = rrule_full
+        .parse()
+        .map_err(|e| Error::Parse(format!("Invalid RRULE: {:?}", e)))?;
+
+    for date in &exceptions.exdates {
+        rrule_set = rrule_set.exdate(parse_datetime(date)?.with_timezone(&tz));
+    }
+    for date in &exceptions.rdates {
+        rrule_set = rrule_set.rdate(parse_datetime(date)?.with_timezone(&tz));
+    }
+
+    Ok(rrule_set)
+}
+
+fn format_utc(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn normalize_dates(dates: &[String]) -> Result<Vec<String>> {
+    let mut parsed = dates
+        .iter()
+        .map(|date| parse_datetime(date))
+        .collect::<Result<Vec<_>>>()?;
+    parsed.sort();
+    parsed.dedup();
+    Ok(parsed.iter().map(format_utc).collect())
+}
+
+/// Parse an iCalendar DATE or DATE-TIME value; floating times are in `tz`,
+/// or UTC without one
+fn parse_ical_datetime(value: &str, tz: Option<chrono_tz::Tz>) -> Result<DateTime<Utc>> {
+    let invalid = || Error::Parse(format!("Invalid date: {}", value));
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let dt =
+            chrono::NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok(Utc.from_utc_datetime(&dt));
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return match tz {
+            Some(tz) => tz
+                .from_local_datetime(&dt)
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok_or_else(invalid),
+            None => Ok(Utc.from_utc_datetime(&dt)),
+        };
+    }
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+    Ok(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?))
+}
+
 fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
     // Try ISO 8601 with Z suffix
     if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
@@ -402,6 +788,10 @@ mod tests {
             frequency: RecurrenceFrequency::Daily,
             interval: 1,
             weekdays: None,
+            nth_weekdays: None,
+            month_days: None,
+            months: None,
+            set_positions: None,
             end_date: None,
             count: Some(10),
         };
@@ -415,6 +805,10 @@ mod tests {
             frequency: RecurrenceFrequency::Weekly,
             interval: 1,
             weekdays: Some(vec![1, 3, 5]), // Mon, Wed, Fri
+            nth_weekdays: None,
+            month_days: None,
+            months: None,
+            set_positions: None,
             end_date: None,
             count: None,
         };
@@ -431,12 +825,262 @@ mod tests {
         assert_eq!(parsed.rule.weekdays, Some(vec![1, 5]));
     }
 
+    #[test]
+    fn test_expand_rrule_skips_exdates_and_adds_rdates() {
+        let exceptions = RecurrenceExceptions {
+            exdates: vec!["2026-03-16T12:00:00Z".to_string()],
+            rdates: vec!["2026-03-18T15:00:00Z".to_string()],
+        };
+        let occurrences = expand_rrule(
+            "FREQ=WEEKLY;BYDAY=MO;COUNT=3",
+            "2026-03-09T12:00:00Z",
+            "2026-03-01T00:00:00Z",
+            "2026-04-01T00:00:00Z",
+            None,
+            &exceptions,
+            None,
+        )
+        .unwrap();
+        let dates: Vec<&str> = occurrences.iter().map(|o| o.date.as_str()).collect();
+        assert_eq!(
+            dates,
+            vec![
+                "2026-03-09T12:00:00Z",
+                "2026-03-18T15:00:00Z",
+                "2026-03-23T12:00:00Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_occurrence_skips_exdate() {
+        let mut exceptions = RecurrenceExceptions::default();
+        exceptions.skip("2026-03-16T09:00:00-03:00").unwrap();
+        let next = get_next_occurrence(
+            "FREQ=WEEKLY;BYDAY=MO",
+            "2026-03-09T12:00:00Z",
+            "2026-03-10T00:00:00Z",
+            &exceptions,
+            None,
+        )
+        .unwrap();
+        assert_eq!(next.as_deref(), Some("2026-03-23T12:00:00Z"));
+    }
+
+    #[test]
+    fn test_skip_removes_rdate() {
+        let mut exceptions = RecurrenceExceptions {
+            exdates: Vec::new(),
+            rdates: vec!["2026-03-18T15:00:00Z".to_string()],
+        };
+        exceptions.skip("2026-03-18T15:00:00Z").unwrap();
+        exceptions.skip("2026-03-18T15:00:00Z").unwrap();
+        assert!(exceptions.rdates.is_empty());
+        assert_eq!(exceptions.exdates, vec!["2026-03-18T15:00:00Z"]);
+    }
+
+    #[test]
+    fn test_build_exception_lines() {
+        let exceptions = RecurrenceExceptions {
+            exdates: vec![
+                "2026-03-23T12:00:00Z".to_string(),
+                "2026-03-16T12:00:00Z".to_string(),
+            ],
+            rdates: vec!["2026-03-18T15:00:00Z".to_string()],
+        };
+        assert_eq!(
+            build_exception_lines(&exceptions).unwrap(),
+            "EXDATE:20260316T120000Z,20260323T120000Z\nRDATE:20260318T150000Z"
+        );
+        assert_eq!(
+            build_exception_lines(&RecurrenceExceptions::default()).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_parse_exception_lines() {
+        let parsed = parse_exception_lines(
+            "DTSTART:20260309T120000Z\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO\n\
+             EXDATE;TZID=America/Sao_Paulo:20260316T090000,20260323T090000\n\
+             EXDATE;VALUE=DATE:20260330\n\
+             RDATE:20260318T150000Z",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.exdates,
+            vec![
+                "2026-03-16T12:00:00Z",
+                "2026-03-23T12:00:00Z",
+                "2026-03-30T00:00:00Z"
+            ]
+        );
+        assert_eq!(parsed.rdates, vec!["2026-03-18T15:00:00Z"]);
+        assert!(parse_exception_lines("EXDATE:tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_parse_advanced_rrule_roundtrip() {
+        for rrule in [
+            "FREQ=MONTHLY;BYDAY=TU;BYSETPOS=2",
+            "FREQ=MONTHLY;BYDAY=2TU,-1FR",
+            "FREQ=MONTHLY;BYMONTHDAY=-1",
+            "FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=15",
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+        ] {
+            let parsed = parse_rrule(rrule).unwrap();
+            assert_eq!(build_rrule(&parsed.rule), rrule);
+        }
+
+        let parsed = parse_rrule("FREQ=MONTHLY;BYDAY=2TU,-1FR").unwrap();
+        assert_eq!(parsed.rule.weekdays, None);
+        assert_eq!(
+            parsed.rule.nth_weekdays,
+            Some(vec![
+                NthWeekday {
+                    ordinal: 2,
+                    weekday: 2
+                },
+                NthWeekday {
+                    ordinal: -1,
+                    weekday: 5
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_rrule_rejects_invalid_parts() {
+        assert!(parse_rrule("FREQ=MONTHLY;BYMONTHDAY=32").is_err());
+        assert!(parse_rrule("FREQ=YEARLY;BYMONTH=13").is_err());
+        assert!(parse_rrule("FREQ=MONTHLY;BYDAY=TU;BYSETPOS=0").is_err());
+        assert!(parse_rrule("FREQ=MONTHLY;BYDAY=0TU").is_err());
+    }
+
+    #[test]
+    fn test_expand_rrule_keeps_local_time_across_dst() {
+        let occurrences = expand_rrule(
+            "FREQ=WEEKLY;BYDAY=MO;COUNT=2",
+            "2026-03-02T14:00:00Z",
+            "2026-03-01T00:00:00Z",
+            "2026-04-01T00:00:00Z",
+            None,
+            &RecurrenceExceptions::default(),
+            Some("America/New_York"),
+        )
+        .unwrap();
+        let dates: Vec<&str> = occurrences.iter().map(|o| o.date.as_str()).collect();
+        // 09:00 EST, then 09:00 EDT after clocks sprang forward on 03-08
+        assert_eq!(dates, vec!["2026-03-02T14:00:00Z", "2026-03-09T13:00:00Z"]);
+
+        let next = get_next_occurrence(
+            "FREQ=WEEKLY;BYDAY=MO",
+            "2026-03-02T14:00:00Z",
+            "2026-03-02T14:00:00Z",
+            &RecurrenceExceptions::default(),
+            Some("America/New_York"),
+        )
+        .unwrap();
+        assert_eq!(next, Some("2026-03-09T13:00:00Z".to_string()));
+
+        assert!(expand_rrule(
+            "FREQ=DAILY",
+            "2026-03-02T14:00:00Z",
+            "2026-03-01T00:00:00Z",
+            "2026-04-01T00:00:00Z",
+            None,
+            &RecurrenceExceptions::default(),
+            Some("Nowhere/Special"),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_occurrences_between_far_from_start() {
+        let range = (
+            parse_datetime("2029-06-04T00:00:00Z").unwrap(),
+            parse_datetime("2029-06-06T23:59:59Z").unwrap(),
+        );
+        let starts = occurrences_between(
+            "FREQ=DAILY",
+            "2025-01-06T14:00:00Z",
+            range,
+            10,
+            &RecurrenceExceptions::default(),
+            Some("America/New_York"),
+        )
+        .unwrap();
+        let dates: Vec<String> = starts.iter().map(format_utc).collect();
+        // Over 1500 days in, still at 09:00 local, now EDT
+        assert_eq!(
+            dates,
+            vec![
+                "2029-06-04T13:00:00Z",
+                "2029-06-05T13:00:00Z",
+                "2029-06-06T13:00:00Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_second_tuesday() {
+        let occurrences = expand_rrule(
+            "FREQ=MONTHLY;BYDAY=TU;BYSETPOS=2;COUNT=3",
+            "2026-03-10T13:00:00Z",
+            "2026-03-01T00:00:00Z",
+            "2026-12-31T00:00:00Z",
+            None,
+            &RecurrenceExceptions::default(),
+            None,
+        )
+        .unwrap();
+        let dates: Vec<&str> = occurrences.iter().map(|o| o.date.as_str()).collect();
+        assert_eq!(
+            dates,
+            vec![
+                "2026-03-10T13:00:00Z",
+                "2026-04-14T13:00:00Z",
+                "2026-05-12T13:00:00Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe_advanced_rrule() {
+        let describe = |rrule: &str| describe_rrule(&parse_rrule(rrule).unwrap().rule);
+        assert_eq!(
+            describe("FREQ=MONTHLY;BYDAY=TU;BYSETPOS=2"),
+            "Repete mensalmente (2ª ter)"
+        );
+        assert_eq!(
+            describe("FREQ=MONTHLY;BYDAY=-1FR"),
+            "Repete mensalmente (última sex)"
+        );
+        assert_eq!(
+            describe("FREQ=MONTHLY;BYMONTHDAY=-1"),
+            "Repete mensalmente (último dia)"
+        );
+        assert_eq!(
+            describe("FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=15;COUNT=5"),
+            "Repete anualmente (dia 15 de mar), 5 vezes"
+        );
+        assert_eq!(
+            describe("FREQ=YEARLY;BYMONTH=1,7"),
+            "Repete anualmente (em jan, jul)"
+        );
+    }
+
     #[test]
     fn test_describe_rrule() {
         let rule = RecurrenceRule {
             frequency: RecurrenceFrequency::Weekly,
             interval: 1,
             weekdays: Some(vec![1, 3, 5]),
+            nth_weekdays: None,
+            month_days: None,
+            months: None,
+            set_positions: None,
             end_date: None,
             count: None,
         };
@@ -446,4 +1090,16 @@ mod tests {
         assert!(desc.contains("qua"));
         assert!(desc.contains("sex"));
     }
+
+    #[test]
+    fn test_occurrence_id_round_trip() {
+        let id = occurrence_id("3f2a-b1", "2026-03-09T09:00:00-03:00").unwrap();
+        assert_eq!(id, "3f2a-b1_20260309T120000Z");
+        assert_eq!(
+            parse_occurrence_id(&id),
+            Some(("3f2a-b1".to_string(), "2026-03-09T12:00:00Z".to_string()))
+        );
+        assert_eq!(parse_occurrence_id("3f2a-b1"), None);
+        assert_eq!(parse_occurrence_id("3f2a_b1"), None);
+    }
 }
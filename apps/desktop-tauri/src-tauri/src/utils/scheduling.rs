@@ -0,0 +1,504 @@
+//! Meeting conflict detection and free-slot suggestions
+//!
+//! Attendees' meetings (recurring ones expanded in their time zone) and
+//! optional external busy times become busy intervals; a proposed time
+//! conflicts with every interval it overlaps, and suggestions are the
+//! candidate times within working hours that overlap none. Participants
+//! with working hours of their own are only scheduled within them.
+
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use super::datetime::{parse_datetime, parse_time_zone};
+use super::rrule::{occurrences_between, RecurrenceExceptions, MAX_RANGE_OCCURRENCES};
+use crate::{Error, Result};
+
+/// Longest range searched for free slots
+const MAX_SEARCH_DAYS: i64 = 31;
+
+/// Where a busy interval comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusySource {
+    Meeting,
+    Google,
+    /// Outside the participant's working hours
+    OutsideWorkingHours,
+}
+
+/// A meeting that keeps one of its participants busy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusyMeeting {
+    pub user_id: String,
+    pub scheduled_at: String,
+    pub duration_minutes: i32,
+    pub recurrence_rule: Option<String>,
+    #[serde(default)]
+    pub exceptions: RecurrenceExceptions,
+    pub time_zone: Option<String>,
+}
+
+/// A time a user is busy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusyInterval {
+    pub user_id: String,
+    pub starts_at: String,
+    pub ends_at: String,
+    pub source: BusySource,
+}
+
+/// When a user works, in their time zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkingHours {
+    pub user_id: String,
+    /// IANA time zone of the hours; UTC when unset
+    pub time_zone: Option<String>,
+    pub day_start_hour: u32,
+    pub day_end_hour: u32,
+    /// ISO weekdays worked, Monday = 1 to Sunday = 7
+    pub working_days: Vec<u32>,
+}
+
+impl WorkingHours {
+    pub fn validate(&self) -> Result<()> {
+        validate_hours(self.day_start_hour, self.day_end_hour)?;
+        if let Some(day) = self.working_days.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(Error::Parse(format!("Invalid weekday: {}", day)));
+        }
+        if let Some(ref name) = self.time_zone {
+            parse_time_zone(name)?;
+        }
+        Ok(())
+    }
+
+    /// Whether a meeting from `start` to `end` is within the hours, on a
+    /// working day
+    pub fn contains(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<bool> {
+        let tz = match self.time_zone.as_deref() {
+            Some(name) => parse_time_zone(name)?,
+            None => chrono_tz::UTC,
+        };
+        let weekday = start.with_timezone(&tz).weekday().number_from_monday();
+        Ok(self.working_days.contains(&weekday)
+            && within_hours(start, end, tz, self.day_start_hour, self.day_end_hour))
+    }
+}
+
+/// Where and how to look for free slots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotSearch {
+    pub range_start: String,
+    pub range_end: String,
+    pub duration_minutes: i32,
+    /// Minutes between candidate start times
+    pub step_minutes: i32,
+    /// Working hours, in `time_zone`; a slot must fit between them
+    pub day_start_hour: u32,
+    pub day_end_hour: u32,
+    /// IANA time zone of the working hours; UTC when unset
+    pub time_zone: Option<String>,
+    pub include_weekends: bool,
+    pub max_results: usize,
+    /// Participants' own working hours, which a slot must also fit
+    #[serde(default)]
+    pub working_hours: Vec<WorkingHours>,
+}
+
+/// A time every participant is free
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotSuggestion {
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+/// Busy intervals of the meetings that overlap the range
+pub fn busy_intervals(
+    meetings: &[BusyMeeting],
+    range_start: &str,
+    range_end: &str,
+) -> Result<Vec<BusyInterval>> {
+    let range_start_dt = parse_datetime(range_start)?;
+    let range_end_dt = parse_datetime(range_end)?;
+
+    let mut intervals = Vec::new();
+    for meeting in meetings {
+        let duration = Duration::minutes(meeting.duration_minutes.max(0) as i64);
+        let starts = match meeting.recurrence_rule.as_deref() {
+            Some(rule) => occurrences_between(
+                rule,
+                &meeting.scheduled_at,
+                (range_start_dt - duration, range_end_dt),
+                MAX_RANGE_OCCURRENCES,
+                &meeting.exceptions,
+                meeting.time_zone.as_deref(),
+            )?,
+            None => vec![parse_datetime(&meeting.scheduled_at)?],
+        };
+
+        for start in starts {
+            let end = start + duration;
+            if start < range_end_dt && end > range_start_dt {
+                intervals.push(BusyInterval {
+                    user_id: meeting.user_id.clone(),
+                    starts_at: format_datetime(&start),
+                    ends_at: format_datetime(&end),
+                    source: BusySource::Meeting,
+                });
+            }
+        }
+    }
+
+    intervals.sort_by(|a, b| a.starts_at.cmp(&b.starts_at));
+    Ok(intervals)
+}
+
+/// Busy intervals a meeting at `starts_at` would overlap
+pub fn find_conflicts(
+    busy: &[BusyInterval],
+    starts_at: &str,
+    duration_minutes: i32,
+) -> Result<Vec<BusyInterval>> {
+    let start = parse_datetime(starts_at)?;
+    let end = start + Duration::minutes(duration_minutes as i64);
+
+    let mut conflicts = Vec::new();
+    for interval in busy {
+        if overlaps(interval, start, end)? && !conflicts.contains(interval) {
+            conflicts.push(interval.clone());
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Participants a meeting at `starts_at` would hold outside their working
+/// hours, each as an interval covering the meeting
+pub fn outside_working_hours(
+    working_hours: &[WorkingHours],
+    starts_at: &str,
+    duration_minutes: i32,
+) -> Result<Vec<BusyInterval>> {
+    let start = parse_datetime(starts_at)?;
+    let end = start + Duration::minutes(duration_minutes as i64);
+
+    let mut outside = Vec::new();
+    for hours in working_hours {
+        if !hours.contains(start, end)? {
+            outside.push(BusyInterval {
+                user_id: hours.user_id.clone(),
+                starts_at: format_datetime(&start),
+                ends_at: format_datetime(&end),
+                source: BusySource::OutsideWorkingHours,
+            });
+        }
+    }
+    Ok(outside)
+}
+
+/// Free slots within working hours, earliest first
+pub fn suggest_slots(busy: &[BusyInterval], search: &SlotSearch) -> Result<Vec<SlotSuggestion>> {
+    if search.duration_minutes <= 0 || search.step_minutes <= 0 {
+        return Err(Error::Parse("Invalid slot durations".to_string()));
+    }
+    validate_hours(search.day_start_hour, search.day_end_hour)?;
+
+    let tz = match search.time_zone.as_deref() {
+        Some(name) => parse_time_zone(name)?,
+        None => chrono_tz::UTC,
+    };
+    let range_start = parse_datetime(&search.range_start)?;
+    let range_end = parse_datetime(&search.range_end)?;
+    if range_end - range_start > Duration::days(MAX_SEARCH_DAYS) {
+        return Err(Error::Parse(format!(
+            "Search range is longer than {} days",
+            MAX_SEARCH_DAYS
+        )));
+    }
+
+    let duration = Duration::minutes(search.duration_minutes as i64);
+    let step = Duration::minutes(search.step_minutes as i64);
+
+    // Candidates start on step boundaries
+    let step_secs = step.num_seconds();
+    let offset = range_start.timestamp().rem_euclid(step_secs);
+    let mut candidate = if offset == 0 {
+        range_start
+    } else {
+        range_start + Duration::seconds(step_secs - offset)
+    };
+
+    let mut slots = Vec::new();
+    while candidate + duration <= range_end && slots.len() < search.max_results {
+        let end = candidate + duration;
+        let is_weekend = matches!(
+            candidate.with_timezone(&tz).weekday(),
+            Weekday::Sat | Weekday::Sun
+        );
+        let mut in_hours = within_hours(
+            candidate,
+            end,
+            tz,
+            search.day_start_hour,
+            search.day_end_hour,
+        );
+        for hours in &search.working_hours {
+            if !in_hours {
+                break;
+            }
+            in_hours = hours.contains(candidate, end)?;
+        }
+
+        if (search.include_weekends || !is_weekend) && in_hours {
+            let mut is_free = true;
+            for interval in busy {
+                if overlaps(interval, candidate, end)? {
+                    is_free = false;
+                    break;
+                }
+            }
+            if is_free {
+                slots.push(SlotSuggestion {
+                    starts_at: format_datetime(&candidate),
+                    ends_at: format_datetime(&end),
+                });
+            }
+        }
+
+        candidate += step;
+    }
+
+    Ok(slots)
+}
+
+// ==========================================
+// Helper Functions
+// ==========================================
+
+fn validate_hours(day_start_hour: u32, day_end_hour: u32) -> Result<()> {
+    if day_start_hour >= day_end_hour || day_end_hour > 24 {
+        return Err(Error::Parse(format!(
+            "Invalid working hours: {}-{}",
+            day_start_hour, day_end_hour
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `start` to `end` fits between the hours of the day it starts on,
+/// in `tz`
+fn within_hours(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tz: Tz,
+    day_start_hour: u32,
+    day_end_hour: u32,
+) -> bool {
+    let local_start = start.with_timezone(&tz);
+    let local_end = end.with_timezone(&tz);
+    let end_minutes = if local_end.date_naive() == local_start.date_naive() {
+        local_end.hour() * 60 + local_end.minute()
+    } else {
+        24 * 60 + local_end.hour() * 60 + local_end.minute()
+    };
+    local_start.hour() >= day_start_hour && end_minutes <= day_end_hour * 60
+}
+
+fn overlaps(interval: &BusyInterval, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<bool> {
+    let busy_start = parse_datetime(&interval.starts_at)?;
+    let busy_end = parse_datetime(&interval.ends_at)?;
+    Ok(busy_start < end && start < busy_end)
+}
+
+fn format_datetime(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standup() -> BusyMeeting {
+        // Weekdays 09:00-09:30 in São Paulo (12:00 UTC)
+        BusyMeeting {
+            user_id: "ana".to_string(),
+            scheduled_at: "2026-03-02T12:00:00Z".to_string(),
+            duration_minutes: 30,
+            recurrence_rule: Some("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR".to_string()),
+            exceptions: RecurrenceExceptions::default(),
+            time_zone: Some("America/Sao_Paulo".to_string()),
+        }
+    }
+
+    fn review() -> BusyMeeting {
+        BusyMeeting {
+            user_id: "bruno".to_string(),
+            scheduled_at: "2026-03-10T13:00:00Z".to_string(),
+            duration_minutes: 60,
+            recurrence_rule: None,
+            exceptions: RecurrenceExceptions::default(),
+            time_zone: None,
+        }
+    }
+
+    fn workday_search() -> SlotSearch {
+        SlotSearch {
+            range_start: "2026-03-10T11:50:00Z".to_string(),
+            range_end: "2026-03-10T21:00:00Z".to_string(),
+            duration_minutes: 60,
+            step_minutes: 30,
+            day_start_hour: 9,
+            day_end_hour: 12,
+            time_zone: Some("America/Sao_Paulo".to_string()),
+            include_weekends: false,
+            max_results: 10,
+            working_hours: Vec::new(),
+        }
+    }
+
+    fn lisbon_mornings() -> WorkingHours {
+        // 09:00-12:00 in Lisbon (UTC in March), Monday to Thursday
+        WorkingHours {
+            user_id: "carla".to_string(),
+            time_zone: Some("Europe/Lisbon".to_string()),
+            day_start_hour: 9,
+            day_end_hour: 12,
+            working_days: vec![1, 2, 3, 4],
+        }
+    }
+
+    #[test]
+    fn test_busy_intervals_expand_recurring_meetings() {
+        let busy = busy_intervals(
+            &[standup(), review()],
+            "2026-03-09T00:00:00Z",
+            "2026-03-11T00:00:00Z",
+        )
+        .unwrap();
+        let starts: Vec<(&str, &str)> = busy
+            .iter()
+            .map(|b| (b.user_id.as_str(), b.starts_at.as_str()))
+            .collect();
+        assert_eq!(
+            starts,
+            vec![
+                ("ana", "2026-03-09T12:00:00Z"),
+                ("ana", "2026-03-10T12:00:00Z"),
+                ("bruno", "2026-03-10T13:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let busy = busy_intervals(
+            &[standup(), review()],
+            "2026-03-10T00:00:00Z",
+            "2026-03-11T00:00:00Z",
+        )
+        .unwrap();
+
+        let conflicts = find_conflicts(&busy, "2026-03-10T12:15:00Z", 60).unwrap();
+        assert_eq!(conflicts.len(), 2);
+
+        // Back to back is not a conflict
+        assert!(find_conflicts(&busy, "2026-03-10T14:00:00Z", 30)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_suggest_slots_skips_busy_times_and_outside_hours() {
+        let busy = busy_intervals(
+            &[standup(), review()],
+            "2026-03-10T00:00:00Z",
+            "2026-03-11T00:00:00Z",
+        )
+        .unwrap();
+
+        // 09:00-12:00 local: standup until 09:30, review 10:00-11:00
+        let slots = suggest_slots(&busy, &workday_search()).unwrap();
+        let starts: Vec<&str> = slots.iter().map(|s| s.starts_at.as_str()).collect();
+        assert_eq!(starts, vec!["2026-03-10T14:00:00Z"]);
+    }
+
+    #[test]
+    fn test_suggest_slots_skips_weekends() {
+        let mut search = workday_search();
+        search.range_start = "2026-03-14T00:00:00Z".to_string();
+        search.range_end = "2026-03-16T23:00:00Z".to_string();
+        let slots = suggest_slots(&[], &search).unwrap();
+        assert_eq!(slots.first().unwrap().starts_at, "2026-03-16T12:00:00Z");
+        assert_eq!(slots.len(), 5);
+
+        search.include_weekends = true;
+        let slots = suggest_slots(&[], &search).unwrap();
+        assert_eq!(slots.first().unwrap().starts_at, "2026-03-14T12:00:00Z");
+    }
+
+    #[test]
+    fn test_outside_working_hours() {
+        let hours = [lisbon_mornings()];
+        assert!(outside_working_hours(&hours, "2026-03-10T10:00:00Z", 60)
+            .unwrap()
+            .is_empty());
+
+        // Runs past noon
+        let outside = outside_working_hours(&hours, "2026-03-10T11:30:00Z", 60).unwrap();
+        assert_eq!(outside.len(), 1);
+        assert_eq!(outside[0].user_id, "carla");
+        assert_eq!(outside[0].source, BusySource::OutsideWorkingHours);
+
+        // Friday is not a working day
+        assert_eq!(
+            outside_working_hours(&hours, "2026-03-13T10:00:00Z", 60)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_suggest_slots_within_participants_hours() {
+        // 09:00-12:00 in São Paulo is 12:00-15:00 UTC; Carla works until
+        // 12:00 UTC, so no slot fits both
+        let mut search = workday_search();
+        search.working_hours = vec![lisbon_mornings()];
+        assert!(suggest_slots(&[], &search).unwrap().is_empty());
+
+        search.working_hours[0].day_end_hour = 14;
+        let slots = suggest_slots(&[], &search).unwrap();
+        let starts: Vec<&str> = slots.iter().map(|s| s.starts_at.as_str()).collect();
+        assert_eq!(
+            starts,
+            vec![
+                "2026-03-10T12:00:00Z",
+                "2026-03-10T12:30:00Z",
+                "2026-03-10T13:00:00Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_working_hours_validate() {
+        assert!(lisbon_mornings().validate().is_ok());
+
+        let mut hours = lisbon_mornings();
+        hours.working_days.push(8);
+        assert!(hours.validate().is_err());
+
+        let mut hours = lisbon_mornings();
+        hours.time_zone = Some("Mars/Olympus".to_string());
+        assert!(hours.validate().is_err());
+    }
+
+    #[test]
+    fn test_suggest_slots_rejects_invalid_search() {
+        let mut search = workday_search();
+        search.day_end_hour = 8;
+        assert!(suggest_slots(&[], &search).is_err());
+
+        let mut search = workday_search();
+        search.range_end = "2026-05-01T00:00:00Z".to_string();
+        assert!(suggest_slots(&[], &search).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Text utilities
+//!
+//! Grapheme-aware truncation for message previews and notification bodies.
+//! Cutting by bytes or `char`s splits emoji sequences (flags, skin tones,
+//! ZWJ families) and combining marks, so lengths here count user-perceived
+//! characters instead. Also tells links apart from other free-form text.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Appended to truncated text
+pub const ELLIPSIS: &str = "…";
+
+/// Number of user-perceived characters in `text`
+pub fn grapheme_len(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Truncate to at most `max_graphemes` characters, ellipsis included
+pub fn truncate_graphemes(text: &str, max_graphemes: usize) -> String {
+    if max_graphemes == 0 {
+        return String::new();
+    }
+
+    // Start of the grapheme the ellipsis replaces
+    let mut starts = text.grapheme_indices(true).map(|(i, _)| i);
+    let Some(cut) = starts.nth(max_graphemes - 1) else {
+        return text.to_string();
+    };
+    if starts.next().is_none() {
+        return text.to_string();
+    }
+
+    format!("{}{}", text[..cut].trim_end(), ELLIPSIS)
+}
+
+/// Single-line preview of a message: whitespace runs collapsed, then truncated
+pub fn message_preview(text: &str, max_graphemes: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_graphemes(&collapsed, max_graphemes)
+}
+
+/// Whether `text` is an absolute http(s) link, e.g. a video call URL
+pub fn is_web_url(text: &str) -> bool {
+    reqwest::Url::parse(text.trim())
+        .map(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_short_text() {
+        assert_eq!(truncate_graphemes("hello", 5), "hello");
+        assert_eq!(truncate_graphemes("hello", 10), "hello");
+        assert_eq!(truncate_graphemes("", 3), "");
+        assert_eq!(truncate_graphemes("hello", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_counts_ellipsis() {
+        assert_eq!(truncate_graphemes("hello world", 6), "hello…");
+        assert_eq!(grapheme_len(&truncate_graphemes("hello world", 6)), 6);
+        // Trailing whitespace before the ellipsis is dropped
+        assert_eq!(truncate_graphemes("hello world", 7), "hello…");
+    }
+
+    #[test]
+    fn test_truncate_never_splits_graphemes() {
+        // Family ZWJ sequence, flag and skin tone modifier are one grapheme each
+        let text = "👨‍👩‍👧‍👦🇧🇷👍🏽 olá";
+        assert_eq!(grapheme_len(text), 7);
+        assert_eq!(truncate_graphemes(text, 3), "👨‍👩‍👧‍👦🇧🇷…");
+        // Combining accent stays attached to its base letter
+        assert_eq!(
+            truncate_graphemes("cafe\u{301} com leite", 5),
+            "cafe\u{301}…"
+        );
+    }
+
+    #[test]
+    fn test_message_preview_is_single_line() {
+        assert_eq!(
+            message_preview("  first line\n\nsecond\tline  ", 100),
+            "first line second line"
+        );
+        assert_eq!(message_preview("one\ntwo three", 8), "one two…");
+    }
+
+    #[test]
+    fn test_is_web_url() {
+        assert!(is_web_url("https://meet.google.com/abc-defg-hij"));
+        assert!(is_web_url(" http://zoom.us/j/123 "));
+        assert!(!is_web_url("Sala 3, 2º andar"));
+        assert!(!is_web_url("ftp://files.example.com"));
+        assert!(!is_web_url("javascript:alert(1)"));
+    }
+}
@@ -0,0 +1,341 @@
+//! Voice messages
+//!
+//! A voice message is recorded from the microphone, encoded to Opus in 20 ms
+//! frames like voice chat, and stored in an Ogg container, which the webview
+//! plays natively. The message content is JSON with the duration and a
+//! waveform preview, so chat can draw the message before the audio loads.
+//!
+//! The microphone is driven from the recorder's own thread, as its stream
+//! cannot be moved across threads.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{self, FRAME_DURATION, FRAME_SAMPLES, SAMPLE_RATE};
+use crate::{Error, Result};
+
+/// `message_type` of voice messages
+pub const MESSAGE_TYPE: &str = "voice";
+
+/// Recording stops on its own after this long
+pub const MAX_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// Shortest recording worth sending
+pub const MIN_DURATION: Duration = Duration::from_millis(500);
+
+/// Bars in a waveform preview
+pub const WAVEFORM_BARS: usize = 64;
+
+/// Samples the decoder drops at the start: the encoder's lookahead at 48 kHz
+const PRE_SKIP: u16 = 312;
+
+/// Opus packets grouped in an Ogg page, one second of audio
+const PACKETS_PER_PAGE: usize = 50;
+
+/// Ogg pages hold at most this many lacing values
+const MAX_PAGE_SEGMENTS: usize = 255;
+
+/// Content of a voice message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceMessageContent {
+    pub duration_ms: u64,
+    /// Loudness of successive slices of the recording, 0-100
+    pub waveform: Vec<u8>,
+}
+
+/// Finished recording, ready to send
+#[derive(Debug, Clone)]
+pub struct Recording {
+    /// Ogg Opus file
+    pub audio: Vec<u8>,
+    pub content: VoiceMessageContent,
+}
+
+impl Recording {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.content.duration_ms)
+    }
+}
+
+/// Waveform preview from the level of each frame, scaled so the loudest bar
+/// is 100; recordings shorter than `bars` frames get one bar per frame
+pub fn waveform(levels: &[f32], bars: usize) -> Vec<u8> {
+    let bars = bars.min(levels.len());
+    let peaks: Vec<f32> = (0..bars)
+        .map(|i| {
+            levels[i * levels.len() / bars..(i + 1) * levels.len() / bars]
+                .iter()
+                .fold(0.0f32, |peak, level| peak.max(*level))
+        })
+        .collect();
+
+    let loudest = peaks.iter().fold(0.0f32, |max, peak| max.max(*peak));
+    if loudest <= 0.0 {
+        return vec![0; bars];
+    }
+    peaks
+        .iter()
+        .map(|peak| (peak / loudest * 100.0).round() as u8)
+        .collect()
+}
+
+/// Ogg Opus file of mono Opus packets of `frame_samples` each (RFC 7845)
+pub fn ogg_opus(packets: &[Vec<u8>], frame_samples: u64, serial: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+    head.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+
+    let vendor = b"squadx";
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes()); // no comments
+
+    let mut out = Vec::new();
+    write_page(&mut out, 0x02, 0, serial, 0, &[&head]);
+    write_page(&mut out, 0, 0, serial, 1, &[&tags]);
+
+    let mut sequence = 2;
+    let mut written = 0;
+    while written < packets.len() {
+        let mut segments = 0;
+        let mut page = Vec::new();
+        for packet in &packets[written..] {
+            let needed = packet.len() / 255 + 1;
+            if page.len() == PACKETS_PER_PAGE || segments + needed > MAX_PAGE_SEGMENTS {
+                break;
+            }
+            segments += needed;
+            page.push(packet.as_slice());
+        }
+        written += page.len();
+
+        let header_type = if written == packets.len() { 0x04 } else { 0 };
+        let granule = written as u64 * frame_samples;
+        write_page(&mut out, header_type, granule, serial, sequence, &page);
+        sequence += 1;
+    }
+    out
+}
+
+/// Append an Ogg page holding whole packets
+fn write_page(
+    out: &mut Vec<u8>,
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    sequence: u32,
+    packets: &[&[u8]],
+) {
+    let start = out.len();
+    out.extend_from_slice(b"OggS");
+    out.push(0); // version
+    out.push(header_type);
+    out.extend_from_slice(&granule.to_le_bytes());
+    out.extend_from_slice(&serial.to_le_bytes());
+    out.extend_from_slice(&sequence.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // checksum, filled in below
+
+    let segments: Vec<u8> = packets
+        .iter()
+        .flat_map(|packet| {
+            std::iter::repeat(255)
+                .take(packet.len() / 255)
+                .chain(std::iter::once((packet.len() % 255) as u8))
+        })
+        .collect();
+    out.push(segments.len() as u8);
+    out.extend_from_slice(&segments);
+    for packet in packets {
+        out.extend_from_slice(packet);
+    }
+
+    let crc = ogg_crc(&out[start..]);
+    out[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+}
+
+/// CRC-32 of Ogg pages: polynomial 0x04c11db7, unreflected, no final xor
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ (u32::from(*byte) << 24), |crc, _| {
+            if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Voice message being recorded from the default microphone
+pub struct VoiceRecorder {
+    stop_flag: Arc<AtomicBool>,
+    thread: JoinHandle<Result<Recording>>,
+}
+
+impl VoiceRecorder {
+    /// Start recording; fails when the microphone cannot be opened
+    pub fn start() -> Result<Self> {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let flag = stop_flag.clone();
+        let thread = std::thread::spawn(move || record(&flag, ready_tx));
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self { stop_flag, thread }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(Error::Capture(
+                "Voice recording stopped unexpectedly".to_string(),
+            )),
+        }
+    }
+
+    /// Stop recording and package what was recorded
+    pub fn stop(self) -> Result<Recording> {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        self.thread
+            .join()
+            .map_err(|_| Error::Capture("Voice recording crashed".to_string()))?
+    }
+}
+
+fn record(stop_flag: &AtomicBool, ready: mpsc::Sender<Result<()>>) -> Result<Recording> {
+    let (tx, rx) = mpsc::channel();
+    let setup = audio::open_microphone(tx).and_then(|(stream, input_rate)| {
+        let encoder =
+            opus::Encoder::new(SAMPLE_RATE, opus::Channels::Mono, opus::Application::Voip)
+                .map_err(|e| Error::Capture(format!("Failed to create Opus encoder: {}", e)))?;
+        Ok((stream, input_rate, encoder))
+    });
+    let (stream, input_rate, mut encoder) = match setup {
+        Ok(setup) => {
+            let _ = ready.send(Ok(()));
+            setup
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return Err(Error::Capture("Microphone unavailable".to_string()));
+        }
+    };
+
+    let max_frames = (MAX_DURATION.as_millis() / FRAME_DURATION.as_millis()) as usize;
+    let mut pending: Vec<f32> = Vec::new();
+    let mut packets = Vec::new();
+    let mut levels = Vec::new();
+    let mut packet = vec![0u8; 4000];
+
+    while !stop_flag.load(Ordering::Relaxed) && packets.len() < max_frames {
+        let samples = match rx.recv_timeout(audio::STOP_POLL_INTERVAL) {
+            Ok(samples) => samples,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        pending.extend(audio::resample(&samples, input_rate, SAMPLE_RATE));
+
+        while pending.len() >= FRAME_SAMPLES && packets.len() < max_frames {
+            let frame: Vec<f32> = pending.drain(..FRAME_SAMPLES).collect();
+            let len = encoder
+                .encode_float(&frame, &mut packet)
+                .map_err(|e| Error::Capture(format!("Failed to encode audio frame: {}", e)))?;
+            packets.push(packet[..len].to_vec());
+            levels.push(audio::frame_level(&frame));
+        }
+    }
+    drop(stream);
+    tracing::info!("Recorded voice message of {} frames", packets.len());
+
+    Ok(Recording {
+        audio: ogg_opus(&packets, FRAME_SAMPLES as u64, rand::random()),
+        content: VoiceMessageContent {
+            duration_ms: packets.len() as u64 * FRAME_DURATION.as_millis() as u64,
+            waveform: waveform(&levels, WAVEFORM_BARS),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waveform() {
+        assert_eq!(waveform(&[], WAVEFORM_BARS), Vec::<u8>::new());
+        assert_eq!(waveform(&[0.0, 0.0], 4), vec![0, 0]);
+        assert_eq!(waveform(&[0.1, 0.2, 0.05, 0.4], 2), vec![50, 100]);
+        assert_eq!(waveform(&[0.2, 0.1], 8), vec![100, 50]);
+
+        let levels: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        let bars = waveform(&levels, WAVEFORM_BARS);
+        assert_eq!(bars.len(), WAVEFORM_BARS);
+        assert_eq!(bars.last(), Some(&100));
+    }
+
+    #[test]
+    fn test_ogg_crc() {
+        assert_eq!(ogg_crc(b"123456789"), 0x89a1_897f);
+        assert_eq!(ogg_crc(&[]), 0);
+    }
+
+    /// Pages of an Ogg stream as (header type, granule, sequence, lacing)
+    fn pages(mut data: &[u8]) -> Vec<(u8, u64, u32, Vec<u8>)> {
+        let mut pages = Vec::new();
+        while !data.is_empty() {
+            assert_eq!(&data[..4], b"OggS");
+            let header_type = data[5];
+            let granule = u64::from_le_bytes(data[6..14].try_into().unwrap());
+            let sequence = u32::from_le_bytes(data[18..22].try_into().unwrap());
+            let crc = u32::from_le_bytes(data[22..26].try_into().unwrap());
+            let table = data[27..27 + data[26] as usize].to_vec();
+            let len = 27 + table.len() + table.iter().map(|s| *s as usize).sum::<usize>();
+
+            let mut page = data[..len].to_vec();
+            page[22..26].copy_from_slice(&[0; 4]);
+            assert_eq!(ogg_crc(&page), crc);
+
+            pages.push((header_type, granule, sequence, table));
+            data = &data[len..];
+        }
+        pages
+    }
+
+    #[test]
+    fn test_ogg_opus() {
+        let packets: Vec<Vec<u8>> = (0..120).map(|i| vec![i as u8; 60]).collect();
+        let file = ogg_opus(&packets, 960, 7);
+        assert_eq!(&file[28..36], b"OpusHead");
+
+        let pages = pages(&file);
+        // Two header pages, then a second of audio per page
+        assert_eq!(pages.len(), 5);
+        assert_eq!(pages[0].0, 0x02);
+        assert_eq!(pages[2].1, 50 * 960);
+        assert_eq!(pages[2].3.len(), 50);
+        assert_eq!(pages[4].0, 0x04);
+        assert_eq!(pages[4].1, 120 * 960);
+        assert_eq!(
+            pages.iter().map(|p| p.2).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_ogg_opus_large_packets() {
+        // 2000 bytes take eight lacing values, so 31 packets fill a page
+        let packets = vec![vec![0u8; 2000]; 100];
+        let pages = pages(&ogg_opus(&packets, 960, 7));
+        assert_eq!(pages.len(), 6);
+        assert_eq!(pages[2].3.len(), 31 * 8);
+        assert_eq!(&pages[2].3[..8], &[255, 255, 255, 255, 255, 255, 255, 215]);
+        assert_eq!(pages[2].1, 31 * 960);
+        assert_eq!(pages.last().unwrap().1, 100 * 960);
+    }
+}
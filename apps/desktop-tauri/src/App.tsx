@@ -7,6 +7,7 @@ import { ViewSession } from "./components/session/ViewSession";
 import { ChatPage } from "./pages/ChatPage";
 import { CalendarPage } from "./pages/CalendarPage";
 import { getCurrentUser, logout, type UserInfo } from "./lib/auth";
+import { useNotifications } from "./hooks/useNotifications";
 
 interface User {
   id: string;
@@ -16,6 +17,7 @@ interface User {
 function App() {
   const [user, setUser] = useState<User | null>(null);
   const [loading, setLoading] = useState(true);
+  useNotifications();
 
   useEffect(() => {
     // Check for existing session on startup (via secure backend)
@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { Loader2, Unlink, RefreshCw, Calendar, CheckCircle, XCircle } from "lucide-react";
 
 interface GoogleCalendarStatus {
@@ -8,6 +9,12 @@ interface GoogleCalendarStatus {
   sync_enabled: boolean;
 }
 
+interface GoogleAuthOutcome {
+  attempt_id: string;
+  connected: boolean;
+  error?: string;
+}
+
 interface GoogleCalendarConnectProps {
   onStatusChange?: (status: GoogleCalendarStatus) => void;
 }
@@ -42,33 +49,39 @@ export function GoogleCalendarConnect({ onStatusChange }: GoogleCalendarConnectP
   };
 
   const handleConnect = async () => {
+    let unlisten: UnlistenFn | undefined;
     try {
       setIsConnecting(true);
       setError(null);
 
-      // Get OAuth URL
-      const authUrl = await invoke<string>("start_google_auth");
-
-      // Open in browser
+      // The backend catches the redirect and reports how it went, tagged
+      // with the attempt so outcomes of replaced attempts are told apart
+      const attemptId = crypto.randomUUID();
+      let resolveOutcome: (outcome: GoogleAuthOutcome) => void = () => {};
+      const outcome = new Promise<GoogleAuthOutcome>((resolve) => {
+        resolveOutcome = resolve;
+      });
+      unlisten = await listen<GoogleAuthOutcome>("calendar:google-auth", (event) => {
+        if (event.payload.attempt_id === attemptId) {
+          resolveOutcome(event.payload);
+        }
+      });
+
+      // Get OAuth URL and open it in the browser
+      const authUrl = await invoke<string>("start_google_auth", { attemptId });
       window.open(authUrl, "_blank");
 
-      // Show instructions
-      setError("Apos autorizar, cole o codigo aqui");
-
-      // In a real implementation, you would:
-      // 1. Use a custom URL scheme to receive the callback
-      // 2. Or show a dialog for the user to paste the code
-      // For now, we'll prompt for the code
-      const code = prompt("Cole o codigo de autorizacao do Google:");
-
-      if (code) {
-        await invoke("complete_google_auth", { code: code.trim() });
+      const result = await outcome;
+      if (result.connected) {
         await loadStatus();
+      } else {
+        setError(result.error ?? "Erro ao conectar");
       }
     } catch (err) {
       console.error("Failed to connect Google:", err);
       setError(err instanceof Error ? err.message : "Erro ao conectar");
     } finally {
+      unlisten?.();
       setIsConnecting(false);
     }
   };
@@ -31,6 +31,8 @@ export function ChatPanel({
     createDirectConversation,
     createGroup,
     markAsRead,
+    openConversation,
+    closeConversation,
     getTeamMembers,
     getConversationMessages,
   } = useChat();
@@ -66,6 +68,16 @@ export function ChatPanel({
     }
   }, [selectedConversationId, conversations]);
 
+  // Receive the selected conversation's broadcasts while it is shown
+  const selectedId = selectedConversation?.id;
+  useEffect(() => {
+    if (!selectedId) return;
+    openConversation(selectedId);
+    return () => {
+      closeConversation(selectedId);
+    };
+  }, [selectedId, openConversation, closeConversation]);
+
   const handleSelectConversation = useCallback(
     async (conversation: Conversation) => {
       setSelectedConversation(conversation);
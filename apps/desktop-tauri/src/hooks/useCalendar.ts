@@ -3,9 +3,23 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import type {
   Meeting,
+  AgendaDay,
+  AttendanceType,
   CreateMeetingParams,
+  MeetingGuest,
+  MeetingStarting,
+  MeetingStats,
+  MeetingTag,
   UpdateMeetingParams,
+  GoogleCalendarInfo,
   GoogleCalendarStatus,
+  GoogleSyncStatus,
+  IcsImportResult,
+  MeetingConflict,
+  SlotSuggestion,
+  SuggestMeetingSlotsParams,
+  AvailabilityParams,
+  WorkingHours,
 } from "../types/calendar";
 
 export function useCalendar() {
@@ -97,6 +111,27 @@ export function useCalendar() {
     }
   }, [loadUpcoming]);
 
+  // Copy a meeting, with its attendees, to a new time
+  const duplicateMeeting = useCallback(async (meetingId: string, newTime: string): Promise<Meeting> => {
+    try {
+      setError(null);
+      const meeting = await invoke<Meeting>("duplicate_meeting", { meetingId, newTime });
+
+      // Add to local state
+      setMeetings((prev) => [...prev, meeting].sort(
+        (a, b) => new Date(a.scheduled_at).getTime() - new Date(b.scheduled_at).getTime()
+      ));
+
+      // Refresh upcoming
+      await loadUpcoming();
+
+      return meeting;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, [loadUpcoming]);
+
   // Update a meeting
   const updateMeeting = useCallback(async (meetingId: string, params: UpdateMeetingParams) => {
     try {
@@ -186,6 +221,30 @@ export function useCalendar() {
     }
   }, [getMeeting]);
 
+  // Make an attendee required or optional, with an RSVP deadline
+  const setAttendeeAttendance = useCallback(async (
+    meetingId: string,
+    userId: string,
+    attendanceType: AttendanceType,
+    rsvpDeadline?: string
+  ) => {
+    try {
+      setError(null);
+      await invoke("set_attendee_attendance", { meetingId, userId, attendanceType, rsvpDeadline });
+
+      // Refresh meeting
+      const updated = await getMeeting(meetingId);
+      if (updated) {
+        setMeetings((prev) =>
+          prev.map((m) => (m.id === meetingId ? updated : m))
+        );
+      }
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, [getMeeting]);
+
   // Remove attendee from meeting
   const removeAttendee = useCallback(async (meetingId: string, userId: string) => {
     try {
@@ -205,6 +264,43 @@ export function useCalendar() {
     }
   }, [getMeeting]);
 
+  // Get external guests of a meeting
+  const getMeetingGuests = useCallback(async (meetingId: string): Promise<MeetingGuest[]> => {
+    try {
+      setError(null);
+      return await invoke<MeetingGuest[]>("get_meeting_guests", { meetingId });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Invite someone without an account by email
+  const addGuest = useCallback(async (
+    meetingId: string,
+    email: string,
+    displayName?: string
+  ): Promise<MeetingGuest> => {
+    try {
+      setError(null);
+      return await invoke<MeetingGuest>("add_meeting_guest", { meetingId, email, displayName });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Remove an external guest from a meeting
+  const removeGuest = useCallback(async (meetingId: string, guestId: string) => {
+    try {
+      setError(null);
+      await invoke("remove_meeting_guest", { meetingId, guestId });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
   // Start a meeting (create session)
   const startMeeting = useCallback(async (meetingId: string): Promise<string> => {
     try {
@@ -227,6 +323,27 @@ export function useCalendar() {
     }
   }, []);
 
+  // Start a meeting for all attendees (organizer only)
+  const startMeetingEverywhere = useCallback(async (meetingId: string): Promise<MeetingStarting> => {
+    try {
+      setError(null);
+      const started = await invoke<MeetingStarting>("start_meeting_everywhere", { meetingId });
+
+      setMeetings((prev) =>
+        prev.map((m) =>
+          m.id === started.meeting_id
+            ? { ...m, session_id: started.session_id, join_code: started.join_code, status: 'ongoing' as const }
+            : m
+        )
+      );
+
+      return started;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
   // Get meetings for a specific date
   const getMeetingsForDate = useCallback((date: Date): Meeting[] => {
     const dateStr = date.toISOString().split('T')[0];
@@ -271,6 +388,254 @@ export function useCalendar() {
     await changeDate(new Date());
   }, [changeDate]);
 
+  // Skip one occurrence of a recurring meeting
+  const skipOccurrence = useCallback(async (meetingId: string, occurrenceDate: string) => {
+    try {
+      setError(null);
+      const meeting = await invoke<Meeting>("skip_meeting_occurrence", {
+        meetingId,
+        occurrenceDate,
+      });
+
+      setMeetings((prev) => prev.map((m) => (m.id === meetingId ? meeting : m)));
+      await loadUpcoming();
+
+      return meeting;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, [loadUpcoming]);
+
+  // iCalendar export, returning the .ics file contents
+  const exportMeetingIcs = useCallback(async (meetingId: string): Promise<string> => {
+    try {
+      setError(null);
+      return await invoke<string>("export_meeting_ics", { meetingId });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  const exportCalendarIcs = useCallback(async (startDate: Date, endDate: Date): Promise<string> => {
+    try {
+      setError(null);
+      return await invoke<string>("export_calendar_ics", {
+        startDate: startDate.toISOString(),
+        endDate: endDate.toISOString(),
+      });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // iCalendar import, creating a meeting per event
+  const importIcs = useCallback(async (contents: string): Promise<IcsImportResult> => {
+    try {
+      setError(null);
+      const result = await invoke<IcsImportResult>("import_ics", { contents });
+
+      setMeetings((prev) => [...prev, ...result.meetings].sort(
+        (a, b) => new Date(a.scheduled_at).getTime() - new Date(b.scheduled_at).getTime()
+      ));
+      await loadUpcoming();
+
+      return result;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, [loadUpcoming]);
+
+  // Agenda list of days from startDate, recurrences expanded
+  const getAgenda = useCallback(async (
+    startDate: string,
+    days: number,
+    timeZone?: string
+  ): Promise<AgendaDay[]> => {
+    try {
+      setError(null);
+      return await invoke<AgendaDay[]>("get_agenda", { startDate, days, timeZone });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Week holding date, grouped by day
+  const getWeekMeetings = useCallback(async (
+    date: string,
+    startOnSunday = false,
+    timeZone?: string
+  ): Promise<AgendaDay[]> => {
+    try {
+      setError(null);
+      return await invoke<AgendaDay[]>("get_week_meetings", { date, startOnSunday, timeZone });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Double-booking check of a proposed time, organizer included
+  const checkConflicts = useCallback(async (
+    scheduledAt: string,
+    durationMinutes: number,
+    attendeeIds: string[],
+    excludeMeetingId?: string,
+    includeGoogle = false
+  ): Promise<MeetingConflict[]> => {
+    try {
+      setError(null);
+      return await invoke<MeetingConflict[]>("check_conflicts", {
+        scheduledAt,
+        durationMinutes,
+        attendeeIds,
+        excludeMeetingId,
+        includeGoogle,
+      });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Times everyone is free within working hours
+  const suggestMeetingSlots = useCallback(async (
+    params: SuggestMeetingSlotsParams
+  ): Promise<SlotSuggestion[]> => {
+    try {
+      setError(null);
+      return await invoke<SlotSuggestion[]>("suggest_meeting_slots", { params });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Meeting tags
+  const listMeetingTags = useCallback(async (): Promise<MeetingTag[]> => {
+    try {
+      setError(null);
+      return await invoke<MeetingTag[]>("list_meeting_tags");
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  const createMeetingTag = useCallback(async (name: string, color: string): Promise<MeetingTag> => {
+    try {
+      setError(null);
+      return await invoke<MeetingTag>("create_meeting_tag", { name, color });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  const updateMeetingTag = useCallback(async (
+    tagId: string,
+    changes: { name?: string; color?: string }
+  ): Promise<MeetingTag> => {
+    try {
+      setError(null);
+      const tag = await invoke<MeetingTag>("update_meeting_tag", { tagId, ...changes });
+      if (changes.color) {
+        setMeetings((prev) =>
+          prev.map((m) => (m.tag_id === tagId ? { ...m, color: tag.color } : m))
+        );
+      }
+      return tag;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  const deleteMeetingTag = useCallback(async (tagId: string) => {
+    try {
+      setError(null);
+      await invoke("delete_meeting_tag", { tagId });
+      setMeetings((prev) =>
+        prev.map((m) => (m.tag_id === tagId ? { ...m, tag_id: undefined } : m))
+      );
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Tag a meeting, taking the tag's color; null removes the tag and color
+  const setMeetingTag = useCallback(async (meetingId: string, tag: MeetingTag | null) => {
+    try {
+      setError(null);
+      await invoke("set_meeting_tag", { meetingId, tagId: tag?.id ?? null });
+      setMeetings((prev) =>
+        prev.map((m) =>
+          m.id === meetingId ? { ...m, tag_id: tag?.id, color: tag?.color } : m
+        )
+      );
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Color a meeting without a tag; null clears it
+  const setMeetingColor = useCallback(async (meetingId: string, color: string | null) => {
+    try {
+      setError(null);
+      await invoke("set_meeting_color", { meetingId, color });
+      setMeetings((prev) =>
+        prev.map((m) =>
+          m.id === meetingId ? { ...m, tag_id: undefined, color: color ?? undefined } : m
+        )
+      );
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Calendar health of the current user between two dates
+  const getMeetingStats = useCallback(async (
+    startDate: string,
+    endDate: string,
+    timeZone?: string
+  ): Promise<MeetingStats> => {
+    try {
+      setError(null);
+      return await invoke<MeetingStats>("get_meeting_stats", { startDate, endDate, timeZone });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Working hours, of the current user unless another is given
+  const getUserAvailability = useCallback(async (userId?: string): Promise<WorkingHours | null> => {
+    try {
+      setError(null);
+      return await invoke<WorkingHours | null>("get_user_availability", { userId });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  const setUserAvailability = useCallback(async (params: AvailabilityParams): Promise<WorkingHours> => {
+    try {
+      setError(null);
+      return await invoke<WorkingHours>("set_user_availability", { params });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
   // Google Calendar integration (placeholders)
   const connectGoogle = useCallback(async () => {
     // TODO: Implement Google OAuth flow
@@ -283,8 +648,38 @@ export function useCalendar() {
   }, []);
 
   const syncWithGoogle = useCallback(async () => {
-    // TODO: Implement Google sync
-    console.log("Google Calendar sync not yet implemented");
+    try {
+      const status = await invoke<GoogleSyncStatus>("sync_google_calendar_now");
+      await loadMonthMeetings(selectedDate);
+      return status;
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, [loadMonthMeetings, selectedDate]);
+
+  const listGoogleCalendars = useCallback(async (): Promise<GoogleCalendarInfo[]> => {
+    try {
+      setError(null);
+      return await invoke<GoogleCalendarInfo[]>("list_google_calendars");
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
+  }, []);
+
+  // Calendar meetings sync to, and the other calendars imports read from
+  const setGoogleCalendars = useCallback(async (
+    calendarId: string,
+    selectedCalendarIds: string[]
+  ) => {
+    try {
+      setError(null);
+      await invoke("set_google_calendars", { calendarId, selectedCalendarIds });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      throw err;
+    }
   }, []);
 
   // Initial load
@@ -296,6 +691,7 @@ export function useCalendar() {
   // Listen for realtime updates
   useEffect(() => {
     let unlistenMeetingUpdated: UnlistenFn | undefined;
+    let unlistenMeetingStarting: UnlistenFn | undefined;
 
     const setupListeners = async () => {
       unlistenMeetingUpdated = await listen<Meeting>("calendar:meeting-updated", (event) => {
@@ -304,12 +700,23 @@ export function useCalendar() {
           prev.map((m) => (m.id === meeting.id ? meeting : m))
         );
       });
+      unlistenMeetingStarting = await listen<MeetingStarting>("meeting:starting", (event) => {
+        const started = event.payload;
+        setMeetings((prev) =>
+          prev.map((m) =>
+            m.id === started.meeting_id
+              ? { ...m, session_id: started.session_id, join_code: started.join_code, status: 'ongoing' as const }
+              : m
+          )
+        );
+      });
     };
 
     setupListeners();
 
     return () => {
       unlistenMeetingUpdated?.();
+      unlistenMeetingStarting?.();
     };
   }, []);
 
@@ -328,23 +735,58 @@ export function useCalendar() {
     loadUpcoming,
     getMeeting,
     createMeeting,
+    duplicateMeeting,
     updateMeeting,
     cancelMeeting,
     deleteMeeting,
     respondToMeeting,
     addAttendee,
+    setAttendeeAttendance,
     removeAttendee,
+    getMeetingGuests,
+    addGuest,
+    removeGuest,
     startMeeting,
+    startMeetingEverywhere,
     getMeetingsForDate,
     changeDate,
     previousMonth,
     nextMonth,
     goToToday,
     setSelectedDate,
+    skipOccurrence,
+
+    // Agenda
+    getAgenda,
+    getWeekMeetings,
+
+    // Scheduling
+    checkConflicts,
+    suggestMeetingSlots,
+    getUserAvailability,
+    setUserAvailability,
+
+    // Tags
+    listMeetingTags,
+    createMeetingTag,
+    updateMeetingTag,
+    deleteMeetingTag,
+    setMeetingTag,
+    setMeetingColor,
+
+    // Stats
+    getMeetingStats,
+
+    // iCalendar
+    exportMeetingIcs,
+    exportCalendarIcs,
+    importIcs,
 
     // Google Calendar
     connectGoogle,
     disconnectGoogle,
     syncWithGoogle,
+    listGoogleCalendars,
+    setGoogleCalendars,
   };
 }
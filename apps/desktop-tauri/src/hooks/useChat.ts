@@ -1,7 +1,7 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
-import type { Conversation, Message, TeamMember, CreateGroupParams } from "../types/chat";
+import type { Conversation, Message, MessagePage, TeamMember, CreateGroupParams } from "../types/chat";
 
 export function useChat() {
   const [conversations, setConversations] = useState<Conversation[]>([]);
@@ -48,35 +48,36 @@ export function useChat() {
   }, []);
 
   // Load messages for a conversation
-  const loadMessages = useCallback(async (conversationId: string, limit?: number, before?: string) => {
+  const loadMessages = useCallback(async (conversationId: string, limit?: number, cursor?: string) => {
     try {
       setError(null);
-      const msgs = await invoke<Message[]>("get_messages", {
+      const page = await invoke<MessagePage>("get_messages", {
         conversationId,
         limit: limit || 50,
-        before,
+        cursor,
       });
+      const msgs = [...page.messages].reverse();
 
       setMessages((prev) => {
         const updated = new Map(prev);
         const existing = updated.get(conversationId) || [];
 
-        if (before) {
+        if (cursor) {
           // Prepend older messages
-          updated.set(conversationId, [...msgs.reverse(), ...existing]);
+          updated.set(conversationId, [...msgs, ...existing]);
         } else {
           // Replace with fresh messages
-          updated.set(conversationId, msgs.reverse());
+          updated.set(conversationId, msgs);
         }
 
         return updated;
       });
 
-      return msgs;
+      return page;
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
       console.error("Failed to load messages:", err);
-      return [];
+      return null;
     }
   }, []);
 
@@ -216,6 +217,60 @@ export function useChat() {
     }
   }, [loadConversations]);
 
+  // Promote a group member to admin, or demote an admin to member
+  const setGroupMemberRole = useCallback(
+    async (conversationId: string, userId: string, admin: boolean) => {
+      try {
+        setError(null);
+        await invoke(admin ? "promote_member" : "demote_member", {
+          conversationId,
+          userId,
+        });
+
+        // Refresh conversation to get updated roles
+        await loadConversations();
+      } catch (err) {
+        setError(err instanceof Error ? err.message : String(err));
+        console.error("Failed to change member role:", err);
+        throw err;
+      }
+    },
+    [loadConversations]
+  );
+
+  // Make another member the group owner
+  const transferGroupOwnership = useCallback(async (conversationId: string, userId: string) => {
+    try {
+      setError(null);
+      await invoke("transfer_group_ownership", {
+        conversationId,
+        userId,
+      });
+
+      // Refresh conversation to get the new owner
+      await loadConversations();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      console.error("Failed to transfer ownership:", err);
+      throw err;
+    }
+  }, [loadConversations]);
+
+  // Turn on end-to-end encryption for a conversation
+  const enableEncryption = useCallback(async (conversationId: string) => {
+    try {
+      setError(null);
+      await invoke("enable_conversation_encryption", { conversationId });
+
+      // Refresh conversation to get the encrypted flag
+      await loadConversations();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      console.error("Failed to enable encryption:", err);
+      throw err;
+    }
+  }, [loadConversations]);
+
   // Leave a group
   const leaveGroup = useCallback(async (conversationId: string) => {
     try {
@@ -250,6 +305,24 @@ export function useChat() {
     }
   }, []);
 
+  // Join a conversation's realtime channel while it is shown; each call
+  // is matched by closeConversation
+  const openConversation = useCallback(async (conversationId: string) => {
+    try {
+      await invoke("open_conversation", { conversationId });
+    } catch (err) {
+      console.error("Failed to open conversation:", err);
+    }
+  }, []);
+
+  const closeConversation = useCallback(async (conversationId: string) => {
+    try {
+      await invoke("close_conversation", { conversationId });
+    } catch (err) {
+      console.error("Failed to close conversation:", err);
+    }
+  }, []);
+
   // Get team members
   const getTeamMembers = useCallback(async () => {
     try {
@@ -272,6 +345,7 @@ export function useChat() {
   useEffect(() => {
     let unlistenNewMessage: UnlistenFn | undefined;
     let unlistenPresence: UnlistenFn | undefined;
+    let unlistenParticipants: UnlistenFn | undefined;
 
     const setupListeners = async () => {
       unlistenNewMessage = await listen<Message>("chat:new-message", (event) => {
@@ -309,6 +383,11 @@ export function useChat() {
         // Refresh conversations to get updated presence
         loadConversations();
       });
+
+      unlistenParticipants = await listen("chat:participants-changed", () => {
+        // Joined, left or changed a conversation, possibly from another client
+        loadConversations();
+      });
     };
 
     setupListeners();
@@ -316,6 +395,7 @@ export function useChat() {
     return () => {
       unlistenNewMessage?.();
       unlistenPresence?.();
+      unlistenParticipants?.();
     };
   }, [loadConversations]);
 
@@ -338,8 +418,13 @@ export function useChat() {
     updateGroup,
     addGroupMember,
     removeGroupMember,
+    setGroupMemberRole,
+    transferGroupOwnership,
+    enableEncryption,
     leaveGroup,
     markAsRead,
+    openConversation,
+    closeConversation,
     getTeamMembers,
     getConversationMessages,
   };
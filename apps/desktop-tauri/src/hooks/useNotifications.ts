@@ -0,0 +1,94 @@
+import { useState, useEffect, useCallback } from "react";
+import { useNavigate } from "react-router-dom";
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import type {
+  FrontendNotification,
+  NotificationAction,
+  NotificationSettings,
+} from "../types/notifications";
+
+export function useNotifications() {
+  const navigate = useNavigate();
+  const [settings, setSettings] = useState<NotificationSettings | null>(null);
+
+  // Load notification settings
+  const loadSettings = useCallback(async () => {
+    try {
+      const result = await invoke<NotificationSettings>("get_notification_settings");
+      setSettings(result);
+    } catch (err) {
+      console.error("Failed to load notification settings:", err);
+    }
+  }, []);
+
+  // Save notification settings
+  const updateSettings = useCallback(async (next: NotificationSettings) => {
+    try {
+      const result = await invoke<NotificationSettings>("set_notification_settings", {
+        settings: next,
+      });
+      setSettings(result);
+    } catch (err) {
+      console.error("Failed to save notification settings:", err);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadSettings();
+  }, [loadSettings]);
+
+  // Display notifications the backend hands over, and route clicks back to it
+  useEffect(() => {
+    let unlistenShow: UnlistenFn | undefined;
+    let unlistenAction: UnlistenFn | undefined;
+
+    const setupListeners = async () => {
+      if ("Notification" in window && Notification.permission === "default") {
+        await Notification.requestPermission();
+      }
+
+      unlistenShow = await listen<FrontendNotification>("notification:show", (event) => {
+        if (!("Notification" in window) || Notification.permission !== "granted") {
+          return;
+        }
+        const notification = new Notification(event.payload.title, {
+          body: event.payload.body,
+          requireInteraction: event.payload.urgent,
+        });
+        notification.onclick = () => {
+          invoke("handle_notification_action", { arguments: event.payload.launch }).catch(
+            (err) => console.error("Failed to act on notification:", err)
+          );
+          notification.close();
+        };
+      });
+
+      unlistenAction = await listen<NotificationAction>("notification:action", (event) => {
+        // Replies and meeting answers are already done; opening shows the subject
+        const action = event.payload;
+        if (action.action !== "open") {
+          return;
+        }
+        if (action.kind === "chat") {
+          navigate(`/chat/${action.conversation_id}`);
+        } else if (action.kind === "meeting_invite" || action.kind === "meeting_reminder") {
+          navigate("/calendar");
+        }
+      });
+    };
+
+    setupListeners();
+
+    return () => {
+      unlistenShow?.();
+      unlistenAction?.();
+    };
+  }, [navigate]);
+
+  return {
+    settings,
+    loadSettings,
+    updateSettings,
+  };
+}
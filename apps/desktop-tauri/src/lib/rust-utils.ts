@@ -29,16 +29,34 @@ export interface FormattedDateTime {
   is_past: boolean;
 }
 
+export interface ZonedDateTime {
+  time_zone: string;
+  local: string;
+  utc_offset: string;
+  abbreviation: string;
+  formatted: string;
+}
+
 // ==========================================
 // RRULE Types
 // ==========================================
 
 export type RecurrenceFrequency = "daily" | "weekly" | "monthly" | "yearly";
 
+// A day of the week counted within the month or year, like the second Tuesday
+export interface NthWeekday {
+  ordinal: number; // 1 for the first, -1 for the last
+  weekday: number; // 0-6 for Sunday-Saturday
+}
+
 export interface RecurrenceRule {
   frequency: RecurrenceFrequency;
   interval?: number;
   weekdays?: number[];
+  nth_weekdays?: NthWeekday[];
+  month_days?: number[]; // 1-31, or -1 for the last day
+  months?: number[]; // 1-12
+  set_positions?: number[]; // 1 for the first match, -1 for the last
   end_date?: string;
   count?: number;
 }
@@ -55,6 +73,12 @@ export interface RecurrenceOccurrence {
   occurrence_index: number;
 }
 
+// Dates removed from (EXDATE) and added to (RDATE) a recurrence
+export interface RecurrenceExceptions {
+  exdates: string[];
+  rdates: string[];
+}
+
 // ==========================================
 // Calendar Grid Types
 // ==========================================
@@ -172,6 +196,44 @@ export async function getMonthBounds(
   return invoke<[string, string]>("get_month_bounds", { year, month });
 }
 
+/**
+ * Show a datetime as it is in an IANA time zone
+ */
+export async function convertTimeZone(
+  datetime: string,
+  timeZone: string
+): Promise<ZonedDateTime> {
+  return invoke<ZonedDateTime>("convert_time_zone", {
+    datetime,
+    time_zone: timeZone,
+  });
+}
+
+/**
+ * Convert a wall-clock time ("2026-03-09T09:00:00") in an IANA time zone to UTC
+ */
+export async function zonedToUtc(
+  local: string,
+  timeZone: string
+): Promise<string> {
+  return invoke<string>("zoned_to_utc", { local, time_zone: timeZone });
+}
+
+/**
+ * Format a meeting time with duration in an IANA time zone
+ */
+export async function formatMeetingTimeInZone(
+  start: string,
+  durationMinutes: number,
+  timeZone: string
+): Promise<string> {
+  return invoke<string>("format_meeting_time_in_zone", {
+    start,
+    duration_minutes: durationMinutes,
+    time_zone: timeZone,
+  });
+}
+
 // ==========================================
 // RRULE Functions
 // ==========================================
@@ -205,7 +267,9 @@ export async function expandRRule(
   startDate: string,
   rangeStart: string,
   rangeEnd: string,
-  maxOccurrences?: number
+  maxOccurrences?: number,
+  exceptions?: RecurrenceExceptions,
+  timeZone?: string
 ): Promise<RecurrenceOccurrence[]> {
   return invoke<RecurrenceOccurrence[]>("expand_rrule", {
     rrule_str: rruleStr,
@@ -213,6 +277,8 @@ export async function expandRRule(
     range_start: rangeStart,
     range_end: rangeEnd,
     max_occurrences: maxOccurrences,
+    exceptions,
+    time_zone: timeZone,
   });
 }
 
@@ -229,15 +295,37 @@ export async function describeRRule(rule: RecurrenceRule): Promise<string> {
 export async function getNextOccurrence(
   rruleStr: string,
   startDate: string,
-  after: string
+  after: string,
+  exceptions?: RecurrenceExceptions,
+  timeZone?: string
 ): Promise<string | null> {
   return invoke<string | null>("get_next_occurrence", {
     rrule_str: rruleStr,
     start_date: startDate,
     after,
+    exceptions,
+    time_zone: timeZone,
   });
 }
 
+/**
+ * Build the EXDATE and RDATE lines of a recurrence
+ */
+export async function buildRecurrenceExceptions(
+  exceptions: RecurrenceExceptions
+): Promise<string> {
+  return invoke<string>("build_recurrence_exceptions", { exceptions });
+}
+
+/**
+ * Parse the EXDATE and RDATE lines of a recurrence
+ */
+export async function parseRecurrenceExceptions(
+  text: string
+): Promise<RecurrenceExceptions> {
+  return invoke<RecurrenceExceptions>("parse_recurrence_exceptions", { text });
+}
+
 // ==========================================
 // Calendar Grid Functions
 // ==========================================
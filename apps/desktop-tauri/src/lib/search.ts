@@ -73,6 +73,12 @@ export interface MeetingFilter {
   organizer_id?: string;
   /** Filter by title (case-insensitive search) */
   title_search?: string;
+  /** Filter by location or room (case-insensitive search) */
+  location_search?: string;
+  /** Only show meetings with (true) or without (false) a video call link */
+  online?: boolean;
+  /** Filter by meeting tag ID */
+  tag_id?: string;
   /** Only show meetings where I haven't responded yet */
   pending_response?: boolean;
 }
@@ -13,7 +13,32 @@ export interface Meeting {
   status: 'scheduled' | 'ongoing' | 'completed' | 'cancelled';
   session_id?: string;
   recurrence_rule?: string;
+  recurrence_exceptions: {
+    exdates: string[];
+    rdates: string[];
+  };
+  // IANA time zone, e.g. "America/Sao_Paulo"; UTC when unset
+  time_zone?: string;
+  // Set on later occurrences of a recurring meeting, whose id is then
+  // "<meeting id>_<YYYYMMDDTHHMMSSZ>"; act on this id instead
+  recurring_meeting_id?: string;
+  // Address or place the meeting is held at
+  location?: string;
+  // Video call link
+  meeting_url?: string;
+  room?: string;
+  // Session starts on its own at the scheduled time
+  auto_start: boolean;
+  // Google Calendar creates a Meet link, kept as meeting_url, on sync
+  google_meet: boolean;
+  // Join code of the session, once the meeting started
+  join_code?: string;
+  // Organizer's tag, whose color the meeting takes
+  tag_id?: string;
+  // "#rrggbb"
+  color?: string;
   google_event_id?: string;
+  outlook_event_id?: string;
   attendees: MeetingAttendee[];
   created_at?: string;
   updated_at?: string;
@@ -25,6 +50,60 @@ export interface MeetingAttendee {
   avatar_url?: string;
   response_status: 'invited' | 'accepted' | 'declined' | 'tentative';
   responded_at?: string;
+  attendance_type: AttendanceType;
+  rsvp_deadline?: string;
+}
+
+export type AttendanceType = 'required' | 'optional';
+
+// Colored tag the user categorizes their meetings with
+export interface MeetingTag {
+  id: string;
+  name: string;
+  // "#rrggbb"
+  color: string;
+  created_at?: string;
+}
+
+// Calendar health of the current user over a range. Cancelled meetings
+// and those the user declined don't count as time in meetings
+export interface MeetingStats {
+  meeting_count: number;
+  hours_in_meetings: number;
+  cancelled_count: number;
+  // Most meetings first
+  by_organizer: {
+    organizer_id: string;
+    organizer_name: string;
+    meeting_count: number;
+    hours: number;
+  }[];
+  // The user's answers to the meetings they were invited to
+  invitations: ResponseCounts;
+  acceptance_rate?: number;
+  // Answers of the attendees of the meetings the user organizes
+  attendee_responses: ResponseCounts;
+  attendance_rate?: number;
+  // Most hours first; date is YYYY-MM-DD
+  busiest_days: { date: string; meeting_count: number; hours: number }[];
+  // Monday (1) to Sunday (7)
+  by_weekday: { weekday: number; meeting_count: number; hours: number }[];
+}
+
+export interface ResponseCounts {
+  accepted: number;
+  tentative: number;
+  declined: number;
+  pending: number;
+}
+
+// External attendee invited by email, answering through the email's links
+export interface MeetingGuest {
+  id: string;
+  email: string;
+  display_name?: string;
+  response_status: 'invited' | 'accepted' | 'declined' | 'tentative';
+  responded_at?: string;
 }
 
 export interface CreateMeetingParams {
@@ -33,7 +112,34 @@ export interface CreateMeetingParams {
   scheduled_at: string;
   duration_minutes: number;
   attendee_ids: string[];
+  // Attendees of attendee_ids who are optional
+  optional_attendee_ids?: string[];
+  rsvp_deadline?: string;
   recurrence_rule?: string;
+  time_zone?: string;
+  location?: string;
+  meeting_url?: string;
+  room?: string;
+  auto_start?: boolean;
+  google_meet?: boolean;
+}
+
+// Payload of "meeting:starting", sent when a meeting's session started
+export interface MeetingStarting {
+  meeting_id: string;
+  title: string;
+  session_id: string;
+  join_code: string;
+  // Video call link, e.g. Google Meet, for attendees who can't join the session
+  meeting_url?: string;
+}
+
+export interface IcsImportResult {
+  meetings: Meeting[];
+  // Events already in the calendar, cancelled, or unreadable
+  skipped: number;
+  // Attendee addresses that don't match an app user
+  unmatched_attendees: string[];
 }
 
 export interface UpdateMeetingParams {
@@ -41,6 +147,71 @@ export interface UpdateMeetingParams {
   description?: string;
   scheduled_at?: string;
   duration_minutes?: number;
+  // An empty string resets it to UTC
+  time_zone?: string;
+  // An empty string clears them
+  location?: string;
+  meeting_url?: string;
+  room?: string;
+  auto_start?: boolean;
+  google_meet?: boolean;
+}
+
+// A participant already busy during a proposed meeting, or held outside
+// their working hours by it
+export interface MeetingConflict {
+  user_id: string;
+  display_name: string;
+  starts_at: string;
+  ends_at: string;
+  source: 'meeting' | 'google' | 'outside_working_hours';
+}
+
+// When a user works, in their time zone
+export interface WorkingHours {
+  user_id: string;
+  // IANA time zone; UTC when unset
+  time_zone?: string;
+  day_start_hour: number;
+  day_end_hour: number;
+  // ISO weekdays, Monday = 1 to Sunday = 7
+  working_days: number[];
+}
+
+export type AvailabilityParams = Omit<WorkingHours, 'user_id'>;
+
+export interface SuggestMeetingSlotsParams {
+  attendee_ids: string[];
+  duration_minutes: number;
+  range_start: string;
+  range_end: string;
+  // IANA time zone of the working hours; unset fields default to the
+  // organizer's working hours, then UTC 9-18
+  time_zone?: string;
+  day_start_hour?: number;
+  day_end_hour?: number;
+  step_minutes?: number;    // Default 30
+  include_weekends?: boolean;
+  max_results?: number;     // Default 10
+  // Meeting being rescheduled, whose own time isn't busy
+  exclude_meeting_id?: string;
+  include_google?: boolean;
+}
+
+export interface SlotSuggestion {
+  starts_at: string;
+  ends_at: string;
+}
+
+// A meeting occurrence on the agenda
+export interface AgendaMeeting extends Meeting {
+  starts_at: string;
+  ends_at: string;
+}
+
+export interface AgendaDay {
+  date: string;
+  items: AgendaMeeting[];
 }
 
 export type RecurrenceFrequency = 'daily' | 'weekly' | 'monthly';
@@ -57,6 +228,51 @@ export interface GoogleCalendarStatus {
   connected: boolean;
   email?: string;
   sync_enabled: boolean;
+  // Calendar meetings are synced to
+  calendar_id?: string;
+  // Calendars imports read from besides calendar_id
+  selected_calendar_ids?: string[];
+}
+
+// A calendar of the user's Google account
+export interface GoogleCalendarInfo {
+  id: string;
+  summary: string;
+  primary: boolean;
+  access_role: 'owner' | 'writer' | 'reader' | 'freeBusyReader';
+  background_color?: string;
+  // Meetings are synced to it
+  sync_target: boolean;
+  // Imports and free/busy checks read from it
+  selected: boolean;
+}
+
+export type GoogleConflictPolicy = 'latest_wins' | 'prefer_local' | 'prefer_remote';
+
+export interface GoogleSyncResult {
+  created_remote: number;
+  updated_remote: number;
+  deleted_remote: number;
+  created_local: number;
+  updated_local: number;
+  cancelled_local: number;
+  conflicts: number;
+  failed: number;
+}
+
+// Emitted as "calendar:google-sync" whenever it changes
+export interface GoogleSyncStatus {
+  running: boolean;
+  last_synced_at?: string;
+  last_error?: string;
+  last_result?: GoogleSyncResult;
+  conflict_policy: GoogleConflictPolicy;
+}
+
+export interface OutlookCalendarStatus {
+  connected: boolean;
+  email?: string;
+  sync_enabled: boolean;
 }
 
 export interface CalendarState {
@@ -13,6 +13,7 @@ export interface Conversation {
   participants: Participant[];
   last_message?: Message;
   unread_count: number;
+  encrypted?: boolean;
 }
 
 export interface Participant {
@@ -34,6 +35,13 @@ export interface Message {
   created_at?: string;
 }
 
+export interface MessagePage {
+  messages: Message[];
+  next_cursor?: string;
+  prev_cursor?: string;
+  has_more: boolean;
+}
+
 export interface TeamMember {
   user_id: string;
   display_name: string;
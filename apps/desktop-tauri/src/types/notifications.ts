@@ -0,0 +1,42 @@
+// ==========================================
+// Notification Types
+// ==========================================
+
+export type NotificationKind =
+  | { kind: 'chat'; conversation_id: string }
+  | { kind: 'meeting_invite'; meeting_id: string }
+  | { kind: 'meeting_reminder'; meeting_id: string }
+  | { kind: 'control_request'; user_id: string }
+  | { kind: 'general' };
+
+export type AppNotification = NotificationKind & {
+  title: string;
+  body: string;
+  urgent: boolean;
+};
+
+// Notification the backend asks the frontend to display
+export type FrontendNotification = AppNotification & {
+  // Argument for handle_notification_action when clicked
+  launch: string;
+};
+
+export type NotificationAction =
+  | { action: 'reply'; conversation_id: string; text: string }
+  | { action: 'accept_meeting'; meeting_id: string }
+  | { action: 'decline_meeting'; meeting_id: string }
+  | ({ action: 'open' } & NotificationKind);
+
+export interface QuietHours {
+  // Local times as HH:MM; an end before the start runs past midnight
+  start: string;
+  end: string;
+}
+
+export interface NotificationSettings {
+  messages: boolean;
+  mentions: boolean;
+  meeting_reminders: boolean;
+  control_requests: boolean;
+  quiet_hours?: QuietHours | null;
+}
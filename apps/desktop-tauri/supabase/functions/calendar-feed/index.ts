@@ -0,0 +1,156 @@
+import { serve } from "https://deno.land/std@0.168.0/http/server.ts";
+import { createClient } from "https://esm.sh/@supabase/supabase-js@2";
+
+const SUPABASE_URL = Deno.env.get("SUPABASE_URL")!;
+const SUPABASE_SERVICE_ROLE_KEY = Deno.env.get("SUPABASE_SERVICE_ROLE_KEY")!;
+const APP_URL = Deno.env.get("APP_URL") || "https://app.squadx.live";
+
+// Only recent history is exported to keep the feed small
+const PAST_DAYS = 90;
+
+interface Meeting {
+  id: string;
+  title: string;
+  description?: string;
+  scheduled_at: string;
+  duration_minutes: number;
+  status: string;
+  recurrence_rule?: string;
+  updated_at?: string;
+}
+
+// RFC 5545 TEXT escaping
+function escapeText(value: string): string {
+  return value
+    .replace(/\\/g, "\\\\")
+    .replace(/;/g, "\\;")
+    .replace(/,/g, "\\,")
+    .replace(/\r?\n/g, "\\n");
+}
+
+// RFC 5545 lines are folded at 75 octets
+function foldLine(line: string): string {
+  const bytes = new TextEncoder().encode(line);
+  if (bytes.length <= 75) return line;
+
+  const parts: string[] = [];
+  let current = "";
+  for (const char of line) {
+    const limit = parts.length === 0 ? 75 : 74;
+    if (new TextEncoder().encode(current + char).length > limit) {
+      parts.push(current);
+      current = "";
+    }
+    current += char;
+  }
+  parts.push(current);
+  return parts.join("\r\n ");
+}
+
+function formatDate(date: Date): string {
+  return date.toISOString().replace(/[-:]/g, "").replace(/\.\d{3}/, "");
+}
+
+function buildEvent(meeting: Meeting): string[] {
+  const start = new Date(meeting.scheduled_at);
+  const end = new Date(start.getTime() + (meeting.duration_minutes || 30) * 60 * 1000);
+
+  const lines = [
+    "BEGIN:VEVENT",
+    `UID:${meeting.id}@squadx.live`,
+    `DTSTAMP:${formatDate(new Date(meeting.updated_at || Date.now()))}`,
+    `DTSTART:${formatDate(start)}`,
+    `DTEND:${formatDate(end)}`,
+    `SUMMARY:${escapeText(meeting.title)}`,
+    `URL:${APP_URL}/calendar?meeting=${meeting.id}`,
+    `STATUS:${meeting.status === "cancelled" ? "CANCELLED" : "CONFIRMED"}`,
+  ];
+  if (meeting.description) {
+    lines.push(`DESCRIPTION:${escapeText(meeting.description)}`);
+  }
+  if (meeting.recurrence_rule) {
+    lines.push(`RRULE:${meeting.recurrence_rule.replace(/^RRULE:/, "")}`);
+  }
+  lines.push("END:VEVENT");
+  return lines;
+}
+
+serve(async (req) => {
+  try {
+    const token = new URL(req.url).searchParams.get("token");
+    if (!token) {
+      return new Response("Missing token", { status: 401 });
+    }
+
+    const supabase = createClient(SUPABASE_URL, SUPABASE_SERVICE_ROLE_KEY);
+
+    // Resolve the feed token to its owner
+    const { data: feed, error: feedError } = await supabase
+      .from("calendar_feed_tokens")
+      .select("user_id")
+      .eq("token", token)
+      .single();
+
+    if (feedError || !feed) {
+      return new Response("Invalid token", { status: 404 });
+    }
+
+    const since = new Date(Date.now() - PAST_DAYS * 24 * 60 * 60 * 1000).toISOString();
+
+    // Meetings the user organizes or was invited to (and did not decline)
+    const { data: attendance } = await supabase
+      .from("meeting_attendees")
+      .select("meeting_id")
+      .eq("user_id", feed.user_id)
+      .neq("response_status", "declined");
+
+    const meetingIds = (attendance || []).map((a: { meeting_id: string }) => a.meeting_id);
+    const filter = meetingIds.length > 0
+      ? `organizer_id.eq.${feed.user_id},id.in.(${meetingIds.join(",")})`
+      : `organizer_id.eq.${feed.user_id}`;
+
+    const { data: meetings, error: meetingsError } = await supabase
+      .from("meetings")
+      .select("id, title, description, scheduled_at, duration_minutes, status, recurrence_rule, updated_at")
+      .or(filter)
+      .or(`scheduled_at.gte.${since},recurrence_rule.not.is.null`)
+      .order("scheduled_at", { ascending: true });
+
+    if (meetingsError) {
+      throw new Error(`Failed to load meetings: ${meetingsError.message}`);
+    }
+
+    await supabase
+      .from("calendar_feed_tokens")
+      .update({ last_accessed_at: new Date().toISOString() })
+      .eq("user_id", feed.user_id);
+
+    const lines = [
+      "BEGIN:VCALENDAR",
+      "VERSION:2.0",
+      "PRODID:-//SquadX Live//Calendar Feed//EN",
+      "CALSCALE:GREGORIAN",
+      "METHOD:PUBLISH",
+      "X-WR-CALNAME:SquadX Live",
+      "REFRESH-INTERVAL;VALUE=DURATION:PT15M",
+      "X-PUBLISHED-TTL:PT15M",
+      ...(meetings || []).flatMap((m: Meeting) => buildEvent(m)),
+      "END:VCALENDAR",
+    ];
+
+    return new Response(lines.map(foldLine).join("\r\n") + "\r\n", {
+      headers: {
+        "Content-Type": "text/calendar; charset=utf-8",
+        "Content-Disposition": "inline; filename=\"squadx-live.ics\"",
+        "Cache-Control": "private, max-age=300",
+      },
+    });
+
+  } catch (error) {
+    console.error("Calendar feed error:", error);
+    return new Response(JSON.stringify({ success: false, error: error.message }), {
+      status: 500,
+      headers: { "Content-Type": "application/json" },
+    });
+  }
+});
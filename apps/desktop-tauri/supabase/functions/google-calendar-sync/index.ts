@@ -1,11 +1,25 @@
 import { serve } from "https://deno.land/std@0.168.0/http/server.ts";
 import { createClient } from "https://esm.sh/@supabase/supabase-js@2";
+import {
+  decode as decodeBase64,
+  encode as encodeBase64,
+} from "https://deno.land/std@0.168.0/encoding/base64.ts";
+import { xchacha20poly1305 } from "https://esm.sh/@noble/ciphers@0.5.3/chacha";
 
 const SUPABASE_URL = Deno.env.get("SUPABASE_URL")!;
 const SUPABASE_SERVICE_ROLE_KEY = Deno.env.get("SUPABASE_SERVICE_ROLE_KEY")!;
 const GOOGLE_CLIENT_ID = Deno.env.get("GOOGLE_CLIENT_ID");
 const GOOGLE_CLIENT_SECRET = Deno.env.get("GOOGLE_CLIENT_SECRET");
 
+// Tokens sealed by the app: "tok.v1:" then base64 of the nonce and the
+// XChaCha20-Poly1305 ciphertext, under the key the app escrowed in
+// user_google_token_keys, with the user ID as associated data. Values
+// without the prefix were stored before encryption
+const SEALED_PREFIX = "tok.v1:";
+const NONCE_BYTES = 24;
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
 interface GoogleTokens {
   user_id: string;
   access_token: string;
@@ -14,6 +28,11 @@ interface GoogleTokens {
   email?: string;
 }
 
+// Tokens with the key they are sealed under, if any
+interface OpenedTokens extends GoogleTokens {
+  key: Uint8Array | null;
+}
+
 interface GoogleEvent {
   id: string;
   summary: string;
@@ -63,6 +82,52 @@ serve(async (req) => {
   }
 });
 
+async function getTokenKey(supabase: any, userId: string): Promise<Uint8Array | null> {
+  const { data, error } = await supabase
+    .from("user_google_token_keys")
+    .select("token_key")
+    .eq("user_id", userId)
+    .maybeSingle();
+
+  if (error) {
+    throw new Error(`Failed to fetch token key: ${error.message}`);
+  }
+  return data ? decodeBase64(data.token_key) : null;
+}
+
+function openToken(key: Uint8Array | null, userId: string, value: string): string {
+  if (!value.startsWith(SEALED_PREFIX)) {
+    return value;
+  }
+  if (!key) {
+    throw new Error("Tokens are sealed but no key was escrowed");
+  }
+  const sealed = decodeBase64(value.slice(SEALED_PREFIX.length));
+  const cipher = xchacha20poly1305(key, sealed.slice(0, NONCE_BYTES), encoder.encode(userId));
+  return decoder.decode(cipher.decrypt(sealed.slice(NONCE_BYTES)));
+}
+
+function sealToken(key: Uint8Array, userId: string, token: string): string {
+  const nonce = crypto.getRandomValues(new Uint8Array(NONCE_BYTES));
+  const cipher = xchacha20poly1305(key, nonce, encoder.encode(userId));
+  const ciphertext = cipher.encrypt(encoder.encode(token));
+  const sealed = new Uint8Array(nonce.length + ciphertext.length);
+  sealed.set(nonce);
+  sealed.set(ciphertext, nonce.length);
+  return SEALED_PREFIX + encodeBase64(sealed);
+}
+
+// Open a user's stored tokens
+async function openTokens(supabase: any, tokens: GoogleTokens): Promise<OpenedTokens> {
+  const key = await getTokenKey(supabase, tokens.user_id);
+  return {
+    ...tokens,
+    access_token: openToken(key, tokens.user_id, tokens.access_token),
+    refresh_token: openToken(key, tokens.user_id, tokens.refresh_token),
+    key,
+  };
+}
+
 // Refresh tokens that are expired or expiring soon
 async function handleRefreshTokens(supabase: any) {
   const now = new Date();
@@ -90,7 +155,7 @@ async function handleRefreshTokens(supabase: any) {
 
   for (const tokens of expiringTokens as GoogleTokens[]) {
     try {
-      await refreshToken(supabase, tokens);
+      await refreshToken(supabase, await openTokens(supabase, tokens));
       refreshed++;
     } catch (err) {
       console.error(`Failed to refresh token for user ${tokens.user_id}:`, err);
@@ -103,8 +168,8 @@ async function handleRefreshTokens(supabase: any) {
   });
 }
 
-// Refresh a single user's token
-async function refreshToken(supabase: any, tokens: GoogleTokens) {
+// Refresh a single user's token, returning the new access token
+async function refreshToken(supabase: any, tokens: OpenedTokens): Promise<string> {
   if (!GOOGLE_CLIENT_ID || !GOOGLE_CLIENT_SECRET) {
     throw new Error("Google credentials not configured");
   }
@@ -131,11 +196,15 @@ async function refreshToken(supabase: any, tokens: GoogleTokens) {
   await supabase
     .from("user_google_tokens")
     .update({
-      access_token: data.access_token,
+      access_token: tokens.key
+        ? sealToken(tokens.key, tokens.user_id, data.access_token)
+        : data.access_token,
       expires_at: newExpiresAt.toISOString(),
       updated_at: new Date().toISOString(),
     })
     .eq("user_id", tokens.user_id);
+
+  return data.access_token;
 }
 
 // Sync all meetings for all connected users
@@ -158,28 +227,21 @@ async function handleSyncAll(supabase: any) {
   let synced = 0;
   let failed = 0;
 
-  for (const tokens of allTokens as GoogleTokens[]) {
+  for (const row of allTokens as GoogleTokens[]) {
     try {
+      const tokens = await openTokens(supabase, row);
+
       // Refresh token if needed
       const now = new Date();
       const expiresAt = new Date(tokens.expires_at);
       if (expiresAt < new Date(now.getTime() + 5 * 60 * 1000)) {
-        await refreshToken(supabase, tokens);
-        // Re-fetch the updated token
-        const { data: updated } = await supabase
-          .from("user_google_tokens")
-          .select("access_token")
-          .eq("user_id", tokens.user_id)
-          .single();
-        if (updated) {
-          tokens.access_token = updated.access_token;
-        }
+        tokens.access_token = await refreshToken(supabase, tokens);
       }
 
       await syncUserMeetings(supabase, tokens);
       synced++;
     } catch (err) {
-      console.error(`Failed to sync for user ${tokens.user_id}:`, err);
+      console.error(`Failed to sync for user ${row.user_id}:`, err);
       failed++;
     }
   }
@@ -297,13 +359,13 @@ async function handleWebhook(req: Request, supabase: any) {
   }
 
   // Get the user's tokens
-  const { data: tokens, error } = await supabase
+  const { data: row, error } = await supabase
     .from("user_google_tokens")
     .select("*")
     .eq("user_id", userId)
     .single();
 
-  if (error || !tokens) {
+  if (error || !row) {
     return new Response(JSON.stringify({ error: "User tokens not found" }), {
       status: 404,
       headers: { "Content-Type": "application/json" },
@@ -312,18 +374,11 @@ async function handleWebhook(req: Request, supabase: any) {
 
   // Refresh token if needed and sync
   try {
+    const tokens = await openTokens(supabase, row);
     const now = new Date();
     const expiresAt = new Date(tokens.expires_at);
     if (expiresAt < new Date(now.getTime() + 5 * 60 * 1000)) {
-      await refreshToken(supabase, tokens);
-      const { data: updated } = await supabase
-        .from("user_google_tokens")
-        .select("access_token")
-        .eq("user_id", tokens.user_id)
-        .single();
-      if (updated) {
-        tokens.access_token = updated.access_token;
-      }
+      tokens.access_token = await refreshToken(supabase, tokens);
     }
 
     await syncUserMeetings(supabase, tokens);
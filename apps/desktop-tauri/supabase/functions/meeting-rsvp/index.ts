@@ -0,0 +1,102 @@
+import { serve } from "https://deno.land/std@0.168.0/http/server.ts";
+import { createClient } from "https://esm.sh/@supabase/supabase-js@2";
+
+const SUPABASE_URL = Deno.env.get("SUPABASE_URL")!;
+const SUPABASE_SERVICE_ROLE_KEY = Deno.env.get("SUPABASE_SERVICE_ROLE_KEY")!;
+
+const RESPONSES: Record<string, string> = {
+  accepted: "Presença confirmada",
+  tentative: "Resposta registrada como talvez",
+  declined: "Convite recusado",
+};
+
+interface GuestResponse {
+  meeting_id: string;
+  title: string;
+  scheduled_at: string;
+  response_status: string;
+}
+
+function escapeHtml(value: string): string {
+  return value
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;");
+}
+
+function page(status: number, heading: string, message: string): Response {
+  const html = `
+<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>${escapeHtml(heading)}</title>
+  <style>
+    body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; line-height: 1.6; color: #333; background: #f5f5f5; }
+    .container { max-width: 480px; margin: 60px auto; padding: 30px; background: white; border-radius: 10px; text-align: center; }
+    h1 { font-size: 22px; }
+    .footer { margin-top: 20px; color: #666; font-size: 12px; }
+  </style>
+</head>
+<body>
+  <div class="container">
+    <h1>${escapeHtml(heading)}</h1>
+    <p>${message}</p>
+    <div class="footer">SquadX Live</div>
+  </div>
+</body>
+</html>
+  `;
+  return new Response(html, {
+    status,
+    headers: { "Content-Type": "text/html; charset=utf-8" },
+  });
+}
+
+serve(async (req) => {
+  try {
+    const params = new URL(req.url).searchParams;
+    const token = params.get("token");
+    const response = params.get("response");
+
+    if (!token) {
+      return page(401, "Link inválido", "O link de resposta está incompleto.");
+    }
+    if (!response || !(response in RESPONSES)) {
+      return page(400, "Resposta inválida", "Use um dos botões do convite para responder.");
+    }
+
+    const supabase = createClient(SUPABASE_URL, SUPABASE_SERVICE_ROLE_KEY);
+
+    const { data, error } = await supabase.rpc("respond_to_guest_invite", {
+      p_token: token,
+      p_response: response,
+    });
+
+    if (error) {
+      throw new Error(`Failed to record response: ${error.message}`);
+    }
+
+    const answered = (data as GuestResponse[] | null)?.[0];
+    if (!answered) {
+      return page(404, "Convite não encontrado", "Este convite não existe mais ou a reunião foi cancelada.");
+    }
+
+    const when = new Date(answered.scheduled_at).toLocaleString("pt-BR", {
+      dateStyle: "full",
+      timeStyle: "short",
+    });
+
+    return page(
+      200,
+      RESPONSES[answered.response_status],
+      `<strong>${escapeHtml(answered.title)}</strong><br>${escapeHtml(when)}`,
+    );
+
+  } catch (error) {
+    console.error("Meeting RSVP error:", error);
+    return page(500, "Erro", "Não foi possível registrar sua resposta. Tente novamente mais tarde.");
+  }
+});
@@ -8,7 +8,9 @@ const SEND_NOTIFICATION_URL = `${SUPABASE_URL}/functions/v1/send-meeting-notific
 interface NotificationQueueItem {
   id: string;
   meeting_id: string;
-  user_id: string;
+  user_id: string | null;
+  // Set instead of user_id for external guests invited by email
+  guest_id: string | null;
   notification_type: "invite" | "reminder" | "update" | "cancel";
   scheduled_for: string;
   status: string;
@@ -59,6 +61,7 @@ serve(async (req) => {
           body: JSON.stringify({
             meeting_id: notification.meeting_id,
             user_id: notification.user_id,
+            guest_id: notification.guest_id,
             notification_type: notification.notification_type,
           }),
         });
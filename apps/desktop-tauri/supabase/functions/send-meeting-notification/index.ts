@@ -4,10 +4,13 @@ import { createClient } from "https://esm.sh/@supabase/supabase-js@2";
 const RESEND_API_KEY = Deno.env.get("RESEND_API_KEY");
 const EMAIL_FROM = Deno.env.get("EMAIL_FROM") || "reunioes@squadx.live";
 const APP_URL = Deno.env.get("APP_URL") || "https://app.squadx.live";
+const SUPABASE_URL = Deno.env.get("SUPABASE_URL")!;
 
+// Either a user or an external guest invited by email
 interface NotificationPayload {
   meeting_id: string;
-  user_id: string;
+  user_id?: string;
+  guest_id?: string;
   notification_type: "invite" | "reminder" | "update" | "cancel";
 }
 
@@ -18,6 +21,15 @@ interface Meeting {
   scheduled_at: string;
   duration_minutes: number;
   organizer_id: string;
+  recurrence_rule?: string;
+  updated_at?: string;
+}
+
+interface Recipient {
+  email: string;
+  name: string;
+  // Link answering the invitation with the given response
+  rsvpUrl: (response: string) => string;
 }
 
 interface UserProfile {
@@ -29,12 +41,11 @@ interface UserProfile {
 serve(async (req) => {
   try {
     const payload: NotificationPayload = await req.json();
-    const { meeting_id, user_id, notification_type } = payload;
+    const { meeting_id, user_id, guest_id, notification_type } = payload;
 
     // Initialize Supabase client
-    const supabaseUrl = Deno.env.get("SUPABASE_URL")!;
     const supabaseKey = Deno.env.get("SUPABASE_SERVICE_ROLE_KEY")!;
-    const supabase = createClient(supabaseUrl, supabaseKey);
+    const supabase = createClient(SUPABASE_URL, supabaseKey);
 
     // Get meeting details
     const { data: meeting, error: meetingError } = await supabase
@@ -47,26 +58,18 @@ serve(async (req) => {
       throw new Error(`Meeting not found: ${meetingError?.message}`);
     }
 
-    // Get user profile with email from auth.users
-    const { data: authUser, error: authError } = await supabase.auth.admin.getUserById(user_id);
+    const recipient = guest_id
+      ? await getGuestRecipient(supabase, guest_id)
+      : await getUserRecipient(supabase, user_id!, meeting_id);
 
-    if (authError || !authUser?.user?.email) {
-      console.log(`User ${user_id} has no email, skipping notification`);
+    if (!recipient) {
+      console.log(`No email for ${guest_id ? `guest ${guest_id}` : `user ${user_id}`}, skipping notification`);
       return new Response(JSON.stringify({ success: true, skipped: true }), {
         headers: { "Content-Type": "application/json" },
       });
     }
 
-    const userEmail = authUser.user.email;
-
-    // Get user profile for display name
-    const { data: profile } = await supabase
-      .from("user_profiles")
-      .select("display_name")
-      .eq("user_id", user_id)
-      .single();
-
-    const userName = profile?.display_name || userEmail.split("@")[0];
+    const userName = recipient.name;
 
     // Get organizer name
     const { data: organizerProfile } = await supabase
@@ -97,7 +100,7 @@ serve(async (req) => {
     switch (notification_type) {
       case "invite":
         subject = `Convite: ${meeting.title}`;
-        htmlContent = buildInviteEmail(meeting, userName, organizerName, formattedDate, formattedTime);
+        htmlContent = buildInviteEmail(meeting, userName, organizerName, formattedDate, formattedTime, recipient.rsvpUrl);
         break;
       case "reminder":
         subject = `Lembrete: ${meeting.title} em 15 minutos`;
@@ -123,6 +126,16 @@ serve(async (req) => {
       });
     }
 
+    // Invitations, updates and cancellations carry the event for calendar apps
+    const attachments = notification_type === "reminder"
+      ? undefined
+      : [{
+        filename: notification_type === "cancel" ? "cancel.ics" : "invite.ics",
+        content: btoa(unescape(encodeURIComponent(
+          buildIcs(meeting, recipient, organizerName, notification_type === "cancel"),
+        ))),
+      }];
+
     const emailResponse = await fetch("https://api.resend.com/emails", {
       method: "POST",
       headers: {
@@ -131,9 +144,10 @@ serve(async (req) => {
       },
       body: JSON.stringify({
         from: EMAIL_FROM,
-        to: userEmail,
+        to: recipient.email,
         subject: subject,
         html: htmlContent,
+        attachments,
       }),
     });
 
@@ -155,7 +169,129 @@ serve(async (req) => {
   }
 });
 
-function buildInviteEmail(meeting: Meeting, userName: string, organizerName: string, date: string, time: string): string {
+// deno-lint-ignore no-explicit-any
+async function getUserRecipient(supabase: any, userId: string, meetingId: string): Promise<Recipient | null> {
+  // Get user email from auth.users
+  const { data: authUser, error: authError } = await supabase.auth.admin.getUserById(userId);
+  if (authError || !authUser?.user?.email) {
+    return null;
+  }
+  const email: string = authUser.user.email;
+
+  // Get user profile for display name
+  const { data: profile } = await supabase
+    .from("user_profiles")
+    .select("display_name")
+    .eq("user_id", userId)
+    .single();
+
+  return {
+    email,
+    name: profile?.display_name || email.split("@")[0],
+    rsvpUrl: (response) => `${APP_URL}/calendar?meeting=${meetingId}&response=${response}`,
+  };
+}
+
+// deno-lint-ignore no-explicit-any
+async function getGuestRecipient(supabase: any, guestId: string): Promise<Recipient | null> {
+  const { data: guest } = await supabase
+    .from("meeting_guests")
+    .select("email, display_name")
+    .eq("id", guestId)
+    .single();
+  const { data: guestToken } = await supabase
+    .from("meeting_guest_tokens")
+    .select("token")
+    .eq("guest_id", guestId)
+    .single();
+  if (!guest || !guestToken) {
+    return null;
+  }
+
+  return {
+    email: guest.email,
+    name: guest.display_name || guest.email.split("@")[0],
+    rsvpUrl: (response) =>
+      `${SUPABASE_URL}/functions/v1/meeting-rsvp?token=${guestToken.token}&response=${response}`,
+  };
+}
+
+// RFC 5545 TEXT escaping
+function escapeText(value: string): string {
+  return value
+    .replace(/\\/g, "\\\\")
+    .replace(/;/g, "\\;")
+    .replace(/,/g, "\\,")
+    .replace(/\r?\n/g, "\\n");
+}
+
+// RFC 5545 lines are folded at 75 octets
+function foldLine(line: string): string {
+  const bytes = new TextEncoder().encode(line);
+  if (bytes.length <= 75) return line;
+
+  const parts: string[] = [];
+  let current = "";
+  for (const char of line) {
+    const limit = parts.length === 0 ? 75 : 74;
+    if (new TextEncoder().encode(current + char).length > limit) {
+      parts.push(current);
+      current = "";
+    }
+    current += char;
+  }
+  parts.push(current);
+  return parts.join("\r\n ");
+}
+
+function formatIcsDate(date: Date): string {
+  return date.toISOString().replace(/[-:]/g, "").replace(/\.\d{3}/, "");
+}
+
+// iTIP request (or cancellation) of the meeting addressed to the recipient;
+// the sequence follows the last update so calendar apps replace the event
+function buildIcs(meeting: Meeting, recipient: Recipient, organizerName: string, cancelled: boolean): string {
+  const start = new Date(meeting.scheduled_at);
+  const end = new Date(start.getTime() + (meeting.duration_minutes || 30) * 60 * 1000);
+  const updated = new Date(meeting.updated_at || Date.now());
+
+  const lines = [
+    "BEGIN:VCALENDAR",
+    "VERSION:2.0",
+    "PRODID:-//SquadX Live//Meeting Invitations//EN",
+    "CALSCALE:GREGORIAN",
+    `METHOD:${cancelled ? "CANCEL" : "REQUEST"}`,
+    "BEGIN:VEVENT",
+    `UID:${meeting.id}@squadx.live`,
+    `SEQUENCE:${Math.floor(updated.getTime() / 1000)}`,
+    `DTSTAMP:${formatIcsDate(new Date())}`,
+    `DTSTART:${formatIcsDate(start)}`,
+    `DTEND:${formatIcsDate(end)}`,
+    `SUMMARY:${escapeText(meeting.title)}`,
+    `URL:${APP_URL}/calendar?meeting=${meeting.id}`,
+    `STATUS:${cancelled ? "CANCELLED" : "CONFIRMED"}`,
+    `ORGANIZER;CN="${organizerName.replace(/"/g, "'")}":mailto:${EMAIL_FROM}`,
+    `ATTENDEE;CN="${recipient.name.replace(/"/g, "'")}";ROLE=REQ-PARTICIPANT;RSVP=TRUE:mailto:${recipient.email}`,
+  ];
+  if (meeting.description) {
+    lines.push(`DESCRIPTION:${escapeText(meeting.description)}`);
+  }
+  if (meeting.recurrence_rule) {
+    lines.push(`RRULE:${meeting.recurrence_rule.replace(/^RRULE:/, "")}`);
+  }
+  lines.push("END:VEVENT", "END:VCALENDAR");
+
+  return lines.map(foldLine).join("\r\n") + "\r\n";
+}
+
+function buildInviteEmail(
+  meeting: Meeting,
+  userName: string,
+  organizerName: string,
+  date: string,
+  time: string,
+  rsvpUrl: (response: string) => string,
+): string {
   return `
     <!DOCTYPE html>
     <html>
@@ -193,9 +329,9 @@ function buildInviteEmail(meeting: Meeting, userName: string, organizerName: str
 
           <p>Voce vai participar?</p>
           <div>
-            <a href="${APP_URL}/calendar?meeting=${meeting.id}&response=accepted" class="btn btn-accept">Aceitar</a>
-            <a href="${APP_URL}/calendar?meeting=${meeting.id}&response=tentative" class="btn btn-tentative">Talvez</a>
-            <a href="${APP_URL}/calendar?meeting=${meeting.id}&response=declined" class="btn btn-decline">Recusar</a>
+            <a href="${rsvpUrl("accepted")}" class="btn btn-accept">Aceitar</a>
+            <a href="${rsvpUrl("tentative")}" class="btn btn-tentative">Talvez</a>
+            <a href="${rsvpUrl("declined")}" class="btn btn-decline">Recusar</a>
           </div>
         </div>
         <div class="footer">
@@ -0,0 +1,81 @@
+import { serve } from "https://deno.land/std@0.168.0/http/server.ts";
+import { createClient } from "https://esm.sh/@supabase/supabase-js@2";
+
+const SUPABASE_URL = Deno.env.get("SUPABASE_URL")!;
+const SUPABASE_ANON_KEY = Deno.env.get("SUPABASE_ANON_KEY")!;
+// Shared secret configured as `static-auth-secret` on the TURN server (coturn)
+const TURN_SECRET = Deno.env.get("TURN_SECRET");
+// Comma-separated list, e.g. "turn:turn.squadx.live:3478,turns:turn.squadx.live:5349"
+const TURN_URLS = (Deno.env.get("TURN_URLS") || "").split(",").map((u) => u.trim()).filter(Boolean);
+const TURN_TTL_SECONDS = parseInt(Deno.env.get("TURN_TTL_SECONDS") || "3600", 10);
+
+interface TurnCredentials {
+  urls: string[];
+  username: string;
+  credential: string;
+  ttl_seconds: number;
+}
+
+// TURN REST API: credential = base64(HMAC-SHA1(secret, username))
+async function hmacSha1Base64(secret: string, message: string): Promise<string> {
+  const key = await crypto.subtle.importKey(
+    "raw",
+    new TextEncoder().encode(secret),
+    { name: "HMAC", hash: "SHA-1" },
+    false,
+    ["sign"]
+  );
+  const signature = await crypto.subtle.sign("HMAC", key, new TextEncoder().encode(message));
+  return btoa(String.fromCharCode(...new Uint8Array(signature)));
+}
+
+serve(async (req) => {
+  try {
+    if (!TURN_SECRET || TURN_URLS.length === 0) {
+      throw new Error("TURN server is not configured");
+    }
+
+    // Only authenticated users get relay credentials
+    const authHeader = req.headers.get("Authorization");
+    if (!authHeader) {
+      return new Response(JSON.stringify({ success: false, error: "Missing authorization" }), {
+        status: 401,
+        headers: { "Content-Type": "application/json" },
+      });
+    }
+
+    const supabase = createClient(SUPABASE_URL, SUPABASE_ANON_KEY, {
+      global: { headers: { Authorization: authHeader } },
+    });
+    const { data: { user }, error: authError } = await supabase.auth.getUser();
+
+    if (authError || !user) {
+      return new Response(JSON.stringify({ success: false, error: "Invalid token" }), {
+        status: 401,
+        headers: { "Content-Type": "application/json" },
+      });
+    }
+
+    // Username embeds the expiry timestamp, the TURN server rejects it afterwards
+    const expiresAt = Math.floor(Date.now() / 1000) + TURN_TTL_SECONDS;
+    const username = `${expiresAt}:${user.id}`;
+
+    const credentials: TurnCredentials = {
+      urls: TURN_URLS,
+      username,
+      credential: await hmacSha1Base64(TURN_SECRET, username),
+      ttl_seconds: TURN_TTL_SECONDS,
+    };
+
+    return new Response(JSON.stringify(credentials), {
+      headers: { "Content-Type": "application/json" },
+    });
+
+  } catch (error) {
+    console.error("TURN credentials error:", error);
+    return new Response(JSON.stringify({ success: false, error: error.message }), {
+      status: 500,
+      headers: { "Content-Type": "application/json" },
+    });
+  }
+});
@@ -0,0 +1,64 @@
+-- =============================================
+-- SquadX Live Meeting Agenda - Database Schema
+-- =============================================
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Meeting Agenda Items Table
+CREATE TABLE IF NOT EXISTS meeting_agenda_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meeting_id UUID NOT NULL REFERENCES meetings(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    duration_minutes INT NOT NULL DEFAULT 5 CHECK (duration_minutes > 0),
+    presenter_id UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    position INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meeting_agenda_items_meeting ON meeting_agenda_items(meeting_id, position);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE meeting_agenda_items ENABLE ROW LEVEL SECURITY;
+
+-- Attendees and organizers can see the agenda
+CREATE POLICY "Users can view agenda of their meetings"
+    ON meeting_agenda_items FOR SELECT
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+-- Only organizers can manage the agenda
+CREATE POLICY "Organizers can manage agenda items"
+    ON meeting_agenda_items FOR ALL
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        )
+    );
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_update_agenda_item_timestamp ON meeting_agenda_items;
+CREATE TRIGGER trigger_update_agenda_item_timestamp
+    BEFORE UPDATE ON meeting_agenda_items
+    FOR EACH ROW
+    EXECUTE FUNCTION update_meeting_timestamp();
+
+-- =============================================
+-- End of Migration
+-- =============================================
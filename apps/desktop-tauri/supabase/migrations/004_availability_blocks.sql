@@ -0,0 +1,135 @@
+-- =============================================
+-- SquadX Live Availability Blocks - Database Schema
+-- =============================================
+-- Recurring "available for pairing" blocks (office hours)
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Availability Blocks Table
+CREATE TABLE IF NOT EXISTS availability_blocks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    title TEXT NOT NULL DEFAULT 'Office hours',
+    starts_at TIMESTAMPTZ NOT NULL,
+    duration_minutes INT NOT NULL CHECK (duration_minutes > 0),
+    slot_minutes INT NOT NULL DEFAULT 30 CHECK (slot_minutes > 0),
+    recurrence_rule TEXT NOT NULL,
+    is_active BOOLEAN DEFAULT TRUE,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- 2. Link booked meetings to the block they were booked in
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS availability_block_id UUID REFERENCES availability_blocks(id) ON DELETE SET NULL;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_availability_blocks_user ON availability_blocks(user_id);
+CREATE INDEX IF NOT EXISTS idx_meetings_availability_block ON meetings(availability_block_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE availability_blocks ENABLE ROW LEVEL SECURITY;
+
+-- Any authenticated teammate can see active blocks
+CREATE POLICY "Authenticated users can view active availability blocks"
+    ON availability_blocks FOR SELECT
+    USING (auth.uid() IS NOT NULL AND (is_active = TRUE OR user_id = auth.uid()));
+
+-- Users manage their own blocks
+CREATE POLICY "Users can manage their own availability blocks"
+    ON availability_blocks FOR ALL
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_update_availability_block_timestamp ON availability_blocks;
+CREATE TRIGGER trigger_update_availability_block_timestamp
+    BEFORE UPDATE ON availability_blocks
+    FOR EACH ROW
+    EXECUTE FUNCTION update_meeting_timestamp();
+
+-- Booked times inside a block, without exposing meeting details
+CREATE OR REPLACE FUNCTION get_availability_bookings(block_id UUID, range_start TIMESTAMPTZ, range_end TIMESTAMPTZ)
+RETURNS TABLE (scheduled_at TIMESTAMPTZ, duration_minutes INT) AS $$
+BEGIN
+    RETURN QUERY
+    SELECT m.scheduled_at, m.duration_minutes
+    FROM meetings m
+    WHERE m.availability_block_id = block_id
+    AND m.status <> 'cancelled'
+    AND m.scheduled_at < range_end
+    AND m.scheduled_at + make_interval(mins => m.duration_minutes) > range_start
+    ORDER BY m.scheduled_at;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Book a slot in someone's block: the meeting, its link to the block and
+-- the block owner's invite are created together, with the block locked so
+-- two bookings of the same slot can't both pass the overlap check. Whether
+-- the slot falls in an occurrence of the block is checked by the app,
+-- which expands the recurrence
+CREATE OR REPLACE FUNCTION book_availability_slot(
+    target_block_id UUID,
+    meeting_title TEXT,
+    meeting_description TEXT,
+    slot_start TIMESTAMPTZ,
+    slot_duration_minutes INT
+)
+RETURNS SETOF meetings AS $$
+DECLARE
+    block availability_blocks%ROWTYPE;
+    booked meetings%ROWTYPE;
+BEGIN
+    IF auth.uid() IS NULL THEN
+        RAISE SQLSTATE '42501' USING MESSAGE = 'Not authenticated';
+    END IF;
+
+    SELECT * INTO block
+    FROM availability_blocks
+    WHERE id = target_block_id AND is_active = TRUE
+    FOR UPDATE;
+
+    IF NOT FOUND THEN
+        RAISE SQLSTATE 'PT404' USING MESSAGE = 'Availability block not found';
+    END IF;
+    IF block.user_id = auth.uid() THEN
+        RAISE SQLSTATE 'PT400' USING MESSAGE = 'Cannot book your own availability block';
+    END IF;
+
+    IF EXISTS (
+        SELECT 1 FROM get_availability_bookings(
+            block.id,
+            slot_start,
+            slot_start + make_interval(mins => slot_duration_minutes)
+        )
+    ) THEN
+        RAISE SQLSTATE 'PT409' USING MESSAGE = 'Requested slot is already booked';
+    END IF;
+
+    INSERT INTO meetings (
+        organizer_id, title, description, scheduled_at, duration_minutes, availability_block_id
+    )
+    VALUES (
+        auth.uid(), meeting_title, meeting_description, slot_start, slot_duration_minutes, block.id
+    )
+    RETURNING * INTO booked;
+
+    INSERT INTO meeting_attendees (meeting_id, user_id)
+    VALUES (booked.id, block.user_id)
+    ON CONFLICT (meeting_id, user_id) DO NOTHING;
+
+    RETURN NEXT booked;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,122 @@
+-- =============================================
+-- SquadX Live Reminder Telemetry - Database Schema
+-- =============================================
+-- Tracks how long users take to join after a reminder
+-- so reminder offsets can be tuned per user
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Reminder Telemetry Table
+CREATE TABLE IF NOT EXISTS reminder_telemetry (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    meeting_id UUID NOT NULL REFERENCES meetings(id) ON DELETE CASCADE,
+    offset_minutes INT NOT NULL,
+    fired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    joined_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- 2. Reminder Preferences Table
+CREATE TABLE IF NOT EXISTS user_reminder_preferences (
+    user_id UUID PRIMARY KEY REFERENCES auth.users(id) ON DELETE CASCADE,
+    reminder_offset_minutes INT NOT NULL DEFAULT 15 CHECK (reminder_offset_minutes > 0),
+    auto_adjust BOOLEAN DEFAULT FALSE,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_reminder_telemetry_user ON reminder_telemetry(user_id, fired_at DESC);
+CREATE INDEX IF NOT EXISTS idx_reminder_telemetry_meeting ON reminder_telemetry(meeting_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE reminder_telemetry ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_reminder_preferences ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can manage their own reminder telemetry"
+    ON reminder_telemetry FOR ALL
+    USING (auth.uid() = user_id);
+
+CREATE POLICY "Users can manage their own reminder preferences"
+    ON user_reminder_preferences FOR ALL
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_update_reminder_preferences_timestamp ON user_reminder_preferences;
+CREATE TRIGGER trigger_update_reminder_preferences_timestamp
+    BEFORE UPDATE ON user_reminder_preferences
+    FOR EACH ROW
+    EXECUTE FUNCTION update_meeting_timestamp();
+
+-- A user's reminder offset, 15 minutes by default. The triggers below run
+-- as the organizer, who can't read attendees' preferences
+CREATE OR REPLACE FUNCTION user_reminder_offset(target_user_id UUID)
+RETURNS INT AS $$
+    SELECT COALESCE(
+        (SELECT reminder_offset_minutes FROM user_reminder_preferences WHERE user_id = target_user_id),
+        15
+    );
+$$ LANGUAGE sql STABLE SECURITY DEFINER;
+
+-- Queue reminders using each attendee's preferred offset
+CREATE OR REPLACE FUNCTION queue_attendee_invite()
+RETURNS TRIGGER AS $$
+DECLARE
+    meeting_time TIMESTAMPTZ;
+BEGIN
+    -- Get meeting time for reminder scheduling
+    SELECT scheduled_at INTO meeting_time FROM meetings WHERE id = NEW.meeting_id;
+
+    -- Queue invite notification (immediate)
+    INSERT INTO notification_queue (meeting_id, user_id, notification_type, scheduled_for, status)
+    VALUES (NEW.meeting_id, NEW.user_id, 'invite', NOW(), 'pending');
+
+    -- Queue reminder notification
+    INSERT INTO notification_queue (meeting_id, user_id, notification_type, scheduled_for, status)
+    VALUES (
+        NEW.meeting_id, NEW.user_id, 'reminder',
+        meeting_time - make_interval(mins => user_reminder_offset(NEW.user_id)), 'pending'
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Move pending reminders of a rescheduled meeting, keeping each
+-- attendee's offset
+CREATE OR REPLACE FUNCTION queue_meeting_update_notification()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Only if scheduled_at or title changed
+    IF OLD.scheduled_at != NEW.scheduled_at OR OLD.title != NEW.title THEN
+        INSERT INTO notification_queue (meeting_id, user_id, notification_type, scheduled_for, status)
+        SELECT NEW.id, user_id, 'update', NOW(), 'pending'
+        FROM meeting_attendees
+        WHERE meeting_id = NEW.id AND user_id != NEW.organizer_id;
+
+        -- Update reminder times
+        UPDATE notification_queue
+        SET scheduled_for = NEW.scheduled_at - make_interval(mins => user_reminder_offset(user_id)),
+            status = 'pending',
+            sent_at = NULL
+        WHERE meeting_id = NEW.id
+          AND notification_type = 'reminder'
+          AND status = 'pending';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,38 @@
+-- =============================================
+-- SquadX Live Bulk Meeting Operations
+-- =============================================
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Update several meetings in one call. Fields left NULL are kept;
+-- shift_minutes moves each meeting relative to its own start time.
+-- Runs with the caller's permissions, so RLS still restricts updates
+-- to meetings the caller organizes.
+CREATE OR REPLACE FUNCTION bulk_update_meetings(
+    meeting_ids UUID[],
+    shift_minutes INT DEFAULT NULL,
+    new_title TEXT DEFAULT NULL,
+    new_description TEXT DEFAULT NULL,
+    new_duration_minutes INT DEFAULT NULL
+)
+RETURNS SETOF meetings AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE meetings
+    SET
+        scheduled_at = scheduled_at + make_interval(mins => COALESCE(shift_minutes, 0)),
+        title = COALESCE(new_title, title),
+        description = COALESCE(new_description, description),
+        duration_minutes = COALESCE(new_duration_minutes, duration_minutes)
+    WHERE id = ANY(meeting_ids)
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY INVOKER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,181 @@
+-- =============================================
+-- SquadX Live Scheduling Polls - Database Schema
+-- =============================================
+-- Doodle-style polls posted in chat conversations
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Scheduling Polls Table
+CREATE TABLE IF NOT EXISTS scheduling_polls (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    conversation_id UUID NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    description TEXT,
+    duration_minutes INT NOT NULL DEFAULT 30 CHECK (duration_minutes > 0),
+    status TEXT DEFAULT 'open' CHECK (status IN ('open', 'closed')),
+    meeting_id UUID REFERENCES meetings(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    closed_at TIMESTAMPTZ
+);
+
+-- 2. Poll Options (candidate slots)
+CREATE TABLE IF NOT EXISTS scheduling_poll_options (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    poll_id UUID NOT NULL REFERENCES scheduling_polls(id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    position INT NOT NULL DEFAULT 0
+);
+
+-- 3. Poll Votes
+CREATE TABLE IF NOT EXISTS scheduling_poll_votes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    poll_id UUID NOT NULL REFERENCES scheduling_polls(id) ON DELETE CASCADE,
+    option_id UUID NOT NULL REFERENCES scheduling_poll_options(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(option_id, user_id)
+);
+
+-- 4. Allow poll messages in chat
+ALTER TABLE messages DROP CONSTRAINT IF EXISTS messages_message_type_check;
+ALTER TABLE messages ADD CONSTRAINT messages_message_type_check
+    CHECK (message_type IN ('text', 'system', 'poll'));
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_scheduling_polls_conversation ON scheduling_polls(conversation_id);
+CREATE INDEX IF NOT EXISTS idx_scheduling_poll_options_poll ON scheduling_poll_options(poll_id, position);
+CREATE INDEX IF NOT EXISTS idx_scheduling_poll_votes_poll ON scheduling_poll_votes(poll_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE scheduling_polls ENABLE ROW LEVEL SECURITY;
+ALTER TABLE scheduling_poll_options ENABLE ROW LEVEL SECURITY;
+ALTER TABLE scheduling_poll_votes ENABLE ROW LEVEL SECURITY;
+
+-- Polls: conversation participants can see and create, creators can update
+CREATE POLICY "Participants can view polls of their conversations"
+    ON scheduling_polls FOR SELECT
+    USING (
+        conversation_id IN (
+            SELECT conversation_id FROM conversation_participants WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Participants can create polls"
+    ON scheduling_polls FOR INSERT
+    WITH CHECK (
+        auth.uid() = created_by AND
+        conversation_id IN (
+            SELECT conversation_id FROM conversation_participants WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Creators can update their polls"
+    ON scheduling_polls FOR UPDATE
+    USING (auth.uid() = created_by);
+
+-- Options: visible with the poll, added by the poll creator
+CREATE POLICY "Participants can view poll options"
+    ON scheduling_poll_options FOR SELECT
+    USING (poll_id IN (SELECT id FROM scheduling_polls));
+
+CREATE POLICY "Creators can add poll options"
+    ON scheduling_poll_options FOR INSERT
+    WITH CHECK (
+        poll_id IN (SELECT id FROM scheduling_polls WHERE created_by = auth.uid())
+    );
+
+-- Votes: visible with the poll, users manage their own votes on open polls
+CREATE POLICY "Participants can view poll votes"
+    ON scheduling_poll_votes FOR SELECT
+    USING (poll_id IN (SELECT id FROM scheduling_polls));
+
+CREATE POLICY "Users can vote on open polls"
+    ON scheduling_poll_votes FOR INSERT
+    WITH CHECK (
+        auth.uid() = user_id AND
+        poll_id IN (SELECT id FROM scheduling_polls WHERE status = 'open')
+    );
+
+CREATE POLICY "Users can remove their own votes"
+    ON scheduling_poll_votes FOR DELETE
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Close a poll and schedule the meeting in the winning slot (most votes,
+-- earliest slot on ties). Runs as definer so the final voter can finalize
+-- on the creator's behalf; only the creator may close early. Returns the
+-- meeting id, or NULL when the poll had no votes or was already closed
+CREATE OR REPLACE FUNCTION finalize_scheduling_poll(target_poll_id UUID)
+RETURNS UUID AS $$
+DECLARE
+    poll scheduling_polls%ROWTYPE;
+    winner_starts_at TIMESTAMPTZ;
+    new_meeting_id UUID;
+BEGIN
+    SELECT * INTO poll FROM scheduling_polls WHERE id = target_poll_id FOR UPDATE;
+
+    IF NOT FOUND OR NOT EXISTS (
+        SELECT 1 FROM conversation_participants
+        WHERE conversation_id = poll.conversation_id AND user_id = auth.uid()
+    ) THEN
+        RAISE SQLSTATE 'PT404' USING MESSAGE = 'Poll not found';
+    END IF;
+
+    IF poll.status <> 'open' THEN
+        RETURN NULL;
+    END IF;
+
+    IF poll.created_by <> auth.uid() AND EXISTS (
+        SELECT 1 FROM conversation_participants cp
+        WHERE cp.conversation_id = poll.conversation_id
+        AND NOT EXISTS (
+            SELECT 1 FROM scheduling_poll_votes v
+            WHERE v.poll_id = poll.id AND v.user_id = cp.user_id
+        )
+    ) THEN
+        RAISE SQLSTATE 'PT403' USING MESSAGE = 'Only the poll creator can close it before everyone voted';
+    END IF;
+
+    SELECT o.starts_at INTO winner_starts_at
+    FROM scheduling_poll_options o
+    JOIN scheduling_poll_votes v ON v.option_id = o.id
+    WHERE o.poll_id = poll.id
+    GROUP BY o.id, o.starts_at
+    ORDER BY COUNT(DISTINCT v.user_id) DESC, o.starts_at ASC
+    LIMIT 1;
+
+    IF winner_starts_at IS NOT NULL THEN
+        -- Organizer is auto-added by trigger
+        INSERT INTO meetings (organizer_id, title, description, scheduled_at, duration_minutes)
+        VALUES (poll.created_by, poll.title, poll.description, winner_starts_at, poll.duration_minutes)
+        RETURNING id INTO new_meeting_id;
+
+        INSERT INTO meeting_attendees (meeting_id, user_id)
+        SELECT new_meeting_id, cp.user_id
+        FROM conversation_participants cp
+        WHERE cp.conversation_id = poll.conversation_id
+        AND cp.user_id <> poll.created_by
+        ON CONFLICT (meeting_id, user_id) DO NOTHING;
+    END IF;
+
+    UPDATE scheduling_polls
+    SET status = 'closed', closed_at = NOW(), meeting_id = new_meeting_id
+    WHERE id = poll.id;
+
+    RETURN new_meeting_id;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,48 @@
+-- =============================================
+-- SquadX Live Calendar Feeds - Database Schema
+-- =============================================
+-- Private tokens for read-only ICS subscription feeds
+-- (served by the calendar-feed edge function)
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Calendar Feed Tokens Table
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens (
+    user_id UUID PRIMARY KEY REFERENCES auth.users(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE CHECK (char_length(token) >= 32),
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    last_accessed_at TIMESTAMPTZ
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+-- token lookups are covered by the UNIQUE constraint index
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE calendar_feed_tokens ENABLE ROW LEVEL SECURITY;
+
+-- The edge function resolves tokens with the service role key,
+-- users only ever see and rotate their own token
+CREATE POLICY "Users can manage their own calendar feed token"
+    ON calendar_feed_tokens FOR ALL
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_update_calendar_feed_tokens_timestamp ON calendar_feed_tokens;
+CREATE TRIGGER trigger_update_calendar_feed_tokens_timestamp
+    BEFORE UPDATE ON calendar_feed_tokens
+    FOR EACH ROW
+    EXECUTE FUNCTION update_meeting_timestamp();
+
+-- =============================================
+-- End of Migration
+-- =============================================
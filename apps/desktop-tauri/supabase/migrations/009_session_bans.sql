@@ -0,0 +1,35 @@
+-- =============================================
+-- SquadX Live Session Bans
+-- =============================================
+-- Participants banned by the host can't join the session again
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Banned users per session
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS banned_user_ids UUID[] NOT NULL DEFAULT '{}';
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Add a user to a session's ban list, once. Runs with the caller's
+-- permissions, so RLS still restricts it to the session host.
+CREATE OR REPLACE FUNCTION ban_session_user(
+    target_session_id UUID,
+    target_user_id UUID
+)
+RETURNS SETOF sessions AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE sessions
+    SET banned_user_ids = array_append(banned_user_ids, target_user_id)
+    WHERE id = target_session_id
+      AND NOT (target_user_id = ANY(banned_user_ids))
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY INVOKER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,82 @@
+-- =============================================
+-- SquadX Live Session Co-hosts
+-- =============================================
+-- The host can name co-hosts and hand the session over, so it carries on
+-- when the host has to leave
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Co-hosts per session, and when the host left it to them
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS cohost_ids UUID[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS host_left_at TIMESTAMPTZ;
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Name or drop a co-host. Runs with the caller's permissions, so RLS still
+-- restricts it to the session host.
+CREATE OR REPLACE FUNCTION set_session_cohost(
+    target_session_id UUID,
+    target_user_id UUID,
+    make_cohost BOOLEAN
+)
+RETURNS SETOF sessions AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE sessions
+    SET cohost_ids = CASE
+        WHEN make_cohost AND NOT (target_user_id = ANY(cohost_ids))
+            THEN array_append(cohost_ids, target_user_id)
+        WHEN NOT make_cohost
+            THEN array_remove(cohost_ids, target_user_id)
+        ELSE cohost_ids
+    END
+    WHERE id = target_session_id
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY INVOKER;
+
+-- Record that the host left the session to its co-hosts. Runs with the
+-- caller's permissions, so RLS still restricts it to the session host.
+CREATE OR REPLACE FUNCTION mark_host_left(target_session_id UUID)
+RETURNS VOID AS $$
+BEGIN
+    UPDATE sessions
+    SET host_left_at = NOW()
+    WHERE id = target_session_id
+      AND host_id = auth.uid();
+END;
+$$ LANGUAGE plpgsql SECURITY INVOKER;
+
+-- Make another participant the host; the previous host becomes a co-host.
+-- The host may hand over to anyone, and a co-host may take over for itself
+-- once the host has recorded leaving, so this bypasses the host-only RLS
+-- policy.
+CREATE OR REPLACE FUNCTION transfer_session_host(
+    target_session_id UUID,
+    new_host_id UUID
+)
+RETURNS SETOF sessions AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE sessions
+    SET cohost_ids = array_append(array_remove(cohost_ids, new_host_id), host_id),
+        host_id = new_host_id,
+        host_left_at = NULL
+    WHERE id = target_session_id
+      AND host_id <> new_host_id
+      AND (
+          host_id = auth.uid()
+          OR (new_host_id = auth.uid()
+              AND auth.uid() = ANY(cohost_ids)
+              AND host_left_at IS NOT NULL)
+      )
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,26 @@
+-- =============================================
+-- SquadX Live Meeting Conversations
+-- =============================================
+-- Group conversations created from a meeting's attendees, linked both ways
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Meeting a conversation was created from
+ALTER TABLE conversations
+    ADD COLUMN IF NOT EXISTS meeting_id UUID REFERENCES meetings(id) ON DELETE SET NULL;
+
+-- 2. Conversation created for a meeting
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS conversation_id UUID REFERENCES conversations(id) ON DELETE SET NULL;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+-- A meeting gets at most one conversation
+CREATE UNIQUE INDEX IF NOT EXISTS idx_conversations_meeting ON conversations(meeting_id)
+    WHERE meeting_id IS NOT NULL;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,54 @@
+-- =============================================
+-- SquadX Live Session Chat History
+-- =============================================
+-- In-session chat is broadcast over the signaling channel; each sender
+-- also stores its messages so late joiners and post-mortems can read them
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Session Messages Table
+-- The ID is the one broadcast with the message, so retries don't duplicate it
+CREATE TABLE IF NOT EXISTS session_messages (
+    id UUID PRIMARY KEY,
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    sender_id UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    sender_name TEXT NOT NULL,
+    content TEXT NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_session_messages_session ON session_messages(session_id, sent_at DESC);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE session_messages ENABLE ROW LEVEL SECURITY;
+
+-- Whoever can see the session (its host and admitted viewers) can read its
+-- chat, except banned users
+CREATE POLICY "Users can view chat of sessions they may join"
+    ON session_messages FOR SELECT
+    USING (
+        auth.uid() IS NOT NULL AND
+        session_id IN (
+            SELECT id FROM sessions WHERE NOT (auth.uid() = ANY(banned_user_ids))
+        )
+    );
+
+CREATE POLICY "Users can store their own session messages"
+    ON session_messages FOR INSERT
+    WITH CHECK (
+        auth.uid() = sender_id AND
+        session_id IN (
+            SELECT id FROM sessions WHERE NOT (auth.uid() = ANY(banned_user_ids))
+        )
+    );
+
+-- =============================================
+-- End of Migration
+-- =============================================
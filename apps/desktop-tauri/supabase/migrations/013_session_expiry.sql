@@ -0,0 +1,75 @@
+-- =============================================
+-- SquadX Live Session Expiry
+-- =============================================
+-- Sessions carry an idle timeout and an optional hard lifetime, and the host
+-- keeps last_activity_at fresh while it is around, so sessions abandoned in
+-- the "active" state can be ended instead of accumulating forever
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Expiry columns
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS last_activity_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN IF NOT EXISTS idle_timeout_secs INTEGER NOT NULL DEFAULT 1800
+        CHECK (idle_timeout_secs >= 0),
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+-- 2. Index for finding stale active sessions
+CREATE INDEX IF NOT EXISTS idx_sessions_active_activity
+    ON sessions(last_activity_at)
+    WHERE status = 'active';
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- End the caller's active sessions that went idle for longer than their
+-- timeout (0 disables it) or outlived their expiry, returning them. Runs with
+-- the caller's permissions, so RLS still restricts it to sessions they host.
+CREATE OR REPLACE FUNCTION end_stale_sessions()
+RETURNS SETOF sessions AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE sessions
+    SET status = 'ended'
+    WHERE host_id = auth.uid()
+      AND status = 'active'
+      AND (
+          (idle_timeout_secs > 0
+              AND last_activity_at < NOW() - make_interval(secs => idle_timeout_secs))
+          OR expires_at <= NOW()
+      )
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY INVOKER;
+
+-- Same as before, and a co-host may also take over from a host whose
+-- activity heartbeat went stale, which is how a host that crashed or lost
+-- its connection without recording leaving shows
+CREATE OR REPLACE FUNCTION transfer_session_host(
+    target_session_id UUID,
+    new_host_id UUID
+)
+RETURNS SETOF sessions AS $$
+BEGIN
+    RETURN QUERY
+    UPDATE sessions
+    SET cohost_ids = array_append(array_remove(cohost_ids, new_host_id), host_id),
+        host_id = new_host_id,
+        host_left_at = NULL
+    WHERE id = target_session_id
+      AND host_id <> new_host_id
+      AND (
+          host_id = auth.uid()
+          OR (new_host_id = auth.uid()
+              AND auth.uid() = ANY(cohost_ids)
+              AND (host_left_at IS NOT NULL
+                  OR last_activity_at < NOW() - INTERVAL '3 minutes'))
+      )
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
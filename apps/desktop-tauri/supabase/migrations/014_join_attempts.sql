@@ -0,0 +1,133 @@
+-- =============================================
+-- SquadX Live Join Code Throttling
+-- =============================================
+-- Join codes are short enough to guess, so lookups by code go through a
+-- function that counts failures per user and IP address, delays attempts
+-- exponentially after a few and locks guessing out after too many. The
+-- open lookup policy on sessions is dropped so the function is the only way
+-- to find a session by its code; viewers the host admitted from the waiting
+-- room are recorded on the session and can read it, and its chat, after.
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Admitted viewers, kept across reconnects and app restarts
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS admitted_user_ids UUID[] NOT NULL DEFAULT '{}';
+
+-- 2. Join Attempts Table
+CREATE TABLE IF NOT EXISTS session_join_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES auth.users(id) ON DELETE CASCADE,
+    ip_address TEXT,
+    succeeded BOOLEAN NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_session_join_attempts_user ON session_join_attempts(user_id, attempted_at DESC);
+CREATE INDEX IF NOT EXISTS idx_session_join_attempts_ip ON session_join_attempts(ip_address, attempted_at DESC);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+-- No policies: attempts are only read and written by find_session_by_code
+ALTER TABLE session_join_attempts ENABLE ROW LEVEL SECURITY;
+
+-- Listing sessions by code directly would bypass the throttle
+DROP POLICY IF EXISTS "Anyone can lookup session by join code" ON sessions;
+
+-- Banned viewers stay admitted, so they still see the session to learn
+-- they were banned; its chat policies leave them out
+CREATE POLICY "Admitted viewers can view their sessions"
+    ON sessions FOR SELECT
+    USING (auth.uid() = ANY(admitted_user_ids));
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Look up an active session by join code. Failures in the last 15 minutes,
+-- by the caller or from their IP address, delay the next attempt by
+-- 2^(failures - 3) seconds from the third on, and 10 lock lookups out for
+-- 15 minutes. Throttled calls fail with HTTP 429 and the seconds left in the
+-- error details; users banned from the session fail with HTTP 403.
+CREATE OR REPLACE FUNCTION find_session_by_code(code TEXT)
+RETURNS SETOF sessions AS $$
+DECLARE
+    caller_ip TEXT := NULLIF(TRIM(split_part(
+        COALESCE(current_setting('request.headers', true)::json->>'x-forwarded-for', ''),
+        ',', 1
+    )), '');
+    failures INTEGER;
+    last_failure TIMESTAMPTZ;
+    retry_after INTEGER := 0;
+    found sessions%ROWTYPE;
+BEGIN
+    SELECT COUNT(*), MAX(attempted_at)
+    INTO failures, last_failure
+    FROM session_join_attempts
+    WHERE NOT succeeded
+      AND attempted_at > NOW() - INTERVAL '15 minutes'
+      AND (user_id = auth.uid() OR (caller_ip IS NOT NULL AND ip_address = caller_ip));
+
+    IF failures >= 10 THEN
+        retry_after := CEIL(EXTRACT(EPOCH FROM last_failure + INTERVAL '15 minutes' - NOW()));
+    ELSIF failures >= 3 THEN
+        retry_after := CEIL(EXTRACT(EPOCH FROM
+            last_failure + make_interval(secs => power(2, failures - 3)) - NOW()));
+    END IF;
+
+    IF retry_after > 0 THEN
+        RAISE SQLSTATE 'PT429'
+            USING MESSAGE = 'Too many join attempts', DETAIL = retry_after::TEXT;
+    END IF;
+
+    SELECT * INTO found
+    FROM sessions
+    WHERE join_code = code AND status = 'active'
+    LIMIT 1;
+
+    INSERT INTO session_join_attempts (user_id, ip_address, succeeded)
+    VALUES (auth.uid(), caller_ip, found.id IS NOT NULL);
+
+    IF auth.uid() = ANY(found.banned_user_ids) THEN
+        RAISE SQLSTATE 'PT403' USING MESSAGE = 'Banned from session';
+    END IF;
+
+    IF found.id IS NOT NULL THEN
+        RETURN NEXT found;
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Record a viewer the host let in from the waiting room
+CREATE OR REPLACE FUNCTION admit_session_user(
+    target_session_id UUID,
+    target_user_id UUID
+)
+RETURNS VOID AS $$
+BEGIN
+    UPDATE sessions
+    SET admitted_user_ids = array_append(admitted_user_ids, target_user_id)
+    WHERE id = target_session_id
+      AND host_id = auth.uid()
+      AND NOT (target_user_id = ANY(admitted_user_ids));
+END;
+$$ LANGUAGE plpgsql SECURITY INVOKER;
+
+-- Drop attempts too old to count towards any limit
+CREATE OR REPLACE FUNCTION prune_session_join_attempts()
+RETURNS VOID AS $$
+BEGIN
+    DELETE FROM session_join_attempts
+    WHERE attempted_at < NOW() - INTERVAL '1 day';
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
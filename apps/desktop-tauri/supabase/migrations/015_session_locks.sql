@@ -0,0 +1,74 @@
+-- =============================================
+-- SquadX Live Session Locking
+-- =============================================
+-- The host can lock a session once everyone expected is in, after which
+-- lookups by join code are refused
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Lock flag per session
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS locked BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Same throttling and ban check as before; a locked session now fails with
+-- HTTP 423, except for its host and co-hosts
+CREATE OR REPLACE FUNCTION find_session_by_code(code TEXT)
+RETURNS SETOF sessions AS $$
+DECLARE
+    caller_ip TEXT := NULLIF(TRIM(split_part(
+        COALESCE(current_setting('request.headers', true)::json->>'x-forwarded-for', ''),
+        ',', 1
+    )), '');
+    failures INTEGER;
+    last_failure TIMESTAMPTZ;
+    retry_after INTEGER := 0;
+    found sessions%ROWTYPE;
+BEGIN
+    SELECT COUNT(*), MAX(attempted_at)
+    INTO failures, last_failure
+    FROM session_join_attempts
+    WHERE NOT succeeded
+      AND attempted_at > NOW() - INTERVAL '15 minutes'
+      AND (user_id = auth.uid() OR (caller_ip IS NOT NULL AND ip_address = caller_ip));
+
+    IF failures >= 10 THEN
+        retry_after := CEIL(EXTRACT(EPOCH FROM last_failure + INTERVAL '15 minutes' - NOW()));
+    ELSIF failures >= 3 THEN
+        retry_after := CEIL(EXTRACT(EPOCH FROM
+            last_failure + make_interval(secs => power(2, failures - 3)) - NOW()));
+    END IF;
+
+    IF retry_after > 0 THEN
+        RAISE SQLSTATE 'PT429'
+            USING MESSAGE = 'Too many join attempts', DETAIL = retry_after::TEXT;
+    END IF;
+
+    SELECT * INTO found
+    FROM sessions
+    WHERE join_code = code AND status = 'active'
+    LIMIT 1;
+
+    INSERT INTO session_join_attempts (user_id, ip_address, succeeded)
+    VALUES (auth.uid(), caller_ip, found.id IS NOT NULL);
+
+    IF auth.uid() = ANY(found.banned_user_ids) THEN
+        RAISE SQLSTATE 'PT403' USING MESSAGE = 'Banned from session';
+    END IF;
+
+    IF found.locked AND found.host_id <> auth.uid() AND NOT (auth.uid() = ANY(found.cohost_ids)) THEN
+        RAISE SQLSTATE 'PT423' USING MESSAGE = 'Session is locked';
+    END IF;
+
+    IF found.id IS NOT NULL THEN
+        RETURN NEXT found;
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
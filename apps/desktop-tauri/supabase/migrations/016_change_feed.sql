@@ -0,0 +1,191 @@
+-- =============================================
+-- SquadX Live Change Feed
+-- =============================================
+-- Sync clients page through meetings, conversations and messages by
+-- (updated_at, id) instead of refetching everything. This keeps updated_at
+-- current on every change, including attendee responses, and records
+-- deletions (or lost access) as tombstones visible to the users affected
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Deleted Entities Table
+CREATE TABLE IF NOT EXISTS deleted_entities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    entity_type TEXT NOT NULL CHECK (entity_type IN ('meeting', 'conversation', 'message')),
+    entity_id UUID NOT NULL,
+    -- Users who could see the entity and must drop it
+    user_ids UUID[] NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meetings_updated_at ON meetings(updated_at, id);
+CREATE INDEX IF NOT EXISTS idx_conversations_updated_at_id ON conversations(updated_at, id);
+CREATE INDEX IF NOT EXISTS idx_messages_updated_at ON messages(updated_at, id);
+CREATE INDEX IF NOT EXISTS idx_deleted_entities_updated_at ON deleted_entities(updated_at, id);
+CREATE INDEX IF NOT EXISTS idx_deleted_entities_user_ids ON deleted_entities USING GIN (user_ids);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE deleted_entities ENABLE ROW LEVEL SECURITY;
+
+-- Tombstones are written by triggers only
+CREATE POLICY "Users can view their tombstones"
+    ON deleted_entities FOR SELECT
+    USING (auth.uid() = ANY(user_ids));
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Keep updated_at current on any update
+CREATE OR REPLACE FUNCTION touch_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_touch_message ON messages;
+CREATE TRIGGER trigger_touch_message
+    BEFORE UPDATE ON messages
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_updated_at();
+
+DROP TRIGGER IF EXISTS trigger_touch_conversation ON conversations;
+CREATE TRIGGER trigger_touch_conversation
+    BEFORE UPDATE ON conversations
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_updated_at();
+
+-- Attendee changes are part of the meeting as clients see it
+CREATE OR REPLACE FUNCTION touch_meeting_from_attendee()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE meetings
+    SET updated_at = NOW()
+    WHERE id = COALESCE(NEW.meeting_id, OLD.meeting_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_touch_meeting_from_attendee ON meeting_attendees;
+CREATE TRIGGER trigger_touch_meeting_from_attendee
+    AFTER INSERT OR UPDATE OR DELETE ON meeting_attendees
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_meeting_from_attendee();
+
+-- Record a tombstone for whoever could see a deleted meeting
+CREATE OR REPLACE FUNCTION record_meeting_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deleted_entities (entity_type, entity_id, user_ids)
+    SELECT 'meeting', OLD.id, array_agg(DISTINCT u)
+    FROM (
+        SELECT OLD.organizer_id AS u
+        UNION
+        SELECT user_id FROM meeting_attendees WHERE meeting_id = OLD.id
+    ) audience;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_record_meeting_deletion ON meetings;
+CREATE TRIGGER trigger_record_meeting_deletion
+    BEFORE DELETE ON meetings
+    FOR EACH ROW
+    EXECUTE FUNCTION record_meeting_deletion();
+
+-- An attendee taken off a meeting loses sight of it
+CREATE OR REPLACE FUNCTION record_attendee_removal()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM meetings
+        WHERE id = OLD.meeting_id AND organizer_id = OLD.user_id
+    ) THEN
+        INSERT INTO deleted_entities (entity_type, entity_id, user_ids)
+        VALUES ('meeting', OLD.meeting_id, ARRAY[OLD.user_id]);
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_record_attendee_removal ON meeting_attendees;
+CREATE TRIGGER trigger_record_attendee_removal
+    AFTER DELETE ON meeting_attendees
+    FOR EACH ROW
+    EXECUTE FUNCTION record_attendee_removal();
+
+-- Record a tombstone for the participants of a deleted conversation
+CREATE OR REPLACE FUNCTION record_conversation_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deleted_entities (entity_type, entity_id, user_ids)
+    SELECT 'conversation', OLD.id, array_agg(user_id)
+    FROM conversation_participants
+    WHERE conversation_id = OLD.id
+    HAVING COUNT(*) > 0;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_record_conversation_deletion ON conversations;
+CREATE TRIGGER trigger_record_conversation_deletion
+    BEFORE DELETE ON conversations
+    FOR EACH ROW
+    EXECUTE FUNCTION record_conversation_deletion();
+
+-- A participant leaving a conversation loses sight of it
+CREATE OR REPLACE FUNCTION record_participant_removal()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deleted_entities (entity_type, entity_id, user_ids)
+    VALUES ('conversation', OLD.conversation_id, ARRAY[OLD.user_id]);
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_record_participant_removal ON conversation_participants;
+CREATE TRIGGER trigger_record_participant_removal
+    AFTER DELETE ON conversation_participants
+    FOR EACH ROW
+    EXECUTE FUNCTION record_participant_removal();
+
+-- Record a tombstone for the participants of a deleted message's conversation
+CREATE OR REPLACE FUNCTION record_message_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deleted_entities (entity_type, entity_id, user_ids)
+    SELECT 'message', OLD.id, array_agg(user_id)
+    FROM conversation_participants
+    WHERE conversation_id = OLD.conversation_id
+    HAVING COUNT(*) > 0;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_record_message_deletion ON messages;
+CREATE TRIGGER trigger_record_message_deletion
+    BEFORE DELETE ON messages
+    FOR EACH ROW
+    EXECUTE FUNCTION record_message_deletion();
+
+-- Drop tombstones older than any client is expected to have synced
+CREATE OR REPLACE FUNCTION prune_deleted_entities()
+RETURNS VOID AS $$
+BEGIN
+    DELETE FROM deleted_entities
+    WHERE updated_at < NOW() - INTERVAL '90 days';
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
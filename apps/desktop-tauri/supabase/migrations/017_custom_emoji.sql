@@ -0,0 +1,68 @@
+-- =============================================
+-- SquadX Live Custom Emoji
+-- =============================================
+-- Team-wide custom emoji, written as :shortcode: in messages. Images are
+-- stored in the public custom-emoji bucket; every teammate can use them,
+-- only the uploader can remove one
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Custom Emoji Table
+CREATE TABLE IF NOT EXISTS custom_emojis (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    shortcode TEXT NOT NULL UNIQUE CHECK (shortcode ~ '^[a-z0-9_+-]{2,32}$'),
+    -- Object path in the custom-emoji bucket
+    storage_path TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    created_by UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- 2. Storage Bucket
+INSERT INTO storage.buckets (id, name, public)
+VALUES ('custom-emoji', 'custom-emoji', true)
+ON CONFLICT (id) DO NOTHING;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_custom_emojis_created_by ON custom_emojis(created_by);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE custom_emojis ENABLE ROW LEVEL SECURITY;
+
+-- Any authenticated teammate can use every emoji
+CREATE POLICY "Authenticated users can view custom emoji"
+    ON custom_emojis FOR SELECT
+    USING (auth.uid() IS NOT NULL);
+
+CREATE POLICY "Users can add custom emoji"
+    ON custom_emojis FOR INSERT
+    WITH CHECK (auth.uid() = created_by);
+
+CREATE POLICY "Users can delete their custom emoji"
+    ON custom_emojis FOR DELETE
+    USING (auth.uid() = created_by);
+
+-- Images are public to read; uploads go under the uploader's folder
+CREATE POLICY "Users can upload custom emoji images"
+    ON storage.objects FOR INSERT
+    WITH CHECK (
+        bucket_id = 'custom-emoji'
+        AND (storage.foldername(name))[1] = auth.uid()::text
+    );
+
+CREATE POLICY "Users can delete their custom emoji images"
+    ON storage.objects FOR DELETE
+    USING (
+        bucket_id = 'custom-emoji'
+        AND (storage.foldername(name))[1] = auth.uid()::text
+    );
+
+-- =============================================
+-- End of Migration
+-- =============================================
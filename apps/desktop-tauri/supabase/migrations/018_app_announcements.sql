@@ -0,0 +1,64 @@
+-- =============================================
+-- SquadX Live App Announcements
+-- =============================================
+-- Notices the desktop app polls for: maintenance windows, versions that are
+-- too old, and general news. Readable without signing in so they reach
+-- every client; managed from the dashboard
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. App Announcements Table
+CREATE TABLE IF NOT EXISTS app_announcements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL DEFAULT 'info' CHECK (kind IN ('info', 'maintenance', 'deprecation')),
+    severity TEXT NOT NULL DEFAULT 'info' CHECK (severity IN ('info', 'warning', 'critical')),
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    -- Link to release notes, a status page or the download page
+    url TEXT,
+    -- Shown from starts_at until ends_at (open-ended when NULL)
+    starts_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ends_at TIMESTAMPTZ,
+    -- Downtime a maintenance notice is about
+    maintenance_starts_at TIMESTAMPTZ,
+    maintenance_ends_at TIMESTAMPTZ,
+    -- Only app versions below this see the notice (semver, e.g. 1.4.0)
+    below_version TEXT,
+    -- Only these platforms see the notice ('macos', 'windows', 'linux'); all when NULL
+    platforms TEXT[],
+    dismissible BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_app_announcements_window ON app_announcements(starts_at, ends_at);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE app_announcements ENABLE ROW LEVEL SECURITY;
+
+-- Public, including signed-out clients; written from the dashboard only
+CREATE POLICY "Anyone can view app announcements"
+    ON app_announcements FOR SELECT
+    USING (TRUE);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_touch_app_announcement ON app_announcements;
+CREATE TRIGGER trigger_touch_app_announcement
+    BEFORE UPDATE ON app_announcements
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_updated_at();
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,62 @@
+-- =============================================
+-- SquadX Live Chat Attachments
+-- =============================================
+-- Files and images sent in chat. The files live in the private
+-- chat-attachments bucket under <conversation_id>/<sender_id>/, readable by
+-- the conversation's participants; their metadata is kept on the message
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Attachment Metadata on Messages
+-- Array of { path, name, content_type, size, width, height }
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS attachments JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+-- Messages carrying only attachments
+ALTER TABLE messages DROP CONSTRAINT IF EXISTS messages_message_type_check;
+ALTER TABLE messages ADD CONSTRAINT messages_message_type_check
+    CHECK (message_type IN ('text', 'system', 'poll', 'file'));
+
+-- 2. Storage Bucket
+INSERT INTO storage.buckets (id, name, public)
+VALUES ('chat-attachments', 'chat-attachments', false)
+ON CONFLICT (id) DO NOTHING;
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+-- Participants read every attachment of their conversations
+CREATE POLICY "Users can view attachments in their conversations"
+    ON storage.objects FOR SELECT
+    USING (
+        bucket_id = 'chat-attachments'
+        AND (storage.foldername(name))[1] IN (
+            SELECT conversation_id::text
+            FROM conversation_participants
+            WHERE user_id = auth.uid()
+        )
+    );
+
+-- Uploads go under the sender's folder of a conversation they are in
+CREATE POLICY "Users can upload attachments to their conversations"
+    ON storage.objects FOR INSERT
+    WITH CHECK (
+        bucket_id = 'chat-attachments'
+        AND (storage.foldername(name))[2] = auth.uid()::text
+        AND (storage.foldername(name))[1] IN (
+            SELECT conversation_id::text
+            FROM conversation_participants
+            WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Users can delete their attachments"
+    ON storage.objects FOR DELETE
+    USING (
+        bucket_id = 'chat-attachments'
+        AND (storage.foldername(name))[2] = auth.uid()::text
+    );
+
+-- =============================================
+-- End of Migration
+-- =============================================
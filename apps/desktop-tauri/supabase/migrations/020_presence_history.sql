@@ -0,0 +1,101 @@
+-- =============================================
+-- SquadX Live Presence History
+-- =============================================
+-- The desktop app refreshes its presence row on a heartbeat while it runs,
+-- so last_seen_at is the last time the app was actually up rather than the
+-- last manual status change. last_seen_at is stamped with the server clock,
+-- and every status change is kept as a period in presence_history. An app
+-- that stopped without signing off is recorded as offline from its last
+-- heartbeat on
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Presence History Table
+CREATE TABLE IF NOT EXISTS presence_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL CHECK (status IN ('online', 'away', 'offline')),
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL while this is the user's current status
+    ended_at TIMESTAMPTZ,
+    CHECK (ended_at IS NULL OR ended_at >= started_at)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_presence_history_user ON presence_history(user_id, started_at DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_presence_history_open ON presence_history(user_id) WHERE ended_at IS NULL;
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE presence_history ENABLE ROW LEVEL SECURITY;
+
+-- Same visibility as user_presence; written by the trigger only
+CREATE POLICY "Users can view all presence history"
+    ON presence_history FOR SELECT
+    USING (true);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Stamp presence with the server clock and record status periods
+CREATE OR REPLACE FUNCTION record_presence()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_lapsed BOOLEAN := FALSE;
+BEGIN
+    NEW.last_seen_at := NOW();
+
+    IF TG_OP = 'UPDATE' THEN
+        -- Heartbeats come every minute; three missed ones mean the app
+        -- stopped without going offline
+        v_lapsed := OLD.status <> 'offline'
+            AND OLD.last_seen_at < NOW() - INTERVAL '3 minutes';
+
+        IF NEW.status = OLD.status AND NOT v_lapsed THEN
+            RETURN NEW;
+        END IF;
+
+        IF v_lapsed THEN
+            UPDATE presence_history
+            SET ended_at = GREATEST(started_at, OLD.last_seen_at)
+            WHERE user_id = NEW.user_id AND ended_at IS NULL;
+
+            INSERT INTO presence_history (user_id, status, started_at)
+            VALUES (NEW.user_id, 'offline', OLD.last_seen_at);
+
+            IF NEW.status = 'offline' THEN
+                RETURN NEW;
+            END IF;
+        END IF;
+    END IF;
+
+    UPDATE presence_history
+    SET ended_at = NOW()
+    WHERE user_id = NEW.user_id AND ended_at IS NULL;
+
+    INSERT INTO presence_history (user_id, status, started_at)
+    VALUES (NEW.user_id, NEW.status, NOW());
+
+    -- Keep a month of history
+    DELETE FROM presence_history
+    WHERE user_id = NEW.user_id AND ended_at < NOW() - INTERVAL '30 days';
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_record_presence ON user_presence;
+CREATE TRIGGER trigger_record_presence
+    BEFORE INSERT OR UPDATE ON user_presence
+    FOR EACH ROW
+    EXECUTE FUNCTION record_presence();
+
+-- =============================================
+-- End of Migration
+-- =============================================
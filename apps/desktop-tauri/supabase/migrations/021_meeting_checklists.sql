@@ -0,0 +1,54 @@
+-- =============================================
+-- SquadX Live Meeting Checklists
+-- =============================================
+-- Meeting descriptions are Markdown and may hold task lists for meeting
+-- prep. Only the organizer may edit a meeting, so attendees tick boxes
+-- through set_meeting_checklist, which accepts a new description only if
+-- nothing but checkbox states changed. The update is conditional on the
+-- description the caller started from, so concurrent ticks don't undo each
+-- other; an empty result means it changed in the meantime
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+CREATE OR REPLACE FUNCTION set_meeting_checklist(
+    target_meeting_id UUID,
+    previous_description TEXT,
+    new_description TEXT
+)
+RETURNS SETOF meetings AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM meetings m
+        WHERE m.id = target_meeting_id
+          AND (
+              m.organizer_id = auth.uid() OR
+              EXISTS (
+                  SELECT 1 FROM meeting_attendees a
+                  WHERE a.meeting_id = m.id AND a.user_id = auth.uid()
+              )
+          )
+    ) THEN
+        RAISE SQLSTATE '42501' USING MESSAGE = 'Not an attendee of this meeting';
+    END IF;
+
+    -- Checkbox states are the only thing attendees may change
+    IF regexp_replace(previous_description, '\[[ xX]\]', '[ ]', 'g')
+        <> regexp_replace(new_description, '\[[ xX]\]', '[ ]', 'g') THEN
+        RAISE SQLSTATE '22023' USING MESSAGE = 'Only checklist items can be changed';
+    END IF;
+
+    RETURN QUERY
+    UPDATE meetings
+    SET description = new_description
+    WHERE id = target_meeting_id AND description = previous_description
+    RETURNING *;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
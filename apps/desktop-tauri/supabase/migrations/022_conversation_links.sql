@@ -0,0 +1,77 @@
+-- =============================================
+-- SquadX Live Conversation Links
+-- =============================================
+-- Index of the URLs shared in each conversation, filled by the sender's app
+-- when a message is written (with the page title when it could be fetched),
+-- so the Links tab is one query instead of a scan of the history. Existing
+-- messages are indexed below, without titles
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Conversation Links Table
+CREATE TABLE IF NOT EXISTS conversation_links (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    conversation_id UUID NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    sender_id UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    url TEXT NOT NULL,
+    title TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(message_id, url)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_conversation_links_conversation ON conversation_links(conversation_id, created_at DESC);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE conversation_links ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can view links in their conversations"
+    ON conversation_links FOR SELECT
+    USING (
+        conversation_id IN (
+            SELECT conversation_id
+            FROM conversation_participants
+            WHERE user_id = auth.uid()
+        )
+    );
+
+-- Senders index the links of their own messages
+CREATE POLICY "Users can index links of their messages"
+    ON conversation_links FOR INSERT
+    WITH CHECK (
+        sender_id = auth.uid() AND
+        message_id IN (
+            SELECT id FROM messages
+            WHERE sender_id = auth.uid() AND conversation_id = conversation_links.conversation_id
+        )
+    );
+
+-- =============================================
+-- Backfill
+-- =============================================
+
+INSERT INTO conversation_links (conversation_id, message_id, sender_id, url, created_at)
+SELECT DISTINCT ON (m.id, link.url)
+    m.conversation_id,
+    m.id,
+    m.sender_id,
+    link.url,
+    m.created_at
+FROM messages m
+CROSS JOIN LATERAL (
+    SELECT rtrim(match[1], '.,;:!?') AS url
+    FROM regexp_matches(m.content, '(https?://[^\s<>"''`]+)', 'gi') AS match
+) AS link
+WHERE m.message_type = 'text'
+ON CONFLICT (message_id, url) DO NOTHING;
+
+-- =============================================
+-- End of Migration
+-- =============================================
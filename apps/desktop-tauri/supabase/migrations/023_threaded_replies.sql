@@ -0,0 +1,142 @@
+-- =============================================
+-- SquadX Live Threaded Replies
+-- =============================================
+-- Replies hang off a top-level message of the same conversation, one level
+-- deep, and stay out of the main message list. The parent keeps a reply
+-- count and the time of its last reply. Unread counts separate the main
+-- list from replies in the threads a user follows: threads they started,
+-- replied to or opened
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Reply Columns on Messages
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS parent_message_id UUID REFERENCES messages(id) ON DELETE CASCADE;
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS reply_count INT NOT NULL DEFAULT 0;
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS last_reply_at TIMESTAMPTZ;
+
+-- 2. Thread Reads Table
+CREATE TABLE IF NOT EXISTS thread_reads (
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    parent_message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    last_read_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, parent_message_id)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_messages_thread ON messages(parent_message_id, created_at) WHERE parent_message_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_messages_top_level ON messages(conversation_id, created_at DESC) WHERE parent_message_id IS NULL;
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE thread_reads ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can view their thread reads"
+    ON thread_reads FOR SELECT
+    USING (user_id = auth.uid());
+
+CREATE POLICY "Users can insert their thread reads"
+    ON thread_reads FOR INSERT
+    WITH CHECK (user_id = auth.uid());
+
+CREATE POLICY "Users can update their thread reads"
+    ON thread_reads FOR UPDATE
+    USING (user_id = auth.uid());
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Replies go to a top-level message of the same conversation
+CREATE OR REPLACE FUNCTION check_message_parent()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.parent_message_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM messages
+        WHERE id = NEW.parent_message_id
+          AND conversation_id = NEW.conversation_id
+          AND parent_message_id IS NULL
+    ) THEN
+        RAISE SQLSTATE '22023'
+            USING MESSAGE = 'Replies must be to a top-level message of the same conversation';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_check_message_parent ON messages;
+CREATE TRIGGER trigger_check_message_parent
+    BEFORE INSERT ON messages
+    FOR EACH ROW
+    EXECUTE FUNCTION check_message_parent();
+
+-- Keep the parent's reply count and last reply time; replies are written by
+-- other users than the parent's sender, hence SECURITY DEFINER
+CREATE OR REPLACE FUNCTION count_message_replies()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_parent_id UUID := COALESCE(NEW.parent_message_id, OLD.parent_message_id);
+BEGIN
+    IF v_parent_id IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    UPDATE messages
+    SET reply_count = (SELECT COUNT(*) FROM messages WHERE parent_message_id = v_parent_id),
+        last_reply_at = (SELECT MAX(created_at) FROM messages WHERE parent_message_id = v_parent_id)
+    WHERE id = v_parent_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_count_message_replies ON messages;
+CREATE TRIGGER trigger_count_message_replies
+    AFTER INSERT OR DELETE ON messages
+    FOR EACH ROW
+    EXECUTE FUNCTION count_message_replies();
+
+-- Unread messages of the caller's conversations: in the main list, and
+-- replies in the threads they follow
+CREATE OR REPLACE FUNCTION get_unread_counts()
+RETURNS TABLE (
+    conversation_id UUID,
+    unread_count BIGINT,
+    unread_thread_replies BIGINT
+) AS $$
+    SELECT
+        cp.conversation_id,
+        (
+            SELECT COUNT(*) FROM messages m
+            WHERE m.conversation_id = cp.conversation_id
+              AND m.parent_message_id IS NULL
+              AND m.sender_id IS DISTINCT FROM auth.uid()
+              AND m.created_at > COALESCE(cp.last_read_at, '-infinity')
+        ),
+        (
+            SELECT COUNT(*) FROM messages r
+            JOIN messages p ON p.id = r.parent_message_id
+            LEFT JOIN thread_reads tr
+                ON tr.parent_message_id = p.id AND tr.user_id = auth.uid()
+            WHERE p.conversation_id = cp.conversation_id
+              AND r.sender_id IS DISTINCT FROM auth.uid()
+              AND r.created_at > COALESCE(tr.last_read_at, cp.last_read_at, '-infinity')
+              AND (
+                  p.sender_id = auth.uid()
+                  OR tr.user_id IS NOT NULL
+                  OR EXISTS (
+                      SELECT 1 FROM messages mine
+                      WHERE mine.parent_message_id = p.id AND mine.sender_id = auth.uid()
+                  )
+              )
+        )
+    FROM conversation_participants cp
+    WHERE cp.user_id = auth.uid();
+$$ LANGUAGE sql STABLE SECURITY INVOKER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
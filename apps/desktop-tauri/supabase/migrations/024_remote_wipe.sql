@@ -0,0 +1,171 @@
+-- =============================================
+-- SquadX Live Remote Wipe
+-- =============================================
+-- Every install registers a random device ID for the signed-in user. An
+-- administrator flags a lost or stolen device from the dashboard; the app
+-- checks the flag at startup, periodically and after signing in, wipes its
+-- local data the next time it is online and reports what it removed. The
+-- check and report work signed out, keyed by the device ID and a secret
+-- the app keeps in the keychain, so revoking the user's sessions doesn't
+-- keep a device from wiping and knowing an ID isn't enough to fake a wipe
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+CREATE EXTENSION IF NOT EXISTS pgcrypto WITH SCHEMA extensions;
+
+-- 1. Devices Table
+CREATE TABLE IF NOT EXISTS devices (
+    -- Generated by the app and kept in its data directory
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    -- SHA-256 of the secret the app generated with the ID
+    secret_hash TEXT,
+    platform TEXT NOT NULL,
+    app_version TEXT NOT NULL,
+    first_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Set from the dashboard; pending until wiped_at is later
+    wipe_requested_at TIMESTAMPTZ,
+    wipe_requested_by TEXT,
+    wipe_reason TEXT,
+    wiped_at TIMESTAMPTZ
+);
+
+-- 2. Device Wipe Audit Table
+CREATE TABLE IF NOT EXISTS device_wipe_audit (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    device_id UUID NOT NULL,
+    user_id UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    platform TEXT NOT NULL,
+    app_version TEXT NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL,
+    requested_by TEXT,
+    reason TEXT,
+    wiped_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- What the app removed and what it could not
+    report JSONB NOT NULL DEFAULT '{}'
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_devices_user ON devices(user_id, last_seen_at DESC);
+CREATE INDEX IF NOT EXISTS idx_device_wipe_audit_device ON device_wipe_audit(device_id, wiped_at DESC);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE devices ENABLE ROW LEVEL SECURITY;
+ALTER TABLE device_wipe_audit ENABLE ROW LEVEL SECURITY;
+
+-- Written through the functions below and from the dashboard only
+CREATE POLICY "Users can view their devices"
+    ON devices FOR SELECT
+    USING (user_id = auth.uid());
+
+CREATE POLICY "Users can view wipes of their devices"
+    ON device_wipe_audit FOR SELECT
+    USING (user_id = auth.uid());
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+CREATE OR REPLACE FUNCTION device_secret_hash(device_secret TEXT)
+RETURNS TEXT AS $$
+    SELECT encode(extensions.digest(device_secret, 'sha256'), 'hex');
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Record this device for the caller. A device ID registered to someone
+-- else, or with another secret, is refused with HTTP 409 rather than
+-- handed over, so nobody who learns an ID can move a device out of its
+-- owner's reach; the app registers under a new ID instead
+CREATE OR REPLACE FUNCTION register_device(
+    target_device_id UUID,
+    device_secret TEXT,
+    device_platform TEXT,
+    device_app_version TEXT
+)
+RETURNS VOID AS $$
+BEGIN
+    IF auth.uid() IS NULL THEN
+        RAISE SQLSTATE '42501' USING MESSAGE = 'Not authenticated';
+    END IF;
+
+    INSERT INTO devices (id, user_id, secret_hash, platform, app_version)
+    VALUES (
+        target_device_id, auth.uid(), device_secret_hash(device_secret),
+        device_platform, device_app_version
+    )
+    ON CONFLICT (id) DO UPDATE
+    SET secret_hash = EXCLUDED.secret_hash,
+        platform = EXCLUDED.platform,
+        app_version = EXCLUDED.app_version,
+        last_seen_at = NOW()
+    WHERE devices.user_id = EXCLUDED.user_id
+      -- Devices registered before secrets existed take the first one
+      AND (devices.secret_hash IS NULL OR devices.secret_hash = EXCLUDED.secret_hash);
+
+    IF NOT FOUND THEN
+        RAISE SQLSTATE 'PT409' USING MESSAGE = 'Device is registered to another user';
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- The wipe requested for a device and not carried out yet, if any
+CREATE OR REPLACE FUNCTION get_pending_device_wipe(target_device_id UUID, device_secret TEXT)
+RETURNS TABLE (
+    wipe_requested_at TIMESTAMPTZ,
+    wipe_reason TEXT
+) AS $$
+    SELECT d.wipe_requested_at, d.wipe_reason
+    FROM devices d
+    WHERE d.id = target_device_id
+      AND d.secret_hash = device_secret_hash(device_secret)
+      AND d.wipe_requested_at IS NOT NULL
+      AND (d.wiped_at IS NULL OR d.wiped_at < d.wipe_requested_at);
+$$ LANGUAGE sql STABLE SECURITY DEFINER;
+
+-- Mark a pending wipe done and audit it; false when none was pending
+CREATE OR REPLACE FUNCTION complete_device_wipe(
+    target_device_id UUID,
+    device_secret TEXT,
+    wipe_report JSONB
+)
+RETURNS BOOLEAN AS $$
+DECLARE
+    v_device devices%ROWTYPE;
+BEGIN
+    UPDATE devices
+    SET wiped_at = NOW()
+    WHERE id = target_device_id
+      AND secret_hash = device_secret_hash(device_secret)
+      AND wipe_requested_at IS NOT NULL
+      AND (wiped_at IS NULL OR wiped_at < wipe_requested_at)
+    RETURNING * INTO v_device;
+
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    INSERT INTO device_wipe_audit (
+        device_id, user_id, platform, app_version,
+        requested_at, requested_by, reason, report
+    )
+    VALUES (
+        v_device.id, v_device.user_id, v_device.platform, v_device.app_version,
+        v_device.wipe_requested_at, v_device.wipe_requested_by, v_device.wipe_reason,
+        COALESCE(wipe_report, '{}')
+    );
+    RETURN TRUE;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+GRANT EXECUTE ON FUNCTION get_pending_device_wipe(UUID, TEXT) TO anon, authenticated;
+GRANT EXECUTE ON FUNCTION complete_device_wipe(UUID, TEXT, JSONB) TO anon, authenticated;
+
+-- =============================================
+-- End of Migration
+-- =============================================
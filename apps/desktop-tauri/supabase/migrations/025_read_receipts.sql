@@ -0,0 +1,59 @@
+-- =============================================
+-- SquadX Live Read Receipts
+-- =============================================
+-- Who has seen a message, from the read state already kept: a message in
+-- the main list was seen by participants whose last_read_at is past it, a
+-- reply by those who read its thread since. Thread reads are private to
+-- their user, so the lookup goes through a function that checks the caller
+-- takes part in the conversation
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_thread_reads_parent ON thread_reads(parent_message_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Participants other than the sender who have seen a message, earliest first
+CREATE OR REPLACE FUNCTION get_message_read_by(target_message_id UUID)
+RETURNS TABLE (
+    user_id UUID,
+    display_name TEXT,
+    avatar_url TEXT,
+    read_at TIMESTAMPTZ
+) AS $$
+    SELECT reads.user_id, reads.display_name, reads.avatar_url, reads.read_at
+    FROM (
+        SELECT
+            cp.user_id,
+            COALESCE(up.display_name, 'Unknown') AS display_name,
+            up.avatar_url,
+            CASE
+                WHEN m.parent_message_id IS NULL THEN cp.last_read_at
+                ELSE tr.last_read_at
+            END AS read_at,
+            m.created_at
+        FROM messages m
+        JOIN conversation_participants cp ON cp.conversation_id = m.conversation_id
+        LEFT JOIN thread_reads tr
+            ON tr.parent_message_id = m.parent_message_id AND tr.user_id = cp.user_id
+        LEFT JOIN user_profiles up ON up.user_id = cp.user_id
+        WHERE m.id = target_message_id
+          AND cp.user_id IS DISTINCT FROM m.sender_id
+          AND EXISTS (
+              SELECT 1 FROM conversation_participants me
+              WHERE me.conversation_id = m.conversation_id AND me.user_id = auth.uid()
+          )
+    ) reads
+    WHERE reads.read_at >= reads.created_at
+    ORDER BY reads.read_at;
+$$ LANGUAGE sql STABLE SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
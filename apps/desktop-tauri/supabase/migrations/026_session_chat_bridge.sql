@@ -0,0 +1,78 @@
+-- =============================================
+-- SquadX Live Session Chat Bridge
+-- =============================================
+-- The host can pick one of their conversations when starting a session.
+-- Every session chat message stored from then on is copied into it, marked
+-- with the session it came from, so what was decided during the call stays
+-- in the team's chat history
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Conversation a session's chat is copied to
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS bridge_conversation_id UUID REFERENCES conversations(id) ON DELETE SET NULL;
+
+-- 2. Session a message was copied from
+ALTER TABLE messages
+    ADD COLUMN IF NOT EXISTS session_id UUID REFERENCES sessions(id) ON DELETE SET NULL;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_messages_session ON messages(session_id) WHERE session_id IS NOT NULL;
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Bridge a session's chat to a conversation of the host, or stop with NULL
+CREATE OR REPLACE FUNCTION set_session_chat_bridge(
+    target_session_id UUID,
+    target_conversation_id UUID
+)
+RETURNS VOID AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM sessions WHERE id = target_session_id AND host_id = auth.uid()
+    ) THEN
+        RAISE SQLSTATE '42501' USING MESSAGE = 'Only the host can bridge the session chat';
+    END IF;
+
+    IF target_conversation_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM conversation_participants
+        WHERE conversation_id = target_conversation_id AND user_id = auth.uid()
+    ) THEN
+        RAISE SQLSTATE '42501' USING MESSAGE = 'Not a participant of the conversation';
+    END IF;
+
+    UPDATE sessions
+    SET bridge_conversation_id = target_conversation_id
+    WHERE id = target_session_id;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Copy a stored session message into the bridged conversation; senders are
+-- not necessarily participants of it, hence SECURITY DEFINER. The copy
+-- keeps the message ID, so a retried store doesn't duplicate it
+CREATE OR REPLACE FUNCTION bridge_session_message()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO messages (id, conversation_id, sender_id, content, message_type, session_id, created_at)
+    SELECT NEW.id, s.bridge_conversation_id, NEW.sender_id, NEW.content, 'text', NEW.session_id, NEW.sent_at
+    FROM sessions s
+    WHERE s.id = NEW.session_id AND s.bridge_conversation_id IS NOT NULL
+    ON CONFLICT (id) DO NOTHING;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_bridge_session_message ON session_messages;
+CREATE TRIGGER trigger_bridge_session_message
+    AFTER INSERT ON session_messages
+    FOR EACH ROW
+    EXECUTE FUNCTION bridge_session_message();
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,22 @@
+-- =============================================
+-- SquadX Live Conversation Notification Preferences
+-- =============================================
+-- Each participant picks how a conversation notifies them: every message,
+-- only messages mentioning them, or nothing. A preference can be set until
+-- a given time, after which the conversation notifies of every message
+-- again. Participants update their own row, which the existing policy on
+-- conversation_participants already allows
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Notification preference of each participant
+ALTER TABLE conversation_participants
+    ADD COLUMN IF NOT EXISTS notification_level TEXT NOT NULL DEFAULT 'all'
+        CHECK (notification_level IN ('all', 'mentions_only', 'muted'));
+
+ALTER TABLE conversation_participants
+    ADD COLUMN IF NOT EXISTS muted_until TIMESTAMPTZ;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,46 @@
+-- =============================================
+-- SquadX Live Archived Conversations
+-- =============================================
+-- Each participant can archive a conversation to hide it from their list.
+-- A new message in the conversation brings it back for everyone who
+-- archived it. Participants update their own row, which the existing
+-- policy on conversation_participants already allows
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. When the participant archived the conversation
+ALTER TABLE conversation_participants
+    ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_conversation_participants_archived
+    ON conversation_participants(conversation_id) WHERE archived_at IS NOT NULL;
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Unarchive a conversation for all its participants when a message arrives;
+-- the sender may not update other participants' rows, hence SECURITY DEFINER
+CREATE OR REPLACE FUNCTION unarchive_conversation_on_message()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE conversation_participants
+    SET archived_at = NULL
+    WHERE conversation_id = NEW.conversation_id AND archived_at IS NOT NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_unarchive_conversation_on_message ON messages;
+CREATE TRIGGER trigger_unarchive_conversation_on_message
+    AFTER INSERT ON messages
+    FOR EACH ROW
+    EXECUTE FUNCTION unarchive_conversation_on_message();
+
+-- =============================================
+-- End of Migration
+-- =============================================
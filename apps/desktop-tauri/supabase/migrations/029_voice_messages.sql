@@ -0,0 +1,17 @@
+-- =============================================
+-- SquadX Live Voice Messages
+-- =============================================
+-- Recorded voice messages. The Ogg Opus audio is attached to the message
+-- like any file, in the chat-attachments bucket; the content holds the
+-- duration and a waveform preview as JSON
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Voice Message Type
+ALTER TABLE messages DROP CONSTRAINT IF EXISTS messages_message_type_check;
+ALTER TABLE messages ADD CONSTRAINT messages_message_type_check
+    CHECK (message_type IN ('text', 'system', 'poll', 'file', 'voice'));
+
+-- =============================================
+-- End of Migration
+-- =============================================
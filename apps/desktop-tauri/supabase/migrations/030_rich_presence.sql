@@ -0,0 +1,26 @@
+-- =============================================
+-- SquadX Live Rich Presence
+-- =============================================
+-- Besides online, away and offline, users can be busy, and the app
+-- publishes in_session for an online user who is in a session. A custom
+-- status message can be shown next to any status; it is kept until the
+-- user changes it
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. More Statuses
+ALTER TABLE user_presence DROP CONSTRAINT IF EXISTS user_presence_status_check;
+ALTER TABLE user_presence ADD CONSTRAINT user_presence_status_check
+    CHECK (status IN ('online', 'away', 'busy', 'in_session', 'offline'));
+
+ALTER TABLE presence_history DROP CONSTRAINT IF EXISTS presence_history_status_check;
+ALTER TABLE presence_history ADD CONSTRAINT presence_history_status_check
+    CHECK (status IN ('online', 'away', 'busy', 'in_session', 'offline'));
+
+-- 2. Custom Status Message
+ALTER TABLE user_presence ADD COLUMN IF NOT EXISTS status_message TEXT
+    CHECK (char_length(status_message) <= 100);
+
+-- =============================================
+-- End of Migration
+-- =============================================
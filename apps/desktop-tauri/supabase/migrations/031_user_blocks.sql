@@ -0,0 +1,117 @@
+-- =============================================
+-- SquadX Live User Blocks and Reports
+-- =============================================
+-- A user can block another user: the app hides the blocked user's
+-- messages from the blocker, and the blocked user can no longer start a
+-- direct conversation with them. Blocks are private to the blocker. Users
+-- can also report another user for review; reports can be filed but not
+-- read back
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. User Blocks Table
+CREATE TABLE IF NOT EXISTS user_blocks (
+    blocker_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    blocked_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (blocker_id, blocked_id),
+    CHECK (blocker_id <> blocked_id)
+);
+
+-- 2. User Reports Table
+CREATE TABLE IF NOT EXISTS user_reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    reporter_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    reported_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL CHECK (char_length(reason) BETWEEN 1 AND 1000),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (reporter_id <> reported_id)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_user_blocks_blocked ON user_blocks(blocked_id);
+CREATE INDEX IF NOT EXISTS idx_user_reports_reported ON user_reports(reported_id, created_at DESC);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE user_blocks ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_reports ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can view their blocks"
+    ON user_blocks FOR SELECT
+    USING (blocker_id = auth.uid());
+
+CREATE POLICY "Users can block others"
+    ON user_blocks FOR INSERT
+    WITH CHECK (blocker_id = auth.uid());
+
+CREATE POLICY "Users can unblock others"
+    ON user_blocks FOR DELETE
+    USING (blocker_id = auth.uid());
+
+CREATE POLICY "Users can report others"
+    ON user_reports FOR INSERT
+    WITH CHECK (reporter_id = auth.uid());
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Same as before, but refuses when the other user blocked the caller. The
+-- check runs as definer since blocks aren't visible to the blocked user
+CREATE OR REPLACE FUNCTION find_or_create_direct_conversation(user1_id UUID, user2_id UUID)
+RETURNS UUID AS $$
+DECLARE
+    conv_id UUID;
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM user_blocks
+        WHERE blocker_id = user2_id AND blocked_id IN (user1_id, auth.uid())
+    ) THEN
+        RAISE EXCEPTION 'Cannot start a conversation with this user'
+            USING ERRCODE = '42501';
+    END IF;
+
+    -- Try to find existing direct conversation between these two users
+    SELECT c.id INTO conv_id
+    FROM conversations c
+    WHERE c.type = 'direct'
+    AND EXISTS (
+        SELECT 1 FROM conversation_participants cp1
+        WHERE cp1.conversation_id = c.id AND cp1.user_id = user1_id
+    )
+    AND EXISTS (
+        SELECT 1 FROM conversation_participants cp2
+        WHERE cp2.conversation_id = c.id AND cp2.user_id = user2_id
+    )
+    AND (
+        SELECT COUNT(*) FROM conversation_participants cp
+        WHERE cp.conversation_id = c.id
+    ) = 2
+    LIMIT 1;
+
+    -- If not found, create new conversation
+    IF conv_id IS NULL THEN
+        INSERT INTO conversations (type, created_by)
+        VALUES ('direct', user1_id)
+        RETURNING id INTO conv_id;
+
+        -- Add both participants
+        INSERT INTO conversation_participants (conversation_id, user_id, role)
+        VALUES
+            (conv_id, user1_id, 'member'),
+            (conv_id, user2_id, 'member');
+    END IF;
+
+    RETURN conv_id;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,141 @@
+-- =============================================
+-- SquadX Live Group Roles
+-- =============================================
+-- Only group admins change roles, and only the group's creator, its
+-- owner, hands ownership over. The owner stays an admin. Before this,
+-- any participant could make themselves an admin through the policy that
+-- lets them update their own participant row
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Role changes, and joining as an admin, take the conversation's creator or
+-- an admin. Runs as definer to see the conversation before the creator
+-- joined it; changes made without a user (dashboard, service role) pass
+CREATE OR REPLACE FUNCTION check_participant_role()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.role IS NOT DISTINCT FROM OLD.role THEN
+        RETURN NEW;
+    END IF;
+    IF TG_OP = 'INSERT' AND NEW.role IS DISTINCT FROM 'admin' THEN
+        RETURN NEW;
+    END IF;
+    IF auth.uid() IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM conversations
+        WHERE id = NEW.conversation_id AND created_by = auth.uid()
+    ) AND NOT EXISTS (
+        SELECT 1 FROM conversation_participants
+        WHERE conversation_id = NEW.conversation_id
+        AND user_id = auth.uid() AND role = 'admin'
+    ) THEN
+        RAISE EXCEPTION 'Only group admins can change roles'
+            USING ERRCODE = '42501';
+    END IF;
+
+    IF TG_OP = 'UPDATE' AND NEW.role <> 'admin' AND EXISTS (
+        SELECT 1 FROM conversations
+        WHERE id = NEW.conversation_id AND created_by = NEW.user_id
+    ) THEN
+        RAISE EXCEPTION 'The group owner stays an admin'
+            USING ERRCODE = '42501';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_check_participant_role ON conversation_participants;
+CREATE TRIGGER trigger_check_participant_role
+    BEFORE INSERT OR UPDATE OF role ON conversation_participants
+    FOR EACH ROW
+    EXECUTE FUNCTION check_participant_role();
+
+-- Only the owner hands ownership over
+CREATE OR REPLACE FUNCTION check_conversation_owner()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.created_by IS DISTINCT FROM OLD.created_by
+        AND auth.uid() IS NOT NULL
+        AND auth.uid() IS DISTINCT FROM OLD.created_by THEN
+        RAISE EXCEPTION 'Only the group owner can transfer ownership'
+            USING ERRCODE = '42501';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_check_conversation_owner ON conversations;
+CREATE TRIGGER trigger_check_conversation_owner
+    BEFORE UPDATE OF created_by ON conversations
+    FOR EACH ROW
+    EXECUTE FUNCTION check_conversation_owner();
+
+-- Promote or demote a group member. Admins may not update other members'
+-- rows under RLS, so this runs as definer; the role trigger still checks
+-- the caller
+CREATE OR REPLACE FUNCTION set_participant_role(
+    target_conversation_id UUID,
+    target_user_id UUID,
+    new_role TEXT
+)
+RETURNS VOID AS $$
+BEGIN
+    IF auth.uid() IS NULL THEN
+        RAISE EXCEPTION 'Not authenticated' USING ERRCODE = '42501';
+    END IF;
+
+    UPDATE conversation_participants
+    SET role = new_role
+    WHERE conversation_id = target_conversation_id
+    AND user_id = target_user_id
+    AND conversation_id IN (SELECT id FROM conversations WHERE type = 'group');
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION 'Not a member of this group' USING ERRCODE = 'P0002';
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Make another member the group owner, and an admin; the previous owner
+-- stays an admin
+CREATE OR REPLACE FUNCTION transfer_group_ownership(
+    target_conversation_id UUID,
+    new_owner_id UUID
+)
+RETURNS VOID AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM conversations
+        WHERE id = target_conversation_id
+        AND type = 'group'
+        AND created_by = auth.uid()
+    ) THEN
+        RAISE EXCEPTION 'Only the group owner can transfer ownership'
+            USING ERRCODE = '42501';
+    END IF;
+
+    UPDATE conversation_participants
+    SET role = 'admin'
+    WHERE conversation_id = target_conversation_id AND user_id = new_owner_id;
+
+    IF NOT FOUND THEN
+        RAISE EXCEPTION 'Not a member of this group' USING ERRCODE = 'P0002';
+    END IF;
+
+    UPDATE conversations
+    SET created_by = new_owner_id
+    WHERE id = target_conversation_id;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,115 @@
+-- =============================================
+-- SquadX Live Encrypted Conversations
+-- =============================================
+-- Conversations can opt into end-to-end encryption. Each device publishes
+-- an X25519 public key; the app encrypts message content for every device
+-- of every participant, so the server only stores ciphertext. Encryption
+-- can't be turned off again once on
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE conversations
+    ADD COLUMN IF NOT EXISTS encrypted BOOLEAN NOT NULL DEFAULT false;
+
+-- Public chat encryption keys, one per device of a user
+CREATE TABLE IF NOT EXISTS device_keys (
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    device_id TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (user_id, device_id)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_device_keys_user_id ON device_keys(user_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE device_keys ENABLE ROW LEVEL SECURITY;
+
+-- Public keys are public: anyone signed in can encrypt for any device
+DROP POLICY IF EXISTS "Authenticated users can view device keys" ON device_keys;
+CREATE POLICY "Authenticated users can view device keys"
+    ON device_keys FOR SELECT
+    TO authenticated
+    USING (true);
+
+DROP POLICY IF EXISTS "Users can publish own device keys" ON device_keys;
+CREATE POLICY "Users can publish own device keys"
+    ON device_keys FOR INSERT
+    WITH CHECK (auth.uid() = user_id);
+
+DROP POLICY IF EXISTS "Users can update own device keys" ON device_keys;
+CREATE POLICY "Users can update own device keys"
+    ON device_keys FOR UPDATE
+    USING (auth.uid() = user_id)
+    WITH CHECK (auth.uid() = user_id);
+
+DROP POLICY IF EXISTS "Users can delete own device keys" ON device_keys;
+CREATE POLICY "Users can delete own device keys"
+    ON device_keys FOR DELETE
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_device_keys_updated_at ON device_keys;
+CREATE TRIGGER trigger_device_keys_updated_at
+    BEFORE UPDATE ON device_keys
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_updated_at();
+
+-- Encryption stays on, so no participant can quietly switch a
+-- conversation back to plaintext
+CREATE OR REPLACE FUNCTION check_conversation_encrypted()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.encrypted AND NOT NEW.encrypted THEN
+        RAISE EXCEPTION 'Encryption can''t be turned off'
+            USING ERRCODE = '42501';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_check_conversation_encrypted ON conversations;
+CREATE TRIGGER trigger_check_conversation_encrypted
+    BEFORE UPDATE OF encrypted ON conversations
+    FOR EACH ROW
+    EXECUTE FUNCTION check_conversation_encrypted();
+
+-- Turn on encryption: either participant of a direct conversation, or a
+-- group admin
+CREATE OR REPLACE FUNCTION enable_conversation_encryption(
+    target_conversation_id UUID
+)
+RETURNS VOID AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1
+        FROM conversations c
+        JOIN conversation_participants cp ON cp.conversation_id = c.id
+        WHERE c.id = target_conversation_id
+        AND cp.user_id = auth.uid()
+        AND (c.type = 'direct' OR cp.role = 'admin')
+    ) THEN
+        RAISE EXCEPTION 'Only group admins can turn on encryption'
+            USING ERRCODE = '42501';
+    END IF;
+
+    UPDATE conversations
+    SET encrypted = true
+    WHERE id = target_conversation_id;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
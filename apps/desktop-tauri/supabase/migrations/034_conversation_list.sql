@@ -0,0 +1,27 @@
+-- =============================================
+-- SquadX Live Conversation List
+-- =============================================
+-- Latest message of many conversations in one call, so the chat list
+-- loads with a fixed number of queries rather than a few per conversation
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Latest top-level message of each conversation, as the caller may see
+-- them: runs as invoker so message policies apply. Served by
+-- idx_messages_top_level
+CREATE OR REPLACE FUNCTION get_last_messages(conversation_ids UUID[])
+RETURNS SETOF messages AS $$
+    SELECT DISTINCT ON (conversation_id) *
+    FROM messages
+    WHERE conversation_id = ANY(conversation_ids)
+    AND parent_message_id IS NULL
+    ORDER BY conversation_id, created_at DESC, id DESC;
+$$ LANGUAGE sql STABLE;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,69 @@
+-- =============================================
+-- SquadX Live Outlook Calendar Integration
+-- =============================================
+-- Microsoft Graph OAuth tokens per user, and the Outlook event each
+-- meeting was synced to, alongside the Google Calendar integration
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS outlook_event_id TEXT;
+
+-- Microsoft Graph tokens, one set per user
+CREATE TABLE IF NOT EXISTS user_outlook_tokens (
+    user_id UUID PRIMARY KEY REFERENCES auth.users(id) ON DELETE CASCADE,
+    access_token TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    email TEXT,
+    sync_enabled BOOLEAN DEFAULT TRUE,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meetings_outlook_event ON meetings(outlook_event_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE user_outlook_tokens ENABLE ROW LEVEL SECURITY;
+
+-- Outlook Tokens: Users can only access their own tokens
+DROP POLICY IF EXISTS "Users can view their own outlook tokens" ON user_outlook_tokens;
+CREATE POLICY "Users can view their own outlook tokens"
+    ON user_outlook_tokens FOR SELECT
+    USING (user_id = auth.uid());
+
+DROP POLICY IF EXISTS "Users can insert their own outlook tokens" ON user_outlook_tokens;
+CREATE POLICY "Users can insert their own outlook tokens"
+    ON user_outlook_tokens FOR INSERT
+    WITH CHECK (user_id = auth.uid());
+
+DROP POLICY IF EXISTS "Users can update their own outlook tokens" ON user_outlook_tokens;
+CREATE POLICY "Users can update their own outlook tokens"
+    ON user_outlook_tokens FOR UPDATE
+    USING (user_id = auth.uid());
+
+DROP POLICY IF EXISTS "Users can delete their own outlook tokens" ON user_outlook_tokens;
+CREATE POLICY "Users can delete their own outlook tokens"
+    ON user_outlook_tokens FOR DELETE
+    USING (user_id = auth.uid());
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_user_outlook_tokens_updated_at ON user_outlook_tokens;
+CREATE TRIGGER trigger_user_outlook_tokens_updated_at
+    BEFORE UPDATE ON user_outlook_tokens
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_updated_at();
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,76 @@
+-- =============================================
+-- SquadX Live Google Calendar Two-Way Sync
+-- =============================================
+-- Where each user's background sync left off on both sides, the conflict
+-- policy they chose, and the link between each synced meeting and its
+-- Google event with the etag and meeting update time last synced
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE user_google_tokens
+    ADD COLUMN IF NOT EXISTS sync_token TEXT,
+    ADD COLUMN IF NOT EXISTS sync_cursor TEXT,
+    ADD COLUMN IF NOT EXISTS last_synced_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS conflict_policy TEXT NOT NULL DEFAULT 'latest_wins'
+        CHECK (conflict_policy IN ('latest_wins', 'prefer_local', 'prefer_remote'));
+
+-- Synced meetings and their events; kept after a meeting is deleted so the
+-- event can be deleted too
+CREATE TABLE IF NOT EXISTS google_event_links (
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    meeting_id UUID NOT NULL,
+    google_event_id TEXT NOT NULL,
+    etag TEXT,
+    meeting_updated_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (user_id, meeting_id),
+    UNIQUE (user_id, google_event_id)
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_google_event_links_user ON google_event_links(user_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE google_event_links ENABLE ROW LEVEL SECURITY;
+
+-- Event Links: Users can only access their own links
+DROP POLICY IF EXISTS "Users can view their own google event links" ON google_event_links;
+CREATE POLICY "Users can view their own google event links"
+    ON google_event_links FOR SELECT
+    USING (user_id = auth.uid());
+
+DROP POLICY IF EXISTS "Users can insert their own google event links" ON google_event_links;
+CREATE POLICY "Users can insert their own google event links"
+    ON google_event_links FOR INSERT
+    WITH CHECK (user_id = auth.uid());
+
+DROP POLICY IF EXISTS "Users can update their own google event links" ON google_event_links;
+CREATE POLICY "Users can update their own google event links"
+    ON google_event_links FOR UPDATE
+    USING (user_id = auth.uid());
+
+DROP POLICY IF EXISTS "Users can delete their own google event links" ON google_event_links;
+CREATE POLICY "Users can delete their own google event links"
+    ON google_event_links FOR DELETE
+    USING (user_id = auth.uid());
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_google_event_links_updated_at ON google_event_links;
+CREATE TRIGGER trigger_google_event_links_updated_at
+    BEFORE UPDATE ON google_event_links
+    FOR EACH ROW
+    EXECUTE FUNCTION touch_updated_at();
+
+-- =============================================
+-- End of Migration
+-- =============================================
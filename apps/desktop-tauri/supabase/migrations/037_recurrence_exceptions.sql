@@ -0,0 +1,16 @@
+-- =============================================
+-- SquadX Live Recurrence Exceptions
+-- =============================================
+-- Occurrences removed from (EXDATE) and added to (RDATE) a recurring
+-- meeting, so a single occurrence can be skipped or an extra one added
+-- without changing the recurrence rule
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS recurrence_exdates TIMESTAMPTZ[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS recurrence_rdates TIMESTAMPTZ[] NOT NULL DEFAULT '{}';
+
+-- =============================================
+-- End of Migration
+-- =============================================
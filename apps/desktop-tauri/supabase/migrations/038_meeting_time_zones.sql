@@ -0,0 +1,15 @@
+-- =============================================
+-- SquadX Live Meeting Time Zones
+-- =============================================
+-- The IANA time zone a meeting is scheduled in (e.g. America/Sao_Paulo),
+-- so its recurrence keeps the same local time across daylight saving
+-- changes; meetings without one are in UTC
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS time_zone TEXT;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,59 @@
+-- =============================================
+-- SquadX Live Meeting Busy Times
+-- =============================================
+-- When users are busy with meetings they organize or haven't declined, so
+-- double-booking can be detected and free times suggested without
+-- exposing meeting details
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- Recurring meetings are returned whole, since any occurrence may overlap
+-- the range; the client expands them
+CREATE OR REPLACE FUNCTION get_busy_meetings(
+    user_ids UUID[],
+    range_start TIMESTAMPTZ,
+    range_end TIMESTAMPTZ,
+    exclude_meeting_id UUID DEFAULT NULL
+)
+RETURNS TABLE (
+    user_id UUID,
+    scheduled_at TIMESTAMPTZ,
+    duration_minutes INT,
+    recurrence_rule TEXT,
+    recurrence_exdates TIMESTAMPTZ[],
+    recurrence_rdates TIMESTAMPTZ[],
+    time_zone TEXT
+) AS $$
+BEGIN
+    IF auth.uid() IS NULL THEN
+        RETURN;
+    END IF;
+
+    RETURN QUERY
+    SELECT busy.participant_id, m.scheduled_at, m.duration_minutes, m.recurrence_rule,
+        m.recurrence_exdates, m.recurrence_rdates, m.time_zone
+    FROM meetings m
+    JOIN (
+        SELECT o.id AS meeting_id, o.organizer_id AS participant_id
+        FROM meetings o
+        WHERE o.organizer_id = ANY(user_ids)
+        UNION
+        SELECT a.meeting_id, a.user_id AS participant_id
+        FROM meeting_attendees a
+        WHERE a.user_id = ANY(user_ids)
+        AND a.response_status <> 'declined'
+    ) busy ON busy.meeting_id = m.id
+    WHERE m.status <> 'cancelled'
+    AND (exclude_meeting_id IS NULL OR m.id <> exclude_meeting_id)
+    AND m.scheduled_at <= range_end
+    AND (
+        m.recurrence_rule IS NOT NULL
+        OR m.scheduled_at + make_interval(mins => m.duration_minutes) >= range_start
+    )
+    ORDER BY m.scheduled_at;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================
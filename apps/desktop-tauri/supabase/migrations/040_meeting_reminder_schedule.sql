@@ -0,0 +1,35 @@
+-- =============================================
+-- SquadX Live Meeting Reminder Schedule
+-- =============================================
+-- Earlier reminders (an hour or a day before) on top of each user's tuned
+-- reminder offset, and a fresh reminder_sent flag whenever a meeting is
+-- rescheduled so its reminders go out again
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE user_reminder_preferences
+    ADD COLUMN IF NOT EXISTS extra_offsets_minutes INT[] NOT NULL DEFAULT '{}';
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+CREATE OR REPLACE FUNCTION reset_meeting_reminder_sent()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.scheduled_at IS DISTINCT FROM OLD.scheduled_at THEN
+        NEW.reminder_sent := FALSE;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_reset_meeting_reminder_sent ON meetings;
+CREATE TRIGGER trigger_reset_meeting_reminder_sent
+    BEFORE UPDATE OF scheduled_at ON meetings
+    FOR EACH ROW
+    EXECUTE FUNCTION reset_meeting_reminder_sent();
+
+-- =============================================
+-- End of Migration
+-- =============================================
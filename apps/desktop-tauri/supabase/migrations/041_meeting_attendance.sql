@@ -0,0 +1,71 @@
+-- =============================================
+-- SquadX Live Meeting Attendance
+-- =============================================
+-- Required and optional attendees, and a deadline by which each attendee
+-- should answer; organizers are told once about answers that didn't come
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meeting_attendees
+    ADD COLUMN IF NOT EXISTS attendance_type TEXT NOT NULL DEFAULT 'required'
+        CHECK (attendance_type IN ('required', 'optional')),
+    ADD COLUMN IF NOT EXISTS rsvp_deadline TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS rsvp_deadline_notified BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_meeting_attendees_rsvp_deadline
+    ON meeting_attendees(rsvp_deadline)
+    WHERE response_status = 'invited' AND NOT rsvp_deadline_notified;
+
+-- =============================================
+-- Row Level Security
+-- =============================================
+
+DROP POLICY IF EXISTS "Organizers can update attendance" ON meeting_attendees;
+CREATE POLICY "Organizers can update attendance"
+    ON meeting_attendees FOR UPDATE
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        )
+    );
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Attendees answer for themselves and organizers set attendance; neither
+-- may change the other's columns. A new deadline is notified again
+CREATE OR REPLACE FUNCTION guard_meeting_attendance()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF auth.uid() IS NOT NULL THEN
+        IF NOT EXISTS (
+            SELECT 1 FROM meetings
+            WHERE id = NEW.meeting_id AND organizer_id = auth.uid()
+        ) THEN
+            NEW.attendance_type := OLD.attendance_type;
+            NEW.rsvp_deadline := OLD.rsvp_deadline;
+            NEW.rsvp_deadline_notified := OLD.rsvp_deadline_notified;
+        END IF;
+        IF NEW.user_id <> auth.uid() THEN
+            NEW.response_status := OLD.response_status;
+            NEW.responded_at := OLD.responded_at;
+        END IF;
+    END IF;
+
+    IF NEW.rsvp_deadline IS DISTINCT FROM OLD.rsvp_deadline THEN
+        NEW.rsvp_deadline_notified := FALSE;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_guard_meeting_attendance ON meeting_attendees;
+CREATE TRIGGER trigger_guard_meeting_attendance
+    BEFORE UPDATE ON meeting_attendees
+    FOR EACH ROW
+    EXECUTE FUNCTION guard_meeting_attendance();
+
+-- =============================================
+-- End of Migration
+-- =============================================
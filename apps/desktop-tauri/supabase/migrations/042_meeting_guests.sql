@@ -0,0 +1,164 @@
+-- =============================================
+-- SquadX Live Meeting Guests
+-- =============================================
+-- External attendees invited by email. They get invitation, update and
+-- cancellation emails with an .ics attachment (sent by the
+-- send-meeting-notification edge function) and answer through tokenized
+-- links (served by the meeting-rsvp edge function)
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Meeting Guests Table
+CREATE TABLE IF NOT EXISTS meeting_guests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meeting_id UUID NOT NULL REFERENCES meetings(id) ON DELETE CASCADE,
+    email TEXT NOT NULL CHECK (email = lower(email)),
+    display_name TEXT,
+    response_status TEXT NOT NULL DEFAULT 'invited' CHECK (response_status IN ('invited', 'accepted', 'declined', 'tentative')),
+    responded_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(meeting_id, email)
+);
+
+-- 2. Guest RSVP Tokens Table
+-- Kept apart from meeting_guests so participants who can see the guests
+-- can't answer for them
+CREATE TABLE IF NOT EXISTS meeting_guest_tokens (
+    guest_id UUID PRIMARY KEY REFERENCES meeting_guests(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE DEFAULT replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', ''),
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- 3. Guest notifications go through the same queue as attendees'
+ALTER TABLE notification_queue
+    ADD COLUMN IF NOT EXISTS guest_id UUID REFERENCES meeting_guests(id) ON DELETE CASCADE;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meeting_guests_meeting ON meeting_guests(meeting_id);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE meeting_guests ENABLE ROW LEVEL SECURITY;
+ALTER TABLE meeting_guest_tokens ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can view guests of their meetings"
+    ON meeting_guests FOR SELECT
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Organizers can add guests"
+    ON meeting_guests FOR INSERT
+    WITH CHECK (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Organizers can remove guests"
+    ON meeting_guests FOR DELETE
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        )
+    );
+
+-- meeting_guest_tokens has no policies: only the service role reads them
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Give a new guest an RSVP token and queue the invitation
+CREATE OR REPLACE FUNCTION queue_guest_invite()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO meeting_guest_tokens (guest_id) VALUES (NEW.id);
+
+    INSERT INTO notification_queue (meeting_id, guest_id, notification_type, scheduled_for, status)
+    VALUES (NEW.meeting_id, NEW.id, 'invite', NOW(), 'pending');
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_queue_guest_invite ON meeting_guests;
+CREATE TRIGGER trigger_queue_guest_invite
+    AFTER INSERT ON meeting_guests
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_guest_invite();
+
+-- Tell guests who haven't declined about reschedules and cancellations
+CREATE OR REPLACE FUNCTION queue_guest_meeting_notification()
+RETURNS TRIGGER AS $$
+DECLARE
+    kind TEXT;
+BEGIN
+    IF NEW.status = 'cancelled' AND OLD.status != 'cancelled' THEN
+        kind := 'cancel';
+    ELSIF NEW.status = 'scheduled' AND (
+        NEW.scheduled_at IS DISTINCT FROM OLD.scheduled_at OR
+        NEW.duration_minutes IS DISTINCT FROM OLD.duration_minutes OR
+        NEW.title IS DISTINCT FROM OLD.title
+    ) THEN
+        kind := 'update';
+    ELSE
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO notification_queue (meeting_id, guest_id, notification_type, scheduled_for, status)
+    SELECT NEW.id, id, kind, NOW(), 'pending'
+    FROM meeting_guests
+    WHERE meeting_id = NEW.id AND response_status != 'declined';
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS trigger_queue_guest_meeting_notification ON meetings;
+CREATE TRIGGER trigger_queue_guest_meeting_notification
+    AFTER UPDATE ON meetings
+    FOR EACH ROW
+    EXECUTE FUNCTION queue_guest_meeting_notification();
+
+-- Record a guest's answer from their RSVP link; called by the meeting-rsvp
+-- edge function with the service role key
+CREATE OR REPLACE FUNCTION respond_to_guest_invite(p_token TEXT, p_response TEXT)
+RETURNS TABLE (
+    meeting_id UUID,
+    title TEXT,
+    scheduled_at TIMESTAMPTZ,
+    response_status TEXT
+) AS $$
+BEGIN
+    IF p_response NOT IN ('accepted', 'declined', 'tentative') THEN
+        RAISE EXCEPTION 'Invalid response: %', p_response;
+    END IF;
+
+    RETURN QUERY
+    UPDATE meeting_guests g
+    SET response_status = p_response, responded_at = NOW()
+    FROM meeting_guest_tokens t, meetings m
+    WHERE t.token = p_token
+      AND g.id = t.guest_id
+      AND m.id = g.meeting_id
+      AND m.status != 'cancelled'
+    RETURNING g.meeting_id, m.title, m.scheduled_at, g.response_status;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+REVOKE EXECUTE ON FUNCTION respond_to_guest_invite(TEXT, TEXT) FROM PUBLIC, anon, authenticated;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,17 @@
+-- =============================================
+-- SquadX Live Meeting Locations
+-- =============================================
+-- Where a meeting takes place: a free-form location (address or place),
+-- a video call link and a room
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS location TEXT,
+    ADD COLUMN IF NOT EXISTS meeting_url TEXT
+        CHECK (meeting_url ~* '^https?://'),
+    ADD COLUMN IF NOT EXISTS room TEXT;
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,124 @@
+-- =============================================
+-- SquadX Live Meeting Notes - Database Schema
+-- =============================================
+-- Notes shared by everyone in a meeting, and the action items that came
+-- out of it. Notes carry a version so concurrent edits don't overwrite
+-- each other silently
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Meeting Notes Table
+CREATE TABLE IF NOT EXISTS meeting_notes (
+    meeting_id UUID PRIMARY KEY REFERENCES meetings(id) ON DELETE CASCADE,
+    content TEXT NOT NULL DEFAULT '',
+    version INT NOT NULL DEFAULT 1,
+    updated_by UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- 2. Meeting Action Items Table
+CREATE TABLE IF NOT EXISTS meeting_action_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meeting_id UUID NOT NULL REFERENCES meetings(id) ON DELETE CASCADE,
+    title TEXT NOT NULL CHECK (length(trim(title)) > 0),
+    assignee_id UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    due_date DATE,
+    completed_at TIMESTAMPTZ,
+    completed_by UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    created_by UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meeting_action_items_meeting ON meeting_action_items(meeting_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_meeting_action_items_assignee ON meeting_action_items(assignee_id, due_date)
+    WHERE completed_at IS NULL;
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE meeting_notes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE meeting_action_items ENABLE ROW LEVEL SECURITY;
+
+-- Organizers and attendees read and write the notes together
+CREATE POLICY "Participants can view meeting notes"
+    ON meeting_notes FOR SELECT
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Participants can write meeting notes"
+    ON meeting_notes FOR ALL
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+-- Assignees also see their items after leaving the meeting
+CREATE POLICY "Participants can view action items"
+    ON meeting_action_items FOR SELECT
+    USING (
+        assignee_id = auth.uid() OR
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Participants can manage action items"
+    ON meeting_action_items FOR ALL
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Every change to the notes is a new version
+CREATE OR REPLACE FUNCTION bump_meeting_notes_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    NEW.updated_at := NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_bump_meeting_notes_version ON meeting_notes;
+CREATE TRIGGER trigger_bump_meeting_notes_version
+    BEFORE UPDATE ON meeting_notes
+    FOR EACH ROW
+    EXECUTE FUNCTION bump_meeting_notes_version();
+
+DROP TRIGGER IF EXISTS trigger_update_action_item_timestamp ON meeting_action_items;
+CREATE TRIGGER trigger_update_action_item_timestamp
+    BEFORE UPDATE ON meeting_action_items
+    FOR EACH ROW
+    EXECUTE FUNCTION update_meeting_timestamp();
+
+-- =============================================
+-- End of Migration
+-- =============================================
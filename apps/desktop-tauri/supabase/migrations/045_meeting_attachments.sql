@@ -0,0 +1,119 @@
+-- =============================================
+-- SquadX Live Meeting Attachments
+-- =============================================
+-- Files attached to meetings (agenda docs, specs). The files live in the
+-- private meeting-attachments bucket under <meeting_id>/<uploader_id>/,
+-- readable by the meeting's organizer and attendees; their metadata is kept
+-- in meeting_attachments
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Meeting Attachments Table
+CREATE TABLE IF NOT EXISTS meeting_attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meeting_id UUID NOT NULL REFERENCES meetings(id) ON DELETE CASCADE,
+    uploaded_by UUID REFERENCES auth.users(id) ON DELETE SET NULL,
+    path TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size BIGINT NOT NULL CHECK (size >= 0),
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- 2. Storage Bucket
+INSERT INTO storage.buckets (id, name, public)
+VALUES ('meeting-attachments', 'meeting-attachments', false)
+ON CONFLICT (id) DO NOTHING;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meeting_attachments_meeting ON meeting_attachments(meeting_id, created_at);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE meeting_attachments ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Participants can view meeting attachments"
+    ON meeting_attachments FOR SELECT
+    USING (
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        ) OR
+        meeting_id IN (
+            SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Participants can attach files"
+    ON meeting_attachments FOR INSERT
+    WITH CHECK (
+        uploaded_by = auth.uid()
+        AND (
+            meeting_id IN (
+                SELECT id FROM meetings WHERE organizer_id = auth.uid()
+            ) OR
+            meeting_id IN (
+                SELECT meeting_id FROM meeting_attendees WHERE user_id = auth.uid()
+            )
+        )
+    );
+
+-- Uploaders remove their files, organizers any file of their meetings
+CREATE POLICY "Uploaders and organizers can remove attachments"
+    ON meeting_attachments FOR DELETE
+    USING (
+        uploaded_by = auth.uid() OR
+        meeting_id IN (
+            SELECT id FROM meetings WHERE organizer_id = auth.uid()
+        )
+    );
+
+CREATE POLICY "Users can view attachments of their meetings"
+    ON storage.objects FOR SELECT
+    USING (
+        bucket_id = 'meeting-attachments'
+        AND (
+            (storage.foldername(name))[1] IN (
+                SELECT id::text FROM meetings WHERE organizer_id = auth.uid()
+            ) OR
+            (storage.foldername(name))[1] IN (
+                SELECT meeting_id::text FROM meeting_attendees WHERE user_id = auth.uid()
+            )
+        )
+    );
+
+-- Uploads go under the uploader's folder of a meeting they are in
+CREATE POLICY "Users can upload attachments to their meetings"
+    ON storage.objects FOR INSERT
+    WITH CHECK (
+        bucket_id = 'meeting-attachments'
+        AND (storage.foldername(name))[2] = auth.uid()::text
+        AND (
+            (storage.foldername(name))[1] IN (
+                SELECT id::text FROM meetings WHERE organizer_id = auth.uid()
+            ) OR
+            (storage.foldername(name))[1] IN (
+                SELECT meeting_id::text FROM meeting_attendees WHERE user_id = auth.uid()
+            )
+        )
+    );
+
+CREATE POLICY "Uploaders and organizers can delete meeting attachments"
+    ON storage.objects FOR DELETE
+    USING (
+        bucket_id = 'meeting-attachments'
+        AND (
+            (storage.foldername(name))[2] = auth.uid()::text OR
+            (storage.foldername(name))[1] IN (
+                SELECT id::text FROM meetings WHERE organizer_id = auth.uid()
+            )
+        )
+    );
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,23 @@
+-- =============================================
+-- SquadX Live Meeting Auto-Start
+-- =============================================
+-- Meetings can start their squad session on their own at the scheduled
+-- time. The join code of the session a meeting started is kept on the
+-- meeting, so attendees can join from the meeting itself
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS auto_start BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS join_code TEXT;
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meetings_auto_start ON meetings(organizer_id, scheduled_at)
+    WHERE auto_start AND status = 'scheduled';
+
+-- =============================================
+-- End of Migration
+-- =============================================
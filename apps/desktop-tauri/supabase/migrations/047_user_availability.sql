@@ -0,0 +1,50 @@
+-- =============================================
+-- SquadX Live User Availability
+-- =============================================
+-- Working hours and time zone of each user, so conflict checks and
+-- meeting slot suggestions keep to times everyone is at work. Anyone
+-- signed in can read them, only their owner can change them
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. User Availability Table
+CREATE TABLE IF NOT EXISTS user_availability (
+    user_id UUID PRIMARY KEY REFERENCES auth.users(id) ON DELETE CASCADE,
+    -- IANA time zone the hours are in; UTC when unset
+    time_zone TEXT,
+    day_start_hour INT NOT NULL DEFAULT 9 CHECK (day_start_hour BETWEEN 0 AND 23),
+    day_end_hour INT NOT NULL DEFAULT 18 CHECK (day_end_hour BETWEEN 1 AND 24),
+    -- ISO weekdays, Monday = 1 to Sunday = 7
+    working_days INT[] NOT NULL DEFAULT '{1,2,3,4,5}'
+        CHECK (working_days <@ ARRAY[1, 2, 3, 4, 5, 6, 7]),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CHECK (day_start_hour < day_end_hour)
+);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE user_availability ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Signed in users can view availability"
+    ON user_availability FOR SELECT
+    USING (auth.uid() IS NOT NULL);
+
+CREATE POLICY "Users can manage their own availability"
+    ON user_availability FOR ALL
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+DROP TRIGGER IF EXISTS trigger_update_user_availability_timestamp ON user_availability;
+CREATE TRIGGER trigger_update_user_availability_timestamp
+    BEFORE UPDATE ON user_availability
+    FOR EACH ROW
+    EXECUTE FUNCTION update_meeting_timestamp();
+
+-- =============================================
+-- End of Migration
+-- =============================================
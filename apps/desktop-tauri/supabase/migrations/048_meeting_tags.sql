@@ -0,0 +1,62 @@
+-- =============================================
+-- SquadX Live Meeting Tags
+-- =============================================
+-- Colored tags users define to categorize the meetings they organize.
+-- A meeting keeps its tag's color, so attendees see the color without
+-- access to the organizer's tags
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Meeting Tags Table
+CREATE TABLE IF NOT EXISTS meeting_tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL CHECK (length(trim(name)) > 0),
+    color TEXT NOT NULL CHECK (color ~ '^#[0-9a-f]{6}$'),
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+-- 2. Meeting Tag and Color
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS tag_id UUID REFERENCES meeting_tags(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS color TEXT CHECK (color ~ '^#[0-9a-f]{6}$');
+
+-- =============================================
+-- Indexes for Performance
+-- =============================================
+
+CREATE INDEX IF NOT EXISTS idx_meetings_tag ON meetings(tag_id) WHERE tag_id IS NOT NULL;
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+ALTER TABLE meeting_tags ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can manage their own meeting tags"
+    ON meeting_tags FOR ALL
+    USING (auth.uid() = user_id);
+
+-- =============================================
+-- Functions and Triggers
+-- =============================================
+
+-- Meetings follow the color of their tag
+CREATE OR REPLACE FUNCTION sync_meeting_tag_color()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE meetings SET color = NEW.color WHERE tag_id = NEW.id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_sync_meeting_tag_color ON meeting_tags;
+CREATE TRIGGER trigger_sync_meeting_tag_color
+    AFTER UPDATE OF color ON meeting_tags
+    FOR EACH ROW
+    EXECUTE FUNCTION sync_meeting_tag_color();
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,15 @@
+-- =============================================
+-- SquadX Live Google Meet Links
+-- =============================================
+-- Meetings can ask Google Calendar for a Meet conference when they are
+-- synced there. The Meet link is stored as the meeting's video call link,
+-- so attendees who can't join the squad session can join the call
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE meetings
+    ADD COLUMN IF NOT EXISTS google_meet BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- =============================================
+-- End of Migration
+-- =============================================
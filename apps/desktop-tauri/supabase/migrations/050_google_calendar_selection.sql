@@ -0,0 +1,15 @@
+-- =============================================
+-- SquadX Live Google Calendar Selection
+-- =============================================
+-- Users pick the Google calendar meetings are synced to (calendar_id,
+-- 'primary' by default) and the calendars imports and free/busy checks
+-- read from, e.g. a team calendar alongside their own
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE user_google_tokens
+    ADD COLUMN IF NOT EXISTS selected_calendar_ids TEXT[] NOT NULL DEFAULT '{}';
+
+-- =============================================
+-- End of Migration
+-- =============================================
@@ -0,0 +1,15 @@
+-- =============================================
+-- SquadX Live Availability Block Time Zones
+-- =============================================
+-- The IANA time zone an availability block repeats in, so office hours
+-- keep the same local time across daylight saving changes; blocks
+-- without one are in UTC
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+ALTER TABLE availability_blocks
+    ADD COLUMN IF NOT EXISTS time_zone TEXT;
+
+-- =============================================
+-- End of Migration
+-- =============================================
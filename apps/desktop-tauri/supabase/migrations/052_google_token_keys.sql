@@ -0,0 +1,51 @@
+-- =============================================
+-- SquadX Live Google Token Keys
+-- =============================================
+-- Google tokens are sealed by the app under a key kept in the device's
+-- keychain. A copy of the key is escrowed here for the
+-- google-calendar-sync edge function, which refreshes and syncs with the
+-- service role. Users can set their key but nobody can read it back
+-- through the API, so a leaked user_google_tokens row stays sealed.
+-- Run this migration in your Supabase SQL Editor
+-- =============================================
+
+-- 1. Token Keys Table
+CREATE TABLE IF NOT EXISTS user_google_token_keys (
+    user_id UUID PRIMARY KEY REFERENCES auth.users(id) ON DELETE CASCADE,
+    -- Base64 XChaCha20-Poly1305 key
+    token_key TEXT NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- =============================================
+-- Row Level Security (RLS) Policies
+-- =============================================
+
+-- No policies: only the service role and the function below touch it
+ALTER TABLE user_google_token_keys ENABLE ROW LEVEL SECURITY;
+
+-- =============================================
+-- Functions
+-- =============================================
+
+-- Escrow the caller's token key, replacing the one of a device that
+-- connected before
+CREATE OR REPLACE FUNCTION set_google_token_key(key TEXT)
+RETURNS VOID AS $$
+BEGIN
+    IF auth.uid() IS NULL THEN
+        RAISE SQLSTATE '42501' USING MESSAGE = 'Not authenticated';
+    END IF;
+
+    INSERT INTO user_google_token_keys (user_id, token_key)
+    VALUES (auth.uid(), key)
+    ON CONFLICT (user_id) DO UPDATE
+    SET token_key = EXCLUDED.token_key,
+        updated_at = NOW()
+    WHERE user_google_token_keys.token_key IS DISTINCT FROM EXCLUDED.token_key;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- =============================================
+-- End of Migration
+-- =============================================